@@ -0,0 +1,298 @@
+use sorcery_engine::prelude::*;
+
+use crate::render::Numbering;
+
+/// A target as it is typed, either a player like `p2` or the number of a permanent.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum TargetRef {
+    /// The player with the specified seat, counting from 1.
+    Player(usize),
+    Permanent(usize),
+}
+
+/// A command typed by the human player. Cards in the hand and permanents on the battlefield are
+/// referred to by the numbers they are rendered with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Command {
+    /// `play 3` plays the third card in the hand as a land.
+    Play(usize),
+    /// `cast 2 x 3 targeting p2` casts the second card in the hand choosing X and its targets.
+    Cast {
+        card: usize,
+        x: u64,
+        targets: Vec<TargetRef>,
+    },
+    /// `activate 4 1 targeting 2` activates the first ability of the fourth permanent.
+    Activate {
+        permanent: usize,
+        ability: usize,
+        targets: Vec<TargetRef>,
+    },
+    /// `attack 1 2` attacks with the first and the second permanent, `attack` with nothing.
+    Attack(Vec<usize>),
+    /// `block 4 1` blocks the first permanent with the fourth one, `block` blocks nothing.
+    Block(Vec<(usize, usize)>),
+    /// `target p2` chooses the targets of a triggered ability.
+    Target(Vec<TargetRef>),
+    /// `choose 2` chooses the second option of a choice.
+    Choose(usize),
+    Pass,
+    Concede,
+    Help,
+}
+
+/// The commands shown by `help`.
+pub(crate) const HELP: &str = "\
+Commands:
+  play <card>                            play a land from your hand
+  cast <card> [x <value>] [targeting <target>...]
+                                         cast a spell from your hand
+  activate <permanent> <ability> [targeting <target>...]
+                                         activate an ability of a permanent
+  attack [<permanent>...]                declare attackers
+  block [<blocker> <attacker>]...        declare blockers
+  target [<target>...]                   choose the targets of a triggered ability
+  choose <option>                        choose an option of a choice
+  pass                                   pass priority
+  concede                                concede the game
+Cards in your hand and permanents are referred to by their numbers, players by p1 and p2.";
+
+impl Command {
+    /// Parses a line typed by the human player.
+    pub(crate) fn parse(line: &str) -> Result<Self, String> {
+        let mut words = line.split_whitespace();
+        let Some(command) = words.next() else {
+            return Err("Type a command or `help`.".into());
+        };
+        let words = words.collect::<Vec<_>>();
+        let command = match command {
+            "play" => match words[..] {
+                [card] => Self::Play(number(card)?),
+                _ => return Err("Usage: play <card>".into()),
+            },
+            "cast" => {
+                let (arguments, targets) = split_targets(&words)?;
+                match arguments[..] {
+                    [card] => Self::Cast {
+                        card: number(card)?,
+                        x: 0,
+                        targets,
+                    },
+                    [card, "x", x] => Self::Cast {
+                        card: number(card)?,
+                        x: x.parse()
+                            .map_err(|_| format!("{x} is not a value for X."))?,
+                        targets,
+                    },
+                    _ => {
+                        return Err("Usage: cast <card> [x <value>] [targeting <target>...]".into())
+                    }
+                }
+            }
+            "activate" => {
+                let (arguments, targets) = split_targets(&words)?;
+                match arguments[..] {
+                    [permanent, ability] => Self::Activate {
+                        permanent: number(permanent)?,
+                        ability: number(ability)?,
+                        targets,
+                    },
+                    _ => {
+                        return Err(
+                            "Usage: activate <permanent> <ability> [targeting <target>...]".into(),
+                        )
+                    }
+                }
+            }
+            "attack" => Self::Attack(
+                words
+                    .iter()
+                    .map(|it| number(it))
+                    .collect::<Result<_, _>>()?,
+            ),
+            "block" => {
+                if words.len() % 2 != 0 {
+                    return Err("Usage: block [<blocker> <attacker>]...".into());
+                }
+                Self::Block(
+                    words
+                        .chunks(2)
+                        .map(|it| Ok((number(it[0])?, number(it[1])?)))
+                        .collect::<Result<_, String>>()?,
+                )
+            }
+            "target" => Self::Target(
+                words
+                    .iter()
+                    .map(|it| target(it))
+                    .collect::<Result<_, _>>()?,
+            ),
+            "choose" => match words[..] {
+                [option] => Self::Choose(number(option)?),
+                _ => return Err("Usage: choose <option>".into()),
+            },
+            "pass" if words.is_empty() => Self::Pass,
+            "concede" if words.is_empty() => Self::Concede,
+            "help" => Self::Help,
+            _ => return Err(format!("Unknown command `{line}`, type `help` for a list.")),
+        };
+        Ok(command)
+    }
+
+    /// Returns the action the command stands for in the game as the player sees it, which is
+    /// `None` for `help`.
+    pub(crate) fn to_action(
+        &self,
+        view: &PlayerView,
+        numbering: &Numbering,
+    ) -> Result<Option<GameAction>, String> {
+        let targets = |targets: &[TargetRef]| {
+            targets
+                .iter()
+                .map(|&it| match it {
+                    TargetRef::Player(seat) => view
+                        .players
+                        .get(seat.wrapping_sub(1))
+                        .map(|it| Target::Player(it.id))
+                        .ok_or_else(|| format!("There is no player p{seat}.")),
+                    TargetRef::Permanent(number) => numbering.permanent(number).map(Target::Object),
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let action = match self {
+            Self::Play(card) => GameAction::PlayLand {
+                card: numbering.card(*card)?,
+            },
+            Self::Cast {
+                card,
+                x,
+                targets: chosen,
+            } => GameAction::CastSpell {
+                spell: numbering.card(*card)?,
+                targets: targets(chosen)?,
+                x: *x,
+                modes: Vec::new(),
+            },
+            Self::Activate {
+                permanent,
+                ability,
+                targets: chosen,
+            } => GameAction::ActivateAbility {
+                source: numbering.permanent(*permanent)?,
+                index: ability.wrapping_sub(1),
+                targets: targets(chosen)?,
+            },
+            Self::Attack(attackers) => GameAction::DeclareAttackers {
+                attackers: attackers
+                    .iter()
+                    .map(|&it| numbering.permanent(it))
+                    .collect::<Result<_, _>>()?,
+            },
+            Self::Block(blocks) => GameAction::DeclareBlockers {
+                blocks: blocks
+                    .iter()
+                    .map(|&(blocker, attacker)| {
+                        Ok((
+                            numbering.permanent(blocker)?,
+                            numbering.permanent(attacker)?,
+                        ))
+                    })
+                    .collect::<Result<_, String>>()?,
+            },
+            Self::Target(chosen) => GameAction::ResolveTrigger {
+                targets: targets(chosen)?,
+            },
+            Self::Choose(option) => GameAction::Choose { option: option - 1 },
+            Self::Pass => GameAction::PassPriority,
+            Self::Concede => GameAction::Concede,
+            Self::Help => return Ok(None),
+        };
+        Ok(Some(action))
+    }
+}
+
+/// Splits the words of a command at `targeting` into its arguments and its targets.
+fn split_targets<'a>(words: &[&'a str]) -> Result<(Vec<&'a str>, Vec<TargetRef>), String> {
+    match words.iter().position(|&it| it == "targeting") {
+        Some(index) => Ok((
+            words[..index].to_vec(),
+            words[index + 1..]
+                .iter()
+                .map(|it| target(it))
+                .collect::<Result<_, _>>()?,
+        )),
+        None => Ok((words.to_vec(), Vec::new())),
+    }
+}
+
+/// Parses a number a card, permanent or option is rendered with, which starts at 1.
+fn number(word: &str) -> Result<usize, String> {
+    word.parse()
+        .ok()
+        .filter(|&it| it > 0)
+        .ok_or_else(|| format!("{word} is not a number of a card, permanent or option."))
+}
+
+/// Parses a target, which is either a player like `p2` or the number of a permanent.
+fn target(word: &str) -> Result<TargetRef, String> {
+    match word.strip_prefix('p') {
+        Some(seat) => Ok(TargetRef::Player(number(seat)?)),
+        None => Ok(TargetRef::Permanent(number(word)?)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn commands_refer_to_cards_permanents_and_players_by_number() {
+        assert_eq!(Command::parse("play 3"), Ok(Command::Play(3)));
+        assert_eq!(
+            Command::parse("cast 2 targeting p2"),
+            Ok(Command::Cast {
+                card: 2,
+                x: 0,
+                targets: vec![TargetRef::Player(2)],
+            })
+        );
+        assert_eq!(
+            Command::parse("cast 1 x 4"),
+            Ok(Command::Cast {
+                card: 1,
+                x: 4,
+                targets: Vec::new(),
+            })
+        );
+        assert_eq!(
+            Command::parse("activate 4 1 targeting 2 p1"),
+            Ok(Command::Activate {
+                permanent: 4,
+                ability: 1,
+                targets: vec![TargetRef::Permanent(2), TargetRef::Player(1)],
+            })
+        );
+        assert_eq!(
+            Command::parse("attack 1 2"),
+            Ok(Command::Attack(vec![1, 2]))
+        );
+        assert_eq!(Command::parse("attack"), Ok(Command::Attack(Vec::new())));
+        assert_eq!(
+            Command::parse("block 4 1"),
+            Ok(Command::Block(vec![(4, 1)]))
+        );
+        assert_eq!(Command::parse("choose 2"), Ok(Command::Choose(2)));
+        assert_eq!(Command::parse("  pass "), Ok(Command::Pass));
+    }
+
+    #[test]
+    fn malformed_commands_are_rejected() {
+        for line in [
+            "", "play", "play 0", "cast two", "block 1", "choose", "pass 1", "shuffle",
+        ] {
+            assert!(Command::parse(line).is_err(), "{line:?} was accepted.");
+        }
+    }
+}
@@ -0,0 +1,206 @@
+//! Plays a game on the command line against a [`GreedyAgent`], e.g. to goldfish a deck.
+//!
+//! Usage: `sorcery_cli [--seed <seed>] [<your deck> [<opponent's deck>]]`, where a deck is a file
+//! with a `<count> <card name>` line for each entry. Both decks default to the bundled white deck.
+
+mod command;
+mod render;
+
+use std::{
+    error::Error,
+    fs,
+    io::{self, BufRead, Write},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use sorcery_engine::prelude::*;
+
+use crate::{
+    command::{Command, HELP},
+    render::{describe_action, render},
+};
+
+/// The deck that is played unless another one is specified.
+const WHITE_DECK: &str = include_str!("../decks/white.txt");
+
+/// The options the game is started with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Options {
+    seed: u64,
+    /// The decklists of the human player and of the agent.
+    decks: [String; 2],
+}
+
+impl Options {
+    /// Parses the command line arguments, reading the decklists from the specified files.
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, Box<dyn Error>> {
+        let mut seed = None;
+        let mut paths = Vec::new();
+        while let Some(arg) = args.next() {
+            if arg == "--seed" {
+                let value = args.next().ok_or("--seed requires a value.")?;
+                seed = Some(value.parse()?);
+            } else {
+                paths.push(arg);
+            }
+        }
+        if paths.len() > 2 {
+            return Err(
+                "Usage: sorcery_cli [--seed <seed>] [<your deck> [<opponent's deck>]]".into(),
+            );
+        }
+        let mut decks = paths
+            .iter()
+            .map(fs::read_to_string)
+            .collect::<Result<Vec<_>, _>>()?;
+        while decks.len() < 2 {
+            decks.push(decks.first().map_or(WHITE_DECK, String::as_str).to_owned());
+        }
+        let seed = match seed {
+            Some(seed) => seed,
+            None => SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        };
+        Ok(Self {
+            seed,
+            decks: [decks[0].clone(), decks[1].clone()],
+        })
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    env_logger::init();
+
+    let options = Options::parse(std::env::args().skip(1))?;
+    println!("Starting a game with seed {}.", options.seed);
+    let (game, players) = GameBuilder::new()
+        .seed(options.seed)
+        .player("You", Deck::parse(&options.decks[0])?)
+        .player("Greedy", Deck::parse(&options.decks[1])?)
+        .starting_player(0)
+        .build()?;
+    play(game, players[0], io::stdin().lock(), io::stdout().lock())
+}
+
+/// Plays the game until it ends, reading the commands of the human player from the input and
+/// letting a [`GreedyAgent`] decide for everyone else. The human concedes once the input ends.
+fn play(
+    mut game: Game,
+    human: PlayerId,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let mut agent = GreedyAgent;
+    let mut lines = input.lines();
+    while let Some(player) = game.player_to_act()? {
+        let view = game.view_for(player)?;
+        let actions = game.legal_actions(player)?;
+        if player != human {
+            let action = agent.choose(&view, &actions);
+            if action != GameAction::PassPriority {
+                writeln!(output, "{}", describe_action(&view, player, &action))?;
+            }
+            game.apply(player, action)?;
+            continue;
+        }
+
+        let (text, numbering) = render(&view);
+        let prompt = match actions.first() {
+            Some(GameAction::DeclareAttackers { .. }) => "Declare your attackers",
+            Some(GameAction::DeclareBlockers { .. }) => "Declare your blockers",
+            Some(GameAction::ResolveTrigger { .. }) => "Choose the targets of your trigger",
+            Some(GameAction::Choose { .. }) => "Choose an option",
+            _ => "You have priority",
+        };
+        write!(output, "\n{text}{prompt}> ")?;
+        output.flush()?;
+        let Some(line) = lines.next().transpose()? else {
+            game.apply(human, GameAction::Concede)?;
+            break;
+        };
+        match Command::parse(&line).and_then(|it| it.to_action(&view, &numbering)) {
+            Ok(Some(action)) => {
+                if let Err(error) = game.apply(human, action) {
+                    writeln!(output, "{error}")?;
+                }
+            }
+            Ok(None) => writeln!(output, "{HELP}")?,
+            Err(message) => writeln!(output, "{message}")?,
+        }
+    }
+
+    let view = game.view_for(human)?;
+    let result = match view.outcome {
+        Some(GameOutcome::Win(winner)) if winner == human => "You win the game.".to_owned(),
+        Some(GameOutcome::Win(winner)) => {
+            let name = view
+                .players
+                .iter()
+                .find(|it| it.id == winner)
+                .map_or("Someone", |it| it.info.name());
+            format!("{name} wins the game.")
+        }
+        _ => "The game is a draw.".to_owned(),
+    };
+    writeln!(output, "\n{result}")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Starts a game of the bundled white deck against itself, in which the human goes first.
+    fn white_game() -> (Game, PlayerId) {
+        let deck = || Deck::parse(WHITE_DECK).expect("Could not parse the deck.");
+        let (game, players) = GameBuilder::new()
+            .seed(5)
+            .player("You", deck())
+            .player("Greedy", deck())
+            .starting_player(0)
+            .build()
+            .expect("Could not build the game.");
+        (game, players[0])
+    }
+
+    /// Plays the white game with the specified commands and returns what was written.
+    fn play_with(commands: &str) -> String {
+        let (game, human) = white_game();
+        let mut output = Vec::new();
+        play(game, human, commands.as_bytes(), &mut output).expect("Could not play the game.");
+        String::from_utf8(output).expect("Could not read the output.")
+    }
+
+    #[test]
+    fn the_human_sees_their_hand_and_can_concede() {
+        let output = play_with("help\nconcede\n");
+        assert!(output.contains("Turn 1, PrecombatMain step of You"));
+        assert!(output.contains("Your hand:\n  1) "));
+        assert!(output.contains("Commands:"));
+        assert!(output.ends_with("Greedy wins the game.\n"));
+    }
+
+    #[test]
+    fn a_human_who_only_passes_is_beaten_by_the_greedy_agent() {
+        let output = play_with(&"pass\n".repeat(1_000));
+        assert!(output.contains("Greedy plays Plains."));
+        assert!(output.contains("Greedy attacks with"));
+        assert!(output.ends_with("Greedy wins the game.\n"));
+    }
+
+    #[test]
+    fn commands_are_checked_against_the_game() {
+        let output = play_with("play 99\nattack 1\ncast 1 targeting p3\nconcede\n");
+        assert!(output.contains("There is no card 99 in your hand."));
+        assert!(output.contains("There is no permanent [1]."));
+        assert!(output.contains("There is no player p3."));
+    }
+
+    #[test]
+    fn decks_default_to_the_bundled_white_deck() {
+        let options = Options::parse(["--seed", "3"].map(String::from).into_iter())
+            .expect("Could not parse the options.");
+        assert_eq!(options.seed, 3);
+        assert_eq!(options.decks, [WHITE_DECK, WHITE_DECK].map(String::from));
+        assert!(Options::parse(["--seed"].map(String::from).into_iter()).is_err());
+    }
+}
@@ -0,0 +1,196 @@
+use std::fmt::Write;
+
+use sorcery_engine::prelude::*;
+
+/// The numbers the cards in the hand and the permanents on the battlefield are rendered with,
+/// which commands use to refer to them.
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub(crate) struct Numbering {
+    hand: Vec<ObjectId>,
+    permanents: Vec<ObjectId>,
+}
+
+impl Numbering {
+    /// Returns the card in the hand with the specified number.
+    pub(crate) fn card(&self, number: usize) -> Result<ObjectId, String> {
+        number
+            .checked_sub(1)
+            .and_then(|it| self.hand.get(it))
+            .copied()
+            .ok_or_else(|| format!("There is no card {number} in your hand."))
+    }
+
+    /// Returns the permanent with the specified number.
+    pub(crate) fn permanent(&self, number: usize) -> Result<ObjectId, String> {
+        number
+            .checked_sub(1)
+            .and_then(|it| self.permanents.get(it))
+            .copied()
+            .ok_or_else(|| format!("There is no permanent [{number}]."))
+    }
+}
+
+/// Renders the game as the player sees it: each player with their permanents, the stack, the hand
+/// of the player and the choice they face. Permanents are numbered across all players in the order they are listed.
+pub(crate) fn render(view: &PlayerView) -> (String, Numbering) {
+    let mut numbering = Numbering::default();
+    let mut text = String::new();
+    let active_player = view
+        .players
+        .iter()
+        .find(|it| it.id == view.active_player)
+        .map_or("", |it| it.info.name());
+    let _ = writeln!(
+        text,
+        "Turn {}, {:?} step of {active_player}",
+        view.turn, view.step
+    );
+    for (seat, player) in (1..).zip(&view.players) {
+        let _ = write!(
+            text,
+            "{} (p{seat}): {} life, {} cards in hand, {} cards in library",
+            player.info.name(),
+            player.life,
+            player.hand_size,
+            player.library_size
+        );
+        if player.has_left {
+            text.push_str(", left the game");
+        }
+        text.push('\n');
+        let permanents = view
+            .objects_in(Zone::Battlefield)
+            .filter(|it| it.controller == Some(player.id));
+        for permanent in permanents {
+            numbering.permanents.push(permanent.id);
+            let _ = writeln!(
+                text,
+                "  [{}] {}",
+                numbering.permanents.len(),
+                describe_object(permanent)
+            );
+        }
+    }
+    let stack = view
+        .objects_in(Zone::Stack)
+        .rev()
+        .map(|it| it.name.as_str())
+        .collect::<Vec<_>>();
+    if !stack.is_empty() {
+        let _ = writeln!(text, "Stack (top first): {}", stack.join(", "));
+    }
+    text.push_str("Your hand:\n");
+    for card in view.objects_in(Zone::Hand(view.player)) {
+        numbering.hand.push(card.id);
+        let _ = writeln!(
+            text,
+            "  {}) {}",
+            numbering.hand.len(),
+            describe_object(card)
+        );
+    }
+    if let Some(choice) = &view.choice {
+        let _ = writeln!(text, "{}", choice.prompt);
+        for (number, option) in (1..).zip(&choice.options) {
+            let _ = writeln!(text, "  {number}) {option}");
+        }
+    }
+    (text, numbering)
+}
+
+/// Describes an object by its name, mana cost, power and toughness and the states it is in.
+fn describe_object(object: &ObjectStatus) -> String {
+    let mut text = object.name.clone();
+    if let Some(cost) = &object.mana_cost {
+        let _ = write!(text, " {cost}");
+    }
+    if let Some((power, toughness)) = object.power_toughness {
+        let _ = write!(text, " {power}/{toughness}");
+    }
+    for state in &object.statuses {
+        let _ = write!(text, ", {state:?}");
+    }
+    if object.marked_damage > 0 {
+        let _ = write!(text, ", {} damage", object.marked_damage);
+    }
+    text
+}
+
+/// Describes an action the player took in the game as they see it, e.g. to tell a human what
+/// their opponent did.
+pub(crate) fn describe_action(view: &PlayerView, player: PlayerId, action: &GameAction) -> String {
+    let name = |id: ObjectId| {
+        view.objects
+            .iter()
+            .find(|it| it.id == id)
+            .map_or_else(|| "a card".to_owned(), |it| it.name.clone())
+    };
+    let names =
+        |ids: &mut dyn Iterator<Item = ObjectId>| ids.map(name).collect::<Vec<_>>().join(", ");
+    let targets = |targets: &[Target]| {
+        if targets.is_empty() {
+            return String::new();
+        }
+        let targets = targets
+            .iter()
+            .map(|&it| match it {
+                Target::Player(player) => player_name(view, player),
+                Target::Object(id) => name(id),
+            })
+            .collect::<Vec<_>>();
+        format!(" targeting {}", targets.join(", "))
+    };
+    let player = player_name(view, player);
+    match action {
+        GameAction::PlayLand { card } => format!("{player} plays {}.", name(*card)),
+        GameAction::CastSpell {
+            spell,
+            targets: chosen,
+            ..
+        } => format!("{player} casts {}{}.", name(*spell), targets(chosen)),
+        GameAction::ActivateAbility {
+            source,
+            targets: chosen,
+            ..
+        } => format!(
+            "{player} activates an ability of {}{}.",
+            name(*source),
+            targets(chosen)
+        ),
+        GameAction::DeclareAttackers { attackers } if attackers.is_empty() => {
+            format!("{player} doesn't attack.")
+        }
+        GameAction::DeclareAttackers { attackers } => format!(
+            "{player} attacks with {}.",
+            names(&mut attackers.iter().copied())
+        ),
+        GameAction::DeclareBlockers { blocks } if blocks.is_empty() => {
+            format!("{player} doesn't block.")
+        }
+        GameAction::DeclareBlockers { blocks } => format!(
+            "{player} blocks with {}.",
+            names(&mut blocks.iter().map(|it| it.0))
+        ),
+        GameAction::ResolveTrigger { targets: chosen } => {
+            format!("{player} resolves a triggered ability{}.", targets(chosen))
+        }
+        GameAction::Choose { option } => {
+            let chosen = view
+                .choice
+                .as_ref()
+                .and_then(|it| it.options.get(*option))
+                .map_or("an option", String::as_str);
+            format!("{player} chooses “{chosen}”.")
+        }
+        GameAction::PassPriority => format!("{player} passes."),
+        GameAction::Concede => format!("{player} concedes."),
+    }
+}
+
+/// Returns the name of the player with the specified id.
+fn player_name(view: &PlayerView, player: PlayerId) -> String {
+    view.players
+        .iter()
+        .find(|it| it.id == player)
+        .map_or_else(|| "Someone".to_owned(), |it| it.info.name().to_owned())
+}
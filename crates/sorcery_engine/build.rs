@@ -0,0 +1,22 @@
+//! Converts the card database from JSON into MessagePack, which the engine embeds instead so it
+//! doesn't have to parse JSON the first time the database is accessed.
+//!
+//! The cards are converted without knowing their types, so the binary format has to describe its
+//! own structure just like JSON does. This rules out formats like postcard or bincode, which
+//! also can't represent the fields cards leave out when they are empty.
+
+use std::{env, fs, path::Path};
+
+fn main() {
+    println!("cargo:rerun-if-changed=src/cards.json");
+
+    let json = fs::read_to_string("src/cards.json").expect("Could not read the card database.");
+    let cards = serde_json::from_str::<serde_json::Value>(&json)
+        .expect("The card database is not valid JSON.");
+    let mut bytes = Vec::new();
+    rmp_serde::encode::write_named(&mut bytes, &cards)
+        .expect("Could not convert the card database.");
+    let out_dir = env::var_os("OUT_DIR").expect("The output directory is not set.");
+    fs::write(Path::new(&out_dir).join("cards.msgpack"), bytes)
+        .expect("Could not write the card database.");
+}
@@ -0,0 +1,578 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::{
+    BasicLandType, CardType, Color, Cost, CounterKind, CreatureType, KeywordAbility, Mana,
+    ManaCost, PlayerId, Subtype, Supertype,
+};
+
+/// 113.1. An ability can be one of three things:
+///
+/// 113.1a An ability can be a characteristic an object has that lets it affect the game. An
+///        object’s abilities are defined by that object’s rules text or by the effect that created
+///        it. Abilities can also be granted to objects by rules or effects. (Effects that grant
+///        abilities usually use the words “has,” “have,” “gains,” or “gain.”) Abilities generate
+///        effects. (See rule 609, “Effects.”)
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum Ability {
+    /// 113.3c Triggered abilities have a trigger condition and an effect. They are written as
+    ///        “[Trigger condition], [effect],” and include (and usually begin with) the word
+    ///        “when,” “whenever,” or “at.”
+    Triggered(TriggeredAbility),
+    /// 113.3b Activated abilities have a cost and an effect. They are written as “[Cost]:
+    ///        [Effect.] [Activation instructions (if any).]”
+    Activated(ActivatedAbility),
+    /// 113.3d Static abilities are written as statements. They’re simply true.
+    Static(StaticAbility),
+    /// 702.1. [...] the object lists only the name of the ability as a “keyword”; [...]
+    Keyword(KeywordAbility),
+    /// 113.3a Spell abilities are abilities that are followed as instructions while an instant or
+    ///        sorcery spell is resolving. [...]
+    Spell(Vec<Effect>),
+    /// 700.2. A spell or ability is modal if it has two or more options in a bulleted list
+    ///        preceded by instructions for a player to choose a number of those options, such as
+    ///        “Choose one —.” Each of those options is a mode. [...]
+    ///
+    /// A spell ability of which only the modes chosen as the spell is cast are followed.
+    ModalSpell(Modes),
+}
+
+/// The modes of a modal spell ability along with how many of them are chosen.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Modes {
+    pub(crate) count: ModeCount,
+    /// The effects of each mode in the order they are printed.
+    pub(crate) modes: Vec<Vec<Effect>>,
+}
+
+impl Modes {
+    /// Returns whether the specified modes, each referred to by its index, are a legal choice.
+    /// Every mode may be chosen at most once.
+    pub(crate) fn is_legal_choice(&self, chosen: &[usize]) -> bool {
+        let mut sorted = chosen.to_vec();
+        sorted.sort_unstable();
+        sorted.dedup();
+        sorted.len() == chosen.len()
+            && chosen.iter().all(|&it| it < self.modes.len())
+            && match self.count {
+                ModeCount::Exactly(count) => chosen.len() == count,
+                ModeCount::OneOrMore => !chosen.is_empty(),
+            }
+    }
+
+    /// Returns all legal choices of modes, each as the indices of the chosen modes in ascending
+    /// order.
+    pub(crate) fn choices(&self) -> Vec<Vec<usize>> {
+        let mut choices = (1..1_usize << self.modes.len())
+            .map(|mask| {
+                (0..self.modes.len())
+                    .filter(|it| mask & (1 << it) != 0)
+                    .collect::<Vec<_>>()
+            })
+            .filter(|it| self.is_legal_choice(it))
+            .collect::<Vec<_>>();
+        choices.sort();
+        choices
+    }
+}
+
+/// How many modes of a modal spell ability are chosen.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum ModeCount {
+    /// Represents e.g. “Choose one —” or “Choose two —”.
+    Exactly(usize),
+    /// Represents “Choose one or more —” as well as “Choose one or both —”.
+    OneOrMore,
+}
+
+/// 602.1. Activated abilities have a cost and an effect. They are written as “[Cost]: [Effect.]
+///        [Activation instructions (if any).]”
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ActivatedAbility {
+    pub(crate) cost: Vec<ActivationCost>,
+    pub(crate) effects: Vec<Effect>,
+}
+
+impl ActivatedAbility {
+    /// 605.1a An activated ability is a mana ability if it meets all of the following criteria: it
+    ///        doesn’t require a target (see rule 115.6), it could add mana to a player’s mana pool
+    ///        when it resolves, and it’s not a loyalty ability. [...]
+    pub(crate) fn is_mana_ability(&self) -> bool {
+        self.effects
+            .iter()
+            .any(|it| matches!(it, Effect::AddMana(_)))
+            && !self.effects.iter().any(Effect::requires_target)
+    }
+}
+
+/// 602.1a The activation cost is everything before the colon (:). An ability’s activation cost
+///        must be paid by the player who is activating it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum ActivationCost {
+    /// 107.5. The tap symbol is {T}. The tap symbol in an activation cost means “Tap this object.”
+    ///        [...]
+    Tap,
+    Pay(Cost),
+    /// A mana cost, e.g. “{2}”.
+    Mana(ManaCost),
+    /// 701.17a To sacrifice a permanent, its controller moves it from the battlefield directly to
+    ///         its owner’s graveyard. [...]
+    ///
+    /// Represents “Sacrifice a [permanent]”, e.g. “Sacrifice a creature”.
+    Sacrifice(ObjectFilter),
+    /// Represents “Sacrifice [this permanent]”.
+    SacrificeThis,
+    /// 701.8a To discard a card, move it from its owner’s hand to that player’s graveyard.
+    ///
+    /// Represents “Discard a card”.
+    Discard,
+    /// 119.4. If a cost or effect allows a player to pay an amount of life greater than 0, the
+    ///        player may do so only if their life total is greater than or equal to the amount of
+    ///        the payment. [...]
+    ///
+    /// Represents “Pay N life”.
+    PayLife(u64),
+    /// Represents “Exile a card from your hand”.
+    ExileFromHand,
+    /// Represents “Exile a card from your graveyard”.
+    ExileFromGraveyard,
+    /// Represents “Remove N [kind] counters from [this permanent]”, e.g. “Remove a +1/+1 counter
+    /// from this creature”.
+    RemoveCounters(CounterKind, u64),
+}
+
+/// 604.1. Static abilities do something all the time rather than being activated or triggered.
+///        They are written as statements, and they’re simply true.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum StaticAbility {
+    /// 613.4c Layer 7c: Effects and counters that modify power and/or toughness (but don’t set
+    ///        power and/or toughness to a specific number or value) are applied.
+    ///
+    /// Represents “[Objects] get +N/+N.”, e.g. “Creatures you control get +1/+1.”
+    ModifyPowerToughness {
+        affected: ObjectFilter,
+        power: i64,
+        toughness: i64,
+    },
+    /// 613.1d Layer 4: Type-changing effects are applied. [...]
+    ///
+    /// Represents e.g. “Nonbasic lands are Mountains.” or “Each land is a Swamp in addition to its
+    /// other land types.”
+    ChangeTypes {
+        affected: ObjectFilter,
+        change: TypeChange,
+    },
+    /// 402.2. [...] Some effects modify a player’s maximum hand size or change it to a certain
+    ///        number.
+    ///
+    /// Represents “You have no maximum hand size.”
+    NoMaximumHandSize,
+    /// Represents “You can’t lose the game.”
+    CantLoseGame,
+    /// Represents “Your opponents can’t win the game.”
+    OpponentsCantWinGame,
+    /// 614.1a Effects that use the word “instead” are replacement effects. [...]
+    ///
+    /// Represents “If [this card] would be put into a graveyard from anywhere, reveal it and
+    /// shuffle it into its owner’s library instead.” Unlike other static abilities, it functions
+    /// in every zone.
+    ShuffleIntoLibraryInstead,
+    /// 614.1c Effects that read “[This permanent] enters the battlefield with . . . ,” “As [this
+    ///        permanent] enters the battlefield . . . ,” or “[This permanent] enters the
+    ///        battlefield as . . . ” are replacement effects.
+    ///
+    /// Represents a choice made as the permanent enters the battlefield, e.g. “As [this permanent]
+    /// enters the battlefield, choose a color.”
+    AsEntersBattlefield(EntryChoice),
+    /// 614.1c [...]
+    ///
+    /// Represents “[This permanent] enters the battlefield with N counters of the kind on it.”,
+    /// e.g. “with a charge counter on it” or “with X +1/+1 counters on it”.
+    EntersWithCounters(CounterKind, CounterAmount),
+}
+
+/// The number of counters a permanent enters the battlefield with.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum CounterAmount {
+    Fixed(u64),
+    /// The value of X chosen as the spell that became the permanent was cast, or 0 if it wasn't
+    /// cast, see rule 107.3m.
+    X,
+}
+
+/// A choice the controller of a permanent makes as it enters the battlefield.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum EntryChoice {
+    /// Represents “As [this permanent] enters the battlefield, you may pay N life. If you don’t, it
+    /// enters the battlefield tapped.”
+    PayLifeOrEnterTapped(u64),
+    /// Represents “As [this permanent] enters the battlefield, choose a color.”
+    Color,
+    /// Represents “As [this permanent] enters the battlefield, choose a creature type.”
+    CreatureType,
+    /// Represents “As [this permanent] enters the battlefield, choose an opponent.”
+    Opponent,
+}
+
+/// How a type-changing effect changes the types of the objects it affects.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum TypeChange {
+    /// 305.7. If an effect sets a land’s subtype to one or more of the basic land types, the land
+    ///        no longer has its old land type. It loses all abilities generated from its rules
+    ///        text, its old land types, and any copiable effects affecting that land, and it gains
+    ///        the appropriate mana ability for each new basic land type. [...]
+    SetBasicLandType(BasicLandType),
+    /// The objects have the card types and subtypes in addition to their other types.
+    AddTypes {
+        card_types: Vec<CardType>,
+        subtypes: Vec<Subtype>,
+    },
+}
+
+/// The decision made for an [`EntryChoice`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub(crate) enum EntryDecision {
+    /// Whether the life is paid.
+    PayLife(bool),
+    Color(Color),
+    CreatureType(CreatureType),
+    Player(PlayerId),
+}
+
+/// 603.1. Triggered abilities have a trigger condition and an effect. They are written as
+///        “[When/Whenever/At] [trigger event or trigger condition], [effect]. [Instructions (if
+///        any).]”
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct TriggeredAbility {
+    pub(crate) condition: TriggerCondition,
+    /// The condition of an intervening “if” clause, if the ability has one.
+    #[serde(default)]
+    pub(crate) intervening_if: Option<InterveningIf>,
+    pub(crate) effects: Vec<Effect>,
+}
+
+/// 603.4. A triggered ability may read “When/Whenever/At [trigger event], if [condition],
+///        [effect].” When the trigger event occurs, the ability checks whether the stated
+///        condition is true. The ability triggers only if it is; otherwise it does nothing. If
+///        the ability triggers, it checks the stated condition again as it resolves. If the
+///        condition isn’t true at that time, the ability is removed from the stack and does
+///        nothing. [...]
+///
+/// The conditions are evaluated from the perspective of the controller of the ability.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum InterveningIf {
+    /// The number of permanents matching the filter satisfies the comparison, e.g. “if you
+    /// control three or more artifacts”.
+    Permanents(ObjectFilter, Comparison),
+    /// The number satisfies the comparison, e.g. “if you have five or more experience counters”.
+    Quantity(Quantity, Comparison),
+}
+
+/// 603.2. Whenever a game event or game state matches a triggered ability’s trigger event, that
+///        ability automatically triggers. The ability doesn’t do anything at this point.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum TriggerCondition {
+    /// 603.6a Enters-the-battlefield abilities trigger when a permanent enters the battlefield.
+    ///        These are written, “When [this object] enters the battlefield, . . . ” or “Whenever
+    ///        a [type] enters the battlefield, . . .” Each time an event puts one or more
+    ///        permanents onto the battlefield, all permanents on the battlefield (including the
+    ///        newcomers) are checked for any enters-the-battlefield triggers that match the event.
+    EntersBattlefield(TriggerSubject),
+    /// 700.4. The term dies means “is put into a graveyard from the battlefield.”
+    Dies(TriggerSubject),
+    /// 603.6c Leaves-the-battlefield abilities trigger when a permanent moves from the
+    ///        battlefield to another zone, [...]
+    ///
+    /// Triggers whichever zone the permanent moves to, e.g. when it is exiled or returned to its
+    /// owner's hand.
+    LeavesBattlefield(TriggerSubject),
+    /// 508.3a An ability that reads “Whenever [a creature] attacks, . . .” triggers if that
+    ///        creature is declared as an attacker. [...]
+    Attacks(TriggerSubject),
+    /// 503.1a Any abilities that triggered during the untap step and any abilities that trigger at
+    ///        the beginning of the upkeep are put onto the stack before the active player gets
+    ///        priority; [...]
+    BeginningOfUpkeep(PlayerScope),
+    /// 504.2. Second, the active player gets priority. [...]
+    BeginningOfDrawStep(PlayerScope),
+    /// 513.1. First, all abilities that trigger “at the beginning of the end step” or “at the
+    ///        beginning of the next end step” trigger.
+    BeginningOfEndStep(PlayerScope),
+    /// An ability that reads “Whenever you roll a die, . . .” triggers once for each die rolled,
+    /// including dice whose results are ignored.
+    RollsDie(PlayerScope),
+    /// 601.2i [...] Any abilities that trigger when a spell is cast or put onto the stack trigger
+    ///        at this time. [...]
+    ///
+    /// Triggers whenever the controller of the ability casts a spell matching the filter. Lands
+    /// aren't cast and activating an ability isn't casting a spell.
+    YouCastSpell { filter: ObjectFilter },
+    /// Landfall — Whenever a land enters the battlefield under your control, . . .
+    ///
+    /// Triggers for lands that are played as well as lands put onto the battlefield by effects.
+    LandEntersBattlefieldUnderYourControl,
+    /// Represents “When [this card] is put into a graveyard from anywhere, . . .” The ability
+    /// triggers from the graveyard and its owner controls it.
+    PutIntoGraveyardFromAnywhere,
+}
+
+/// Whose turn or action a trigger condition refers to, e.g. “At the beginning of your upkeep,
+/// . . .”, “At the beginning of each opponent’s end step, . . .” or “Whenever you roll a die,
+/// . . .”
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum PlayerScope {
+    You,
+    EachPlayer,
+    Opponent,
+}
+
+impl PlayerScope {
+    /// Returns whether the turn or action of the specified player is included for the controller of
+    /// the ability.
+    pub(crate) fn includes(self, controller: PlayerId, player: PlayerId) -> bool {
+        match self {
+            Self::You => controller == player,
+            Self::EachPlayer => true,
+            Self::Opponent => controller != player,
+        }
+    }
+}
+
+/// The object whose game event a trigger condition refers to.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum TriggerSubject {
+    /// The object that has the triggered ability, e.g. “When [this object] enters the
+    /// battlefield, . . .”
+    This,
+    /// Any object matching the filter, e.g. “Whenever a creature enters the battlefield under your
+    /// control, . . .”
+    Matching(ObjectFilter),
+}
+
+/// Describes a set of objects based on their characteristics and controller.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ObjectFilter {
+    /// The card type the object must have, if any.
+    #[serde(default)]
+    pub(crate) card_type: Option<CardType>,
+    /// The card type the object must not have, if any, e.g. “noncreature”.
+    #[serde(default)]
+    pub(crate) excluded_card_type: Option<CardType>,
+    /// The supertype the object must not have, if any, e.g. “nonbasic”.
+    #[serde(default)]
+    pub(crate) excluded_supertype: Option<Supertype>,
+    /// Whether the object must be controlled by the controller of the ability.
+    #[serde(default)]
+    pub(crate) you_control: bool,
+    /// Whether the object must be controlled by an opponent of the controller of the ability.
+    #[serde(default)]
+    pub(crate) opponent_controls: bool,
+    /// The subtype the object must have, if any.
+    #[serde(default)]
+    pub(crate) subtype: Option<Subtype>,
+    /// The color the object must have, if any.
+    #[serde(default)]
+    pub(crate) color: Option<Color>,
+    /// The comparison the power of the object must satisfy, if any. Objects without power never
+    /// satisfy it.
+    #[serde(default)]
+    pub(crate) power: Option<Comparison>,
+    /// Whether the object must have the color chosen for the source of the ability as it entered
+    /// the battlefield, e.g. “creatures of the chosen color”. Nothing matches if no color was
+    /// chosen.
+    #[serde(default)]
+    pub(crate) of_chosen_color: bool,
+}
+
+/// Compares a number such as the power of an object to a fixed value.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum Comparison {
+    /// N or less, e.g. “creatures with power 2 or less”.
+    AtMost(i64),
+    /// N or greater, e.g. “creatures with power 4 or greater”.
+    AtLeast(i64),
+}
+
+impl Comparison {
+    pub(crate) fn matches(self, value: i64) -> bool {
+        match self {
+            Self::AtMost(limit) => value <= limit,
+            Self::AtLeast(limit) => value >= limit,
+        }
+    }
+}
+
+/// 609.1. An effect is something that happens in the game as a result of a spell or ability. When
+///        a spell, activated ability, or triggered ability resolves, it may create one or more
+///        one-shot or continuous effects. Static abilities may create one or more continuous
+///        effects. Text itself is never an effect.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum Effect {
+    /// You gain N life.
+    GainLife(u64),
+    /// You lose N life.
+    LoseLife(u64),
+    /// Draw N cards, e.g. “Draw a card for each Elf you control.”
+    DrawCards(Quantity),
+    /// Shuffle your library.
+    ShuffleLibrary,
+    /// Deal N damage to any target.
+    DealDamage(Quantity),
+    /// 106.4. When an effect instructs a player to add mana, that mana goes into a player’s mana
+    ///        pool. [...]
+    AddMana(Mana),
+    /// [This object] gets +N/+N until end of turn.
+    PumpUntilEndOfTurn { power: i64, toughness: i64 },
+    /// 701.7a To destroy a permanent, move it from the battlefield to its owner’s graveyard.
+    ///
+    /// Destroy target permanent matching the filter.
+    DestroyTarget(ObjectFilter),
+    /// Destroy all permanents matching the filter, e.g. “Destroy all creatures.”
+    DestroyAll(ObjectFilter),
+    /// Exile all permanents matching the filter, e.g. “Exile all creatures.”
+    ExileAll(ObjectFilter),
+    /// Deal N damage to each permanent matching the filter, e.g. “Deal 2 damage to each
+    /// creature.”
+    DamageEach(ObjectFilter, u64),
+    /// 702.26a [...] “Target permanent phases out.”
+    PhaseOut,
+    /// Exile [this object], then return it to the battlefield under its owner’s control.
+    Blink,
+    /// Tap target creature. Put N stun counters on it.
+    TapAndStun(u64),
+    /// You get N counters of the kind, e.g. “You get an experience counter.”
+    GetCounters(CounterKind, u64),
+    /// Investigate, i.e. create a Clue token.
+    Investigate,
+    /// Create N Treasure tokens.
+    CreateTreasure(u64),
+    /// Create N Food tokens.
+    CreateFood(u64),
+    /// Create N Blood tokens.
+    CreateBlood(u64),
+    /// [This creature] explores, i.e. its controller reveals the top card of their library. A land
+    /// card is put into their hand. Otherwise, a +1/+1 counter is put on the creature and the card
+    /// may be put into their graveyard.
+    Explore,
+    /// Target creature loses all abilities until end of turn.
+    LoseAllAbilitiesUntilEndOfTurn,
+    /// Each permanent matching the filter gains the keyword ability until end of turn, e.g.
+    /// “Creatures you control gain flying until end of turn.”
+    GainKeywordUntilEndOfTurn(ObjectFilter, KeywordAbility),
+    /// 104.2b An effect may state that a player wins the game.
+    ///
+    /// You win the game.
+    WinGame,
+    /// Return target card matching the filter from your graveyard to your hand, e.g. “Return
+    /// target creature card from your graveyard to your hand.” The card is targeted as a
+    /// [`PermanentOrPlayer::Permanent`].
+    ///
+    /// [`PermanentOrPlayer::Permanent`]: crate::game::PermanentOrPlayer::Permanent
+    ReturnFromGraveyardToHand(ObjectFilter),
+    /// Exile all cards from target player’s graveyard.
+    ExileGraveyard,
+    /// Shuffle your graveyard into your library.
+    ShuffleGraveyardIntoLibrary,
+    /// Sacrifice [this permanent].
+    SacrificeThis,
+    /// 118.12. Some spells, activated abilities, and triggered abilities read, “[Do something]
+    ///         unless [a player does something else].” [...]
+    ///
+    /// Sacrifice [this permanent] unless you pay [cost], e.g. the upkeep cost of Force of Nature.
+    /// Its controller decides whether to pay as the ability resolves.
+    SacrificeUnlessPay(ManaCost),
+    /// 603.5. Some triggered abilities’ effects are optional (they contain “may,” as in “At the
+    ///        beginning of your upkeep, you may draw a card”). These abilities go on the stack when
+    ///        they trigger, regardless of whether their controller intends to exercise the
+    ///        ability’s option or not. The choice is made when the ability resolves. [...]
+    ///
+    /// You may [effects]. When you do, [reflexive effects], e.g. “You may sacrifice it. When you
+    /// do, it deals 2 damage to any target.” The optional effects can't have targets, while the
+    /// reflexive triggered ability chooses its own targets as it is put on the stack.
+    May {
+        effects: Vec<Effect>,
+        when_you_do: Vec<Effect>,
+    },
+}
+
+/// A number an effect refers to, which is either fixed or determined as the effect resolves.
+///
+/// 608.2h If an effect requires information from the game (such as the number of creatures on
+///        the battlefield), the answer is determined only once, when the effect is applied. If
+///        the effect requires information from a specific object, including the source of the
+///        ability itself, the effect uses the current information of that object if it’s in the
+///        public zone it was expected to be in; if it’s no longer in that zone, [...] the effect
+///        uses the object’s last known information. [...]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum Quantity {
+    Fixed(u64),
+    /// The number of counters of the kind the controller of the effect has, e.g. “the number of
+    /// experience counters you have.”
+    CountersOnYou(CounterKind),
+    /// The number of counters of the kind on the source of the effect, e.g. “the number of +1/+1
+    /// counters on this creature.”
+    CountersOnThis(CounterKind),
+    /// The number of permanents matching the filter, e.g. “the number of Elves you control.”
+    Permanents(ObjectFilter),
+    /// The number of cards matching the filter in a zone, e.g. “the number of creature cards in
+    /// your graveyard.” Whether the controller of the effect controls a card is determined by
+    /// whether they own it.
+    Cards(CountedZone, ObjectFilter),
+    /// The life total of the controller of the effect, or 0 if it's negative.
+    YourLife,
+    /// The value chosen for X as the spell that is the source of the effect was cast, or 0 if
+    /// none was chosen, e.g. “Draw X cards.”
+    X,
+}
+
+/// A zone whose cards a [`Quantity`] counts.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum CountedZone {
+    YourHand,
+    YourLibrary,
+    YourGraveyard,
+    Exile,
+}
+
+impl Effect {
+    /// 115.1. Some spells and abilities require their controller to choose one or more targets for
+    ///        them. [...]
+    pub(crate) fn requires_target(&self) -> bool {
+        self.target_requirement().is_some()
+    }
+
+    /// Returns whether the controller chooses whether the effect happens as it resolves, or
+    /// whether they pay to keep it from happening.
+    pub(crate) fn is_optional(&self) -> bool {
+        matches!(self, Self::May { .. } | Self::SacrificeUnlessPay(_))
+    }
+
+    /// Returns what the effect may target, or `None` if it doesn't target anything.
+    pub(crate) fn target_requirement(&self) -> Option<TargetRequirement> {
+        Some(match self {
+            Self::DealDamage(_) => TargetRequirement::AnyTarget,
+            Self::DestroyTarget(_) | Self::PhaseOut => TargetRequirement::Permanent,
+            Self::TapAndStun(_) | Self::LoseAllAbilitiesUntilEndOfTurn => {
+                TargetRequirement::Creature
+            }
+            Self::ReturnFromGraveyardToHand(_) => TargetRequirement::CardInGraveyard,
+            Self::ExileGraveyard => TargetRequirement::Player,
+            _ => return None,
+        })
+    }
+}
+
+/// 115.1. [...] The targets are object(s) and/or player(s) the spell or ability will affect.
+///        [...]
+///
+/// The kind of object or player a targeting effect accepts as its target.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum TargetRequirement {
+    /// A creature, player, planeswalker or battle, as the phrase “any target” is defined in rule
+    /// 115.4.
+    AnyTarget,
+    Permanent,
+    Creature,
+    Player,
+    /// A card in a graveyard rather than a permanent.
+    CardInGraveyard,
+}
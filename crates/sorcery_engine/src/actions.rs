@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::{ObjectId, PlayerId};
+
+/// 115.1. Some spells and abilities require their controller to choose one or more targets for
+///        them. The targets are object(s) and/or player(s) the spell or ability will affect. [...]
+///
+/// A player or an object chosen as the target of a spell or ability.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Target {
+    Player(PlayerId),
+    Object(ObjectId),
+}
+
+/// A decision a player submits to a game, see [`Game::apply`](crate::game::Game::apply). The
+/// actions a player can currently take are listed by
+/// [`Game::legal_actions`](crate::game::Game::legal_actions).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameAction {
+    /// 305.1. A player who has priority may play a land card from their hand during a main phase
+    ///        of their turn when the stack is empty. [...]
+    PlayLand { card: ObjectId },
+    /// 601.2. To cast a spell is to take it from where it is (usually the hand), put it on the
+    ///        stack, and pay its costs [...]
+    ///
+    /// The targets are listed in the order of the effects that require them. The modes of a modal
+    /// spell are referred to by their index.
+    CastSpell {
+        spell: ObjectId,
+        targets: Vec<Target>,
+        x: u64,
+        modes: Vec<usize>,
+    },
+    /// 602.2. To activate an ability is to put it onto the stack and pay its costs [...]
+    ///
+    /// The ability is referred to by its index among the activated abilities of the source.
+    ActivateAbility {
+        source: ObjectId,
+        index: usize,
+        targets: Vec<Target>,
+    },
+    /// 508.1a The active player chooses which creatures that they control, if any, will attack.
+    ///        [...]
+    DeclareAttackers { attackers: Vec<ObjectId> },
+    /// 509.1a The defending player chooses which creatures they control, if any, will block. [...]
+    ///
+    /// Each block pairs a blocking creature with the attacking creature it blocks.
+    DeclareBlockers { blocks: Vec<(ObjectId, ObjectId)> },
+    /// 603.3d The remainder of the process for putting a triggered ability on the stack is
+    ///        identical to the process for casting a spell listed in rules 601.2c–d. [...]
+    ///
+    /// Chooses the targets of the oldest pending triggered ability, which then resolves.
+    ResolveTrigger { targets: Vec<Target> },
+    /// Chooses the option at the specified index of the choice the player faces, which their
+    /// [`PlayerView`](crate::spectator::PlayerView) describes, e.g. the order of replacement
+    /// effects competing to modify the same event.
+    Choose { option: usize },
+    /// 117.3d If a player has priority and chooses not to take any actions, that player passes
+    ///        priority. [...]
+    PassPriority,
+    /// 104.3a A player can concede the game at any time. [...]
+    Concede,
+}
@@ -0,0 +1,120 @@
+use std::cmp::Reverse;
+
+use crate::{
+    actions::{GameAction, Target},
+    core::ObjectId,
+    spectator::{ObjectStatus, PlayerView},
+};
+
+/// Decides on behalf of a player in a game driven by [`Game::apply`](crate::game::Game::apply),
+/// e.g. a bot or a bridge to a human on the other end of a connection.
+pub trait PlayerAgent {
+    /// Chooses one of the actions the player can take, which are never empty, based on the game
+    /// as the player sees it.
+    fn choose(&mut self, view: &PlayerView, actions: &[GameAction]) -> GameAction;
+}
+
+/// An agent that plays a land whenever it can, casts the most expensive spell it can afford,
+/// attacks with all its creatures and only blocks with creatures that survive the block. Targets
+/// are chosen among its opponents and the permanents they control where possible, and activated
+/// abilities are left alone.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct GreedyAgent;
+
+impl PlayerAgent for GreedyAgent {
+    fn choose(&mut self, view: &PlayerView, actions: &[GameAction]) -> GameAction {
+        // The first of the best actions is taken, so ties are broken in the order they are listed.
+        actions
+            .iter()
+            .min_by_key(|it| Reverse(GreedyAgent::score(view, it)))
+            .cloned()
+            .unwrap_or(GameAction::PassPriority)
+    }
+}
+
+impl GreedyAgent {
+    /// Returns how much the agent likes the action, the higher the better.
+    fn score(view: &PlayerView, action: &GameAction) -> u64 {
+        let object = |id: ObjectId| view.objects.iter().find(|it| it.id == id);
+        let hostile_targets = |targets: &[Target]| {
+            targets
+                .iter()
+                .filter(|it| match **it {
+                    Target::Player(player) => player != view.player,
+                    Target::Object(id) => object(id)
+                        .is_some_and(|it| it.controller.unwrap_or(it.owner) != view.player),
+                })
+                .count() as u64
+        };
+        match action {
+            GameAction::PlayLand { .. } => 10_000,
+            GameAction::CastSpell {
+                spell, targets, x, ..
+            } => {
+                let mana_value = object(*spell).map_or(0, |it| it.mana_value);
+                1_000 + 10 * (mana_value + x) + hostile_targets(targets)
+            }
+            GameAction::DeclareAttackers { attackers } => attackers.len() as u64,
+            GameAction::DeclareBlockers { blocks } => {
+                let survives = |&(blocker, attacker): &(ObjectId, ObjectId)| {
+                    let power_toughness =
+                        |id| object(id).and_then(|it: &ObjectStatus| it.power_toughness);
+                    match (power_toughness(blocker), power_toughness(attacker)) {
+                        (Some((_, toughness)), Some((power, _))) => toughness > power,
+                        _ => false,
+                    }
+                };
+                if blocks.iter().all(survives) {
+                    1 + blocks.len() as u64
+                } else {
+                    0
+                }
+            }
+            GameAction::ResolveTrigger { targets } => hostile_targets(targets),
+            // The options of a choice are listed with the most likely preferred one first.
+            GameAction::Choose { option } => u64::MAX - *option as u64,
+            GameAction::PassPriority => 1,
+            GameAction::ActivateAbility { .. } | GameAction::Concede => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{core::Deck, setup::GameBuilder};
+
+    #[test]
+    fn greedy_agents_play_a_game_to_the_end() {
+        let deck = || {
+            Deck::parse("24 Plains\n18 Soulmender\n18 Steppe Lynx")
+                .expect("Could not parse the deck.")
+        };
+        let (mut game, players) = GameBuilder::new()
+            .seed(7)
+            .player("Alice", deck())
+            .player("Bob", deck())
+            .build()
+            .expect("Could not build the game.");
+        let mut agents = [GreedyAgent, GreedyAgent];
+
+        while let Some(player) = game.player_to_act().expect("Could not advance the game.") {
+            assert!(game.turn().number <= 200, "The game did not end.");
+            let view = game.view_for(player).expect("Could not view the game.");
+            let actions = game
+                .legal_actions(player)
+                .expect("Could not list the actions.");
+            let index = players
+                .iter()
+                .position(|&it| it == player)
+                .expect("Could not find the player.");
+            let action = agents[index].choose(&view, &actions);
+            game.apply(player, action)
+                .expect("Could not apply the action.");
+        }
+        assert!(game.outcome().is_some());
+        assert_eq!(game.check_invariants(), Ok(()));
+        // The game is decided in combat long before the libraries run out.
+        assert!(game.turn().number < 50);
+    }
+}
@@ -0,0 +1,41 @@
+use serde::Serialize;
+
+use crate::core::{ManaCost, ObjectId, Zone};
+
+/// 601.2. To cast a spell is to take it from where it is (usually the hand), put it on the stack,
+///        and pay its costs, so that it will eventually resolve and have its effect. [...]
+///
+/// A way a player can currently cast one of their cards, along with what a client needs to offer
+/// it to them.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub(crate) struct CastingOption {
+    pub(crate) spell: ObjectId,
+    pub(crate) name: String,
+    /// The zone the card is cast from.
+    pub(crate) zone: Zone,
+    pub(crate) method: CastingMethod,
+    /// The mana cost that is paid instead of the mana cost of the card if it is cast this way.
+    pub(crate) cost: ManaCost,
+    /// The modes chosen for a modal spell, each referred to by its index.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) modes: Vec<usize>,
+}
+
+/// 118.9. Some spells have alternative costs. An alternative cost is a cost listed in a spell’s
+///        text, or applied to it from another effect, that its controller may pay rather than
+///        paying the spell’s mana cost. [...]
+///
+/// The permission a card is cast with, which determines the zone it is cast from and the cost that
+/// is paid.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize)]
+pub(crate) enum CastingMethod {
+    /// The card is cast from the hand for its mana cost.
+    Normal,
+    /// 702.34a [...] “Flashback [cost]” means “You may cast this card from your graveyard if the
+    ///         resulting spell is an instant or sorcery spell by paying [cost] rather than paying
+    ///         its mana cost” [...]
+    Flashback,
+    /// 702.143a [...] They may cast it after the current turn has ended by paying any foretell
+    ///          cost it has rather than paying that card’s mana cost. [...]
+    Foretold,
+}
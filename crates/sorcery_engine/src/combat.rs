@@ -0,0 +1,185 @@
+use hecs::Entity;
+use indexmap::IndexMap;
+
+use crate::{
+    core::{PlayerId, Subtype},
+    game::PermanentOrPlayer,
+};
+
+/// 506.1. The combat phase has five steps, which proceed in order: beginning of combat, declare
+///        attackers, declare blockers, combat damage, and end of combat. [...]
+///
+/// Keeps track of who attacks whom and which creatures block which attackers during the current
+/// combat phase. Attacking creatures are marked with the [`Attacking`] component as well.
+///
+/// [`Attacking`]: crate::components::Attacking
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Combat {
+    /// The player each attacking creature attacks.
+    defenders: IndexMap<Entity, PlayerId>,
+    /// Whether attackers have been declared during this combat.
+    attackers_declared: bool,
+    /// Whether blockers have been declared during this combat.
+    blockers_declared: bool,
+    /// 509.1h An attacking creature with one or more creatures declared as blockers for it
+    ///        becomes a blocked creature; one with no creatures declared as blockers for it becomes
+    ///        an unblocked creature. This remains unchanged until the creature is removed from
+    ///        combat, an effect says that it becomes blocked or unblocked, or the combat phase
+    ///        ends, whichever comes first. [...]
+    ///
+    /// The blocked creatures, each with the creatures blocking it in its damage assignment order.
+    blocked: IndexMap<Entity, Vec<Entity>>,
+    /// The blocking creatures, each with the creatures it blocks in its damage assignment order.
+    blocking: IndexMap<Entity, Vec<Entity>>,
+    /// The combat damage the controllers of attacking creatures chose to assign to the creatures
+    /// blocking them and, if they have trample, to the player they attack.
+    assignments: IndexMap<Entity, Vec<(PermanentOrPlayer, u64)>>,
+}
+
+impl Combat {
+    /// Records that the creature attacks the specified player.
+    pub(crate) fn attack(&mut self, attacker: Entity, defender: PlayerId) {
+        self.defenders.insert(attacker, defender);
+    }
+
+    /// Returns the player the creature attacks, if it is attacking.
+    pub(crate) fn defender(&self, attacker: Entity) -> Option<PlayerId> {
+        self.defenders.get(&attacker).copied()
+    }
+
+    /// Returns the attacking creatures in the order they were declared.
+    pub(crate) fn attackers(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.defenders.keys().copied()
+    }
+
+    /// Records the attacks, each as a pair of an attacker and the player it attacks.
+    pub(crate) fn declare_attacks(&mut self, attacks: &[(Entity, PlayerId)]) {
+        for &(attacker, defender) in attacks {
+            self.attack(attacker, defender);
+        }
+        self.attackers_declared = true;
+    }
+
+    /// Returns whether attackers have been declared during this combat.
+    pub(crate) fn attackers_declared(&self) -> bool {
+        self.attackers_declared
+    }
+
+    /// Returns whether blockers have been declared during this combat.
+    pub(crate) fn blockers_declared(&self) -> bool {
+        self.blockers_declared
+    }
+
+    /// Records the blocks, each as a pair of a blocker and the attacker it blocks. The damage
+    /// assignment orders follow the order of the pairs.
+    pub(crate) fn declare_blocks(&mut self, blocks: &[(Entity, Entity)]) {
+        for &(blocker, attacker) in blocks {
+            self.blocked.entry(attacker).or_default().push(blocker);
+            self.blocking.entry(blocker).or_default().push(attacker);
+        }
+        self.blockers_declared = true;
+    }
+
+    /// Returns the blocking creatures in the order they were declared.
+    pub(crate) fn blocking_creatures(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.blocking.keys().copied()
+    }
+
+    /// Returns whether the creature has become blocked.
+    pub(crate) fn is_blocked(&self, attacker: Entity) -> bool {
+        self.blocked.contains_key(&attacker)
+    }
+
+    /// Returns the creatures blocking the attacker in its damage assignment order.
+    pub(crate) fn blockers(&self, attacker: Entity) -> &[Entity] {
+        self.blocked.get(&attacker).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the creatures the blocker blocks in its damage assignment order.
+    pub(crate) fn blocked_by(&self, blocker: Entity) -> &[Entity] {
+        self.blocking.get(&blocker).map_or(&[], Vec::as_slice)
+    }
+
+    /// 509.2. Second, for each attacking creature that’s become blocked, the active player
+    ///        announces that creature’s damage assignment order, which consists of the creatures
+    ///        blocking it in an order of that player’s choice. [...]
+    ///
+    /// Replaces the damage assignment order of the attacker and returns whether the order consists
+    /// of exactly the creatures blocking it.
+    pub(crate) fn order_blockers(&mut self, attacker: Entity, order: &[Entity]) -> bool {
+        let Some(blockers) = self.blocked.get_mut(&attacker) else {
+            return false;
+        };
+        let mut sorted = order.to_vec();
+        sorted.sort();
+        let mut expected = blockers.clone();
+        expected.sort();
+        if sorted != expected {
+            return false;
+        }
+        *blockers = order.to_vec();
+        true
+    }
+
+    /// 506.4. A permanent that’s removed from combat stops being an attacking, blocking, blocked,
+    ///        and/or unblocked creature. [...]
+    ///
+    /// Removes the creature from combat. Attackers it blocked remain blocked and choose anew how to
+    /// assign their combat damage.
+    pub(crate) fn remove(&mut self, entity: Entity) {
+        self.defenders.shift_remove(&entity);
+        self.assignments.shift_remove(&entity);
+        for attacker in self.blocking.shift_remove(&entity).unwrap_or_default() {
+            if let Some(blockers) = self.blocked.get_mut(&attacker) {
+                blockers.retain(|&it| it != entity);
+            }
+            self.assignments.shift_remove(&attacker);
+        }
+        for blocker in self.blocked.shift_remove(&entity).unwrap_or_default() {
+            if let Some(attackers) = self.blocking.get_mut(&blocker) {
+                attackers.retain(|&it| it != entity);
+            }
+        }
+    }
+
+    /// Stores how the attacker assigns its combat damage among the creatures blocking it.
+    pub(crate) fn assign(&mut self, attacker: Entity, assignment: Vec<(PermanentOrPlayer, u64)>) {
+        self.assignments.insert(attacker, assignment);
+    }
+
+    /// Returns how the attacker assigns its combat damage, if its controller chose that already.
+    pub(crate) fn assignment(&self, attacker: Entity) -> Option<&[(PermanentOrPlayer, u64)]> {
+        self.assignments.get(&attacker).map(Vec::as_slice)
+    }
+}
+
+/// A restriction or requirement an effect imposes on a creature when attackers are declared.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum AttackConstraint {
+    /// 508.1d The active player checks each creature they control to see whether it’s affected by
+    ///        any requirements (effects that say a creature must attack, or that it must attack if
+    ///        some condition is met). [...]
+    ///
+    /// Represents “This creature attacks each combat if able.”
+    MustAttack,
+    /// The creature was goaded by the specified player, so it attacks each combat if able and
+    /// attacks a player other than the goading player if able.
+    Goaded(PlayerId),
+    /// 508.1c The active player checks each creature they control to see whether it’s affected by
+    ///        any restrictions (effects that say a creature can’t attack, or that it can’t attack
+    ///        unless some condition is met). [...]
+    ///
+    /// Represents “This creature can’t attack.”
+    CantAttack,
+    /// Represents “This creature can’t attack you.”
+    CantAttackPlayer(PlayerId),
+    /// Represents e.g. “This creature can’t attack unless defending player controls an Island.”
+    CantAttackUnlessDefenderControls(Subtype),
+}
+
+impl AttackConstraint {
+    /// Returns whether the constraint requires the creature to attack rather than restricting it.
+    pub(crate) fn is_requirement(self) -> bool {
+        matches!(self, Self::MustAttack | Self::Goaded(_))
+    }
+}
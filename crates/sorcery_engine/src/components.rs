@@ -1,4 +1,13 @@
-use crate::core::PlayerId;
+use std::sync::Arc;
+
+use hecs::Entity;
+use indexmap::IndexSet;
+
+use crate::{
+    abilities::{ActivatedAbility, Effect, EntryDecision, StaticAbility, TriggeredAbility},
+    core::{Card, Color, CreatureType, KeywordAbility, ObjectId, PlayerId},
+    game::PermanentOrPlayer,
+};
 
 /// 109.1. An object is an ability on the stack, a card, a copy of a card, a token, a spell, a
 ///        permanent, or an emblem.
@@ -11,6 +20,113 @@ use crate::core::PlayerId;
 ///        what an Aura enchants, and so on.
 pub(crate) struct Object;
 
+/// 109.3. [...] An object’s characteristics are name, mana cost, color, color indicator, card
+///        type, subtype, supertype, rules text, abilities, power, toughness, loyalty, hand
+///        modifier, and life modifier. [...]
+///
+/// The card an object was spawned from, which provides its printed characteristics such as the
+/// name, mana cost, type line and rules text. The card is shared by all objects spawned from it.
+pub(crate) struct CardRef(pub(crate) Arc<Card>);
+
+/// 707.2. When copying an object, the copy acquires the copiable values of the original object’s
+///        characteristics [...]
+///
+/// The card a permanent was spawned from while it is a copy of another object, whose printed
+/// characteristics it regains once it leaves the battlefield.
+pub(crate) struct PrintedCard(pub(crate) Arc<Card>);
+
+/// 712.8e While a double-faced permanent’s back face is up, it has only the characteristics of
+///        its back face. [...]
+///
+/// Marks a transformed permanent, whose printed card is kept as its [`PrintedCard`] to transform it
+/// back.
+pub(crate) struct Transformed;
+
+/// 702.140c As a mutating creature spell resolves, if its target is legal, it doesn’t enter the
+///          battlefield. Rather, it merges with the target creature and becomes one object
+///          represented by more than one card or token [...]
+///
+/// The cards a mutated permanent is represented by, ordered from top to bottom. One of them is the
+/// entity of the permanent itself while the others aren't objects on their own until the
+/// permanent leaves the battlefield.
+pub(crate) struct MergedWith(pub(crate) Vec<Entity>);
+
+/// 707.10. To copy a spell, activated ability, or triggered ability means to put a copy of it onto
+///         the stack; a copy of a spell isn’t cast and a copy of an activated ability isn’t
+///         activated. [...] A copy of a spell is owned by the player under whose control it was
+///         put on the stack. [...] A copy of a spell is itself a spell, even though it has no
+///         spell card associated with it. [...]
+pub(crate) struct SpellCopy;
+
+/// 602.2a [...] The ability is created on the stack as an object that’s not a card. It becomes the
+///        topmost object on the stack. It has the text of the ability that created it, and no
+///        other characteristics. Its controller is the player who activated the ability. [...]
+///
+/// An activated ability on the stack along with the object it was activated from, whose effects
+/// it follows once all players have passed priority.
+pub(crate) struct StackedAbility {
+    pub(crate) source: Entity,
+    pub(crate) effects: Vec<Effect>,
+}
+
+/// 702.34a [...] “If the flashback cost was paid, exile this card instead of putting it anywhere
+///         else any time it would leave the stack.”
+pub(crate) struct CastWithFlashback;
+
+/// 601.2b If the spell is modal, the player announces the mode choice (see rule 700.2). [...]
+///
+/// The modes chosen for a modal spell on the stack, each referred to by its index in ascending
+/// order.
+pub(crate) struct ChosenModes(pub(crate) Vec<usize>);
+
+/// 608.2d If an effect of a spell or ability offers any choices other than choices already made as
+///        part of casting the spell, activating the ability, or otherwise putting it on the stack,
+///        the player announces these while applying the effect. [...]
+///
+/// The decisions of the controller of a spell on the stack whether to take each of its optional
+/// actions in order, which they make once all players have passed priority and before it resolves.
+pub(crate) struct OptionDecisions(pub(crate) Vec<bool>);
+
+/// 107.3m If an object’s enters-the-battlefield triggered ability or replacement effect refers to
+///        X, and the spell that became that object as it resolved had a value of X chosen for any
+///        of its costs, the value of X for that ability is the same as the value of X for that
+///        spell, although the value of X for that permanent is 0. [...]
+///
+/// The value of X chosen for a spell on the stack, which is kept until the permanent it becomes
+/// has entered the battlefield.
+pub(crate) struct ChosenX(pub(crate) u64);
+
+/// 601.2c The player announces their choice of an appropriate player, object, or zone for each
+///        target the spell requires. [...]
+///
+/// The targets chosen for a spell on the stack in the order of the effects that require them,
+/// which it uses as it resolves once all players have passed priority.
+pub(crate) struct ChosenTargets(pub(crate) Vec<PermanentOrPlayer>);
+
+/// 406.3. Exiled cards are, by default, kept face up and may be examined by any player at any
+///        time. Cards “exiled face down” can’t be examined by any player except when instructions
+///        allow it. [...]
+///
+/// A card in exile that is face down, which only its owner may look at.
+pub(crate) struct FaceDown;
+
+/// 702.143a [...] That player may look at that card as long as it remains in exile. They may cast
+///          it after the current turn has ended [...]
+///
+/// The number of the turn a card in exile was foretold during.
+pub(crate) struct Foretold(pub(crate) u64);
+
+/// The id of the creature a mutating creature spell on the stack targets.
+pub(crate) struct MutatingOnto(pub(crate) ObjectId);
+
+/// The id the object had before its most recent zone change, which is used to look up last known
+/// information about the previous object.
+pub(crate) struct PreviousId(pub(crate) ObjectId);
+
+/// 111.1. Some effects put tokens onto the battlefield. A token is a marker used to represent any
+///        permanent that isn’t represented by a card.
+pub(crate) struct Token;
+
 /// 108.3. The owner of a card in the game is the player who started the game with it in their deck.
 ///        If a card is brought into the game from outside the game rather than starting in a
 ///        player’s deck, its owner is the player who brought it into the game. If a card starts the
@@ -23,3 +139,90 @@ pub(crate) struct Owner(pub(crate) PlayerId);
 ///        neither on the stack nor on the battlefield aren’t controlled by any player. See rule
 ///        108.4.
 pub(crate) struct Controller(pub(crate) PlayerId);
+
+/// 302.6. A creature’s activated ability with the tap symbol or the untap symbol in its activation
+///        cost can’t be activated unless the creature has been under its controller’s control
+///        continuously since their most recent turn began. A creature can’t attack unless it has
+///        been under its controller’s control continuously since their most recent turn began.
+///        This rule is informally called the “summoning sickness” rule.
+///
+/// Marks a permanent that came under the control of its controller since their most recent turn
+/// began.
+pub(crate) struct SummoningSick;
+
+/// 702.1. [...] In these cases, the object lists only the name of the ability as a “keyword”;
+///        sometimes reminder text summarizes the game rule.
+#[derive(Default)]
+pub(crate) struct Keywords(pub(crate) IndexSet<KeywordAbility>);
+
+/// 120.3e Damage dealt to a creature by a source with neither wither nor infect causes that much
+///        damage to be marked on that creature.
+#[derive(Copy, Clone, Default)]
+pub(crate) struct MarkedDamage(pub(crate) u64);
+
+/// 603.1. Triggered abilities have a trigger condition and an effect. [...]
+pub(crate) struct TriggeredAbilities(pub(crate) Vec<TriggeredAbility>);
+
+/// 602.1. Activated abilities have a cost and an effect. [...]
+pub(crate) struct ActivatedAbilities(pub(crate) Vec<ActivatedAbility>);
+
+/// 604.1. Static abilities do something all the time rather than being activated or triggered.
+///        [...]
+pub(crate) struct StaticAbilities(pub(crate) Vec<StaticAbility>);
+
+/// 110.5. A permanent’s status is its physical state. There are four status categories, each of
+///        which has two possible values: tapped/untapped, flipped/unflipped, face up/face down,
+///        and phased in/phased out. [...]
+///
+/// 110.5b Permanents enter the battlefield untapped, unflipped, face up, and phased in unless a
+///        spell or ability says otherwise.
+pub(crate) struct Tapped;
+
+/// 701.15a [...] “Regenerate [permanent]” means “The next time [permanent] would be destroyed this
+///         turn, instead remove all damage marked on it and its controller taps it. [...]”
+///
+/// The number of times a permanent is going to regenerate instead of being destroyed this turn.
+pub(crate) struct RegenerationShields(pub(crate) u32);
+
+/// The color chosen for a permanent as it entered the battlefield.
+pub(crate) struct ChosenColor(pub(crate) Color);
+
+/// The creature type chosen for a permanent as it entered the battlefield.
+pub(crate) struct ChosenCreatureType(pub(crate) CreatureType);
+
+/// The player chosen for a permanent as it entered the battlefield, e.g. an opponent.
+pub(crate) struct ChosenPlayer(pub(crate) PlayerId);
+
+/// 614.12a If a replacement effect that modifies how a permanent enters the battlefield requires a
+///         choice, that choice is made before the permanent enters the battlefield.
+///
+/// The decisions the controller of a permanent spell or a land being played made for the choices
+/// its abilities require as it enters the battlefield, in order.
+pub(crate) struct EntryDecisions(pub(crate) Vec<EntryDecision>);
+
+/// 702.26b If a permanent phases out, its status changes to “phased out.” Except for rules and
+///         effects that specifically mention phased-out permanents, a phased-out permanent is
+///         treated as though it does not exist. [...]
+pub(crate) struct PhasedOut {
+    /// 702.26g If an object would simultaneously phase out directly and indirectly, it just phases
+    ///         out indirectly.
+    ///
+    /// Whether the permanent phased out because another permanent it was attached to phased out.
+    pub(crate) indirectly: bool,
+}
+
+/// 301.5. Some artifacts have the subtype “Equipment.” An Equipment can be attached to a
+///        creature. [...]
+///
+/// 303.4. Some enchantments have the subtype “Aura.” An Aura enters the battlefield attached to
+///        an object or player. What an Aura is attached to is defined by its enchant keyword
+///        ability (see rule 702.5). Other effects can limit what a permanent can be enchanted by.
+///
+/// The permanent this permanent is attached to.
+pub(crate) struct AttachedTo(pub(crate) Entity);
+
+/// 506.4. A permanent that’s removed from combat stops being an attacking, blocking, blocked,
+///        and/or unblocked creature. [...]
+///
+/// Marks a creature that has been declared as an attacker and hasn't been removed from combat yet.
+pub(crate) struct Attacking;
@@ -0,0 +1,162 @@
+use crate::game::Game;
+
+/// The hashes of the state of a game after each action of a run, which are compared with those of
+/// a previously recorded run to detect nondeterminism such as iterating a `HashMap` where the
+/// order matters or using randomness that isn't seeded.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct StateTrace(Vec<u64>);
+
+impl StateTrace {
+    /// Records the current state of the game. In debug builds, the invariants of the game are
+    /// checked as well.
+    pub(crate) fn record(&mut self, game: &Game) {
+        game.debug_assert_invariants();
+        self.0.push(game.state_hash());
+    }
+
+    /// Returns the recorded hashes in the order they were recorded.
+    pub(crate) fn hashes(&self) -> &[u64] {
+        &self.0
+    }
+
+    /// Compares the trace with the hashes of a previous run and returns the index of the first
+    /// action after which the states differ, if any.
+    pub(crate) fn first_divergence(&self, expected: &[u64]) -> Option<usize> {
+        let diverged = self
+            .0
+            .iter()
+            .zip(expected)
+            .position(|(it, other)| it != other);
+        diverged
+            .or_else(|| (self.0.len() != expected.len()).then(|| self.0.len().min(expected.len())))
+    }
+}
+
+/// Returns a 64-bit FNV-1a hash of the text. Unlike the hashers of the standard library, the hash
+/// is guaranteed to be the same on every platform and with every version of Rust, so it can be
+/// recorded in test fixtures.
+pub(crate) fn stable_hash(text: &str) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    text.bytes().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    })
+}
+
+/// Returns the inputs recorded for the specified parser in the regression corpus, ordered by their
+/// file names. The corpus collects inputs that once crashed or nearly crashed a parser.
+#[cfg(test)]
+pub(crate) fn read_corpus(target: &str) -> Vec<String> {
+    read_corpus_bytes(target)
+        .into_iter()
+        .map(|it| String::from_utf8_lossy(&it).into_owned())
+        .collect()
+}
+
+/// Returns the raw inputs recorded for the specified fuzz target in the regression corpus, see
+/// [`read_corpus`].
+#[cfg(test)]
+pub(crate) fn read_corpus_bytes(target: &str) -> Vec<Vec<u8>> {
+    let directory = format!(
+        "{}/src/fixtures/corpus/{target}",
+        env!("CARGO_MANIFEST_DIR")
+    );
+    let mut paths = std::fs::read_dir(directory)
+        .expect("Could not read the corpus.")
+        .map(|it| it.expect("Could not read the corpus.").path())
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths
+        .into_iter()
+        .map(|it| std::fs::read(it).expect("Could not read the input."))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{
+        core::{Deck, ManaCost, ManaSymbol, Zone},
+        turn::Step,
+    };
+
+    #[test]
+    fn stable_hashes_match_the_reference_implementation() {
+        assert_eq!(stable_hash(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(stable_hash("a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(stable_hash("foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    /// Plays a scripted two-player game with a seed and records its state after every action.
+    fn scripted_trace() -> StateTrace {
+        let mut trace = StateTrace::default();
+        let mut game = Game::new(2);
+        game.set_seed(2022);
+        let players = game.players().iter().map(|it| it.id).collect::<Vec<_>>();
+        let decks = [
+            Deck::from(&[("Plains", 30), ("Soulmender", 30)]),
+            Deck::from(&[("Forest", 30), ("Llanowar Elves", 30)]),
+        ]
+        .into_iter()
+        .map(|it| it.expect("Could not build the deck."));
+        game.start(
+            &players
+                .iter()
+                .copied()
+                .zip(decks)
+                .collect::<HashMap<_, _>>(),
+        )
+        .expect("Could not start the game.");
+        trace.record(&game);
+
+        for &player in &players {
+            for _ in 0..7 {
+                game.draw_card(player).expect("Could not draw a card.");
+            }
+            trace.record(&game);
+        }
+        for turn in 0..4 {
+            let player = game.turn().active_player;
+            game.begin_step(Step::PrecombatMain)
+                .expect("Could not begin the step.");
+            trace.record(&game);
+            let land = game
+                .library(player)
+                .expect("Could not access the library.")
+                .cards()
+                .next()
+                .expect("The library is empty.");
+            game.move_object(land, Zone::Battlefield)
+                .expect("Could not move the object.");
+            trace.record(&game);
+            if turn >= 2 {
+                // Tapping whatever was put onto the battlefield is fine as long as it is the same
+                // in every run.
+                let _ =
+                    game.pay_mana_cost(player, &ManaCost(vec![ManaSymbol::Generic(1)]), None, None);
+                trace.record(&game);
+            }
+            game.next_turn().expect("Could not begin the next turn.");
+            trace.record(&game);
+        }
+        trace
+    }
+
+    #[test]
+    fn scripted_games_replay_the_recorded_trace() {
+        let expected =
+            serde_json::from_str::<Vec<u64>>(include_str!("./fixtures/consistency_trace.json"))
+                .expect("Could not parse the trace.");
+        let trace = scripted_trace();
+        assert_eq!(
+            trace.first_divergence(&expected),
+            None,
+            "{:?}",
+            trace.hashes()
+        );
+    }
+}
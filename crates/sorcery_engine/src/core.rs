@@ -1,27 +1,122 @@
-use std::collections::BTreeSet;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt,
+    sync::Arc,
+};
 
 #[cfg(test)]
 use derive_builder::Builder;
-use indexmap::IndexSet;
-use serde::{Deserialize, Serialize};
+use indexmap::{IndexMap, IndexSet};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-use crate::game::find_card_by_name;
+use crate::{
+    abilities::{Ability, ActivationCost, Effect},
+    error::EngineError,
+    game::{find_card_by_localized_name, find_card_by_name},
+};
 
 /// Opaque type to reference a player within a game.
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Hash)]
 pub struct PlayerId(pub(crate) u32);
 
+/// Opaque type to reference an object within a game. Unlike an entity of the underlying world, an
+/// id is never reused and remains meaningful outside of the engine.
+///
+/// 400.7. An object that moves from one zone to another becomes a new object with no memory of, or
+///        relation to, its previous existence. [...]
+///
+/// Accordingly, an object is issued a new id whenever it changes zones.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Hash)]
+pub struct ObjectId(pub(crate) u64);
+
 /// 201.2. A card’s name is always considered to be the English version of its name, regardless of
 ///        printed language.
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Name(pub(crate) String);
 
+/// A language cards are printed in. Clients may export decklists with the printed names of the
+/// cards, which are resolved to their English names.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Hash)]
+pub(crate) enum Locale {
+    English,
+    German,
+    French,
+    Italian,
+    Spanish,
+    Portuguese,
+    Japanese,
+    Korean,
+    Russian,
+    SimplifiedChinese,
+    TraditionalChinese,
+}
+
 /// 102.1. A player is one of the people in the game. The active player is the player whose turn it
 ///        is. The other players are nonactive players.
 pub(crate) struct Player {
     pub(crate) id: PlayerId,
-    pub(crate) name: String,
+    pub(crate) info: PlayerInfo,
     pub(crate) life: i64,
+    pub(crate) counters: Counters,
+    pub(crate) mana_pool: ManaPool,
+    /// 402.2. Each player has a maximum hand size, which is normally seven cards. A player may
+    ///        have any number of cards in their hand, but as part of their cleanup step, the
+    ///        player must discard excess cards down to the maximum hand size.
+    ///
+    /// The maximum hand size before continuous effects modify it.
+    pub(crate) maximum_hand_size: u64,
+    /// The designations the player has for the rest of the game in the order they got them.
+    pub(crate) designations: IndexSet<Designation>,
+    /// Whether the player has left the game, e.g. by conceding.
+    pub(crate) has_left: bool,
+    /// Whether the engine makes the decisions of the player that have a single legal option
+    /// without asking them, which saves a round trip to their client. This covers discarding a
+    /// single card or the whole hand and choosing the only legal targets of a triggered ability.
+    pub(crate) auto_choose_trivial: bool,
+    /// 704.5b If a player attempted to draw a card from a library with no cards in it since the
+    ///        last time state-based actions were checked, that player loses the game.
+    pub(crate) drew_from_empty_library: bool,
+}
+
+/// Describes a player joining a game, e.g. a user in a lobby.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerSpec {
+    /// The name the player is shown with.
+    pub(crate) name: String,
+    /// An opaque id of the player outside of the game, such as the id of their account.
+    pub(crate) external_id: String,
+    /// Whether the engine makes the decisions of the player that have a single legal option
+    /// without asking them.
+    #[serde(default)]
+    pub(crate) auto_choose_trivial: bool,
+}
+
+/// The identity of a player in a game.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerInfo {
+    /// The name the player is shown with.
+    pub(crate) name: String,
+    /// An opaque id of the player outside of the game, unless the game was created without one.
+    pub(crate) external_id: Option<String>,
+}
+
+impl PlayerInfo {
+    /// Returns the name the player is shown with.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// 104.1. A game ends immediately when a player wins, when the game is a draw, or when the game is
+///        restarted.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum GameOutcome {
+    /// 104.2a A player still in the game wins the game if that player’s opponents have all left
+    ///        the game. [...]
+    Win(PlayerId),
+    /// 104.4a If all the players remaining in a game lose simultaneously, the game is a draw.
+    Draw,
 }
 
 /// 105.1. There are five colors in the Magic game: white, blue, black, red, and green.
@@ -34,6 +129,79 @@ pub enum Color {
     Green,
 }
 
+impl Color {
+    /// The five colors in the order they appear on the color wheel.
+    pub(crate) const ALL: [Color; 5] = [
+        Color::White,
+        Color::Blue,
+        Color::Black,
+        Color::Red,
+        Color::Green,
+    ];
+
+    /// Returns the two colors adjacent to this one on the color wheel, starting with the next one
+    /// clockwise.
+    pub(crate) fn allies(self) -> [Color; 2] {
+        [self.offset(1), self.offset(4)]
+    }
+
+    /// Returns the two colors opposite of this one on the color wheel, starting with the next one
+    /// clockwise.
+    pub(crate) fn enemies(self) -> [Color; 2] {
+        [self.offset(2), self.offset(3)]
+    }
+
+    /// Returns the color the specified number of steps clockwise from this one on the color wheel.
+    fn offset(self, steps: usize) -> Color {
+        Self::ALL[(self as usize + steps) % Self::ALL.len()]
+    }
+
+    /// Returns the letter representing the color in mana symbols.
+    pub(crate) fn letter(self) -> char {
+        match self {
+            Color::White => 'W',
+            Color::Blue => 'U',
+            Color::Black => 'B',
+            Color::Red => 'R',
+            Color::Green => 'G',
+        }
+    }
+
+    /// Returns the color represented by the letter in mana symbols, if any.
+    pub(crate) fn from_letter(letter: char) -> Option<Color> {
+        Self::ALL.into_iter().find(|it| it.letter() == letter)
+    }
+}
+
+/// Returns the colors in the order they are printed in on cards, e.g. green before white but white
+/// before blue.
+///
+/// The order follows the color wheel, starting at the color that keeps the colors closest
+/// together: allied colors come in clockwise order while enemy colors skip a color, shards start
+/// at the first color of their arc, wedges alternate around the wheel and four colors start after
+/// the missing one.
+pub(crate) fn canonical_color_order(colors: &BTreeSet<Color>) -> Vec<Color> {
+    let clockwise_from = |start: Color, steps: &[usize]| {
+        steps.iter().map(|&it| start.offset(it)).collect::<Vec<_>>()
+    };
+    let contains_all = |order: &[Color]| order.iter().all(|it| colors.contains(it));
+    let patterns: &[&[usize]] = match colors.len() {
+        2 => &[&[0, 1], &[0, 2]],
+        3 => &[&[0, 1, 2], &[0, 2, 4]],
+        4 => &[&[0, 1, 2, 3]],
+        _ => return colors.iter().copied().collect(),
+    };
+    patterns
+        .iter()
+        .find_map(|steps| {
+            Color::ALL
+                .into_iter()
+                .map(|start| clockwise_from(start, steps))
+                .find(|it| contains_all(it))
+        })
+        .expect("Could not find an order for the colors.")
+}
+
 /// 105.2. An object can be one or more of the five colors, or it can be no color at all. An object
 ///        is the color or colors of the mana symbols in its mana cost, regardless of the color of
 ///        its frame. An object’s color or colors may also be defined by a color indicator or a
@@ -52,9 +220,53 @@ pub enum ColorIdentity {
     Colorless,
 }
 
+impl ColorIdentity {
+    /// Returns whether the specified color is one of the colors of this color identity.
+    pub(crate) fn contains(&self, color: Color) -> bool {
+        match self {
+            ColorIdentity::Monocolored(it) => *it == color,
+            ColorIdentity::Multicolored(colors) => colors.contains(&color),
+            ColorIdentity::Colorless => false,
+        }
+    }
+
+    /// Returns the colors of this color identity in the order they are printed in.
+    pub(crate) fn colors(&self) -> Vec<Color> {
+        match self {
+            ColorIdentity::Monocolored(it) => vec![*it],
+            ColorIdentity::Multicolored(colors) => canonical_color_order(colors),
+            ColorIdentity::Colorless => vec![],
+        }
+    }
+
+    /// Returns whether this color identity consists of exactly the specified colors.
+    pub(crate) fn is_exactly(&self, colors: &[Color]) -> bool {
+        let colors = colors.iter().copied().collect::<BTreeSet<_>>();
+        match self {
+            ColorIdentity::Monocolored(it) => colors.len() == 1 && colors.contains(it),
+            ColorIdentity::Multicolored(it) => *it == colors,
+            ColorIdentity::Colorless => colors.is_empty(),
+        }
+    }
+}
+
+impl fmt::Display for ColorIdentity {
+    /// Writes the letters of the colors in the order they are printed in, e.g. “GW”, or “C” for
+    /// colorless.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if *self == ColorIdentity::Colorless {
+            return f.write_str("C");
+        }
+        self.colors()
+            .into_iter()
+            .try_for_each(|it| write!(f, "{}", it.letter()))
+    }
+}
+
 /// 105.5. If an effect refers to a color pair, it means exactly two of the five colors. There are
 ///        ten color pairs: white and blue, white and black, blue and black, blue and red, black and
 ///        red, black and green, red and green, red and white, green and white, and green and blue.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub(crate) enum ColorPair {
     WhiteBlue,
     WhiteBlack,
@@ -68,9 +280,132 @@ pub(crate) enum ColorPair {
     GreenBlue,
 }
 
+impl ColorPair {
+    pub(crate) const ALL: [ColorPair; 10] = [
+        ColorPair::WhiteBlue,
+        ColorPair::WhiteBlack,
+        ColorPair::BlueBlack,
+        ColorPair::BlueRed,
+        ColorPair::BlackRed,
+        ColorPair::BlackGreen,
+        ColorPair::RedGreen,
+        ColorPair::RedWhite,
+        ColorPair::GreenWhite,
+        ColorPair::GreenBlue,
+    ];
+
+    /// Returns the two colors of the pair.
+    pub(crate) fn colors(self) -> [Color; 2] {
+        match self {
+            ColorPair::WhiteBlue => [Color::White, Color::Blue],
+            ColorPair::WhiteBlack => [Color::White, Color::Black],
+            ColorPair::BlueBlack => [Color::Blue, Color::Black],
+            ColorPair::BlueRed => [Color::Blue, Color::Red],
+            ColorPair::BlackRed => [Color::Black, Color::Red],
+            ColorPair::BlackGreen => [Color::Black, Color::Green],
+            ColorPair::RedGreen => [Color::Red, Color::Green],
+            ColorPair::RedWhite => [Color::Red, Color::White],
+            ColorPair::GreenWhite => [Color::Green, Color::White],
+            ColorPair::GreenBlue => [Color::Green, Color::Blue],
+        }
+    }
+
+    /// Returns whether the two colors of the pair are adjacent on the color wheel.
+    pub(crate) fn is_allied(self) -> bool {
+        let [first, second] = self.colors();
+        first.allies().contains(&second)
+    }
+}
+
+/// A combination of three colors. A shard consists of a color and both of its allies, while a
+/// wedge consists of a color and both of its enemies.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ColorTriple {
+    Bant,
+    Esper,
+    Grixis,
+    Jund,
+    Naya,
+    Abzan,
+    Jeskai,
+    Sultai,
+    Mardu,
+    Temur,
+}
+
+impl ColorTriple {
+    pub(crate) const ALL: [ColorTriple; 10] = [
+        ColorTriple::Bant,
+        ColorTriple::Esper,
+        ColorTriple::Grixis,
+        ColorTriple::Jund,
+        ColorTriple::Naya,
+        ColorTriple::Abzan,
+        ColorTriple::Jeskai,
+        ColorTriple::Sultai,
+        ColorTriple::Mardu,
+        ColorTriple::Temur,
+    ];
+
+    /// Returns the three colors of the triple, starting with the color the others are centered
+    /// around.
+    pub(crate) fn colors(self) -> [Color; 3] {
+        let (center, others) = match self {
+            ColorTriple::Bant => (Color::White, Color::White.allies()),
+            ColorTriple::Esper => (Color::Blue, Color::Blue.allies()),
+            ColorTriple::Grixis => (Color::Black, Color::Black.allies()),
+            ColorTriple::Jund => (Color::Red, Color::Red.allies()),
+            ColorTriple::Naya => (Color::Green, Color::Green.allies()),
+            ColorTriple::Abzan => (Color::Black, Color::Black.enemies()),
+            ColorTriple::Jeskai => (Color::Red, Color::Red.enemies()),
+            ColorTriple::Sultai => (Color::Green, Color::Green.enemies()),
+            ColorTriple::Mardu => (Color::White, Color::White.enemies()),
+            ColorTriple::Temur => (Color::Blue, Color::Blue.enemies()),
+        };
+        [center, others[0], others[1]]
+    }
+
+    /// Returns whether the triple is a shard, i.e. a color and both of its allies.
+    pub(crate) fn is_shard(self) -> bool {
+        let [center, first, second] = self.colors();
+        center.allies() == [first, second]
+    }
+}
+
+impl TryFrom<&BTreeSet<Color>> for ColorTriple {
+    type Error = ();
+
+    /// Returns the triple consisting of exactly the specified colors.
+    fn try_from(colors: &BTreeSet<Color>) -> Result<Self, Self::Error> {
+        Self::ALL
+            .into_iter()
+            .find(|it| it.colors().iter().copied().collect::<BTreeSet<_>>() == *colors)
+            .ok_or(())
+    }
+}
+
+impl fmt::Display for ColorTriple {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ColorTriple::Bant => "Bant",
+            ColorTriple::Esper => "Esper",
+            ColorTriple::Grixis => "Grixis",
+            ColorTriple::Jund => "Jund",
+            ColorTriple::Naya => "Naya",
+            ColorTriple::Abzan => "Abzan",
+            ColorTriple::Jeskai => "Jeskai",
+            ColorTriple::Sultai => "Sultai",
+            ColorTriple::Mardu => "Mardu",
+            ColorTriple::Temur => "Temur",
+        };
+        f.write_str(name)
+    }
+}
+
 /// 106.1. Mana is the primary resource in the game. Players spend mana to pay costs, usually when
 ///        casting spells and activating abilities.
-pub(crate) enum Mana {
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Mana {
     /// 106.1a There are five colors of mana: white, blue, black, red, and green.
     Monocolored(Color),
     /// 106.1b There are six types of mana: white, blue, black, red, green, and colorless.
@@ -83,10 +418,42 @@ pub(crate) enum Mana {
 ///        and the player is said to lose this mana. Cards with abilities that produce mana or refer
 ///        to unspent mana have received errata in the Oracle(TM) card reference to no longer
 ///        explicitly refer to the mana pool.
+#[derive(Default)]
 pub(crate) struct ManaPool {
     mana: Vec<Mana>,
 }
 
+impl ManaPool {
+    /// Returns the amount of the specified mana in the pool.
+    pub(crate) fn amount(&self, mana: Mana) -> usize {
+        self.mana.iter().filter(|&&it| it == mana).count()
+    }
+
+    /// Returns all mana in the pool in the order it was added.
+    pub(crate) fn mana(&self) -> &[Mana] {
+        &self.mana
+    }
+
+    /// Removes a single mana of the specified type from the pool and returns whether there was any.
+    pub(crate) fn remove(&mut self, mana: Mana) -> bool {
+        let Some(index) = self.mana.iter().position(|&it| it == mana) else {
+            return false;
+        };
+        self.mana.remove(index);
+        true
+    }
+
+    pub(crate) fn add(&mut self, mana: Mana) {
+        self.mana.push(mana);
+    }
+
+    /// 500.4. When a step or phase ends, any unused mana left in a player’s mana pool empties.
+    ///        This turn-based action doesn’t use the stack.
+    pub(crate) fn empty(&mut self) {
+        self.mana.clear();
+    }
+}
+
 /// 107.4. The mana symbols are {W}, {U}, {B}, {R}, {G}, and {C}; the numerical symbols {0}, {1},
 ///        {2}, {3}, {4}, and so on; the variable symbol {X}; the hybrid symbols {W/U}, {W/B},
 ///        {U/B}, {U/R}, {B/R}, {B/G}, {R/G}, {R/W}, {G/W}, and {G/U}; the monocolored hybrid
@@ -108,17 +475,20 @@ pub(crate) enum ManaSymbol {
     /// 107.4c The colorless mana symbol {C} is used to represent one colorless mana, and also to
     ///        represent a cost that can be paid only with one colorless mana.
     Colorless,
+    /// 107.4e Hybrid mana symbols are also colored mana symbols. Each one represents a cost that
+    ///        can be paid in one of two ways, as represented by the two halves of the symbol. A
+    ///        hybrid symbol such as {W/U} can be paid with either white or blue mana, and a
+    ///        monocolored hybrid symbol such as {2/B} can be paid with either one black mana or
+    ///        two mana of any type. A hybrid mana symbol is all of its component colors.
+    ///
+    /// Example: {G/W}{G/W} can be paid by spending {G}{G}, {G}{W}, or {W}{W}.
+    Hybrid(Color, Color),
+    /// A monocolored hybrid symbol such as {2/B}, which can be paid with either one mana of its
+    /// color or two mana of any type, see rule 107.4e.
+    MonocoloredHybrid(Color),
     // 107.4d The symbol {0} represents zero mana and is used as a placeholder for a cost that can
     //        be paid with no resources. (See rule 118.5.)
     //
-    // 107.4e Hybrid mana symbols are also colored mana symbols. Each one represents a cost that can
-    //        be paid in one of two ways, as represented by the two halves of the symbol. A hybrid
-    //        symbol such as {W/U} can be paid with either white or blue mana, and a monocolored
-    //        hybrid symbol such as {2/B} can be paid with either one black mana or two mana of any
-    //        type. A hybrid mana symbol is all of its component colors.
-    //
-    // Example: {G/W}{G/W} can be paid by spending {G}{G}, {G}{W}, or {W}{W}.
-    //
     // 107.4f Phyrexian mana symbols are colored mana symbols: {W/P} is white, {U/P} is blue, {B/P}
     //        is black, {R/P} is red, and {G/P} is green. A Phyrexian mana symbol represents a cost
     //        that can be paid either with one mana of its color or by paying 2 life. There are also
@@ -139,22 +509,191 @@ pub(crate) enum ManaSymbol {
     //        is neither a color nor a type of mana.
 }
 
+impl fmt::Display for ManaSymbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ManaSymbol::Colored(color) => write!(f, "{{{}}}", color.letter()),
+            ManaSymbol::Generic(amount) => write!(f, "{{{amount}}}"),
+            ManaSymbol::Variable => f.write_str("{X}"),
+            ManaSymbol::Colorless => f.write_str("{C}"),
+            ManaSymbol::Hybrid(first, second) => {
+                write!(f, "{{{}/{}}}", first.letter(), second.letter())
+            }
+            ManaSymbol::MonocoloredHybrid(color) => write!(f, "{{2/{}}}", color.letter()),
+        }
+    }
+}
+
 /// 202.1. A card’s mana cost is indicated by mana symbols near the top of the card. (See rule
 ///        107.4.) On most cards, these symbols are printed in the upper right corner. Some cards
 ///        from the Future Sight set have alternate frames in which the mana symbols appear to the
 ///        left of the illustration.
-#[derive(Clone, Serialize, Deserialize)]
-pub struct ManaCost(pub(crate) IndexSet<ManaSymbol>);
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Hash)]
+pub struct ManaCost(pub(crate) Vec<ManaSymbol>);
+
+impl ManaCost {
+    /// 202.3. The mana value of an object is a number equal to the total amount of mana in its
+    ///        mana cost, regardless of color.
+    ///
+    /// 202.3e When calculating the mana value of an object with an {X} in its mana cost, X is
+    ///        treated as 0 while the object is not on the stack, and X is treated as the number
+    ///        chosen for it while the object is on the stack.
+    ///
+    /// 202.3f When calculating the mana value of an object with a hybrid mana symbol in its mana
+    ///        cost, use the largest component of each hybrid symbol.
+    pub(crate) fn mana_value(&self) -> u64 {
+        self.0
+            .iter()
+            .map(|it| match it {
+                ManaSymbol::Colored(_) | ManaSymbol::Colorless | ManaSymbol::Hybrid(..) => 1,
+                ManaSymbol::MonocoloredHybrid(_) => 2,
+                ManaSymbol::Generic(amount) => *amount,
+                ManaSymbol::Variable => 0,
+            })
+            .sum()
+    }
+
+    /// 107.3a If a spell or activated ability has a mana cost, alternative cost, additional cost,
+    ///        and/or activation cost with an {X}, [-X], or X in it, and the value of X isn’t
+    ///        defined by the text of that spell or ability, the controller of that spell or
+    ///        ability chooses and announces the value of X as part of casting the spell or
+    ///        activating the ability. [...]
+    ///
+    /// Returns the cost with each {X} replaced by generic mana of the chosen value.
+    pub(crate) fn with_x(&self, x: u64) -> Self {
+        Self(
+            self.0
+                .iter()
+                .map(|&it| match it {
+                    ManaSymbol::Variable => ManaSymbol::Generic(x),
+                    it => it,
+                })
+                .collect(),
+        )
+    }
+
+    /// Parses a mana cost written as mana symbols in braces, e.g. “{X}{2}{G/W}{2/B}{G}”, which is the
+    /// format it is displayed in. Returns `None` if the text contains anything else.
+    pub(crate) fn parse(text: &str) -> Option<Self> {
+        let mut symbols = Vec::new();
+        let mut rest = text;
+        while !rest.is_empty() {
+            let (symbol, remainder) = rest.strip_prefix('{')?.split_once('}')?;
+            let letter = |text: &str| {
+                let mut chars = text.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(letter), None) => Color::from_letter(letter),
+                    _ => None,
+                }
+            };
+            symbols.push(match symbol {
+                "X" => ManaSymbol::Variable,
+                "C" => ManaSymbol::Colorless,
+                _ => match symbol.split_once('/') {
+                    Some(("2", color)) => ManaSymbol::MonocoloredHybrid(letter(color)?),
+                    Some((first, second)) => ManaSymbol::Hybrid(letter(first)?, letter(second)?),
+                    None => match letter(symbol) {
+                        Some(color) => ManaSymbol::Colored(color),
+                        None => ManaSymbol::Generic(symbol.parse().ok()?),
+                    },
+                },
+            });
+            rest = remainder;
+        }
+        Some(Self(symbols))
+    }
+
+    /// Returns the same cost with its symbols in the order they are printed in: {X}, generic mana
+    /// combined into a single symbol, colorless mana, hybrid symbols, monocolored hybrid symbols and
+    /// finally colored symbols grouped by color in the canonical color order, e.g. {X}{2}{G}{G}{W}.
+    pub(crate) fn normalized(&self) -> Self {
+        let mut symbols = Vec::new();
+        let count = |symbol| self.0.iter().filter(|&&it| it == symbol).count();
+        symbols.extend(vec![ManaSymbol::Variable; count(ManaSymbol::Variable)]);
+        let generic = self
+            .0
+            .iter()
+            .map(|it| match it {
+                ManaSymbol::Generic(amount) => *amount,
+                _ => 0,
+            })
+            .sum::<u64>();
+        if generic > 0 {
+            symbols.push(ManaSymbol::Generic(generic));
+        }
+        symbols.extend(vec![ManaSymbol::Colorless; count(ManaSymbol::Colorless)]);
+
+        let mut hybrid = self
+            .0
+            .iter()
+            .filter_map(|it| match *it {
+                ManaSymbol::Hybrid(first, second) => {
+                    let order = canonical_color_order(&[first, second].into());
+                    Some(match order[..] {
+                        [first, second] => ManaSymbol::Hybrid(first, second),
+                        _ => *it,
+                    })
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let pair_index = |symbol: &ManaSymbol| {
+            ColorPair::ALL.iter().position(|pair| match *symbol {
+                ManaSymbol::Hybrid(first, second) => pair.colors() == [first, second],
+                _ => false,
+            })
+        };
+        hybrid.sort_by_key(pair_index);
+        symbols.extend(hybrid);
+
+        let monocolored_hybrid = self
+            .0
+            .iter()
+            .filter_map(|it| match it {
+                ManaSymbol::MonocoloredHybrid(color) => Some(*color),
+                _ => None,
+            })
+            .collect::<BTreeSet<_>>();
+        for color in canonical_color_order(&monocolored_hybrid) {
+            symbols.extend(vec![
+                ManaSymbol::MonocoloredHybrid(color);
+                count(ManaSymbol::MonocoloredHybrid(color))
+            ]);
+        }
+
+        let colors = self
+            .0
+            .iter()
+            .filter_map(|it| match it {
+                ManaSymbol::Colored(color) => Some(*color),
+                _ => None,
+            })
+            .collect::<BTreeSet<_>>();
+        for color in canonical_color_order(&colors) {
+            symbols.extend(vec![
+                ManaSymbol::Colored(color);
+                count(ManaSymbol::Colored(color))
+            ]);
+        }
+        Self(symbols)
+    }
+}
+
+impl fmt::Display for ManaCost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|it| write!(f, "{it}"))
+    }
+}
 
 /// 207.1. The text box is printed on the lower half of the card. It usually contains rules text
 ///        defining the card’s abilities.
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RulesText(pub(crate) String);
 
 /// 209.1. Each planeswalker card has a loyalty number printed in its lower right corner. This
 ///        indicates its loyalty while it’s not on the battlefield, and it also indicates that the
 ///        planeswalker enters the battlefield with that many loyalty counters on it.
-#[derive(Copy, Clone, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct Loyalty(pub(crate) u64);
 
 /// 212.1. Each card features text printed below the text box that has no effect on game play. Not
@@ -164,9 +703,13 @@ pub struct Loyalty(pub(crate) u64);
 ///        [card number]/[total cards in the set] or simply [card number]. Some cards, such as
 ///        unique cards in Planeswalker Decks, have card numbers that exceed the listed total number
 ///        of cards.
-#[derive(Copy, Clone, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct CollectorNumber(pub(crate) u64);
 
+/// The largest amount of a single card a line of a decklist may contain. No deck needs more copies
+/// of a card, and larger amounts would only exhaust the memory of the server.
+const MAX_DECKLIST_AMOUNT: u64 = 1000;
+
 /// 100.2. To play, each player needs their own deck of traditional Magic cards, small items to
 ///        represent any tokens and counters, and some way to clearly track life totals.
 ///
@@ -182,29 +725,397 @@ pub struct CollectorNumber(pub(crate) u64);
 ///
 /// 100.2c Commander decks are subject to additional deckbuilding restrictions and requirements. See
 ///        rule 903, “Commander,” for details.
-pub struct Deck<'a>(Vec<&'a Card>);
+///
+/// The deck keeps the printing of each of its cards, so the objects of a game are spawned from the
+/// exact printings that were chosen for it.
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct Deck(Vec<Arc<Card>>);
 
-impl<'a> Deck<'a> {
+impl Deck {
     /// Creates a new deck using the specified decklist.
-    pub(crate) fn from(decklist: &[(&'a str, u64)]) -> Self {
-        Self(
-            decklist
-                .iter()
-                .flat_map(|&(name, amount)| {
-                    (0..amount).map(move |_| {
-                        find_card_by_name(name).unwrap_or_else(|| {
-                            panic!("Cannot find card with name {} in database.", name)
-                        })
-                    })
-                })
-                .collect(),
-        )
+    pub(crate) fn from(decklist: &[(&str, u64)]) -> Result<Self, EngineError> {
+        let mut cards = Vec::new();
+        for &(name, amount) in decklist {
+            let card = find_card_by_name(name)?;
+            cards.extend((0..amount).map(|_| Arc::clone(card)));
+        }
+        Ok(Self(cards))
+    }
+
+    /// Creates a new deck from a decklist in the usual text format, which has a line with the
+    /// amount and the name of each card, e.g. “4 Llanowar Elves”. Empty lines and lines starting
+    /// with `#` are ignored.
+    ///
+    /// # Errors
+    /// Fails if a line isn't of that form or names a card that isn't in the card database.
+    pub fn parse(decklist: &str) -> Result<Self, EngineError> {
+        Self::parse_localized(decklist, Locale::English)
+    }
+
+    /// Creates a new deck from a decklist in the usual text format whose card names may be printed
+    /// in the specified language. Names that aren't known in that language are looked up in
+    /// English instead.
+    pub(crate) fn parse_localized(decklist: &str, locale: Locale) -> Result<Self, EngineError> {
+        let mut entries = Vec::new();
+        for (number, line) in (1..).zip(decklist.lines()) {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let entry = line.split_once(' ').and_then(|(amount, name)| {
+                Some((
+                    name.trim(),
+                    amount.trim_end_matches('x').parse::<u64>().ok()?,
+                ))
+            });
+            let Some(entry) = entry else {
+                return Err(EngineError::InvalidDeck(format!(
+                    "Line {number} is not of the form “[amount] [card name]”."
+                )));
+            };
+            if entry.1 > MAX_DECKLIST_AMOUNT {
+                return Err(EngineError::InvalidDeck(format!(
+                    "Line {number} contains more than {MAX_DECKLIST_AMOUNT} copies of a card."
+                )));
+            }
+            entries.push(entry);
+        }
+
+        let mut cards = Vec::new();
+        for (name, amount) in entries {
+            let card =
+                find_card_by_localized_name(locale, name).or_else(|_| find_card_by_name(name))?;
+            cards.extend((0..amount).map(|_| Arc::clone(card)));
+        }
+        Ok(Self(cards))
+    }
+
+    /// Creates a new deck consisting of the specified cards.
+    pub(crate) fn from_cards(cards: Vec<Arc<Card>>) -> Self {
+        Self(cards)
     }
 
     /// Returns a slice of all cards in the deck.
-    pub(crate) fn cards(&self) -> &[&Card] {
+    pub(crate) fn cards(&self) -> &[Arc<Card>] {
         &self.0
     }
+
+    /// Creates an empty deck, which cards can be added to one entry at a time.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the specified number of copies of a printing of a card. The copies join an entry of the
+    /// same printing if the deck already contains one.
+    pub fn add(&mut self, card: &Arc<Card>, count: u32) {
+        let position = self
+            .0
+            .iter()
+            .rposition(|it| it.is_same_printing(card))
+            .map_or(self.0.len(), |it| it + 1);
+        self.0
+            .splice(position..position, (0..count).map(|_| Arc::clone(card)));
+    }
+
+    /// Removes up to the specified number of copies of the card with the specified name, whatever
+    /// their printings, starting with the copies added last. Returns the number of removed copies.
+    pub fn remove(&mut self, name: &str, count: u32) -> u32 {
+        let mut removed = 0;
+        while removed < count {
+            let Some(index) = self.0.iter().rposition(|it| it.name.0 == name) else {
+                break;
+            };
+            self.0.remove(index);
+            removed += 1;
+        }
+        removed
+    }
+
+    /// Replaces every copy of the card with the name of the specified printing by that printing,
+    /// e.g. to play basic lands from a specific set. Returns the number of replaced copies.
+    pub fn replace_printing(&mut self, printing: &Arc<Card>) -> u32 {
+        let mut replaced = 0;
+        for card in self.0.iter_mut().filter(|it| it.name.0 == printing.name.0) {
+            *card = Arc::clone(printing);
+            replaced += 1;
+        }
+        replaced
+    }
+
+    /// Returns the number of copies of the card with the specified name, whatever their printings.
+    #[must_use]
+    pub fn count_of(&self, name: &str) -> u32 {
+        self.0.iter().filter(|it| it.name.0 == name).count() as u32
+    }
+
+    /// Returns the total number of cards in the deck.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns whether the deck contains no cards.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns an iterator over the entries of the deck, which are its printings along with the
+    /// number of their copies in the order they were first added.
+    pub fn iter(&self) -> impl Iterator<Item = (&Arc<Card>, u32)> {
+        self.into_iter()
+    }
+
+    /// Returns statistics about the cards in the deck.
+    pub(crate) fn statistics(&self) -> DeckStatistics {
+        let mut mana_curve = BTreeMap::new();
+        let mut color_symbols = BTreeMap::new();
+        let (mut lands, mut creatures, mut total_mana_value) = (0, 0, 0);
+        for card in &self.0 {
+            let card_type = &card.type_line.card_type;
+            if card_type.contains(&CardType::Creature) {
+                creatures += 1;
+            }
+            if card_type.contains(&CardType::Land) {
+                lands += 1;
+            } else {
+                *mana_curve.entry(card.mana_value()).or_default() += 1;
+                total_mana_value += card.mana_value();
+            }
+            for symbol in card.mana_cost.iter().flat_map(|it| &it.0) {
+                if let ManaSymbol::Colored(color) = symbol {
+                    *color_symbols.entry(*color).or_default() += 1;
+                }
+            }
+        }
+        let spells = self.0.len() - lands;
+        DeckStatistics {
+            card_count: self.0.len(),
+            mana_curve,
+            color_symbols,
+            lands,
+            creatures,
+            spells,
+            average_mana_value: if spells == 0 {
+                0.0
+            } else {
+                total_mana_value as f64 / spells as f64
+            },
+        }
+    }
+
+    /// Returns whether the lands of the deck can produce the mana to pay for the cost, assuming
+    /// each land is tapped for a single mana.
+    pub(crate) fn castability(&self, cost: &ManaCost) -> CastabilityReport {
+        let lands = self
+            .0
+            .iter()
+            .filter(|it| it.type_line.card_type.contains(&CardType::Land))
+            .map(|it| it.produced_mana())
+            .collect::<Vec<_>>();
+        let count_sources = |mana| lands.iter().filter(|it| it.contains(&mana)).count();
+
+        let mut required = BTreeMap::<Color, usize>::new();
+        let mut sources = BTreeMap::new();
+        for symbol in &cost.0 {
+            match *symbol {
+                ManaSymbol::Colored(color) => {
+                    *required.entry(color).or_default() += 1;
+                    sources.insert(color, count_sources(Mana::Monocolored(color)));
+                }
+                ManaSymbol::MonocoloredHybrid(color) => {
+                    sources.insert(color, count_sources(Mana::Monocolored(color)));
+                }
+                ManaSymbol::Hybrid(first, second) => {
+                    for color in [first, second] {
+                        sources.insert(color, count_sources(Mana::Monocolored(color)));
+                    }
+                }
+                ManaSymbol::Generic(_) | ManaSymbol::Variable | ManaSymbol::Colorless => {}
+            }
+        }
+
+        let mut unproducible = Vec::new();
+        for symbol in &cost.0 {
+            let is_producible = match *symbol {
+                ManaSymbol::Colored(color) => sources[&color] >= required[&color],
+                ManaSymbol::Hybrid(first, second) => sources[&first] + sources[&second] > 0,
+                ManaSymbol::Colorless => count_sources(Mana::Colorless) > 0,
+                // Two mana of any type can pay for a monocolored hybrid symbol instead.
+                ManaSymbol::Generic(_)
+                | ManaSymbol::Variable
+                | ManaSymbol::MonocoloredHybrid(_) => true,
+            };
+            if !is_producible && !unproducible.contains(symbol) {
+                unproducible.push(*symbol);
+            }
+        }
+        CastabilityReport {
+            sources,
+            unproducible,
+            enough_lands: lands.len() as u64 >= cost.mana_value(),
+        }
+    }
+
+    /// Returns the cards in the deck whose mana costs can't be paid using the lands of the deck.
+    pub(crate) fn uncastable_cards(&self) -> Vec<&Arc<Card>> {
+        self.0
+            .iter()
+            .filter(|card| {
+                card.mana_cost
+                    .as_ref()
+                    .is_some_and(|it| !self.castability(it).is_castable())
+            })
+            .collect()
+    }
+
+    /// Returns the hypergeometric probability of having at least one card matching the predicate
+    /// among the specified number of cards from the top of the shuffled deck, e.g. the opening
+    /// hand.
+    pub(crate) fn probability_drawn(
+        &self,
+        predicate: impl Fn(&Card) -> bool,
+        cards_seen: usize,
+    ) -> f64 {
+        let total = self.0.len();
+        let misses = self.0.iter().filter(|it| !predicate(it)).count();
+        // The probability of seeing only cards that don't match, one card at a time.
+        let none_drawn = (0..cards_seen.min(total))
+            .map(|drawn| misses.saturating_sub(drawn) as f64 / (total - drawn) as f64)
+            .product::<f64>();
+        1.0 - none_drawn
+    }
+
+    /// Validates that every card in the deck may be played in the specified format.
+    ///
+    /// # Remarks
+    /// Cards without legality data for the format are assumed to be legal.
+    pub(crate) fn validate(&self, format: Format) -> Result<(), DeckError> {
+        let mut restricted = IndexSet::new();
+        for card in &self.0 {
+            let name = &card.name.0;
+            match card.legality(format) {
+                Legality::Legal => {}
+                Legality::Banned => {
+                    return Err(DeckError::Banned {
+                        name: name.clone(),
+                        format,
+                    })
+                }
+                Legality::NotLegal => {
+                    return Err(DeckError::NotLegal {
+                        name: name.clone(),
+                        format,
+                    })
+                }
+                // A restricted card may only be included as a single copy.
+                Legality::Restricted => {
+                    if !restricted.insert(name) {
+                        return Err(DeckError::Restricted {
+                            name: name.clone(),
+                            format,
+                        });
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> IntoIterator for &'a Deck {
+    type Item = (&'a Arc<Card>, u32);
+    type IntoIter = std::vec::IntoIter<(&'a Arc<Card>, u32)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let mut entries = Vec::<(&Arc<Card>, u32)>::new();
+        for card in &self.0 {
+            match entries.iter_mut().find(|(it, _)| it.is_same_printing(card)) {
+                Some((_, count)) => *count += 1,
+                None => entries.push((card, 1)),
+            }
+        }
+        entries.into_iter()
+    }
+}
+
+/// Statistics about the cards in a deck that help with deckbuilding.
+#[derive(Debug, PartialEq)]
+pub(crate) struct DeckStatistics {
+    pub(crate) card_count: usize,
+    /// The number of nonland cards for each mana value.
+    pub(crate) mana_curve: BTreeMap<u64, usize>,
+    /// The number of colored mana symbols of each color in the mana costs of all cards.
+    pub(crate) color_symbols: BTreeMap<Color, usize>,
+    pub(crate) lands: usize,
+    pub(crate) creatures: usize,
+    /// The number of nonland cards.
+    pub(crate) spells: usize,
+    /// The average mana value of all nonland cards.
+    pub(crate) average_mana_value: f64,
+}
+
+/// Whether the lands of a deck can produce the mana to pay for a cost.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct CastabilityReport {
+    /// The number of lands that can produce each of the colors in the cost.
+    pub(crate) sources: BTreeMap<Color, usize>,
+    /// The symbols of the cost that the lands can't produce enough mana for.
+    pub(crate) unproducible: Vec<ManaSymbol>,
+    /// Whether the deck contains at least as many lands as the mana value of the cost.
+    pub(crate) enough_lands: bool,
+}
+
+impl CastabilityReport {
+    /// Returns whether the cost can be paid using the lands of the deck.
+    pub(crate) fn is_castable(&self) -> bool {
+        self.unproducible.is_empty() && self.enough_lands
+    }
+}
+
+/// A reason for a deck not being valid in a format.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum DeckError {
+    Banned { name: String, format: Format },
+    NotLegal { name: String, format: Format },
+    Restricted { name: String, format: Format },
+}
+
+impl fmt::Display for DeckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Banned { name, format } => write!(f, "{name} is banned in {format:?}."),
+            Self::NotLegal { name, format } => write!(f, "{name} is not legal in {format:?}."),
+            Self::Restricted { name, format } => {
+                write!(f, "{name} is restricted to a single copy in {format:?}.")
+            }
+        }
+    }
+}
+
+/// 100.6. Most Magic tournaments (organized play activities where players compete against other
+///        players to win prizes) have additional rules covered in the Magic: The Gathering
+///        Tournament Rules (found at WPN.Wizards.com/en/resources/rules-documents). These rules
+///        may limit the use of some cards, including barring all cards from some older sets.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Hash)]
+pub enum Format {
+    Standard,
+    Pioneer,
+    Modern,
+    Legacy,
+    Vintage,
+    Commander,
+    Pauper,
+}
+
+/// Whether a card may be played in a format.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum Legality {
+    Legal,
+    Banned,
+    Restricted,
+    NotLegal,
 }
 
 /// 200.1. The parts of a card are name, mana cost, illustration, color indicator, type line,
@@ -214,7 +1125,7 @@ impl<'a> Deck<'a> {
 ///
 /// 200.2. Some parts of a card are also characteristics of the object that has them. See rule
 ///        109.3.
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[cfg_attr(
     test,
     derive(Builder),
@@ -260,6 +1171,46 @@ pub struct Card {
     ///        unique cards in Planeswalker Decks, have card numbers that exceed the listed total
     ///        number of cards.
     pub(crate) collector_number: CollectorNumber,
+    /// 113.1a An ability can be a characteristic an object has that lets it affect the game. An
+    ///        object’s abilities are defined by that object’s rules text or by the effect that
+    ///        created it. [...]
+    #[serde(default)]
+    pub(crate) abilities: Vec<Ability>,
+    /// Whether the card may be played in the respective formats.
+    #[serde(default)]
+    pub(crate) legalities: HashMap<Format, Legality>,
+    /// The names the card is printed with in languages other than English.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub(crate) localized_names: HashMap<Locale, String>,
+    /// Information about the card that has no effect on game play but is passed through for tools
+    /// and user interfaces.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) metadata: Option<CardMetadata>,
+    /// 712.1. A double-faced card has a Magic card face on each side rather than a Magic card face
+    ///        on one side and a Magic card back on the other. [...]
+    ///
+    /// The back face of the card if it is a transforming double-faced card.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) back_face: Option<Box<Card>>,
+}
+
+/// Information about a card that the engine doesn't use itself, such as where to find an image of
+/// it. Unknown information is omitted when serialized.
+#[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CardMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) scryfall_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) multiverse_ids: Vec<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) image_uri: Option<String>,
+    /// 212.1b The illustration credit for a card follows the paintbrush icon or, on older cards,
+    ///        the abbreviation “Illus.” [...]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) artist: Option<String>,
+    /// The italic text following the rules text in the text box.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) flavor_text: Option<String>,
 }
 
 #[cfg(test)]
@@ -281,10 +1232,15 @@ impl Default for Card {
                 set: "TEST".into(),
                 rarity: Rarity::Common,
             },
-            rules_text: RulesText("".into()),
+            rules_text: RulesText(String::new()),
             pt: None,
             loyalty: None,
             collector_number: CollectorNumber(0),
+            abilities: Vec::new(),
+            legalities: HashMap::new(),
+            localized_names: HashMap::new(),
+            metadata: None,
+            back_face: None,
         }
     }
 }
@@ -296,6 +1252,55 @@ impl Card {
         CardBuilder::default()
     }
 
+    /// Returns whether both cards are the same printing, which is identified by the name, the set
+    /// and the collector number.
+    pub(crate) fn is_same_printing(&self, other: &Card) -> bool {
+        self.name.0 == other.name.0
+            && self.expansion_symbol.set == other.expansion_symbol.set
+            && self.collector_number.0 == other.collector_number.0
+    }
+
+    /// Returns the legality of the card in the specified format, assuming it is legal if there is no
+    /// data for the format.
+    pub(crate) fn legality(&self, format: Format) -> Legality {
+        self.legalities
+            .get(&format)
+            .copied()
+            .unwrap_or(Legality::Legal)
+    }
+
+    /// Returns the kinds of mana the card can be tapped for once it is on the battlefield, including
+    /// the intrinsic mana abilities of its basic land types.
+    pub(crate) fn produced_mana(&self) -> Vec<Mana> {
+        let mut produced = Vec::new();
+        if self.type_line.card_type.contains(&CardType::Land) {
+            // 305.6. [...] An object with the land card type and a basic land type has the
+            //        intrinsic ability “{T}: Add [mana symbol],” [...]
+            for subtype in &self.type_line.subtype {
+                if let Subtype::Land(LandType::Basic(land_type)) = subtype {
+                    produced.push(Mana::Monocolored(land_type.color()));
+                }
+            }
+        }
+        for ability in &self.abilities {
+            if let Ability::Activated(ability) = ability {
+                if let ([ActivationCost::Tap], [Effect::AddMana(mana)]) =
+                    (&ability.cost[..], &ability.effects[..])
+                {
+                    if !produced.contains(mana) {
+                        produced.push(*mana);
+                    }
+                }
+            }
+        }
+        produced
+    }
+
+    /// 202.3a The mana value of an object with no mana cost is 0, unless [...]
+    pub(crate) fn mana_value(&self) -> u64 {
+        self.mana_cost.as_ref().map_or(0, ManaCost::mana_value)
+    }
+
     /// 202.2. An object is the color or colors of the mana symbols in its mana cost, regardless of
     ///        the color of its frame.
     pub(crate) fn color(&self) -> ColorIdentity {
@@ -308,10 +1313,14 @@ impl Card {
             .as_ref()
             .map(|it| {
                 it.0.iter().fold(BTreeSet::new(), |mut colors, symbol| {
-                    // TODO: Extend this to a match clause once hybrid mana symbols and other have
-                    //  been implemented.
-                    if let ManaSymbol::Colored(color) = symbol {
-                        colors.insert(*color);
+                    match *symbol {
+                        ManaSymbol::Colored(color) | ManaSymbol::MonocoloredHybrid(color) => {
+                            colors.insert(color);
+                        }
+                        ManaSymbol::Hybrid(first, second) => {
+                            colors.extend([first, second]);
+                        }
+                        ManaSymbol::Generic(_) | ManaSymbol::Variable | ManaSymbol::Colorless => {}
                     }
                     colors
                 })
@@ -335,7 +1344,7 @@ impl Card {
 
 /// 205.1. The type line is printed directly below the illustration. It contains the card’s card
 ///        type(s). It also contains the card’s subtype(s) and supertype(s), if applicable.
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TypeLine {
     /// 205.2a The card types are artifact, conspiracy, creature, dungeon, enchantment, instant,
     ///        land, phenomenon, plane, planeswalker, scheme, sorcery, tribal, and vanguard. See
@@ -348,9 +1357,69 @@ pub struct TypeLine {
     pub(crate) supertype: IndexSet<Supertype>,
 }
 
+impl TypeLine {
+    /// Parses a type line as it is printed on cards, e.g. “Legendary Creature — Elf Druid”, in
+    /// which the supertypes and card types are separated from the subtypes by a long dash. Returns
+    /// `None` if a word isn't a type or a subtype doesn't belong to any of the card types.
+    ///
+    /// # Remarks
+    /// Subtypes whose names contain anything but letters, e.g. “Assembly-Worker” or “Serra’s
+    /// Realm”, aren't recognized.
+    pub(crate) fn parse(text: &str) -> Option<Self> {
+        let (types, subtypes) = text.split_once('—').unwrap_or((text, ""));
+        let mut card_type = IndexSet::new();
+        let mut supertype = IndexSet::new();
+        for word in types.split_whitespace() {
+            match variant(word) {
+                Some(it) => supertype.insert(it),
+                None => card_type.insert(variant(word)?),
+            };
+        }
+        if card_type.is_empty() {
+            return None;
+        }
+        // 205.3c If a card with multiple card types has one or more subtypes, each subtype is
+        //        correlated to its appropriate card type.
+        let subtype = subtypes
+            .split_whitespace()
+            .map(|word| card_type.iter().find_map(|&it| subtype_of(it, word)))
+            .collect::<Option<_>>()?;
+        Some(Self {
+            card_type,
+            subtype,
+            supertype,
+        })
+    }
+}
+
+/// Returns the unit variant with the specified name, e.g. `Legendary`, which is how it is
+/// serialized.
+fn variant<T: DeserializeOwned>(name: &str) -> Option<T> {
+    serde_json::from_value(serde_json::Value::String(name.into())).ok()
+}
+
+/// 205.3d An object can’t gain a subtype that doesn’t correspond to one of that object’s types.
+///
+/// Returns the subtype with the specified name that corresponds to the card type, if any.
+fn subtype_of(card_type: CardType, name: &str) -> Option<Subtype> {
+    match card_type {
+        CardType::Artifact => variant(name).map(Subtype::Artifact),
+        CardType::Creature | CardType::Tribal => variant(name).map(Subtype::Creature),
+        CardType::Enchantment => variant(name).map(Subtype::Enchantment),
+        CardType::Land => variant(name)
+            .map(LandType::Basic)
+            .or_else(|| variant(name))
+            .map(Subtype::Land),
+        CardType::Plane => variant(name).map(Subtype::Plane),
+        CardType::Planeswalker => variant(name).map(Subtype::Planeswalker),
+        CardType::Instant | CardType::Sorcery => variant(name).map(Subtype::Spell),
+        _ => None,
+    }
+}
+
 /// 206.1. The expansion symbol indicates which Magic set a card is from. It’s a small icon normally
 ///        printed below the right edge of the illustration. It has no effect on game play.
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ExpansionSymbol {
     // TODO: Figure out whether to use a String or enum for this.
     pub(crate) set: String,
@@ -366,7 +1435,7 @@ pub struct ExpansionSymbol {
 ///        symbols were black, regardless of rarity. Also, prior to the Sixth Edition core set, with
 ///        the exception of the Simplified Chinese Fifth Edition core set, Magic core sets didn’t
 ///        have expansion symbols at all.)
-#[derive(Copy, Clone, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub(crate) enum Rarity {
     MythicRare,
     Rare,
@@ -376,15 +1445,16 @@ pub(crate) enum Rarity {
     Timeshifted,
 }
 
-/// 300.1. The card types are artifact, conspiracy, creature, dungeon, enchantment, instant, land,
-///        phenomenon, plane, planeswalker, scheme, sorcery, tribal, and vanguard. See section 3,
-///        “Card Types.”
+/// 300.1. The card types are artifact, battle, conspiracy, creature, dungeon, enchantment,
+///        instant, land, phenomenon, plane, planeswalker, scheme, sorcery, tribal, and vanguard.
+///        See section 3, “Card Types.”
 /// 300.2. Some objects have more than one card type (for example, an artifact creature). Such
 ///        objects combine the aspects of each of those card types, and are subject to spells and
 ///        abilities that affect either or all of those card types.
-#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Hash)]
 pub(crate) enum CardType {
     Artifact,
+    Battle,
     Conspiracy,
     Creature,
     Dungeon,
@@ -417,7 +1487,7 @@ pub(crate) enum CardType {
 ///
 /// Example: Dryad Arbor’s type line says “Land Creature — Forest Dryad.” Forest is a land type,
 ///          and Dryad is a creature type.
-#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Hash)]
 pub(crate) enum Subtype {
     Artifact(ArtifactType),
     Creature(CreatureType),
@@ -428,10 +1498,25 @@ pub(crate) enum Subtype {
     Spell(SpellType),
 }
 
+impl Subtype {
+    /// Returns the name of the subtype as printed on type lines, e.g. “Elf” or “Urza’s”.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Subtype::Artifact(subtype) => subtype.name(),
+            Subtype::Creature(subtype) => subtype.name(),
+            Subtype::Enchantment(subtype) => subtype.name(),
+            Subtype::Land(subtype) => subtype.name(),
+            Subtype::Plane(subtype) => subtype.name(),
+            Subtype::Planeswalker(subtype) => subtype.name(),
+            Subtype::Spell(subtype) => subtype.name(),
+        }
+    }
+}
+
 /// 301.3. Artifact subtypes are always a single word and are listed after a long dash: “Artifact —
 ///        Equipment.” Artifact subtypes are also called artifact types. Artifacts may have multiple
 ///        subtypes. See rule 205.3g for the complete list of artifact types.
-#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Hash)]
 pub(crate) enum ArtifactType {
     Blood,
     Clue,
@@ -444,6 +1529,23 @@ pub(crate) enum ArtifactType {
     Vehicle,
 }
 
+impl ArtifactType {
+    /// Returns the name of the artifact type as printed on type lines, e.g. “Equipment”.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            ArtifactType::Blood => "Blood",
+            ArtifactType::Clue => "Clue",
+            ArtifactType::Contraption => "Contraption",
+            ArtifactType::Equipment => "Equipment",
+            ArtifactType::Food => "Food",
+            ArtifactType::Fortification => "Fortification",
+            ArtifactType::Gold => "Gold",
+            ArtifactType::Treasure => "Treasure",
+            ArtifactType::Vehicle => "Vehicle",
+        }
+    }
+}
+
 /// 302.3. Creature subtypes are always a single word and are listed after a long dash: “Creature —
 ///        Human Soldier,” “Artifact Creature — Golem,” and so on. Creature subtypes are also called
 ///        creature types. Creatures may have multiple subtypes. See rule 205.3m for the complete
@@ -451,7 +1553,7 @@ pub(crate) enum ArtifactType {
 ///
 /// Example: “Creature — Goblin Wizard” means the card is a creature with the subtypes Goblin and
 ///          Wizard.
-#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Hash)]
 pub(crate) enum CreatureType {
     Advisor,
     Aetherborn,
@@ -716,11 +1818,547 @@ pub(crate) enum CreatureType {
     Zubera,
 }
 
+impl CreatureType {
+    /// All of the creature types in alphabetical order.
+    pub(crate) const ALL: [CreatureType; 261] = [
+        CreatureType::Advisor,
+        CreatureType::Aetherborn,
+        CreatureType::Ally,
+        CreatureType::Angel,
+        CreatureType::Antelope,
+        CreatureType::Ape,
+        CreatureType::Archer,
+        CreatureType::Archon,
+        CreatureType::Army,
+        CreatureType::Artificer,
+        CreatureType::Assassin,
+        CreatureType::AssemblyWorker,
+        CreatureType::Atog,
+        CreatureType::Aurochs,
+        CreatureType::Avatar,
+        CreatureType::Azra,
+        CreatureType::Badger,
+        CreatureType::Barbarian,
+        CreatureType::Bard,
+        CreatureType::Basilisk,
+        CreatureType::Bat,
+        CreatureType::Bear,
+        CreatureType::Beast,
+        CreatureType::Beeble,
+        CreatureType::Beholder,
+        CreatureType::Berserker,
+        CreatureType::Bird,
+        CreatureType::Blinkmoth,
+        CreatureType::Boar,
+        CreatureType::Bringer,
+        CreatureType::Brushwagg,
+        CreatureType::Camarid,
+        CreatureType::Camel,
+        CreatureType::Caribou,
+        CreatureType::Carrier,
+        CreatureType::Cat,
+        CreatureType::Centaur,
+        CreatureType::Cephalid,
+        CreatureType::Chimera,
+        CreatureType::Citizen,
+        CreatureType::Cleric,
+        CreatureType::Cockatrice,
+        CreatureType::Construct,
+        CreatureType::Coward,
+        CreatureType::Crab,
+        CreatureType::Crocodile,
+        CreatureType::Cyclops,
+        CreatureType::Dauthi,
+        CreatureType::Demigod,
+        CreatureType::Demon,
+        CreatureType::Deserter,
+        CreatureType::Devil,
+        CreatureType::Dinosaur,
+        CreatureType::Djinn,
+        CreatureType::Dog,
+        CreatureType::Dragon,
+        CreatureType::Drake,
+        CreatureType::Dreadnought,
+        CreatureType::Drone,
+        CreatureType::Druid,
+        CreatureType::Dryad,
+        CreatureType::Dwarf,
+        CreatureType::Efreet,
+        CreatureType::Egg,
+        CreatureType::Elder,
+        CreatureType::Eldrazi,
+        CreatureType::Elemental,
+        CreatureType::Elephant,
+        CreatureType::Elf,
+        CreatureType::Elk,
+        CreatureType::Eye,
+        CreatureType::Faerie,
+        CreatureType::Ferret,
+        CreatureType::Fish,
+        CreatureType::Flagbearer,
+        CreatureType::Fox,
+        CreatureType::Fractal,
+        CreatureType::Frog,
+        CreatureType::Fungus,
+        CreatureType::Gargoyle,
+        CreatureType::Germ,
+        CreatureType::Giant,
+        CreatureType::Gnoll,
+        CreatureType::Gnome,
+        CreatureType::Goat,
+        CreatureType::Goblin,
+        CreatureType::God,
+        CreatureType::Golem,
+        CreatureType::Gorgon,
+        CreatureType::Graveborn,
+        CreatureType::Gremlin,
+        CreatureType::Griffin,
+        CreatureType::Hag,
+        CreatureType::Halfling,
+        CreatureType::Hamster,
+        CreatureType::Harpy,
+        CreatureType::Hellion,
+        CreatureType::Hippo,
+        CreatureType::Hippogriff,
+        CreatureType::Homarid,
+        CreatureType::Homunculus,
+        CreatureType::Horror,
+        CreatureType::Horse,
+        CreatureType::Human,
+        CreatureType::Hydra,
+        CreatureType::Hyena,
+        CreatureType::Illusion,
+        CreatureType::Imp,
+        CreatureType::Incarnation,
+        CreatureType::Inkling,
+        CreatureType::Insect,
+        CreatureType::Jackal,
+        CreatureType::Jellyfish,
+        CreatureType::Juggernaut,
+        CreatureType::Kavu,
+        CreatureType::Kirin,
+        CreatureType::Kithkin,
+        CreatureType::Knight,
+        CreatureType::Kobold,
+        CreatureType::Kor,
+        CreatureType::Kraken,
+        CreatureType::Lamia,
+        CreatureType::Lammasu,
+        CreatureType::Leech,
+        CreatureType::Leviathan,
+        CreatureType::Lhurgoyf,
+        CreatureType::Licid,
+        CreatureType::Lizard,
+        CreatureType::Manticore,
+        CreatureType::Masticore,
+        CreatureType::Mercenary,
+        CreatureType::Merfolk,
+        CreatureType::Metathran,
+        CreatureType::Minion,
+        CreatureType::Minotaur,
+        CreatureType::Mole,
+        CreatureType::Monger,
+        CreatureType::Mongoose,
+        CreatureType::Monk,
+        CreatureType::Monkey,
+        CreatureType::Moonfolk,
+        CreatureType::Mouse,
+        CreatureType::Mutant,
+        CreatureType::Myr,
+        CreatureType::Mystic,
+        CreatureType::Naga,
+        CreatureType::Nautilus,
+        CreatureType::Nephilim,
+        CreatureType::Nightmare,
+        CreatureType::Nightstalker,
+        CreatureType::Ninja,
+        CreatureType::Noble,
+        CreatureType::Noggle,
+        CreatureType::Nomad,
+        CreatureType::Nymph,
+        CreatureType::Octopus,
+        CreatureType::Ogre,
+        CreatureType::Ooze,
+        CreatureType::Orb,
+        CreatureType::Orc,
+        CreatureType::Orgg,
+        CreatureType::Otter,
+        CreatureType::Ouphe,
+        CreatureType::Ox,
+        CreatureType::Oyster,
+        CreatureType::Pangolin,
+        CreatureType::Peasant,
+        CreatureType::Pegasus,
+        CreatureType::Pentavite,
+        CreatureType::Pest,
+        CreatureType::Phelddagrif,
+        CreatureType::Phoenix,
+        CreatureType::Phyrexian,
+        CreatureType::Pilot,
+        CreatureType::Pincher,
+        CreatureType::Pirate,
+        CreatureType::Plant,
+        CreatureType::Praetor,
+        CreatureType::Prism,
+        CreatureType::Processor,
+        CreatureType::Rabbit,
+        CreatureType::Ranger,
+        CreatureType::Rat,
+        CreatureType::Rebel,
+        CreatureType::Reflection,
+        CreatureType::Rhino,
+        CreatureType::Rigger,
+        CreatureType::Rogue,
+        CreatureType::Sable,
+        CreatureType::Salamander,
+        CreatureType::Samurai,
+        CreatureType::Sand,
+        CreatureType::Saproling,
+        CreatureType::Satyr,
+        CreatureType::Scarecrow,
+        CreatureType::Scion,
+        CreatureType::Scorpion,
+        CreatureType::Scout,
+        CreatureType::Sculpture,
+        CreatureType::Serf,
+        CreatureType::Serpent,
+        CreatureType::Servo,
+        CreatureType::Shade,
+        CreatureType::Shaman,
+        CreatureType::Shapeshifter,
+        CreatureType::Shark,
+        CreatureType::Sheep,
+        CreatureType::Siren,
+        CreatureType::Skeleton,
+        CreatureType::Slith,
+        CreatureType::Sliver,
+        CreatureType::Slug,
+        CreatureType::Snake,
+        CreatureType::Soldier,
+        CreatureType::Soltari,
+        CreatureType::Spawn,
+        CreatureType::Specter,
+        CreatureType::Spellshaper,
+        CreatureType::Sphinx,
+        CreatureType::Spider,
+        CreatureType::Spike,
+        CreatureType::Spirit,
+        CreatureType::Splinter,
+        CreatureType::Sponge,
+        CreatureType::Squid,
+        CreatureType::Squirrel,
+        CreatureType::Starfish,
+        CreatureType::Surrakar,
+        CreatureType::Survivor,
+        CreatureType::Tentacle,
+        CreatureType::Tetravite,
+        CreatureType::Thalakos,
+        CreatureType::Thopter,
+        CreatureType::Thrull,
+        CreatureType::Tiefling,
+        CreatureType::Treefolk,
+        CreatureType::Trilobite,
+        CreatureType::Triskelavite,
+        CreatureType::Troll,
+        CreatureType::Turtle,
+        CreatureType::Unicorn,
+        CreatureType::Vampire,
+        CreatureType::Vedalken,
+        CreatureType::Viashino,
+        CreatureType::Volver,
+        CreatureType::Wall,
+        CreatureType::Warlock,
+        CreatureType::Warrior,
+        CreatureType::Weird,
+        CreatureType::Werewolf,
+        CreatureType::Whale,
+        CreatureType::Wizard,
+        CreatureType::Wolf,
+        CreatureType::Wolverine,
+        CreatureType::Wombat,
+        CreatureType::Worm,
+        CreatureType::Wraith,
+        CreatureType::Wurm,
+        CreatureType::Yeti,
+        CreatureType::Zombie,
+        CreatureType::Zubera,
+    ];
+
+    /// Returns the name of the creature type as printed on type lines, e.g. “Assembly-Worker”.
+    // The table has an arm for each of the creature types, so it can't be any shorter.
+    #[allow(clippy::too_many_lines)]
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            CreatureType::Advisor => "Advisor",
+            CreatureType::Aetherborn => "Aetherborn",
+            CreatureType::Ally => "Ally",
+            CreatureType::Angel => "Angel",
+            CreatureType::Antelope => "Antelope",
+            CreatureType::Ape => "Ape",
+            CreatureType::Archer => "Archer",
+            CreatureType::Archon => "Archon",
+            CreatureType::Army => "Army",
+            CreatureType::Artificer => "Artificer",
+            CreatureType::Assassin => "Assassin",
+            CreatureType::AssemblyWorker => "Assembly-Worker",
+            CreatureType::Atog => "Atog",
+            CreatureType::Aurochs => "Aurochs",
+            CreatureType::Avatar => "Avatar",
+            CreatureType::Azra => "Azra",
+            CreatureType::Badger => "Badger",
+            CreatureType::Barbarian => "Barbarian",
+            CreatureType::Bard => "Bard",
+            CreatureType::Basilisk => "Basilisk",
+            CreatureType::Bat => "Bat",
+            CreatureType::Bear => "Bear",
+            CreatureType::Beast => "Beast",
+            CreatureType::Beeble => "Beeble",
+            CreatureType::Beholder => "Beholder",
+            CreatureType::Berserker => "Berserker",
+            CreatureType::Bird => "Bird",
+            CreatureType::Blinkmoth => "Blinkmoth",
+            CreatureType::Boar => "Boar",
+            CreatureType::Bringer => "Bringer",
+            CreatureType::Brushwagg => "Brushwagg",
+            CreatureType::Camarid => "Camarid",
+            CreatureType::Camel => "Camel",
+            CreatureType::Caribou => "Caribou",
+            CreatureType::Carrier => "Carrier",
+            CreatureType::Cat => "Cat",
+            CreatureType::Centaur => "Centaur",
+            CreatureType::Cephalid => "Cephalid",
+            CreatureType::Chimera => "Chimera",
+            CreatureType::Citizen => "Citizen",
+            CreatureType::Cleric => "Cleric",
+            CreatureType::Cockatrice => "Cockatrice",
+            CreatureType::Construct => "Construct",
+            CreatureType::Coward => "Coward",
+            CreatureType::Crab => "Crab",
+            CreatureType::Crocodile => "Crocodile",
+            CreatureType::Cyclops => "Cyclops",
+            CreatureType::Dauthi => "Dauthi",
+            CreatureType::Demigod => "Demigod",
+            CreatureType::Demon => "Demon",
+            CreatureType::Deserter => "Deserter",
+            CreatureType::Devil => "Devil",
+            CreatureType::Dinosaur => "Dinosaur",
+            CreatureType::Djinn => "Djinn",
+            CreatureType::Dog => "Dog",
+            CreatureType::Dragon => "Dragon",
+            CreatureType::Drake => "Drake",
+            CreatureType::Dreadnought => "Dreadnought",
+            CreatureType::Drone => "Drone",
+            CreatureType::Druid => "Druid",
+            CreatureType::Dryad => "Dryad",
+            CreatureType::Dwarf => "Dwarf",
+            CreatureType::Efreet => "Efreet",
+            CreatureType::Egg => "Egg",
+            CreatureType::Elder => "Elder",
+            CreatureType::Eldrazi => "Eldrazi",
+            CreatureType::Elemental => "Elemental",
+            CreatureType::Elephant => "Elephant",
+            CreatureType::Elf => "Elf",
+            CreatureType::Elk => "Elk",
+            CreatureType::Eye => "Eye",
+            CreatureType::Faerie => "Faerie",
+            CreatureType::Ferret => "Ferret",
+            CreatureType::Fish => "Fish",
+            CreatureType::Flagbearer => "Flagbearer",
+            CreatureType::Fox => "Fox",
+            CreatureType::Fractal => "Fractal",
+            CreatureType::Frog => "Frog",
+            CreatureType::Fungus => "Fungus",
+            CreatureType::Gargoyle => "Gargoyle",
+            CreatureType::Germ => "Germ",
+            CreatureType::Giant => "Giant",
+            CreatureType::Gnoll => "Gnoll",
+            CreatureType::Gnome => "Gnome",
+            CreatureType::Goat => "Goat",
+            CreatureType::Goblin => "Goblin",
+            CreatureType::God => "God",
+            CreatureType::Golem => "Golem",
+            CreatureType::Gorgon => "Gorgon",
+            CreatureType::Graveborn => "Graveborn",
+            CreatureType::Gremlin => "Gremlin",
+            CreatureType::Griffin => "Griffin",
+            CreatureType::Hag => "Hag",
+            CreatureType::Halfling => "Halfling",
+            CreatureType::Hamster => "Hamster",
+            CreatureType::Harpy => "Harpy",
+            CreatureType::Hellion => "Hellion",
+            CreatureType::Hippo => "Hippo",
+            CreatureType::Hippogriff => "Hippogriff",
+            CreatureType::Homarid => "Homarid",
+            CreatureType::Homunculus => "Homunculus",
+            CreatureType::Horror => "Horror",
+            CreatureType::Horse => "Horse",
+            CreatureType::Human => "Human",
+            CreatureType::Hydra => "Hydra",
+            CreatureType::Hyena => "Hyena",
+            CreatureType::Illusion => "Illusion",
+            CreatureType::Imp => "Imp",
+            CreatureType::Incarnation => "Incarnation",
+            CreatureType::Inkling => "Inkling",
+            CreatureType::Insect => "Insect",
+            CreatureType::Jackal => "Jackal",
+            CreatureType::Jellyfish => "Jellyfish",
+            CreatureType::Juggernaut => "Juggernaut",
+            CreatureType::Kavu => "Kavu",
+            CreatureType::Kirin => "Kirin",
+            CreatureType::Kithkin => "Kithkin",
+            CreatureType::Knight => "Knight",
+            CreatureType::Kobold => "Kobold",
+            CreatureType::Kor => "Kor",
+            CreatureType::Kraken => "Kraken",
+            CreatureType::Lamia => "Lamia",
+            CreatureType::Lammasu => "Lammasu",
+            CreatureType::Leech => "Leech",
+            CreatureType::Leviathan => "Leviathan",
+            CreatureType::Lhurgoyf => "Lhurgoyf",
+            CreatureType::Licid => "Licid",
+            CreatureType::Lizard => "Lizard",
+            CreatureType::Manticore => "Manticore",
+            CreatureType::Masticore => "Masticore",
+            CreatureType::Mercenary => "Mercenary",
+            CreatureType::Merfolk => "Merfolk",
+            CreatureType::Metathran => "Metathran",
+            CreatureType::Minion => "Minion",
+            CreatureType::Minotaur => "Minotaur",
+            CreatureType::Mole => "Mole",
+            CreatureType::Monger => "Monger",
+            CreatureType::Mongoose => "Mongoose",
+            CreatureType::Monk => "Monk",
+            CreatureType::Monkey => "Monkey",
+            CreatureType::Moonfolk => "Moonfolk",
+            CreatureType::Mouse => "Mouse",
+            CreatureType::Mutant => "Mutant",
+            CreatureType::Myr => "Myr",
+            CreatureType::Mystic => "Mystic",
+            CreatureType::Naga => "Naga",
+            CreatureType::Nautilus => "Nautilus",
+            CreatureType::Nephilim => "Nephilim",
+            CreatureType::Nightmare => "Nightmare",
+            CreatureType::Nightstalker => "Nightstalker",
+            CreatureType::Ninja => "Ninja",
+            CreatureType::Noble => "Noble",
+            CreatureType::Noggle => "Noggle",
+            CreatureType::Nomad => "Nomad",
+            CreatureType::Nymph => "Nymph",
+            CreatureType::Octopus => "Octopus",
+            CreatureType::Ogre => "Ogre",
+            CreatureType::Ooze => "Ooze",
+            CreatureType::Orb => "Orb",
+            CreatureType::Orc => "Orc",
+            CreatureType::Orgg => "Orgg",
+            CreatureType::Otter => "Otter",
+            CreatureType::Ouphe => "Ouphe",
+            CreatureType::Ox => "Ox",
+            CreatureType::Oyster => "Oyster",
+            CreatureType::Pangolin => "Pangolin",
+            CreatureType::Peasant => "Peasant",
+            CreatureType::Pegasus => "Pegasus",
+            CreatureType::Pentavite => "Pentavite",
+            CreatureType::Pest => "Pest",
+            CreatureType::Phelddagrif => "Phelddagrif",
+            CreatureType::Phoenix => "Phoenix",
+            CreatureType::Phyrexian => "Phyrexian",
+            CreatureType::Pilot => "Pilot",
+            CreatureType::Pincher => "Pincher",
+            CreatureType::Pirate => "Pirate",
+            CreatureType::Plant => "Plant",
+            CreatureType::Praetor => "Praetor",
+            CreatureType::Prism => "Prism",
+            CreatureType::Processor => "Processor",
+            CreatureType::Rabbit => "Rabbit",
+            CreatureType::Ranger => "Ranger",
+            CreatureType::Rat => "Rat",
+            CreatureType::Rebel => "Rebel",
+            CreatureType::Reflection => "Reflection",
+            CreatureType::Rhino => "Rhino",
+            CreatureType::Rigger => "Rigger",
+            CreatureType::Rogue => "Rogue",
+            CreatureType::Sable => "Sable",
+            CreatureType::Salamander => "Salamander",
+            CreatureType::Samurai => "Samurai",
+            CreatureType::Sand => "Sand",
+            CreatureType::Saproling => "Saproling",
+            CreatureType::Satyr => "Satyr",
+            CreatureType::Scarecrow => "Scarecrow",
+            CreatureType::Scion => "Scion",
+            CreatureType::Scorpion => "Scorpion",
+            CreatureType::Scout => "Scout",
+            CreatureType::Sculpture => "Sculpture",
+            CreatureType::Serf => "Serf",
+            CreatureType::Serpent => "Serpent",
+            CreatureType::Servo => "Servo",
+            CreatureType::Shade => "Shade",
+            CreatureType::Shaman => "Shaman",
+            CreatureType::Shapeshifter => "Shapeshifter",
+            CreatureType::Shark => "Shark",
+            CreatureType::Sheep => "Sheep",
+            CreatureType::Siren => "Siren",
+            CreatureType::Skeleton => "Skeleton",
+            CreatureType::Slith => "Slith",
+            CreatureType::Sliver => "Sliver",
+            CreatureType::Slug => "Slug",
+            CreatureType::Snake => "Snake",
+            CreatureType::Soldier => "Soldier",
+            CreatureType::Soltari => "Soltari",
+            CreatureType::Spawn => "Spawn",
+            CreatureType::Specter => "Specter",
+            CreatureType::Spellshaper => "Spellshaper",
+            CreatureType::Sphinx => "Sphinx",
+            CreatureType::Spider => "Spider",
+            CreatureType::Spike => "Spike",
+            CreatureType::Spirit => "Spirit",
+            CreatureType::Splinter => "Splinter",
+            CreatureType::Sponge => "Sponge",
+            CreatureType::Squid => "Squid",
+            CreatureType::Squirrel => "Squirrel",
+            CreatureType::Starfish => "Starfish",
+            CreatureType::Surrakar => "Surrakar",
+            CreatureType::Survivor => "Survivor",
+            CreatureType::Tentacle => "Tentacle",
+            CreatureType::Tetravite => "Tetravite",
+            CreatureType::Thalakos => "Thalakos",
+            CreatureType::Thopter => "Thopter",
+            CreatureType::Thrull => "Thrull",
+            CreatureType::Tiefling => "Tiefling",
+            CreatureType::Treefolk => "Treefolk",
+            CreatureType::Trilobite => "Trilobite",
+            CreatureType::Triskelavite => "Triskelavite",
+            CreatureType::Troll => "Troll",
+            CreatureType::Turtle => "Turtle",
+            CreatureType::Unicorn => "Unicorn",
+            CreatureType::Vampire => "Vampire",
+            CreatureType::Vedalken => "Vedalken",
+            CreatureType::Viashino => "Viashino",
+            CreatureType::Volver => "Volver",
+            CreatureType::Wall => "Wall",
+            CreatureType::Warlock => "Warlock",
+            CreatureType::Warrior => "Warrior",
+            CreatureType::Weird => "Weird",
+            CreatureType::Werewolf => "Werewolf",
+            CreatureType::Whale => "Whale",
+            CreatureType::Wizard => "Wizard",
+            CreatureType::Wolf => "Wolf",
+            CreatureType::Wolverine => "Wolverine",
+            CreatureType::Wombat => "Wombat",
+            CreatureType::Worm => "Worm",
+            CreatureType::Wraith => "Wraith",
+            CreatureType::Wurm => "Wurm",
+            CreatureType::Yeti => "Yeti",
+            CreatureType::Zombie => "Zombie",
+            CreatureType::Zubera => "Zubera",
+        }
+    }
+}
+
 /// 303.3. Enchantment subtypes are always a single word and are listed after a long dash:
 ///        “Enchantment — Shrine.” Each word after the dash is a separate subtype. Enchantment
 ///        subtypes are also called enchantment types. Enchantments may have multiple subtypes.
 ///        See rule 205.3h for the complete list of enchantment types.
-#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Hash)]
 pub(crate) enum EnchantmentType {
     Aura,
     Cartouche,
@@ -732,12 +2370,28 @@ pub(crate) enum EnchantmentType {
     Shrine,
 }
 
+impl EnchantmentType {
+    /// Returns the name of the enchantment type as printed on type lines, e.g. “Aura”.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            EnchantmentType::Aura => "Aura",
+            EnchantmentType::Cartouche => "Cartouche",
+            EnchantmentType::Class => "Class",
+            EnchantmentType::Curse => "Curse",
+            EnchantmentType::Rune => "Rune",
+            EnchantmentType::Saga => "Saga",
+            EnchantmentType::Shard => "Shard",
+            EnchantmentType::Shrine => "Shrine",
+        }
+    }
+}
+
 /// 305.5. Land subtypes are always a single word and are listed after a long dash. Land subtypes
 ///        are also called land types. Lands may have multiple subtypes. See rule 205.3i for the
 ///        complete list of land types.
 ///
 /// Example: “Basic Land — Mountain” means the card is a land with the subtype Mountain.
-#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Hash)]
 pub(crate) enum LandType {
     Basic(BasicLandType),
     Desert,
@@ -750,13 +2404,30 @@ pub(crate) enum LandType {
     Urzas,
 }
 
+impl LandType {
+    /// Returns the name of the land type as printed on type lines, e.g. “Urza’s”.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            LandType::Basic(basic) => basic.name(),
+            LandType::Desert => "Desert",
+            LandType::Gate => "Gate",
+            LandType::Lair => "Lair",
+            LandType::Locus => "Locus",
+            LandType::Mine => "Mine",
+            LandType::PowerPlant => "Power-Plant",
+            LandType::Tower => "Tower",
+            LandType::Urzas => "Urza’s",
+        }
+    }
+}
+
 /// 305.6. The basic land types are Plains, Island, Swamp, Mountain, and Forest. If an object uses
 ///        the words “basic land type,” it’s referring to one of these subtypes. An object with the
 ///        land card type and a basic land type has the intrinsic ability “{T}: Add [mana symbol],”
 ///        even if the text box doesn’t actually contain that text or the object has no text box.
 ///        For Plains, [mana symbol] is {W}; for Islands, {U}; for Swamps, {B}; for Mountains, {R};
 ///        and for Forests, {G}. See rule 107.4a. See also rule 605, “Mana Abilities.”
-#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Hash)]
 pub(crate) enum BasicLandType {
     Forest,
     Island,
@@ -765,11 +2436,35 @@ pub(crate) enum BasicLandType {
     Swamp,
 }
 
+impl BasicLandType {
+    /// Returns the color of the mana the intrinsic ability of the land type adds.
+    pub(crate) fn color(self) -> Color {
+        match self {
+            BasicLandType::Forest => Color::Green,
+            BasicLandType::Island => Color::Blue,
+            BasicLandType::Mountain => Color::Red,
+            BasicLandType::Plains => Color::White,
+            BasicLandType::Swamp => Color::Black,
+        }
+    }
+
+    /// Returns the name of the basic land type as printed on type lines, e.g. “Forest”.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            BasicLandType::Forest => "Forest",
+            BasicLandType::Island => "Island",
+            BasicLandType::Mountain => "Mountain",
+            BasicLandType::Plains => "Plains",
+            BasicLandType::Swamp => "Swamp",
+        }
+    }
+}
+
 /// 306.3. Planeswalker subtypes are always a single word and are listed after a long dash:
 ///        “Planeswalker — Jace.” Each word after the dash is a separate subtype. Planeswalker
 ///        subtypes are also called planeswalker types. Planeswalkers may have multiple subtypes.
 ///        See rule 205.3j for the complete list of planeswalker types.
-#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Hash)]
 pub(crate) enum PlaneswalkerType {
     Ajani,
     Aminatou,
@@ -842,6 +2537,83 @@ pub(crate) enum PlaneswalkerType {
     Zariel,
 }
 
+impl PlaneswalkerType {
+    /// Returns the name of the planeswalker type as printed on type lines, e.g. “Jace”.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            PlaneswalkerType::Ajani => "Ajani",
+            PlaneswalkerType::Aminatou => "Aminatou",
+            PlaneswalkerType::Angrath => "Angrath",
+            PlaneswalkerType::Arlinn => "Arlinn",
+            PlaneswalkerType::Ashiok => "Ashiok",
+            PlaneswalkerType::Bahamut => "Bahamut",
+            PlaneswalkerType::Basri => "Basri",
+            PlaneswalkerType::Bolas => "Bolas",
+            PlaneswalkerType::Calix => "Calix",
+            PlaneswalkerType::Chandra => "Chandra",
+            PlaneswalkerType::Dack => "Dack",
+            PlaneswalkerType::Dakkon => "Dakkon",
+            PlaneswalkerType::Daretti => "Daretti",
+            PlaneswalkerType::Davriel => "Davriel",
+            PlaneswalkerType::Dihada => "Dihada",
+            PlaneswalkerType::Domri => "Domri",
+            PlaneswalkerType::Dovin => "Dovin",
+            PlaneswalkerType::Ellywick => "Ellywick",
+            PlaneswalkerType::Elspeth => "Elspeth",
+            PlaneswalkerType::Estrid => "Estrid",
+            PlaneswalkerType::Freyalise => "Freyalise",
+            PlaneswalkerType::Garruk => "Garruk",
+            PlaneswalkerType::Gideon => "Gideon",
+            PlaneswalkerType::Grist => "Grist",
+            PlaneswalkerType::Huatli => "Huatli",
+            PlaneswalkerType::Jace => "Jace",
+            PlaneswalkerType::Jaya => "Jaya",
+            PlaneswalkerType::Jeska => "Jeska",
+            PlaneswalkerType::Kaito => "Kaito",
+            PlaneswalkerType::Karn => "Karn",
+            PlaneswalkerType::Kasmina => "Kasmina",
+            PlaneswalkerType::Kaya => "Kaya",
+            PlaneswalkerType::Kiora => "Kiora",
+            PlaneswalkerType::Koth => "Koth",
+            PlaneswalkerType::Liliana => "Liliana",
+            PlaneswalkerType::Lolth => "Lolth",
+            PlaneswalkerType::Lukka => "Lukka",
+            PlaneswalkerType::Mordenkainen => "Mordenkainen",
+            PlaneswalkerType::Nahiri => "Nahiri",
+            PlaneswalkerType::Narset => "Narset",
+            PlaneswalkerType::Niko => "Niko",
+            PlaneswalkerType::Nissa => "Nissa",
+            PlaneswalkerType::Nixilis => "Nixilis",
+            PlaneswalkerType::Oko => "Oko",
+            PlaneswalkerType::Ral => "Ral",
+            PlaneswalkerType::Rowan => "Rowan",
+            PlaneswalkerType::Saheeli => "Saheeli",
+            PlaneswalkerType::Samut => "Samut",
+            PlaneswalkerType::Sarkhan => "Sarkhan",
+            PlaneswalkerType::Serra => "Serra",
+            PlaneswalkerType::Sorin => "Sorin",
+            PlaneswalkerType::Szat => "Szat",
+            PlaneswalkerType::Tamiyo => "Tamiyo",
+            PlaneswalkerType::Teferi => "Teferi",
+            PlaneswalkerType::Teyo => "Teyo",
+            PlaneswalkerType::Tezzeret => "Tezzeret",
+            PlaneswalkerType::Tibalt => "Tibalt",
+            PlaneswalkerType::Tyvar => "Tyvar",
+            PlaneswalkerType::Ugin => "Ugin",
+            PlaneswalkerType::Venser => "Venser",
+            PlaneswalkerType::Vivien => "Vivien",
+            PlaneswalkerType::Vraska => "Vraska",
+            PlaneswalkerType::Will => "Will",
+            PlaneswalkerType::Windgrace => "Windgrace",
+            PlaneswalkerType::Wrenn => "Wrenn",
+            PlaneswalkerType::Xenagos => "Xenagos",
+            PlaneswalkerType::Yanggu => "Yanggu",
+            PlaneswalkerType::Yanling => "Yanling",
+            PlaneswalkerType::Zariel => "Zariel",
+        }
+    }
+}
+
 /// 304.3. Instant subtypes are always a single word and are listed after a long dash: “Instant —
 ///        Arcane.” Each word after the dash is a separate subtype. The set of instant subtypes is
 ///        the same as the set of sorcery subtypes; these subtypes are called spell types. Instants
@@ -851,7 +2623,7 @@ pub(crate) enum PlaneswalkerType {
 ///        Arcane.” Each word after the dash is a separate subtype. The set of sorcery subtypes is
 ///        the same as the set of instant subtypes; these subtypes are called spell types. Sorceries
 ///        may have multiple subtypes. See rule 205.3k for the complete list of spell types.
-#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Hash)]
 pub(crate) enum SpellType {
     Adventure,
     Arcane,
@@ -859,11 +2631,23 @@ pub(crate) enum SpellType {
     Trap,
 }
 
+impl SpellType {
+    /// Returns the name of the spell type as printed on type lines, e.g. “Arcane”.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            SpellType::Adventure => "Adventure",
+            SpellType::Arcane => "Arcane",
+            SpellType::Lesson => "Lesson",
+            SpellType::Trap => "Trap",
+        }
+    }
+}
+
 /// 310.3. Plane subtypes are listed after a long dash, and may be multiple words: “Plane — Serra’s
 ///        Realm.” All words after the dash are, collectively, a single subtype. Planar subtypes are
 ///        called planar types. A plane can have only one subtype. See rule 205.3n for the complete
 ///        list of planar types.
-#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Hash)]
 pub(crate) enum PlanarType {
     Alara,
     Arkhos,
@@ -910,6 +2694,57 @@ pub(crate) enum PlanarType {
     Zendikar,
 }
 
+impl PlanarType {
+    /// Returns the name of the planar type as printed on type lines, e.g. “Serra’s Realm”.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            PlanarType::Alara => "Alara",
+            PlanarType::Arkhos => "Arkhos",
+            PlanarType::Azgol => "Azgol",
+            PlanarType::Belenon => "Belenon",
+            PlanarType::BolassMeditationRealm => "Bolas’s Meditation Realm",
+            PlanarType::Dominaria => "Dominaria",
+            PlanarType::Equilor => "Equilor",
+            PlanarType::Ergamon => "Ergamon",
+            PlanarType::Fabacin => "Fabacin",
+            PlanarType::Innistrad => "Innistrad",
+            PlanarType::Iquatana => "Iquatana",
+            PlanarType::Ir => "Ir",
+            PlanarType::Kaldheim => "Kaldheim",
+            PlanarType::Kamigawa => "Kamigawa",
+            PlanarType::Karsus => "Karsus",
+            PlanarType::Kephalai => "Kephalai",
+            PlanarType::Kinshala => "Kinshala",
+            PlanarType::Kolbahan => "Kolbahan",
+            PlanarType::Kyneth => "Kyneth",
+            PlanarType::Lorwyn => "Lorwyn",
+            PlanarType::Luvion => "Luvion",
+            PlanarType::Mercadia => "Mercadia",
+            PlanarType::Mirrodin => "Mirrodin",
+            PlanarType::Moag => "Moag",
+            PlanarType::Mongseng => "Mongseng",
+            PlanarType::Muraganda => "Muraganda",
+            PlanarType::NewPhyrexia => "New Phyrexia",
+            PlanarType::Phyrexia => "Phyrexia",
+            PlanarType::Pyrulea => "Pyrulea",
+            PlanarType::Rabiah => "Rabiah",
+            PlanarType::Rath => "Rath",
+            PlanarType::Ravnica => "Ravnica",
+            PlanarType::Regatha => "Regatha",
+            PlanarType::Segovia => "Segovia",
+            PlanarType::SerrasRealm => "Serra’s Realm",
+            PlanarType::Shadowmoor => "Shadowmoor",
+            PlanarType::Shandalar => "Shandalar",
+            PlanarType::Ulgrotha => "Ulgrotha",
+            PlanarType::Valla => "Valla",
+            PlanarType::Vryn => "Vryn",
+            PlanarType::Wildfire => "Wildfire",
+            PlanarType::Xerex => "Xerex",
+            PlanarType::Zendikar => "Zendikar",
+        }
+    }
+}
+
 /// 205.4a An object can have one or more supertypes. A card’s supertypes are printed directly
 ///        before its card types. The supertypes are basic, legendary, ongoing, snow, and world.
 ///
@@ -921,7 +2756,7 @@ pub(crate) enum PlanarType {
 ///
 /// Example: An ability reads, “All lands are 1/1 creatures that are still lands.” If any of the
 ///          affected lands were legendary, they are still legendary.
-#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Hash)]
 pub(crate) enum Supertype {
     Basic,
     Legendary,
@@ -935,13 +2770,13 @@ pub(crate) enum Supertype {
 ///        its toughness (the amount of damage needed to destroy it). For example, 2/3 means the
 ///        object has power 2 and toughness 3. Power and toughness can be modified or set to
 ///        particular values by effects.
-#[derive(Copy, Clone, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct PtCharacteristic {
     pub(crate) power: PtValue,
     pub(crate) toughness: PtValue,
 }
 
-#[derive(Copy, Clone, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub(crate) enum PtValue {
     Fixed(i64),
     /// 208.2. Rather than a fixed number, some creature cards have power and/or toughness that
@@ -963,12 +2798,152 @@ pub(crate) enum PtValue {
     Variable,
 }
 
+/// 122.1. A counter is a marker placed on an object or player that modifies its characteristics
+///        and/or interacts with a rule, ability, or effect. Counters are not objects and have no
+///        characteristics. Notably, a counter is not a token, and a token is not a counter.
+///        Counters with the same name or description are interchangeable.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Hash)]
+pub enum CounterKind {
+    PlusOnePlusOne,
+    MinusOneMinusOne,
+    Loyalty,
+    Charge,
+    Lore,
+    Poison,
+    Energy,
+    Experience,
+    Shield,
+    Stun,
+    /// The counters that track the remaining defense of a battle.
+    Defense,
+    /// Any other kind of counter identified by its name, e.g. a time or a fade counter.
+    Other(String),
+}
+
+/// The counters placed on a single permanent or player, grouped by their kind.
+#[derive(Clone, Default)]
+pub(crate) struct Counters(IndexMap<CounterKind, u64>);
+
+impl Counters {
+    /// Returns the number of counters of the specified kind.
+    pub(crate) fn get(&self, kind: &CounterKind) -> u64 {
+        self.0.get(kind).copied().unwrap_or_default()
+    }
+
+    /// Returns all kinds of counters of which there is at least one along with their amounts.
+    pub(crate) fn to_vec(&self) -> Vec<(CounterKind, u64)> {
+        self.0
+            .iter()
+            .map(|(kind, &amount)| (kind.clone(), amount))
+            .collect()
+    }
+
+    /// Returns an iterator over all kinds of counters of which there is at least one.
+    pub(crate) fn kinds(&self) -> impl Iterator<Item = &CounterKind> {
+        self.0.keys()
+    }
+
+    /// Puts the specified amount of counters of the given kind.
+    pub(crate) fn add(&mut self, kind: CounterKind, amount: u64) {
+        if amount > 0 {
+            *self.0.entry(kind).or_default() += amount;
+        }
+    }
+
+    /// Removes up to the specified amount of counters of the given kind and returns how many
+    /// counters were actually removed.
+    pub(crate) fn remove(&mut self, kind: &CounterKind, amount: u64) -> u64 {
+        let present = self.get(kind);
+        let removed = present.min(amount);
+        if removed == present {
+            self.0.shift_remove(kind);
+        } else {
+            self.0.insert(kind.clone(), present - removed);
+        }
+        removed
+    }
+
+    /// 701.27a To proliferate means to choose any number of permanents and/or players, then give
+    ///         each another counter of each kind already there.
+    pub(crate) fn proliferate(&mut self) {
+        for amount in self.0.values_mut() {
+            *amount += 1;
+        }
+    }
+}
+
+/// 118.1. A cost is an action or payment necessary to take another action or to stop another
+///        action from taking place. To pay a cost, a player carries out the instructions specified
+///        by the spell, ability, or effect that contains that cost.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum Cost {
+    /// 107.14. The energy symbol is {E}. It represents one energy counter. To pay {E}, a player
+    ///         removes one energy counter from themselves.
+    PayEnergy(u64),
+}
+
+/// 730.1. Day and night are designations that the game itself can have. The game starts with
+///        neither designation.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum DayNight {
+    Day,
+    Night,
+}
+
+impl DayNight {
+    /// 730.2a If it’s day and the previous turn’s active player cast no spells during that turn, it
+    ///        becomes night. If it’s night and the previous turn’s active player cast two or more
+    ///        spells during that turn, it becomes day.
+    pub(crate) fn next(self, spells_cast_last_turn: u64) -> Self {
+        match self {
+            DayNight::Day if spells_cast_last_turn == 0 => DayNight::Night,
+            DayNight::Night if spells_cast_last_turn >= 2 => DayNight::Day,
+            _ => self,
+        }
+    }
+}
+
+/// A designation a player can get that stays with them for the rest of the game. Unlike the
+/// monarch, any number of players can have the same designation at once.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Hash)]
+pub enum Designation {
+    /// 702.131c The city’s blessing is a designation that has no rules meaning other than to act as
+    ///          a marker that other rules and effects can identify. Any number of players may have
+    ///          the city’s blessing at the same time.
+    CitysBlessing,
+}
+
+/// The side a coin lands on when it is flipped.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CoinResult {
+    Heads,
+    Tails,
+}
+
+/// 705.1. To flip a coin for an object that cares whether a player wins or loses the flip, the
+///        player flips the coin and calls “heads” or “tails.” [...]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct CoinCall {
+    pub(crate) caller: PlayerId,
+    pub(crate) call: CoinResult,
+}
+
+/// How the results of rolling several dice at once are combined into a single result.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum DiceRoll {
+    /// A single die is rolled, e.g. “Roll a d20.”
+    Single,
+    /// The specified number of dice are rolled and only the highest result counts, e.g. “Roll
+    /// two d20 and ignore the lower roll.”
+    KeepHighest(u32),
+}
+
 /// 400.1. A zone is a place where objects can be during a game. There are normally seven zones:
 ///        library, hand, battlefield, graveyard, stack, exile, and command. Some older cards also
 ///        use the ante zone. Each player has their own library, hand, and graveyard. The other
 ///        zones are shared by all players.
-#[derive(PartialEq, Eq)]
-pub(crate) enum Zone {
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Hash)]
+pub enum Zone {
     Library(PlayerId),
     Hand(PlayerId),
     Battlefield,
@@ -976,6 +2951,130 @@ pub(crate) enum Zone {
     Stack,
     Exile,
     Command,
+    /// The zone cards are put into when playing for ante. Only games that are played for ante use
+    /// it.
+    Ante,
+}
+
+/// 702.1. Most abilities describe exactly what they do in the card’s rules text. Some, though, are
+///        very common or would require too much space to define on the card. In these cases, the
+///        object lists only the name of the ability as a “keyword”; sometimes reminder text
+///        summarizes the game rule.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Hash)]
+pub(crate) enum KeywordAbility {
+    /// 702.2b A creature with toughness greater than 0 that’s been dealt damage by a source with
+    ///        deathtouch since the last time state-based actions were checked is destroyed as a
+    ///        state-based action. See rule 704.
+    Deathtouch,
+    /// 702.131b Ascend on a permanent represents a static ability. It means “Any time you control
+    ///          ten or more permanents and you don’t have the city’s blessing, you get the city’s
+    ///          blessing for the rest of the game.”
+    Ascend,
+    /// 702.3b A creature with defender can’t attack.
+    Defender,
+    /// 702.7b A creature with first strike deals combat damage before creatures without first
+    ///        strike. [...]
+    FirstStrike,
+    /// 702.8a Flash is a static ability that functions in any zone from which you could play the
+    ///        card it’s on. “Flash” means “You may play this card any time you could cast an
+    ///        instant.”
+    Flash,
+    /// 702.9b A creature with flying can’t be blocked except by creatures with flying and/or
+    ///        reach. [...]
+    Flying,
+    /// 702.10b If a creature has haste, it can attack even if it hasn’t been under its
+    ///         controller’s control continuously since their most recent turn began.
+    Haste,
+    /// 702.12b A permanent with indestructible can’t be destroyed. Such permanents aren’t destroyed
+    ///         by lethal damage, and they ignore the state-based action that checks for lethal
+    ///         damage (see rule 704.5g).
+    Indestructible,
+    /// 702.15b Damage dealt by a source with lifelink causes that source’s controller to gain that
+    ///         much life (in addition to any other results that damage causes). See rule 120.3.
+    Lifelink,
+    /// 702.16a Protection is a static ability, written “Protection from [quality].” This quality
+    ///         is usually a color (as in “protection from black”) but can be any characteristic
+    ///         value or information.
+    Protection(ProtectionQuality),
+    /// 702.17b A creature with reach can block creatures with flying.
+    Reach,
+    /// 702.19b The controller of an attacking creature with trample first assigns damage to the
+    ///         creature(s) blocking it. [...]
+    Trample,
+    /// 702.20b Attacking doesn’t cause creatures with vigilance to tap.
+    Vigilance,
+    /// 702.40a Storm is a triggered ability that functions on the stack. “Storm” means “When you
+    ///         cast this spell, copy it for each other spell that was cast before it this turn. If
+    ///         the spell has any targets, you may choose new targets for any of the copies.”
+    Storm,
+    /// 702.34a Flashback appears on some instants and sorceries. It represents two static
+    ///         abilities: one that functions while the card is in a player’s graveyard and another
+    ///         that functions while the card is on the stack. [...]
+    Flashback(ManaCost),
+    /// 702.49a Ninjutsu is an activated ability that functions only while the card with ninjutsu
+    ///         is in a player’s hand. “Ninjutsu [cost]” means “[Cost], Reveal this card from your
+    ///         hand, Return an unblocked attacking creature you control to its owner’s hand: Put
+    ///         this card onto the battlefield from your hand tapped and attacking.”
+    Ninjutsu(ManaCost),
+    /// 702.51a Convoke is a static ability that functions while the spell with convoke is on the
+    ///         stack. [...]
+    Convoke,
+    /// 702.66a Delve is a static ability that functions while the spell with delve is on the
+    ///         stack. [...]
+    Delve,
+    /// 702.108a Prowess is a triggered ability. “Prowess” means “Whenever you cast a noncreature
+    ///          spell, this creature gets +1/+1 until end of turn.”
+    Prowess,
+    /// 702.143a Foretell is a keyword that functions while the card with foretell is in a player’s
+    ///          hand. Any time a player has priority during their turn, that player may pay {2} and
+    ///          exile a card with foretell from their hand face down. [...]
+    Foretell(ManaCost),
+    /// 702.140a Mutate appears on some creature cards. It represents a static ability that
+    ///          functions while the spell with mutate is on the stack. “Mutate [cost]” means “You
+    ///          may pay [cost] rather than pay this spell’s mana cost. If you do, it becomes a
+    ///          mutating creature spell and targets a non-Human creature with the same owner as
+    ///          this spell.”
+    Mutate(ManaCost),
+    /// 702.145b Daybound is found on the front faces of some transforming double-faced cards and
+    ///          represents three static abilities. “Daybound” means “If it is night and this
+    ///          permanent is represented by a transforming double-faced card, it enters the
+    ///          battlefield transformed,” “As it becomes night, if this permanent is front face up,
+    ///          transform it,” and “This permanent can’t transform except due to its daybound
+    ///          ability.” [...]
+    Daybound,
+    /// 702.145e Nightbound is found on the back faces of some transforming double-faced cards and
+    ///          represents two static abilities. “Nightbound” means “As it becomes day, if this
+    ///          permanent is back face up, transform it” and “This permanent can’t transform
+    ///          except due to its nightbound ability.” [...]
+    Nightbound,
+}
+
+/// The quality a protection ability protects from. See rule 702.16a.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Hash)]
+pub(crate) enum ProtectionQuality {
+    Color(Color),
+    /// A permanent with protection from all colors has protection from each of the five colors.
+    /// Colorless sources don’t have the stated quality.
+    AllColors,
+}
+
+impl ProtectionQuality {
+    /// Returns whether a source of the specified color has the stated quality.
+    ///
+    /// 702.16b A permanent or player with protection can’t be targeted by spells with the stated
+    ///         quality and can’t be targeted by abilities from a source with the stated quality.
+    ///
+    /// 702.16e Any damage that would be dealt by sources that have the stated quality to a
+    ///         permanent or player with protection is prevented.
+    ///
+    /// 702.16f Attacking creatures with protection can’t be blocked by creatures that have the
+    ///         stated quality.
+    pub(crate) fn applies_to(&self, source: &ColorIdentity) -> bool {
+        match self {
+            ProtectionQuality::Color(color) => source.contains(*color),
+            ProtectionQuality::AllColors => *source != ColorIdentity::Colorless,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -985,6 +3084,7 @@ mod tests {
     use pretty_assertions::assert_eq;
 
     use super::*;
+    use crate::consistency::read_corpus;
 
     #[test]
     fn color_order_is_stable() {
@@ -1058,4 +3158,484 @@ mod tests {
             ColorIdentity::Multicolored([Color::Blue, Color::Black, Color::Red].into(),)
         );
     }
+
+    #[test]
+    fn protection_from_a_color_applies_to_sources_of_that_color() {
+        let protection = ProtectionQuality::Color(Color::Red);
+        assert!(protection.applies_to(&ColorIdentity::Monocolored(Color::Red)));
+        assert!(protection.applies_to(&ColorIdentity::Multicolored(
+            [Color::Red, Color::Green].into()
+        )));
+        assert!(!protection.applies_to(&ColorIdentity::Monocolored(Color::Green)));
+        assert!(!protection.applies_to(&ColorIdentity::Colorless));
+    }
+
+    #[test]
+    fn protection_from_all_colors_ignores_colorless_sources() {
+        let protection = ProtectionQuality::AllColors;
+        assert!(protection.applies_to(&ColorIdentity::Monocolored(Color::White)));
+        assert!(protection.applies_to(&ColorIdentity::Multicolored(
+            [Color::Blue, Color::Black].into()
+        )));
+        assert!(!protection.applies_to(&ColorIdentity::Colorless));
+    }
+
+    #[test]
+    fn decks_with_banned_cards_are_invalid() {
+        let banned = Card::builder()
+            .name(Name("Hogaak, Arisen Necropolis".into()))
+            .legalities([(Format::Modern, Legality::Banned)].into())
+            .build()
+            .expect("Failed to build the card.");
+        let other = Card::builder().build().expect("Failed to build the card.");
+        let deck = Deck(vec![Arc::new(other), Arc::new(banned)]);
+
+        let error = deck.validate(Format::Modern).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Hogaak, Arisen Necropolis is banned in Modern."
+        );
+        assert_eq!(deck.validate(Format::Vintage), Ok(()));
+    }
+
+    #[test]
+    fn restricted_cards_are_limited_to_a_single_copy() {
+        let restricted = Arc::new(
+            Card::builder()
+                .name(Name("Ancestral Recall".into()))
+                .legalities([(Format::Vintage, Legality::Restricted)].into())
+                .build()
+                .expect("Failed to build the card."),
+        );
+
+        assert_eq!(
+            Deck(vec![Arc::clone(&restricted)]).validate(Format::Vintage),
+            Ok(())
+        );
+        assert_eq!(
+            Deck(vec![Arc::clone(&restricted), restricted]).validate(Format::Vintage),
+            Err(DeckError::Restricted {
+                name: "Ancestral Recall".into(),
+                format: Format::Vintage
+            })
+        );
+    }
+
+    #[test]
+    fn mana_values_count_every_symbol() {
+        // Build a card with mana cost {X}{2}{B}{B}.
+        let card = Card::builder()
+            .mana_cost(ManaCost(vec![
+                ManaSymbol::Variable,
+                ManaSymbol::Generic(2),
+                ManaSymbol::Colored(Color::Black),
+                ManaSymbol::Colored(Color::Black),
+            ]))
+            .build()
+            .expect("Failed to build the card.");
+        assert_eq!(card.mana_value(), 4);
+        assert_eq!(Card::default().mana_value(), 0);
+    }
+
+    #[test]
+    fn deck_statistics_are_computed_from_the_cards() {
+        let deck = Deck::from(&[
+            ("Forest", 24),
+            ("Llanowar Elves", 16),
+            ("Soul-Guide Lantern", 12),
+            ("Polukranos, Unchained", 8),
+        ])
+        .expect("Could not create the deck.");
+
+        let statistics = deck.statistics();
+        assert_eq!(statistics.card_count, 60);
+        assert_eq!(statistics.mana_curve, [(1, 28), (4, 8)].into());
+        assert_eq!(
+            statistics.color_symbols,
+            [(Color::Black, 8), (Color::Green, 24)].into()
+        );
+        assert_eq!(
+            (statistics.lands, statistics.creatures, statistics.spells),
+            (24, 24, 36)
+        );
+        assert!((statistics.average_mana_value - 60.0 / 36.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn probability_of_drawing_a_card_is_hypergeometric() {
+        let deck = Deck::from(&[("Forest", 24), ("Llanowar Elves", 36)])
+            .expect("Could not create the deck.");
+        let is_forest = |card: &Card| card.name.0 == "Forest";
+
+        // 1 - C(36, 7) / C(60, 7)
+        assert!((deck.probability_drawn(is_forest, 7) - 0.978_385_472_740_882).abs() < 1e-9);
+        assert!(deck.probability_drawn(is_forest, 0).abs() < 1e-9);
+        assert!((deck.probability_drawn(is_forest, 37) - 1.0).abs() < 1e-9);
+        assert!(deck.probability_drawn(|it| it.name.0 == "Plains", 60).abs() < 1e-9);
+    }
+
+    #[test]
+    fn every_other_color_is_either_an_ally_or_an_enemy() {
+        assert_eq!(Color::White.allies(), [Color::Blue, Color::Green]);
+        assert_eq!(Color::White.enemies(), [Color::Black, Color::Red]);
+        for color in Color::ALL {
+            let mut related = BTreeSet::from([color]);
+            related.extend(color.allies());
+            related.extend(color.enemies());
+            assert_eq!(related, BTreeSet::from(Color::ALL));
+            for ally in color.allies() {
+                assert!(ally.allies().contains(&color));
+            }
+            for enemy in color.enemies() {
+                assert!(enemy.enemies().contains(&color));
+            }
+        }
+    }
+
+    #[test]
+    fn color_pairs_are_allied_when_their_colors_are_allies() {
+        let allied = ColorPair::ALL
+            .into_iter()
+            .filter(|it| it.is_allied())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            allied,
+            vec![
+                ColorPair::WhiteBlue,
+                ColorPair::BlueBlack,
+                ColorPair::BlackRed,
+                ColorPair::RedGreen,
+                ColorPair::GreenWhite,
+            ]
+        );
+        for pair in ColorPair::ALL {
+            let [first, second] = pair.colors();
+            assert_eq!(pair.is_allied(), !first.enemies().contains(&second));
+        }
+        let pairs = ColorPair::ALL
+            .into_iter()
+            .map(|it| BTreeSet::from(it.colors()))
+            .collect::<BTreeSet<_>>();
+        assert_eq!(pairs.len(), 10);
+    }
+
+    #[test]
+    fn color_triples_cover_every_combination_of_three_colors() {
+        let triples = ColorTriple::ALL
+            .into_iter()
+            .map(|it| BTreeSet::from(it.colors()))
+            .collect::<BTreeSet<_>>();
+        assert_eq!(triples.len(), 10);
+        assert!(triples.iter().all(|it| it.len() == 3));
+
+        let names = |shards: bool| {
+            ColorTriple::ALL
+                .into_iter()
+                .filter(|it| it.is_shard() == shards)
+                .map(|it| it.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(true), ["Bant", "Esper", "Grixis", "Jund", "Naya"]);
+        assert_eq!(
+            names(false),
+            ["Abzan", "Jeskai", "Sultai", "Mardu", "Temur"]
+        );
+
+        let colors = |colors: &[Color]| colors.iter().copied().collect::<BTreeSet<_>>();
+        for (triple, expected) in [
+            (ColorTriple::Bant, [Color::Green, Color::White, Color::Blue]),
+            (
+                ColorTriple::Esper,
+                [Color::White, Color::Blue, Color::Black],
+            ),
+            (ColorTriple::Grixis, [Color::Blue, Color::Black, Color::Red]),
+            (ColorTriple::Jund, [Color::Black, Color::Red, Color::Green]),
+            (ColorTriple::Naya, [Color::Red, Color::Green, Color::White]),
+            (
+                ColorTriple::Abzan,
+                [Color::White, Color::Black, Color::Green],
+            ),
+            (ColorTriple::Jeskai, [Color::Blue, Color::Red, Color::White]),
+            (
+                ColorTriple::Sultai,
+                [Color::Black, Color::Green, Color::Blue],
+            ),
+            (ColorTriple::Mardu, [Color::Red, Color::White, Color::Black]),
+            (ColorTriple::Temur, [Color::Green, Color::Blue, Color::Red]),
+        ] {
+            assert_eq!(ColorTriple::try_from(&colors(&expected)), Ok(triple));
+        }
+        assert_eq!(
+            ColorTriple::try_from(&colors(&[Color::White, Color::Blue])),
+            Err(())
+        );
+    }
+
+    #[test]
+    fn color_identities_are_exactly_their_colors() {
+        let esper = ColorIdentity::Multicolored(ColorTriple::Esper.colors().into_iter().collect());
+        assert!(esper.is_exactly(&[Color::Black, Color::White, Color::Blue]));
+        assert!(!esper.is_exactly(&[Color::White, Color::Blue]));
+        assert!(ColorIdentity::Monocolored(Color::Red).is_exactly(&[Color::Red, Color::Red]));
+        assert!(!ColorIdentity::Monocolored(Color::Red).is_exactly(&[]));
+        assert!(ColorIdentity::Colorless.is_exactly(&[]));
+    }
+
+    /// Returns the colors spelled out by the letters of mana symbols, e.g. “GW”.
+    fn colors_of(letters: &str) -> Vec<Color> {
+        letters
+            .chars()
+            .map(|letter| {
+                Color::ALL
+                    .into_iter()
+                    .find(|it| it.letter() == letter)
+                    .expect("Could not find the color.")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn colors_are_ordered_like_on_printed_cards() {
+        for expected in [
+            "WU", "UB", "BR", "RG", "GW", "WB", "UR", "BG", "RW", "GU", "GWU", "WUB", "UBR", "BRG",
+            "RGW", "WBG", "URW", "BGU", "RWB", "GUR", "WUBR", "UBRG", "BRGW", "RGWU", "GWUB",
+            "WUBRG",
+        ] {
+            let colors = colors_of(expected);
+            assert_eq!(
+                canonical_color_order(&colors.iter().copied().collect()),
+                colors,
+                "{expected} is not in the canonical order."
+            );
+        }
+    }
+
+    #[test]
+    fn multicolored_identities_and_costs_are_displayed_in_the_canonical_order() {
+        let selesnya = ColorIdentity::Multicolored(colors_of("WG").into_iter().collect());
+        assert_eq!(selesnya.to_string(), "GW");
+        assert_eq!(ColorIdentity::Monocolored(Color::Blue).to_string(), "U");
+        assert_eq!(ColorIdentity::Colorless.to_string(), "C");
+
+        let cost = ManaCost(vec![
+            ManaSymbol::Colored(Color::White),
+            ManaSymbol::Hybrid(Color::White, Color::Red),
+            ManaSymbol::Generic(1),
+            ManaSymbol::Colored(Color::Green),
+            ManaSymbol::Variable,
+            ManaSymbol::Generic(1),
+            ManaSymbol::Colored(Color::White),
+            ManaSymbol::Colorless,
+            ManaSymbol::MonocoloredHybrid(Color::White),
+        ]);
+        assert_eq!(
+            cost.normalized().to_string(),
+            "{X}{2}{C}{R/W}{2/W}{G}{W}{W}"
+        );
+        assert_eq!(cost.normalized().mana_value(), cost.mana_value());
+        assert_eq!(cost.mana_value(), 9);
+        assert_eq!(
+            ManaCost::parse("{X}{2}{C}{R/W}{2/W}{G}{W}{W}"),
+            Some(cost.normalized())
+        );
+    }
+
+    #[test]
+    fn type_lines_are_parsed_as_printed() {
+        let line = TypeLine::parse("Legendary Creature — Elf Druid")
+            .expect("Could not parse the type line.");
+        assert_eq!(line.supertype, [Supertype::Legendary].into());
+        assert_eq!(line.card_type, [CardType::Creature].into());
+        assert_eq!(
+            line.subtype,
+            [
+                Subtype::Creature(CreatureType::Elf),
+                Subtype::Creature(CreatureType::Druid)
+            ]
+            .into()
+        );
+
+        // 205.3c If a card with multiple card types has one or more subtypes, each subtype is
+        //        correlated to its appropriate card type.
+        let line = TypeLine::parse("Land Creature — Forest Dryad")
+            .expect("Could not parse the type line.");
+        assert_eq!(
+            line.subtype,
+            [
+                Subtype::Land(LandType::Basic(BasicLandType::Forest)),
+                Subtype::Creature(CreatureType::Dryad)
+            ]
+            .into()
+        );
+        assert!(TypeLine::parse("Basic Land").is_some_and(|it| it.subtype.is_empty()));
+
+        for text in [
+            "",
+            "Legendary",
+            "Creature Elf",
+            "Instant — Elf",
+            "Sorcery —— Arcane",
+        ] {
+            assert!(TypeLine::parse(text).is_none(), "{text:?} was parsed.");
+        }
+    }
+
+    #[test]
+    fn castability_depends_on_the_colors_the_lands_produce() {
+        let deck = Deck::from(&[("Forest", 30), ("Llanowar Elves", 30)])
+            .expect("Could not create the deck.");
+
+        let report = deck.castability(&ManaCost(vec![
+            ManaSymbol::Generic(2),
+            ManaSymbol::Colored(Color::Green),
+        ]));
+        assert_eq!(report.sources, BTreeMap::from([(Color::Green, 30)]));
+        assert!(report.is_castable());
+
+        let report = deck.castability(&ManaCost(vec![ManaSymbol::Colored(Color::White)]));
+        assert_eq!(report.sources, BTreeMap::from([(Color::White, 0)]));
+        assert_eq!(report.unproducible, vec![ManaSymbol::Colored(Color::White)]);
+        assert!(!report.is_castable());
+
+        let report = deck.castability(&ManaCost(vec![
+            ManaSymbol::Hybrid(Color::Green, Color::White),
+            ManaSymbol::Generic(31),
+        ]));
+        assert!(report.unproducible.is_empty());
+        assert!(!report.enough_lands);
+        assert!(deck.uncastable_cards().is_empty());
+
+        let deck =
+            Deck::from(&[("Forest", 10), ("Soulmender", 10)]).expect("Could not create the deck.");
+        let uncastable = deck.uncastable_cards();
+        assert_eq!(uncastable.len(), 10);
+        assert!(uncastable.iter().all(|it| it.name.0 == "Soulmender"));
+    }
+
+    #[test]
+    fn decks_are_built_and_edited_one_entry_at_a_time() {
+        let plains = find_card_by_name("Plains").expect("Could not find the card.");
+        let soulmender = find_card_by_name("Soulmender").expect("Could not find the card.");
+        let mut reprint = Card::clone(plains);
+        reprint.expansion_symbol.set = "M21".into();
+        reprint.collector_number = CollectorNumber(260);
+        let reprint = Arc::new(reprint);
+        let entries = |deck: &Deck| {
+            deck.iter()
+                .map(|(card, count)| {
+                    (
+                        card.name.0.clone(),
+                        card.expansion_symbol.set.clone(),
+                        count,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut deck = Deck::new();
+        assert!(deck.is_empty());
+        deck.add(plains, 10);
+        deck.add(soulmender, 4);
+        deck.add(&reprint, 2);
+        deck.add(plains, 2);
+        assert_eq!(deck.len(), 18);
+        assert_eq!(deck.count_of("Plains"), 14);
+        assert_eq!(
+            entries(&deck),
+            vec![
+                ("Plains".to_owned(), "THB".to_owned(), 12),
+                ("Soulmender".to_owned(), "M20".to_owned(), 4),
+                ("Plains".to_owned(), "M21".to_owned(), 2),
+            ]
+        );
+
+        assert_eq!(deck.remove("Soulmender", 5), 4);
+        assert_eq!(deck.count_of("Soulmender"), 0);
+        assert_eq!(deck.replace_printing(&reprint), 14);
+        assert_eq!(
+            entries(&deck),
+            vec![("Plains".to_owned(), "M21".to_owned(), 14)]
+        );
+        assert!(deck.validate(Format::Standard).is_ok());
+    }
+
+    #[test]
+    fn decklists_are_parsed_from_text() {
+        let deck = Deck::parse("# Green\n20 Forest\n\n4x Llanowar Elves\n")
+            .expect("Could not parse the deck.");
+        assert_eq!(deck.cards().len(), 24);
+        assert_eq!(deck.statistics().lands, 20);
+
+        assert_eq!(
+            Deck::parse("20 Forest\nLlanowar Elves").err(),
+            Some(EngineError::InvalidDeck(
+                "Line 2 is not of the form “[amount] [card name]”.".into()
+            ))
+        );
+        assert!(matches!(
+            Deck::parse("1 Unknown Card"),
+            Err(EngineError::Database(_))
+        ));
+    }
+
+    #[test]
+    fn card_metadata_is_passed_through_serialization() {
+        let json = serde_json::json!({
+            "scryfall_id": "bd8fa327-dd41-4737-8f19-2cf5eb1f7cdd",
+            "multiverse_ids": [443_154],
+            "artist": "Lake Hurwitz",
+        });
+        let card = Card::builder()
+            .metadata(serde_json::from_value(json.clone()).expect("Could not parse the metadata."))
+            .build()
+            .expect("Failed to build the card.");
+
+        let serialized = serde_json::to_value(&card).expect("Could not serialize the card.");
+        assert_eq!(serialized["metadata"], json);
+        let deserialized =
+            serde_json::from_value::<Card>(serialized).expect("Could not deserialize the card.");
+        assert_eq!(deserialized.metadata, card.metadata);
+
+        // Cards without metadata neither need nor emit it.
+        let card = Card::builder().build().expect("Failed to build the card.");
+        let serialized = serde_json::to_value(&card).expect("Could not serialize the card.");
+        assert!(serialized.get("metadata").is_none());
+        let deserialized =
+            serde_json::from_value::<Card>(serialized).expect("Could not deserialize the card.");
+        assert_eq!(deserialized.metadata, None);
+    }
+
+    #[test]
+    fn decklists_with_localized_names_are_parsed() {
+        let deck = Deck::parse_localized("20 Wald\n4 Elfen von Llanowar\n", Locale::German)
+            .expect("Could not parse the deck.");
+        assert_eq!(deck.cards().len(), 24);
+        assert_eq!(deck.cards()[0].name.0, "Forest");
+        assert_eq!(deck.cards()[20].name.0, "Llanowar Elves");
+
+        // Names that aren't known in the language are looked up in English.
+        let deck = Deck::parse_localized("20 森\n4 Llanowar Elves\n", Locale::Japanese)
+            .expect("Could not parse the deck.");
+        assert_eq!(deck.statistics().creatures, 4);
+        assert!(matches!(
+            Deck::parse_localized("20 Wald", Locale::Japanese),
+            Err(EngineError::Database(_))
+        ));
+    }
+
+    #[test]
+    fn decklists_in_the_regression_corpus_are_parsed_without_panicking() {
+        let inputs = read_corpus("decklist");
+        assert!(!inputs.is_empty());
+        for input in inputs {
+            // Any result is fine as long as parsing returns.
+            let _ = Deck::parse(&input);
+        }
+        assert_eq!(
+            Deck::parse("4294967296 Forest").err(),
+            Some(EngineError::InvalidDeck(
+                "Line 1 contains more than 1000 copies of a card.".into()
+            ))
+        );
+    }
 }
@@ -0,0 +1,255 @@
+use std::sync::Arc;
+
+use indexmap::IndexMap;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::core::{Card, Deck};
+
+/// The differences between two versions of the card database, e.g. before and after regenerating
+/// it from an upstream source. Cards are matched by their set and collector number, and by their
+/// name if no card of the other version has the same set and collector number.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub(crate) struct DatabaseDiff {
+    /// The cards only the new version contains, in its order.
+    pub(crate) added: Vec<CardIdentity>,
+    /// The cards only the old version contains, in its order.
+    pub(crate) removed: Vec<CardIdentity>,
+    /// The cards whose fields differ between the versions, in the order of the old version.
+    pub(crate) changed: Vec<CardChange>,
+}
+
+/// Identifies a printing of a card in one version of the card database.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub(crate) struct CardIdentity {
+    pub(crate) name: String,
+    pub(crate) set: String,
+    pub(crate) collector_number: u64,
+}
+
+impl CardIdentity {
+    fn of(card: &Card) -> Self {
+        Self {
+            name: card.name.0.clone(),
+            set: card.expansion_symbol.set.clone(),
+            collector_number: card.collector_number.0,
+        }
+    }
+}
+
+/// A card present in both versions of the card database along with the fields that changed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub(crate) struct CardChange {
+    /// The card as it is identified in the old version.
+    pub(crate) card: CardIdentity,
+    pub(crate) fields: Vec<FieldChange>,
+}
+
+/// A field of a card along with its serialized values in the old and the new version.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub(crate) struct FieldChange {
+    pub(crate) field: CardField,
+    pub(crate) old: Value,
+    pub(crate) new: Value,
+}
+
+/// The fields of a card that are compared between versions of the card database.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub(crate) enum CardField {
+    Name,
+    ManaCost,
+    TypeLine,
+    RulesText,
+    PowerToughness,
+}
+
+impl CardField {
+    const ALL: [CardField; 5] = [
+        CardField::Name,
+        CardField::ManaCost,
+        CardField::TypeLine,
+        CardField::RulesText,
+        CardField::PowerToughness,
+    ];
+
+    /// Returns the serialized value of the field of the specified card.
+    fn value(self, card: &Card) -> Value {
+        let value = match self {
+            CardField::Name => serde_json::to_value(&card.name),
+            CardField::ManaCost => serde_json::to_value(&card.mana_cost),
+            CardField::TypeLine => serde_json::to_value(&card.type_line),
+            CardField::RulesText => serde_json::to_value(&card.rules_text),
+            CardField::PowerToughness => serde_json::to_value(card.pt),
+        };
+        value.unwrap_or_default()
+    }
+}
+
+impl DatabaseDiff {
+    /// Compares the cards of an old and a new version of the card database.
+    pub(crate) fn between(old: &[Arc<Card>], new: &[Arc<Card>]) -> Self {
+        let key = |card: &Card| (card.expansion_symbol.set.clone(), card.collector_number.0);
+        let mut unmatched = new
+            .iter()
+            .map(|it| (key(it), Arc::clone(it)))
+            .collect::<IndexMap<_, _>>();
+
+        let mut pairs = Vec::new();
+        let mut removed = Vec::new();
+        for card in old {
+            match unmatched.shift_remove(&key(card)) {
+                Some(other) => pairs.push((Arc::clone(card), other)),
+                None => removed.push(Arc::clone(card)),
+            }
+        }
+        // Cards that moved to another set or collector number are still found by their name.
+        removed.retain(|card| {
+            let Some(index) = unmatched
+                .values()
+                .position(|other| other.name.0 == card.name.0)
+            else {
+                return true;
+            };
+            let (_, other) = unmatched
+                .shift_remove_index(index)
+                .expect("Could not remove the matched card.");
+            pairs.push((Arc::clone(card), other));
+            false
+        });
+        let position = |card: &Arc<Card>| old.iter().position(|it| Arc::ptr_eq(it, card));
+        pairs.sort_by_key(|(card, _)| position(card));
+
+        let changed = pairs
+            .into_iter()
+            .filter_map(|(old, new)| {
+                let fields = CardField::ALL
+                    .into_iter()
+                    .filter_map(|field| {
+                        let (old, new) = (field.value(&old), field.value(&new));
+                        (old != new).then_some(FieldChange { field, old, new })
+                    })
+                    .collect::<Vec<_>>();
+                (!fields.is_empty()).then(|| CardChange {
+                    card: CardIdentity::of(&old),
+                    fields,
+                })
+            })
+            .collect();
+        Self {
+            added: unmatched.values().map(|it| CardIdentity::of(it)).collect(),
+            removed: removed.iter().map(|it| CardIdentity::of(it)).collect(),
+            changed,
+        }
+    }
+
+    /// Returns the names of the cards in the deck that were removed or renamed, so that the deck
+    /// can't be loaded by name anymore. Each name is listed once in the order of the deck.
+    pub(crate) fn breaking_for(&self, deck: &Deck) -> Vec<String> {
+        let renamed = self.changed.iter().filter_map(|it| {
+            it.fields
+                .iter()
+                .any(|it| it.field == CardField::Name)
+                .then_some(&it.card)
+        });
+        let missing = self.removed.iter().chain(renamed).collect::<Vec<_>>();
+        let mut names = Vec::<String>::new();
+        for card in deck.cards() {
+            let name = &card.name.0;
+            if missing.iter().any(|it| it.name == *name) && !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+        names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use super::*;
+    use crate::game::CardDatabase;
+
+    fn identity(name: &str, collector_number: u64) -> CardIdentity {
+        CardIdentity {
+            name: name.into(),
+            set: "TST".into(),
+            collector_number,
+        }
+    }
+
+    #[test]
+    fn databases_are_diffed_by_printing_and_by_name() {
+        let old = CardDatabase::from_json(include_str!("./fixtures/database_diff/old.json"))
+            .expect("Could not parse the old database.");
+        let new = CardDatabase::from_json(include_str!("./fixtures/database_diff/new.json"))
+            .expect("Could not parse the new database.");
+
+        let diff = old.diff(&new);
+        assert_eq!(
+            diff,
+            DatabaseDiff {
+                added: vec![identity("Island", 6)],
+                removed: vec![identity("Forest", 3)],
+                changed: vec![
+                    CardChange {
+                        card: identity("Grizzly Bears", 1),
+                        fields: vec![FieldChange {
+                            field: CardField::PowerToughness,
+                            old: json!({"power": {"Fixed": 2}, "toughness": {"Fixed": 2}}),
+                            new: json!({"power": {"Fixed": 3}, "toughness": {"Fixed": 3}}),
+                        }],
+                    },
+                    CardChange {
+                        card: identity("Shock", 2),
+                        fields: vec![
+                            FieldChange {
+                                field: CardField::Name,
+                                old: json!("Shock"),
+                                new: json!("Zap"),
+                            },
+                            FieldChange {
+                                field: CardField::ManaCost,
+                                old: json!([{"Colored": "Red"}]),
+                                new: json!([{"Generic": 1}, {"Colored": "Red"}]),
+                            },
+                        ],
+                    },
+                    CardChange {
+                        card: identity("Llanowar Elves", 4),
+                        fields: vec![FieldChange {
+                            field: CardField::TypeLine,
+                            old: json!({
+                                "card_type": ["Creature"],
+                                "subtype": [{"Creature": "Elf"}],
+                                "supertype": [],
+                            }),
+                            new: json!({
+                                "card_type": ["Creature"],
+                                "subtype": [{"Creature": "Elf"}, {"Creature": "Druid"}],
+                                "supertype": [],
+                            }),
+                        }],
+                    },
+                ],
+            }
+        );
+        assert_eq!(new.diff(&new), DatabaseDiff::default());
+    }
+
+    #[test]
+    fn decks_with_removed_or_renamed_cards_are_flagged() {
+        let old = CardDatabase::from_json(include_str!("./fixtures/database_diff/old.json"))
+            .expect("Could not parse the old database.");
+        let new = CardDatabase::from_json(include_str!("./fixtures/database_diff/new.json"))
+            .expect("Could not parse the new database.");
+        let diff = old.diff(&new);
+
+        let deck = Deck::parse("20 Forest\n4 Llanowar Elves\n4 Soulmender")
+            .expect("Could not parse the deck.");
+        assert_eq!(diff.breaking_for(&deck), vec!["Forest".to_owned()]);
+        let deck = Deck::parse("4 Llanowar Elves").expect("Could not parse the deck.");
+        assert!(diff.breaking_for(&deck).is_empty());
+    }
+}
@@ -0,0 +1,480 @@
+use std::slice::Iter;
+
+use crate::{
+    abilities::{Comparison, Effect, ObjectFilter, Quantity},
+    core::{
+        CardType, Color, CounterKind, KeywordAbility, Mana, ManaSymbol, ProtectionQuality,
+        Supertype,
+    },
+};
+
+/// The names and numbers a description of effects refers to. They are captured as the effects are
+/// applied, so the description remains valid after the objects it names have left the game.
+pub(crate) struct DescriptionContext<'a> {
+    /// The name of the source of the effects.
+    pub(crate) source: &'a str,
+    /// The name of the player controlling the effects.
+    pub(crate) controller: &'a str,
+    /// The names of the targets in the order the effects use them.
+    pub(crate) targets: &'a [String],
+    /// Determines the value of a quantity, or returns `None` if it can't be determined.
+    pub(crate) quantity: &'a dyn Fn(&Quantity) -> Option<u64>,
+}
+
+/// Describes the effects of an ability with the names and numbers of the context filled in, e.g.
+/// “Perilous Myr: Perilous Myr deals 2 damage to Bob.”, or returns `None` if there are no effects.
+pub(crate) fn describe_effects(effects: &[Effect], context: &DescriptionContext) -> Option<String> {
+    if effects.is_empty() {
+        return None;
+    }
+    let sentences = sentences(effects, context, &mut context.targets.iter());
+    Some(format!("{}: {}", context.source, sentences.join(" ")))
+}
+
+/// Describes each effect as a sentence, using up the names of the targets in order.
+fn sentences(
+    effects: &[Effect],
+    context: &DescriptionContext,
+    targets: &mut Iter<'_, String>,
+) -> Vec<String> {
+    effects
+        .iter()
+        .filter_map(|effect| sentence(effect, context, targets))
+        .collect()
+}
+
+/// Describes a single effect by the first of the helpers that knows it, or returns `None` if
+/// none of them does.
+fn sentence(
+    effect: &Effect,
+    context: &DescriptionContext,
+    targets: &mut Iter<'_, String>,
+) -> Option<String> {
+    player_sentence(effect, context)
+        .or_else(|| target_sentence(effect, context, targets))
+        .or_else(|| filter_sentence(effect, context))
+        .or_else(|| source_sentence(effect, context))
+}
+
+/// Describes an effect on the controller of the effects, e.g. “Alice draws 2 cards.”
+fn player_sentence(effect: &Effect, context: &DescriptionContext) -> Option<String> {
+    let player = context.controller;
+    Some(match effect {
+        Effect::GainLife(amount) => format!("{player} gains {amount} life."),
+        Effect::LoseLife(amount) => format!("{player} loses {amount} life."),
+        Effect::DrawCards(quantity) => match (context.quantity)(quantity) {
+            Some(amount) => format!("{player} draws {}.", count(amount, "card")),
+            None => format!("{player} draws cards."),
+        },
+        Effect::ShuffleLibrary => format!("{player} shuffles their library."),
+        Effect::GetCounters(kind, amount) => format!(
+            "{player} gets {}.",
+            count(*amount, &format!("{} counter", counter_name(kind)))
+        ),
+        Effect::WinGame => format!("{player} wins the game."),
+        Effect::SacrificeUnlessPay(cost) => format!(
+            "{player} sacrifices {} unless they pay {cost}.",
+            context.source
+        ),
+        Effect::ShuffleGraveyardIntoLibrary => {
+            format!("{player} shuffles their graveyard into their library.")
+        }
+        Effect::May {
+            effects,
+            when_you_do,
+        } => {
+            // The reflexive ability chooses its targets only once the option was taken.
+            let optional = sentences(effects, context, &mut [].iter()).join(" ");
+            if when_you_do.is_empty() {
+                return Some(format!("{player} may: {optional}"));
+            }
+            let reflexive = sentences(when_you_do, context, &mut [].iter()).join(" ");
+            format!("{player} may: {optional} When they do: {reflexive}")
+        }
+        _ => return None,
+    })
+}
+
+/// Describes an effect on the next target, e.g. “Destroy Grizzly Bears.”
+fn target_sentence(
+    effect: &Effect,
+    context: &DescriptionContext,
+    targets: &mut Iter<'_, String>,
+) -> Option<String> {
+    let (source, player) = (context.source, context.controller);
+    let mut target = || {
+        targets
+            .next()
+            .map_or("a target", String::as_str)
+            .to_string()
+    };
+    Some(match effect {
+        Effect::DealDamage(quantity) => {
+            let amount =
+                (context.quantity)(quantity).map_or_else(|| "X".to_string(), |it| it.to_string());
+            format!("{source} deals {amount} damage to {}.", target())
+        }
+        Effect::DestroyTarget(_) => format!("Destroy {}.", target()),
+        Effect::PhaseOut => format!("{} phases out.", target()),
+        Effect::TapAndStun(amount) => format!(
+            "Tap {}. Put {} on it.",
+            target(),
+            count(*amount, "stun counter")
+        ),
+        Effect::LoseAllAbilitiesUntilEndOfTurn => {
+            format!("{} loses all abilities until end of turn.", target())
+        }
+        Effect::ReturnFromGraveyardToHand(_) => format!(
+            "Return {} from {player}'s graveyard to their hand.",
+            target()
+        ),
+        Effect::ExileGraveyard => format!("Exile all cards from {}'s graveyard.", target()),
+        _ => return None,
+    })
+}
+
+/// Describes an effect on every permanent a filter matches, e.g. “Destroy all creatures.”
+fn filter_sentence(effect: &Effect, context: &DescriptionContext) -> Option<String> {
+    let source = context.source;
+    Some(match effect {
+        Effect::DestroyAll(filter) => format!("Destroy all {}.", describe_filter(filter, true)),
+        Effect::ExileAll(filter) => format!("Exile all {}.", describe_filter(filter, true)),
+        Effect::DamageEach(filter, amount) => format!(
+            "{source} deals {amount} damage to each {}.",
+            describe_filter(filter, false)
+        ),
+        Effect::GainKeywordUntilEndOfTurn(filter, keyword) => format!(
+            "{} gain {} until end of turn.",
+            capitalize(&describe_filter(filter, true)),
+            keyword_name(keyword)
+        ),
+        _ => return None,
+    })
+}
+
+/// Describes an effect on the source itself or one that creates something, e.g. “Add {G}.”
+fn source_sentence(effect: &Effect, context: &DescriptionContext) -> Option<String> {
+    let source = context.source;
+    Some(match effect {
+        Effect::AddMana(mana) => {
+            let symbol = match *mana {
+                Mana::Monocolored(color) => ManaSymbol::Colored(color),
+                Mana::Colorless => ManaSymbol::Colorless,
+            };
+            format!("Add {symbol}.")
+        }
+        Effect::PumpUntilEndOfTurn { power, toughness } => {
+            format!("{source} gets {power:+}/{toughness:+} until end of turn.")
+        }
+        Effect::Blink => {
+            format!("Exile {source}, then return it to the battlefield under its owner's control.")
+        }
+        Effect::Investigate => "Investigate.".into(),
+        Effect::CreateTreasure(amount) => format!("Create {}.", count(*amount, "Treasure token")),
+        Effect::CreateFood(amount) => format!("Create {}.", count(*amount, "Food token")),
+        Effect::CreateBlood(amount) => format!("Create {}.", count(*amount, "Blood token")),
+        Effect::Explore => format!("{source} explores."),
+        Effect::SacrificeThis => format!("Sacrifice {source}."),
+        _ => return None,
+    })
+}
+
+/// Describes the permanents a filter matches, e.g. “nonland permanents your opponents control”
+/// or “green creature” if the description isn't plural.
+fn describe_filter(filter: &ObjectFilter, plural: bool) -> String {
+    let mut parts = Vec::new();
+    if let Some(supertype) = filter.excluded_supertype {
+        parts.push(format!("non{}", supertype_name(supertype)));
+    }
+    if let Some(color) = filter.color {
+        parts.push(color_name(color).into());
+    }
+    if let Some(card_type) = filter.excluded_card_type {
+        parts.push(format!("non{}", card_type_name(card_type)));
+    }
+    if let Some(subtype) = filter.subtype {
+        parts.push(subtype.name().into());
+    }
+    let noun = filter.card_type.map_or("permanent", card_type_name);
+    parts.push(if plural {
+        format!("{noun}s")
+    } else {
+        noun.into()
+    });
+
+    let mut text = parts.join(" ");
+    if filter.of_chosen_color {
+        text.push_str(" of the chosen color");
+    }
+    match filter.power {
+        Some(Comparison::AtMost(power)) => text = format!("{text} with power {power} or less"),
+        Some(Comparison::AtLeast(power)) => text = format!("{text} with power {power} or greater"),
+        None => {}
+    }
+    if filter.you_control {
+        text.push_str(" you control");
+    } else if filter.opponent_controls {
+        text.push_str(" your opponents control");
+    }
+    text
+}
+
+/// Returns the amount followed by the noun, which is pluralized unless the amount is 1.
+pub(crate) fn count(amount: u64, noun: &str) -> String {
+    let suffix = if amount == 1 { "" } else { "s" };
+    format!("{amount} {noun}{suffix}")
+}
+
+/// Returns the name of a kind of counter as printed on cards, e.g. “+1/+1”.
+pub(crate) fn counter_name(kind: &CounterKind) -> &str {
+    match kind {
+        CounterKind::PlusOnePlusOne => "+1/+1",
+        CounterKind::MinusOneMinusOne => "-1/-1",
+        CounterKind::Loyalty => "loyalty",
+        CounterKind::Charge => "charge",
+        CounterKind::Lore => "lore",
+        CounterKind::Poison => "poison",
+        CounterKind::Energy => "energy",
+        CounterKind::Experience => "experience",
+        CounterKind::Shield => "shield",
+        CounterKind::Stun => "stun",
+        CounterKind::Defense => "defense",
+        CounterKind::Other(name) => name,
+    }
+}
+
+/// Returns the name of a supertype as written in rules text, e.g. “legendary”.
+fn supertype_name(supertype: Supertype) -> &'static str {
+    match supertype {
+        Supertype::Basic => "basic",
+        Supertype::Legendary => "legendary",
+        Supertype::Ongoing => "ongoing",
+        Supertype::Snow => "snow",
+        Supertype::World => "world",
+    }
+}
+
+/// Returns the name of a color as written in rules text, e.g. “green”.
+pub(crate) fn color_name(color: Color) -> &'static str {
+    match color {
+        Color::White => "white",
+        Color::Blue => "blue",
+        Color::Black => "black",
+        Color::Red => "red",
+        Color::Green => "green",
+    }
+}
+
+/// Returns the name of a card type as written in rules text, e.g. “creature”.
+fn card_type_name(card_type: CardType) -> &'static str {
+    match card_type {
+        CardType::Artifact => "artifact",
+        CardType::Battle => "battle",
+        CardType::Conspiracy => "conspiracy",
+        CardType::Creature => "creature",
+        CardType::Dungeon => "dungeon",
+        CardType::Enchantment => "enchantment",
+        CardType::Instant => "instant",
+        CardType::Land => "land",
+        CardType::Phenomenon => "phenomenon",
+        CardType::Plane => "plane",
+        CardType::Planeswalker => "planeswalker",
+        CardType::Scheme => "scheme",
+        CardType::Sorcery => "sorcery",
+        CardType::Tribal => "tribal",
+        CardType::Vanguard => "vanguard",
+    }
+}
+
+/// Returns the name of a keyword ability as written in rules text, including its quality or
+/// cost, e.g. “first strike” or “protection from green”.
+fn keyword_name(keyword: &KeywordAbility) -> String {
+    let name = match keyword {
+        KeywordAbility::Deathtouch => "deathtouch",
+        KeywordAbility::Ascend => "ascend",
+        KeywordAbility::Defender => "defender",
+        KeywordAbility::FirstStrike => "first strike",
+        KeywordAbility::Flash => "flash",
+        KeywordAbility::Flying => "flying",
+        KeywordAbility::Haste => "haste",
+        KeywordAbility::Indestructible => "indestructible",
+        KeywordAbility::Lifelink => "lifelink",
+        KeywordAbility::Protection(ProtectionQuality::Color(color)) => {
+            return format!("protection from {}", color_name(*color));
+        }
+        KeywordAbility::Protection(ProtectionQuality::AllColors) => "protection from all colors",
+        KeywordAbility::Reach => "reach",
+        KeywordAbility::Trample => "trample",
+        KeywordAbility::Vigilance => "vigilance",
+        KeywordAbility::Storm => "storm",
+        KeywordAbility::Flashback(cost) => return format!("flashback {cost}"),
+        KeywordAbility::Ninjutsu(cost) => return format!("ninjutsu {cost}"),
+        KeywordAbility::Convoke => "convoke",
+        KeywordAbility::Delve => "delve",
+        KeywordAbility::Prowess => "prowess",
+        KeywordAbility::Foretell(cost) => return format!("foretell {cost}"),
+        KeywordAbility::Mutate(cost) => return format!("mutate {cost}"),
+        KeywordAbility::Daybound => "daybound",
+        KeywordAbility::Nightbound => "nightbound",
+    };
+    name.into()
+}
+
+/// Returns the text with its first letter in uppercase.
+fn capitalize(text: &str) -> String {
+    let mut characters = text.chars();
+    characters.next().map_or_else(String::new, |first| {
+        first.to_uppercase().chain(characters).collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::core::{CreatureType, ManaCost, Subtype};
+
+    fn describe(effects: &[Effect], targets: &[&str]) -> Option<String> {
+        let targets = targets.iter().map(ToString::to_string).collect::<Vec<_>>();
+        describe_effects(
+            effects,
+            &DescriptionContext {
+                source: "Llanowar Elves",
+                controller: "Alice",
+                targets: &targets,
+                quantity: &|it: &Quantity| match it {
+                    Quantity::Fixed(amount) => Some(*amount),
+                    _ => None,
+                },
+            },
+        )
+    }
+
+    #[test]
+    fn effects_are_described_with_the_names_and_numbers_filled_in() {
+        assert_eq!(describe(&[], &[]), None);
+        assert_eq!(
+            describe(&[Effect::AddMana(Mana::Monocolored(Color::Green))], &[]),
+            Some("Llanowar Elves: Add {G}.".into())
+        );
+        assert_eq!(
+            describe(
+                &[
+                    Effect::DealDamage(Quantity::Fixed(3)),
+                    Effect::DrawCards(Quantity::Fixed(1)),
+                ],
+                &["Bob"]
+            ),
+            Some(
+                "Llanowar Elves: Llanowar Elves deals 3 damage to Bob. Alice draws 1 card.".into()
+            )
+        );
+        assert_eq!(
+            describe(
+                &[
+                    Effect::TapAndStun(2),
+                    Effect::GetCounters(CounterKind::PlusOnePlusOne, 1),
+                ],
+                &[]
+            ),
+            Some(
+                "Llanowar Elves: Tap a target. Put 2 stun counters on it. Alice gets 1 +1/+1 \
+                 counter."
+                    .into()
+            )
+        );
+        assert_eq!(
+            describe(
+                &[Effect::DrawCards(Quantity::X), Effect::CreateTreasure(2)],
+                &[]
+            ),
+            Some("Llanowar Elves: Alice draws cards. Create 2 Treasure tokens.".into())
+        );
+    }
+
+    #[test]
+    fn filters_are_described_like_rules_text() {
+        let elves = ObjectFilter {
+            card_type: Some(CardType::Creature),
+            subtype: Some(Subtype::Creature(CreatureType::Elf)),
+            you_control: true,
+            ..ObjectFilter::default()
+        };
+        let nonland = ObjectFilter {
+            excluded_card_type: Some(CardType::Land),
+            opponent_controls: true,
+            power: Some(Comparison::AtMost(2)),
+            ..ObjectFilter::default()
+        };
+        assert_eq!(
+            describe(
+                &[
+                    Effect::GainKeywordUntilEndOfTurn(elves, KeywordAbility::FirstStrike),
+                    Effect::DestroyAll(nonland.clone()),
+                    Effect::DamageEach(nonland, 1),
+                ],
+                &[]
+            ),
+            Some(
+                "Llanowar Elves: Elf creatures you control gain first strike until end of turn. \
+                 Destroy all nonland permanents with power 2 or less your opponents control. \
+                 Llanowar Elves deals 1 damage to each nonland permanent with power 2 or less \
+                 your opponents control."
+                    .into()
+            )
+        );
+    }
+
+    #[test]
+    fn types_and_keywords_are_named_as_printed() {
+        let workers = ObjectFilter {
+            card_type: Some(CardType::Creature),
+            subtype: Some(Subtype::Creature(CreatureType::AssemblyWorker)),
+            excluded_supertype: Some(Supertype::Legendary),
+            color: Some(Color::Blue),
+            ..ObjectFilter::default()
+        };
+        assert_eq!(
+            describe(
+                &[Effect::GainKeywordUntilEndOfTurn(
+                    workers,
+                    KeywordAbility::Protection(ProtectionQuality::Color(Color::Red)),
+                )],
+                &[]
+            ),
+            Some(
+                "Llanowar Elves: Nonlegendary blue Assembly-Worker creatures gain protection \
+                 from red until end of turn."
+                    .into()
+            )
+        );
+    }
+
+    #[test]
+    fn optional_effects_are_described_with_their_reflexive_effects() {
+        assert_eq!(
+            describe(
+                &[Effect::May {
+                    effects: vec![Effect::SacrificeThis],
+                    when_you_do: vec![Effect::DealDamage(Quantity::Fixed(2))],
+                }],
+                &["Bob"]
+            ),
+            Some(
+                "Llanowar Elves: Alice may: Sacrifice Llanowar Elves. When they do: Llanowar \
+                 Elves deals 2 damage to a target."
+                    .into()
+            )
+        );
+        assert_eq!(
+            describe(
+                &[Effect::SacrificeUnlessPay(ManaCost(vec![
+                    ManaSymbol::Generic(2)
+                ]))],
+                &[]
+            ),
+            Some("Llanowar Elves: Alice sacrifices Llanowar Elves unless they pay {2}.".into())
+        );
+    }
+}
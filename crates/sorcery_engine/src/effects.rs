@@ -0,0 +1,193 @@
+use hecs::Entity;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    combat::AttackConstraint,
+    core::{KeywordAbility, PlayerId},
+};
+
+/// 613.7. Within a layer or sublayer, determining which order effects are applied in is usually
+///        done using a timestamp system. An effect with an earlier timestamp is applied before an
+///        effect with a later timestamp.
+///
+/// Timestamps are handed out by the game in increasing order and are also attached to objects as a
+/// component whenever they enter a zone.
+#[derive(
+    Copy, Clone, Default, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Hash,
+)]
+pub(crate) struct Timestamp(pub(crate) u64);
+
+/// 611.1. A continuous effect modifies characteristics of objects, modifies control of objects, or
+///        affects players or the rules of the game, for a fixed or indefinite period.
+pub(crate) struct ContinuousEffect {
+    /// The object that generated the effect, if any.
+    pub(crate) source: Option<Entity>,
+    /// The player that controlled the spell or ability that generated the effect.
+    pub(crate) controller: PlayerId,
+    pub(crate) duration: Duration,
+    pub(crate) kind: EffectKind,
+}
+
+/// 611.2a A continuous effect generated by the resolution of a spell or ability lasts as long as
+///        stated by the spell or ability creating it (such as “until end of turn”). If no duration
+///        is stated, it lasts until the end of the game.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum Duration {
+    /// 514.2. [...] all “until end of turn” and “this turn” effects end.
+    UntilEndOfTurn,
+    /// Ends as the next turn of the controller of the effect begins.
+    UntilYourNextTurn,
+    /// Ends as soon as the source of the effect leaves the battlefield.
+    WhileSourceOnBattlefield,
+    /// 511.3. As the end of combat step ends, all “until end of combat” effects end.
+    UntilEndOfCombat,
+    /// Ends as soon as the condition stops being true, even if it becomes true again later. An
+    /// effect whose condition isn't true as it is created ends right away.
+    AsLongAs(DurationCondition),
+    Permanent,
+}
+
+/// A condition a continuous effect lasts for as long as it is true.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum DurationCondition {
+    /// Represents “for as long as you control [this permanent]”.
+    YouControlSource,
+    /// Represents “for as long as [permanent] remains tapped”, e.g. the enchanted creature.
+    RemainsTapped(Entity),
+}
+
+/// The modification a continuous effect applies.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum EffectKind {
+    /// 613.1b Layer 2: Control-changing effects are applied.
+    ChangeControl {
+        target: Entity,
+        controller: PlayerId,
+    },
+    /// 613.1f Layer 6: Ability-adding effects, keyword counters, ability-removing effects, and
+    ///        effects that say an object can’t have an ability are applied.
+    AddKeyword {
+        target: Entity,
+        keyword: KeywordAbility,
+    },
+    /// Represents e.g. “Target creature loses all abilities until end of turn.”
+    LoseAllAbilities { target: Entity },
+    /// 613.4b Layer 7b: Effects that set power and/or toughness to a specific number or value are
+    ///        applied. [...]
+    SetPowerToughness {
+        target: Entity,
+        power: i64,
+        toughness: i64,
+    },
+    /// 613.4c Layer 7c: Effects and counters that modify power and/or toughness (but don’t set
+    ///        power and/or toughness to a specific number or value) are applied.
+    ModifyPowerToughness {
+        target: Entity,
+        power: i64,
+        toughness: i64,
+    },
+    /// 402.2. [...] Some effects modify a player’s maximum hand size or change it to a certain
+    ///        number.
+    ///
+    /// Raises or lowers the maximum hand size of a player, e.g. the hand modifier of a Vanguard.
+    ModifyMaximumHandSize { player: PlayerId, amount: i64 },
+    /// Represents “You have no maximum hand size.”
+    NoMaximumHandSize { player: PlayerId },
+    /// Represents e.g. “You can’t lose the game this turn.”
+    CantLoseGame { player: PlayerId },
+    /// Represents e.g. “Your opponents can’t win the game this turn.” for each opponent.
+    CantWinGame { player: PlayerId },
+    /// 509.1b [...] some effects may allow a creature to block more than one attacking creature.
+    ///
+    /// Represents e.g. “Target creature can block an additional creature each combat this turn.”
+    BlockAdditionalCreatures { target: Entity, amount: u64 },
+    /// Imposes a restriction or requirement on whether the creature attacks.
+    AttackConstraint {
+        target: Entity,
+        constraint: AttackConstraint,
+    },
+}
+
+/// Stores all continuous effects currently affecting the game ordered by their timestamps.
+#[derive(Default)]
+pub(crate) struct ContinuousEffects(Vec<(Timestamp, ContinuousEffect)>);
+
+impl ContinuousEffects {
+    /// Returns an iterator over all active effects and their timestamps, starting with the earliest
+    /// timestamp.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (Timestamp, &ContinuousEffect)> {
+        self.0
+            .iter()
+            .map(|(timestamp, effect)| (*timestamp, effect))
+    }
+
+    pub(crate) fn add(&mut self, effect: ContinuousEffect, timestamp: Timestamp) {
+        let index = self.0.partition_point(|(it, _)| *it <= timestamp);
+        self.0.insert(index, (timestamp, effect));
+    }
+
+    /// 514.2. Second, the following actions happen simultaneously: all damage marked on
+    ///        permanents (including phased-out permanents) is removed and all “until end of turn”
+    ///        and “this turn” effects end. This turn-based action doesn’t use the stack.
+    pub(crate) fn end_of_turn(&mut self) -> Vec<ContinuousEffect> {
+        self.remove_where(|it| it.duration == Duration::UntilEndOfTurn)
+    }
+
+    /// Ends all effects lasting until the next turn of the specified player, which is about to
+    /// begin.
+    pub(crate) fn start_of_turn(&mut self, active_player: PlayerId) -> Vec<ContinuousEffect> {
+        self.remove_where(|it| {
+            it.duration == Duration::UntilYourNextTurn && it.controller == active_player
+        })
+    }
+
+    /// 511.3. As the end of combat step ends, all “until end of combat” effects end.
+    pub(crate) fn end_of_combat(&mut self) -> Vec<ContinuousEffect> {
+        self.remove_where(|it| it.duration == Duration::UntilEndOfCombat)
+    }
+
+    /// 800.4a [...] any effects which give that player control of any objects or players end. [...]
+    pub(crate) fn player_left(&mut self, player: PlayerId) -> Vec<ContinuousEffect> {
+        self.remove_where(|it| {
+            matches!(it.kind, EffectKind::ChangeControl { controller, .. } if controller == player)
+        })
+    }
+
+    /// Ends all effects lasting as long as the specified object remains on the battlefield.
+    pub(crate) fn source_left_battlefield(&mut self, source: Entity) -> Vec<ContinuousEffect> {
+        self.remove_where(|it| {
+            it.duration == Duration::WhileSourceOnBattlefield && it.source == Some(source)
+        })
+    }
+
+    /// Ends all effects lasting for as long as a condition is true whose condition isn't true
+    /// anymore.
+    pub(crate) fn conditions_changed(
+        &mut self,
+        holds: impl Fn(&ContinuousEffect, DurationCondition) -> bool,
+    ) -> Vec<ContinuousEffect> {
+        self.remove_where(|it| match it.duration {
+            Duration::AsLongAs(condition) => !holds(it, condition),
+            _ => false,
+        })
+    }
+
+    /// Removes all effects matching the predicate and returns them in timestamp order, so that the
+    /// game can record them as ended.
+    fn remove_where(
+        &mut self,
+        predicate: impl Fn(&ContinuousEffect) -> bool,
+    ) -> Vec<ContinuousEffect> {
+        let (expired, remaining) = std::mem::take(&mut self.0)
+            .into_iter()
+            .partition::<Vec<_>, _>(|(_, it)| predicate(it));
+        self.0 = remaining;
+        expired
+            .into_iter()
+            .map(|(_, it)| {
+                log::debug!("Continuous effect {:?} ended.", it.kind);
+                it
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,46 @@
+use std::{error, fmt};
+
+use hecs::Entity;
+
+use crate::core::PlayerId;
+
+/// An error that occurred while processing input to the engine.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EngineError {
+    /// The card database could not be loaded or doesn't contain a requested card.
+    Database(String),
+    /// A deck can't be used to start a game.
+    InvalidDeck(String),
+    /// There is no player with the specified id in the game.
+    UnknownPlayer(PlayerId),
+    /// There is no object with the specified entity in the game.
+    UnknownObject(Entity),
+    /// An object can't be put into or taken out of a zone.
+    IllegalZoneOperation(String),
+    /// An action was taken at a time it isn't allowed.
+    IllegalTiming(String),
+    /// A player isn't allowed to take an action.
+    IllegalAction(String),
+    /// A target is missing or not legal.
+    InvalidTarget(String),
+    /// 118.3. A player can’t pay a cost without having the necessary resources to pay it fully.
+    PaymentFailed(String),
+}
+
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Database(message) => write!(f, "Card database error: {message}"),
+            Self::InvalidDeck(message) => write!(f, "Invalid deck: {message}"),
+            Self::UnknownPlayer(player) => write!(f, "There is no player with id {}.", player.0),
+            Self::UnknownObject(entity) => write!(f, "There is no object {entity:?}."),
+            Self::IllegalZoneOperation(message) => write!(f, "Illegal zone operation: {message}"),
+            Self::IllegalTiming(message) => write!(f, "Illegal timing: {message}"),
+            Self::IllegalAction(message) => write!(f, "Illegal action: {message}"),
+            Self::InvalidTarget(message) => write!(f, "Invalid target: {message}"),
+            Self::PaymentFailed(message) => write!(f, "Payment failed: {message}"),
+        }
+    }
+}
+
+impl error::Error for EngineError {}
@@ -0,0 +1,325 @@
+use hecs::{Entity, World};
+use serde::Serialize;
+
+use crate::{
+    components::CardRef,
+    core::{
+        CardMetadata, CoinCall, CoinResult, CounterKind, Counters, Designation, GameOutcome,
+        ObjectId, PlayerId, Zone,
+    },
+    turn::Step,
+};
+
+/// Something that happened during the game, recorded in the order it happened.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum GameEvent {
+    TurnBegan {
+        number: u64,
+        active_player: PlayerId,
+    },
+    StepBegan {
+        number: u64,
+        active_player: PlayerId,
+        step: Step,
+    },
+    SpellCast {
+        caster: PlayerId,
+        name: String,
+    },
+    /// The description of the effects of the ability is missing if it has none.
+    AbilityActivated {
+        player: PlayerId,
+        source: ObjectSnapshot,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+    },
+    /// 603.2. Whenever a game event or game state matches a triggered ability’s trigger event,
+    ///        that ability automatically triggers. [...]
+    ///
+    /// An ability triggered and is about to be put on the stack. Whether its controller decides to
+    /// take an optional action of it as it resolves is known as soon as it triggers.
+    AbilityTriggered {
+        controller: PlayerId,
+        source: ObjectSnapshot,
+        optional: bool,
+    },
+    /// 603.3. Once an ability has triggered, its controller puts it on the stack as an object
+    ///        that’s not a card the next time a player would receive priority. [...]
+    ///
+    /// A triggered ability is resolving, i.e. its intervening-if condition still holds and its
+    /// effects are about to be applied as described.
+    TriggeredAbilityResolved {
+        controller: PlayerId,
+        source: ObjectSnapshot,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+    },
+    /// 608.2. If the object that’s resolving is an instant spell, a sorcery spell, or an ability,
+    ///        its resolution may involve several steps. [...]
+    ///
+    /// An instant or sorcery spell is resolving and its effects are about to be applied as
+    /// described. Permanent spells are reported as entering the battlefield instead.
+    SpellResolved {
+        controller: PlayerId,
+        spell: ObjectSnapshot,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+    },
+    /// 701.17a To sacrifice a permanent, its controller moves it from the battlefield directly to
+    ///         its owner’s graveyard. [...]
+    ///
+    /// The permanent is still reported as moving to the graveyard afterwards.
+    Sacrificed {
+        player: PlayerId,
+        object: ObjectSnapshot,
+    },
+    /// 701.8a To discard a card, move it from its owner’s hand to that player’s graveyard.
+    ///
+    /// The card is still reported as moving to the graveyard afterwards.
+    Discarded {
+        player: PlayerId,
+        object: ObjectSnapshot,
+    },
+    /// 705.1. [...] If the call matches the result, that player wins the flip. Otherwise, the
+    ///        player loses the flip. [...]
+    ///
+    /// The call is missing if the flip doesn't care about winning or losing.
+    CoinFlipped {
+        player: PlayerId,
+        call: Option<CoinCall>,
+        result: CoinResult,
+    },
+    /// A player rolled one or more dice with the specified number of sides. All rolls are listed,
+    /// including the ones that were ignored to get the result.
+    DieRolled {
+        player: PlayerId,
+        sides: u32,
+        rolls: Vec<u32>,
+        result: u32,
+    },
+    /// A player got a designation for the rest of the game.
+    DesignationGained {
+        player: PlayerId,
+        designation: Designation,
+    },
+    /// An object moved to another zone or was created in a zone, in which case it has no zone it
+    /// moved from. Cards exiled face down are only revealed to their owners.
+    ZoneChanged {
+        object: ObjectSnapshot,
+        owner: PlayerId,
+        from: Option<Zone>,
+        to: Zone,
+        #[serde(skip_serializing_if = "std::ops::Not::not")]
+        face_down: bool,
+    },
+    /// 701.20a To shuffle a library or a face-down pile of cards, randomize the cards within it so
+    ///         that no player knows their order.
+    LibraryShuffled {
+        player: PlayerId,
+    },
+    DamageDealt {
+        source: ObjectSnapshot,
+        recipient: DamageRecipient,
+        amount: u64,
+    },
+    /// 122.1. A counter is a marker placed on an object or player that modifies its characteristics
+    ///        and/or interacts with a rule, ability, or effect. [...]
+    CountersAdded {
+        holder: CounterHolder,
+        kind: CounterKind,
+        amount: u64,
+    },
+    /// Counters were removed from a permanent or player, e.g. to pay a cost or by damage dealt to a
+    /// planeswalker.
+    CountersRemoved {
+        holder: CounterHolder,
+        kind: CounterKind,
+        amount: u64,
+    },
+    /// 107.14. [...] It represents one energy counter. [...]
+    ///
+    /// Energy is recorded by its own events instead of counters being added or removed, and
+    /// `total` is the energy the player has afterwards.
+    EnergyGained {
+        player: PlayerId,
+        amount: u64,
+        total: u64,
+    },
+    /// Energy was paid, e.g. as part of the cost of an activated ability.
+    EnergySpent {
+        player: PlayerId,
+        amount: u64,
+        total: u64,
+    },
+    LifeGained {
+        player: PlayerId,
+        amount: u64,
+        life: i64,
+    },
+    LifeLost {
+        player: PlayerId,
+        amount: u64,
+        life: i64,
+    },
+    /// 611.2a A continuous effect generated by the resolution of a spell or ability lasts as long
+    ///        as stated by the spell or ability creating it [...]
+    ///
+    /// A continuous effect ended, e.g. because its duration ran out or its source left the
+    /// battlefield. The source is the object that generated the effect, if any, as it was when the
+    /// effect ended.
+    EffectEnded {
+        source: Option<ObjectSnapshot>,
+        controller: PlayerId,
+    },
+    /// 104.3a A player can concede the game at any time. A player who concedes leaves the game
+    ///        immediately. That player loses the game.
+    PlayerConceded {
+        player: PlayerId,
+    },
+    /// 104.3b If a player’s life total is 0 or less, that player loses the game the next time a
+    ///        player would receive priority. [...]
+    PlayerLost {
+        player: PlayerId,
+    },
+    /// The winner of a game played for ante became the owner of the cards in the ante zone.
+    AnteWon {
+        winner: PlayerId,
+        cards: Vec<ObjectSnapshot>,
+    },
+    GameEnded {
+        outcome: GameOutcome,
+    },
+    /// 603.5. [...] The choice is made when the ability resolves. [...]
+    ///
+    /// The controller of a resolving spell or ability chose whether to take an optional action.
+    /// Mandatory actions are taken without such an event.
+    OptionChosen {
+        player: PlayerId,
+        source: ObjectSnapshot,
+        accepted: bool,
+    },
+    /// 514.1. [...] they discard enough cards to reduce their hand size to that number. [...]
+    ///
+    /// The active player chose the cards to discard down to their maximum hand size, which are
+    /// reported as they are discarded. The choice was made by the engine if it was the only legal
+    /// one and the player lets the engine make such choices.
+    DiscardsChosen {
+        player: PlayerId,
+        amount: u64,
+        automatic: bool,
+    },
+    /// 603.3d The remainder of the process for putting a triggered ability on the stack is
+    ///        identical to the process for casting a spell listed in rules 601.2c–d. [...]
+    ///
+    /// The controller of a triggered ability chose its targets, which are reported as it resolves.
+    /// The choice was made by the engine if it was the only legal one and the player lets the
+    /// engine make such choices.
+    TargetsChosen {
+        player: PlayerId,
+        source: ObjectSnapshot,
+        automatic: bool,
+    },
+}
+
+impl GameEvent {
+    /// 400.2. [...] Library and hand are hidden zones, even if all the cards in one such zone
+    ///        happen to be revealed.
+    ///
+    /// Returns the event as it may be revealed to the specified player, or to spectators if there
+    /// is none. Objects moving between hidden zones are replaced by an unidentified card unless the
+    /// player is their owner and sees them in their hand. Nobody sees the cards in a library.
+    pub(crate) fn visible_to(&self, player: Option<PlayerId>) -> GameEvent {
+        match self {
+            GameEvent::ZoneChanged {
+                owner,
+                from,
+                to,
+                face_down,
+                ..
+            } if !can_see(player, *owner, *from, *to) || (*face_down && player != Some(*owner)) => {
+                GameEvent::ZoneChanged {
+                    object: ObjectSnapshot::hidden(),
+                    owner: *owner,
+                    from: *from,
+                    to: *to,
+                    face_down: *face_down,
+                }
+            }
+            event => event.clone(),
+        }
+    }
+}
+
+/// Returns whether the specified player, or a spectator if there is none, can see the identity of an
+/// object moving between the specified zones.
+fn can_see(player: Option<PlayerId>, owner: PlayerId, from: Option<Zone>, to: Zone) -> bool {
+    let is_hidden = |zone| matches!(zone, Some(Zone::Library(_) | Zone::Hand(_)) | None);
+    if !is_hidden(from) || !is_hidden(Some(to)) {
+        return true;
+    }
+    player.is_some_and(|it| {
+        it == owner && (matches!(from, Some(Zone::Hand(_))) || matches!(to, Zone::Hand(_)))
+    })
+}
+
+/// The recipient of damage as it was right after the damage was dealt.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum DamageRecipient {
+    Permanent(ObjectSnapshot),
+    Player { player: PlayerId, life: i64 },
+}
+
+/// The permanent or player that has counters, as it was right after they were added or removed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum CounterHolder {
+    Permanent(ObjectSnapshot),
+    Player(PlayerId),
+}
+
+/// The characteristics of an object at the time of an event, which remain available after the
+/// object has left the game.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ObjectSnapshot {
+    /// The id of the object, unless it hasn't been spawned by the game.
+    pub(crate) id: Option<ObjectId>,
+    pub(crate) name: String,
+    /// The metadata of the card the object is based on, e.g. to show an image of it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) metadata: Option<CardMetadata>,
+    /// The counters that were on the object, which effects referring to them use as last known
+    /// information once the object has left the battlefield.
+    #[serde(skip)]
+    pub(crate) counters: Vec<(CounterKind, u64)>,
+}
+
+impl ObjectSnapshot {
+    /// Captures the current characteristics of the specified object.
+    pub(crate) fn of(world: &World, entity: Entity) -> Self {
+        let card = world.get::<CardRef>(entity).ok();
+        let name = card
+            .as_ref()
+            .map_or_else(|| "An unnamed object".into(), |it| it.0.name.0.clone());
+        let metadata = card.and_then(|it| it.0.metadata.clone());
+        let id = world.get::<ObjectId>(entity).ok().map(|it| *it);
+        let counters = world
+            .get::<Counters>(entity)
+            .map(|it| it.to_vec())
+            .unwrap_or_default();
+        Self {
+            id,
+            name,
+            metadata,
+            counters,
+        }
+    }
+
+    /// Returns a snapshot that doesn't reveal which object it is.
+    pub(crate) fn hidden() -> Self {
+        Self {
+            id: None,
+            name: "a card".into(),
+            metadata: None,
+            counters: Vec::new(),
+        }
+    }
+}
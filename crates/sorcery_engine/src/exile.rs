@@ -0,0 +1,97 @@
+use hecs::Entity;
+use serde::Serialize;
+
+use crate::{
+    core::{ObjectId, PlayerId},
+    events::ObjectSnapshot,
+};
+
+/// 610.3. Some one-shot effects cause an object to change zones “until” a specified event occurs.
+///        [...]
+///
+/// The event after which an object exiled by a linked exile effect returns to the battlefield.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ReturnCondition {
+    /// The object returns as the current turn ends, e.g. “Exile target creature until end of turn.”
+    EndOfTurn,
+    /// The object returns at the beginning of the next end step, e.g. “Exile target creature.
+    /// Return it to the battlefield at the beginning of the next end step.”
+    NextEndStep,
+    /// The object returns once the source of the effect leaves the battlefield, e.g. “When this
+    /// enters the battlefield, exile target creature an opponent controls until this leaves the
+    /// battlefield.”
+    SourceLeavesBattlefield,
+}
+
+/// An object exiled by a linked exile effect that waits to return to the battlefield.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct LinkedExile {
+    pub(crate) source: Entity,
+    /// The id of the object in exile. If it leaves exile in the meantime, it becomes a new object
+    /// that doesn't return.
+    pub(crate) exiled: ObjectId,
+    pub(crate) condition: ReturnCondition,
+}
+
+/// Stores all objects exiled by linked exile effects that haven't returned yet.
+#[derive(Default)]
+pub(crate) struct LinkedExiles(Vec<LinkedExile>);
+
+impl LinkedExiles {
+    pub(crate) fn add(&mut self, exile: LinkedExile) {
+        self.0.push(exile);
+    }
+
+    /// Returns whether the object with the specified id waits to return to the battlefield.
+    pub(crate) fn contains(&self, exiled: ObjectId) -> bool {
+        self.0.iter().any(|it| it.exiled == exiled)
+    }
+
+    /// Returns the source of the effect that exiled the object with the specified id, if it waits
+    /// to return to the battlefield.
+    pub(crate) fn source_of(&self, exiled: ObjectId) -> Option<Entity> {
+        self.0
+            .iter()
+            .find(|it| it.exiled == exiled)
+            .map(|it| it.source)
+    }
+
+    /// Removes and returns the exiled objects whose return condition has been met according to
+    /// the predicate, in the order they were exiled.
+    pub(crate) fn take_where(
+        &mut self,
+        predicate: impl Fn(&LinkedExile) -> bool,
+    ) -> Vec<LinkedExile> {
+        let (returning, remaining) = self.0.drain(..).partition(|it| predicate(it));
+        self.0 = remaining;
+        returning
+    }
+}
+
+/// 406.3. Exiled cards are, by default, kept face up and may be examined by any player at any
+///        time. Cards “exiled face down” can’t be examined by any player except when instructions
+///        allow it. [...]
+///
+/// A card in exile along with what effects that care about exiled cards need to know about it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub(crate) struct ExiledCard {
+    pub(crate) card: ObjectSnapshot,
+    pub(crate) owner: PlayerId,
+    pub(crate) face_down: bool,
+    /// The id of the object whose linked exile effect exiled the card, if it is waiting to return.
+    pub(crate) exiled_by: Option<ObjectId>,
+}
+
+impl ExiledCard {
+    /// Returns the card as it may be revealed to the specified player, or to spectators if there
+    /// is none. Only the owner of a card exiled face down sees which card it is.
+    pub(crate) fn visible_to(&self, player: Option<PlayerId>) -> ExiledCard {
+        if !self.face_down || player == Some(self.owner) {
+            return self.clone();
+        }
+        ExiledCard {
+            card: ObjectSnapshot::hidden(),
+            ..self.clone()
+        }
+    }
+}
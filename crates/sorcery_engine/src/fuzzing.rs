@@ -0,0 +1,186 @@
+//! Entry points for the fuzz targets in `fuzz/`, which feed arbitrary inputs to the parsers and
+//! arbitrary action sequences to a game. Each of them panics if the input revealed a bug.
+
+use crate::{
+    actions::{GameAction, Target},
+    core::{Deck, ManaCost, PlayerId, TypeLine},
+    game::Game,
+    setup::GameBuilder,
+};
+
+/// The decks of the two players of a fuzzed game.
+const DECKLISTS: [&str; 2] = [
+    "24 Plains\n18 Soulmender\n18 Steppe Lynx",
+    "24 Forest\n36 Llanowar Elves",
+];
+
+/// The number of turns after which a fuzzed game is stopped, which keeps each run short.
+const TURN_LIMIT: u64 = 20;
+
+/// Parses a mana cost, which has to be printed the way it is parsed again.
+///
+/// # Panics
+/// Panics if the printed mana cost is parsed differently.
+pub fn mana_cost(text: &str) {
+    if let Some(cost) = ManaCost::parse(text) {
+        let printed = cost.to_string();
+        assert_eq!(
+            ManaCost::parse(&printed).as_ref(),
+            Some(&cost),
+            "{text:?} was printed as {printed:?}"
+        );
+    }
+}
+
+/// Parses a type line, which has at least one card type if it is valid.
+///
+/// # Panics
+/// Panics if a type line without a card type is accepted.
+pub fn type_line(text: &str) {
+    if let Some(type_line) = TypeLine::parse(text) {
+        assert!(!type_line.card_type.is_empty(), "{text:?} has no card type");
+    }
+}
+
+/// Parses a decklist, which may be rejected but must not panic.
+pub fn decklist(text: &str) {
+    let _ = Deck::parse(text);
+}
+
+/// Plays a game seeded by the first eight bytes, in which each of the remaining bytes picks the
+/// next action. Most bytes pick one of the legal actions of the player to act so that the game
+/// progresses, while the others submit an arbitrary action that the game has to reject or handle
+/// gracefully. The game has to accept every legal action and uphold its invariants after each
+/// action.
+///
+/// # Panics
+/// Panics if the game fails, rejects a legal action or violates an invariant.
+pub fn actions(data: &[u8]) {
+    let (seed, data) = data.split_at(data.len().min(8));
+    let mut seed_bytes = [0; 8];
+    seed_bytes[..seed.len()].copy_from_slice(seed);
+    let mut builder = GameBuilder::new().seed(u64::from_le_bytes(seed_bytes));
+    for (index, decklist) in DECKLISTS.into_iter().enumerate() {
+        let deck = Deck::parse(decklist).expect("Could not parse the deck.");
+        builder = builder.player(format!("Player {index}"), deck);
+    }
+    let (mut game, players) = builder.build().expect("Could not build the game.");
+
+    let mut bytes = data.iter().copied();
+    while let Some(byte) = bytes.next() {
+        let Some(player) = game.player_to_act().expect("Could not advance the game.") else {
+            break;
+        };
+        if game.turn().number > TURN_LIMIT {
+            break;
+        }
+        let legal_actions = game
+            .legal_actions(player)
+            .expect("Could not list the legal actions.");
+        if byte < 0xF0 && !legal_actions.is_empty() {
+            let action = legal_actions[usize::from(byte) % legal_actions.len()].clone();
+            if let Err(error) = game.apply(player, action.clone()) {
+                panic!("The legal action {action:?} was rejected: {error}");
+            }
+        } else {
+            let (player, action) = arbitrary_action(&game, &players, &mut bytes);
+            let _ = game.apply(player, action);
+        }
+        if let Err(violations) = game.check_invariants() {
+            panic!("The invariants are violated: {violations:?}");
+        }
+    }
+}
+
+/// Returns an arbitrary action of an arbitrary player, which is most likely illegal.
+fn arbitrary_action(
+    game: &Game,
+    players: &[PlayerId],
+    bytes: &mut impl Iterator<Item = u8>,
+) -> (PlayerId, GameAction) {
+    let objects = game
+        .spectator_view(true)
+        .objects
+        .into_iter()
+        .map(|it| it.id)
+        .collect::<Vec<_>>();
+    let player = pick(players, bytes).unwrap_or(players[0]);
+    // Conceding ends the game, so it is picked far less often than the other actions.
+    let kind = bytes.next().map(|it| it % 16);
+    let mut object = || pick(&objects, bytes);
+    let action = match kind {
+        Some(0) => object().map(|card| GameAction::PlayLand { card }),
+        Some(1) => object().map(|spell| GameAction::CastSpell {
+            spell,
+            targets: object().map(Target::Object).into_iter().collect(),
+            x: 0,
+            modes: Vec::new(),
+        }),
+        Some(2) => object().map(|source| GameAction::ActivateAbility {
+            source,
+            index: 0,
+            targets: object().map(Target::Object).into_iter().collect(),
+        }),
+        Some(3) => Some(GameAction::DeclareAttackers {
+            attackers: object().into_iter().chain(object()).collect(),
+        }),
+        Some(4) => Some(GameAction::DeclareBlockers {
+            blocks: object().zip(object()).into_iter().collect(),
+        }),
+        Some(5) => Some(GameAction::ResolveTrigger {
+            targets: object().map(Target::Object).into_iter().collect(),
+        }),
+        Some(6) => bytes.next().map(|option| GameAction::Choose {
+            option: usize::from(option % 4),
+        }),
+        Some(15) => Some(GameAction::Concede),
+        _ => None,
+    };
+    (player, action.unwrap_or(GameAction::PassPriority))
+}
+
+/// Picks one of the items by the next byte, unless there are no items or bytes left.
+fn pick<T: Copy>(items: &[T], bytes: &mut impl Iterator<Item = u8>) -> Option<T> {
+    if items.is_empty() {
+        return None;
+    }
+    bytes.next().map(|it| items[usize::from(it) % items.len()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consistency::{read_corpus, read_corpus_bytes};
+
+    #[test]
+    fn mana_costs_in_the_regression_corpus_are_handled() {
+        for input in read_corpus("mana_cost") {
+            mana_cost(&input);
+        }
+    }
+
+    #[test]
+    fn type_lines_in_the_regression_corpus_are_handled() {
+        for input in read_corpus("type_line") {
+            type_line(&input);
+        }
+    }
+
+    #[test]
+    fn action_sequences_in_the_regression_corpus_are_handled() {
+        for input in read_corpus_bytes("actions") {
+            actions(&input);
+        }
+    }
+
+    #[test]
+    fn random_action_sequences_are_handled() {
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+        for _ in 0..20 {
+            let data = (0..400).map(|_| rng.gen()).collect::<Vec<u8>>();
+            actions(&data);
+        }
+    }
+}
@@ -1,25 +1,250 @@
-use std::collections::HashMap;
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::{Arc, LazyLock},
+};
 
 use hecs::{Entity, EntityBuilder, World};
-use once_cell::sync::Lazy;
-use rand::prelude::SliceRandom;
+use indexmap::IndexSet;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::Deserialize;
 
+#[cfg(feature = "tracing")]
+use crate::spans::TurnSpans;
 use crate::{
-    components::{Object, Owner},
-    core::{Card, Deck, Player, PlayerId, Zone},
+    abilities::{
+        Ability, ActivationCost, CountedZone, CounterAmount, Effect, EntryChoice, EntryDecision,
+        InterveningIf, Modes, ObjectFilter, Quantity, StaticAbility, TargetRequirement,
+        TriggerCondition, TriggerSubject, TriggeredAbility,
+    },
+    actions::{GameAction, Target},
+    casting::{CastingMethod, CastingOption},
+    combat::{AttackConstraint, Combat},
+    components::{
+        ActivatedAbilities, AttachedTo, Attacking, CardRef, CastWithFlashback, ChosenColor,
+        ChosenCreatureType, ChosenModes, ChosenPlayer, ChosenTargets, ChosenX, Controller,
+        EntryDecisions, FaceDown, Foretold, Keywords, MarkedDamage, MergedWith, MutatingOnto,
+        Object, OptionDecisions, Owner, PhasedOut, PreviousId, PrintedCard, RegenerationShields,
+        SpellCopy, StackedAbility, StaticAbilities, SummoningSick, Tapped, Token, Transformed,
+        TriggeredAbilities,
+    },
+    consistency,
+    core::{
+        ArtifactType, Card, CardType, CoinCall, CoinResult, Color, ColorIdentity, Cost,
+        CounterKind, Counters, CreatureType, DayNight, Deck, Designation, DiceRoll, Format,
+        GameOutcome, KeywordAbility, Legality, Locale, Loyalty, ManaCost, ManaPool, ManaSymbol,
+        ObjectId, PlaneswalkerType, Player, PlayerId, PlayerInfo, PlayerSpec, PtCharacteristic,
+        PtValue, Subtype, Supertype, TypeLine, Zone,
+    },
+    database_diff::DatabaseDiff,
+    description::{self, DescriptionContext},
+    effects::{
+        ContinuousEffect, ContinuousEffects, Duration, DurationCondition, EffectKind, Timestamp,
+    },
+    error::EngineError,
+    events::{CounterHolder, DamageRecipient, GameEvent, ObjectSnapshot},
+    exile::{ExiledCard, LinkedExile, LinkedExiles, ReturnCondition},
+    invariants::InvariantViolation,
+    layers::{TypeChangingEffect, TypeLayer},
+    library::{Library, ScryChoice},
+    payment::{self, ManaSource, PaymentPlan},
+    prevention::{Damage, PreventionEffect, PreventionEffects},
+    replacement::{
+        ReplaceableEvent, ReplacementEffect, ReplacementEffects, ShieldCounter,
+        ShuffleIntoLibraryInstead, StunCounter,
+    },
+    setup::GameBuilder,
+    snapshot::{GameInput, GameSnapshot},
+    spectator::{
+        ChoiceStatus, ObjectState, ObjectStatus, PlayerStatus, PlayerView, SpectatorDelta,
+        SpectatorView,
+    },
+    stats::GameStats,
+    tokens::PredefinedToken,
+    turn::{PriorityStop, SpellCast, Step, Stop, TurnOrder, TurnState, TurnTracker},
+    zones::ZoneManager,
 };
 
 /// A statically loaded database of all cards that can be used as templates to spawn new instances.
-static CARD_DATABASE: Lazy<Vec<Card>> = Lazy::new(|| {
-    let database = include_str!("./cards.json");
-    serde_json::from_str(database).expect("Could not initialize the card database.")
-});
+/// It is embedded in the binary format the build script converts `cards.json` into.
+static CARD_DATABASE: LazyLock<Result<CardDatabase, EngineError>> =
+    LazyLock::new(|| CardDatabase::from_msgpack(EMBEDDED_CARDS));
+
+/// The embedded card database, see `build.rs`.
+const EMBEDDED_CARDS: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/cards.msgpack"));
+
+/// 103.4. Each player draws a number of cards equal to their starting hand size, which is normally
+///        seven. [...]
+const STARTING_HAND_SIZE: usize = 7;
 
 /// Returns a reference to the first card with the specified name. In case multiple cards share the
 /// same name, i.e. lands or reprints in different sets, there is no guarantee the same card will be
 /// selected on subsequent calls.
-pub(crate) fn find_card_by_name(name: &str) -> Option<&'_ Card> {
-    CARD_DATABASE.iter().find(|it| it.name.0 == name)
+pub(crate) fn find_card_by_name(name: &str) -> Result<&'static Arc<Card>, EngineError> {
+    CARD_DATABASE
+        .as_ref()
+        .map_err(Clone::clone)?
+        .find_by_name(name)
+        .ok_or_else(|| EngineError::Database(format!("Cannot find card with name {name}.")))
+}
+
+/// Returns a reference to the first card that is printed with the specified name in the specified
+/// language.
+pub(crate) fn find_card_by_localized_name(
+    locale: Locale,
+    name: &str,
+) -> Result<&'static Arc<Card>, EngineError> {
+    CARD_DATABASE
+        .as_ref()
+        .map_err(Clone::clone)?
+        .find_by_localized_name(locale, name)
+        .ok_or_else(|| {
+            EngineError::Database(format!("Cannot find card with {locale:?} name {name}."))
+        })
+}
+
+/// Returns how well the name matches the query, if at all. Both must be lowercase.
+fn search_match(query: &str, name: &str) -> Option<SearchMatch> {
+    if name == query {
+        Some(SearchMatch::Exact)
+    } else if name.starts_with(query) {
+        Some(SearchMatch::Prefix)
+    } else if name.contains(query) {
+        Some(SearchMatch::Substring)
+    } else {
+        let max = (query.chars().count() / CHARACTERS_PER_SEARCH_TYPO).min(MAX_SEARCH_TYPOS);
+        edit_distance(query, name, max).map(SearchMatch::Typo)
+    }
+}
+
+/// Returns the number of characters that have to be inserted, deleted or replaced to turn one
+/// string into the other, unless it exceeds the maximum.
+fn edit_distance(first: &str, second: &str, max: usize) -> Option<usize> {
+    let first = first.chars().collect::<Vec<_>>();
+    let second = second.chars().collect::<Vec<_>>();
+    if first.len().abs_diff(second.len()) > max {
+        return None;
+    }
+    // The distances between the prefixes of the first string and the current prefix of the second.
+    let mut distances = (0..=first.len()).collect::<Vec<_>>();
+    for (row, &character) in (1..).zip(&second) {
+        let mut diagonal = distances[0];
+        distances[0] = row;
+        for column in 1..=first.len() {
+            let replaced = diagonal + usize::from(first[column - 1] != character);
+            diagonal = distances[column];
+            distances[column] = replaced
+                .min(distances[column] + 1)
+                .min(distances[column - 1] + 1);
+        }
+        if distances.iter().all(|&it| it > max) {
+            return None;
+        }
+    }
+    Some(distances[first.len()]).filter(|&it| it <= max)
+}
+
+/// A collection of cards that can be used as templates to spawn new instances. Cards are shared
+/// with the objects spawned from them rather than copied.
+#[derive(Deserialize)]
+#[serde(from = "Vec<Arc<Card>>")]
+pub(crate) struct CardDatabase {
+    cards: Vec<Arc<Card>>,
+    /// The lowercase name of each card at the same index, so searching doesn't need to normalize
+    /// the names over and over again.
+    normalized_names: Vec<String>,
+}
+
+impl From<Vec<Arc<Card>>> for CardDatabase {
+    fn from(cards: Vec<Arc<Card>>) -> Self {
+        let normalized_names = cards.iter().map(|it| it.name.0.to_lowercase()).collect();
+        Self {
+            cards,
+            normalized_names,
+        }
+    }
+}
+
+/// How well the name of a card matches a search query, from best to worst.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum SearchMatch {
+    Exact,
+    Prefix,
+    Substring,
+    /// The name differs from the query by the specified number of inserted, deleted or replaced
+    /// characters.
+    Typo(usize),
+}
+
+/// The number of typos a name may differ from a search query by to still be a match. Shorter
+/// queries allow fewer typos, since they would match unrelated names otherwise.
+const MAX_SEARCH_TYPOS: usize = 2;
+
+/// The number of characters of a search query per allowed typo.
+const CHARACTERS_PER_SEARCH_TYPO: usize = 4;
+
+impl CardDatabase {
+    /// Parses a database from its JSON representation.
+    pub(crate) fn from_json(json: &str) -> Result<Self, EngineError> {
+        serde_json::from_str(json).map_err(|it| EngineError::Database(it.to_string()))
+    }
+
+    /// Parses a database from its binary representation in the `MessagePack` format, e.g. the one
+    /// the build script converts `cards.json` into.
+    pub(crate) fn from_msgpack(bytes: &[u8]) -> Result<Self, EngineError> {
+        rmp_serde::from_slice(bytes).map_err(|it| EngineError::Database(it.to_string()))
+    }
+
+    /// Returns a reference to the first card with the specified name.
+    pub(crate) fn find_by_name(&self, name: &str) -> Option<&Arc<Card>> {
+        self.cards.iter().find(|it| it.name.0 == name)
+    }
+
+    /// Returns a reference to the first card that is printed with the specified name in the
+    /// specified language. The card itself keeps its English name.
+    pub(crate) fn find_by_localized_name(&self, locale: Locale, name: &str) -> Option<&Arc<Card>> {
+        if locale == Locale::English {
+            return self.find_by_name(name);
+        }
+        self.cards
+            .iter()
+            .find(|it| it.localized_names.get(&locale).is_some_and(|it| it == name))
+    }
+
+    /// Returns all cards that may be played in the specified format.
+    pub(crate) fn legal_in(&self, format: Format) -> impl Iterator<Item = &Arc<Card>> {
+        self.cards
+            .iter()
+            .filter(move |it| matches!(it.legality(format), Legality::Legal | Legality::Restricted))
+    }
+
+    /// Returns at most `limit` cards whose names match the query, ignoring case. Exact matches come
+    /// first, followed by names starting with the query, names containing it and finally names
+    /// that differ from it by a typo or two. Matches of the same quality are ordered by name.
+    pub(crate) fn search(&self, query: &str, limit: usize) -> Vec<&Arc<Card>> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return vec![];
+        }
+        let mut matches = self
+            .normalized_names
+            .iter()
+            .enumerate()
+            .filter_map(|(index, name)| Some((search_match(&query, name)?, name, index)))
+            .collect::<Vec<_>>();
+        matches.sort_unstable();
+        matches
+            .into_iter()
+            .take(limit)
+            .map(|(_, _, index)| &self.cards[index])
+            .collect()
+    }
+
+    /// Returns the differences between this version of the database and a newer one.
+    pub(crate) fn diff(&self, other: &CardDatabase) -> DatabaseDiff {
+        DatabaseDiff::between(&self.cards, &other.cards)
+    }
 }
 
 /// 100.1. These Magic rules apply to any Magic game with two or more players, including two-player
@@ -27,30 +252,208 @@ pub(crate) fn find_card_by_name(name: &str) -> Option<&'_ Card> {
 pub struct Game {
     world: World,
     players: Vec<Player>,
-    libraries: HashMap<PlayerId, Library>,
+    zones: ZoneManager,
+    turn: TurnState,
+    previous_turn: Option<TurnState>,
+    turn_order: TurnOrder,
+    /// The outcome of the game once it has ended.
+    outcome: Option<GameOutcome>,
+    /// The number of times the same game state may repeat while mandatory triggers resolve before
+    /// the game is considered to be in an unbreakable loop.
+    loop_threshold: usize,
+    /// How often each game state has been seen while mandatory actions happened since a player
+    /// last made a decision in the current turn, keyed by the fingerprint of the state.
+    loop_states: HashMap<u64, usize>,
+    /// The steps at which each player wants to receive priority instead of passing it
+    /// automatically.
+    priority_stops: HashMap<PlayerId, IndexSet<PriorityStop>>,
+    /// 117.1. [...] The player with priority may cast spells, activate abilities, and take special
+    ///        actions.
+    ///
+    /// The player who has priority in the current step of a game driven by [`Game::apply`], if
+    /// any player does.
+    priority: Option<PlayerId>,
+    /// The number of players who passed priority in succession since the last action was taken.
+    passes: usize,
+    /// Whether players lose life for the mana that empties from their mana pools, as under the
+    /// rules before Magic 2010.
+    mana_burn: bool,
+    /// 407.1. Earlier versions of the Magic rules included an ante rule as a way of playing “for
+    ///        keeps.” [...]
+    ///
+    /// Whether the game is played for ante, which is disabled by default.
+    ante: bool,
+    /// Whether the planeswalker uniqueness rule of earlier versions of the rules applies, which
+    /// is disabled by default. It was replaced by the legend rule once planeswalkers became
+    /// legendary.
+    planeswalker_uniqueness: bool,
+    /// 730.1. Day and night are designations that the game itself can have. The game starts with
+    ///        neither designation.
+    day_night: Option<DayNight>,
+    effects: ContinuousEffects,
+    prevention_effects: PreventionEffects,
+    replacement_effects: ReplacementEffects,
+    /// 616.1. If two or more replacement and/or prevention effects are attempting to modify the
+    ///        way an event affects an object or player, the affected object’s controller (or its
+    ///        owner if it has no controller) or the affected player chooses one to apply [...]
+    ///
+    /// The order the affected players chose for the effects competing to modify each kind of
+    /// event, by the descriptions of the effects.
+    replacement_orders: Vec<(ReplaceableEvent, Vec<String>)>,
+    /// The choice the player to act faces before the game goes on.
+    choice: Option<PendingChoice>,
+    linked_exiles: LinkedExiles,
+    combat: Combat,
+    /// The number of cards each player has brought into the game, all of which have to be in some
+    /// zone or part of a merged permanent. Tokens and copies of spells aren't cards and aren't
+    /// counted.
+    brought_cards: HashMap<PlayerId, usize>,
+    pending_triggers: Vec<PendingTrigger>,
+    /// The events abilities trigger on while objects are moved at the same time, which are only
+    /// checked once all of them have moved.
+    simultaneous_events: Option<Vec<TriggerEvent>>,
+    events: Vec<GameEvent>,
+    stats: GameStats,
+    /// The most recently handed out timestamp.
+    timestamp: Timestamp,
+    /// The entity currently representing the object with each id.
+    objects: HashMap<ObjectId, Entity>,
+    /// 608.2h If an effect requires information from the game (such as the number of creatures on
+    ///        the battlefield), the answer is determined only once, when the effect is applied. If
+    ///        the effect requires information from a specific object, including the source of the
+    ///        ability itself, the effect uses the current information of that object if it’s in
+    ///        the public zone it was expected to be in; if it’s no longer in that zone, or if the
+    ///        effect has moved it from a public zone to a hidden zone, the effect uses the
+    ///        object’s last known information. [...]
+    last_known_information: HashMap<ObjectId, ObjectSnapshot>,
+    /// The most recently issued object id.
+    object_id: u64,
+    /// The source of all randomness in the game, e.g. for shuffling libraries.
+    random: StdRng,
+    /// The decisions of the controller of the resolving spell or ability whether to take its
+    /// optional actions that are still to be applied, in order. Optional actions without a
+    /// decision are declined.
+    option_decisions: Vec<bool>,
+    /// Chooses the cards players discard down to their maximum hand size.
+    discard_chooser: Option<Box<DiscardChooser>>,
+    /// The builder the game was set up with, if it was, with the seed it used, see
+    /// [`Game::snapshot`].
+    setup: Option<GameBuilder>,
+    /// The inputs the game received through its public interface since it was set up.
+    inputs: Vec<GameInput>,
+    /// The spans of the current turn, phase and step the diagnostics are recorded in.
+    #[cfg(feature = "tracing")]
+    spans: TurnSpans,
 }
 
+/// Is called with a player, the cards in their hand and the number of cards they have to discard
+/// and returns the cards they discard.
+pub(crate) type DiscardChooser = dyn FnMut(PlayerId, &[Entity], usize) -> Vec<Entity> + Send;
+
 impl Game {
+    /// Creates a game for the specified number of players, who are named “Player 1”, “Player 2”
+    /// and so on.
+    #[must_use]
+    pub fn new(players: u32) -> Self {
+        Self::with_players(
+            (0..players)
+                .map(|it| PlayerInfo {
+                    name: format!("Player {}", it + 1),
+                    external_id: None,
+                })
+                .collect(),
+        )
+    }
+
+    /// Creates a game for the specified players. The players receive their ids in the order they
+    /// are specified, so the first one has the same id as “Player 1” in a game created using
+    /// [`Game::new`].
+    #[must_use]
+    pub fn new_with_players(specs: Vec<PlayerSpec>) -> Self {
+        let automatic = specs
+            .iter()
+            .map(|it| it.auto_choose_trivial)
+            .collect::<Vec<_>>();
+        let mut game = Self::with_players(
+            specs
+                .into_iter()
+                .map(|it| PlayerInfo {
+                    name: it.name,
+                    external_id: Some(it.external_id),
+                })
+                .collect(),
+        );
+        for (player, automatic) in game.players.iter_mut().zip(automatic) {
+            player.auto_choose_trivial = automatic;
+        }
+        game
+    }
+
     /// 119.1. Each player begins the game with a starting life total of 20. Some variant games have
     ///        different starting life totals.
-    pub fn new(players: u32) -> Self {
-        let players = (0..players)
-            .map(|it| Player {
+    pub(crate) fn with_players(players: Vec<PlayerInfo>) -> Self {
+        let players = (0..)
+            .zip(players)
+            .map(|(it, info)| Player {
                 id: PlayerId(it),
+                info,
                 life: 20,
-                name: format!("Player {}", it + 1),
+                counters: Counters::default(),
+                mana_pool: ManaPool::default(),
+                maximum_hand_size: 7,
+                designations: IndexSet::new(),
+                has_left: false,
+                auto_choose_trivial: false,
+                drew_from_empty_library: false,
             })
             .collect::<Vec<_>>();
 
-        let libraries = players
+        let zones = ZoneManager::new(players.iter().map(|it| it.id));
+        let priority_stops = players
             .iter()
-            .map(|it| (it.id, Library::default()))
+            .map(|it| (it.id, PriorityStop::defaults()))
             .collect();
 
         Self {
             world: World::new(),
             players,
-            libraries,
+            zones,
+            turn: TurnState::new(PlayerId(0), 1),
+            previous_turn: None,
+            turn_order: TurnOrder::default(),
+            outcome: None,
+            loop_threshold: 3,
+            loop_states: HashMap::new(),
+            priority_stops,
+            priority: None,
+            passes: 0,
+            mana_burn: false,
+            ante: false,
+            planeswalker_uniqueness: false,
+            day_night: None,
+            effects: ContinuousEffects::default(),
+            prevention_effects: PreventionEffects::default(),
+            replacement_effects: ReplacementEffects::default(),
+            replacement_orders: Vec::new(),
+            choice: None,
+            linked_exiles: LinkedExiles::default(),
+            combat: Combat::default(),
+            brought_cards: HashMap::new(),
+            pending_triggers: Vec::new(),
+            simultaneous_events: None,
+            events: Vec::new(),
+            stats: GameStats::default(),
+            timestamp: Timestamp::default(),
+            objects: HashMap::new(),
+            last_known_information: HashMap::new(),
+            object_id: 0,
+            random: StdRng::from_entropy(),
+            option_decisions: Vec::new(),
+            discard_chooser: None,
+            setup: None,
+            inputs: Vec::new(),
+            #[cfg(feature = "tracing")]
+            spans: TurnSpans::default(),
         }
     }
 
@@ -87,126 +490,14859 @@ impl Game {
     ///        then repeated until no player takes a mulligan. A player can take mulligans until
     ///        their opening hand would be zero cards, after which they may not take further
     ///        mulligans.
-    pub fn start(&mut self, decks: &HashMap<PlayerId, Deck>) {
-        assert_eq!(decks.len(), self.players.len());
-        // TODO: Implement rule 103.1. For now we just implicitly start with player 1.
+    ///
+    /// # Errors
+    /// Fails unless there is a nonempty deck for each player.
+    pub fn start(&mut self, decks: &HashMap<PlayerId, Deck>) -> Result<(), EngineError> {
+        if decks.len() != self.players.len() {
+            return Err(EngineError::InvalidDeck(format!(
+                "Expected a deck for each of the {} players but got {}.",
+                self.players.len(),
+                decks.len()
+            )));
+        }
+        // The decks are checked in a fixed order so that the same decks always result in the same
+        // error.
+        let mut ids = decks.keys().copied().collect::<Vec<_>>();
+        ids.sort();
+        for id in ids {
+            self.player(id)?;
+        }
+        for player in &self.players {
+            if decks[&player.id].cards().is_empty() {
+                return Err(EngineError::InvalidDeck(format!(
+                    "The deck of {} is empty.",
+                    player.info.name
+                )));
+            }
+        }
+        // TODO: Implement rule 103.1. For now the starting player is player 1 unless another one
+        //       has been chosen using `set_starting_player`.
+        self.stats.record_turn(self.turn.active_player);
+        #[cfg(feature = "tracing")]
+        self.spans
+            .begin_turn(self.turn.number, self.turn.active_player);
 
-        for (&id, deck) in decks.iter() {
-            for card in deck.cards() {
-                self.spawn_object(card, &Zone::Library(id));
+        // The decks are handled in turn order so that a seeded game always ends up with the same
+        // libraries.
+        let players = self.players.iter().map(|it| it.id).collect::<Vec<_>>();
+        for &id in &players {
+            for card in decks[&id].cards() {
+                self.spawn_object(card, id, Zone::Library(id))?;
             }
         }
+        for &id in &players {
+            self.shuffle_library(id)?;
+        }
+        if self.ante {
+            for &id in &players {
+                if let Some(&card) = self.zones.library(id)?.peek_top(1).first() {
+                    self.move_object(card, Zone::Ante)?;
+                }
+            }
+        }
+        Ok(())
+    }
 
-        for library in self.libraries.values_mut() {
-            library.shuffle();
+    /// Seeds the random number generator of the game, which makes the outcome of everything random
+    /// in the game such as shuffling reproducible.
+    pub(crate) fn set_seed(&mut self, seed: u64) {
+        self.random = StdRng::seed_from_u64(seed);
+    }
+
+    /// 103.1. [...] The player chosen to take the first turn is the starting player. [...]
+    ///
+    /// Makes the specified player take the first turn. This is only possible before the game has
+    /// started.
+    pub(crate) fn set_starting_player(&mut self, player: PlayerId) -> Result<(), EngineError> {
+        self.player(player)?;
+        if !self.events.is_empty() || self.turn.number != 1 {
+            return Err(EngineError::IllegalTiming(
+                "The starting player can only be chosen before the game starts.".into(),
+            ));
         }
+        self.turn = TurnState::new(player, 1);
+        Ok(())
     }
 
-    /// Returns a slice of players within the current game.
-    pub(crate) fn players(&self) -> &[Player] {
-        &self.players
+    /// 103.1. At the start of a game, the players determine which one of them will choose who takes
+    ///        the first turn. [...]
+    ///
+    /// Chooses the starting player at random, as if the players flipped a coin or rolled dice, and
+    /// returns them. This is only possible before the game has started.
+    pub(crate) fn choose_random_starting_player(&mut self) -> Result<PlayerId, EngineError> {
+        if self.players.is_empty() {
+            return Err(EngineError::IllegalAction(
+                "A game without players has no starting player.".into(),
+            ));
+        }
+        let player = self.players[self.random.gen_range(0..self.players.len())].id;
+        self.set_starting_player(player)?;
+        Ok(player)
     }
 
-    /// Returns a mutable reference of the internal world that stores all entities. This method is
-    /// only available to conveniently setup the game world from within tests and will be most
-    /// likely be removed once the core gameplay loop is implemented.
-    #[cfg(test)]
-    pub(crate) fn world_mut(&mut self) -> &mut World {
-        &mut self.world
+    /// 103.4. Each player draws a number of cards equal to their starting hand size, which is
+    ///        normally seven. [...]
+    ///
+    /// Makes each player draw their opening hand in turn order, beginning with the starting
+    /// player.
+    ///
+    /// # Remarks
+    /// Mulligans aren't supported yet, so every player keeps their first hand.
+    pub(crate) fn draw_opening_hands(&mut self) -> Result<(), EngineError> {
+        let mut players = self.players.iter().map(|it| it.id).collect::<Vec<_>>();
+        let starting = players
+            .iter()
+            .position(|&it| it == self.turn.active_player)
+            .unwrap_or_default();
+        players.rotate_left(starting);
+        for player in players {
+            for _ in 0..STARTING_HAND_SIZE {
+                self.draw_card(player)?;
+            }
+        }
+        Ok(())
     }
 
-    /// Spawns an instance of a [`Card`] in the specified [`Zone`].
-    pub(crate) fn spawn_object(&mut self, card: &Card, zone: &Zone) {
-        let mut builder = EntityBuilder::new();
-        builder
-            .add(Object)
-            .add(card.name.clone())
-            .add(card.type_line.clone())
-            .add(card.expansion_symbol.clone())
-            .add(card.rules_text.clone())
-            .add(card.collector_number)
-            .add(card.color());
+    /// Saves the game as a snapshot that can be serialized and restored later, see
+    /// [`GameSnapshot`].
+    ///
+    /// # Errors
+    /// Fails if the game wasn't set up by a [`GameBuilder`], since only then the seed and the
+    /// decks are known.
+    pub fn snapshot(&self) -> Result<GameSnapshot, EngineError> {
+        let setup = self.setup.clone().ok_or_else(|| {
+            EngineError::IllegalAction("Only games set up by a builder can be saved.".into())
+        })?;
+        Ok(GameSnapshot {
+            setup,
+            inputs: self.inputs.clone(),
+        })
+    }
+
+    /// Remembers the builder the game was set up with, which has to have a seed, so that the game
+    /// can be saved. The inputs the builder made while setting up the game are part of it.
+    pub(crate) fn set_setup(&mut self, setup: GameBuilder) {
+        self.setup = Some(setup);
+        self.inputs.clear();
+    }
+
+    /// Returns the statistics collected about the game so far.
+    pub(crate) fn stats(&self) -> &GameStats {
+        &self.stats
+    }
+
+    /// Returns all events that happened during the game in the order they happened.
+    pub(crate) fn events(&self) -> &[GameEvent] {
+        &self.events
+    }
+
+    /// Returns all events that happened during the game as they may be revealed to the specified
+    /// player, or to spectators if there is none. Unlike the events themselves, these can be sent
+    /// to the player without leaking hidden information.
+    #[must_use]
+    pub fn events_for(&self, player: Option<PlayerId>) -> Vec<GameEvent> {
+        self.events.iter().map(|it| it.visible_to(player)).collect()
+    }
+
+    /// Returns the outcome of the game once it has ended.
+    pub(crate) fn outcome(&self) -> Option<GameOutcome> {
+        self.outcome
+    }
+
+    /// 104.3a A player can concede the game at any time. A player who concedes leaves the game
+    ///        immediately. That player loses the game.
+    ///
+    /// Conceding doesn't require priority and happens even while triggered abilities are waiting
+    /// to be put on the stack.
+    pub(crate) fn concede(&mut self, player: PlayerId) -> Result<(), EngineError> {
+        #[cfg(feature = "tracing")]
+        let _span = self.spans.action("concede", player);
+        self.ensure_game_in_progress()?;
+        if self.player(player)?.has_left {
+            return Err(EngineError::IllegalAction(format!(
+                "Player with id {} has already left the game.",
+                player.0
+            )));
+        }
+        self.player_mut(player)?.has_left = true;
+        self.events.push(GameEvent::PlayerConceded { player });
+        self.leave_game(player)?;
+        self.check_remaining_players();
+        Ok(())
+    }
 
-        if let Some(ref mana_cost) = card.mana_cost {
-            builder.add(mana_cost.clone());
+    /// 704.5a If a player has 0 or less life, that player loses the game.
+    ///
+    /// 704.5b If a player attempted to draw a card from a library with no cards in it since the
+    ///        last time state-based actions were checked, that player loses the game.
+    ///
+    /// Makes each player with 0 or less life or who drew from an empty library lose the game unless
+    /// an effect says they can't. This is checked whenever a player loses life or draws a card,
+    /// whenever a permanent leaves the battlefield and whenever “until end of turn” effects end,
+    /// since each of them may end an effect that kept a player in the game.
+    fn check_life_totals(&mut self) -> Result<(), EngineError> {
+        if self.outcome.is_some() {
+            return Ok(());
+        }
+        let losing = self
+            .players
+            .iter()
+            .filter(|it| !it.has_left && (it.life <= 0 || it.drew_from_empty_library))
+            .map(|it| it.id)
+            .collect::<Vec<_>>();
+        for player in &mut self.players {
+            player.drew_from_empty_library = false;
         }
-        if let Some(pt) = card.pt {
-            builder.add(pt);
+        let losing = losing
+            .into_iter()
+            .filter(|&it| self.can_lose(it))
+            .collect::<Vec<_>>();
+        #[cfg(feature = "tracing")]
+        tracing::trace!(rule = "704.5a", players = ?losing, "State-based actions are checked.");
+        // 104.4a If all the players remaining in a game lose simultaneously, the game is a draw.
+        for &player in &losing {
+            self.player_mut(player)?.has_left = true;
+            self.events.push(GameEvent::PlayerLost { player });
+        }
+        for &player in &losing {
+            self.leave_game(player)?;
         }
-        if let Some(loyalty) = card.loyalty {
-            builder.add(loyalty);
+        if !losing.is_empty() {
+            self.check_remaining_players();
         }
+        Ok(())
+    }
 
-        match zone {
-            &Zone::Library(owner) => {
-                builder
-                    // 108.3. The owner of a card in the game is the player who started the game
-                    //        with it in their deck. [...]
-                    .add(Owner(owner))
-                    .add(Zone::Library(owner));
-
-                let entity = self.world.spawn(builder.build());
-                self.libraries
-                    .get_mut(&owner)
-                    .unwrap_or_else(|| {
-                        panic!(
-                            "Could not access the library of player with id {}.",
-                            owner.0
-                        )
-                    })
-                    .cards
-                    .push(entity);
+    /// 104.2b An effect may state that a player wins the game.
+    ///
+    /// Ends the game with the player as its winner unless an effect says they can't win.
+    ///
+    /// # Remarks
+    /// The other players don't lose one by one, so an opponent who can't lose the game doesn't
+    /// keep the game going in a multiplayer game.
+    pub(crate) fn win_game(&mut self, player: PlayerId) -> Result<(), EngineError> {
+        self.ensure_game_in_progress()?;
+        if self.player(player)?.has_left {
+            return Err(EngineError::IllegalAction(format!(
+                "Player with id {} has already left the game.",
+                player.0
+            )));
+        }
+        if self.can_win(player) {
+            self.end_game(GameOutcome::Win(player));
+        } else {
+            log::debug!("Player with id {} can't win the game.", player.0);
+        }
+        Ok(())
+    }
+
+    /// Returns whether the player can lose the game, i.e. no effect says that they can't.
+    fn can_lose(&self, player: PlayerId) -> bool {
+        !self.effects.iter().any(|(_, effect)| {
+            matches!(effect.kind, EffectKind::CantLoseGame { player: affected } if affected == player)
+        }) && !self.any_static_ability(|controller, ability| {
+            controller == player && *ability == StaticAbility::CantLoseGame
+        })
+    }
+
+    /// Returns whether the player can win the game, i.e. no effect says that they can't.
+    fn can_win(&self, player: PlayerId) -> bool {
+        !self.effects.iter().any(|(_, effect)| {
+            matches!(effect.kind, EffectKind::CantWinGame { player: affected } if affected == player)
+        }) && !self.any_static_ability(|controller, ability| {
+            controller != player && *ability == StaticAbility::OpponentsCantWinGame
+        })
+    }
+
+    /// 104.2a A player still in the game wins the game if that player’s opponents have all left
+    ///        the game. [...]
+    ///
+    /// Ends the game once at most one player remains in it.
+    fn check_remaining_players(&mut self) {
+        let remaining = self
+            .players
+            .iter()
+            .filter(|it| !it.has_left)
+            .map(|it| it.id)
+            .collect::<Vec<_>>();
+        match remaining[..] {
+            [winner] => self.end_game(GameOutcome::Win(winner)),
+            [] => self.end_game(GameOutcome::Draw),
+            _ => {}
+        }
+    }
+
+    /// 800.4a When a player leaves the game, all objects (see rule 109) owned by that player leave
+    ///        the game and any effects which give that player control of any objects or players
+    ///        end. Then, if that player controlled any objects on the stack not represented by
+    ///        cards, those objects cease to exist. Then, if there are any objects still controlled
+    ///        by that player, those objects are exiled. [...]
+    fn leave_game(&mut self, player: PlayerId) -> Result<(), EngineError> {
+        // The cards in the ante zone stay there for the winner to take.
+        let owned = self
+            .world
+            .query::<&Owner>()
+            .iter()
+            .filter(|&(entity, owner)| {
+                owner.0 == player && self.zones.zone_of(entity) != Some(Zone::Ante)
+            })
+            .map(|(entity, _)| entity)
+            .collect::<Vec<_>>();
+        for entity in owned {
+            self.despawn_object(entity)?;
+        }
+        let ended = self.effects.player_left(player);
+        self.effects_ended(ended);
+        self.update_controllers();
+        self.pending_triggers.retain(|it| it.controller != player);
+
+        let controlled = self
+            .world
+            .query::<&Controller>()
+            .iter()
+            .filter(|(_, controller)| controller.0 == player)
+            .map(|(entity, _)| entity)
+            .collect::<Vec<_>>();
+        for entity in controlled {
+            self.move_object(entity, Zone::Exile)?;
+        }
+        Ok(())
+    }
+
+    /// 104.1. A game ends immediately when a player wins, when the game is a draw, or when the game
+    ///        is restarted.
+    fn end_game(&mut self, outcome: GameOutcome) {
+        if let (true, GameOutcome::Win(winner)) = (self.ante, outcome) {
+            self.transfer_ante(winner);
+        }
+        self.outcome = Some(outcome);
+        self.events.push(GameEvent::GameEnded { outcome });
+    }
+
+    /// Makes the winner the owner of all cards in the ante zone.
+    fn transfer_ante(&mut self, winner: PlayerId) {
+        let mut cards = self
+            .world
+            .query::<(&Zone, &Owner)>()
+            .iter()
+            .filter(|(_, (&zone, _))| zone == Zone::Ante)
+            .map(|(entity, (_, owner))| (entity, owner.0))
+            .collect::<Vec<_>>();
+        cards.sort_by_key(|&(entity, _)| self.object_id(entity));
+        for &(entity, owner) in &cards {
+            if let Some(count) = self.brought_cards.get_mut(&owner) {
+                *count = count.saturating_sub(1);
             }
-            _ => unimplemented!(),
+            *self.brought_cards.entry(winner).or_default() += 1;
+            let _ = self.world.insert_one(entity, Owner(winner));
         }
+        self.events.push(GameEvent::AnteWon {
+            winner,
+            cards: cards
+                .iter()
+                .map(|&(entity, _)| ObjectSnapshot::of(&self.world, entity))
+                .collect(),
+        });
     }
-}
 
-/// 401.1. When a game begins, each player’s deck becomes their library.
-#[derive(Default)]
-struct Library {
-    cards: Vec<Entity>,
-}
+    /// Sets the number of times the same game state may repeat while mandatory triggers resolve
+    /// before the game ends in a draw.
+    pub(crate) fn set_loop_threshold(&mut self, threshold: usize) {
+        self.loop_threshold = threshold;
+    }
 
-impl Library {
-    /// Shuffles the library using a thread-local random number generator.
-    fn shuffle(&mut self) {
-        self.cards.shuffle(&mut rand::thread_rng());
+    /// Enables or disables mana burn, which is disabled by default. While enabled, each player
+    /// loses 1 life for each mana that empties from their mana pool at the end of a step or phase.
+    pub(crate) fn set_mana_burn(&mut self, mana_burn: bool) {
+        self.mana_burn = mana_burn;
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use hecs::With;
+    /// Enables or disables playing for ante, which is disabled by default. While enabled, each
+    /// player puts the top card of their library into the ante zone once the game starts and the
+    /// winner becomes the owner of all cards in the ante zone.
+    pub(crate) fn set_playing_for_ante(&mut self, ante: bool) {
+        self.ante = ante;
+    }
 
-    use super::*;
+    /// Enables or disables the planeswalker uniqueness rule, which is disabled by default. While
+    /// enabled, a player who controls two or more planeswalkers that share a planeswalker type
+    /// keeps only one of them, as under the rules before Ixalan.
+    pub(crate) fn set_planeswalker_uniqueness_rule(&mut self, planeswalker_uniqueness: bool) {
+        self.planeswalker_uniqueness = planeswalker_uniqueness;
+    }
 
-    #[test]
-    #[allow(clippy::needless_collect)]
-    fn sample_game() {
-        let mut game = Game::new(2);
+    /// 104.4b If a game that’s not using the limited range of influence option (including a
+    ///        two-player game) somehow enters a “loop” of mandatory actions, repeating a sequence
+    ///        of events with no way to stop, the game is a draw. Loops that contain an optional
+    ///        action don’t result in a draw.
+    ///
+    /// Resolves pending triggers as long as none of them requires a decision, i.e. targets or
+    /// whether to take an optional action, from a player. Triggers whose optional actions have
+    /// been decided on already resolve, since the decision was the player's to make. If the same
+    /// game state repeats more often than the loop threshold allows, the game ends in a draw
+    /// instead.
+    pub(crate) fn resolve_mandatory_triggers(&mut self) -> Result<(), EngineError> {
+        while let Some(trigger) = self.pending_triggers.first() {
+            if self.outcome.is_some()
+                || trigger.needs_decision()
+                || trigger.effects.iter().any(Effect::requires_target)
+                || self.is_in_loop()
+            {
+                break;
+            }
+            self.resolve_next_trigger(&[])?;
+        }
+        Ok(())
+    }
 
-        let white_deck = Deck::from(&[("Plains", 30), ("Soulmender", 30)]);
-        let green_deck = Deck::from(&[("Forest", 30), ("Llanowar Elves", 30)]);
+    /// Records the current game state as reached by a mandatory action and ends the game in a draw
+    /// if it repeated more often than the loop threshold allows. The states are forgotten whenever
+    /// a player makes a decision or the turn ends, as loops that contain an optional action don't
+    /// result in a draw.
+    fn is_in_loop(&mut self) -> bool {
+        let repetitions = self
+            .loop_states
+            .entry(self.state_fingerprint())
+            .or_default();
+        *repetitions += 1;
+        if *repetitions <= self.loop_threshold {
+            return false;
+        }
+        log::debug!("The game entered an unbreakable loop.");
+        self.end_game(GameOutcome::Draw);
+        true
+    }
 
-        let mut players = game.players().iter();
-        let first_player = players.next().expect("Could not get the first player.").id;
-        let second_player = players.next().expect("Could not get the second player.").id;
+    /// Returns a hash of the parts of the game state that matter for detecting loops, so that a
+    /// loop that keeps adding counters, damage or effects isn't mistaken for one without progress.
+    /// Object ids and timestamps are deliberately left out as they change with every zone change.
+    fn state_fingerprint(&self) -> u64 {
+        let hash = |value: &dyn Fn(&mut DefaultHasher)| {
+            let mut hasher = DefaultHasher::new();
+            value(&mut hasher);
+            hasher.finish()
+        };
+        let mut objects = self
+            .world
+            .query::<(
+                &Zone,
+                &CardRef,
+                Option<&Controller>,
+                Option<&Tapped>,
+                Option<&Counters>,
+                Option<&MarkedDamage>,
+            )>()
+            .iter()
+            .map(|(_, (zone, card, controller, tapped, counters, damage))| {
+                hash(&|hasher| {
+                    zone.hash(hasher);
+                    card.0.name.0.hash(hasher);
+                    controller.map(|it| it.0).hash(hasher);
+                    tapped.is_some().hash(hasher);
+                    counters.map(Counters::to_vec).hash(hasher);
+                    damage.map(|it| it.0).hash(hasher);
+                })
+            })
+            .collect::<Vec<_>>();
+        objects.sort_unstable();
+        hash(&|hasher| {
+            objects.hash(hasher);
+            for player in &self.players {
+                (player.id, player.life, player.has_left).hash(hasher);
+                player.counters.to_vec().hash(hasher);
+            }
+            for (_, effect) in self.effects.iter() {
+                (effect.controller, effect.duration).hash(hasher);
+                format!("{:?}", effect.kind).hash(hasher);
+            }
+            for trigger in &self.pending_triggers {
+                trigger.controller.hash(hasher);
+                if let Ok(card) = self.world.get::<CardRef>(trigger.source) {
+                    card.0.name.0.hash(hasher);
+                }
+            }
+        })
+    }
 
-        game.start(&[(first_player, white_deck), (second_player, green_deck)].into());
+    /// Returns a hash of the whole observable game state that is stable across runs, platforms
+    /// and versions of Rust. Two runs of the same actions with the same seed must always result in
+    /// the same hash.
+    pub(crate) fn state_hash(&self) -> u64 {
+        let mut lines = vec![format!(
+            "turn {} {:?} {:?}",
+            self.turn.number, self.turn.active_player, self.turn.step
+        )];
+        for player in &self.players {
+            lines.push(format!(
+                "{:?} {} {} {:?}",
+                player.id,
+                player.life,
+                player.has_left,
+                player.mana_pool.mana()
+            ));
+            if let Ok(library) = self.zones.library(player.id) {
+                let library = library
+                    .cards()
+                    .map(|it| self.object_id(it))
+                    .collect::<Vec<_>>();
+                lines.push(format!("library {library:?}"));
+            }
+        }
+        let mut objects = self
+            .world
+            .query::<(&ObjectId, &Zone, &CardRef)>()
+            .iter()
+            .map(|(entity, (id, zone, card))| {
+                format!(
+                    "{id:?} {} {zone:?} {:?} {} {:?}",
+                    card.0.name.0,
+                    self.controller(entity),
+                    self.world.get::<Tapped>(entity).is_ok(),
+                    self.world.get::<MarkedDamage>(entity).map(|it| it.0).ok(),
+                )
+            })
+            .collect::<Vec<_>>();
+        objects.sort();
+        lines.extend(objects);
+        lines.push(format!("events {}", self.events.len()));
+        consistency::stable_hash(&lines.join("\n"))
+    }
 
-        let mut objects = game.world_mut().query::<With<Object, &Zone>>();
+    /// Returns the whole state of the game as seen by an omniscient spectator. The cards in the
+    /// libraries and their order are only included if requested.
+    pub(crate) fn spectator_view(&self, reveal_libraries: bool) -> SpectatorView {
+        let players = self
+            .players
+            .iter()
+            .map(|player| PlayerStatus {
+                library: self
+                    .zones
+                    .library(player.id)
+                    .ok()
+                    .filter(|_| reveal_libraries)
+                    .map(|it| it.cards().filter_map(|it| self.object_id(it)).collect()),
+                ..self.player_status(player)
+            })
+            .collect();
+        let mut objects = self
+            .world
+            .query::<(&ObjectId, &Zone, &CardRef, &Owner)>()
+            .iter()
+            .filter(|(_, (_, zone, ..))| reveal_libraries || !matches!(zone, Zone::Library(_)))
+            .map(|(entity, (&id, &zone, card, owner))| {
+                self.object_status(entity, id, zone, &card.0, owner.0)
+            })
+            .collect::<Vec<_>>();
+        objects.sort_by_key(|it| it.id);
+        SpectatorView {
+            event_index: self.events.len(),
+            libraries_revealed: reveal_libraries,
+            turn: self.turn.number,
+            active_player: self.turn.active_player,
+            step: self.turn.step,
+            players,
+            objects,
+        }
+    }
 
-        let white_library = objects
+    /// Returns the state of the game as seen by the specified player, who neither sees the
+    /// libraries nor the hands of the other players. Cards the other players exiled face down are
+    /// included without their identity so they can still be counted.
+    ///
+    /// # Errors
+    /// Fails with [`EngineError::UnknownPlayer`] if there is no player with the specified id.
+    pub fn view_for(&self, player: PlayerId) -> Result<PlayerView, EngineError> {
+        self.player(player)?;
+        let mut objects = self
+            .world
+            .query::<(&ObjectId, &Zone, &CardRef, &Owner)>()
             .iter()
-            .filter(|(_, zone)| **zone == Zone::Library(first_player))
+            .filter(|(_, (_, &zone, ..))| match zone {
+                Zone::Library(_) => false,
+                Zone::Hand(owner) => owner == player,
+                _ => true,
+            })
+            .map(|(entity, (&id, &zone, card, owner))| {
+                let status = self.object_status(entity, id, zone, &card.0, owner.0);
+                if zone != Zone::Exile
+                    || owner.0 == player
+                    || self.world.get::<FaceDown>(entity).is_err()
+                {
+                    return status;
+                }
+                let hidden = ObjectSnapshot::hidden();
+                ObjectStatus {
+                    name: hidden.name,
+                    mana_cost: None,
+                    mana_value: 0,
+                    power_toughness: None,
+                    counters: hidden.counters,
+                    ..status
+                }
+            })
             .collect::<Vec<_>>();
-        assert_eq!(white_library.len(), 60);
+        objects.sort_by_key(|it| it.id);
+        Ok(PlayerView {
+            player,
+            event_index: self.events.len(),
+            turn: self.turn.number,
+            active_player: self.turn.active_player,
+            step: self.turn.step,
+            outcome: self.outcome,
+            players: self
+                .players
+                .iter()
+                .map(|it| self.player_status(it))
+                .collect(),
+            objects,
+            choice: self
+                .choice
+                .as_ref()
+                .filter(|it| it.player == player)
+                .map(|it| ChoiceStatus {
+                    prompt: it.prompt.clone(),
+                    options: it.options.clone(),
+                }),
+        })
+    }
+
+    /// Returns the state of the player as it is shown in views of the game, leaving out the cards
+    /// in their library.
+    fn player_status(&self, player: &Player) -> PlayerStatus {
+        PlayerStatus {
+            id: player.id,
+            info: player.info.clone(),
+            life: player.life,
+            counters: player.counters.to_vec(),
+            mana_pool: player.mana_pool.mana().to_vec(),
+            hand_size: self.hand_size(player.id),
+            library_size: self.zones.library(player.id).map_or(0, Library::len),
+            library: None,
+            designations: player.designations.iter().copied().collect(),
+            has_left: player.has_left,
+        }
+    }
+
+    /// Returns the state of the object as it is shown in views of the game.
+    fn object_status(
+        &self,
+        entity: Entity,
+        id: ObjectId,
+        zone: Zone,
+        card: &Card,
+        owner: PlayerId,
+    ) -> ObjectStatus {
+        ObjectStatus {
+            id,
+            name: card.name.0.clone(),
+            mana_cost: card.mana_cost.as_ref().map(ToString::to_string),
+            mana_value: card.mana_value(),
+            power_toughness: self.power_toughness(entity),
+            owner,
+            controller: self.controller(entity),
+            zone,
+            token: self.world.get::<Token>(entity).is_ok(),
+            statuses: [
+                (
+                    ObjectState::Tapped,
+                    self.world.get::<Tapped>(entity).is_ok(),
+                ),
+                (ObjectState::PhasedOut, self.is_phased_out(entity)),
+                (
+                    ObjectState::SummoningSick,
+                    self.world.get::<SummoningSick>(entity).is_ok(),
+                ),
+                (
+                    ObjectState::Attacking,
+                    self.world.get::<Attacking>(entity).is_ok(),
+                ),
+                (
+                    ObjectState::Blocking,
+                    !self.combat.blocked_by(entity).is_empty(),
+                ),
+                (
+                    ObjectState::FaceDown,
+                    self.world.get::<FaceDown>(entity).is_ok(),
+                ),
+            ]
+            .into_iter()
+            .filter_map(|(state, applies)| applies.then_some(state))
+            .collect(),
+            marked_damage: self.world.get::<MarkedDamage>(entity).map_or(0, |it| it.0),
+            counters: self
+                .world
+                .get::<Counters>(entity)
+                .map(|it| it.to_vec())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Returns the changes to the game since the specified spectator view was taken, including all
+    /// events that happened since. The libraries are revealed just like in the previous view.
+    pub(crate) fn spectator_delta(&self, since: &SpectatorView) -> SpectatorDelta {
+        let events = self.events[since.event_index.min(self.events.len())..].to_vec();
+        since.delta_to(&self.spectator_view(since.libraries_revealed), events)
+    }
+
+    /// Returns an error if the game has already ended.
+    fn ensure_game_in_progress(&self) -> Result<(), EngineError> {
+        if self.outcome.is_some() {
+            return Err(EngineError::IllegalAction(
+                "The game has already ended.".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns a slice of players within the current game.
+    pub(crate) fn players(&self) -> &[Player] {
+        &self.players
+    }
+
+    /// Returns the state of the current turn.
+    pub(crate) fn turn(&self) -> &TurnState {
+        &self.turn
+    }
+
+    /// Returns the state of the previous turn, if any.
+    pub(crate) fn previous_turn(&self) -> Option<&TurnState> {
+        self.previous_turn.as_ref()
+    }
+
+    /// Returns the current day/night designation of the game, if any.
+    pub(crate) fn day_night(&self) -> Option<DayNight> {
+        self.day_night
+    }
 
-        let green_library = objects
+    /// 702.145b [...] “As it becomes night, if this permanent is front face up, transform it,”
+    ///          [...]
+    ///
+    /// 702.145e [...] “As it becomes day, if this permanent is back face up, transform it” [...]
+    ///
+    /// Makes it day or night, e.g. because a daybound permanent entered the battlefield. The
+    /// daybound permanents transform as it becomes night and the nightbound ones as it becomes day.
+    pub(crate) fn set_day_night(&mut self, day_night: DayNight) -> Result<(), EngineError> {
+        if self.day_night.replace(day_night) == Some(day_night) {
+            return Ok(());
+        }
+        log::debug!("It becomes {day_night:?}.");
+        let keyword = match day_night {
+            DayNight::Day => KeywordAbility::Nightbound,
+            DayNight::Night => KeywordAbility::Daybound,
+        };
+        let transforming = self
+            .world
+            .query::<&Zone>()
             .iter()
-            .filter(|(_, zone)| **zone == Zone::Library(second_player))
+            .filter(|&(entity, &zone)| {
+                zone == Zone::Battlefield && self.has_keyword(entity, &keyword)
+            })
+            .map(|(entity, _)| entity)
             .collect::<Vec<_>>();
-        assert_eq!(green_library.len(), 60);
+        for permanent in transforming {
+            self.transform(permanent)?;
+        }
+        Ok(())
+    }
+
+    /// 701.28a To transform a permanent, turn it over so that its other face is up. Only
+    ///         permanents represented by double-faced tokens and double-faced cards can transform.
+    ///         [...]
+    ///
+    /// Turns a permanent over to its other face. Permanents without a back face don't transform.
+    pub(crate) fn transform(&mut self, entity: Entity) -> Result<(), EngineError> {
+        if self.world.remove_one::<Transformed>(entity).is_ok() {
+            if let Ok(PrintedCard(front)) = self.world.remove_one::<PrintedCard>(entity) {
+                self.replace_characteristics(entity, &front)?;
+            }
+            log::debug!("{entity:?} transforms to its front face.");
+            return Ok(());
+        }
+        let Some(back) = self
+            .world
+            .get::<CardRef>(entity)
+            .map_err(|_| EngineError::UnknownObject(entity))?
+            .0
+            .back_face
+            .clone()
+        else {
+            return Ok(());
+        };
+        let front = self.replace_characteristics(entity, &Arc::new(*back))?;
+        self.world
+            .insert(entity, (PrintedCard(front), Transformed))
+            .map_err(|_| EngineError::UnknownObject(entity))?;
+        log::debug!("{entity:?} transforms to its back face.");
+        Ok(())
+    }
+
+    /// Returns the facts tracked about the current turn.
+    pub(crate) fn turn_tracker(&self) -> &TurnTracker {
+        &self.turn.tracker
+    }
+
+    /// Returns the facts tracked about the previous turn, if any.
+    pub(crate) fn previous_turn_tracker(&self) -> Option<&TurnTracker> {
+        self.previous_turn.as_ref().map(|it| &it.tracker)
+    }
+
+    /// Records that the specified player cast a spell from the given card during the current turn.
+    pub(crate) fn record_spell_cast(&mut self, player: PlayerId, card: &Card) {
+        self.record_cast(player, None, card);
+    }
+
+    /// Records that the specified player cast the given spell, if it is known, from the given card
+    /// during the current turn. Abilities that trigger on casting a spell trigger right away, so
+    /// they resolve even if the spell is countered.
+    fn record_cast(&mut self, player: PlayerId, spell: Option<Entity>, card: &Card) {
+        self.turn.tracker.record_spell_cast(SpellCast {
+            spell: spell.and_then(|it| self.object_id(it)),
+            caster: player,
+            card_type: card.type_line.card_type.clone(),
+            color: card.color(),
+        });
+        self.stats.record_spell_cast(&card.type_line.card_type);
+        self.events.push(GameEvent::SpellCast {
+            caster: player,
+            name: card.name.0.clone(),
+        });
+        if let Some(spell) = spell {
+            self.check_triggers(&TriggerEvent::SpellCast {
+                spell,
+                caster: player,
+            });
+        }
+    }
+
+    /// 119.3. If an effect causes a player to gain or lose life, that player’s life total is
+    ///        adjusted accordingly.
+    pub(crate) fn gain_life(&mut self, player: PlayerId, amount: u64) -> Result<(), EngineError> {
+        let life = {
+            let player = self.player_mut(player)?;
+            player.life += amount as i64;
+            player.life
+        };
+        self.turn.tracker.record_life_gained(player, amount);
+        self.events.push(GameEvent::LifeGained {
+            player,
+            amount,
+            life,
+        });
+        Ok(())
+    }
+
+    /// 119.3. If an effect causes a player to gain or lose life, that player’s life total is
+    ///        adjusted accordingly.
+    pub(crate) fn lose_life(&mut self, player: PlayerId, amount: u64) -> Result<(), EngineError> {
+        let life = {
+            let player = self.player_mut(player)?;
+            player.life -= amount as i64;
+            player.life
+        };
+        self.turn.tracker.record_life_lost(player, amount);
+        self.events.push(GameEvent::LifeLost {
+            player,
+            amount,
+            life,
+        });
+        self.check_life_totals()
+    }
+
+    /// Creates a new prevention effect that lasts until the end of the turn.
+    pub(crate) fn add_prevention_effect(&mut self, effect: PreventionEffect) {
+        self.prevention_effects.add(effect);
+    }
+
+    /// Creates a new replacement effect that lasts until the end of the turn.
+    pub(crate) fn add_replacement_effect(&mut self, effect: impl ReplacementEffect + 'static) {
+        self.replacement_effects.add(effect);
+    }
+
+    /// 120.3. Damage may have one or more of the following results, depending on whether the
+    ///        recipient of the damage is a player or permanent, the characteristics of the damage’s
+    ///        source, and the characteristics of the damage’s recipient (if it’s a permanent).
+    ///
+    /// Deals the specified damage after applying prevention effects and returns the amount of
+    /// damage that was prevented, including damage a shield counter was removed instead of.
+    pub(crate) fn deal_damage(&mut self, damage: &Damage) -> Result<u64, EngineError> {
+        if !self.world.contains(damage.source) {
+            return Err(EngineError::UnknownObject(damage.source));
+        }
+        match damage.recipient {
+            PermanentOrPlayer::Permanent(entity) if !self.is_on_battlefield(entity) => {
+                return Err(EngineError::InvalidTarget(format!(
+                    "Only permanents can be dealt damage but {entity:?} is not on the \
+                     battlefield."
+                )));
+            }
+            PermanentOrPlayer::Permanent(_) => {}
+            PermanentOrPlayer::Player(player) => {
+                self.player(player)?;
+            }
+        }
+
+        let prevented = if self.is_protected_from(damage.recipient, damage.source) {
+            // 702.16e Any damage that would be dealt by sources that have the stated quality to
+            //         a permanent or player with protection is prevented.
+            damage.amount
+        } else {
+            self.prevention_effects.prevent(damage)
+        };
+
+        let mut amount = damage.amount - prevented;
+        if amount > 0 {
+            let event = ReplaceableEvent::DealDamage {
+                recipient: damage.recipient,
+                amount,
+            };
+            amount = 0;
+            for event in self.replace(event)? {
+                match event {
+                    ReplaceableEvent::DealDamage { amount: dealt, .. } => amount += dealt,
+                    event => self.perform_replacing_event(event)?,
+                }
+            }
+        }
+        let prevented = damage.amount - amount;
+        if amount > 0 {
+            let recipient = match damage.recipient {
+                PermanentOrPlayer::Permanent(entity) => {
+                    self.deal_damage_to_permanent(entity, amount)?;
+                    DamageRecipient::Permanent(ObjectSnapshot::of(&self.world, entity))
+                }
+                PermanentOrPlayer::Player(player) => {
+                    self.deal_damage_to_player(player, amount)?;
+                    DamageRecipient::Player {
+                        player,
+                        life: self.player(player)?.life,
+                    }
+                }
+            };
+            let dealt_by = self
+                .controller(damage.source)
+                .or_else(|| self.world.get::<Owner>(damage.source).ok().map(|it| it.0));
+            if let Some(player) = dealt_by {
+                self.stats.record_damage_dealt(player, amount);
+            }
+            self.events.push(GameEvent::DamageDealt {
+                source: ObjectSnapshot::of(&self.world, damage.source),
+                recipient,
+                amount,
+            });
+        }
+        Ok(prevented)
+    }
+
+    /// Returns whether the specified entity is a permanent, i.e. an object on the battlefield.
+    fn is_on_battlefield(&self, entity: Entity) -> bool {
+        self.world
+            .get::<Zone>(entity)
+            .is_ok_and(|it| *it == Zone::Battlefield)
+            && !self.is_phased_out(entity)
+    }
+
+    /// 702.26b [...] a phased-out permanent is treated as though it does not exist. [...]
+    fn is_phased_out(&self, entity: Entity) -> bool {
+        self.world.get::<PhasedOut>(entity).is_ok()
+    }
+
+    /// 702.26g If a permanent phases out, any Auras, Equipment, or Fortifications attached to that
+    ///         permanent phase out at the same time. This alternate way of phasing out is known as
+    ///         phasing out “indirectly.” An Aura, Equipment, or Fortification that phased out
+    ///         indirectly won’t phase in by itself, but instead phases in along with the permanent
+    ///         it’s attached to.
+    pub(crate) fn phase_out(&mut self, entity: Entity) -> Result<(), EngineError> {
+        if !self.is_on_battlefield(entity) {
+            return Err(EngineError::InvalidTarget(format!(
+                "Only phased-in permanents can phase out but {entity:?} is not one."
+            )));
+        }
+        let mut phasing = vec![(entity, false)];
+        let mut index = 0;
+        while let Some(&(permanent, _)) = phasing.get(index) {
+            let attachments = self
+                .world
+                .query::<&AttachedTo>()
+                .iter()
+                .filter(|(attachment, it)| it.0 == permanent && !self.is_phased_out(*attachment))
+                .map(|(attachment, _)| (attachment, true))
+                .collect::<Vec<_>>();
+            phasing.extend(attachments);
+            index += 1;
+        }
+        for (permanent, indirectly) in phasing {
+            self.world
+                .insert_one(permanent, PhasedOut { indirectly })
+                .map_err(|_| EngineError::UnknownObject(permanent))?;
+        }
+        Ok(())
+    }
+
+    /// 702.26a [...] During each player’s untap step, before the active player untaps
+    ///         permanents, all phased-in permanents with phasing that player controls phase out,
+    ///         and simultaneously all phased-out permanents that had phased out under that player’s
+    ///         control phase in.
+    ///
+    /// Phases in all permanents of the active player that phased out directly along with the
+    /// permanents that phased out indirectly with them.
+    fn phase_in(&mut self) {
+        let mut phasing = self
+            .world
+            .query::<(&Controller, &PhasedOut)>()
+            .iter()
+            .filter(|(_, (controller, phased_out))| {
+                controller.0 == self.turn.active_player && !phased_out.indirectly
+            })
+            .map(|(entity, _)| entity)
+            .collect::<Vec<_>>();
+        let mut index = 0;
+        while let Some(&permanent) = phasing.get(index) {
+            let attachments = self
+                .world
+                .query::<(&AttachedTo, &PhasedOut)>()
+                .iter()
+                .filter(|(_, (it, phased_out))| it.0 == permanent && phased_out.indirectly)
+                .map(|(attachment, _)| attachment)
+                .collect::<Vec<_>>();
+            phasing.extend(attachments);
+            index += 1;
+        }
+        for permanent in phasing {
+            let _ = self.world.remove_one::<PhasedOut>(permanent);
+        }
+        let _ = self.check_ascend();
+        let _ = self.check_uniqueness_rules();
+    }
+
+    /// 701.3a To attach an Aura, Equipment, or Fortification to an object means to take it from
+    ///        where it currently is and put it onto that object. [...]
+    ///
+    /// 702.16c A permanent or player with protection can’t be enchanted by Auras that have the
+    ///         stated quality. [...]
+    ///
+    /// 702.16d A permanent with protection can’t be equipped by Equipment that have the stated
+    ///         quality or fortified by Fortifications that have the stated quality. [...]
+    pub(crate) fn attach(&mut self, attachment: Entity, target: Entity) -> Result<(), EngineError> {
+        if !self.is_on_battlefield(attachment)
+            || !self.is_on_battlefield(target)
+            || self.is_protected_from(PermanentOrPlayer::Permanent(target), attachment)
+        {
+            return Err(EngineError::InvalidTarget(format!(
+                "{attachment:?} can't be attached to {target:?}."
+            )));
+        }
+        self.world
+            .insert_one(attachment, AttachedTo(target))
+            .map_err(|_| EngineError::UnknownObject(attachment))
+    }
+
+    /// Returns whether the specified recipient has protection from the given source. Only
+    /// permanents on the battlefield can have protection.
+    fn is_protected_from(&self, recipient: PermanentOrPlayer, source: Entity) -> bool {
+        let PermanentOrPlayer::Permanent(entity) = recipient else {
+            return false;
+        };
+        if !self.is_on_battlefield(entity) {
+            return false;
+        }
+        let Ok(color) = self.world.get::<ColorIdentity>(source) else {
+            return false;
+        };
+        self.keywords(entity).iter().any(|it| match it {
+            KeywordAbility::Protection(quality) => quality.applies_to(&color),
+            _ => false,
+        })
+    }
+
+    /// Applies the results of damage dealt to a permanent according to its card types, so damage
+    /// dealt to a planeswalker creature both removes loyalty counters and is marked on it.
+    /// Permanents that are neither planeswalkers nor battles are marked damage like creatures.
+    fn deal_damage_to_permanent(&mut self, entity: Entity, amount: u64) -> Result<(), EngineError> {
+        let is_planeswalker = self.has_card_type(entity, CardType::Planeswalker);
+        let is_battle = self.has_card_type(entity, CardType::Battle);
+        let permanent = PermanentOrPlayer::Permanent(entity);
+        if is_planeswalker {
+            // 120.3c Damage dealt to a planeswalker causes that many loyalty counters to be
+            //        removed from that planeswalker.
+            self.remove_counters(permanent, &CounterKind::Loyalty, amount)?;
+        }
+        if is_battle {
+            // 120.3h Damage dealt to a battle causes that many defense counters to be removed from
+            //        that battle.
+            self.remove_counters(permanent, &CounterKind::Defense, amount)?;
+        }
+        if self.has_card_type(entity, CardType::Creature) || !(is_planeswalker || is_battle) {
+            // 120.3e Damage dealt to a creature by a source with neither wither nor infect causes
+            //        that much damage to be marked on that creature.
+            let marked_damage = self.world.get::<MarkedDamage>(entity).map_or(0, |it| it.0);
+            self.world
+                .insert_one(entity, MarkedDamage(marked_damage + amount))
+                .map_err(|_| EngineError::UnknownObject(entity))?;
+        }
+        Ok(())
+    }
+
+    /// 120.3a Damage dealt to a player by a source without infect causes that player to lose that
+    ///        much life.
+    fn deal_damage_to_player(&mut self, player: PlayerId, amount: u64) -> Result<(), EngineError> {
+        self.player_mut(player)?.life -= amount as i64;
+        self.turn.tracker.record_damage_dealt(player, amount);
+        self.turn.tracker.record_life_lost(player, amount);
+        self.check_life_totals()
+    }
+
+    /// Creates a new continuous effect.
+    ///
+    /// 613.7b A continuous effect generated by the resolution of a spell or ability receives a
+    ///        timestamp at the time it’s created.
+    pub(crate) fn add_effect(&mut self, effect: ContinuousEffect) {
+        let timestamp = self.next_timestamp();
+        self.effects.add(effect, timestamp);
+        self.update_controllers();
+    }
+
+    /// Creates a new continuous effect of a resolving spell or ability lasting until end of turn.
+    fn add_effect_until_end_of_turn(
+        &mut self,
+        source: Entity,
+        controller: PlayerId,
+        kind: EffectKind,
+    ) {
+        self.add_effect(ContinuousEffect {
+            source: Some(source),
+            controller,
+            duration: Duration::UntilEndOfTurn,
+            kind,
+        });
+    }
+
+    /// 613.1b Layer 2: Control-changing effects are applied.
+    ///
+    /// Updates the controller of every permanent according to the control-changing effects with the
+    /// latest timestamp. Permanents not affected by any such effect are controlled by the player
+    /// they entered the battlefield under, which is always their owner for now.
+    fn update_controllers(&mut self) {
+        log::trace!("Layer 2 is applied.");
+        #[cfg(feature = "tracing")]
+        tracing::trace!(layer = 2, "Continuous effects are applied.");
+        let changes = self
+            .world
+            .query::<(&Owner, &Controller, &Zone)>()
+            .iter()
+            .filter(|(_, (.., zone))| **zone == Zone::Battlefield)
+            .filter_map(|(entity, (owner, controller, _))| {
+                let expected = self
+                    .effects
+                    .iter()
+                    .filter_map(|(_, effect)| match effect.kind {
+                        EffectKind::ChangeControl { target, controller } if target == entity => {
+                            Some(controller)
+                        }
+                        _ => None,
+                    })
+                    .last()
+                    .unwrap_or(owner.0);
+                (expected != controller.0).then_some((entity, expected))
+            })
+            .collect::<Vec<_>>();
+        let has_changes = !changes.is_empty();
+        for (entity, controller) in changes {
+            log::debug!(
+                "Player with id {} gains control of {entity:?}.",
+                controller.0
+            );
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                object = ?self.object_id(entity),
+                player = controller.0,
+                "Control of a permanent changes."
+            );
+            self.remove_from_combat(entity);
+            let _ = self
+                .world
+                .insert(entity, (Controller(controller), SummoningSick));
+        }
+        let _ = self.check_ascend();
+        if has_changes {
+            let _ = self.check_uniqueness_rules();
+        }
+        if self.end_effects_with_failed_conditions() {
+            // Effects that ended may have changed control again.
+            self.update_controllers();
+        }
+    }
+
+    /// Ends the effects lasting for as long as a condition is true once it isn't anymore and
+    /// returns whether any effect ended. This is checked whenever control changes, a permanent
+    /// leaves the battlefield or an effect is created, as well as whenever a permanent untaps.
+    fn end_effects_with_failed_conditions(&mut self) -> bool {
+        let world = &self.world;
+        let on_battlefield = |entity| {
+            world
+                .get::<Zone>(entity)
+                .is_ok_and(|it| *it == Zone::Battlefield)
+        };
+        let ended = self
+            .effects
+            .conditions_changed(|effect, condition| match condition {
+                DurationCondition::YouControlSource => effect.source.is_some_and(|source| {
+                    on_battlefield(source)
+                        && world
+                            .get::<Controller>(source)
+                            .is_ok_and(|it| it.0 == effect.controller)
+                }),
+                DurationCondition::RemainsTapped(object) => {
+                    on_battlefield(object) && world.get::<Tapped>(object).is_ok()
+                }
+            });
+        let any_ended = !ended.is_empty();
+        self.effects_ended(ended);
+        any_ended
+    }
+
+    /// Records the continuous effects that just ended as events.
+    fn effects_ended(&mut self, ended: Vec<ContinuousEffect>) {
+        for effect in ended {
+            self.events.push(GameEvent::EffectEnded {
+                source: effect
+                    .source
+                    .map(|source| ObjectSnapshot::of(&self.world, source)),
+                controller: effect.controller,
+            });
+        }
+    }
+
+    /// 702.131b Ascend on a permanent represents a static ability. It means “Any time you control
+    ///          ten or more permanents and you don’t have the city’s blessing, you get the city’s
+    ///          blessing for the rest of the game.”
+    ///
+    /// Gives the city’s blessing to every player who meets the condition. This is checked whenever
+    /// the number of permanents a player controls may have grown, i.e. whenever a permanent enters
+    /// the battlefield, phases in or changes control.
+    fn check_ascend(&mut self) -> Result<(), EngineError> {
+        let mut permanents = HashMap::<PlayerId, usize>::new();
+        let mut ascending = Vec::new();
+        for (entity, (controller, zone)) in &mut self
+            .world
+            .query::<(&Controller, &Zone)>()
+            .without::<PhasedOut>()
+        {
+            if *zone != Zone::Battlefield {
+                continue;
+            }
+            *permanents.entry(controller.0).or_default() += 1;
+            if self.has_keyword(entity, &KeywordAbility::Ascend) {
+                ascending.push(controller.0);
+            }
+        }
+        for player in self.apnap_order()? {
+            if ascending.contains(&player)
+                && permanents.get(&player).copied().unwrap_or_default() >= 10
+            {
+                let _ = self.gain_designation(player, Designation::CitysBlessing);
+            }
+        }
+        Ok(())
+    }
+
+    /// 704.5j If a player controls two or more legendary permanents with the same name, that player
+    ///        chooses one of them, and the rest are put into their owners’ graveyards. This is
+    ///        called the “legend rule.”
+    ///
+    /// 704.5k If two or more permanents have the supertype world, all except the one that has had
+    ///        the world supertype for the shortest amount of time are put into their owners’
+    ///        graveyards. In the event of a tie for the shortest amount of time, all are put into
+    ///        their owners’ graveyards. This is called the “world rule.”
+    ///
+    /// Puts the permanents that violate the legend rule, the world rule or the planeswalker
+    /// uniqueness rule, if enabled, into their owners’ graveyards at the same time. This is checked
+    /// whenever a permanent enters the battlefield, phases in or changes control.
+    ///
+    /// # Remarks
+    /// The player can't choose which permanent to keep yet, so the newest one is kept.
+    fn check_uniqueness_rules(&mut self) -> Result<(), EngineError> {
+        let mut permanents = self
+            .world
+            .query::<(&Zone, &Timestamp, &Controller, &Owner, &CardRef)>()
+            .without::<PhasedOut>()
+            .iter()
+            .filter(|(_, (&zone, ..))| zone == Zone::Battlefield)
+            .map(|(entity, (_, &timestamp, controller, owner, card))| {
+                (
+                    timestamp,
+                    entity,
+                    controller.0,
+                    owner.0,
+                    Arc::clone(&card.0),
+                )
+            })
+            .collect::<Vec<_>>();
+        // Newer permanents come first, so the first one of each group is the one that stays.
+        permanents.sort_unstable_by_key(|it| Reverse(it.0));
+
+        let mut kept = HashSet::new();
+        let mut moves = Vec::new();
+        for (_, entity, controller, owner, card) in permanents {
+            let type_line = &card.type_line;
+            let mut groups = Vec::new();
+            if type_line.supertype.contains(&Supertype::Legendary) {
+                groups.push(UniquenessGroup::Legend(controller, card.name.0.clone()));
+            }
+            if type_line.supertype.contains(&Supertype::World) {
+                groups.push(UniquenessGroup::World);
+            }
+            if self.planeswalker_uniqueness && type_line.card_type.contains(&CardType::Planeswalker)
+            {
+                groups.extend(type_line.subtype.iter().filter_map(|it| match it {
+                    Subtype::Planeswalker(subtype) => {
+                        Some(UniquenessGroup::Planeswalker(controller, *subtype))
+                    }
+                    _ => None,
+                }));
+            }
+            // A permanent that is kept for one rule may still have to go for another one.
+            let mut goes = false;
+            for group in groups {
+                goes |= !kept.insert(group);
+            }
+            if goes {
+                log::debug!("{entity:?} is put into the graveyard by a uniqueness rule.");
+                moves.push((entity, Zone::Graveyard(owner)));
+            }
+        }
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            rule = "704.5j",
+            objects = ?self.object_ids(moves.iter().map(|it| it.0)),
+            "State-based actions are checked."
+        );
+        self.move_objects_simultaneously(&moves)
+    }
+
+    /// Gives the specified designation to a player for the rest of the game unless they already
+    /// have it.
+    pub(crate) fn gain_designation(
+        &mut self,
+        player: PlayerId,
+        designation: Designation,
+    ) -> Result<(), EngineError> {
+        if self.player_mut(player)?.designations.insert(designation) {
+            log::debug!("Player with id {} gets {designation:?}.", player.0);
+            self.events.push(GameEvent::DesignationGained {
+                player,
+                designation,
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns whether the specified player has the designation.
+    pub(crate) fn has_designation(&self, player: PlayerId, designation: Designation) -> bool {
+        self.player(player)
+            .is_ok_and(|it| it.designations.contains(&designation))
+    }
+
+    /// Makes the specified player gain control of a permanent for the given duration, e.g. “Gain
+    /// control of target creature until end of turn.”
+    pub(crate) fn gain_control(
+        &mut self,
+        new_controller: PlayerId,
+        entity: Entity,
+        duration: Duration,
+    ) -> Result<(), EngineError> {
+        self.player(new_controller)?;
+        if !self.is_on_battlefield(entity) {
+            return Err(EngineError::InvalidTarget(format!(
+                "Only permanents can change control but {entity:?} is not on the battlefield."
+            )));
+        }
+        self.add_effect(ContinuousEffect {
+            source: None,
+            controller: new_controller,
+            duration,
+            kind: EffectKind::ChangeControl {
+                target: entity,
+                controller: new_controller,
+            },
+        });
+        Ok(())
+    }
+
+    /// Replaces the chooser that chooses the cards players discard down to their maximum hand
+    /// size. Without one, players discard the cards that were put into their hand most recently.
+    pub(crate) fn set_discard_chooser(&mut self, chooser: Box<DiscardChooser>) {
+        self.discard_chooser = Some(chooser);
+    }
+
+    /// Returns the current controller of the specified permanent.
+    pub(crate) fn controller(&self, entity: Entity) -> Option<PlayerId> {
+        self.world.get::<Controller>(entity).ok().map(|it| it.0)
+    }
+
+    /// Returns the keyword abilities the specified object currently has, including the ones granted
+    /// by continuous effects.
+    pub(crate) fn keywords(&self, entity: Entity) -> IndexSet<KeywordAbility> {
+        let mut keywords = self
+            .world
+            .get::<Keywords>(entity)
+            .map(|it| it.0.clone())
+            .unwrap_or_default();
+        // 613.1f Layer 6: Ability-adding effects, keyword counters, ability-removing effects, and
+        //        effects that say an object can’t have an ability are applied.
+        //
+        // Effects are iterated in timestamp order, so that an ability-removing effect only removes
+        // the abilities granted by earlier effects.
+        for (_, effect) in self.effects.iter() {
+            match effect.kind {
+                EffectKind::AddKeyword {
+                    target,
+                    ref keyword,
+                } if target == entity => {
+                    keywords.insert(keyword.clone());
+                }
+                EffectKind::LoseAllAbilities { target } if target == entity => keywords.clear(),
+                _ => {}
+            }
+        }
+        keywords
+    }
+
+    /// Returns whether the specified object still has the abilities it is printed with, i.e. no
+    /// ability-removing effect applies to it. Printed abilities are always older than the effects
+    /// applying to the object, so any such effect removes them.
+    ///
+    /// # Remarks
+    /// Type-changing effects generated by the static abilities of the object still apply, since
+    /// layer 4 is applied before the abilities are removed.
+    fn has_printed_abilities(&self, entity: Entity) -> bool {
+        !self.effects.iter().any(|(_, effect)| {
+            matches!(effect.kind, EffectKind::LoseAllAbilities { target } if target == entity)
+        })
+    }
+
+    /// Returns whether the specified object currently has the keyword ability.
+    pub(crate) fn has_keyword(&self, entity: Entity, keyword: &KeywordAbility) -> bool {
+        self.keywords(entity).contains(keyword)
+    }
+
+    /// 508.1a The active player chooses which creatures that they control, if any, will attack. The
+    ///        chosen creatures must be untapped, they can’t also be battles, and each one must
+    ///        either have haste or have been controlled by the active player continuously since
+    ///        the turn began.
+    ///
+    /// 702.3b A creature with defender can’t attack.
+    pub(crate) fn can_attack(&self, entity: Entity) -> bool {
+        self.is_on_battlefield(entity)
+            && self.has_card_type(entity, CardType::Creature)
+            && self.controller(entity) == Some(self.turn.active_player)
+            && self.world.get::<Tapped>(entity).is_err()
+            && self.world.get::<Attacking>(entity).is_err()
+            && !self.is_summoning_sick(entity)
+            && !self.has_keyword(entity, &KeywordAbility::Defender)
+    }
+
+    /// 302.6. A creature’s activated ability with the tap symbol or the untap symbol in its
+    ///        activation cost can’t be activated unless the creature has been under its
+    ///        controller’s control continuously since their most recent turn began. [...]
+    ///
+    /// Returns whether the specified object is a creature that can't pay {T} costs yet because
+    /// it has neither been under the control of its controller since their turn began nor haste.
+    pub(crate) fn is_summoning_sick(&self, entity: Entity) -> bool {
+        self.has_card_type(entity, CardType::Creature)
+            && self.world.get::<SummoningSick>(entity).is_ok()
+            && !self.has_keyword(entity, &KeywordAbility::Haste)
+    }
+
+    /// 508.1d The active player checks each creature they control to see whether it’s affected by
+    ///        any requirements (effects that say a creature must attack, or that it must attack if
+    ///        some condition is met). If the number of requirements that are being obeyed is fewer
+    ///        than the maximum possible number of requirements that could be obeyed without
+    ///        disobeying any restrictions, the declaration of attackers is illegal. [...]
+    ///
+    /// 508.1f The active player taps the chosen creatures. Tapping a creature when it’s declared as
+    ///        an attacker isn’t a cost; attacking simply causes creatures to become tapped.
+    ///
+    /// Declares all attackers of the active player at once. The declaration is rejected as a whole
+    /// if a creature can't attack, attacking disobeys a restriction or a creature that must attack
+    /// is left out although it could attack without disobeying any restriction.
+    ///
+    /// # Remarks
+    /// All creatures attack the next player in turn order since attacks on other players and on
+    /// planeswalkers aren't supported yet. Restrictions only ever affect a single creature, so the
+    /// maximum number of requirements is obeyed exactly if every creature with requirements that
+    /// is able to attack does so. Requirements are only enforced if attackers are declared at all.
+    pub(crate) fn declare_attackers(
+        &mut self,
+        player: PlayerId,
+        attackers: &[Entity],
+    ) -> Result<(), EngineError> {
+        #[cfg(feature = "tracing")]
+        let _span = self.spans.action("declare_attackers", player);
+        if self.turn.step != Step::DeclareAttackers || player != self.turn.active_player {
+            return Err(EngineError::IllegalTiming(
+                "Only the active player can declare attackers during the declare attackers step."
+                    .into(),
+            ));
+        }
+        if self.combat.attackers_declared() {
+            return Err(EngineError::IllegalAction(
+                "Attackers have already been declared this combat.".into(),
+            ));
+        }
+        let defender = self.apnap_order()?.get(1).copied();
+        for (index, &entity) in attackers.iter().enumerate() {
+            if attackers[..index].contains(&entity) {
+                return Err(EngineError::IllegalAction(format!(
+                    "{entity:?} is declared as an attacker more than once."
+                )));
+            }
+            if !self.can_attack(entity) {
+                return Err(EngineError::IllegalAction(format!(
+                    "{entity:?} can't attack."
+                )));
+            }
+            if let Some(constraint) = self.disobeyed_attack_restriction(entity, defender) {
+                return Err(EngineError::IllegalAction(format!(
+                    "{entity:?} can't attack because of the restriction {constraint:?}."
+                )));
+            }
+        }
+        let creatures = self.creatures_controlled_by(player);
+        for entity in creatures.into_iter().filter_map(|it| self.object(it)) {
+            if attackers.contains(&entity)
+                || !self.can_attack(entity)
+                || self
+                    .disobeyed_attack_restriction(entity, defender)
+                    .is_some()
+            {
+                continue;
+            }
+            if let Some(constraint) = self
+                .attack_constraints(entity)
+                .into_iter()
+                .find(|it| it.is_requirement())
+            {
+                return Err(EngineError::IllegalAction(format!(
+                    "{entity:?} is able to attack and has to because of the requirement \
+                     {constraint:?}."
+                )));
+            }
+        }
+
+        for &entity in attackers {
+            // 702.20b Attacking doesn’t cause creatures with vigilance to tap. [...]
+            if !self.has_keyword(entity, &KeywordAbility::Vigilance) {
+                self.world
+                    .insert_one(entity, Tapped)
+                    .map_err(|_| EngineError::UnknownObject(entity))?;
+            }
+            self.world
+                .insert_one(entity, Attacking)
+                .map_err(|_| EngineError::UnknownObject(entity))?;
+            log::trace!("{entity:?} attacks.");
+            self.turn.tracker.record_attacker(entity);
+        }
+        let attacks = defender.map_or_else(Vec::new, |defender| {
+            attackers.iter().map(|&it| (it, defender)).collect()
+        });
+        self.combat.declare_attacks(&attacks);
+        // 508.3a An ability that reads “Whenever [a creature] attacks, . . .” triggers if that
+        //        creature is declared as an attacker. [...]
+        for &object in attackers {
+            self.check_triggers(&TriggerEvent::Attacks {
+                object,
+                controller: player,
+            });
+        }
+        Ok(())
+    }
+
+    /// 702.49c A ninjutsu ability may be activated only while a creature on the battlefield is
+    ///         unblocked (see rule 509.1h). The creature with ninjutsu is put onto the battlefield
+    ///         unblocked. [...]
+    ///
+    /// Activates the ninjutsu ability of a card in the hand of the specified player by returning
+    /// the unblocked attacker to its owner's hand. The ninja attacks the same player but was never
+    /// declared as an attacker, so abilities that trigger whenever it attacks don't trigger. The
+    /// payment is planned automatically unless a plan is given.
+    pub(crate) fn ninjutsu(
+        &mut self,
+        player: PlayerId,
+        ninja: Entity,
+        attacker: Entity,
+        plan: Option<PaymentPlan>,
+    ) -> Result<(), EngineError> {
+        if self.zones.zone_of(ninja) != Some(Zone::Hand(player)) {
+            return Err(EngineError::IllegalAction(format!(
+                "{ninja:?} is not in the hand of player with id {}.",
+                player.0
+            )));
+        }
+        let cost = self.world.get::<CardRef>(ninja).ok().and_then(|it| {
+            it.0.abilities.iter().find_map(|it| match it {
+                Ability::Keyword(KeywordAbility::Ninjutsu(cost)) => Some(cost.clone()),
+                _ => None,
+            })
+        });
+        let Some(cost) = cost else {
+            return Err(EngineError::IllegalAction(format!(
+                "{ninja:?} doesn't have ninjutsu."
+            )));
+        };
+        if !self.combat.blockers_declared() {
+            return Err(EngineError::IllegalTiming(
+                "Ninjutsu can only be activated after blockers have been declared.".into(),
+            ));
+        }
+        let defender = self.combat.defender(attacker);
+        let Some(defender) = defender.filter(|_| {
+            !self.combat.is_blocked(attacker) && self.is_controlled_by(attacker, player)
+        }) else {
+            return Err(EngineError::IllegalAction(format!(
+                "{attacker:?} is not an unblocked attacking creature controlled by player with id \
+                 {}.",
+                player.0
+            )));
+        };
+
+        self.pay_mana_cost(player, &cost, None, plan)?;
+        log::debug!(
+            "Player with id {} swaps {attacker:?} for {ninja:?} using ninjutsu.",
+            player.0
+        );
+        let owner = self
+            .world
+            .get::<Owner>(attacker)
+            .map_err(|_| EngineError::UnknownObject(attacker))?
+            .0;
+        self.move_object(attacker, Zone::Hand(owner))?;
+        self.move_object(ninja, Zone::Battlefield)?;
+        self.world
+            .insert(ninja, (Tapped, Attacking))
+            .map_err(|_| EngineError::UnknownObject(ninja))?;
+        self.combat.attack(ninja, defender);
+        Ok(())
+    }
+
+    /// Returns the restrictions and requirements continuous effects impose on whether the
+    /// specified creature attacks, ordered by their timestamps.
+    fn attack_constraints(&self, entity: Entity) -> Vec<AttackConstraint> {
+        self.effects
+            .iter()
+            .filter_map(|(_, effect)| match effect.kind {
+                EffectKind::AttackConstraint { target, constraint } if target == entity => {
+                    Some(constraint)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// 508.1c [...] If any restrictions are being disobeyed, the declaration of attackers is
+    ///        illegal.
+    ///
+    /// Returns the first restriction the specified creature would disobey by attacking the
+    /// defending player, if any.
+    fn disobeyed_attack_restriction(
+        &self,
+        entity: Entity,
+        defender: Option<PlayerId>,
+    ) -> Option<AttackConstraint> {
+        self.attack_constraints(entity)
+            .into_iter()
+            .find(|&constraint| match constraint {
+                AttackConstraint::MustAttack | AttackConstraint::Goaded(_) => false,
+                AttackConstraint::CantAttack => true,
+                AttackConstraint::CantAttackPlayer(player) => defender == Some(player),
+                AttackConstraint::CantAttackUnlessDefenderControls(subtype) => !defender
+                    .is_some_and(|defender| {
+                        self.permanents_with_subtype(subtype)
+                            .into_iter()
+                            .filter_map(|it| self.object(it))
+                            .any(|it| self.is_controlled_by(it, defender))
+                    }),
+            })
+    }
+
+    /// Goads the specified creature on behalf of a player. Until that player's next turn, the
+    /// creature attacks each combat if able and attacks a player other than them if able.
+    pub(crate) fn goad(&mut self, player: PlayerId, entity: Entity) -> Result<(), EngineError> {
+        self.player(player)?;
+        if !self.is_on_battlefield(entity) || !self.has_card_type(entity, CardType::Creature) {
+            return Err(EngineError::InvalidTarget(format!(
+                "Only creatures on the battlefield can be goaded but {entity:?} is none."
+            )));
+        }
+        self.add_effect(ContinuousEffect {
+            source: None,
+            controller: player,
+            duration: Duration::UntilYourNextTurn,
+            kind: EffectKind::AttackConstraint {
+                target: entity,
+                constraint: AttackConstraint::Goaded(player),
+            },
+        });
+        Ok(())
+    }
+
+    /// Returns the state of the current combat.
+    pub(crate) fn combat(&self) -> &Combat {
+        &self.combat
+    }
+
+    /// 509.1a The defending player chooses which creatures they control, if any, will block. The
+    ///        chosen creatures must be untapped and they can’t also be battles. For each of the
+    ///        chosen creatures, the defending player chooses one creature for it to block that’s
+    ///        attacking that player, a planeswalker they control, or a battle they protect.
+    ///
+    /// Declares all blocks of the specified player at once, each as a pair of a blocker and the
+    /// attacker it blocks. A creature can appear in several pairs if it can block additional
+    /// creatures. The damage assignment order of each attacker follows the order of the pairs
+    /// until the active player announces a different one.
+    pub(crate) fn declare_blockers(
+        &mut self,
+        player: PlayerId,
+        blocks: &[(Entity, Entity)],
+    ) -> Result<(), EngineError> {
+        #[cfg(feature = "tracing")]
+        let _span = self.spans.action("declare_blockers", player);
+        if self.turn.step != Step::DeclareBlockers {
+            return Err(EngineError::IllegalTiming(
+                "Blockers can only be declared during the declare blockers step.".into(),
+            ));
+        }
+        if self.combat.blockers_declared() {
+            return Err(EngineError::IllegalAction(
+                "Blockers have already been declared this combat.".into(),
+            ));
+        }
+        for (index, &(blocker, attacker)) in blocks.iter().enumerate() {
+            if blocks[..index].contains(&(blocker, attacker)) {
+                return Err(EngineError::IllegalAction(format!(
+                    "{blocker:?} is declared as a blocker for {attacker:?} more than once."
+                )));
+            }
+            self.ensure_can_block(player, blocker, attacker)?;
+            let count = blocks.iter().filter(|it| it.0 == blocker).count() as u64;
+            if count > 1 + self.additional_blocks(blocker) {
+                return Err(EngineError::IllegalAction(format!(
+                    "{blocker:?} can't block {count} creatures."
+                )));
+            }
+        }
+
+        for &(blocker, attacker) in blocks {
+            log::trace!("{blocker:?} blocks {attacker:?}.");
+        }
+        self.combat.declare_blocks(blocks);
+        Ok(())
+    }
+
+    /// Returns an error unless the specified creature of the player can block the attacker on its
+    /// own, leaving aside how many other creatures it blocks.
+    fn ensure_can_block(
+        &self,
+        player: PlayerId,
+        blocker: Entity,
+        attacker: Entity,
+    ) -> Result<(), EngineError> {
+        if !self.can_block(player, blocker) {
+            return Err(EngineError::IllegalAction(format!(
+                "{blocker:?} can't block."
+            )));
+        }
+        if self.combat.defender(attacker) != Some(player) {
+            return Err(EngineError::IllegalAction(format!(
+                "{attacker:?} isn't attacking player with id {}.",
+                player.0
+            )));
+        }
+        // 702.9b A creature with flying can’t be blocked except by creatures with flying and/or
+        //        reach. [...]
+        if self.has_keyword(attacker, &KeywordAbility::Flying)
+            && !self.has_keyword(blocker, &KeywordAbility::Flying)
+            && !self.has_keyword(blocker, &KeywordAbility::Reach)
+        {
+            return Err(EngineError::IllegalAction(format!(
+                "{blocker:?} can't block {attacker:?} since it has flying."
+            )));
+        }
+        // 702.16f Attacking creatures with protection can’t be blocked by creatures that have the
+        //         stated quality.
+        if self.is_protected_from(PermanentOrPlayer::Permanent(attacker), blocker) {
+            return Err(EngineError::IllegalAction(format!(
+                "{blocker:?} can't block {attacker:?} since it has protection from it."
+            )));
+        }
+        Ok(())
+    }
+
+    /// 506.4. A permanent that’s removed from combat stops being an attacking, blocking, blocked,
+    ///        and/or unblocked creature. [...]
+    ///
+    /// Removes the specified creature from combat, which happens when it leaves the battlefield,
+    /// changes control, stops being a creature or regenerates.
+    pub(crate) fn remove_from_combat(&mut self, entity: Entity) {
+        if self.world.remove_one::<Attacking>(entity).is_ok()
+            || !self.combat.blocked_by(entity).is_empty()
+        {
+            log::trace!("{entity:?} is removed from combat.");
+        }
+        self.combat.remove(entity);
+    }
+
+    /// 509.1a [...] The chosen creatures must be untapped and they can’t also be battles. [...]
+    fn can_block(&self, player: PlayerId, entity: Entity) -> bool {
+        self.is_on_battlefield(entity)
+            && self.has_card_type(entity, CardType::Creature)
+            && self.controller(entity) == Some(player)
+            && self.world.get::<Tapped>(entity).is_err()
+    }
+
+    /// Returns the number of creatures the specified creature can block in addition to the first
+    /// one.
+    fn additional_blocks(&self, entity: Entity) -> u64 {
+        self.effects
+            .iter()
+            .filter_map(|(_, effect)| match effect.kind {
+                EffectKind::BlockAdditionalCreatures { target, amount } if target == entity => {
+                    Some(amount)
+                }
+                _ => None,
+            })
+            .sum()
+    }
+
+    /// 509.2. Second, for each attacking creature that’s become blocked, the active player
+    ///        announces that creature’s damage assignment order, which consists of the creatures
+    ///        blocking it in an order of that player’s choice. [...]
+    ///
+    /// Announces the damage assignment order of the specified attacker on behalf of the active
+    /// player, which must consist of exactly the creatures blocking it.
+    pub(crate) fn order_blockers(
+        &mut self,
+        player: PlayerId,
+        attacker: Entity,
+        order: &[Entity],
+    ) -> Result<(), EngineError> {
+        if self.turn.step != Step::DeclareBlockers || player != self.turn.active_player {
+            return Err(EngineError::IllegalTiming(
+                "Only the active player can announce damage assignment orders during the declare \
+                 blockers step."
+                    .into(),
+            ));
+        }
+        if !self.combat.order_blockers(attacker, order) {
+            return Err(EngineError::IllegalAction(format!(
+                "{order:?} are not exactly the creatures blocking {attacker:?}."
+            )));
+        }
+        Ok(())
+    }
+
+    /// 510.1c A blocked creature assigns its combat damage to the creatures blocking it. [...] it
+    ///        can’t assign combat damage to a creature that’s blocking it unless, when combat
+    ///        damage assignments are complete, each creature that precedes that blocking creature
+    ///        in its order is assigned lethal damage. [...]
+    ///
+    /// 702.19b The controller of an attacking creature with trample first assigns damage to the
+    ///         creature(s) blocking it. Once all those blocking creatures are assigned lethal
+    ///         damage, any excess damage is assigned as its controller chooses among those blocking
+    ///         creatures and the player, planeswalker, or battle the creature is attacking. [...]
+    ///
+    /// Chooses how the specified blocked attacker assigns its combat damage in the upcoming combat
+    /// damage step. Attackers whose controllers don't choose assign lethal damage to each blocker
+    /// in order and the rest to the last one or, if they have trample, to the player they attack.
+    pub(crate) fn assign_combat_damage(
+        &mut self,
+        attacker: Entity,
+        assignment: Vec<(PermanentOrPlayer, u64)>,
+    ) -> Result<(), EngineError> {
+        if self.turn.step != Step::DeclareBlockers {
+            return Err(EngineError::IllegalTiming(
+                "Combat damage can only be assigned ahead of time during the declare blockers \
+                 step."
+                    .into(),
+            ));
+        }
+        if !self.combat.is_blocked(attacker) {
+            return Err(EngineError::IllegalAction(format!(
+                "{attacker:?} isn't blocked."
+            )));
+        }
+        let power = self.combat_power(attacker);
+        let total = assignment.iter().map(|(_, amount)| amount).sum::<u64>();
+        if total != power {
+            return Err(EngineError::IllegalAction(format!(
+                "{attacker:?} has to assign {power} combat damage but assigns {total}."
+            )));
+        }
+
+        let blockers = self.combat.blockers(attacker).to_vec();
+        let defender = self.combat.defender(attacker);
+        let trample = self.has_keyword(attacker, &KeywordAbility::Trample);
+        let assigned = |recipient| {
+            assignment
+                .iter()
+                .filter(|(it, _)| *it == recipient)
+                .map(|(_, amount)| amount)
+                .sum::<u64>()
+        };
+        for (recipient, _) in &assignment {
+            let is_allowed = match *recipient {
+                PermanentOrPlayer::Permanent(entity) => blockers.contains(&entity),
+                PermanentOrPlayer::Player(player) => trample && Some(player) == defender,
+            };
+            if !is_allowed {
+                return Err(EngineError::IllegalAction(format!(
+                    "{attacker:?} can't assign combat damage to {recipient:?}."
+                )));
+            }
+        }
+        // Every recipient needs all blockers before it in the order to be assigned lethal damage.
+        let recipients = blockers
+            .iter()
+            .map(|&it| PermanentOrPlayer::Permanent(it))
+            .chain(defender.map(PermanentOrPlayer::Player));
+        let mut all_lethal = true;
+        for recipient in recipients {
+            if assigned(recipient) > 0 && !all_lethal {
+                return Err(EngineError::IllegalAction(format!(
+                    "{attacker:?} can't assign combat damage to {recipient:?} before each \
+                     creature ahead of it in the damage assignment order is assigned lethal \
+                     damage."
+                )));
+            }
+            if let PermanentOrPlayer::Permanent(blocker) = recipient {
+                all_lethal &= assigned(recipient) >= self.lethal_damage(attacker, blocker);
+            }
+        }
+        self.combat.assign(attacker, assignment);
+        Ok(())
+    }
+
+    /// 510.1a Each attacking creature and each blocking creature assigns combat damage equal to its
+    ///        power. Creatures that would assign 0 or less damage this way don’t assign combat
+    ///        damage at all.
+    fn combat_power(&self, entity: Entity) -> u64 {
+        self.power_toughness(entity)
+            .map_or(0, |(power, _)| power.max(0).unsigned_abs())
+    }
+
+    /// 702.2c [...] any nonzero amount of combat damage assigned to a creature by a source with
+    ///        deathtouch is considered to be lethal damage [...]
+    ///
+    /// Returns the amount of damage the source has to assign to the creature for it to be lethal,
+    /// taking damage already marked on the creature into account.
+    fn lethal_damage(&self, source: Entity, creature: Entity) -> u64 {
+        if self.has_keyword(source, &KeywordAbility::Deathtouch) {
+            return 1;
+        }
+        let toughness = self.power_toughness(creature).map_or(0, |(_, it)| it);
+        let marked_damage = self
+            .world
+            .get::<MarkedDamage>(creature)
+            .map_or(0, |it| it.0);
+        (toughness - marked_damage as i64).max(0).unsigned_abs()
+    }
+
+    /// Divides the damage among the recipients in order, assigning lethal damage to each creature
+    /// before moving on to the next one. Whatever is left is assigned to the player, if there is
+    /// one, or to the last creature otherwise.
+    fn default_assignment(
+        &self,
+        source: Entity,
+        creatures: &[Entity],
+        player: Option<PlayerId>,
+        damage: u64,
+    ) -> Vec<(PermanentOrPlayer, u64)> {
+        let mut remaining = damage;
+        let mut assignment = Vec::new();
+        for &creature in creatures {
+            let amount = remaining.min(self.lethal_damage(source, creature));
+            assignment.push((PermanentOrPlayer::Permanent(creature), amount));
+            remaining -= amount;
+        }
+        match (player, assignment.last_mut()) {
+            (Some(player), _) => assignment.push((PermanentOrPlayer::Player(player), remaining)),
+            (None, Some((_, amount))) => *amount += remaining,
+            (None, None) => {}
+        }
+        assignment.retain(|(_, amount)| *amount > 0);
+        assignment
+    }
+
+    /// 510.1. First, the active player announces how each attacking creature assigns its combat
+    ///        damage, then the defending player announces how each blocking creature assigns its
+    ///        combat damage. [...]
+    ///
+    /// 510.2. Second, all combat damage that’s been assigned is dealt simultaneously. [...]
+    ///
+    /// # Remarks
+    /// There is no additional combat damage step for creatures with first strike or double strike
+    /// yet.
+    fn deal_combat_damage(&mut self) -> Result<(), EngineError> {
+        let participants = self
+            .combat
+            .attackers()
+            .chain(self.combat.blocking_creatures())
+            .collect::<Vec<_>>();
+        for entity in participants {
+            if !self.has_card_type(entity, CardType::Creature) {
+                self.remove_from_combat(entity);
+            }
+        }
+
+        let mut assignments = Vec::new();
+        for attacker in self.combat.attackers().collect::<Vec<_>>() {
+            let power = self.combat_power(attacker);
+            if power == 0 {
+                continue;
+            }
+            let defender = self.combat.defender(attacker);
+            let assignment = if let Some(assignment) = self.combat.assignment(attacker) {
+                assignment.to_vec()
+            } else if self.combat.is_blocked(attacker) {
+                let blockers = self.combat.blockers(attacker).to_vec();
+                let trample = self.has_keyword(attacker, &KeywordAbility::Trample);
+                self.default_assignment(attacker, &blockers, defender.filter(|_| trample), power)
+            } else {
+                defender
+                    .map(|it| vec![(PermanentOrPlayer::Player(it), power)])
+                    .unwrap_or_default()
+            };
+            assignments.push((attacker, assignment));
+        }
+        // 510.1d A blocking creature assigns combat damage to the creatures it’s blocking. [...]
+        for blocker in self.combat.blocking_creatures().collect::<Vec<_>>() {
+            let power = self.combat_power(blocker);
+            if power == 0 {
+                continue;
+            }
+            let attackers = self.combat.blocked_by(blocker).to_vec();
+            let assignment = self.default_assignment(blocker, &attackers, None, power);
+            assignments.push((blocker, assignment));
+        }
+
+        for (source, assignment) in assignments {
+            // 800.4a When a player leaves the game, all objects (see rule 109) owned by that player
+            //        leave the game [...]
+            //
+            // Damage is dealt one assignment at a time, so a player may already have lost the game
+            // and taken their creatures with them.
+            if self.outcome.is_some() || !self.is_on_battlefield(source) {
+                continue;
+            }
+            for (recipient, amount) in assignment {
+                if matches!(recipient, PermanentOrPlayer::Permanent(it) if !self.is_on_battlefield(it))
+                {
+                    continue;
+                }
+                self.deal_damage(&Damage {
+                    source,
+                    recipient,
+                    amount,
+                    combat: true,
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Untaps all creatures that attacked this turn and are still on the battlefield, e.g. “Untap
+    /// all creatures that attacked this turn.”
+    pub(crate) fn untap_attackers(&mut self) -> Result<(), EngineError> {
+        let attackers = self.turn.tracker.attackers().collect::<Vec<_>>();
+        for attacker in attackers {
+            if self.is_on_battlefield(attacker) {
+                self.untap(attacker)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Untaps the specified permanent if it is tapped, unless the untapping is replaced, e.g.
+    /// because of a stun counter on it.
+    fn untap(&mut self, entity: Entity) -> Result<(), EngineError> {
+        if self.world.get::<Tapped>(entity).is_err() {
+            return Ok(());
+        }
+        for event in self.replace(ReplaceableEvent::Untap { object: entity })? {
+            match event {
+                ReplaceableEvent::Untap { object } => {
+                    let _ = self.world.remove_one::<Tapped>(object);
+                    if self.end_effects_with_failed_conditions() {
+                        self.update_controllers();
+                    }
+                }
+                event => self.perform_replacing_event(event)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the events that happen instead of the specified event once all replacement effects
+    /// that watch for it are applied, in the order the affected player chose for them.
+    fn replace(&self, event: ReplaceableEvent) -> Result<Vec<ReplaceableEvent>, EngineError> {
+        let effects = self.replacement_effects_for(&event);
+        let order = self
+            .replacement_orders
+            .iter()
+            .find(|(it, _)| *it == event.generalized())
+            .map(|(_, order)| order.as_slice())
+            .unwrap_or_default();
+        // Effects the player hasn't ordered, e.g. those that watch for objects entering the
+        // battlefield, apply in the order they were created.
+        effects.apply(event, &mut |_, candidates| {
+            let rank = |&&index: &&usize| {
+                let description = effects.describe(index);
+                order
+                    .iter()
+                    .position(|it| *it == description)
+                    .unwrap_or(usize::MAX)
+            };
+            candidates
+                .iter()
+                .min_by_key(rank)
+                .copied()
+                .unwrap_or_default()
+        })
+    }
+
+    /// Returns the replacement effects that may watch for the specified event: those created by
+    /// spells and abilities, those of counters on the affected permanent, e.g. of a stun or a
+    /// shield counter, and those of the abilities of the affected object itself.
+    fn replacement_effects_for(&self, event: &ReplaceableEvent) -> ReplacementEffects {
+        let mut effects = self.replacement_effects.clone();
+        match *event {
+            ReplaceableEvent::Untap { object }
+            | ReplaceableEvent::Destroy { object }
+            | ReplaceableEvent::DealDamage {
+                recipient: PermanentOrPlayer::Permanent(object),
+                ..
+            } => {
+                let has_counter = |kind| {
+                    self.counters(PermanentOrPlayer::Permanent(object), &kind)
+                        .unwrap_or_default()
+                        > 0
+                };
+                if has_counter(CounterKind::Stun) {
+                    effects.add(StunCounter { object });
+                }
+                if has_counter(CounterKind::Shield) {
+                    effects.add(ShieldCounter { object });
+                }
+            }
+            ReplaceableEvent::PutIntoGraveyard { object }
+                if self
+                    .world
+                    .get::<StaticAbilities>(object)
+                    .is_ok_and(|it| it.0.contains(&StaticAbility::ShuffleIntoLibraryInstead)) =>
+            {
+                effects.add(ShuffleIntoLibraryInstead { object });
+            }
+            _ => {}
+        }
+        effects
+    }
+
+    /// Applies the replacement effects watching for an object being put into a graveyard and
+    /// returns whether they replaced it, in which case the events that happen instead have
+    /// happened. A creature put there from the battlefield dies, which effects watching for either
+    /// event replace.
+    fn replace_moving_to_graveyard(
+        &mut self,
+        object: Entity,
+        from: Zone,
+    ) -> Result<bool, EngineError> {
+        let events = if from == Zone::Battlefield && self.has_card_type(object, CardType::Creature)
+        {
+            self.replace(ReplaceableEvent::Die { object })?
+        } else {
+            vec![ReplaceableEvent::PutIntoGraveyard { object }]
+        };
+        let mut replaced = Vec::new();
+        for event in events {
+            match event {
+                ReplaceableEvent::Die { object }
+                | ReplaceableEvent::PutIntoGraveyard { object } => {
+                    replaced.extend(self.replace(ReplaceableEvent::PutIntoGraveyard { object })?);
+                }
+                event => replaced.push(event),
+            }
+        }
+        if replaced.contains(&ReplaceableEvent::PutIntoGraveyard { object }) {
+            return Ok(false);
+        }
+        for event in replaced {
+            self.perform_replacing_event(event)?;
+        }
+        Ok(true)
+    }
+
+    /// Applies the replacement effects watching for a permanent entering the battlefield, e.g.
+    /// making it enter tapped, right after it was put onto the battlefield.
+    fn replace_entering_battlefield(&mut self, object: Entity) -> Result<(), EngineError> {
+        let event = ReplaceableEvent::EnterBattlefield {
+            object,
+            tapped: false,
+        };
+        for event in self.replace(event)? {
+            match event {
+                ReplaceableEvent::EnterBattlefield { object, tapped } => {
+                    if tapped {
+                        let _ = self.world.insert_one(object, Tapped);
+                    }
+                }
+                event => self.perform_replacing_event(event)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Performs an event that happens instead of another one, e.g. removing a shield counter
+    /// instead of destroying a permanent.
+    fn perform_replacing_event(&mut self, event: ReplaceableEvent) -> Result<(), EngineError> {
+        match event {
+            ReplaceableEvent::DrawCard { player } => self.draw_top_card(player).map(|_| ()),
+            ReplaceableEvent::GainLife { player, amount } => self.gain_life(player, amount),
+            ReplaceableEvent::RemoveCounters {
+                object,
+                kind,
+                amount,
+            } => self
+                .remove_counters(PermanentOrPlayer::Permanent(object), &kind, amount)
+                .map(|_| ()),
+            ReplaceableEvent::ShuffleIntoLibrary { object } => self.shuffle_into_library(object),
+            ReplaceableEvent::Exile { object } => self.move_object(object, Zone::Exile),
+            event => Err(EngineError::IllegalAction(format!(
+                "{event:?} can't happen instead of another event."
+            ))),
+        }
+    }
+
+    /// Adds an additional combat phase followed by an additional main phase after the current
+    /// phase, e.g. “After this main phase, there is an additional combat phase followed by an
+    /// additional main phase.”
+    pub(crate) fn add_combat_phase(&mut self) {
+        self.turn.add_combat_phase();
+    }
+
+    /// 511.3. As the end of combat step ends, all creatures and planeswalkers are removed from
+    ///        combat. After the end of combat step ends, all “until end of combat” effects end.
+    fn end_combat(&mut self) {
+        let attacking = self
+            .world
+            .query::<&Attacking>()
+            .iter()
+            .map(|(entity, _)| entity)
+            .collect::<Vec<_>>();
+        for entity in attacking {
+            let _ = self.world.remove_one::<Attacking>(entity);
+        }
+        self.combat = Combat::default();
+        let ended = self.effects.end_of_combat();
+        self.effects_ended(ended);
+    }
+
+    /// Returns the timestamp the specified object received as it entered its current zone.
+    pub(crate) fn timestamp(&self, entity: Entity) -> Option<Timestamp> {
+        self.world.get::<Timestamp>(entity).ok().map(|it| *it)
+    }
+
+    /// Hands out a timestamp that is later than all timestamps handed out before.
+    fn next_timestamp(&mut self) -> Timestamp {
+        self.timestamp = Timestamp(self.timestamp.0 + 1);
+        self.timestamp
+    }
+
+    /// Returns the current power and toughness of the specified object or [`None`] if it has no
+    /// power and toughness.
+    ///
+    /// # Remarks
+    /// Characteristic-defining abilities are not implemented yet, thus a star (*) is treated as 0.
+    pub(crate) fn power_toughness(&self, entity: Entity) -> Option<(i64, i64)> {
+        let pt = *self.world.get::<PtCharacteristic>(entity).ok()?;
+        let value = |it| match it {
+            PtValue::Fixed(value) => value,
+            PtValue::Variable => 0,
+        };
+        let (mut power, mut toughness) = (value(pt.power), value(pt.toughness));
+
+        // 613.4b Layer 7b: Effects that set power and/or toughness to a specific number or value
+        //        are applied. [...]
+        //
+        // The effects are ordered by timestamp, hence the latest one wins.
+        for (_, effect) in self.effects.iter() {
+            if let EffectKind::SetPowerToughness {
+                target,
+                power: set_power,
+                toughness: set_toughness,
+            } = effect.kind
+            {
+                if target == entity {
+                    (power, toughness) = (set_power, set_toughness);
+                }
+            }
+        }
+
+        // 613.4c Layer 7c: Effects and counters that modify power and/or toughness (but don’t set
+        //        power and/or toughness to a specific number or value) are applied.
+        if let Ok(counters) = self.world.get::<Counters>(entity) {
+            let modifier = counters.get(&CounterKind::PlusOnePlusOne) as i64
+                - counters.get(&CounterKind::MinusOneMinusOne) as i64;
+            power += modifier;
+            toughness += modifier;
+        }
+        for (_, effect) in self.effects.iter() {
+            match effect.kind {
+                EffectKind::ModifyPowerToughness {
+                    target,
+                    power: power_modifier,
+                    toughness: toughness_modifier,
+                } if target == entity => {
+                    power += power_modifier;
+                    toughness += toughness_modifier;
+                }
+                EffectKind::ChangeControl { .. }
+                | EffectKind::AddKeyword { .. }
+                | EffectKind::LoseAllAbilities { .. }
+                | EffectKind::ModifyPowerToughness { .. }
+                | EffectKind::SetPowerToughness { .. }
+                | EffectKind::ModifyMaximumHandSize { .. }
+                | EffectKind::NoMaximumHandSize { .. }
+                | EffectKind::CantLoseGame { .. }
+                | EffectKind::CantWinGame { .. }
+                | EffectKind::BlockAdditionalCreatures { .. }
+                | EffectKind::AttackConstraint { .. } => {}
+            }
+        }
+        if let Ok(object_controller) = self.world.get::<Controller>(entity).map(|it| it.0) {
+            let mut sources = self
+                .world
+                .query::<(&Zone, &Controller, &StaticAbilities)>()
+                .without::<PhasedOut>();
+            for (source, (zone, controller, abilities)) in &mut sources {
+                if *zone != Zone::Battlefield || !self.has_printed_abilities(source) {
+                    continue;
+                }
+                for ability in &abilities.0 {
+                    let StaticAbility::ModifyPowerToughness {
+                        affected,
+                        power: power_modifier,
+                        toughness: toughness_modifier,
+                    } = ability
+                    else {
+                        continue;
+                    };
+                    if self.source_filter_matches(
+                        source,
+                        affected,
+                        controller.0,
+                        entity,
+                        object_controller,
+                    ) {
+                        power += power_modifier;
+                        toughness += toughness_modifier;
+                    }
+                }
+            }
+        }
+
+        Some((power, toughness))
+    }
+
+    /// 500.7. Some effects can give a player extra turns. They do this by adding the turns
+    ///        directly after the specified turn. [...]
+    ///
+    /// Gives the specified player an extra turn directly after the current turn, e.g. “Take an
+    /// extra turn after this one.”
+    pub(crate) fn take_extra_turn(&mut self, player: PlayerId) -> Result<(), EngineError> {
+        self.player(player)?;
+        self.turn_order.add_extra_turn(player);
+        Ok(())
+    }
+
+    /// Makes the specified player skip their next turn, e.g. “Skip your next turn.”
+    pub(crate) fn skip_next_turn(&mut self, player: PlayerId) -> Result<(), EngineError> {
+        self.player(player)?;
+        self.turn_order.skip_next_turn(player);
+        Ok(())
+    }
+
+    /// Returns the players taking the specified number of turns after the current one.
+    pub(crate) fn upcoming_turns(&self, amount: usize) -> Result<Vec<PlayerId>, EngineError> {
+        let players = self.players.iter().map(|it| it.id).collect::<Vec<_>>();
+        self.turn_order
+            .upcoming(self.turn.active_player, &players, amount)
+    }
+
+    /// Ends the current turn and begins the turn of the next player in turn order.
+    ///
+    /// 730.2. As the second part of the untap step (see rule 502.2), the game checks the previous
+    ///        turn to see if the game’s day/night designation should change.
+    pub(crate) fn next_turn(&mut self) -> Result<(), EngineError> {
+        self.ensure_game_in_progress()?;
+        self.ensure_no_pending_triggers()?;
+        self.empty_mana_pools()?;
+        self.loop_states.clear();
+
+        // 514.2. Second, the following actions happen simultaneously: all damage marked on
+        //        permanents (including phased-out permanents) is removed and all “until end of
+        //        turn” and “this turn” effects end. [...]
+        for (_, marked_damage) in self.world.query_mut::<&mut MarkedDamage>() {
+            marked_damage.0 = 0;
+        }
+        for (_, shields) in self.world.query_mut::<&mut RegenerationShields>() {
+            shields.0 = 0;
+        }
+        let ended = self.effects.end_of_turn();
+        self.effects_ended(ended);
+        self.prevention_effects.end_of_turn();
+        self.replacement_effects.end_of_turn();
+        self.replacement_orders.clear();
+        self.return_linked_exiles(|it| it.condition == ReturnCondition::EndOfTurn)?;
+        self.update_controllers();
+        self.check_life_totals()?;
+
+        let players = self.players.iter().map(|it| it.id).collect::<Vec<_>>();
+        let mut next_player = self.turn_order.advance(self.turn.active_player, &players)?;
+        // Players that have left the game don't take any more turns.
+        while self.player(next_player)?.has_left {
+            next_player = self.turn_order.advance(next_player, &players)?;
+        }
+        let next_turn = TurnState::new(next_player, self.turn.number + 1);
+
+        let previous_turn = std::mem::replace(&mut self.turn, next_turn);
+        self.priority = None;
+        self.passes = 0;
+        let ended = self.effects.start_of_turn(self.turn.active_player);
+        self.effects_ended(ended);
+        self.update_controllers();
+        self.phase_in();
+
+        // 502.3. Third, the active player determines which permanents they control will untap.
+        //        Then they untap them all simultaneously. [...]
+        let tapped = self
+            .world
+            .query::<(&Controller, &Tapped)>()
+            .iter()
+            .filter(|(_, (controller, _))| controller.0 == self.turn.active_player)
+            .map(|(entity, _)| entity)
+            .collect::<Vec<_>>();
+        for entity in tapped {
+            self.untap(entity)?;
+        }
+        // 302.6. [...] A creature can’t attack unless it has been under its controller’s control
+        //        continuously since their most recent turn began. [...]
+        let controlled = self
+            .world
+            .query::<(&Controller, &SummoningSick)>()
+            .iter()
+            .filter(|(_, (controller, _))| controller.0 == self.turn.active_player)
+            .map(|(entity, _)| entity)
+            .collect::<Vec<_>>();
+        for entity in controlled {
+            let _ = self.world.remove_one::<SummoningSick>(entity);
+        }
+        if let Some(day_night) = self.day_night {
+            let spells_cast = previous_turn
+                .tracker
+                .spells_cast_by(previous_turn.active_player);
+            self.set_day_night(day_night.next(spells_cast as u64))?;
+        }
+        self.previous_turn = Some(previous_turn);
+        let active_player = self.turn.active_player;
+        self.stats.record_turn(active_player);
+        self.stats
+            .record_hand_size(active_player, self.hand_size(active_player) as u64);
+        log::trace!(
+            "Turn {} of player with id {} begins.",
+            self.turn.number,
+            self.turn.active_player.0
+        );
+        #[cfg(feature = "tracing")]
+        self.spans
+            .begin_turn(self.turn.number, self.turn.active_player);
+        self.events.push(GameEvent::TurnBegan {
+            number: self.turn.number,
+            active_player: self.turn.active_player,
+        });
+        Ok(())
+    }
+
+    /// 117.5. Each time a player would receive priority, the game first performs all applicable
+    ///        state-based actions as a single event (see rule 704, “State-Based Actions”), then
+    ///        repeats this process until no state-based actions are performed. Then triggered
+    ///        abilities are put on the stack (see rule 603, “Handling Triggered Abilities”). [...]
+    ///
+    /// Ensures that no triggered abilities wait to be resolved, since a step can't end before they
+    /// have been put on the stack and resolved.
+    fn ensure_no_pending_triggers(&self) -> Result<(), EngineError> {
+        if self.pending_triggers.is_empty() {
+            Ok(())
+        } else {
+            Err(EngineError::IllegalTiming(format!(
+                "{} triggered abilities have not been resolved yet.",
+                self.pending_triggers.len()
+            )))
+        }
+    }
+
+    /// Returns a reference to the player with the specified id.
+    pub(crate) fn player(&self, id: PlayerId) -> Result<&Player, EngineError> {
+        self.players
+            .iter()
+            .find(|it| it.id == id)
+            .ok_or(EngineError::UnknownPlayer(id))
+    }
+
+    /// Returns the identity of the player with the specified id.
+    ///
+    /// # Errors
+    /// Fails with [`EngineError::UnknownPlayer`] if there is no player with the specified id.
+    pub fn player_info(&self, id: PlayerId) -> Result<&PlayerInfo, EngineError> {
+        Ok(&self.player(id)?.info)
+    }
+
+    /// Returns a mutable reference to the player with the specified id.
+    fn player_mut(&mut self, id: PlayerId) -> Result<&mut Player, EngineError> {
+        self.players
+            .iter_mut()
+            .find(|it| it.id == id)
+            .ok_or(EngineError::UnknownPlayer(id))
+    }
+
+    /// Returns a mutable reference of the internal world that stores all entities. This method is
+    /// only available to conveniently setup the game world from within tests and will be most
+    /// likely be removed once the core gameplay loop is implemented.
+    #[cfg(test)]
+    pub(crate) fn world_mut(&mut self) -> &mut World {
+        &mut self.world
+    }
+
+    /// Puts the specified amount of counters of the given kind on a permanent or player. Energy
+    /// counters a player gets are recorded as energy being gained.
+    pub(crate) fn add_counters(
+        &mut self,
+        recipient: PermanentOrPlayer,
+        kind: CounterKind,
+        amount: u64,
+    ) -> Result<(), EngineError> {
+        if let (PermanentOrPlayer::Player(player), CounterKind::Energy) = (recipient, &kind) {
+            return self.gain_energy(player, amount);
+        }
+        self.put_counters(recipient, kind.clone(), amount)?;
+        if amount > 0 {
+            self.events.push(GameEvent::CountersAdded {
+                holder: self.counter_holder(recipient),
+                kind,
+                amount,
+            });
+        }
+        Ok(())
+    }
+
+    /// Puts counters on a permanent or player without recording an event, which is left to the
+    /// caller.
+    fn put_counters(
+        &mut self,
+        recipient: PermanentOrPlayer,
+        kind: CounterKind,
+        amount: u64,
+    ) -> Result<(), EngineError> {
+        match recipient {
+            PermanentOrPlayer::Permanent(entity) => {
+                let mut counters = self
+                    .world
+                    .remove_one::<Counters>(entity)
+                    .unwrap_or_default();
+                counters.add(kind, amount);
+                self.world
+                    .insert_one(entity, counters)
+                    .map_err(|_| EngineError::UnknownObject(entity))?;
+            }
+            PermanentOrPlayer::Player(id) => self.player_mut(id)?.counters.add(kind, amount),
+        }
+        Ok(())
+    }
+
+    /// Removes up to the specified amount of counters of the given kind from a permanent or player
+    /// and returns how many were removed. Energy counters removed from a player are recorded as energy
+    /// being spent.
+    pub(crate) fn remove_counters(
+        &mut self,
+        holder: PermanentOrPlayer,
+        kind: &CounterKind,
+        amount: u64,
+    ) -> Result<u64, EngineError> {
+        let removed = match holder {
+            PermanentOrPlayer::Permanent(entity) => self
+                .world
+                .get_mut::<Counters>(entity)
+                .map_or(0, |mut it| it.remove(kind, amount)),
+            PermanentOrPlayer::Player(id) => self.player_mut(id)?.counters.remove(kind, amount),
+        };
+        log::trace!("{removed} {kind:?} counters are removed from {holder:?}.");
+        if removed == 0 {
+            return Ok(0);
+        }
+        if let (PermanentOrPlayer::Player(player), CounterKind::Energy) = (holder, kind) {
+            self.events.push(GameEvent::EnergySpent {
+                player,
+                amount: removed,
+                total: self.player(player)?.counters.get(kind),
+            });
+        } else {
+            self.events.push(GameEvent::CountersRemoved {
+                holder: self.counter_holder(holder),
+                kind: kind.clone(),
+                amount: removed,
+            });
+        }
+        Ok(removed)
+    }
+
+    /// Returns the permanent or player that has counters as it is recorded in events.
+    fn counter_holder(&self, holder: PermanentOrPlayer) -> CounterHolder {
+        match holder {
+            PermanentOrPlayer::Permanent(entity) => {
+                CounterHolder::Permanent(ObjectSnapshot::of(&self.world, entity))
+            }
+            PermanentOrPlayer::Player(player) => CounterHolder::Player(player),
+        }
+    }
+
+    /// Returns the amount of counters of the given kind on a permanent or player.
+    pub(crate) fn counters(
+        &self,
+        holder: PermanentOrPlayer,
+        kind: &CounterKind,
+    ) -> Result<u64, EngineError> {
+        match holder {
+            PermanentOrPlayer::Permanent(entity) => Ok(self
+                .world
+                .get::<Counters>(entity)
+                .map_or(0, |counters| counters.get(kind))),
+            PermanentOrPlayer::Player(id) => Ok(self.player(id)?.counters.get(kind)),
+        }
+    }
+
+    /// 122.1. [...] Counters are not objects and have no characteristics. [...]
+    ///
+    /// Returns all kinds of counters the specified player has along with their amounts, e.g. their
+    /// poison, energy and experience counters.
+    pub(crate) fn player_counters(
+        &self,
+        player: PlayerId,
+    ) -> Result<Vec<(CounterKind, u64)>, EngineError> {
+        Ok(self.player(player)?.counters.to_vec())
+    }
+
+    /// 701.27a To proliferate means to choose any number of permanents and/or players, then give
+    ///         each another counter of each kind already there.
+    pub(crate) fn proliferate(
+        &mut self,
+        player: PlayerId,
+        choices: &[PermanentOrPlayer],
+    ) -> Result<(), EngineError> {
+        log::debug!("Player with id {} proliferates.", player.0);
+        for &choice in choices {
+            let counters = match choice {
+                PermanentOrPlayer::Permanent(entity) => self
+                    .world
+                    .get::<Counters>(entity)
+                    .map(|it| it.to_vec())
+                    .unwrap_or_default(),
+                PermanentOrPlayer::Player(id) => self.player(id)?.counters.to_vec(),
+            };
+            for (kind, _) in counters {
+                self.add_counters(choice, kind, 1)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 107.14. The energy symbol is {E}. It represents one energy counter. [...]
+    pub(crate) fn gain_energy(&mut self, player: PlayerId, amount: u64) -> Result<(), EngineError> {
+        self.put_counters(
+            PermanentOrPlayer::Player(player),
+            CounterKind::Energy,
+            amount,
+        )?;
+        if amount > 0 {
+            self.events.push(GameEvent::EnergyGained {
+                player,
+                amount,
+                total: self.player(player)?.counters.get(&CounterKind::Energy),
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns whether the specified player is able to pay the given cost in its entirety.
+    pub(crate) fn can_pay_cost(&self, player: PlayerId, cost: &Cost) -> Result<bool, EngineError> {
+        match *cost {
+            Cost::PayEnergy(amount) => {
+                Ok(self.player(player)?.counters.get(&CounterKind::Energy) >= amount)
+            }
+        }
+    }
+
+    /// 118.3. A player can’t pay a cost without having the necessary resources to pay it fully.
+    ///
+    /// Pays the cost on behalf of the specified player. If the player can’t pay the cost, nothing
+    /// is paid at all.
+    pub(crate) fn pay_cost(&mut self, player: PlayerId, cost: &Cost) -> Result<(), EngineError> {
+        if !self.can_pay_cost(player, cost)? {
+            return Err(EngineError::PaymentFailed(format!(
+                "Player with id {} can't pay {cost:?}.",
+                player.0
+            )));
+        }
+        match *cost {
+            Cost::PayEnergy(amount) => {
+                self.remove_counters(
+                    PermanentOrPlayer::Player(player),
+                    &CounterKind::Energy,
+                    amount,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 605.1a An activated ability is a mana ability if it meets all of the following criteria: it
+    ///        doesn’t require a target (see rule 115.6), it could add mana to a player’s mana pool
+    ///        when it resolves, and it’s not a loyalty ability. [...]
+    ///
+    /// Returns the mana in the pool of the specified player followed by all untapped permanents
+    /// they control that can be tapped for mana, ordered by their ids. If the cost being paid is
+    /// the cost of a spell with convoke, their untapped creatures are included as well, and if the
+    /// spell has delve, the other cards in their graveyard follow.
+    fn mana_sources(
+        &self,
+        player: PlayerId,
+        spell: Option<Entity>,
+    ) -> Result<Vec<ManaSource>, EngineError> {
+        let has_keyword = |keyword| spell.is_some_and(|it| self.has_keyword(it, &keyword));
+        let convoke = has_keyword(KeywordAbility::Convoke);
+        let delve = has_keyword(KeywordAbility::Delve);
+
+        let mut sources = self
+            .player(player)?
+            .mana_pool
+            .mana()
+            .iter()
+            .map(|&it| ManaSource::Pool(it))
+            .collect::<Vec<_>>();
+        let mut permanents = self
+            .world
+            .query::<(&ObjectId, &Controller, Option<&ActivatedAbilities>)>()
+            .without::<Tapped>()
+            .iter()
+            .filter(|&(entity, (_, controller, _))| {
+                controller.0 == player && self.is_on_battlefield(entity)
+            })
+            .filter_map(|(entity, (id, _, abilities))| {
+                // Convoke isn't a {T} cost, so summoning sick creatures may still convoke.
+                let produces = abilities
+                    .iter()
+                    .filter(|_| {
+                        self.has_printed_abilities(entity) && !self.is_summoning_sick(entity)
+                    })
+                    .flat_map(|it| &it.0)
+                    .filter_map(|ability| match (&ability.cost[..], &ability.effects[..]) {
+                        ([ActivationCost::Tap], [Effect::AddMana(mana)]) => Some(*mana),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+                let convoke =
+                    (convoke && self.has_card_type(entity, CardType::Creature)).then(|| {
+                        self.world
+                            .get::<ColorIdentity>(entity)
+                            .map_or(ColorIdentity::Colorless, |it| (*it).clone())
+                    });
+                (!produces.is_empty() || convoke.is_some()).then_some((
+                    *id,
+                    ManaSource::Permanent {
+                        entity,
+                        produces,
+                        convoke,
+                    },
+                ))
+            })
+            .collect::<Vec<_>>();
+        permanents.sort_by_key(|(id, _)| *id);
+        sources.extend(permanents.into_iter().map(|(_, source)| source));
+
+        if delve {
+            let mut graveyard = self
+                .world
+                .query::<(&ObjectId, &Zone)>()
+                .iter()
+                .filter(|&(entity, (_, zone))| {
+                    *zone == Zone::Graveyard(player) && Some(entity) != spell
+                })
+                .map(|(entity, (id, _))| (*id, ManaSource::Graveyard(entity)))
+                .collect::<Vec<_>>();
+            graveyard.sort_by_key(|(id, _)| *id);
+            sources.extend(graveyard.into_iter().map(|(_, source)| source));
+        }
+        Ok(sources)
+    }
+
+    /// Returns a way for the specified player to pay the mana cost using the mana in their pool and
+    /// their untapped permanents that can be tapped for mana, or `None` if they can't pay it. The
+    /// spell whose cost is paid, if any, determines whether convoke and delve can be used.
+    pub(crate) fn plan_payment(
+        &self,
+        player: PlayerId,
+        cost: &ManaCost,
+        spell: Option<Entity>,
+    ) -> Result<Option<PaymentPlan>, EngineError> {
+        Ok(payment::plan_payment(
+            cost,
+            &self.mana_sources(player, spell)?,
+        ))
+    }
+
+    /// Returns the largest value of X the specified player can pay the mana cost with, which can be
+    /// chosen with [`Game::cast_spell_with_x`], or `None` if they can't pay it at all.
+    pub(crate) fn max_x(
+        &self,
+        player: PlayerId,
+        cost: &ManaCost,
+        spell: Option<Entity>,
+    ) -> Result<Option<u64>, EngineError> {
+        Ok(payment::max_x(cost, &self.mana_sources(player, spell)?))
+    }
+
+    /// 601.2h The player pays the total cost. [...]
+    ///
+    /// Pays the mana cost on behalf of the specified player according to the plan, or according to
+    /// a plan found by the engine if there is none. The spell whose cost is paid, if any,
+    /// determines whether convoke and delve can be used. If the plan can't be carried out, nothing
+    /// is paid at all.
+    pub(crate) fn pay_mana_cost(
+        &mut self,
+        player: PlayerId,
+        cost: &ManaCost,
+        spell: Option<Entity>,
+        plan: Option<PaymentPlan>,
+    ) -> Result<(), EngineError> {
+        let payment_failed = || {
+            EngineError::PaymentFailed(format!(
+                "Player with id {} can't pay the mana cost.",
+                player.0
+            ))
+        };
+        let plan = match plan {
+            Some(plan) => plan,
+            None => self
+                .plan_payment(player, cost, spell)?
+                .ok_or_else(payment_failed)?,
+        };
+
+        let sources = self.mana_sources(player, spell)?;
+        if !payment::is_valid(cost, &sources, &plan) {
+            return Err(payment_failed());
+        }
+        log::trace!(
+            "Player with id {} pays {cost} by tapping {:?}, convoking {:?} and delving {:?}.",
+            player.0,
+            plan.tapped,
+            plan.convoked,
+            plan.delved
+        );
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            player = player.0,
+            cost = %cost,
+            tapped = ?self.object_ids(plan.tapped.iter().map(|it| it.0)),
+            convoked = ?self.object_ids(plan.convoked.iter().copied()),
+            delved = ?self.object_ids(plan.delved.iter().copied()),
+            "A mana cost is paid."
+        );
+
+        // 605.3a A player may activate an activated mana ability whenever they have priority,
+        //        [...] or whenever a rule or effect asks for a mana payment, [...]
+        for &(entity, mana) in &plan.tapped {
+            self.world
+                .insert_one(entity, Tapped)
+                .map_err(|_| EngineError::UnknownObject(entity))?;
+            self.player_mut(player)?.mana_pool.add(mana);
+        }
+        let mana_pool = &mut self.player_mut(player)?.mana_pool;
+        for &mana in &plan.spent {
+            mana_pool.remove(mana);
+        }
+        self.stats
+            .record_mana_spent(player, plan.spent.len() as u64);
+        for &entity in &plan.convoked {
+            self.world
+                .insert_one(entity, Tapped)
+                .map_err(|_| EngineError::UnknownObject(entity))?;
+        }
+        for &entity in &plan.delved {
+            self.move_object(entity, Zone::Exile)?;
+        }
+        Ok(())
+    }
+
+    /// Spawns an instance of a [`Card`] owned by the specified player in the specified [`Zone`].
+    pub(crate) fn spawn_object(
+        &mut self,
+        card: &Arc<Card>,
+        owner: PlayerId,
+        zone: Zone,
+    ) -> Result<Entity, EngineError> {
+        self.spawn(card, owner, zone, false)
+    }
+
+    /// Spawns a new object, which is either a card or a token, with the characteristics of the
+    /// specified card in the specified zone.
+    fn spawn(
+        &mut self,
+        card: &Arc<Card>,
+        owner: PlayerId,
+        zone: Zone,
+        is_token: bool,
+    ) -> Result<Entity, EngineError> {
+        self.player(owner)?;
+        self.ensure_zone_accepts(owner, zone)?;
+
+        let mut builder = characteristics(card);
+        builder
+            .add(Object)
+            // 108.3. The owner of a card in the game is the player who started the game with it
+            //        in their deck. [...]
+            .add(Owner(owner));
+        if is_token {
+            builder.add(Token);
+        } else {
+            *self.brought_cards.entry(owner).or_default() += 1;
+        }
+
+        let entity = self.world.spawn(builder.build());
+        self.issue_object_id(entity)?;
+        self.place_object(entity, zone)?;
+        // Cards starting the game in a library are not worth logging.
+        if !matches!(zone, Zone::Library(_)) {
+            self.events.push(GameEvent::ZoneChanged {
+                object: ObjectSnapshot::of(&self.world, entity),
+                owner,
+                from: None,
+                to: zone,
+                face_down: false,
+            });
+        }
+        self.enter_zone(entity, owner, zone)?;
+        Ok(entity)
+    }
+
+    /// 111.2. The player who creates a token is its owner. The token enters the battlefield under
+    ///        that player’s control.
+    ///
+    /// Creates a token with the characteristics of the specified card on the battlefield.
+    pub(crate) fn create_token(
+        &mut self,
+        card: &Arc<Card>,
+        player: PlayerId,
+    ) -> Result<Entity, EngineError> {
+        self.spawn(card, player, Zone::Battlefield, true)
+    }
+
+    /// Creates the specified amount of predefined tokens for the player.
+    fn create_predefined_tokens(
+        &mut self,
+        token: PredefinedToken,
+        amount: u64,
+        player: PlayerId,
+    ) -> Result<(), EngineError> {
+        let card = token.card();
+        for _ in 0..amount {
+            self.create_token(&card, player)?;
+        }
+        Ok(())
+    }
+
+    /// 707.2. When copying an object, the copy acquires the copiable values of the original
+    ///        object’s characteristics and, for an object on the stack, choices made when casting
+    ///        or activating it (mode, targets, the value of X, whether it was kicked, how it will
+    ///        affect multiple targets, and so on). The copiable values are the values derived from
+    ///        the text printed on the object (that text being name, mana cost, color indicator,
+    ///        card type, subtype, supertype, rules text, power, toughness, and/or loyalty), as
+    ///        modified by other copy effects, by its face-down status, and by “as . . . enters the
+    ///        battlefield” and “as . . . is turned face up” abilities that set power and toughness
+    ///        (and may also set additional characteristics). Other effects (including type-changing
+    ///        and text-changing effects), status, counters, and stickers are not copied.
+    ///
+    /// Makes the specified permanent a copy of another object. The copy effect is applied in layer 1
+    /// by replacing the characteristics the permanent is based on, so counters, status and all
+    /// other continuous effects keep applying on top of it. The effect ends once the permanent
+    /// leaves the battlefield.
+    pub(crate) fn copy_permanent(
+        &mut self,
+        entity: Entity,
+        as_copy_of: Entity,
+    ) -> Result<(), EngineError> {
+        if !self.is_on_battlefield(entity) {
+            return Err(EngineError::InvalidTarget(format!(
+                "Only permanents can become copies but {entity:?} is not on the battlefield."
+            )));
+        }
+        // 707.3. The copy’s copiable values become the copied information, as modified by the
+        //        copy’s status (see rule 110.5). [...]
+        let copied = self
+            .world
+            .get::<CardRef>(as_copy_of)
+            .map(|it| Arc::clone(&it.0))
+            .map_err(|_| EngineError::UnknownObject(as_copy_of))?;
+        let printed = self.replace_characteristics(entity, &copied)?;
+        if self.world.get::<PrintedCard>(entity).is_err() {
+            self.world
+                .insert_one(entity, PrintedCard(printed))
+                .map_err(|_| EngineError::UnknownObject(entity))?;
+        }
+        Ok(())
+    }
+
+    /// Replaces the characteristics of an object with the ones of the specified card and returns
+    /// the card it was based on before.
+    fn replace_characteristics(
+        &mut self,
+        entity: Entity,
+        card: &Arc<Card>,
+    ) -> Result<Arc<Card>, EngineError> {
+        let previous = self
+            .world
+            .remove_one::<CardRef>(entity)
+            .map_err(|_| EngineError::UnknownObject(entity))?;
+        let _ = self.world.remove_one::<ColorIdentity>(entity);
+        let _ = self.world.remove_one::<PtCharacteristic>(entity);
+        let _ = self.world.remove_one::<Loyalty>(entity);
+        let _ = self.world.remove_one::<TriggeredAbilities>(entity);
+        let _ = self.world.remove_one::<ActivatedAbilities>(entity);
+        let _ = self.world.remove_one::<StaticAbilities>(entity);
+        let _ = self.world.remove_one::<Keywords>(entity);
+        self.world
+            .insert(entity, characteristics(card).build())
+            .map_err(|_| EngineError::UnknownObject(entity))?;
+        Ok(previous.0)
+    }
+
+    /// 305.1. A player who has priority may play a land card from their hand during a main phase of
+    ///        their turn when the stack is empty. Playing a land is a special action; it doesn’t
+    ///        use the stack (see rule 116). Rather, the player simply puts the land onto the
+    ///        battlefield. [...]
+    ///
+    /// 305.2. A player can normally play one land during their turn; however, continuous effects
+    ///        may increase this number.
+    pub(crate) fn play_land(&mut self, player: PlayerId, land: Entity) -> Result<(), EngineError> {
+        #[cfg(feature = "tracing")]
+        let _span = self.spans.action("play_land", player);
+        self.check_land_play(player, land)?;
+
+        log::debug!("Player with id {} plays {land:?}.", player.0);
+        self.turn.tracker.record_land_played(player);
+        self.move_object(land, Zone::Battlefield)
+    }
+
+    /// Plays a land of the specified player once they have made the choices its abilities require
+    /// as it enters the battlefield, asking them for the next one otherwise.
+    fn play_land_deciding(&mut self, player: PlayerId, land: Entity) -> Result<(), EngineError> {
+        self.check_land_play(player, land)?;
+        if let Some(choice) = self.entry_choice(land, player) {
+            self.choice = Some(choice);
+            return Ok(());
+        }
+        self.play_land(player, land)?;
+        self.passes = 0;
+        Ok(())
+    }
+
+    /// Fails if the specified player can't play the land right now.
+    fn check_land_play(&self, player: PlayerId, land: Entity) -> Result<(), EngineError> {
+        if self.zones.zone_of(land) != Some(Zone::Hand(player)) {
+            return Err(EngineError::IllegalAction(format!(
+                "{land:?} is not in the hand of player with id {}.",
+                player.0
+            )));
+        }
+        if !self.has_card_type(land, CardType::Land) {
+            return Err(EngineError::IllegalAction(format!(
+                "{land:?} is not a land."
+            )));
+        }
+        if !self.has_sorcery_timing(player) {
+            return Err(EngineError::IllegalTiming(format!(
+                "Player with id {} can only play lands during a main phase of their turn while \
+                 the stack is empty.",
+                player.0
+            )));
+        }
+        if self.turn.tracker.lands_played_by(player) >= 1 {
+            return Err(EngineError::IllegalAction(format!(
+                "Player with id {} has already played a land this turn.",
+                player.0
+            )));
+        }
+        Ok(())
+    }
+
+    /// 601.2. To cast a spell is to take it from where it is (usually the hand), put it on the stack,
+    ///        and pay its costs, so that it will eventually resolve and have its effect. [...]
+    ///
+    /// Casts a spell of the specified player from the zone it is in, which is their hand unless the
+    /// card can be cast from elsewhere, e.g. using flashback. The mana cost of the card or the
+    /// alternative cost that comes with the zone is paid, planned automatically unless a plan is
+    /// given. Targets are chosen as the spell resolves.
+    ///
+    /// # Remarks
+    /// The storm trigger isn't put on the stack, the copies are created right away instead.
+    pub(crate) fn cast_spell(
+        &mut self,
+        player: PlayerId,
+        spell: Entity,
+        plan: Option<PaymentPlan>,
+    ) -> Result<(), EngineError> {
+        self.cast_spell_with_modes(player, spell, &[], plan)
+    }
+
+    /// 601.2b If the spell is modal, the player announces the mode choice (see rule 700.2). [...]
+    ///
+    /// Casts a spell like [`Game::cast_spell`] choosing the specified modes, each referred to by its
+    /// index. Modal spells can only be cast by choosing a legal number of modes, each of which must
+    /// have legal targets for the effects that require them, while no modes can be chosen for
+    /// other spells. The chosen modes are locked in for the spell and its copies.
+    pub(crate) fn cast_spell_with_modes(
+        &mut self,
+        player: PlayerId,
+        spell: Entity,
+        modes: &[usize],
+        plan: Option<PaymentPlan>,
+    ) -> Result<(), EngineError> {
+        self.cast_spell_choosing(player, spell, modes, 0, plan)
+    }
+
+    /// 601.2b [...] If the spell has a variable cost that will be paid as it’s being cast (such as
+    ///        an {X} in its mana cost; see rule 107.3), the player announces the value of that
+    ///        variable. [...]
+    ///
+    /// Casts a spell like [`Game::cast_spell`] choosing the specified value of X, which is added to
+    /// its total cost as generic mana for each {X} in it. The value is locked in for the spell and
+    /// its copies and remains known to the permanent the spell becomes as it enters the
+    /// battlefield.
+    pub(crate) fn cast_spell_with_x(
+        &mut self,
+        player: PlayerId,
+        spell: Entity,
+        x: u64,
+        plan: Option<PaymentPlan>,
+    ) -> Result<(), EngineError> {
+        self.cast_spell_choosing(player, spell, &[], x, plan)
+    }
+
+    /// Casts a spell choosing the specified modes and value of X.
+    fn cast_spell_choosing(
+        &mut self,
+        player: PlayerId,
+        spell: Entity,
+        modes: &[usize],
+        x: u64,
+        plan: Option<PaymentPlan>,
+    ) -> Result<(), EngineError> {
+        #[cfg(feature = "tracing")]
+        let _span = self.spans.action("cast_spell", player);
+        let card = self
+            .world
+            .get::<CardRef>(spell)
+            .map(|it| Arc::clone(&it.0))
+            .map_err(|_| EngineError::UnknownObject(spell))?;
+        // 305.1. [...] Playing a land is a special action; it doesn’t use the stack [...]
+        if card.type_line.card_type.contains(&CardType::Land) {
+            return Err(EngineError::IllegalAction(format!(
+                "{} is a land and can't be cast.",
+                card.name.0
+            )));
+        }
+        let Some((method, cost)) = self.casting_method(player, spell, &card) else {
+            return Err(EngineError::IllegalAction(format!(
+                "{} can't be cast by player with id {} from {:?}.",
+                card.name.0,
+                player.0,
+                self.zones.zone_of(spell)
+            )));
+        };
+        match modes_of(&card) {
+            Some(available) if !self.is_castable_choice(player, available, modes) => {
+                return Err(EngineError::IllegalAction(format!(
+                    "The modes {modes:?} are not a legal choice for {}.",
+                    card.name.0
+                )));
+            }
+            None if !modes.is_empty() => {
+                return Err(EngineError::IllegalAction(format!(
+                    "{} is not modal.",
+                    card.name.0
+                )));
+            }
+            _ => {}
+        }
+        if x > 0 && !cost.0.contains(&ManaSymbol::Variable) {
+            return Err(EngineError::IllegalAction(format!(
+                "{} has no {{X}} in its cost to choose a value for.",
+                card.name.0
+            )));
+        }
+
+        self.pay_mana_cost(player, &cost.with_x(x), Some(spell), plan)?;
+        self.move_object(spell, Zone::Stack)?;
+        if method == CastingMethod::Flashback {
+            let _ = self.world.insert_one(spell, CastWithFlashback);
+        }
+        if !modes.is_empty() {
+            let mut modes = modes.to_vec();
+            modes.sort_unstable();
+            let _ = self.world.insert_one(spell, ChosenModes(modes));
+        }
+        if x > 0 {
+            let _ = self.world.insert_one(spell, ChosenX(x));
+        }
+        let id = self
+            .object_id(spell)
+            .ok_or(EngineError::UnknownObject(spell))?;
+        self.record_cast(player, Some(spell), &card);
+
+        if self.has_keyword(spell, &KeywordAbility::Storm) {
+            for _ in 0..self.storm_count(id).unwrap_or_default() {
+                self.copy_spell(spell, player)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the ways the specified player can cast their cards right now along with the mana
+    /// cost each of them requires, ordered by the ids of the cards. Only options the player can
+    /// afford and whose timing is met are included. Modal spells are offered once for each choice
+    /// of modes that have legal targets.
+    ///
+    /// # Remarks
+    /// Cards that can only be cast by choosing a mode, e.g. mutate, aren't included.
+    pub(crate) fn casting_options(
+        &self,
+        player: PlayerId,
+    ) -> Result<Vec<CastingOption>, EngineError> {
+        self.player(player)?;
+        let mut candidates = self
+            .world
+            .query::<(&ObjectId, &CardRef)>()
+            .without::<SpellCopy>()
+            .iter()
+            .filter(|(_, (_, card))| !card.0.type_line.card_type.contains(&CardType::Land))
+            .filter_map(|(entity, (id, card))| {
+                let (method, cost) = self.casting_method(player, entity, &card.0)?;
+                let option = CastingOption {
+                    spell: *id,
+                    name: card.0.name.0.clone(),
+                    zone: self.zones.zone_of(entity)?,
+                    method,
+                    cost,
+                    modes: Vec::new(),
+                };
+                Some((
+                    entity,
+                    card.0.type_line.card_type.contains(&CardType::Instant),
+                    option,
+                ))
+            })
+            .collect::<Vec<_>>();
+        candidates.sort_by_key(|(_, _, option)| option.spell);
+
+        let mut options = Vec::new();
+        for (entity, is_instant, option) in candidates {
+            // 307.1. A player who has priority may cast a sorcery card from their hand during a
+            //        main phase of their turn when the stack is empty. [...]
+            let has_timing = is_instant
+                || self.has_keyword(entity, &KeywordAbility::Flash)
+                || self.has_sorcery_timing(player);
+            if !has_timing
+                || self
+                    .plan_payment(player, &option.cost, Some(entity))?
+                    .is_none()
+            {
+                continue;
+            }
+            let card = self
+                .world
+                .get::<CardRef>(entity)
+                .map(|it| Arc::clone(&it.0))
+                .map_err(|_| EngineError::UnknownObject(entity))?;
+            match modes_of(&card) {
+                Some(modes) => options.extend(
+                    modes
+                        .choices()
+                        .into_iter()
+                        .filter(|it| self.is_castable_choice(player, modes, it))
+                        .map(|modes| CastingOption {
+                            modes,
+                            ..option.clone()
+                        }),
+                ),
+                None => options.push(option),
+            }
+        }
+        Ok(options)
+    }
+
+    /// Returns whether the specified player can choose the modes of a modal spell, which requires
+    /// each of them to have legal targets for the effects that require them.
+    fn is_castable_choice(&self, player: PlayerId, modes: &Modes, chosen: &[usize]) -> bool {
+        modes.is_legal_choice(chosen)
+            && chosen
+                .iter()
+                .all(|&it| self.has_legal_targets(player, &modes.modes[it]))
+    }
+
+    /// 601.2c The player announces their choice of an appropriate player, object, or zone for
+    ///        each target the spell requires. [...]
+    ///
+    /// Returns whether each of the effects that require a target has at least one legal target
+    /// from the perspective of the specified player. Players can always be dealt damage.
+    fn has_legal_targets(&self, player: PlayerId, effects: &[Effect]) -> bool {
+        effects.iter().all(|effect| match effect {
+            Effect::DestroyTarget(filter) => !self.permanents_matching(filter, player).is_empty(),
+            Effect::PhaseOut => !self
+                .permanents_matching(&ObjectFilter::default(), player)
+                .is_empty(),
+            Effect::ReturnFromGraveyardToHand(filter) => {
+                !self.graveyard_cards(player, filter).is_empty()
+            }
+            Effect::TapAndStun(_) | Effect::LoseAllAbilitiesUntilEndOfTurn => !self
+                .permanents_matching(
+                    &ObjectFilter {
+                        card_type: Some(CardType::Creature),
+                        ..ObjectFilter::default()
+                    },
+                    player,
+                )
+                .is_empty(),
+            _ => true,
+        })
+    }
+
+    /// Returns how the specified player can cast the card from the zone it is in along with the
+    /// mana cost they have to pay, or `None` if they can't cast it from there.
+    fn casting_method(
+        &self,
+        player: PlayerId,
+        spell: Entity,
+        card: &Card,
+    ) -> Option<(CastingMethod, ManaCost)> {
+        let keyword_cost = |keyword: fn(&KeywordAbility) -> Option<&ManaCost>| {
+            card.abilities.iter().find_map(|it| match it {
+                Ability::Keyword(it) => keyword(it).cloned(),
+                _ => None,
+            })
+        };
+        match self.zones.zone_of(spell)? {
+            Zone::Hand(owner) if owner == player => {
+                Some((CastingMethod::Normal, card.mana_cost.clone()?))
+            }
+            Zone::Graveyard(owner)
+                if owner == player
+                    && [CardType::Instant, CardType::Sorcery]
+                        .iter()
+                        .any(|it| card.type_line.card_type.contains(it)) =>
+            {
+                let cost = keyword_cost(|it| match it {
+                    KeywordAbility::Flashback(cost) => Some(cost),
+                    _ => None,
+                })?;
+                Some((CastingMethod::Flashback, cost))
+            }
+            Zone::Exile
+                if self
+                    .world
+                    .get::<Owner>(spell)
+                    .is_ok_and(|it| it.0 == player)
+                    && self
+                        .world
+                        .get::<Foretold>(spell)
+                        .is_ok_and(|it| it.0 < self.turn.number) =>
+            {
+                let cost = keyword_cost(|it| match it {
+                    KeywordAbility::Foretell(cost) => Some(cost),
+                    _ => None,
+                })?;
+                Some((CastingMethod::Foretold, cost))
+            }
+            _ => None,
+        }
+    }
+
+    /// 307.1. A player who has priority may cast a sorcery card from their hand during a main phase
+    ///        of their turn when the stack is empty. [...]
+    ///
+    /// Returns whether the specified player could take an action with the timing of a sorcery.
+    fn has_sorcery_timing(&self, player: PlayerId) -> bool {
+        player == self.turn.active_player
+            && matches!(self.turn.step, Step::PrecombatMain | Step::PostcombatMain)
+            && self.objects_on_stack().is_empty()
+    }
+
+    /// 702.143a [...] Any time a player has priority during their turn, that player may pay {2} and
+    ///          exile a card with foretell from their hand face down. [...]
+    ///
+    /// Foretells a card from the hand of the specified player, who can cast it from exile on a later
+    /// turn. The payment is planned automatically unless a plan is given.
+    pub(crate) fn foretell(
+        &mut self,
+        player: PlayerId,
+        card: Entity,
+        plan: Option<PaymentPlan>,
+    ) -> Result<(), EngineError> {
+        if self.zones.zone_of(card) != Some(Zone::Hand(player)) {
+            return Err(EngineError::IllegalAction(format!(
+                "{card:?} is not in the hand of player with id {}.",
+                player.0
+            )));
+        }
+        let has_foretell = self.world.get::<CardRef>(card).is_ok_and(|it| {
+            it.0.abilities
+                .iter()
+                .any(|it| matches!(it, Ability::Keyword(KeywordAbility::Foretell(_))))
+        });
+        if !has_foretell {
+            return Err(EngineError::IllegalAction(format!(
+                "{card:?} doesn't have foretell."
+            )));
+        }
+        if player != self.turn.active_player {
+            return Err(EngineError::IllegalTiming(format!(
+                "Player with id {} can only foretell cards during their turn.",
+                player.0
+            )));
+        }
+
+        self.pay_mana_cost(player, &ManaCost(vec![ManaSymbol::Generic(2)]), None, plan)?;
+        log::debug!("Player with id {} foretells {card:?}.", player.0);
+        let _ = self.world.insert_one(card, FaceDown);
+        self.move_object(card, Zone::Exile)?;
+        self.world
+            .insert_one(card, Foretold(self.turn.number))
+            .map_err(|_| EngineError::UnknownObject(card))
+    }
+
+    /// 701.5a To counter a spell or ability means to cancel it, removing it from the stack. It
+    ///        doesn’t resolve and none of its effects occur. A countered spell is put into its
+    ///        owner’s graveyard.
+    pub(crate) fn counter_spell(&mut self, spell: Entity) -> Result<(), EngineError> {
+        if self.zones.zone_of(spell) != Some(Zone::Stack) {
+            return Err(EngineError::IllegalAction(format!(
+                "{spell:?} is not on the stack."
+            )));
+        }
+        let owner = self
+            .world
+            .get::<Owner>(spell)
+            .map(|it| it.0)
+            .map_err(|_| EngineError::UnknownObject(spell))?;
+        log::debug!("{spell:?} is countered.");
+        self.move_object(spell, Zone::Graveyard(owner))
+    }
+
+    /// 702.40a [...] “Storm” means “When you cast this spell, copy it for each other spell that was
+    ///         cast before it this turn. [...]”
+    ///
+    /// Returns the number of spells cast this turn before the spell with the specified id, or
+    /// `None` if it hasn't been cast this turn.
+    pub(crate) fn storm_count(&self, spell: ObjectId) -> Option<usize> {
+        self.turn.tracker.spells_cast_before(spell)
+    }
+
+    /// 707.10. To copy a spell, activated ability, or triggered ability means to put a copy of it
+    ///         onto the stack; a copy of a spell isn’t cast and a copy of an activated ability
+    ///         isn’t activated. [...]
+    ///
+    /// Puts a copy of the specified spell onto the stack under the control of the specified player,
+    /// who becomes its owner.
+    fn copy_spell(&mut self, spell: Entity, controller: PlayerId) -> Result<Entity, EngineError> {
+        let card = self
+            .world
+            .get::<CardRef>(spell)
+            .map(|it| Arc::clone(&it.0))
+            .map_err(|_| EngineError::UnknownObject(spell))?;
+        let mut builder = characteristics(&card);
+        builder.add(Object).add(Owner(controller)).add(SpellCopy);
+        // 707.10. [...] A copy of a spell or ability copies both the characteristics of the spell
+        //         or ability and all decisions made for it, including modes, targets, the value
+        //         of X, and additional or alternative costs. [...]
+        if let Ok(modes) = self.world.get::<ChosenModes>(spell) {
+            builder.add(ChosenModes(modes.0.clone()));
+        }
+        if let Ok(x) = self.world.get::<ChosenX>(spell) {
+            builder.add(ChosenX(x.0));
+        }
+        if let Ok(targets) = self.world.get::<ChosenTargets>(spell) {
+            builder.add(ChosenTargets(targets.0.clone()));
+        }
+        let copy = self.world.spawn(builder.build());
+        self.issue_object_id(copy)?;
+        self.place_object(copy, Zone::Stack)?;
+        log::trace!("{copy:?} is put onto the stack as a copy of {spell:?}.");
+        self.events.push(GameEvent::ZoneChanged {
+            object: ObjectSnapshot::of(&self.world, copy),
+            owner: controller,
+            from: None,
+            to: Zone::Stack,
+            face_down: false,
+        });
+        Ok(copy)
+    }
+
+    /// 608.2. If the object that’s resolving is an instant spell, a sorcery spell, or an ability,
+    ///        its resolution may involve several steps. [...]
+    ///
+    /// 608.3. If the object that’s resolving is a permanent spell, its resolution may involve
+    ///        several steps. [...]
+    ///
+    /// Resolves a spell on the stack using the specified targets in order. Instants and sorceries
+    /// follow their spell abilities and are put into their owner’s graveyard afterwards while
+    /// permanent spells enter the battlefield. Spells are controlled by their owners since they
+    /// can only be cast from their hands.
+    pub(crate) fn resolve_spell(
+        &mut self,
+        spell: Entity,
+        targets: &[PermanentOrPlayer],
+    ) -> Result<(), EngineError> {
+        if self.zones.zone_of(spell) != Some(Zone::Stack) {
+            return Err(EngineError::IllegalAction(format!(
+                "{spell:?} is not on the stack."
+            )));
+        }
+        let (card, owner) = self
+            .world
+            .query_one_mut::<(&CardRef, &Owner)>(spell)
+            .map(|(card, owner)| (Arc::clone(&card.0), owner.0))
+            .map_err(|_| EngineError::UnknownObject(spell))?;
+        #[cfg(feature = "tracing")]
+        let _span = self.spans.action("resolve_spell", owner);
+        let is_instant_or_sorcery = [CardType::Instant, CardType::Sorcery]
+            .iter()
+            .any(|it| card.type_line.card_type.contains(it));
+        if !is_instant_or_sorcery {
+            self.move_object(spell, Zone::Battlefield)?;
+            // A copy of a permanent spell becomes a token as it resolves.
+            if self.world.remove_one::<SpellCopy>(spell).is_ok() {
+                let _ = self.world.insert_one(spell, Token);
+            }
+            return self.check_state_based_actions();
+        }
+
+        let chosen = self
+            .world
+            .get::<ChosenModes>(spell)
+            .map(|it| it.0.clone())
+            .unwrap_or_default();
+        let effects = spell_effects(&card, &chosen);
+        let description = self.describe_effects(spell, owner, &effects, targets);
+        self.events.push(GameEvent::SpellResolved {
+            controller: owner,
+            spell: ObjectSnapshot::of(&self.world, spell),
+            description,
+        });
+        self.option_decisions = self
+            .world
+            .remove_one::<OptionDecisions>(spell)
+            .map(|it| it.0)
+            .unwrap_or_default();
+        let resolved = self.resolve_effects(spell, owner, &effects, targets);
+        self.option_decisions.clear();
+        resolved?;
+        // 608.2n As the final part of an instant or sorcery spell’s resolution, the spell itself
+        //        is put into its owner’s graveyard.
+        self.move_object(spell, Zone::Graveyard(owner))?;
+        self.check_state_based_actions()
+    }
+
+    /// 702.140a [...] “Mutate [cost]” means “You may pay [cost] rather than pay this spell’s mana
+    ///          cost. If you do, it becomes a mutating creature spell and targets a non-Human
+    ///          creature with the same owner as this spell.”
+    ///
+    /// Casts a creature spell from the hand of the specified player for its mutate cost, targeting
+    /// the specified creature. The payment is planned automatically unless a plan is given.
+    pub(crate) fn cast_mutating_spell(
+        &mut self,
+        player: PlayerId,
+        spell: Entity,
+        target: Entity,
+        plan: Option<PaymentPlan>,
+    ) -> Result<(), EngineError> {
+        if self.zones.zone_of(spell) != Some(Zone::Hand(player)) {
+            return Err(EngineError::IllegalAction(format!(
+                "{spell:?} is not in the hand of player with id {}.",
+                player.0
+            )));
+        }
+        let card = self
+            .world
+            .get::<CardRef>(spell)
+            .map(|it| Arc::clone(&it.0))
+            .map_err(|_| EngineError::UnknownObject(spell))?;
+        let Some(cost) = card.abilities.iter().find_map(|it| match it {
+            Ability::Keyword(KeywordAbility::Mutate(cost)) => Some(cost.clone()),
+            _ => None,
+        }) else {
+            return Err(EngineError::IllegalAction(format!(
+                "{} doesn't have mutate.",
+                card.name.0
+            )));
+        };
+        if !self.can_mutate_onto(spell, target) {
+            return Err(EngineError::InvalidTarget(format!(
+                "{target:?} is not a non-Human creature with the same owner as {spell:?}."
+            )));
+        }
+        let target = self
+            .object_id(target)
+            .ok_or(EngineError::UnknownObject(target))?;
+
+        self.pay_mana_cost(player, &cost, Some(spell), plan)?;
+        self.move_object(spell, Zone::Stack)?;
+        self.world
+            .insert_one(spell, MutatingOnto(target))
+            .map_err(|_| EngineError::UnknownObject(spell))?;
+        self.record_cast(player, Some(spell), &card);
+        Ok(())
+    }
+
+    /// Resolves a mutating creature spell on the stack, which is put on top of or under the
+    /// creature it targets.
+    ///
+    /// 702.140b As a mutating creature spell begins resolving, if its target is illegal, it ceases
+    ///          to be a mutating creature spell and continues resolving as a creature spell and
+    ///          will enter the battlefield the same way it would if it weren’t a mutating creature
+    ///          spell.
+    ///
+    /// 702.140c As a mutating creature spell resolves, if its target is legal, it doesn’t enter the
+    ///          battlefield. Rather, it merges with the target creature and becomes one object
+    ///          represented by more than one card or token [...]. The spell’s controller chooses
+    ///          whether the spell is put on top of the creature or on the bottom. [...]
+    pub(crate) fn resolve_mutating_spell(
+        &mut self,
+        spell: Entity,
+        on_top: bool,
+    ) -> Result<(), EngineError> {
+        if self.zones.zone_of(spell) != Some(Zone::Stack) {
+            return Err(EngineError::IllegalAction(format!(
+                "{spell:?} is not on the stack."
+            )));
+        }
+        let target = self
+            .world
+            .remove_one::<MutatingOnto>(spell)
+            .map_err(|_| {
+                EngineError::IllegalAction(format!("{spell:?} is not a mutating creature spell."))
+            })?
+            .0;
+        // The target is only legal if it is still the same object it was when the spell was cast.
+        let Some(target) = self
+            .object(target)
+            .filter(|&it| self.can_mutate_onto(spell, it))
+        else {
+            return self.move_object(spell, Zone::Battlefield);
+        };
+        log::trace!("{spell:?} mutates onto {target:?}.");
+
+        // The spell stops being an object of its own and becomes part of the target.
+        self.retire_object_id(spell);
+        let _ = self.world.remove_one::<ObjectId>(spell);
+        self.zones.remove(&mut self.world, spell);
+        let mut cards = self
+            .world
+            .remove_one::<MergedWith>(target)
+            .map_or_else(|_| vec![target], |it| it.0);
+        if on_top {
+            cards.insert(0, spell);
+        } else {
+            cards.push(spell);
+        }
+        self.merge_characteristics(target, &cards)?;
+        self.world
+            .insert_one(target, MergedWith(cards))
+            .map_err(|_| EngineError::UnknownObject(target))
+    }
+
+    /// Returns whether a mutating creature spell can target the specified creature.
+    fn can_mutate_onto(&self, spell: Entity, target: Entity) -> bool {
+        let owner = |entity| self.world.get::<Owner>(entity).ok().map(|it| it.0);
+        self.is_on_battlefield(target)
+            && self.has_card_type(target, CardType::Creature)
+            && !self.has_subtype(target, Subtype::Creature(CreatureType::Human))
+            && owner(target).is_some()
+            && owner(target) == owner(spell)
+    }
+
+    /// 702.140e A mutated permanent has all abilities of each card and token that represents it.
+    ///          Its other characteristics are derived from the topmost card or token.
+    ///
+    /// Bases the characteristics of a mutated permanent on the specified cards, ordered from top to
+    /// bottom. The printed card of the permanent itself is kept to be restored once it leaves the
+    /// battlefield.
+    fn merge_characteristics(
+        &mut self,
+        entity: Entity,
+        cards: &[Entity],
+    ) -> Result<(), EngineError> {
+        let printed = |entity| {
+            self.world
+                .get::<PrintedCard>(entity)
+                .map(|it| Arc::clone(&it.0))
+                .or_else(|_| {
+                    self.world
+                        .get::<CardRef>(entity)
+                        .map(|it| Arc::clone(&it.0))
+                })
+                .map_err(|_| EngineError::UnknownObject(entity))
+        };
+        let printed_cards = cards
+            .iter()
+            .map(|&it| printed(it))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut merged = Card::clone(&printed_cards[0]);
+        merged.abilities = printed_cards
+            .iter()
+            .flat_map(|it| it.abilities.iter().cloned())
+            .collect();
+
+        let printed = self.replace_characteristics(entity, &Arc::new(merged))?;
+        if self.world.get::<PrintedCard>(entity).is_err() {
+            self.world
+                .insert_one(entity, PrintedCard(printed))
+                .map_err(|_| EngineError::UnknownObject(entity))?;
+        }
+        Ok(())
+    }
+
+    /// 400.3. If an object would go to any library, graveyard, or hand other than its owner’s, it
+    ///        goes to its owner’s corresponding zone.
+    ///
+    /// Ensures that an object owned by the specified player can be put into the specified zone.
+    fn ensure_zone_accepts(&self, owner: PlayerId, zone: Zone) -> Result<(), EngineError> {
+        match zone {
+            Zone::Library(player) | Zone::Hand(player) | Zone::Graveyard(player) => {
+                self.player(player)?;
+                if player != owner {
+                    return Err(EngineError::IllegalZoneOperation(format!(
+                        "An object owned by player with id {} can't be put into {zone:?}.",
+                        owner.0
+                    )));
+                }
+                Ok(())
+            }
+            Zone::Battlefield | Zone::Stack | Zone::Exile | Zone::Command => Ok(()),
+            Zone::Ante if self.ante => Ok(()),
+            Zone::Ante => Err(EngineError::IllegalZoneOperation(
+                "The game isn't played for ante.".into(),
+            )),
+        }
+    }
+
+    /// Moves an object from its current zone to the specified [`Zone`]. Objects moved into a
+    /// library are put on top of it.
+    pub(crate) fn move_object(&mut self, entity: Entity, to: Zone) -> Result<(), EngineError> {
+        let (Some(from), Ok(owner)) = (
+            self.zones.zone_of(entity),
+            self.world.get::<Owner>(entity).map(|it| it.0),
+        ) else {
+            return Err(EngineError::UnknownObject(entity));
+        };
+        self.ensure_zone_accepts(owner, to)?;
+        // Cards in the ante zone are no longer part of the decks and stay there until the winner
+        // takes them.
+        if from == Zone::Ante {
+            return Err(EngineError::IllegalZoneOperation(format!(
+                "{entity:?} is in the ante zone and can't leave it."
+            )));
+        }
+        // 111.8. A token that has left the battlefield can’t move to another zone or come back
+        //        onto the battlefield. If such a token would change zones, it remains in its
+        //        current zone instead. [...]
+        if from != Zone::Battlefield && self.world.get::<Token>(entity).is_ok() {
+            return Ok(());
+        }
+        // 704.5e If a copy of a spell is in a zone other than the stack, it ceases to exist. [...]
+        if from == Zone::Stack
+            && to != Zone::Battlefield
+            && self.world.get::<SpellCopy>(entity).is_ok()
+        {
+            log::trace!("{entity:?} ceases to exist since it is a copy of a spell.");
+            return self.despawn_object(entity);
+        }
+        // 702.34a [...] “If the flashback cost was paid, exile this card instead of putting it
+        //         anywhere else any time it would leave the stack.”
+        let to =
+            if from == Zone::Stack && self.world.remove_one::<CastWithFlashback>(entity).is_ok() {
+                Zone::Exile
+            } else {
+                to
+            };
+        // 614.6. If an event is replaced, it never happens. [...]
+        if matches!(to, Zone::Graveyard(_)) && self.replace_moving_to_graveyard(entity, from)? {
+            return Ok(());
+        }
+        if from == Zone::Stack {
+            self.forget_spell_choices(entity, to);
+        }
+        if from == Zone::Exile {
+            let _ = self.world.remove_one::<Foretold>(entity);
+            let _ = self.world.remove_one::<FaceDown>(entity);
+        }
+        log::trace!("{entity:?} moves from {from:?} to {to:?}.");
+        let controller = self.world.get::<Controller>(entity).ok().map(|it| it.0);
+
+        if from == Zone::Battlefield {
+            self.remove_from_combat(entity);
+            // 109.4. Only objects on the stack or on the battlefield have a controller. [...]
+            let _ = self.world.remove_one::<Controller>(entity);
+            let _ = self.world.remove_one::<MarkedDamage>(entity);
+            let _ = self.world.remove_one::<SummoningSick>(entity);
+            let _ = self.world.remove_one::<RegenerationShields>(entity);
+            let _ = self.world.remove_one::<AttachedTo>(entity);
+            // 110.5b Permanents enter the battlefield untapped, unflipped, face up, and phased in
+            //        unless a spell or ability says otherwise.
+            let _ = self.world.remove_one::<Tapped>(entity);
+            let _ = self.world.remove_one::<ChosenColor>(entity);
+            let _ = self.world.remove_one::<ChosenCreatureType>(entity);
+            let _ = self.world.remove_one::<ChosenPlayer>(entity);
+            let _ = self.world.remove_one::<PhasedOut>(entity);
+            let ended = self.effects.source_left_battlefield(entity);
+            self.effects_ended(ended);
+            self.update_controllers();
+        }
+        // The last known information is recorded as the id is retired, so it still includes the
+        // counters.
+        self.issue_object_id(entity)?;
+        // 122.2. Counters on an object are not retained if that object moves from one zone to
+        //        another. [...]
+        let _ = self.world.remove_one::<Counters>(entity);
+
+        self.place_object(entity, to)?;
+        self.events.push(GameEvent::ZoneChanged {
+            object: ObjectSnapshot::of(&self.world, entity),
+            owner,
+            from: Some(from),
+            to,
+            face_down: to == Zone::Exile && self.world.get::<FaceDown>(entity).is_ok(),
+        });
+        self.enter_zone(entity, owner, to)?;
+        if from == Zone::Battlefield {
+            self.check_life_totals()?;
+        }
+        if let Zone::Graveyard(_) = to {
+            let object = entity;
+            self.check_zone_change_triggers(TriggerEvent::PutIntoGraveyard { object, owner });
+        }
+
+        if let (Zone::Battlefield, Some(controller)) = (from, controller) {
+            if matches!(to, Zone::Graveyard(_)) && self.has_card_type(entity, CardType::Creature) {
+                self.turn.tracker.record_creature_died(controller);
+            }
+            let object = entity;
+            self.check_zone_change_triggers(TriggerEvent::LeavesBattlefield {
+                object,
+                controller,
+                to,
+            });
+        }
+        // The permanent stops being a copy only after abilities that trigger on it leaving the
+        // battlefield have looked back in time.
+        if from == Zone::Battlefield {
+            let _ = self.world.remove_one::<Transformed>(entity);
+            if let Ok(PrintedCard(card)) = self.world.remove_one::<PrintedCard>(entity) {
+                self.replace_characteristics(entity, &card)?;
+            }
+            if let Ok(MergedWith(cards)) = self.world.remove_one::<MergedWith>(entity) {
+                self.separate_merged_cards(entity, &cards, owner, to)?;
+            }
+            self.return_linked_exiles(|it| {
+                it.source == entity && it.condition == ReturnCondition::SourceLeavesBattlefield
+            })?;
+        }
+        Ok(())
+    }
+
+    /// 603.10a Some zone-change triggers look back in time. These are leaves-the-battlefield
+    ///         abilities, [...]
+    ///
+    /// Moves the objects to the specified zones at the same time. Abilities that trigger on any of
+    /// them leaving the battlefield see the game as it was right before the first one moved.
+    fn move_objects_simultaneously(&mut self, moves: &[(Entity, Zone)]) -> Result<(), EngineError> {
+        // Objects that are moved while others are still being moved are part of the same event.
+        if self.simultaneous_events.is_some() {
+            return moves
+                .iter()
+                .try_for_each(|&(entity, zone)| self.move_object(entity, zone));
+        }
+        let sources = self.trigger_sources();
+        self.simultaneous_events = Some(Vec::new());
+        let result = moves
+            .iter()
+            .try_for_each(|&(entity, zone)| self.move_object(entity, zone));
+        let events = self.simultaneous_events.take().unwrap_or_default();
+        result?;
+        for event in events {
+            self.check_triggers_of(&event, sources.clone());
+        }
+        Ok(())
+    }
+
+    /// Puts the cards a mutated permanent was represented by besides its own into the zone the
+    /// permanent moved to, each as an object of its own.
+    ///
+    /// # Remarks
+    /// The owner can't choose the order yet, so the cards are put into a library below the
+    /// permanent itself from the bottom to the top of the pile.
+    fn separate_merged_cards(
+        &mut self,
+        entity: Entity,
+        cards: &[Entity],
+        owner: PlayerId,
+        to: Zone,
+    ) -> Result<(), EngineError> {
+        for &card in cards.iter().rev().filter(|&&it| it != entity) {
+            self.issue_object_id(card)?;
+            self.place_object(card, to)?;
+            self.events.push(GameEvent::ZoneChanged {
+                object: ObjectSnapshot::of(&self.world, card),
+                owner,
+                from: Some(Zone::Battlefield),
+                to,
+                face_down: false,
+            });
+            self.enter_zone(card, owner, to)?;
+        }
+        Ok(())
+    }
+
+    /// Removes the choices made for a spell that leaves the stack for the specified zone, except
+    /// for the ones a permanent keeps as it enters the battlefield.
+    fn forget_spell_choices(&mut self, entity: Entity, to: Zone) {
+        let _ = self.world.remove_one::<ChosenModes>(entity);
+        let _ = self.world.remove_one::<OptionDecisions>(entity);
+        if to != Zone::Battlefield {
+            let _ = self.world.remove_one::<ChosenX>(entity);
+            let _ = self.world.remove_one::<EntryDecisions>(entity);
+        }
+    }
+
+    /// Puts an object into the specified zone.
+    ///
+    /// 613.7d An object receives a timestamp at the time it enters a zone.
+    fn place_object(&mut self, entity: Entity, zone: Zone) -> Result<(), EngineError> {
+        self.zones.place(&mut self.world, entity, zone)?;
+        let timestamp = self.next_timestamp();
+        self.world
+            .insert_one(entity, timestamp)
+            .map_err(|_| EngineError::UnknownObject(entity))
+    }
+
+    /// Performs the bookkeeping for an object that has just been put into the specified zone.
+    fn enter_zone(
+        &mut self,
+        entity: Entity,
+        owner: PlayerId,
+        zone: Zone,
+    ) -> Result<(), EngineError> {
+        if zone == Zone::Battlefield {
+            self.replace_entering_battlefield(entity)?;
+            // 110.2. A permanent’s owner is the same as the owner of the card that represents it
+            //        (unless it’s a token; see rule 111.2). A permanent’s controller is, by
+            //        default, the player under whose control it entered the battlefield. Every
+            //        permanent has a controller.
+            self.world
+                .insert(entity, (Controller(owner), SummoningSick))
+                .map_err(|_| EngineError::UnknownObject(entity))?;
+            // 306.5b A planeswalker has the intrinsic ability “This permanent enters with a number
+            //        of loyalty counters on it equal to its printed loyalty number.” [...]
+            let loyalty = self.world.get::<Loyalty>(entity).map_or(0, |it| it.0);
+            if self.has_card_type(entity, CardType::Planeswalker) {
+                let permanent = PermanentOrPlayer::Permanent(entity);
+                self.add_counters(permanent, CounterKind::Loyalty, loyalty)?;
+            }
+            self.put_entry_counters(entity)?;
+            self.enter_day_or_night(entity)?;
+            self.make_entry_choices(entity, owner)?;
+            self.check_triggers(&TriggerEvent::EntersBattlefield {
+                object: entity,
+                controller: owner,
+            });
+            self.check_ascend()?;
+            self.check_uniqueness_rules()?;
+        }
+        Ok(())
+    }
+
+    /// 702.145d Any time a player controls a permanent with daybound, if it’s neither day nor night,
+    ///          it becomes day.
+    ///
+    /// Makes it day as the first daybound permanent enters the battlefield, or makes the permanent
+    /// enter transformed if it is night.
+    fn enter_day_or_night(&mut self, entity: Entity) -> Result<(), EngineError> {
+        if !self.has_keyword(entity, &KeywordAbility::Daybound) {
+            return Ok(());
+        }
+        match self.day_night {
+            None => self.set_day_night(DayNight::Day),
+            Some(DayNight::Night) => self.transform(entity),
+            Some(DayNight::Day) => Ok(()),
+        }
+    }
+
+    /// 614.1c Effects that read “[This permanent] enters the battlefield with . . . ,” [...] are
+    ///        replacement effects.
+    ///
+    /// Puts the counters on a permanent entering the battlefield that its abilities say it enters
+    /// with, before any ability can trigger on it entering or any state-based action sees it.
+    fn put_entry_counters(&mut self, entity: Entity) -> Result<(), EngineError> {
+        let x = self
+            .world
+            .remove_one::<ChosenX>(entity)
+            .map_or(0, |it| it.0);
+        let counters = self
+            .world
+            .get::<CardRef>(entity)
+            .map(|card| {
+                card.0
+                    .abilities
+                    .iter()
+                    .filter_map(|it| match it {
+                        Ability::Static(StaticAbility::EntersWithCounters(kind, amount)) => {
+                            Some((kind.clone(), *amount))
+                        }
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        for (kind, amount) in counters {
+            let amount = match amount {
+                CounterAmount::Fixed(amount) => amount,
+                CounterAmount::X => x,
+            };
+            self.add_counters(PermanentOrPlayer::Permanent(entity), kind, amount)?;
+        }
+        Ok(())
+    }
+
+    /// 704.3. Whenever a player would get priority [...], the game checks for any of the listed
+    ///        conditions for state-based actions, then performs all applicable state-based actions
+    ///        simultaneously as a single event. If any state-based actions are performed as a
+    ///        result of a check, the check is repeated; [...]
+    ///
+    /// Performs the state-based actions until none apply anymore. This is checked whenever a
+    /// spell or ability has resolved, a step has begun and before a player is asked to act.
+    pub(crate) fn check_state_based_actions(&mut self) -> Result<(), EngineError> {
+        loop {
+            self.check_life_totals()?;
+            self.check_uniqueness_rules()?;
+            if self.outcome.is_some() {
+                return Ok(());
+            }
+            let creatures_died = self.put_dying_creatures_into_graveyards()?;
+            if !self.remove_illegal_attachments()? && !creatures_died {
+                return Ok(());
+            }
+        }
+    }
+
+    /// 704.5f If a creature has toughness 0 or less, it’s put into its owner’s graveyard.
+    ///        Regeneration can’t replace this event.
+    ///
+    /// 704.5g If a creature has toughness greater than 0, it has damage marked on it, and the
+    ///        total damage marked on it is greater than or equal to its toughness, that creature
+    ///        has been dealt lethal damage and is destroyed. Regeneration can replace this event.
+    ///
+    /// Puts the creatures with toughness 0 or less and the ones destroyed by lethal damage into
+    /// their owners’ graveyards at the same time. Returns whether any creature left the battlefield
+    /// or survived its destruction, in which case the state-based actions are checked again.
+    fn put_dying_creatures_into_graveyards(&mut self) -> Result<bool, EngineError> {
+        let creatures = self
+            .world
+            .query::<(&Owner, Option<&MarkedDamage>)>()
+            .iter()
+            .filter(|&(entity, _)| {
+                self.is_on_battlefield(entity) && self.has_card_type(entity, CardType::Creature)
+            })
+            .filter_map(|(entity, (owner, damage))| {
+                let (_, toughness) = self.power_toughness(entity)?;
+                Some((entity, owner.0, toughness, damage.map_or(0, |it| it.0)))
+            })
+            .collect::<Vec<_>>();
+        let mut dying = Vec::new();
+        let mut survived = false;
+        for (entity, owner, toughness, damage) in creatures {
+            if toughness <= 0 {
+                dying.push((entity, Zone::Graveyard(owner)));
+            } else if damage > 0
+                && damage >= toughness.unsigned_abs()
+                // 702.12b [...] Such permanents aren’t destroyed by lethal damage [...]
+                && !self.has_keyword(entity, &KeywordAbility::Indestructible)
+            {
+                if self.survives_destruction(entity)? {
+                    survived = true;
+                } else {
+                    dying.push((entity, Zone::Graveyard(owner)));
+                }
+            }
+        }
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            rule = "704.5f",
+            objects = ?self.object_ids(dying.iter().map(|it| it.0)),
+            "State-based actions are checked."
+        );
+        if dying.is_empty() {
+            return Ok(survived);
+        }
+        log::trace!("{dying:?} have toughness 0 or less or were dealt lethal damage.");
+        self.move_objects_simultaneously(&dying)?;
+        Ok(true)
+    }
+
+    /// 704.5m If an Aura is attached to an illegal object or player, or is not attached to an
+    ///        object or player, that Aura is put into its owner’s graveyard.
+    ///
+    /// 704.5n If an Equipment or Fortification is attached to an illegal permanent or to a player,
+    ///        it becomes unattached from that permanent. It remains on the battlefield.
+    ///
+    /// Removes the attachments whose permanent left the battlefield or gained protection from
+    /// them, e.g. after they were attached. Attachments other than Equipment and Fortifications
+    /// are treated as Auras. Returns whether any attachment was removed.
+    fn remove_illegal_attachments(&mut self) -> Result<bool, EngineError> {
+        let illegal = self
+            .world
+            .query::<&AttachedTo>()
+            .iter()
+            .filter(|&(attachment, target)| {
+                self.is_on_battlefield(attachment)
+                    && (!self.is_on_battlefield(target.0)
+                        || self
+                            .is_protected_from(PermanentOrPlayer::Permanent(target.0), attachment))
+            })
+            .map(|(attachment, _)| attachment)
+            .collect::<Vec<_>>();
+        let mut auras = Vec::new();
+        for attachment in &illegal {
+            let is_equipment = [ArtifactType::Equipment, ArtifactType::Fortification]
+                .into_iter()
+                .any(|it| self.has_subtype(*attachment, Subtype::Artifact(it)));
+            if is_equipment {
+                log::trace!("{attachment:?} becomes unattached.");
+                let _ = self.world.remove_one::<AttachedTo>(*attachment);
+            } else {
+                let owner = self
+                    .world
+                    .get::<Owner>(*attachment)
+                    .map_err(|_| EngineError::UnknownObject(*attachment))?
+                    .0;
+                auras.push((*attachment, Zone::Graveyard(owner)));
+            }
+        }
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            rule = "704.5m",
+            objects = ?self.object_ids(auras.iter().map(|it| it.0)),
+            "State-based actions are checked."
+        );
+        self.move_objects_simultaneously(&auras)?;
+        Ok(!illegal.is_empty())
+    }
+
+    /// 614.1c Effects that read “[This permanent] enters the battlefield with . . . ,” “As [this
+    ///        permanent] enters the battlefield . . . ,” or “[This permanent] enters the
+    ///        battlefield as . . . ” are replacement effects.
+    ///
+    /// Applies the decisions the controller of a permanent that is entering the battlefield made
+    /// for the choices its abilities require, before any ability can trigger on it entering. The
+    /// choices are stored with the permanent for its abilities to refer to.
+    ///
+    /// # Remarks
+    /// Players decide while permanent spells are about to resolve and while lands are played. For
+    /// permanents put onto the battlefield any other way, the default decisions are made instead.
+    fn make_entry_choices(
+        &mut self,
+        entity: Entity,
+        controller: PlayerId,
+    ) -> Result<(), EngineError> {
+        let mut decisions = self
+            .world
+            .remove_one::<EntryDecisions>(entity)
+            .map(|it| it.0)
+            .unwrap_or_default()
+            .into_iter();
+        for choice in self.entry_choices(entity) {
+            let decision = decisions
+                .next()
+                .unwrap_or_else(|| self.default_entry_decision(controller, choice));
+            log::debug!(
+                "Player with id {} decides {decision:?} for {entity:?}.",
+                controller.0
+            );
+            match (choice, decision) {
+                (EntryChoice::PayLifeOrEnterTapped(amount), EntryDecision::PayLife(true)) => {
+                    // 119.4. If a cost or effect allows a player to pay an amount of life greater
+                    //        than 0, the player may do so only if their life total is greater than
+                    //        or equal to the amount of the payment. [...]
+                    //
+                    // The permanent is already entering the battlefield, so a player who can't pay
+                    // the life doesn't and it enters the battlefield tapped instead.
+                    if self.player(controller)?.life < amount as i64 {
+                        log::debug!(
+                            "Player with id {} can't pay {amount} life for {entity:?}.",
+                            controller.0
+                        );
+                        let _ = self.world.insert_one(entity, Tapped);
+                    } else {
+                        self.lose_life(controller, amount)?;
+                    }
+                }
+                (EntryChoice::PayLifeOrEnterTapped(_), EntryDecision::PayLife(false)) => {
+                    let _ = self.world.insert_one(entity, Tapped);
+                }
+                (EntryChoice::Color, EntryDecision::Color(color)) => {
+                    let _ = self.world.insert_one(entity, ChosenColor(color));
+                }
+                (EntryChoice::CreatureType, EntryDecision::CreatureType(creature_type)) => {
+                    let _ = self
+                        .world
+                        .insert_one(entity, ChosenCreatureType(creature_type));
+                }
+                (EntryChoice::Opponent, EntryDecision::Player(player))
+                    if player != controller && self.apnap_order()?.contains(&player) =>
+                {
+                    let _ = self.world.insert_one(entity, ChosenPlayer(player));
+                }
+                (choice, decision) => {
+                    return Err(EngineError::IllegalAction(format!(
+                        "{decision:?} is not a legal decision for {choice:?}."
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the choices the abilities of a permanent require as it enters the battlefield.
+    fn entry_choices(&self, entity: Entity) -> Vec<EntryChoice> {
+        self.world
+            .get::<CardRef>(entity)
+            .map(|card| {
+                card.0
+                    .abilities
+                    .iter()
+                    .filter_map(|it| match it {
+                        Ability::Static(StaticAbility::AsEntersBattlefield(choice)) => {
+                            Some(*choice)
+                        }
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns the next choice the abilities of a permanent that is about to enter the
+    /// battlefield require that its controller hasn't decided on yet, or `None` if there is none.
+    /// The creature types among the objects the controller owns are offered first, the most common
+    /// one leading.
+    fn entry_choice(&self, permanent: Entity, controller: PlayerId) -> Option<PendingChoice> {
+        let decided = self
+            .world
+            .get::<EntryDecisions>(permanent)
+            .map_or(0, |it| it.0.len());
+        let choice = *self.entry_choices(permanent).get(decided)?;
+        let name = ObjectSnapshot::of(&self.world, permanent).name;
+        let (prompt, options, decisions): (_, Vec<String>, Vec<_>) = match choice {
+            EntryChoice::PayLifeOrEnterTapped(amount) => (
+                format!("Pay {amount} life as {name} enters the battlefield?"),
+                vec![format!("Pay {amount} life"), "Let it enter tapped".into()],
+                vec![EntryDecision::PayLife(true), EntryDecision::PayLife(false)],
+            ),
+            EntryChoice::Color => (
+                format!("Choose a color for {name}."),
+                Color::ALL
+                    .iter()
+                    .map(|&it| description::color_name(it).into())
+                    .collect(),
+                Color::ALL.map(EntryDecision::Color).into(),
+            ),
+            EntryChoice::CreatureType => {
+                let mut owned = HashMap::<CreatureType, usize>::new();
+                for (_, (card, _)) in self
+                    .world
+                    .query::<(&CardRef, &Owner)>()
+                    .iter()
+                    .filter(|(_, (_, owner))| owner.0 == controller)
+                {
+                    for subtype in &card.0.type_line.subtype {
+                        if let Subtype::Creature(creature_type) = subtype {
+                            *owned.entry(*creature_type).or_default() += 1;
+                        }
+                    }
+                }
+                let mut types = CreatureType::ALL.to_vec();
+                types.sort_by_key(|it| std::cmp::Reverse(owned.get(it).copied().unwrap_or(0)));
+                (
+                    format!("Choose a creature type for {name}."),
+                    types.iter().map(|it| it.name().into()).collect(),
+                    types.into_iter().map(EntryDecision::CreatureType).collect(),
+                )
+            }
+            EntryChoice::Opponent => {
+                let order = self.apnap_order().ok()?;
+                let index = order.iter().position(|&it| it == controller).unwrap_or(0);
+                let opponents = order
+                    .iter()
+                    .cycle()
+                    .skip(index + 1)
+                    .take(order.len())
+                    .copied()
+                    .filter(|&it| it != controller)
+                    .collect::<Vec<_>>();
+                (
+                    format!("Choose an opponent for {name}."),
+                    opponents
+                        .iter()
+                        .map(|&it| {
+                            self.player(it).map_or_else(
+                                |_| "An unknown player".into(),
+                                |it| it.info.name.clone(),
+                            )
+                        })
+                        .collect(),
+                    opponents.into_iter().map(EntryDecision::Player).collect(),
+                )
+            }
+        };
+        Some(PendingChoice {
+            player: controller,
+            prompt,
+            options,
+            kind: ChoiceKind::Entry {
+                permanent,
+                decisions,
+            },
+        })
+    }
+
+    /// Returns the decision players make for a choice as their permanent enters the battlefield
+    /// if they didn't decide on it themselves.
+    fn default_entry_decision(&self, controller: PlayerId, choice: EntryChoice) -> EntryDecision {
+        match choice {
+            EntryChoice::PayLifeOrEnterTapped(_) => EntryDecision::PayLife(false),
+            EntryChoice::Color => EntryDecision::Color(Color::ALL[0]),
+            EntryChoice::CreatureType => EntryDecision::CreatureType(CreatureType::Advisor),
+            EntryChoice::Opponent => {
+                let order = self.apnap_order().unwrap_or_default();
+                let index = order.iter().position(|&it| it == controller).unwrap_or(0);
+                let opponent = order
+                    .iter()
+                    .cycle()
+                    .skip(index + 1)
+                    .take(order.len())
+                    .find(|&&it| it != controller);
+                EntryDecision::Player(opponent.copied().unwrap_or(controller))
+            }
+        }
+    }
+
+    /// Removes an object that ceases to exist from the game.
+    pub(crate) fn despawn_object(&mut self, entity: Entity) -> Result<(), EngineError> {
+        if self.is_on_battlefield(entity) {
+            self.return_linked_exiles(|it| {
+                it.source == entity && it.condition == ReturnCondition::SourceLeavesBattlefield
+            })?;
+        }
+        if let Ok(MergedWith(cards)) = self.world.remove_one::<MergedWith>(entity) {
+            for card in cards.into_iter().filter(|&it| it != entity) {
+                self.forget_card(card);
+                let _ = self.world.despawn(card);
+            }
+        }
+        self.forget_card(entity);
+        self.retire_object_id(entity);
+        self.zones.remove(&mut self.world, entity);
+        self.world
+            .despawn(entity)
+            .map_err(|_| EngineError::UnknownObject(entity))
+    }
+
+    /// 610.3. Some one-shot effects cause an object to change zones “until” a specified event
+    ///        occurs. [...]
+    ///
+    /// Exiles the target until the return condition is met, after which it returns to the
+    /// battlefield under its owner’s control as a new object. Tokens cease to exist instead of
+    /// returning.
+    pub(crate) fn exile_linked(
+        &mut self,
+        source: Entity,
+        target: Entity,
+        condition: ReturnCondition,
+    ) -> Result<(), EngineError> {
+        // An effect that lasts until its source leaves the battlefield does nothing if the source
+        // has already left.
+        if condition == ReturnCondition::SourceLeavesBattlefield && !self.is_on_battlefield(source)
+        {
+            return Ok(());
+        }
+        self.move_object(target, Zone::Exile)?;
+        if self.zones.zone_of(target) != Some(Zone::Exile) {
+            return Ok(());
+        }
+        let exiled = self
+            .object_id(target)
+            .ok_or(EngineError::UnknownObject(target))?;
+        log::trace!("{target:?} is exiled by {source:?} until {condition:?}.");
+        self.linked_exiles.add(LinkedExile {
+            source,
+            exiled,
+            condition,
+        });
+        Ok(())
+    }
+
+    /// Returns the objects exiled by linked exile effects whose return condition has been met
+    /// according to the predicate to the battlefield.
+    fn return_linked_exiles(
+        &mut self,
+        predicate: impl Fn(&LinkedExile) -> bool,
+    ) -> Result<(), EngineError> {
+        for exile in self.linked_exiles.take_where(predicate) {
+            // Objects that left exile in the meantime are new objects and stay where they are.
+            let Some(entity) = self.object(exile.exiled) else {
+                continue;
+            };
+            // 704.5d If a token is in a zone other than the battlefield, it ceases to exist.
+            if self.world.get::<Token>(entity).is_ok() {
+                self.despawn_object(entity)?;
+                continue;
+            }
+            self.move_object(entity, Zone::Battlefield)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the cards in exile owned by the specified player, ordered by their ids, including
+    /// the identity of cards exiled face down.
+    pub(crate) fn exile(&self, owner: PlayerId) -> Result<Vec<ExiledCard>, EngineError> {
+        self.player(owner)?;
+        let mut cards = self
+            .world
+            .query::<(&ObjectId, &Zone, &Owner)>()
+            .iter()
+            .filter(|(_, (_, &zone, card_owner))| zone == Zone::Exile && card_owner.0 == owner)
+            .map(|(entity, (&id, ..))| {
+                let exiled_by = self
+                    .linked_exiles
+                    .source_of(id)
+                    .and_then(|it| self.object_id(it));
+                let card = ExiledCard {
+                    card: ObjectSnapshot::of(&self.world, entity),
+                    owner,
+                    face_down: self.world.get::<FaceDown>(entity).is_ok(),
+                    exiled_by,
+                };
+                (id, card)
+            })
+            .collect::<Vec<_>>();
+        cards.sort_by_key(|(id, _)| *id);
+        Ok(cards.into_iter().map(|(_, it)| it).collect())
+    }
+
+    /// Returns the cards in exile owned by the specified player as they may be revealed to another
+    /// player, or to spectators if there is none. Cards exiled face down are still included so
+    /// they can be counted.
+    pub(crate) fn exile_for(
+        &self,
+        owner: PlayerId,
+        player: Option<PlayerId>,
+    ) -> Result<Vec<ExiledCard>, EngineError> {
+        Ok(self
+            .exile(owner)?
+            .iter()
+            .map(|it| it.visible_to(player))
+            .collect())
+    }
+
+    /// Puts a card an opponent of the specified player owns from exile into that opponent’s
+    /// graveyard, which processors like Wasteland Strangler do as part of their effects or costs.
+    pub(crate) fn put_exiled_card_into_graveyard(
+        &mut self,
+        player: PlayerId,
+        card: Entity,
+    ) -> Result<(), EngineError> {
+        if self.zones.zone_of(card) != Some(Zone::Exile) {
+            return Err(EngineError::IllegalAction(format!(
+                "{card:?} is not in exile."
+            )));
+        }
+        let owner = self
+            .world
+            .get::<Owner>(card)
+            .map(|it| it.0)
+            .map_err(|_| EngineError::UnknownObject(card))?;
+        if owner == player {
+            return Err(EngineError::IllegalAction(format!(
+                "{card:?} is owned by player with id {} rather than an opponent.",
+                player.0
+            )));
+        }
+        self.move_object(card, Zone::Graveyard(owner))
+    }
+
+    /// Returns whether the object is exiled until a linked event occurs.
+    fn is_linked_exile(&self, entity: Entity) -> bool {
+        self.object_id(entity)
+            .is_some_and(|it| self.linked_exiles.contains(it))
+    }
+
+    /// Returns the entity currently representing the object with the specified id, if the object
+    /// still exists.
+    pub(crate) fn object(&self, id: ObjectId) -> Option<Entity> {
+        self.objects.get(&id).copied()
+    }
+
+    /// Returns the id of the object represented by the specified entity.
+    pub(crate) fn object_id(&self, entity: Entity) -> Option<ObjectId> {
+        self.world.get::<ObjectId>(entity).ok().map(|it| *it)
+    }
+
+    /// Returns the ids of the objects represented by the entities, which identify them in traces.
+    #[cfg(feature = "tracing")]
+    fn object_ids(&self, entities: impl IntoIterator<Item = Entity>) -> Vec<Option<ObjectId>> {
+        entities.into_iter().map(|it| self.object_id(it)).collect()
+    }
+
+    /// Returns the id the specified object had before its most recent zone change, if any.
+    pub(crate) fn previous_id(&self, entity: Entity) -> Option<ObjectId> {
+        self.world.get::<PreviousId>(entity).ok().map(|it| it.0)
+    }
+
+    /// Returns the last known information about an object that no longer exists.
+    pub(crate) fn last_known_information(&self, id: ObjectId) -> Option<&ObjectSnapshot> {
+        self.last_known_information.get(&id)
+    }
+
+    /// Issues a new id for the specified object and retires its previous one, if any.
+    fn issue_object_id(&mut self, entity: Entity) -> Result<ObjectId, EngineError> {
+        let previous = self.retire_object_id(entity);
+        self.object_id += 1;
+        let id = ObjectId(self.object_id);
+        self.world
+            .insert_one(entity, id)
+            .map_err(|_| EngineError::UnknownObject(entity))?;
+        if let Some(previous) = previous {
+            self.world
+                .insert_one(entity, PreviousId(previous))
+                .map_err(|_| EngineError::UnknownObject(entity))?;
+        }
+        self.objects.insert(id, entity);
+        Ok(id)
+    }
+
+    /// Records the last known information of the object represented by the specified entity and
+    /// returns its id, after which the id no longer refers to it.
+    fn retire_object_id(&mut self, entity: Entity) -> Option<ObjectId> {
+        let id = self.object_id(entity)?;
+        self.last_known_information
+            .insert(id, ObjectSnapshot::of(&self.world, entity));
+        self.objects.remove(&id);
+        Some(id)
+    }
+
+    /// Stops counting a card that ceases to exist as one of the cards its owner brought into the
+    /// game.
+    fn forget_card(&mut self, entity: Entity) {
+        let Ok(owner) = self.world.get::<Owner>(entity).map(|it| it.0) else {
+            return;
+        };
+        if self.world.get::<Token>(entity).is_err() && self.world.get::<SpellCopy>(entity).is_err()
+        {
+            if let Some(count) = self.brought_cards.get_mut(&owner) {
+                *count = count.saturating_sub(1);
+            }
+        }
+    }
+
+    /// Asserts that the zone of every object is tracked consistently.
+    pub(crate) fn debug_assert_zones_consistent(&self) {
+        self.zones.debug_assert_consistent(&self.world);
+    }
+
+    /// Verifies the bookkeeping of the game and returns every violation found, which are ordered by
+    /// their kind and the entities involved:
+    ///
+    /// - The cards of each player in all zones add up to the cards they brought into the game.
+    /// - The zone of every object is tracked consistently.
+    /// - Only objects on the stack or on the battlefield have a controller.
+    /// - Tokens only exist on the battlefield. Tokens exiled until an event occurs are exempt as
+    ///   they cease to exist once it does.
+    pub(crate) fn check_invariants(&self) -> Result<(), Vec<InvariantViolation>> {
+        let mut violations = Vec::new();
+        let merged = self
+            .world
+            .query::<&MergedWith>()
+            .iter()
+            .flat_map(|(entity, it)| it.0.iter().copied().filter(move |&it| it != entity))
+            .collect::<HashSet<_>>();
+        let zone_of = |entity| {
+            if merged.contains(&entity) {
+                Some(Zone::Battlefield)
+            } else {
+                self.zones.zone_of(entity)
+            }
+        };
+
+        let mut owned = HashMap::<PlayerId, usize>::new();
+        for (entity, owner) in &mut self
+            .world
+            .query::<&Owner>()
+            .without::<Token>()
+            .without::<SpellCopy>()
+        {
+            if zone_of(entity).is_some() {
+                *owned.entry(owner.0).or_default() += 1;
+            }
+        }
+        for player in &self.players {
+            let expected = self
+                .brought_cards
+                .get(&player.id)
+                .copied()
+                .unwrap_or_default();
+            let actual = owned.get(&player.id).copied().unwrap_or_default();
+            if expected != actual {
+                violations.push(InvariantViolation::CardsNotConserved {
+                    owner: player.id,
+                    expected,
+                    actual,
+                });
+            }
+        }
+
+        violations.extend(self.zones.out_of_sync(&self.world).into_iter().map(
+            |(entity, tracked, cached)| InvariantViolation::ZoneOutOfSync {
+                entity,
+                tracked,
+                cached,
+            },
+        ));
+
+        let mut controlled = self
+            .world
+            .query::<&Controller>()
+            .iter()
+            .map(|(entity, _)| entity)
+            .filter(|&it| !matches!(zone_of(it), Some(Zone::Stack | Zone::Battlefield)))
+            .collect::<Vec<_>>();
+        controlled.sort();
+        violations.extend(controlled.into_iter().map(|entity| {
+            InvariantViolation::ControllerOutsideStackOrBattlefield {
+                entity,
+                zone: zone_of(entity),
+            }
+        }));
+
+        let mut tokens = self
+            .world
+            .query::<&Token>()
+            .iter()
+            .map(|(entity, _)| entity)
+            .filter(|&it| zone_of(it) != Some(Zone::Battlefield) && !self.is_linked_exile(it))
+            .collect::<Vec<_>>();
+        tokens.sort();
+        violations.extend(tokens.into_iter().map(|entity| {
+            InvariantViolation::TokenOutsideBattlefield {
+                entity,
+                zone: zone_of(entity),
+            }
+        }));
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    /// Asserts that the bookkeeping of the game is consistent, see [`Game::check_invariants`].
+    pub(crate) fn debug_assert_invariants(&self) {
+        self.debug_assert_zones_consistent();
+        debug_assert_eq!(
+            self.check_invariants(),
+            Ok(()),
+            "The game violates its invariants."
+        );
+    }
+
+    /// Returns a reference to the library of the specified player.
+    pub(crate) fn library(&self, player: PlayerId) -> Result<&Library, EngineError> {
+        self.zones.library(player)
+    }
+
+    /// 121.1. A player draws a card by putting the top card of their library into their hand. [...]
+    ///
+    /// 121.4. A player who attempts to draw a card from a library with no cards in it loses the
+    ///        game the next time a player would receive priority. [...]
+    ///
+    /// Returns the drawn card or nothing if the library is empty, in which case the player loses
+    /// the game unless an effect says they can't. If a replacement effect makes the player draw
+    /// more than one card instead, the last one is returned.
+    pub(crate) fn draw_card(&mut self, player: PlayerId) -> Result<Option<Entity>, EngineError> {
+        let mut drawn = None;
+        for event in self.replace(ReplaceableEvent::DrawCard { player })? {
+            match event {
+                ReplaceableEvent::DrawCard { player } => {
+                    drawn = self.draw_top_card(player)?.or(drawn);
+                }
+                event => self.perform_replacing_event(event)?,
+            }
+        }
+        Ok(drawn)
+    }
+
+    /// Puts the top card of the library of the specified player into their hand, once the
+    /// replacement effects watching for the draw are applied.
+    fn draw_top_card(&mut self, player: PlayerId) -> Result<Option<Entity>, EngineError> {
+        let Some(card) = self.library(player)?.peek_top(1).first().copied() else {
+            self.player_mut(player)?.drew_from_empty_library = true;
+            self.check_life_totals()?;
+            return Ok(None);
+        };
+        self.move_object(card, Zone::Hand(player))?;
+        self.stats.record_card_drawn(player);
+        Ok(Some(card))
+    }
+
+    /// Returns the number of cards in the hand of the specified player.
+    fn hand_size(&self, player: PlayerId) -> usize {
+        self.world
+            .query::<&Zone>()
+            .iter()
+            .filter(|(_, &zone)| zone == Zone::Hand(player))
+            .count()
+    }
+
+    /// 701.13a For a player to mill a number of cards, that player puts that many cards from the
+    ///         top of their library into their graveyard.
+    ///
+    /// Returns the milled cards, which are fewer than requested if the library runs out.
+    pub(crate) fn mill(
+        &mut self,
+        player: PlayerId,
+        amount: usize,
+    ) -> Result<Vec<Entity>, EngineError> {
+        let cards = self.library(player)?.peek_top(amount);
+        for &card in &cards {
+            self.move_object(card, Zone::Graveyard(player))?;
+        }
+        Ok(cards)
+    }
+
+    /// 701.18a To “scry N” means to look at the top N cards of your library, then put any number
+    ///         of them on the bottom of your library in any order and the rest on top of your
+    ///         library in any order.
+    ///
+    /// `choose` is called with the looked at cards from top to bottom and returns their new
+    /// arrangement.
+    pub(crate) fn scry(
+        &mut self,
+        player: PlayerId,
+        amount: usize,
+        choose: &mut dyn FnMut(&[Entity]) -> ScryChoice,
+    ) -> Result<(), EngineError> {
+        let cards = self.library(player)?.peek_top(amount);
+        let ScryChoice { top, bottom } = choose(&cards);
+
+        let mut chosen = top.iter().chain(&bottom).copied().collect::<Vec<_>>();
+        let mut expected = cards.clone();
+        chosen.sort();
+        expected.sort();
+        if chosen != expected {
+            return Err(EngineError::IllegalAction(
+                "Scrying must put each looked at card on top or on the bottom exactly once.".into(),
+            ));
+        }
+
+        self.zones.rearrange_library(player, &top, &bottom)
+    }
+
+    /// Lets the specified permanent explore. Its controller reveals the top card of their library
+    /// and puts it into their hand if it is a land card. Otherwise, they put a +1/+1 counter on the
+    /// permanent and `choose` is called with the revealed card to decide whether to put it into
+    /// their graveyard rather than leaving it on top of their library. Returns the revealed card, if
+    /// there was one.
+    ///
+    /// # Remarks
+    /// Effects that let a permanent explore as they resolve always leave the revealed card on top
+    /// of the library, as there is no way to make that choice yet.
+    pub(crate) fn explore(
+        &mut self,
+        entity: Entity,
+        choose: &mut dyn FnMut(Entity) -> bool,
+    ) -> Result<Option<Entity>, EngineError> {
+        let player = self
+            .controller(entity)
+            .ok_or(EngineError::UnknownObject(entity))?;
+        let revealed = self.library(player)?.peek_top(1).first().copied();
+        log::trace!(
+            "Player with id {} reveals {revealed:?} as {entity:?} explores.",
+            player.0
+        );
+        if let Some(card) = revealed.filter(|&it| self.has_card_type(it, CardType::Land)) {
+            self.move_object(card, Zone::Hand(player))?;
+            return Ok(revealed);
+        }
+        // Revealing no card at all still counts as not revealing a land card.
+        if self.is_on_battlefield(entity) {
+            self.add_counters(
+                PermanentOrPlayer::Permanent(entity),
+                CounterKind::PlusOnePlusOne,
+                1,
+            )?;
+        }
+        if let Some(card) = revealed.filter(|&it| choose(it)) {
+            self.move_object(card, Zone::Graveyard(player))?;
+        }
+        Ok(revealed)
+    }
+
+    /// 701.19a To search for a card in a zone, look at all cards in that zone (even if it’s a
+    ///         hidden zone) and find a card that matches the given description.
+    ///
+    /// Searches the library of the specified player for a card matching the filter, puts it into
+    /// the specified zone, e.g. their hand or the battlefield, and shuffles the library afterwards.
+    /// `choose` is called with all matching cards and may decline to find one.
+    pub(crate) fn search_library(
+        &mut self,
+        player: PlayerId,
+        filter: &ObjectFilter,
+        to: Zone,
+        choose: &mut dyn FnMut(&[Entity]) -> Option<Entity>,
+    ) -> Result<Option<Entity>, EngineError> {
+        let candidates = self
+            .library(player)?
+            .cards()
+            .filter(|&it| self.filter_matches(filter, player, it, player))
+            .collect::<Vec<_>>();
+        let chosen = choose(&candidates);
+        if let Some(card) = chosen {
+            if !candidates.contains(&card) {
+                return Err(EngineError::InvalidTarget(format!(
+                    "{card:?} doesn't match the description of the search."
+                )));
+            }
+            self.move_object(card, to)?;
+        }
+        self.shuffle_library(player)?;
+        Ok(chosen)
+    }
+
+    /// 701.20a To shuffle a library or a face-down pile of cards, randomize the cards within it so
+    ///         that no player knows their order.
+    ///
+    /// Shuffles the library of the specified player using the random number generator of the
+    /// game. Nobody knows the top card afterwards.
+    pub(crate) fn shuffle_library(&mut self, player: PlayerId) -> Result<(), EngineError> {
+        self.zones.shuffle_library(player, &mut self.random)?;
+        log::trace!("Player with id {} shuffles their library.", player.0);
+        self.events.push(GameEvent::LibraryShuffled { player });
+        Ok(())
+    }
+
+    /// Makes the specified player flip a coin for an effect that doesn't care whether they win or
+    /// lose the flip, using the random number generator of the game.
+    pub(crate) fn flip_coin(&mut self, player: PlayerId) -> Result<CoinResult, EngineError> {
+        self.flip_coin_with_call(player, None)
+    }
+
+    /// 705.1. To flip a coin for an object that cares whether a player wins or loses the flip, the
+    ///        player flips the coin and calls “heads” or “tails.” If the call matches the result,
+    ///        that player wins the flip. Otherwise, the player loses the flip. [...]
+    ///
+    /// Makes the specified player flip a coin after the call has been made, which some effects let
+    /// another player make, and returns whether the call matches the result.
+    pub(crate) fn call_coin_flip(
+        &mut self,
+        player: PlayerId,
+        call: CoinCall,
+    ) -> Result<bool, EngineError> {
+        self.player(call.caller)?;
+        Ok(self.flip_coin_with_call(player, Some(call))? == call.call)
+    }
+
+    fn flip_coin_with_call(
+        &mut self,
+        player: PlayerId,
+        call: Option<CoinCall>,
+    ) -> Result<CoinResult, EngineError> {
+        self.player(player)?;
+        let result = if self.random.gen() {
+            CoinResult::Heads
+        } else {
+            CoinResult::Tails
+        };
+        self.events.push(GameEvent::CoinFlipped {
+            player,
+            call,
+            result,
+        });
+        Ok(result)
+    }
+
+    /// Makes the specified player roll a die with the specified number of sides using the random
+    /// number generator of the game and returns the result.
+    pub(crate) fn roll_die(&mut self, player: PlayerId, sides: u32) -> Result<u32, EngineError> {
+        self.roll_dice(player, sides, DiceRoll::Single)
+    }
+
+    /// Makes the specified player roll dice with the specified number of sides and returns the
+    /// result that counts. Abilities that trigger whenever a player rolls a die trigger for each
+    /// die, even if its result is ignored.
+    pub(crate) fn roll_dice(
+        &mut self,
+        player: PlayerId,
+        sides: u32,
+        roll: DiceRoll,
+    ) -> Result<u32, EngineError> {
+        self.player(player)?;
+        let count = match roll {
+            DiceRoll::Single => 1,
+            DiceRoll::KeepHighest(count) => count,
+        };
+        if sides == 0 || count == 0 {
+            return Err(EngineError::IllegalAction(format!(
+                "Cannot roll {count} dice with {sides} sides."
+            )));
+        }
+        let rolls = (0..count)
+            .map(|_| self.random.gen_range(1..=sides))
+            .collect::<Vec<_>>();
+        let result = rolls.iter().copied().max().unwrap_or_default();
+        log::trace!(
+            "Player with id {} rolls {rolls:?} on d{sides} dice.",
+            player.0
+        );
+        self.events.push(GameEvent::DieRolled {
+            player,
+            sides,
+            rolls,
+            result,
+        });
+        for _ in 0..count {
+            self.check_triggers(&TriggerEvent::DieRolled { player });
+        }
+        Ok(result)
+    }
+
+    /// Shuffles the specified object into the library of its owner, e.g. “Shuffle target card
+    /// from your graveyard into your library.”
+    pub(crate) fn shuffle_into_library(&mut self, entity: Entity) -> Result<(), EngineError> {
+        let owner = self
+            .world
+            .get::<Owner>(entity)
+            .map_err(|_| EngineError::UnknownObject(entity))?
+            .0;
+        self.move_object(entity, Zone::Library(owner))?;
+        self.shuffle_library(owner)
+    }
+
+    /// Lets a player look at the top card of the library of the specified player, e.g. “You may
+    /// look at the top card of your library any time.”, and returns it, if there is any.
+    pub(crate) fn look_at_top_card(
+        &mut self,
+        library: PlayerId,
+        player: PlayerId,
+    ) -> Result<Option<Entity>, EngineError> {
+        self.player(player)?;
+        self.zones.look_at_top_card(library, player)
+    }
+
+    /// Returns the triggered abilities that have triggered but haven't been resolved yet.
+    pub(crate) fn pending_triggers(&self) -> &[PendingTrigger] {
+        &self.pending_triggers
+    }
+
+    /// 603.2. Whenever a game event or game state matches a triggered ability’s trigger event,
+    ///        that ability automatically triggers. The ability doesn’t do anything at this point.
+    fn check_triggers(&mut self, event: &TriggerEvent) {
+        let sources = self.trigger_sources();
+        self.check_triggers_of(event, sources);
+    }
+
+    /// Checks which abilities trigger on an object changing zones, or records the event to check
+    /// them once all objects moving at the same time have moved.
+    fn check_zone_change_triggers(&mut self, event: TriggerEvent) {
+        match &mut self.simultaneous_events {
+            Some(events) => events.push(event),
+            None => self.check_triggers(&event),
+        }
+    }
+
+    /// Returns the permanents with triggered abilities along with their controllers and abilities.
+    fn trigger_sources(&self) -> Vec<(Entity, PlayerId, Vec<TriggeredAbility>)> {
+        self.world
+            .query::<(&Zone, &Controller, &TriggeredAbilities)>()
+            .without::<PhasedOut>()
+            .iter()
+            .filter(|&(entity, (zone, ..))| {
+                *zone == Zone::Battlefield && self.has_printed_abilities(entity)
+            })
+            .map(|(entity, (_, controller, abilities))| (entity, controller.0, abilities.0.clone()))
+            .collect()
+    }
+
+    /// Checks which abilities of the specified sources trigger on the event.
+    fn check_triggers_of(
+        &mut self,
+        event: &TriggerEvent,
+        mut sources: Vec<(Entity, PlayerId, Vec<TriggeredAbility>)>,
+    ) {
+        self.add_look_back_sources(event, &mut sources);
+
+        let mut triggered_abilities = Vec::new();
+        for (source, controller, abilities) in sources {
+            for ability in abilities {
+                if self.triggers_on(&ability.condition, event, source, controller)
+                    && self.intervening_if_holds(
+                        ability.intervening_if.as_ref(),
+                        source,
+                        controller,
+                    )
+                {
+                    log::trace!(
+                        "An ability of {source:?} triggers for player with id {}.",
+                        controller.0
+                    );
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(
+                        object = ?self.object_id(source),
+                        player = controller.0,
+                        "A triggered ability is queued."
+                    );
+                    let trigger = PendingTrigger {
+                        source,
+                        controller,
+                        intervening_if: ability.intervening_if,
+                        effects: ability.effects,
+                        decisions: Vec::new(),
+                    };
+                    self.events.push(GameEvent::AbilityTriggered {
+                        controller,
+                        source: ObjectSnapshot::of(&self.world, source),
+                        optional: trigger.is_optional(),
+                    });
+                    triggered_abilities.push(trigger);
+                }
+            }
+        }
+
+        // 603.3b If multiple players have triggered abilities that have triggered since the last
+        //        time a player received priority, each player, in APNAP order, puts triggered
+        //        abilities they control on the stack in any order they choose. [...]
+        let order = self.apnap_order().unwrap_or_default();
+        triggered_abilities
+            .sort_by_key(|it| order.iter().position(|&player| player == it.controller));
+        self.pending_triggers.extend(triggered_abilities);
+    }
+
+    /// 603.10a Some zone-change triggers look back in time. These are leaves-the-battlefield
+    ///         abilities, [...]
+    ///
+    /// Adds the object that left the battlefield or was put into a graveyard to the sources whose
+    /// abilities are checked, as its abilities trigger on the event even though it is gone.
+    fn add_look_back_sources(
+        &self,
+        event: &TriggerEvent,
+        sources: &mut Vec<(Entity, PlayerId, Vec<TriggeredAbility>)>,
+    ) {
+        if let TriggerEvent::LeavesBattlefield {
+            object, controller, ..
+        } = *event
+        {
+            if let Ok(abilities) = self.world.get::<TriggeredAbilities>(object) {
+                if self.has_printed_abilities(object)
+                    && !sources.iter().any(|(source, ..)| *source == object)
+                {
+                    sources.push((object, controller, abilities.0.clone()));
+                }
+            }
+        }
+        if let TriggerEvent::PutIntoGraveyard { object, owner } = *event {
+            if let Ok(abilities) = self.world.get::<TriggeredAbilities>(object) {
+                sources.push((object, owner, abilities.0.clone()));
+            }
+        }
+    }
+
+    /// Returns whether the trigger condition of an ability of the specified source controlled by
+    /// the specified player is met by the event, ignoring any intervening “if” clause.
+    fn triggers_on(
+        &self,
+        condition: &TriggerCondition,
+        event: &TriggerEvent,
+        source: Entity,
+        controller: PlayerId,
+    ) -> bool {
+        match (condition, event) {
+            (
+                TriggerCondition::EntersBattlefield(subject),
+                &TriggerEvent::EntersBattlefield {
+                    object,
+                    controller: object_controller,
+                },
+            )
+            | (
+                TriggerCondition::Dies(subject),
+                &TriggerEvent::LeavesBattlefield {
+                    object,
+                    controller: object_controller,
+                    to: Zone::Graveyard(_),
+                },
+            )
+            | (
+                TriggerCondition::LeavesBattlefield(subject),
+                &TriggerEvent::LeavesBattlefield {
+                    object,
+                    controller: object_controller,
+                    ..
+                },
+            )
+            | (
+                TriggerCondition::Attacks(subject),
+                &TriggerEvent::Attacks {
+                    object,
+                    controller: object_controller,
+                },
+            ) => self.subject_matches(subject, source, controller, object, object_controller),
+            (
+                TriggerCondition::BeginningOfUpkeep(scope),
+                TriggerEvent::BeginningOfStep(Step::Upkeep),
+            )
+            | (
+                TriggerCondition::BeginningOfDrawStep(scope),
+                TriggerEvent::BeginningOfStep(Step::Draw),
+            )
+            | (
+                TriggerCondition::BeginningOfEndStep(scope),
+                TriggerEvent::BeginningOfStep(Step::End),
+            ) => scope.includes(controller, self.turn.active_player),
+            (TriggerCondition::RollsDie(scope), &TriggerEvent::DieRolled { player }) => {
+                scope.includes(controller, player)
+            }
+            (
+                TriggerCondition::LandEntersBattlefieldUnderYourControl,
+                &TriggerEvent::EntersBattlefield {
+                    object,
+                    controller: object_controller,
+                },
+            ) => object_controller == controller && self.has_card_type(object, CardType::Land),
+            (
+                TriggerCondition::PutIntoGraveyardFromAnywhere,
+                &TriggerEvent::PutIntoGraveyard { object, .. },
+            ) => object == source,
+            (
+                TriggerCondition::YouCastSpell { filter },
+                &TriggerEvent::SpellCast { spell, caster },
+            ) => caster == controller && self.filter_matches(filter, controller, spell, caster),
+            _ => false,
+        }
+    }
+
+    /// 603.4. [...] This rule is referred to as the “intervening ‘if’ clause” rule. [...]
+    ///
+    /// Returns whether the condition of an intervening “if” clause of an ability of the specified
+    /// source is true for the controller of the ability, which is always the case for abilities
+    /// without such a clause.
+    fn intervening_if_holds(
+        &self,
+        condition: Option<&InterveningIf>,
+        source: Entity,
+        controller: PlayerId,
+    ) -> bool {
+        match condition {
+            None => true,
+            Some(InterveningIf::Permanents(filter, comparison)) => {
+                comparison.matches(self.permanents_matching(filter, controller).len() as i64)
+            }
+            Some(InterveningIf::Quantity(quantity, comparison)) => self
+                .quantity(quantity, source, controller)
+                .is_ok_and(|it| comparison.matches(it as i64)),
+        }
+    }
+
+    /// 101.4. If multiple players would make choices and/or take actions at the same time, the
+    ///        active player (the player whose turn it is) makes any choices required, then the
+    ///        next player in turn order (usually the player seated to the active player’s left)
+    ///        makes any choices required, followed by the remaining nonactive players in turn
+    ///        order. [...]
+    ///
+    /// Returns all players still in the game in APNAP order, starting with the active player.
+    ///
+    /// # Errors
+    /// Fails if the active player isn't one of the players of the game.
+    pub(crate) fn apnap_order(&self) -> Result<Vec<PlayerId>, EngineError> {
+        let active_player = self
+            .players
+            .iter()
+            .position(|it| it.id == self.turn.active_player)
+            .ok_or(EngineError::UnknownPlayer(self.turn.active_player))?;
+        Ok(self.players[active_player..]
+            .iter()
+            .chain(&self.players[..active_player])
+            .filter(|it| !it.has_left)
+            .map(|it| it.id)
+            .collect())
+    }
+
+    /// Begins the next step scheduled for the current turn and returns it, or returns `None` once
+    /// the turn is over and the next turn should begin.
+    pub(crate) fn advance_step(&mut self) -> Result<Option<Step>, EngineError> {
+        self.ensure_no_pending_triggers()?;
+        loop {
+            let Some(step) = self.turn.next_step() else {
+                return Ok(None);
+            };
+            // 508.8. If no creatures are declared as attackers or put onto the battlefield
+            //        attacking, skip the declare blockers and combat damage steps.
+            let has_attackers = self.world.query::<&Attacking>().iter().next().is_some();
+            if matches!(step, Step::DeclareBlockers | Step::CombatDamage) && !has_attackers {
+                continue;
+            }
+            self.begin_step(step)?;
+            return Ok(Some(step));
+        }
+    }
+
+    /// Sets whether the decisions of the specified player that have a single legal option are made
+    /// without consulting the chooser of the decision, i.e. discarding a single card or their
+    /// whole hand and choosing the only legal targets of a triggered ability. The order of
+    /// simultaneous triggered abilities is never a decision of the player since the engine puts
+    /// them in APNAP order.
+    ///
+    /// # Errors
+    /// Fails if the player doesn't exist.
+    pub fn set_auto_choose_trivial(
+        &mut self,
+        player: PlayerId,
+        enabled: bool,
+    ) -> Result<(), EngineError> {
+        self.player_mut(player)?.auto_choose_trivial = enabled;
+        self.inputs
+            .push(GameInput::AutoChooseTrivial { player, enabled });
+        Ok(())
+    }
+
+    /// Replaces the steps at which the specified player wants to receive priority.
+    pub(crate) fn set_priority_stops(
+        &mut self,
+        player: PlayerId,
+        stops: IndexSet<PriorityStop>,
+    ) -> Result<(), EngineError> {
+        self.player(player)?;
+        self.priority_stops.insert(player, stops);
+        Ok(())
+    }
+
+    /// 117.4. If all players pass in succession (that is, if all players pass without taking any
+    ///        actions in between passing), the spell or ability on top of the stack resolves or, if
+    ///        the stack is empty, the phase or step ends.
+    ///
+    /// Advances the game step by step while all players pass priority automatically, resolving
+    /// mandatory triggers on the way. Stops as soon as a player has a priority stop at the current
+    /// step, a trigger requires a choice or the game has ended.
+    pub(crate) fn run_until_stop(&mut self) -> Result<Stop, EngineError> {
+        loop {
+            self.resolve_mandatory_triggers()?;
+            if self.outcome.is_some() {
+                return Ok(Stop::GameOver);
+            }
+            if let Some(trigger) = self.pending_triggers.first() {
+                return Ok(Stop::PendingChoice(trigger.controller));
+            }
+            let Some(step) = self.advance_step()? else {
+                self.next_turn()?;
+                continue;
+            };
+            // The stops are checked again once triggers have been dealt with.
+            self.resolve_mandatory_triggers()?;
+            if self.outcome.is_some() || !self.pending_triggers.is_empty() {
+                continue;
+            }
+            if matches!(step, Step::Untap | Step::Cleanup) {
+                continue;
+            }
+            let active_player = self.turn.active_player;
+            let stopping = self.apnap_order()?.into_iter().find(|player| {
+                self.priority_stops.get(player).is_some_and(|stops| {
+                    stops.contains(&PriorityStop {
+                        step,
+                        own_turn: *player == active_player,
+                    })
+                })
+            });
+            if let Some(player) = stopping {
+                return Ok(Stop::Priority(player));
+            }
+            // 117.4. If all players pass in succession [...], the phase or step ends.
+            let passes = self.apnap_order()?.len() as u64;
+            self.stats.record_priority_passes(passes);
+        }
+    }
+
+    /// 117.1. Unless a spell or ability is instructing a player to take an action, which player
+    ///        can take actions at any given time is determined by a system of priority. [...]
+    ///
+    /// Advances the game until a player has to decide what to do and returns them, or returns
+    /// `None` once the game has ended. The player either declares attackers or blockers, chooses
+    /// the targets of a triggered ability or has priority, see [`Game::legal_actions`]. Players
+    /// pass priority automatically while the stack is empty unless they have a priority stop at
+    /// the current step, and players without creatures that could attack or block declare none.
+    ///
+    /// # Errors
+    /// Fails if resolving a spell or a triggered ability or beginning the next step fails.
+    pub fn player_to_act(&mut self) -> Result<Option<PlayerId>, EngineError> {
+        loop {
+            self.check_state_based_actions()?;
+            self.resolve_mandatory_triggers()?;
+            if self.choice.is_none() && self.outcome.is_none() {
+                self.choice = self
+                    .replacement_order_choice()
+                    .or_else(|| self.trigger_option_choice());
+            }
+            match self.pending_decision() {
+                None if self.outcome.is_some() => return Ok(None),
+                None => self.begin_next_step()?,
+                Some((player, Decision::Attackers))
+                    if self.possible_attackers(player).is_empty() =>
+                {
+                    self.declare_attackers(player, &[])?;
+                }
+                Some((player, Decision::Blockers)) if self.possible_blocks(player).is_empty() => {
+                    self.declare_blockers(player, &[])?;
+                }
+                Some((player, Decision::Priority))
+                    if self.passes_priority_automatically(player) =>
+                {
+                    self.pass_priority(player)?;
+                }
+                Some((player, Decision::Targets))
+                    if self.player(player).is_ok_and(|it| it.auto_choose_trivial) =>
+                {
+                    match self.only_target_choice() {
+                        Some(_) if self.is_in_loop() => {}
+                        Some(targets) => self.resolve_trigger_targeting(player, &targets, true)?,
+                        None => return Ok(Some(player)),
+                    }
+                }
+                Some((player, _)) => return Ok(Some(player)),
+            }
+        }
+    }
+
+    /// Takes the action on behalf of the specified player, who has to be the player to act as
+    /// returned by [`Game::player_to_act`] unless they concede, which they can do at any time. The
+    /// action has to fit the decision the player faces, e.g. only the active player declares
+    /// attackers and only at the beginning of the declare attackers step.
+    ///
+    /// 117.3c If a player has priority when they cast a spell, activate an ability, or take a
+    ///        special action, that player receives priority afterward.
+    ///
+    /// A spell that is cast resolves with the chosen targets once all players have passed priority
+    /// in succession.
+    ///
+    /// # Errors
+    /// Fails if it isn't the player's turn to act, the action doesn't fit their decision or the
+    /// action itself is illegal, e.g. because a target isn't legal or a cost can't be paid.
+    pub fn apply(&mut self, player: PlayerId, action: GameAction) -> Result<(), EngineError> {
+        self.take_action(player, action.clone())?;
+        // Only actions that were taken are recorded, since restoring a snapshot replays them.
+        self.inputs.push(GameInput::Action { player, action });
+        Ok(())
+    }
+
+    /// Takes the action on behalf of the player, see [`Game::apply`].
+    fn take_action(&mut self, player: PlayerId, action: GameAction) -> Result<(), EngineError> {
+        if action == GameAction::Concede {
+            return self.concede(player);
+        }
+        let decision = match self.player_to_act()? {
+            Some(it) if it == player => self.pending_decision().map(|(_, it)| it),
+            _ => None,
+        };
+        if decision.is_some() {
+            self.loop_states.clear();
+        }
+        match (decision, action) {
+            (Some(Decision::Choice), GameAction::Choose { option }) => self.choose(option),
+            (Some(Decision::Priority), GameAction::PassPriority) => self.pass_priority(player),
+            (Some(Decision::Priority), GameAction::PlayLand { card }) => {
+                self.play_land_deciding(player, self.entity_of(card)?)
+            }
+            (
+                Some(Decision::Priority),
+                GameAction::CastSpell {
+                    spell,
+                    targets,
+                    x,
+                    modes,
+                },
+            ) => {
+                self.cast_spell_targeting(player, self.entity_of(spell)?, &targets, x, &modes)?;
+                self.passes = 0;
+                Ok(())
+            }
+            (
+                Some(Decision::Priority),
+                GameAction::ActivateAbility {
+                    source,
+                    index,
+                    targets,
+                },
+            ) => {
+                let source = self.entity_of(source)?;
+                let effects = self
+                    .world
+                    .get::<ActivatedAbilities>(source)
+                    .ok()
+                    .and_then(|it| it.0.get(index).map(|it| it.effects.clone()))
+                    .unwrap_or_default();
+                let targets = self.chosen_targets(source, player, &effects, &targets)?;
+                self.activate_ability(player, source, index, &targets)?;
+                self.passes = 0;
+                Ok(())
+            }
+            (Some(Decision::Attackers), GameAction::DeclareAttackers { attackers }) => {
+                let attackers = attackers
+                    .into_iter()
+                    .map(|it| self.entity_of(it))
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.declare_attackers(player, &attackers)
+            }
+            (Some(Decision::Blockers), GameAction::DeclareBlockers { blocks }) => {
+                let blocks = blocks
+                    .into_iter()
+                    .map(|(blocker, attacker)| {
+                        Ok((self.entity_of(blocker)?, self.entity_of(attacker)?))
+                    })
+                    .collect::<Result<Vec<_>, EngineError>>()?;
+                self.declare_blockers(player, &blocks)
+            }
+            (Some(Decision::Targets), GameAction::ResolveTrigger { targets }) => {
+                self.resolve_trigger_targeting(player, &targets, false)
+            }
+            (_, action) => Err(EngineError::IllegalTiming(format!(
+                "Player with id {} can't take the action {action:?} right now.",
+                player.0
+            ))),
+        }
+    }
+
+    /// Makes the pending choice with the option at the specified index.
+    fn choose(&mut self, option: usize) -> Result<(), EngineError> {
+        let Some(mut choice) = self.choice.take() else {
+            return Err(EngineError::IllegalTiming(
+                "There is no choice to make.".into(),
+            ));
+        };
+        if option >= choice.options.len() {
+            let error = EngineError::IllegalAction(format!(
+                "There is no option {option} to choose for “{}”.",
+                choice.prompt
+            ));
+            self.choice = Some(choice);
+            return Err(error);
+        }
+        log::debug!(
+            "Player with id {} chooses “{}” for “{}”.",
+            choice.player.0,
+            choice.options[option],
+            choice.prompt
+        );
+        match choice.kind {
+            ChoiceKind::ReplacementOrder { event, mut chosen } => {
+                chosen.push(choice.options.remove(option));
+                if choice.options.len() > 1 {
+                    self.choice = Some(PendingChoice {
+                        kind: ChoiceKind::ReplacementOrder { event, chosen },
+                        ..choice
+                    });
+                    return Ok(());
+                }
+                chosen.append(&mut choice.options);
+                self.replacement_orders.retain(|(it, _)| *it != event);
+                self.replacement_orders.push((event, chosen));
+            }
+            ChoiceKind::TriggerOption => {
+                if let Some(trigger) = self.pending_triggers.first_mut() {
+                    trigger.decisions.push(option == 0);
+                }
+            }
+            ChoiceKind::SpellOption { spell } => {
+                let mut decisions = self
+                    .world
+                    .remove_one::<OptionDecisions>(spell)
+                    .map(|it| it.0)
+                    .unwrap_or_default();
+                decisions.push(option == 0);
+                let _ = self.world.insert_one(spell, OptionDecisions(decisions));
+                return self.resolve_top_of_stack();
+            }
+            ChoiceKind::Entry {
+                permanent,
+                mut decisions,
+            } => {
+                let mut decided = self
+                    .world
+                    .remove_one::<EntryDecisions>(permanent)
+                    .map(|it| it.0)
+                    .unwrap_or_default();
+                decided.push(decisions.swap_remove(option));
+                let _ = self.world.insert_one(permanent, EntryDecisions(decided));
+                return if self.zones.zone_of(permanent) == Some(Zone::Stack) {
+                    self.resolve_top_of_stack()
+                } else {
+                    self.play_land_deciding(choice.player, permanent)
+                };
+            }
+        }
+        Ok(())
+    }
+
+    /// 603.5. [...] The choice is made when the ability resolves. [...]
+    ///
+    /// Returns the choice whether to take the next optional action of the oldest pending triggered
+    /// ability that hasn't been decided on yet.
+    fn trigger_option_choice(&self) -> Option<PendingChoice> {
+        let trigger = self.pending_triggers.first()?;
+        self.option_choice(
+            trigger.source,
+            trigger.controller,
+            &trigger.effects,
+            trigger.decisions.len(),
+            ChoiceKind::TriggerOption,
+        )
+    }
+
+    /// 608.2d If an effect of a spell or ability offers any choices other than choices already
+    ///        made as part of casting the spell, activating the ability, or otherwise putting it on
+    ///        the stack, the player announces these while applying the effect. [...]
+    ///
+    /// Returns the choice whether to take the next optional action of the spell that is about to
+    /// resolve that hasn't been decided on yet.
+    fn spell_option_choice(&self, spell: Entity) -> Option<PendingChoice> {
+        let card = Arc::clone(&self.world.get::<CardRef>(spell).ok()?.0);
+        let modes = self
+            .world
+            .get::<ChosenModes>(spell)
+            .map(|it| it.0.clone())
+            .unwrap_or_default();
+        let decided = self
+            .world
+            .get::<OptionDecisions>(spell)
+            .map_or(0, |it| it.0.len());
+        self.option_choice(
+            spell,
+            self.world.get::<Owner>(spell).ok()?.0,
+            &spell_effects(&card, &modes),
+            decided,
+            ChoiceKind::SpellOption { spell },
+        )
+    }
+
+    /// Returns the choice whether to take the optional action among the effects after the ones
+    /// that were already decided on, or `None` if there is none.
+    fn option_choice(
+        &self,
+        source: Entity,
+        controller: PlayerId,
+        effects: &[Effect],
+        decided: usize,
+        kind: ChoiceKind,
+    ) -> Option<PendingChoice> {
+        let effect = effects.iter().filter(|it| it.is_optional()).nth(decided)?;
+        let options = match effect {
+            Effect::SacrificeUnlessPay(cost) => vec![
+                format!("Pay {cost}"),
+                format!("Sacrifice {}", ObjectSnapshot::of(&self.world, source).name),
+            ],
+            _ => vec!["Yes".into(), "No".into()],
+        };
+        Some(PendingChoice {
+            player: controller,
+            prompt: self.describe_effects(source, controller, std::slice::from_ref(effect), &[])?,
+            options,
+            kind,
+        })
+    }
+
+    /// 616.1. If two or more replacement and/or prevention effects are attempting to modify the
+    ///        way an event affects an object or player, the affected object’s controller (or its
+    ///        owner if it has no controller) or the affected player chooses one to apply [...]
+    ///
+    /// Returns the choice of the order of replacement effects that compete to modify the same kind
+    /// of event affecting a player or a permanent, if the affected player hasn't ordered all of
+    /// them yet. The order is asked for as soon as the effects compete and applies to every such
+    /// event until the end of the turn.
+    ///
+    /// # Remarks
+    /// Players choose the order once for each kind of event instead of every time an event happens,
+    /// and effects watching for objects entering the battlefield apply in the order they were
+    /// created.
+    fn replacement_order_choice(&self) -> Option<PendingChoice> {
+        if self.replacement_effects.is_empty() {
+            return None;
+        }
+        let players = self
+            .players
+            .iter()
+            .filter(|it| !it.has_left)
+            .flat_map(|it| {
+                let player = it.id;
+                [
+                    ReplaceableEvent::DrawCard { player },
+                    ReplaceableEvent::GainLife { player, amount: 1 },
+                    ReplaceableEvent::DealDamage {
+                        recipient: PermanentOrPlayer::Player(player),
+                        amount: 1,
+                    },
+                ]
+                .map(|event| (player, event))
+            });
+        let permanents = self
+            .world
+            .query::<(&Zone, &Controller)>()
+            .iter()
+            .filter(|(_, (&zone, _))| zone == Zone::Battlefield)
+            .map(|(object, (_, controller))| (object, controller.0))
+            .collect::<Vec<_>>();
+        let permanents = permanents.into_iter().flat_map(|(object, controller)| {
+            [
+                ReplaceableEvent::DealDamage {
+                    recipient: PermanentOrPlayer::Permanent(object),
+                    amount: 1,
+                },
+                ReplaceableEvent::Destroy { object },
+                ReplaceableEvent::Die { object },
+                ReplaceableEvent::PutIntoGraveyard { object },
+                ReplaceableEvent::Untap { object },
+            ]
+            .map(|event| (controller, event))
+        });
+        players.chain(permanents).find_map(|(player, event)| {
+            let options = self
+                .replacement_effects_for(&event)
+                .describe_candidates(&event);
+            let ordered = self
+                .replacement_orders
+                .iter()
+                .any(|(it, order)| *it == event && options.iter().all(|it| order.contains(it)));
+            if options.len() < 2 || ordered {
+                return None;
+            }
+            let affected = match event {
+                ReplaceableEvent::DrawCard { .. }
+                | ReplaceableEvent::GainLife { .. }
+                | ReplaceableEvent::DealDamage {
+                    recipient: PermanentOrPlayer::Player(_),
+                    ..
+                } => "you".to_owned(),
+                ReplaceableEvent::DealDamage {
+                    recipient: PermanentOrPlayer::Permanent(object),
+                    ..
+                }
+                | ReplaceableEvent::Destroy { object }
+                | ReplaceableEvent::Die { object }
+                | ReplaceableEvent::PutIntoGraveyard { object }
+                | ReplaceableEvent::Untap { object } => {
+                    ObjectSnapshot::of(&self.world, object).name
+                }
+                _ => "it".to_owned(),
+            };
+            Some(PendingChoice {
+                player,
+                prompt: format!(
+                    "Which replacement effect applies first if {affected} would {}?",
+                    event.describe()
+                ),
+                options,
+                kind: ChoiceKind::ReplacementOrder {
+                    event,
+                    chosen: Vec::new(),
+                },
+            })
+        })
+    }
+
+    /// Resolves the oldest pending triggered ability of the specified player with the chosen
+    /// targets, which the engine chose for them if the choice is automatic.
+    fn resolve_trigger_targeting(
+        &mut self,
+        player: PlayerId,
+        targets: &[Target],
+        automatic: bool,
+    ) -> Result<(), EngineError> {
+        let trigger = &self.pending_triggers[0];
+        let source = trigger.source;
+        let targets = self.chosen_targets(source, player, &trigger.effects, targets)?;
+        if !targets.is_empty() {
+            self.events.push(GameEvent::TargetsChosen {
+                player,
+                source: ObjectSnapshot::of(&self.world, source),
+                automatic,
+            });
+        }
+        self.resolve_next_trigger(&targets).map(|_| ())
+    }
+
+    /// Returns the targets of the oldest pending triggered ability if it requires targets and
+    /// there is only a single legal choice of them.
+    fn only_target_choice(&self) -> Option<Vec<Target>> {
+        let trigger = self.pending_triggers.first()?;
+        if !trigger.effects.iter().any(Effect::requires_target) {
+            return None;
+        }
+        let mut choices = self.target_choices(trigger.source, trigger.controller, &trigger.effects);
+        if choices.len() == 1 {
+            choices.pop()
+        } else {
+            None
+        }
+    }
+
+    /// Returns the actions the specified player can take right now, which are none unless they are
+    /// the player to act as returned by [`Game::player_to_act`]. Conceding is left out since it is
+    /// always possible.
+    ///
+    /// # Remarks
+    /// The list is meant for clients and agents, so it is a selection rather than every legal
+    /// action: attackers are offered all at once or one at a time, blockers one at a time, and mana
+    /// abilities aren't offered since mana costs are paid automatically. Activated abilities whose
+    /// costs require choosing objects to sacrifice, discard or exile aren't offered either.
+    ///
+    /// # Errors
+    /// Fails with [`EngineError::UnknownPlayer`] if there is no player with the specified id.
+    pub fn legal_actions(&self, player: PlayerId) -> Result<Vec<GameAction>, EngineError> {
+        self.player(player)?;
+        let decision = match self.pending_decision() {
+            Some((it, decision)) if it == player => decision,
+            _ => return Ok(Vec::new()),
+        };
+        Ok(match decision {
+            Decision::Choice => (0..self.choice.as_ref().map_or(0, |it| it.options.len()))
+                .map(|option| GameAction::Choose { option })
+                .collect(),
+            Decision::Targets => {
+                let trigger = &self.pending_triggers[0];
+                self.target_choices(trigger.source, player, &trigger.effects)
+                    .into_iter()
+                    .map(|targets| GameAction::ResolveTrigger { targets })
+                    .collect()
+            }
+            Decision::Attackers => self.attack_choices(player),
+            Decision::Blockers => {
+                let ids = |(blocker, attacker)| {
+                    Some((self.object_id(blocker)?, self.object_id(attacker)?))
+                };
+                std::iter::once(Vec::new())
+                    .chain(
+                        self.possible_blocks(player)
+                            .into_iter()
+                            .filter_map(ids)
+                            .map(|it| vec![it]),
+                    )
+                    .map(|blocks| GameAction::DeclareBlockers { blocks })
+                    .collect()
+            }
+            Decision::Priority => self.priority_actions(player)?,
+        })
+    }
+
+    /// Returns the player who has to make the next decision in the game as it is and the kind of
+    /// decision, or `None` if the current step ends or the game has ended.
+    fn pending_decision(&self) -> Option<(PlayerId, Decision)> {
+        if self.outcome.is_some() {
+            return None;
+        }
+        if let Some(choice) = &self.choice {
+            return Some((choice.player, Decision::Choice));
+        }
+        if let Some(trigger) = self.pending_triggers.first() {
+            return Some((trigger.controller, Decision::Targets));
+        }
+        match self.turn.step {
+            // 508.1. First, the active player declares attackers. This turn-based action doesn’t
+            //        use the stack. [...]
+            Step::DeclareAttackers if !self.combat.attackers_declared() => {
+                Some((self.turn.active_player, Decision::Attackers))
+            }
+            // 509.1. First, the defending player declares blockers. This turn-based action doesn’t
+            //        use the stack. [...]
+            Step::DeclareBlockers if !self.combat.blockers_declared() => self
+                .apnap_order()
+                .ok()?
+                .get(1)
+                .map(|&it| (it, Decision::Blockers)),
+            _ => self.priority.map(|it| (it, Decision::Priority)),
+        }
+    }
+
+    /// 500.2. A phase or step in which players receive priority ends when the stack is empty and
+    ///        all players pass in succession. [...]
+    ///
+    /// Begins the next step, or the next turn once the current one is over, and gives the active
+    /// player priority.
+    fn begin_next_step(&mut self) -> Result<(), EngineError> {
+        if self.advance_step()?.is_none() {
+            self.next_turn()?;
+        }
+        // 502.4. No player receives priority during the untap step.
+        //
+        // 514.3. Normally, no player receives priority during the cleanup step [...]
+        if !matches!(self.turn.step, Step::Untap | Step::Cleanup) {
+            self.priority = Some(self.turn.active_player);
+        }
+        Ok(())
+    }
+
+    /// Returns whether the specified player passes priority without being asked, which they do
+    /// while the stack is empty unless they have a priority stop at the current step.
+    fn passes_priority_automatically(&self, player: PlayerId) -> bool {
+        let stop = PriorityStop {
+            step: self.turn.step,
+            own_turn: player == self.turn.active_player,
+        };
+        self.objects_on_stack().is_empty()
+            && !self
+                .priority_stops
+                .get(&player)
+                .is_some_and(|it| it.contains(&stop))
+    }
+
+    /// 117.3d If a player has priority and chooses not to take any actions, that player passes
+    ///        priority. [...] Then the next player in turn order receives priority.
+    ///
+    /// 117.4. If all players pass in succession (that is, if all players pass without taking any
+    ///        actions in between passing), the spell or ability on top of the stack resolves or, if
+    ///        the stack is empty, the phase or step ends.
+    fn pass_priority(&mut self, player: PlayerId) -> Result<(), EngineError> {
+        self.stats.record_priority_passes(1);
+        self.passes += 1;
+        let players = self.apnap_order()?;
+        if self.passes < players.len() {
+            let index = players
+                .iter()
+                .position(|&it| it == player)
+                .unwrap_or_default();
+            self.priority = Some(players[(index + 1) % players.len()]);
+            return Ok(());
+        }
+        self.passes = 0;
+        self.resolve_top_of_stack()
+    }
+
+    /// Resolves the spell or ability on top of the stack once the controller of a spell has made
+    /// the decisions it requires, or ends the step if the stack is empty.
+    pub(crate) fn resolve_top_of_stack(&mut self) -> Result<(), EngineError> {
+        let Some(spell) = self
+            .objects_on_stack()
+            .last()
+            .and_then(|&it| self.object(it))
+        else {
+            self.priority = None;
+            return Ok(());
+        };
+        if self.world.get::<StackedAbility>(spell).is_ok() {
+            self.resolve_stacked_ability(spell)?;
+            self.priority = Some(self.turn.active_player);
+            return Ok(());
+        }
+        let controller = self.world.get::<Owner>(spell).map(|it| it.0);
+        let choice = self.spell_option_choice(spell).or_else(|| {
+            controller
+                .ok()
+                .and_then(|controller| self.entry_choice(spell, controller))
+        });
+        if let Some(choice) = choice {
+            self.choice = Some(choice);
+            return Ok(());
+        }
+        let targets = self
+            .world
+            .get::<ChosenTargets>(spell)
+            .map(|it| it.0.clone())
+            .unwrap_or_default();
+        self.resolve_spell(spell, &targets)?;
+        // 117.3b The active player receives priority after a spell or ability (other than a mana
+        //        ability) resolves.
+        self.priority = Some(self.turn.active_player);
+        Ok(())
+    }
+
+    /// 601.2c The player announces their choice of an appropriate player, object, or zone for
+    ///        each target the spell requires. [...]
+    ///
+    /// Casts a spell choosing the specified targets, which it keeps until it resolves.
+    fn cast_spell_targeting(
+        &mut self,
+        player: PlayerId,
+        spell: Entity,
+        targets: &[Target],
+        x: u64,
+        modes: &[usize],
+    ) -> Result<(), EngineError> {
+        let card = self
+            .world
+            .get::<CardRef>(spell)
+            .map(|it| Arc::clone(&it.0))
+            .map_err(|_| EngineError::UnknownObject(spell))?;
+        let targets = self.chosen_targets(spell, player, &spell_effects(&card, modes), targets)?;
+        // The targets are chosen before the costs are paid, so copies of the spell get them too.
+        let _ = self.world.insert_one(spell, ChosenTargets(targets));
+        let cast = self.cast_spell_choosing(player, spell, modes, x, None);
+        if cast.is_err() {
+            let _ = self.world.remove_one::<ChosenTargets>(spell);
+        }
+        cast
+    }
+
+    /// Returns the permanents and players the targets refer to, or an error unless they are legal
+    /// targets for the effects that require them in order.
+    fn chosen_targets(
+        &self,
+        source: Entity,
+        controller: PlayerId,
+        effects: &[Effect],
+        targets: &[Target],
+    ) -> Result<Vec<PermanentOrPlayer>, EngineError> {
+        let targeted = effects
+            .iter()
+            .filter(|it| it.requires_target())
+            .collect::<Vec<_>>();
+        if targeted.len() != targets.len() {
+            return Err(EngineError::InvalidTarget(format!(
+                "{} targets are required but {} were chosen.",
+                targeted.len(),
+                targets.len()
+            )));
+        }
+        targeted
+            .into_iter()
+            .zip(targets)
+            .map(|(effect, &target)| {
+                let chosen = match target {
+                    Target::Player(player) => PermanentOrPlayer::Player(player),
+                    Target::Object(id) => PermanentOrPlayer::Permanent(self.entity_of(id)?),
+                };
+                if !self
+                    .target_candidates(source, controller, effect)
+                    .contains(&chosen)
+                {
+                    return Err(EngineError::InvalidTarget(format!(
+                        "{target:?} is not a legal target."
+                    )));
+                }
+                Ok(chosen)
+            })
+            .collect()
+    }
+
+    /// Returns each combination of legal targets for the effects that require them, in the order of
+    /// those effects. There is a single empty combination if no effect requires a target and none
+    /// if an effect has no legal target.
+    fn target_choices(
+        &self,
+        source: Entity,
+        controller: PlayerId,
+        effects: &[Effect],
+    ) -> Vec<Vec<Target>> {
+        effects.iter().filter(|it| it.requires_target()).fold(
+            vec![Vec::new()],
+            |choices, effect| {
+                let candidates = self
+                    .target_candidates(source, controller, effect)
+                    .into_iter()
+                    .filter_map(|it| match it {
+                        PermanentOrPlayer::Player(player) => Some(Target::Player(player)),
+                        PermanentOrPlayer::Permanent(entity) => {
+                            self.object_id(entity).map(Target::Object)
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                choices
+                    .iter()
+                    .flat_map(|choice| {
+                        candidates
+                            .iter()
+                            .map(move |&it| [choice.as_slice(), &[it]].concat())
+                    })
+                    .collect()
+            },
+        )
+    }
+
+    /// Returns the permanents and players the effect of the specified source may target right
+    /// now, ordered by their ids.
+    fn target_candidates(
+        &self,
+        source: Entity,
+        controller: PlayerId,
+        effect: &Effect,
+    ) -> Vec<PermanentOrPlayer> {
+        let Some(requirement) = effect.target_requirement() else {
+            return Vec::new();
+        };
+        let objects = match effect {
+            Effect::DestroyTarget(filter) => self.permanents_matching(filter, controller),
+            Effect::ReturnFromGraveyardToHand(filter) => self.graveyard_cards(controller, filter),
+            _ => self
+                .objects_in(Zone::Battlefield, |_| true)
+                .into_iter()
+                .filter_map(|it| self.object(it))
+                .collect(),
+        };
+        objects
+            .into_iter()
+            .map(PermanentOrPlayer::Permanent)
+            .chain(
+                self.players
+                    .iter()
+                    .map(|it| PermanentOrPlayer::Player(it.id)),
+            )
+            .filter(|&it| self.is_legal_target(source, requirement, it))
+            .collect()
+    }
+
+    /// Returns the creatures of the specified player that could attack the defending player.
+    fn possible_attackers(&self, player: PlayerId) -> Vec<Entity> {
+        let defender = self.apnap_order().ok().and_then(|it| it.get(1).copied());
+        self.creatures_controlled_by(player)
+            .into_iter()
+            .filter_map(|it| self.object(it))
+            .filter(|&it| {
+                self.can_attack(it) && self.disobeyed_attack_restriction(it, defender).is_none()
+            })
+            .collect()
+    }
+
+    /// Returns the pairs of a creature of the specified player and an attacking creature it could
+    /// block on its own.
+    fn possible_blocks(&self, player: PlayerId) -> Vec<(Entity, Entity)> {
+        let attackers = self.combat.attackers().collect::<Vec<_>>();
+        self.creatures_controlled_by(player)
+            .into_iter()
+            .filter_map(|it| self.object(it))
+            .flat_map(|blocker| attackers.iter().map(move |&attacker| (blocker, attacker)))
+            .filter(|&(blocker, attacker)| self.ensure_can_block(player, blocker, attacker).is_ok())
+            .collect()
+    }
+
+    /// Returns the declarations of attackers offered to the active player: the creatures that
+    /// must attack on their own, along with each other creature and along with all of them.
+    fn attack_choices(&self, player: PlayerId) -> Vec<GameAction> {
+        let possible = self.possible_attackers(player);
+        let (required, optional): (Vec<_>, Vec<_>) = possible.iter().partition(|&&it| {
+            self.attack_constraints(it)
+                .iter()
+                .any(|it| it.is_requirement())
+        });
+        let mut choices = vec![required.clone()];
+        choices.extend(
+            optional
+                .iter()
+                .map(|&it| [required.as_slice(), &[it]].concat()),
+        );
+        choices.push(possible);
+        choices.dedup();
+        choices
+            .into_iter()
+            .map(|attackers| GameAction::DeclareAttackers {
+                attackers: attackers
+                    .into_iter()
+                    .filter_map(|it| self.object_id(it))
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// Returns the actions the specified player can take while they have priority: playing each
+    /// land, casting each spell with each combination of targets and value of X, activating each
+    /// ability that isn't a mana ability and passing priority.
+    fn priority_actions(&self, player: PlayerId) -> Result<Vec<GameAction>, EngineError> {
+        let mut actions = Vec::new();
+        if self.has_sorcery_timing(player) && self.turn.tracker.lands_played_by(player) < 1 {
+            let lands = self.objects_in(Zone::Hand(player), |it| {
+                self.has_card_type(it, CardType::Land)
+            });
+            actions.extend(lands.into_iter().map(|card| GameAction::PlayLand { card }));
+        }
+        for option in self.casting_options(player)? {
+            let spell = self.entity_of(option.spell)?;
+            let card = self
+                .world
+                .get::<CardRef>(spell)
+                .map(|it| Arc::clone(&it.0))
+                .map_err(|_| EngineError::UnknownObject(spell))?;
+            let max_x = if option.cost.0.contains(&ManaSymbol::Variable) {
+                self.max_x(player, &option.cost, Some(spell))?
+                    .unwrap_or_default()
+            } else {
+                0
+            };
+            let effects = spell_effects(&card, &option.modes);
+            for targets in self.target_choices(spell, player, &effects) {
+                actions.extend((0..=max_x).map(|x| GameAction::CastSpell {
+                    spell: option.spell,
+                    targets: targets.clone(),
+                    x,
+                    modes: option.modes.clone(),
+                }));
+            }
+        }
+        let mut sources = self
+            .world
+            .query::<(&ObjectId, &ActivatedAbilities)>()
+            .iter()
+            .filter(|&(entity, _)| {
+                self.is_on_battlefield(entity)
+                    && self.controller(entity) == Some(player)
+                    && self.has_printed_abilities(entity)
+            })
+            .map(|(entity, (&id, abilities))| (id, entity, abilities.0.clone()))
+            .collect::<Vec<_>>();
+        sources.sort_by_key(|(id, ..)| *id);
+        for (id, source, abilities) in sources {
+            for (index, ability) in abilities.iter().enumerate() {
+                let chosen = ChosenCostObjects::default();
+                if ability.is_mana_ability()
+                    || self
+                        .ensure_can_pay_activation_cost(player, source, &ability.cost, &chosen)
+                        .is_err()
+                {
+                    continue;
+                }
+                actions.extend(
+                    self.target_choices(source, player, &ability.effects)
+                        .into_iter()
+                        .map(|targets| GameAction::ActivateAbility {
+                            source: id,
+                            index,
+                            targets,
+                        }),
+                );
+            }
+        }
+        actions.push(GameAction::PassPriority);
+        Ok(actions)
+    }
+
+    /// Returns the entity of the object with the specified id, or an error if there is none.
+    fn entity_of(&self, id: ObjectId) -> Result<Entity, EngineError> {
+        self.object(id).ok_or_else(|| {
+            EngineError::IllegalAction(format!("There is no object with id {}.", id.0))
+        })
+    }
+
+    /// 500.4. When a step or phase ends, any unused mana left in a player’s mana pool empties.
+    ///        This turn-based action doesn’t use the stack.
+    fn empty_mana_pools(&mut self) -> Result<(), EngineError> {
+        let mut lost = Vec::new();
+        for player in &mut self.players {
+            lost.push((player.id, player.mana_pool.mana().len() as u64));
+            player.mana_pool.empty();
+        }
+        if self.mana_burn {
+            for (player, amount) in lost.into_iter().filter(|(_, it)| *it > 0) {
+                self.lose_life(player, amount)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves the current turn to the specified step and checks for abilities that trigger at its
+    /// beginning.
+    pub(crate) fn begin_step(&mut self, step: Step) -> Result<(), EngineError> {
+        self.ensure_game_in_progress()?;
+        self.ensure_no_pending_triggers()?;
+
+        if self.turn.step == Step::EndOfCombat {
+            self.end_combat();
+        }
+        self.empty_mana_pools()?;
+        self.priority = None;
+        self.passes = 0;
+        self.turn.step = step;
+        #[cfg(feature = "tracing")]
+        let _span = self.spans.begin_step(step);
+        log::trace!("Step {step:?} of turn {} begins.", self.turn.number);
+        self.events.push(GameEvent::StepBegan {
+            number: self.turn.number,
+            active_player: self.turn.active_player,
+            step,
+        });
+        // 504.1. First, the active player draws a card. This turn-based action doesn’t use the
+        //        stack.
+        //
+        // 103.8a In a two-player game, the player who plays first skips the draw step (see rule
+        //        504, “Draw Step”) of their first turn.
+        if step == Step::Draw && !(self.turn.number == 1 && self.players.len() == 2) {
+            self.draw_card(self.turn.active_player)?;
+        }
+        if step == Step::End {
+            self.return_linked_exiles(|it| it.condition == ReturnCondition::NextEndStep)?;
+        }
+        if step == Step::CombatDamage {
+            self.deal_combat_damage()?;
+        }
+        if step == Step::Cleanup {
+            self.discard_to_maximum_hand_size()?;
+        }
+        self.check_state_based_actions()?;
+        self.check_triggers(&TriggerEvent::BeginningOfStep(step));
+        Ok(())
+    }
+
+    /// Returns whether the object of a game event is the subject of a trigger condition.
+    fn subject_matches(
+        &self,
+        subject: &TriggerSubject,
+        source: Entity,
+        controller: PlayerId,
+        object: Entity,
+        object_controller: PlayerId,
+    ) -> bool {
+        match subject {
+            TriggerSubject::This => source == object,
+            TriggerSubject::Matching(filter) => {
+                self.source_filter_matches(source, filter, controller, object, object_controller)
+            }
+        }
+    }
+
+    /// Returns whether an object matches a filter of an ability of the specified source, which
+    /// may refer to the choices made as the source entered the battlefield.
+    fn source_filter_matches(
+        &self,
+        source: Entity,
+        filter: &ObjectFilter,
+        controller: PlayerId,
+        object: Entity,
+        object_controller: PlayerId,
+    ) -> bool {
+        let has_chosen_color = !filter.of_chosen_color
+            || self.world.get::<ChosenColor>(source).is_ok_and(|color| {
+                self.world
+                    .get::<ColorIdentity>(object)
+                    .is_ok_and(|it| it.contains(color.0))
+            });
+        has_chosen_color && self.filter_matches(filter, controller, object, object_controller)
+    }
+
+    /// Returns whether an object controlled by `object_controller` matches a filter from the
+    /// perspective of `controller`.
+    fn filter_matches(
+        &self,
+        filter: &ObjectFilter,
+        controller: PlayerId,
+        object: Entity,
+        object_controller: PlayerId,
+    ) -> bool {
+        self.type_line(object).is_some_and(|type_line| {
+            self.filter_matches_with_types(
+                filter,
+                controller,
+                object,
+                object_controller,
+                &type_line,
+            )
+        })
+    }
+
+    /// Returns whether an object matches a filter like [`Game::filter_matches`] if it had the
+    /// specified type line.
+    fn filter_matches_with_types(
+        &self,
+        filter: &ObjectFilter,
+        controller: PlayerId,
+        object: Entity,
+        object_controller: PlayerId,
+        type_line: &TypeLine,
+    ) -> bool {
+        let has_card_type = filter
+            .card_type
+            .is_none_or(|card_type| type_line.card_type.contains(&card_type))
+            && filter
+                .excluded_card_type
+                .is_none_or(|card_type| !type_line.card_type.contains(&card_type))
+            && filter
+                .excluded_supertype
+                .is_none_or(|supertype| !type_line.supertype.contains(&supertype));
+        let has_subtype = filter
+            .subtype
+            .is_none_or(|subtype| type_line.subtype.contains(&subtype));
+        let has_color = filter.color.is_none_or(|color| {
+            self.world
+                .get::<ColorIdentity>(object)
+                .is_ok_and(|it| it.contains(color))
+        });
+        let has_power = filter.power.is_none_or(|comparison| {
+            self.power_toughness(object)
+                .is_some_and(|(power, _)| comparison.matches(power))
+        });
+        has_card_type
+            && has_subtype
+            && has_color
+            && has_power
+            && (!filter.you_control || controller == object_controller)
+            && (!filter.opponent_controls || controller != object_controller)
+    }
+
+    /// Returns whether the type line of the specified object contains the card type.
+    fn has_card_type(&self, object: Entity, card_type: CardType) -> bool {
+        self.type_line(object)
+            .is_some_and(|it| it.card_type.contains(&card_type))
+    }
+
+    /// Returns whether the type line of the specified object contains the subtype.
+    fn has_subtype(&self, object: Entity, subtype: Subtype) -> bool {
+        self.type_line(object)
+            .is_some_and(|it| it.subtype.contains(&subtype))
+    }
+
+    /// Returns the current type line of the specified object, which differs from the one it is
+    /// printed with only while it is a permanent affected by type-changing effects.
+    pub(crate) fn type_line(&self, object: Entity) -> Option<TypeLine> {
+        let printed = self.world.get::<CardRef>(object).ok()?.0.type_line.clone();
+        if !self.is_on_battlefield(object) {
+            return Some(printed);
+        }
+        let effects = self.type_changing_effects();
+        if effects.is_empty() {
+            return Some(printed);
+        }
+        self.type_layer(effects).type_lines.remove(&object)
+    }
+
+    /// Returns the type-changing effects generated by the static abilities of all permanents.
+    fn type_changing_effects(&self) -> Vec<TypeChangingEffect> {
+        let mut sources = self
+            .world
+            .query::<(&Zone, &Controller, &Timestamp, &StaticAbilities)>()
+            .without::<PhasedOut>();
+        sources
+            .iter()
+            .filter(|(_, (zone, ..))| **zone == Zone::Battlefield)
+            .flat_map(|(source, (_, controller, timestamp, abilities))| {
+                abilities.0.iter().filter_map(move |ability| match ability {
+                    StaticAbility::ChangeTypes { affected, change } => Some(TypeChangingEffect {
+                        source,
+                        controller: controller.0,
+                        timestamp: *timestamp,
+                        affected: affected.clone(),
+                        change: change.clone(),
+                    }),
+                    _ => None,
+                })
+            })
+            .collect()
+    }
+
+    /// 613.1d Layer 4: Type-changing effects are applied. [...]
+    ///
+    /// Applies the type-changing effects to the type lines of all permanents.
+    fn type_layer(&self, effects: Vec<TypeChangingEffect>) -> TypeLayer {
+        let type_lines = self
+            .world
+            .query::<(&Zone, &CardRef)>()
+            .iter()
+            .filter(|(_, (zone, _))| **zone == Zone::Battlefield)
+            .map(|(entity, (_, card))| (entity, card.0.type_line.clone()))
+            .collect();
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            layer = 4,
+            effects = effects.len(),
+            "Continuous effects are applied."
+        );
+        TypeLayer::apply(type_lines, effects, &|effect, object, type_line| {
+            !self.is_phased_out(object)
+                && self.controller(object).is_some_and(|object_controller| {
+                    self.filter_matches_with_types(
+                        &effect.affected,
+                        effect.controller,
+                        object,
+                        object_controller,
+                        type_line,
+                    )
+                })
+        })
+    }
+
+    /// Returns the ids of all objects in the specified zone that satisfy the predicate, ordered by
+    /// their ids.
+    fn objects_in(&self, zone: Zone, predicate: impl Fn(Entity) -> bool) -> Vec<ObjectId> {
+        let mut objects = self.world.query::<(&ObjectId, &Zone)>();
+        let mut ids = objects
+            .iter()
+            .filter(|&(entity, (_, it))| {
+                *it == zone && !self.is_phased_out(entity) && predicate(entity)
+            })
+            .map(|(_, (id, _))| *id)
+            .collect::<Vec<_>>();
+        ids.sort();
+        ids
+    }
+
+    /// Returns whether the specified permanent is controlled by the player.
+    fn is_controlled_by(&self, object: Entity, player: PlayerId) -> bool {
+        self.world
+            .get::<Controller>(object)
+            .is_ok_and(|it| it.0 == player)
+    }
+
+    /// Returns the ids of all permanents with the specified card type.
+    pub(crate) fn permanents_of_type(&self, card_type: CardType) -> Vec<ObjectId> {
+        self.objects_in(Zone::Battlefield, |it| self.has_card_type(it, card_type))
+    }
+
+    /// Returns the ids of all permanents with the specified subtype.
+    pub(crate) fn permanents_with_subtype(&self, subtype: Subtype) -> Vec<ObjectId> {
+        self.objects_in(Zone::Battlefield, |it| self.has_subtype(it, subtype))
+    }
+
+    /// Returns the ids of all creatures controlled by the specified player.
+    pub(crate) fn creatures_controlled_by(&self, player: PlayerId) -> Vec<ObjectId> {
+        self.objects_in(Zone::Battlefield, |it| {
+            self.has_card_type(it, CardType::Creature) && self.is_controlled_by(it, player)
+        })
+    }
+
+    /// Returns the ids of all untapped lands controlled by the specified player.
+    pub(crate) fn untapped_lands(&self, player: PlayerId) -> Vec<ObjectId> {
+        self.objects_in(Zone::Battlefield, |it| {
+            self.has_card_type(it, CardType::Land)
+                && self.is_controlled_by(it, player)
+                && self.world.get::<Tapped>(it).is_err()
+        })
+    }
+
+    /// Returns the ids of all objects on the stack.
+    pub(crate) fn objects_on_stack(&self) -> Vec<ObjectId> {
+        self.objects_in(Zone::Stack, |_| true)
+    }
+
+    /// 608.2. If the object that’s resolving is an instant spell, a sorcery spell, or an ability,
+    ///        its resolution may involve several steps. [...]
+    ///
+    /// Resolves the oldest pending trigger using the specified targets in order and returns whether
+    /// there was a trigger to resolve.
+    pub(crate) fn resolve_next_trigger(
+        &mut self,
+        targets: &[PermanentOrPlayer],
+    ) -> Result<bool, EngineError> {
+        if self.pending_triggers.is_empty() {
+            return Ok(false);
+        }
+        let trigger = self.pending_triggers.remove(0);
+        #[cfg(feature = "tracing")]
+        let _span = self.spans.action("resolve_trigger", trigger.controller);
+        if !self.intervening_if_holds(
+            trigger.intervening_if.as_ref(),
+            trigger.source,
+            trigger.controller,
+        ) {
+            log::trace!("An ability of {:?} does nothing.", trigger.source);
+            return Ok(true);
+        }
+        let description = self.describe_effects(
+            trigger.source,
+            trigger.controller,
+            &trigger.effects,
+            targets,
+        );
+        self.events.push(GameEvent::TriggeredAbilityResolved {
+            controller: trigger.controller,
+            source: ObjectSnapshot::of(&self.world, trigger.source),
+            description,
+        });
+        self.option_decisions = trigger.decisions;
+        let resolved = self.resolve_effects(
+            trigger.source,
+            trigger.controller,
+            &trigger.effects,
+            targets,
+        );
+        self.option_decisions.clear();
+        resolved?;
+        self.check_state_based_actions()?;
+        Ok(true)
+    }
+
+    /// 602.2. To activate an ability is to put it onto the stack and pay its costs, so that it will
+    ///        eventually resolve and have its effect. Only an object’s controller (or its owner, if
+    ///        it doesn’t have a controller) can activate its activated ability unless the object
+    ///        specifically says otherwise. [...]
+    ///
+    /// Activates the activated ability with the specified index of an object on behalf of the
+    /// specified player. The ability is put onto the stack with the specified targets in order and
+    /// resolves once all players have passed priority, unless it's a mana ability, which resolves
+    /// immediately.
+    pub(crate) fn activate_ability(
+        &mut self,
+        player: PlayerId,
+        source: Entity,
+        index: usize,
+        targets: &[PermanentOrPlayer],
+    ) -> Result<(), EngineError> {
+        self.activate_ability_sacrificing(player, source, index, targets, &[])
+    }
+
+    /// Activates an ability like [`Game::activate_ability`] and sacrifices the specified
+    /// permanents to pay the sacrifice costs of the ability in order.
+    pub(crate) fn activate_ability_sacrificing(
+        &mut self,
+        player: PlayerId,
+        source: Entity,
+        index: usize,
+        targets: &[PermanentOrPlayer],
+        sacrificed: &[Entity],
+    ) -> Result<(), EngineError> {
+        let chosen = ChosenCostObjects {
+            sacrificed,
+            ..ChosenCostObjects::default()
+        };
+        self.activate_ability_paying(player, source, index, targets, &chosen)
+    }
+
+    /// Activates an ability like [`Game::activate_ability`] and discards the specified cards to pay
+    /// the discard costs of the ability in order.
+    pub(crate) fn activate_ability_discarding(
+        &mut self,
+        player: PlayerId,
+        source: Entity,
+        index: usize,
+        targets: &[PermanentOrPlayer],
+        discarded: &[Entity],
+    ) -> Result<(), EngineError> {
+        let chosen = ChosenCostObjects {
+            discarded,
+            ..ChosenCostObjects::default()
+        };
+        self.activate_ability_paying(player, source, index, targets, &chosen)
+    }
+
+    /// Activates an ability like [`Game::activate_ability`] and exiles the specified cards to pay
+    /// the exile costs of the ability in order.
+    pub(crate) fn activate_ability_exiling(
+        &mut self,
+        player: PlayerId,
+        source: Entity,
+        index: usize,
+        targets: &[PermanentOrPlayer],
+        exiled: &[Entity],
+    ) -> Result<(), EngineError> {
+        let chosen = ChosenCostObjects {
+            exiled,
+            ..ChosenCostObjects::default()
+        };
+        self.activate_ability_paying(player, source, index, targets, &chosen)
+    }
+
+    /// Activates an ability, sacrificing, discarding and exiling the specified objects to pay the
+    /// costs of the ability that require choosing them.
+    ///
+    /// The whole cost is validated before anything is paid, so paying the components one after
+    /// another can't fail halfway through.
+    fn activate_ability_paying(
+        &mut self,
+        player: PlayerId,
+        source: Entity,
+        index: usize,
+        targets: &[PermanentOrPlayer],
+        chosen: &ChosenCostObjects,
+    ) -> Result<(), EngineError> {
+        #[cfg(feature = "tracing")]
+        let _span = self.spans.action("activate_ability", player);
+        self.player(player)?;
+        if !self.world.contains(source) {
+            return Err(EngineError::UnknownObject(source));
+        }
+        if self.world.get::<Controller>(source).map(|it| it.0).ok() != Some(player) {
+            return Err(EngineError::IllegalAction(format!(
+                "Player with id {} doesn't control {source:?}.",
+                player.0
+            )));
+        }
+        let ability = self
+            .world
+            .get::<ActivatedAbilities>(source)
+            .ok()
+            .filter(|_| self.has_printed_abilities(source))
+            .and_then(|it| it.0.get(index).cloned())
+            .ok_or_else(|| {
+                EngineError::IllegalAction(format!(
+                    "{source:?} has no activated ability with index {index}."
+                ))
+            })?;
+
+        let mut mana_payment =
+            self.ensure_can_pay_activation_cost(player, source, &ability.cost, chosen)?;
+        let mut sacrificed = chosen.sacrificed.iter();
+        let mut discarded = chosen.discarded.iter();
+        let mut exiled = chosen.exiled.iter();
+        for cost in &ability.cost {
+            match cost {
+                ActivationCost::Tap => self
+                    .world
+                    .insert_one(source, Tapped)
+                    .map_err(|_| EngineError::UnknownObject(source))?,
+                ActivationCost::Pay(cost) => self.pay_cost(player, cost)?,
+                // All mana components are paid at once by the first of them.
+                ActivationCost::Mana(_) => {
+                    if let Some((cost, plan)) = mana_payment.take() {
+                        self.pay_mana_cost(player, &cost, None, Some(plan))?;
+                    }
+                }
+                ActivationCost::Sacrifice(_) => {
+                    if let Some(&permanent) = sacrificed.next() {
+                        self.sacrifice(player, permanent)?;
+                    }
+                }
+                ActivationCost::SacrificeThis => self.sacrifice(player, source)?,
+                ActivationCost::Discard => {
+                    if let Some(&card) = discarded.next() {
+                        self.discard(player, card)?;
+                    }
+                }
+                ActivationCost::PayLife(amount) => self.lose_life(player, *amount)?,
+                ActivationCost::ExileFromHand | ActivationCost::ExileFromGraveyard => {
+                    if let Some(&card) = exiled.next() {
+                        self.move_object(card, Zone::Exile)?;
+                    }
+                }
+                ActivationCost::RemoveCounters(kind, amount) => {
+                    self.remove_counters(PermanentOrPlayer::Permanent(source), kind, *amount)?;
+                }
+            }
+        }
+
+        log::trace!(
+            "Player with id {} activates ability {index} of {source:?}.",
+            player.0
+        );
+        let description = self.describe_effects(source, player, &ability.effects, targets);
+        self.events.push(GameEvent::AbilityActivated {
+            player,
+            source: ObjectSnapshot::of(&self.world, source),
+            description,
+        });
+        // 605.3b An activated mana ability doesn’t go on the stack, so it can’t be targeted,
+        //        countered, or otherwise responded to. Rather, it resolves immediately after it is
+        //        activated. [...]
+        if ability.is_mana_ability() {
+            self.resolve_effects(source, player, &ability.effects, targets)?;
+            return self.check_state_based_actions();
+        }
+        let stacked = self.world.spawn((
+            Object,
+            Controller(player),
+            StackedAbility {
+                source,
+                effects: ability.effects,
+            },
+            ChosenTargets(targets.to_vec()),
+        ));
+        self.issue_object_id(stacked)?;
+        self.place_object(stacked, Zone::Stack)
+    }
+
+    /// 608.2. If the object that’s resolving is an instant spell, a sorcery spell, or an ability,
+    ///        its resolution may involve several steps. [...]
+    ///
+    /// Resolves an activated ability on the stack using the targets chosen as it was activated,
+    /// after which it ceases to exist.
+    fn resolve_stacked_ability(&mut self, ability: Entity) -> Result<(), EngineError> {
+        let controller = self
+            .world
+            .get::<Controller>(ability)
+            .map(|it| it.0)
+            .map_err(|_| EngineError::UnknownObject(ability))?;
+        let (source, effects) = self
+            .world
+            .get::<StackedAbility>(ability)
+            .map(|it| (it.source, it.effects.clone()))
+            .map_err(|_| EngineError::UnknownObject(ability))?;
+        let targets = self
+            .world
+            .get::<ChosenTargets>(ability)
+            .map(|it| it.0.clone())
+            .unwrap_or_default();
+        log::trace!("{ability:?} resolves.");
+        self.resolve_effects(source, controller, &effects, &targets)?;
+        self.despawn_object(ability)?;
+        self.check_state_based_actions()
+    }
+
+    /// 602.2b [...] The player must pay the total cost in any order. Partial payments are not
+    ///        allowed. Unpayable costs can’t be paid.
+    ///
+    /// Returns an error unless the player can pay the whole activation cost, sacrificing,
+    /// discarding and exiling the specified objects for its sacrifice, discard and exile costs in
+    /// order. Nothing is paid either way. The components are checked together, so the mana can't
+    /// be paid by tapping or sacrificing a permanent that another component taps or sacrifices and
+    /// the life and counters of all components have to be paid at once. Returns the total mana
+    /// cost of the mana components and the plan to pay it with, if there are any.
+    fn ensure_can_pay_activation_cost(
+        &self,
+        player: PlayerId,
+        source: Entity,
+        costs: &[ActivationCost],
+        chosen: &ChosenCostObjects,
+    ) -> Result<Option<(ManaCost, PaymentPlan)>, EngineError> {
+        self.ensure_can_pay_with_chosen_objects(player, costs, chosen)?;
+        let payment_failed = |cost: &ActivationCost| {
+            EngineError::PaymentFailed(format!("Player with id {} can't pay {cost:?}.", player.0))
+        };
+        for cost in costs {
+            let can_pay = match cost {
+                ActivationCost::Tap => {
+                    self.world.get::<Tapped>(source).is_err() && !self.is_summoning_sick(source)
+                }
+                ActivationCost::Pay(cost) => self.can_pay_cost(player, cost)?,
+                ActivationCost::SacrificeThis => {
+                    !chosen.sacrificed.contains(&source)
+                        && self.ensure_can_sacrifice(player, source).is_ok()
+                }
+                ActivationCost::Mana(_)
+                | ActivationCost::Sacrifice(_)
+                | ActivationCost::Discard
+                | ActivationCost::PayLife(_)
+                | ActivationCost::ExileFromHand
+                | ActivationCost::ExileFromGraveyard
+                | ActivationCost::RemoveCounters(..) => true,
+            };
+            if !can_pay {
+                return Err(payment_failed(cost));
+            }
+        }
+
+        // 119.4. If a cost or effect allows a player to pay an amount of life greater than 0, the
+        //        player may do so only if their life total is greater than or equal to the amount
+        //        of the payment. [...]
+        let life = costs
+            .iter()
+            .map(|it| match it {
+                ActivationCost::PayLife(amount) => *amount,
+                _ => 0,
+            })
+            .sum::<u64>();
+        if life > 0 && self.player(player)?.life < life as i64 {
+            return Err(payment_failed(&ActivationCost::PayLife(life)));
+        }
+        let mut counters = Vec::<(&CounterKind, u64)>::new();
+        for cost in costs {
+            if let ActivationCost::RemoveCounters(kind, amount) = cost {
+                match counters.iter_mut().find(|(it, _)| *it == kind) {
+                    Some((_, total)) => *total += amount,
+                    None => counters.push((kind, *amount)),
+                }
+            }
+        }
+        for (kind, amount) in counters {
+            if self.counters(PermanentOrPlayer::Permanent(source), kind)? < amount {
+                return Err(payment_failed(&ActivationCost::RemoveCounters(
+                    kind.clone(),
+                    amount,
+                )));
+            }
+        }
+        self.plan_activation_mana_payment(player, source, costs, chosen.sacrificed)
+    }
+
+    /// Returns an error unless the objects chosen to pay the sacrifice, discard and exile
+    /// components of an activation cost can pay them, each object paying a single component.
+    fn ensure_can_pay_with_chosen_objects(
+        &self,
+        player: PlayerId,
+        costs: &[ActivationCost],
+        chosen: &ChosenCostObjects,
+    ) -> Result<(), EngineError> {
+        let ChosenCostObjects {
+            sacrificed,
+            discarded,
+            exiled,
+        } = *chosen;
+        let filters = costs
+            .iter()
+            .filter_map(|it| match it {
+                ActivationCost::Sacrifice(filter) => Some(filter),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if filters.len() != sacrificed.len() {
+            return Err(EngineError::IllegalAction(format!(
+                "Expected {} permanents to sacrifice but got {}.",
+                filters.len(),
+                sacrificed.len()
+            )));
+        }
+        for (index, (filter, &permanent)) in filters.into_iter().zip(sacrificed).enumerate() {
+            self.ensure_can_sacrifice(player, permanent)?;
+            if sacrificed[..index].contains(&permanent)
+                || !self.filter_matches(filter, player, permanent, player)
+            {
+                return Err(EngineError::PaymentFailed(format!(
+                    "{permanent:?} can't be sacrificed to pay {filter:?}."
+                )));
+            }
+        }
+        let discard_costs = costs
+            .iter()
+            .filter(|it| matches!(it, ActivationCost::Discard))
+            .count();
+        if discard_costs != discarded.len() {
+            return Err(EngineError::IllegalAction(format!(
+                "Expected {discard_costs} cards to discard but got {}.",
+                discarded.len()
+            )));
+        }
+        for (index, &card) in discarded.iter().enumerate() {
+            if discarded[..index].contains(&card)
+                || self.zones.zone_of(card) != Some(Zone::Hand(player))
+            {
+                return Err(EngineError::PaymentFailed(format!(
+                    "{card:?} can't be discarded as it isn't in the hand of player with id {}.",
+                    player.0
+                )));
+            }
+        }
+        let exile_zones = costs
+            .iter()
+            .filter_map(|it| match it {
+                ActivationCost::ExileFromHand => Some(Zone::Hand(player)),
+                ActivationCost::ExileFromGraveyard => Some(Zone::Graveyard(player)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if exile_zones.len() != exiled.len() {
+            return Err(EngineError::IllegalAction(format!(
+                "Expected {} cards to exile but got {}.",
+                exile_zones.len(),
+                exiled.len()
+            )));
+        }
+        for (index, (zone, &card)) in exile_zones.into_iter().zip(exiled).enumerate() {
+            if exiled[..index].contains(&card)
+                || discarded.contains(&card)
+                || self.zones.zone_of(card) != Some(zone)
+            {
+                return Err(EngineError::PaymentFailed(format!(
+                    "{card:?} can't be exiled as it isn't in {zone:?}."
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the total mana cost of the mana components of an activation cost and the plan to
+    /// pay it with at once, or `None` if it has none. The source can't pay for the mana if the cost
+    /// taps or sacrifices it, and neither can the permanents sacrificed to pay it, regardless of
+    /// the order of the components.
+    fn plan_activation_mana_payment(
+        &self,
+        player: PlayerId,
+        source: Entity,
+        costs: &[ActivationCost],
+        sacrificed: &[Entity],
+    ) -> Result<Option<(ManaCost, PaymentPlan)>, EngineError> {
+        let mana_costs = costs
+            .iter()
+            .filter_map(|it| match it {
+                ActivationCost::Mana(cost) => Some(cost),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if mana_costs.is_empty() {
+            return Ok(None);
+        }
+        let cost = ManaCost(mana_costs.iter().flat_map(|it| it.0.clone()).collect());
+        let source_is_used = costs
+            .iter()
+            .any(|it| matches!(it, ActivationCost::Tap | ActivationCost::SacrificeThis));
+        let sources = self
+            .mana_sources(player, None)?
+            .into_iter()
+            .filter(|it| match it {
+                ManaSource::Permanent { entity, .. } => {
+                    !(sacrificed.contains(entity) || source_is_used && *entity == source)
+                }
+                _ => true,
+            })
+            .collect::<Vec<_>>();
+        match payment::plan_payment(&cost, &sources) {
+            Some(plan) => Ok(Some((cost, plan))),
+            None => Err(EngineError::PaymentFailed(format!(
+                "Player with id {} can't pay {cost}.",
+                player.0
+            ))),
+        }
+    }
+
+    /// 608.2c The controller of the spell or ability follows its instructions in the order
+    ///        written. [...]
+    fn resolve_effects(
+        &mut self,
+        source: Entity,
+        controller: PlayerId,
+        effects: &[Effect],
+        targets: &[PermanentOrPlayer],
+    ) -> Result<(), EngineError> {
+        let mut targets = targets.iter();
+        let mut next_target = || {
+            targets
+                .next()
+                .copied()
+                .ok_or_else(|| EngineError::InvalidTarget("A target is missing.".into()))
+        };
+        for effect in effects {
+            match *effect {
+                Effect::GainLife(amount) => self.gain_life(controller, amount)?,
+                Effect::LoseLife(amount) => self.lose_life(controller, amount)?,
+                Effect::DrawCards(ref amount) => {
+                    for _ in 0..self.quantity(amount, source, controller)? {
+                        self.draw_card(controller)?;
+                    }
+                }
+                Effect::ShuffleLibrary => self.shuffle_library(controller)?,
+                Effect::DealDamage(_)
+                | Effect::DestroyTarget(_)
+                | Effect::PhaseOut
+                | Effect::TapAndStun(_)
+                | Effect::LoseAllAbilitiesUntilEndOfTurn
+                | Effect::ReturnFromGraveyardToHand(_)
+                | Effect::ExileGraveyard => {
+                    self.resolve_targeted_effect(source, controller, effect, next_target()?)?;
+                }
+                Effect::AddMana(mana) => self.player_mut(controller)?.mana_pool.add(mana),
+                Effect::PumpUntilEndOfTurn { power, toughness } => {
+                    let kind = EffectKind::ModifyPowerToughness {
+                        target: source,
+                        power,
+                        toughness,
+                    };
+                    self.add_effect_until_end_of_turn(source, controller, kind);
+                }
+                Effect::DestroyAll(ref filter) => self.destroy_all(filter, controller)?,
+                Effect::ExileAll(ref filter) => self.exile_all(filter, controller)?,
+                Effect::DamageEach(ref filter, amount) => {
+                    self.damage_each(source, filter, controller, amount)?;
+                }
+                Effect::Blink => {
+                    if self.is_on_battlefield(source) {
+                        self.move_object(source, Zone::Exile)?;
+                        self.move_object(source, Zone::Battlefield)?;
+                    }
+                }
+                Effect::GetCounters(ref kind, amount) => {
+                    self.add_counters(PermanentOrPlayer::Player(controller), kind.clone(), amount)?;
+                }
+                Effect::Investigate => {
+                    self.create_token(&PredefinedToken::Clue.card(), controller)?;
+                }
+                Effect::CreateTreasure(amount) => {
+                    self.create_predefined_tokens(PredefinedToken::Treasure, amount, controller)?;
+                }
+                Effect::CreateFood(amount) => {
+                    self.create_predefined_tokens(PredefinedToken::Food, amount, controller)?;
+                }
+                Effect::CreateBlood(amount) => {
+                    self.create_predefined_tokens(PredefinedToken::Blood, amount, controller)?;
+                }
+                Effect::Explore => {
+                    self.explore(source, &mut |_| false)?;
+                }
+                Effect::GainKeywordUntilEndOfTurn(ref filter, ref keyword) => {
+                    self.grant_keyword_until_end_of_turn(source, filter, controller, keyword);
+                }
+                Effect::WinGame => self.win_game(controller)?,
+                Effect::ShuffleGraveyardIntoLibrary => {
+                    self.shuffle_graveyard_into_library(controller)?;
+                }
+                Effect::SacrificeThis => {
+                    if self.is_on_battlefield(source) && self.controller(source) == Some(controller)
+                    {
+                        self.sacrifice(controller, source)?;
+                    }
+                }
+                Effect::SacrificeUnlessPay(ref cost) => {
+                    self.sacrifice_unless_paid(source, controller, cost)?;
+                }
+                Effect::May {
+                    ref effects,
+                    ref when_you_do,
+                } => self.resolve_optional_effects(source, controller, effects, when_you_do)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// 118.12. Some spells, activated abilities, and triggered abilities read, “[Do something]
+    ///         unless [a player does something else].” [...]
+    ///
+    /// Pays the mana cost on behalf of the controller if they decided to, or sacrifices the source
+    /// if they declined or can't pay it.
+    fn sacrifice_unless_paid(
+        &mut self,
+        source: Entity,
+        controller: PlayerId,
+        cost: &ManaCost,
+    ) -> Result<(), EngineError> {
+        let pays = !self.option_decisions.is_empty() && self.option_decisions.remove(0);
+        if !self.is_on_battlefield(source) || self.controller(source) != Some(controller) {
+            return Ok(());
+        }
+        let paid = pays
+            && match self.pay_mana_cost(controller, cost, None, None) {
+                Ok(()) => true,
+                Err(EngineError::PaymentFailed(_)) => false,
+                Err(error) => return Err(error),
+            };
+        log::debug!(
+            "Player with id {} decides {pays:?} to pay {cost} for {source:?} and pays {paid:?}.",
+            controller.0
+        );
+        self.events.push(GameEvent::OptionChosen {
+            player: controller,
+            source: ObjectSnapshot::of(&self.world, source),
+            accepted: paid,
+        });
+        if !paid {
+            self.sacrifice(controller, source)?;
+        }
+        Ok(())
+    }
+
+    /// 603.5. [...] The choice is made when the ability resolves. [...]
+    ///
+    /// 603.12. A resolving spell or ability may allow or instruct a player to take an action and
+    ///         create a triggered ability that triggers “when [a player] [does or doesn’t]” take
+    ///         that action or “when [something happens] this way.” These reflexive triggered
+    ///         abilities follow the rules for delayed triggered abilities (see rule 603.7), except
+    ///         that they’re checked immediately after being created and trigger based on whether
+    ///         the trigger event or events occurred earlier during the resolution of the spell or
+    ///         ability that created them.
+    ///
+    /// Lets the controller decide whether to take the optional action. If they do, the reflexive
+    /// triggered ability triggers and is resolved before the triggers that were already pending.
+    fn resolve_optional_effects(
+        &mut self,
+        source: Entity,
+        controller: PlayerId,
+        effects: &[Effect],
+        when_you_do: &[Effect],
+    ) -> Result<(), EngineError> {
+        let accepted = !self.option_decisions.is_empty() && self.option_decisions.remove(0);
+        log::debug!(
+            "Player with id {} decides {accepted:?} for the option of {source:?}.",
+            controller.0
+        );
+        self.events.push(GameEvent::OptionChosen {
+            player: controller,
+            source: ObjectSnapshot::of(&self.world, source),
+            accepted,
+        });
+        if !accepted {
+            return Ok(());
+        }
+        // The decisions that are left belong to the optional actions that follow, not to the ones
+        // among these effects.
+        let decisions = std::mem::take(&mut self.option_decisions);
+        let resolved = self.resolve_effects(source, controller, effects, &[]);
+        self.option_decisions = decisions;
+        resolved?;
+        if !when_you_do.is_empty() {
+            self.pending_triggers.insert(
+                0,
+                PendingTrigger {
+                    source,
+                    controller,
+                    intervening_if: None,
+                    effects: when_you_do.to_vec(),
+                    decisions: Vec::new(),
+                },
+            );
+        }
+        Ok(())
+    }
+
+    /// Resolves an effect that requires a target with the target chosen for it. Effects without
+    /// targets are ignored.
+    fn resolve_targeted_effect(
+        &mut self,
+        source: Entity,
+        controller: PlayerId,
+        effect: &Effect,
+        target: PermanentOrPlayer,
+    ) -> Result<(), EngineError> {
+        if let Some(requirement) = effect.target_requirement() {
+            if !self.is_legal_target(source, requirement, target) {
+                log::trace!("{target:?} is not a legal target of {source:?}.");
+                return Ok(());
+            }
+        }
+        match (effect, target) {
+            (Effect::DealDamage(amount), recipient) => {
+                let amount = self.quantity(amount, source, controller)?;
+                self.deal_damage(&Damage {
+                    source,
+                    recipient,
+                    amount,
+                    combat: false,
+                })?;
+            }
+            (Effect::DestroyTarget(filter), PermanentOrPlayer::Permanent(target)) => {
+                self.destroy_target(filter, controller, target)?;
+            }
+            (Effect::PhaseOut, PermanentOrPlayer::Permanent(target)) => self.phase_out(target)?,
+            (&Effect::TapAndStun(amount), target) => {
+                if let Some(target) = self.target_creature(target) {
+                    let _ = self.world.insert_one(target, Tapped);
+                    self.add_counters(
+                        PermanentOrPlayer::Permanent(target),
+                        CounterKind::Stun,
+                        amount,
+                    )?;
+                }
+            }
+            (Effect::LoseAllAbilitiesUntilEndOfTurn, target) => {
+                if let Some(target) = self.target_creature(target) {
+                    let kind = EffectKind::LoseAllAbilities { target };
+                    self.add_effect_until_end_of_turn(source, controller, kind);
+                }
+            }
+            (Effect::ReturnFromGraveyardToHand(filter), PermanentOrPlayer::Permanent(card))
+                if self.graveyard_cards(controller, filter).contains(&card) =>
+            {
+                self.move_object(card, Zone::Hand(controller))?;
+            }
+            (Effect::ExileGraveyard, PermanentOrPlayer::Player(player)) => {
+                self.exile_graveyard(player)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// 608.2b If the spell or ability specifies targets, it checks whether the targets are still
+    ///        legal. [...]
+    ///
+    /// 702.16b A permanent or player with protection can’t be targeted by spells with the stated
+    ///         quality and can’t be targeted by abilities from a source with the stated quality.
+    ///
+    /// Returns whether the specified permanent or player is a legal target for the requirement of
+    /// an effect of the specified source.
+    fn is_legal_target(
+        &self,
+        source: Entity,
+        requirement: TargetRequirement,
+        target: PermanentOrPlayer,
+    ) -> bool {
+        if self.is_protected_from(target, source) {
+            return false;
+        }
+        match (requirement, target) {
+            (TargetRequirement::AnyTarget, PermanentOrPlayer::Permanent(entity)) => {
+                self.is_on_battlefield(entity)
+                    && [CardType::Creature, CardType::Planeswalker, CardType::Battle]
+                        .into_iter()
+                        .any(|it| self.has_card_type(entity, it))
+            }
+            (TargetRequirement::Permanent, PermanentOrPlayer::Permanent(entity)) => {
+                self.is_on_battlefield(entity)
+            }
+            (TargetRequirement::Creature, target) => self.target_creature(target).is_some(),
+            (TargetRequirement::CardInGraveyard, PermanentOrPlayer::Permanent(entity)) => self
+                .world
+                .get::<Zone>(entity)
+                .is_ok_and(|it| matches!(*it, Zone::Graveyard(_))),
+            (
+                TargetRequirement::AnyTarget | TargetRequirement::Player,
+                PermanentOrPlayer::Player(player),
+            ) => self.player(player).is_ok(),
+            _ => false,
+        }
+    }
+
+    /// Returns the targeted creature if it is still a creature on the battlefield.
+    fn target_creature(&self, target: PermanentOrPlayer) -> Option<Entity> {
+        match target {
+            PermanentOrPlayer::Permanent(target)
+                if self.is_on_battlefield(target)
+                    && self.has_card_type(target, CardType::Creature) =>
+            {
+                Some(target)
+            }
+            _ => None,
+        }
+    }
+
+    /// Describes the effects of an ability of the specified source as they are about to be applied
+    /// with the specified targets. The names and numbers are filled in now, so the description
+    /// remains valid once the objects have left the game.
+    fn describe_effects(
+        &self,
+        source: Entity,
+        controller: PlayerId,
+        effects: &[Effect],
+        targets: &[PermanentOrPlayer],
+    ) -> Option<String> {
+        let name = |player| {
+            self.player(player)
+                .map_or_else(|_| "An unknown player".into(), |it| it.info.name.clone())
+        };
+        let targets = targets
+            .iter()
+            .map(|target| match *target {
+                PermanentOrPlayer::Permanent(entity) => {
+                    ObjectSnapshot::of(&self.world, entity).name
+                }
+                PermanentOrPlayer::Player(player) => name(player),
+            })
+            .collect::<Vec<_>>();
+        description::describe_effects(
+            effects,
+            &DescriptionContext {
+                source: &ObjectSnapshot::of(&self.world, source).name,
+                controller: &name(controller),
+                targets: &targets,
+                quantity: &|it: &Quantity| self.quantity(it, source, controller).ok(),
+            },
+        )
+    }
+
+    /// Determines the number an effect of the specified source refers to from the perspective of
+    /// its controller. Counters on a source that left the battlefield are taken from its last
+    /// known information.
+    fn quantity(
+        &self,
+        quantity: &Quantity,
+        source: Entity,
+        controller: PlayerId,
+    ) -> Result<u64, EngineError> {
+        match quantity {
+            Quantity::Fixed(amount) => Ok(*amount),
+            Quantity::CountersOnYou(kind) => {
+                self.counters(PermanentOrPlayer::Player(controller), kind)
+            }
+            Quantity::CountersOnThis(kind) => {
+                if self.is_on_battlefield(source) {
+                    return self.counters(PermanentOrPlayer::Permanent(source), kind);
+                }
+                Ok(self
+                    .previous_id(source)
+                    .and_then(|it| self.last_known_information(it))
+                    .and_then(|it| it.counters.iter().find(|(counter, _)| counter == kind))
+                    .map_or(0, |&(_, amount)| amount))
+            }
+            Quantity::Permanents(filter) => {
+                Ok(self.permanents_matching(filter, controller).len() as u64)
+            }
+            Quantity::Cards(zone, filter) => {
+                let zone = match zone {
+                    CountedZone::YourHand => Zone::Hand(controller),
+                    CountedZone::YourLibrary => Zone::Library(controller),
+                    CountedZone::YourGraveyard => Zone::Graveyard(controller),
+                    CountedZone::Exile => Zone::Exile,
+                };
+                Ok(self
+                    .world
+                    .query::<(&Zone, &Owner)>()
+                    .iter()
+                    .filter(|&(entity, (&it, owner))| {
+                        it == zone && self.filter_matches(filter, controller, entity, owner.0)
+                    })
+                    .count() as u64)
+            }
+            Quantity::YourLife => Ok(u64::try_from(self.player(controller)?.life).unwrap_or(0)),
+            Quantity::X => Ok(self.world.get::<ChosenX>(source).map_or(0, |it| it.0)),
+        }
+    }
+
+    /// 701.7a To destroy a permanent, move it from the battlefield to its owner’s graveyard.
+    ///
+    /// 608.2b If the spell or ability specifies targets, it checks whether the targets are still
+    ///        legal. [...]
+    fn destroy_target(
+        &mut self,
+        filter: &ObjectFilter,
+        controller: PlayerId,
+        target: Entity,
+    ) -> Result<(), EngineError> {
+        let Ok(target_controller) = self.world.get::<Controller>(target).map(|it| it.0) else {
+            return Ok(());
+        };
+        if !self.is_on_battlefield(target)
+            || !self.filter_matches(filter, controller, target, target_controller)
+        {
+            return Ok(());
+        }
+        self.destroy(target)
+    }
+
+    /// Returns the cards in the graveyard of the specified player that match the filter from their
+    /// perspective, in the order they were put there.
+    fn graveyard_cards(&self, player: PlayerId, filter: &ObjectFilter) -> Vec<Entity> {
+        let mut cards = self
+            .world
+            .query::<(&ObjectId, &Zone)>()
+            .iter()
+            .filter(|&(entity, (_, zone))| {
+                *zone == Zone::Graveyard(player)
+                    && self.filter_matches(filter, player, entity, player)
+            })
+            .map(|(entity, (id, _))| (*id, entity))
+            .collect::<Vec<_>>();
+        cards.sort_unstable();
+        cards.into_iter().map(|(_, it)| it).collect()
+    }
+
+    /// Exiles all cards from the graveyard of the specified player at the same time.
+    fn exile_graveyard(&mut self, player: PlayerId) -> Result<(), EngineError> {
+        let moves = self
+            .graveyard_cards(player, &ObjectFilter::default())
+            .into_iter()
+            .map(|it| (it, Zone::Exile))
+            .collect::<Vec<_>>();
+        self.move_objects_simultaneously(&moves)
+    }
+
+    /// Puts all cards from the graveyard of the specified player into their library and shuffles
+    /// it.
+    fn shuffle_graveyard_into_library(&mut self, player: PlayerId) -> Result<(), EngineError> {
+        let moves = self
+            .graveyard_cards(player, &ObjectFilter::default())
+            .into_iter()
+            .map(|it| (it, Zone::Library(player)))
+            .collect::<Vec<_>>();
+        self.move_objects_simultaneously(&moves)?;
+        self.shuffle_library(player)
+    }
+
+    /// Returns all permanents matching the filter from the perspective of the specified player,
+    /// ordered by their ids.
+    fn permanents_matching(&self, filter: &ObjectFilter, controller: PlayerId) -> Vec<Entity> {
+        let mut permanents = self
+            .world
+            .query::<(&ObjectId, &Zone, &Controller)>()
+            .without::<PhasedOut>()
+            .iter()
+            .filter(|&(entity, (_, zone, permanent_controller))| {
+                *zone == Zone::Battlefield
+                    && self.filter_matches(filter, controller, entity, permanent_controller.0)
+            })
+            .map(|(entity, (id, ..))| (*id, entity))
+            .collect::<Vec<_>>();
+        permanents.sort_unstable();
+        permanents.into_iter().map(|(_, it)| it).collect()
+    }
+
+    /// Grants the keyword ability to each permanent matching the filter until end of turn.
+    ///
+    /// 611.2c [...] a continuous effect from a resolving spell or ability that modifies the
+    ///        characteristics or changes the controller of any objects modifies only the objects
+    ///        on the battlefield at the time that continuous effect began. [...]
+    fn grant_keyword_until_end_of_turn(
+        &mut self,
+        source: Entity,
+        filter: &ObjectFilter,
+        controller: PlayerId,
+        keyword: &KeywordAbility,
+    ) {
+        for target in self.permanents_matching(filter, controller) {
+            let kind = EffectKind::AddKeyword {
+                target,
+                keyword: keyword.clone(),
+            };
+            self.add_effect_until_end_of_turn(source, controller, kind);
+        }
+    }
+
+    /// Destroys all permanents matching the filter at the same time. The effects replacing the
+    /// destruction of each permanent apply to it like to a single destroyed permanent, e.g. it
+    /// regenerates instead, but all others are put into a graveyard together.
+    fn destroy_all(
+        &mut self,
+        filter: &ObjectFilter,
+        controller: PlayerId,
+    ) -> Result<(), EngineError> {
+        let mut moves = Vec::new();
+        for permanent in self.permanents_matching(filter, controller) {
+            // 702.12b A permanent with indestructible can’t be destroyed. [...]
+            if self.has_keyword(permanent, &KeywordAbility::Indestructible)
+                || self.survives_destruction(permanent)?
+            {
+                continue;
+            }
+            let owner = self
+                .world
+                .get::<Owner>(permanent)
+                .map_err(|_| EngineError::UnknownObject(permanent))?
+                .0;
+            moves.push((permanent, Zone::Graveyard(owner)));
+        }
+        self.move_objects_simultaneously(&moves)
+    }
+
+    /// 406.1. The exile zone is essentially a holding area for objects. [...]
+    ///
+    /// Exiles all permanents matching the filter at the same time.
+    fn exile_all(
+        &mut self,
+        filter: &ObjectFilter,
+        controller: PlayerId,
+    ) -> Result<(), EngineError> {
+        let moves = self
+            .permanents_matching(filter, controller)
+            .into_iter()
+            .map(|it| (it, Zone::Exile))
+            .collect::<Vec<_>>();
+        self.move_objects_simultaneously(&moves)
+    }
+
+    /// Deals damage to each permanent matching the filter. The permanents are determined before
+    /// any damage is dealt.
+    fn damage_each(
+        &mut self,
+        source: Entity,
+        filter: &ObjectFilter,
+        controller: PlayerId,
+        amount: u64,
+    ) -> Result<(), EngineError> {
+        for permanent in self.permanents_matching(filter, controller) {
+            self.deal_damage(&Damage {
+                source,
+                recipient: PermanentOrPlayer::Permanent(permanent),
+                amount,
+                combat: false,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// 402.2. Each player has a maximum hand size, which is normally seven cards. [...]
+    ///
+    /// Returns the maximum hand size of the specified player after applying all continuous effects
+    /// or `None` if the player has no maximum hand size.
+    pub(crate) fn maximum_hand_size(&self, player: PlayerId) -> Result<Option<u64>, EngineError> {
+        let mut size = self.player(player)?.maximum_hand_size as i64;
+        for (_, effect) in self.effects.iter() {
+            match effect.kind {
+                EffectKind::ModifyMaximumHandSize {
+                    player: affected,
+                    amount,
+                } if affected == player => size += amount,
+                EffectKind::NoMaximumHandSize { player: affected } if affected == player => {
+                    return Ok(None);
+                }
+                _ => {}
+            }
+        }
+        let unlimited = self.any_static_ability(|controller, ability| {
+            controller == player && *ability == StaticAbility::NoMaximumHandSize
+        });
+        Ok((!unlimited).then_some(u64::try_from(size).unwrap_or_default()))
+    }
+
+    /// Returns whether a static ability of a permanent on the battlefield satisfies the predicate,
+    /// which is given the controller of the permanent along with the ability.
+    fn any_static_ability(&self, predicate: impl Fn(PlayerId, &StaticAbility) -> bool) -> bool {
+        let mut sources = self
+            .world
+            .query::<(&Zone, &Controller, &StaticAbilities)>()
+            .without::<PhasedOut>();
+        sources
+            .iter()
+            .any(|(source, (&zone, controller, abilities))| {
+                zone == Zone::Battlefield
+                    && self.has_printed_abilities(source)
+                    && abilities.0.iter().any(|it| predicate(controller.0, it))
+            })
+    }
+
+    /// 701.8a To discard a card, move it from its owner’s hand to that player’s graveyard.
+    pub(crate) fn discard(&mut self, player: PlayerId, card: Entity) -> Result<(), EngineError> {
+        self.player(player)?;
+        if self.zones.zone_of(card) != Some(Zone::Hand(player)) {
+            return Err(EngineError::IllegalAction(format!(
+                "Player with id {} can't discard {card:?} as it isn't in their hand.",
+                player.0
+            )));
+        }
+        log::trace!("Player with id {} discards {card:?}.", player.0);
+        self.events.push(GameEvent::Discarded {
+            player,
+            object: ObjectSnapshot::of(&self.world, card),
+        });
+        self.move_object(card, Zone::Graveyard(player))
+    }
+
+    /// 514.1. First, if the active player’s hand contains more cards than their maximum hand size
+    ///        (normally seven), they discard enough cards to reduce their hand size to that number.
+    ///        This turn-based action doesn’t use the stack.
+    ///
+    /// The discard chooser chooses the cards unless the player discards a single card or their
+    /// whole hand and lets the engine make such trivial decisions, in which case the cards that
+    /// were put into their hand most recently are discarded.
+    fn discard_to_maximum_hand_size(&mut self) -> Result<(), EngineError> {
+        let player = self.turn.active_player;
+        let Some(maximum) = self.maximum_hand_size(player)? else {
+            return Ok(());
+        };
+        let mut hand = self
+            .world
+            .query::<(&Zone, &Timestamp)>()
+            .iter()
+            .filter(|(_, (&zone, _))| zone == Zone::Hand(player))
+            .map(|(entity, (_, &timestamp))| (timestamp, entity))
+            .collect::<Vec<_>>();
+        hand.sort_unstable_by(|first, second| second.cmp(first));
+        let hand = hand.into_iter().map(|(_, it)| it).collect::<Vec<_>>();
+        let excess = hand.len().saturating_sub(maximum as usize);
+        if excess == 0 {
+            return Ok(());
+        }
+
+        let automatic =
+            (excess == 1 || excess == hand.len()) && self.player(player)?.auto_choose_trivial;
+        let discarded = match self.discard_chooser.as_mut() {
+            Some(choose) if !automatic => choose(player, &hand, excess),
+            _ => hand[..excess].to_vec(),
+        };
+        let distinct = discarded.iter().collect::<HashSet<_>>();
+        if discarded.len() != excess
+            || distinct.len() != excess
+            || discarded.iter().any(|it| !hand.contains(it))
+        {
+            return Err(EngineError::IllegalAction(format!(
+                "Player with id {} has to discard {excess} different cards from their hand.",
+                player.0
+            )));
+        }
+        log::debug!(
+            "Player with id {} discards {excess} cards to their maximum hand size.",
+            player.0
+        );
+        self.events.push(GameEvent::DiscardsChosen {
+            player,
+            amount: excess as u64,
+            automatic,
+        });
+        for card in discarded {
+            self.discard(player, card)?;
+        }
+        Ok(())
+    }
+
+    /// 701.15a If the effect of a resolving spell or ability regenerates a permanent, it creates a
+    ///         replacement effect that protects the permanent the next time it would be destroyed
+    ///         this turn. In this case, “Regenerate [permanent]” means “The next time [permanent]
+    ///         would be destroyed this turn, instead remove all damage marked on it and its
+    ///         controller taps it. If it’s an attacking or blocking creature, remove it from
+    ///         combat.”
+    pub(crate) fn create_regeneration_shield(&mut self, entity: Entity) -> Result<(), EngineError> {
+        if !self.is_on_battlefield(entity) {
+            return Err(EngineError::IllegalAction(format!(
+                "{entity:?} is not on the battlefield."
+            )));
+        }
+        let shields = self
+            .world
+            .get::<RegenerationShields>(entity)
+            .map_or(0, |it| it.0);
+        self.world
+            .insert_one(entity, RegenerationShields(shields + 1))
+            .map_err(|_| EngineError::UnknownObject(entity))
+    }
+
+    /// 701.17a To sacrifice a permanent, its controller moves it from the battlefield directly to
+    ///         its owner’s graveyard. A player can’t sacrifice something that isn’t a permanent,
+    ///         or something that’s a permanent they don’t control. Sacrificing a permanent doesn’t
+    ///         destroy it, so regeneration or other effects that replace destruction can’t affect
+    ///         this action.
+    pub(crate) fn sacrifice(
+        &mut self,
+        player: PlayerId,
+        permanent: Entity,
+    ) -> Result<(), EngineError> {
+        self.ensure_can_sacrifice(player, permanent)?;
+        let owner = self
+            .world
+            .get::<Owner>(permanent)
+            .map_err(|_| EngineError::UnknownObject(permanent))?
+            .0;
+        log::trace!("Player with id {} sacrifices {permanent:?}.", player.0);
+        self.events.push(GameEvent::Sacrificed {
+            player,
+            object: ObjectSnapshot::of(&self.world, permanent),
+        });
+        self.move_object(permanent, Zone::Graveyard(owner))
+    }
+
+    /// Returns an error unless the specified player can sacrifice the permanent.
+    fn ensure_can_sacrifice(&self, player: PlayerId, permanent: Entity) -> Result<(), EngineError> {
+        self.player(player)?;
+        if !self.world.contains(permanent) {
+            return Err(EngineError::UnknownObject(permanent));
+        }
+        if !self.is_on_battlefield(permanent) || self.controller(permanent) != Some(player) {
+            return Err(EngineError::IllegalAction(format!(
+                "Player with id {} can't sacrifice {permanent:?} as they don't control it.",
+                player.0
+            )));
+        }
+        Ok(())
+    }
+
+    /// 701.7a To destroy a permanent, move it from the battlefield to its owner’s graveyard.
+    fn destroy(&mut self, target: Entity) -> Result<(), EngineError> {
+        // 702.12b A permanent with indestructible can’t be destroyed. [...]
+        if self.has_keyword(target, &KeywordAbility::Indestructible)
+            || self.survives_destruction(target)?
+        {
+            return Ok(());
+        }
+        let owner = self
+            .world
+            .get::<Owner>(target)
+            .map_err(|_| EngineError::UnknownObject(target))?
+            .0;
+        self.move_object(target, Zone::Graveyard(owner))
+    }
+
+    /// Applies the effects that replace the destruction of a permanent, i.e. those of its shield
+    /// counters and regeneration shields. Returns whether one of them replaced the destruction, so
+    /// the permanent stays on the battlefield.
+    fn survives_destruction(&mut self, target: Entity) -> Result<bool, EngineError> {
+        let events = self.replace(ReplaceableEvent::Destroy { object: target })?;
+        if !events.contains(&ReplaceableEvent::Destroy { object: target }) {
+            for event in events {
+                self.perform_replacing_event(event)?;
+            }
+            return Ok(true);
+        }
+        let shields = self
+            .world
+            .get::<RegenerationShields>(target)
+            .map_or(0, |it| it.0);
+        if shields == 0 {
+            return Ok(false);
+        }
+        log::trace!("{target:?} regenerates.");
+        let _ = self
+            .world
+            .insert(target, (RegenerationShields(shields - 1), Tapped));
+        let _ = self.world.remove_one::<MarkedDamage>(target);
+        self.remove_from_combat(target);
+        Ok(true)
+    }
+}
+
+/// A triggered ability that has triggered and waits to be resolved.
+pub(crate) struct PendingTrigger {
+    pub(crate) source: Entity,
+    pub(crate) controller: PlayerId,
+    /// The condition of the intervening “if” clause that is checked again as the ability resolves.
+    pub(crate) intervening_if: Option<InterveningIf>,
+    pub(crate) effects: Vec<Effect>,
+    /// The decisions of the controller whether to take each optional action of the ability in
+    /// order, which they make before it resolves.
+    pub(crate) decisions: Vec<bool>,
+}
+
+impl PendingTrigger {
+    /// Returns whether the controller of the ability makes a choice as it resolves, so they need
+    /// to be prompted.
+    pub(crate) fn is_optional(&self) -> bool {
+        self.effects.iter().any(Effect::is_optional)
+    }
+
+    /// Returns whether the controller still has to decide whether to take an optional action of
+    /// the ability.
+    pub(crate) fn needs_decision(&self) -> bool {
+        self.effects.iter().filter(|it| it.is_optional()).count() > self.decisions.len()
+    }
+}
+
+/// A game event that triggered abilities watch for.
+#[derive(Copy, Clone)]
+enum TriggerEvent {
+    EntersBattlefield {
+        object: Entity,
+        controller: PlayerId,
+    },
+    /// A permanent moved from the battlefield to the specified zone, which is a graveyard if it
+    /// died.
+    LeavesBattlefield {
+        object: Entity,
+        controller: PlayerId,
+        to: Zone,
+    },
+    BeginningOfStep(Step),
+    DieRolled {
+        player: PlayerId,
+    },
+    SpellCast {
+        spell: Entity,
+        caster: PlayerId,
+    },
+    Attacks {
+        object: Entity,
+        controller: PlayerId,
+    },
+    PutIntoGraveyard {
+        object: Entity,
+        owner: PlayerId,
+    },
+}
+
+/// The kind of decision a player faces in a game driven by [`Game::apply`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Decision {
+    /// Choosing one of the options of the pending choice.
+    Choice,
+    /// Choosing the targets of the oldest pending triggered ability.
+    Targets,
+    Attackers,
+    Blockers,
+    Priority,
+}
+
+/// A choice a player faces before the game goes on, which they make with [`GameAction::Choose`].
+struct PendingChoice {
+    player: PlayerId,
+    prompt: String,
+    options: Vec<String>,
+    kind: ChoiceKind,
+}
+
+/// What a [`PendingChoice`] decides.
+enum ChoiceKind {
+    /// 616.1. [...] the affected object’s controller (or its owner if it has no controller) or the
+    ///        affected player chooses one to apply [...]
+    ///
+    /// Which of the remaining replacement effects competing to modify the event applies first,
+    /// after the ones that were already chosen.
+    ReplacementOrder {
+        event: ReplaceableEvent,
+        chosen: Vec<String>,
+    },
+    /// 603.5. [...] The choice is made when the ability resolves. [...]
+    ///
+    /// Whether to take the next optional action of the oldest pending triggered ability.
+    TriggerOption,
+    /// Whether to take the next optional action of the spell about to resolve, which resolves once
+    /// all of them are decided on.
+    SpellOption { spell: Entity },
+    /// 614.12a If a replacement effect that modifies how a permanent enters the battlefield
+    ///         requires a choice, that choice is made before the permanent enters the battlefield.
+    ///
+    /// The next choice the abilities of the permanent spell about to resolve or the land about to
+    /// be played require, with the decision each option stands for.
+    Entry {
+        permanent: Entity,
+        decisions: Vec<EntryDecision>,
+    },
+}
+
+/// Either a permanent or a player, i.e. anything that can have counters put on it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum PermanentOrPlayer {
+    Permanent(Entity),
+    Player(PlayerId),
+}
+
+/// The objects a player chose to pay the components of an activation cost that require choosing
+/// them, each in the order of those components.
+#[derive(Copy, Clone, Default, Debug)]
+struct ChosenCostObjects<'a> {
+    sacrificed: &'a [Entity],
+    discarded: &'a [Entity],
+    exiled: &'a [Entity],
+}
+
+/// The permanents of which only one may be on the battlefield at a time according to the legend
+/// rule, the world rule or the planeswalker uniqueness rule.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum UniquenessGroup {
+    /// Legendary permanents with the same name controlled by the same player.
+    Legend(PlayerId, String),
+    /// All permanents with the supertype world.
+    World,
+    /// Planeswalkers with the same planeswalker type controlled by the same player.
+    Planeswalker(PlayerId, PlaneswalkerType),
+}
+
+/// Returns the modes of the modal spell ability of the card, if it has one.
+fn modes_of(card: &Card) -> Option<&Modes> {
+    card.abilities.iter().find_map(|it| match it {
+        Ability::ModalSpell(modes) => Some(modes),
+        _ => None,
+    })
+}
+
+/// Returns the effects of the spell abilities of the card, following the chosen modes of a modal
+/// spell in the order they are printed.
+fn spell_effects(card: &Card, modes: &[usize]) -> Vec<Effect> {
+    card.abilities
+        .iter()
+        .filter_map(|it| match it {
+            Ability::Spell(effects) => Some(effects.clone()),
+            Ability::ModalSpell(available) => Some(
+                modes
+                    .iter()
+                    .filter_map(|&it| available.modes.get(it))
+                    .flatten()
+                    .cloned()
+                    .collect(),
+            ),
+            _ => None,
+        })
+        .flatten()
+        .collect()
+}
+
+/// Returns the components an object based on the specified card starts with. The printed
+/// characteristics are shared with the card while the ones that effects may modify become
+/// components of their own.
+fn characteristics(card: &Arc<Card>) -> EntityBuilder {
+    let mut builder = EntityBuilder::new();
+    builder.add(CardRef(Arc::clone(card))).add(card.color());
+    if let Some(pt) = card.pt {
+        builder.add(pt);
+    }
+    if let Some(loyalty) = card.loyalty {
+        builder.add(loyalty);
+    }
+
+    let mut triggered_abilities = Vec::new();
+    let mut activated_abilities = Vec::new();
+    let mut static_abilities = Vec::new();
+    let mut keywords = IndexSet::new();
+    for ability in &card.abilities {
+        match ability {
+            Ability::Triggered(ability) => triggered_abilities.push(ability.clone()),
+            Ability::Activated(ability) => activated_abilities.push(ability.clone()),
+            Ability::Static(ability) => static_abilities.push(ability.clone()),
+            Ability::Keyword(KeywordAbility::Prowess) => {
+                keywords.insert(KeywordAbility::Prowess);
+                triggered_abilities.push(TriggeredAbility {
+                    condition: TriggerCondition::YouCastSpell {
+                        filter: ObjectFilter {
+                            excluded_card_type: Some(CardType::Creature),
+                            ..ObjectFilter::default()
+                        },
+                    },
+                    intervening_if: None,
+                    effects: vec![Effect::PumpUntilEndOfTurn {
+                        power: 1,
+                        toughness: 1,
+                    }],
+                });
+            }
+            Ability::Keyword(keyword) => {
+                keywords.insert(keyword.clone());
+            }
+            // Spell abilities are read from the card as the spell resolves.
+            Ability::Spell(_) | Ability::ModalSpell(_) => {}
+        }
+    }
+    if !triggered_abilities.is_empty() {
+        builder.add(TriggeredAbilities(triggered_abilities));
+    }
+    if !activated_abilities.is_empty() {
+        builder.add(ActivatedAbilities(activated_abilities));
+    }
+    if !static_abilities.is_empty() {
+        builder.add(StaticAbilities(static_abilities));
+    }
+    if !keywords.is_empty() {
+        builder.add(Keywords(keywords));
+    }
+    builder
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::RefCell,
+        sync::{Mutex, Once},
+        time::Instant,
+    };
+
+    use super::*;
+    use crate::{
+        abilities::{ActivatedAbility, Comparison, ModeCount, PlayerScope, TypeChange},
+        core::{
+            ArtifactType, BasicLandType, CardMetadata, CollectorNumber, Color, CreatureType,
+            EnchantmentType, LandType, Mana, ManaSymbol, Name, ProtectionQuality, TypeLine,
+        },
+        log::{GameLog, LogVisibility},
+        replacement::{EntersTapped, ExiledInsteadOfDying, InsteadOfDrawing},
+        rules_text,
+        setup::GameBuilder,
+    };
+
+    #[test]
+    fn proliferate_adds_one_counter_of_each_kind_already_there() {
+        let mut game = Game::new(2);
+        let opponent = game.players()[1].id;
+
+        let creature = spawn_creature(&mut game, 1, 1);
+        let untouched = spawn_creature(&mut game, 1, 1);
+        game.add_counters(
+            PermanentOrPlayer::Permanent(creature),
+            CounterKind::PlusOnePlusOne,
+            1,
+        )
+        .expect("Could not add the counters.");
+        game.add_counters(
+            PermanentOrPlayer::Permanent(untouched),
+            CounterKind::Charge,
+            1,
+        )
+        .expect("Could not add the counters.");
+        game.add_counters(PermanentOrPlayer::Player(opponent), CounterKind::Poison, 1)
+            .expect("Could not add the counters.");
+
+        let controller = game.players()[0].id;
+        game.proliferate(
+            controller,
+            &[
+                PermanentOrPlayer::Permanent(creature),
+                PermanentOrPlayer::Player(opponent),
+                PermanentOrPlayer::Player(controller),
+            ],
+        )
+        .expect("Could not proliferate.");
+
+        assert_eq!(
+            game.counters(
+                PermanentOrPlayer::Permanent(creature),
+                &CounterKind::PlusOnePlusOne
+            )
+            .expect("Could not access the counters."),
+            2
+        );
+        assert_eq!(
+            game.counters(
+                PermanentOrPlayer::Permanent(untouched),
+                &CounterKind::Charge
+            )
+            .expect("Could not access the counters."),
+            1
+        );
+        assert_eq!(
+            game.counters(PermanentOrPlayer::Player(opponent), &CounterKind::Poison)
+                .expect("Could not access the counters."),
+            2
+        );
+        assert_eq!(
+            game.counters(PermanentOrPlayer::Player(controller), &CounterKind::Poison)
+                .expect("Could not access the counters."),
+            0
+        );
+        let events = game.events();
+        assert_eq!(
+            events[events.len() - 2..],
+            [
+                GameEvent::CountersAdded {
+                    holder: CounterHolder::Permanent(ObjectSnapshot::of(&game.world, creature)),
+                    kind: CounterKind::PlusOnePlusOne,
+                    amount: 1,
+                },
+                GameEvent::CountersAdded {
+                    holder: CounterHolder::Player(opponent),
+                    kind: CounterKind::Poison,
+                    amount: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn energy_costs_can_only_be_paid_with_enough_energy() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let card = creature_card(vec![
+            // When ~ enters the battlefield, you get {E}{E}.
+            Ability::Triggered(TriggeredAbility {
+                condition: TriggerCondition::EntersBattlefield(TriggerSubject::This),
+                intervening_if: None,
+                effects: vec![Effect::GetCounters(CounterKind::Energy, 2)],
+            }),
+            // Pay {E}{E}{E}: You gain 3 life.
+            Ability::Activated(ActivatedAbility {
+                cost: vec![ActivationCost::Pay(Cost::PayEnergy(3))],
+                effects: vec![Effect::GainLife(3)],
+            }),
+        ]);
+        let energy = |game: &Game| {
+            game.counters(PermanentOrPlayer::Player(player), &CounterKind::Energy)
+                .expect("Could not access the counters.")
+        };
+
+        let creature = game
+            .spawn_object(&card, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        game.resolve_next_trigger(&[])
+            .expect("Could not resolve the trigger.");
+        assert_eq!(energy(&game), 2);
+        assert!(game.activate_ability(player, creature, 0, &[]).is_err());
+        assert_eq!(energy(&game), 2);
+
+        game.spawn_object(&card, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        game.resolve_next_trigger(&[])
+            .expect("Could not resolve the trigger.");
+        game.activate_ability(player, creature, 0, &[])
+            .expect("Could not activate the ability.");
+        assert_eq!(energy(&game), 1);
+
+        let energy_events = game
+            .events()
+            .iter()
+            .filter(|it| {
+                matches!(
+                    it,
+                    GameEvent::EnergyGained { .. } | GameEvent::EnergySpent { .. }
+                )
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(
+            energy_events,
+            vec![
+                GameEvent::EnergyGained {
+                    player,
+                    amount: 2,
+                    total: 2,
+                },
+                GameEvent::EnergyGained {
+                    player,
+                    amount: 2,
+                    total: 4,
+                },
+                GameEvent::EnergySpent {
+                    player,
+                    amount: 3,
+                    total: 1,
+                },
+            ]
+        );
+        assert!(!game
+            .events()
+            .iter()
+            .any(|it| matches!(it, GameEvent::CountersAdded { .. })));
+    }
+
+    #[test]
+    fn day_and_night_change_based_on_spells_cast_in_the_previous_turn() {
+        let mut game = Game::new(2);
+        let first_player = game.players()[0].id;
+        let second_player = game.players()[1].id;
+        let spell = find_card_by_name("Llanowar Elves").expect("Could not find the card.");
+
+        game.next_turn().expect("Could not begin the next turn.");
+        assert_eq!(game.day_night(), None);
+
+        game.set_day_night(DayNight::Day)
+            .expect("Could not make it day.");
+        game.next_turn().expect("Could not begin the next turn.");
+        assert_eq!(game.turn().active_player, first_player);
+        assert_eq!(game.day_night(), Some(DayNight::Night));
+
+        // Spells cast by nonactive players don't count.
+        game.record_spell_cast(second_player, spell);
+        game.record_spell_cast(second_player, spell);
+        game.next_turn().expect("Could not begin the next turn.");
+        assert_eq!(game.day_night(), Some(DayNight::Night));
+
+        game.record_spell_cast(second_player, spell);
+        game.record_spell_cast(second_player, spell);
+        game.next_turn().expect("Could not begin the next turn.");
+        assert_eq!(game.day_night(), Some(DayNight::Day));
+
+        game.record_spell_cast(first_player, spell);
+        game.next_turn().expect("Could not begin the next turn.");
+        assert_eq!(game.day_night(), Some(DayNight::Day));
+        assert_eq!(game.turn().number, 6);
+    }
+
+    #[test]
+    fn daybound_werewolves_transform_as_day_and_night_change() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let face = |name: &str, keyword, power| {
+            Card::builder()
+                .name(Name(name.into()))
+                .type_line(TypeLine {
+                    card_type: [CardType::Creature].into(),
+                    subtype: [Subtype::Creature(CreatureType::Werewolf)].into(),
+                    supertype: [].into(),
+                })
+                .pt(PtCharacteristic {
+                    power: PtValue::Fixed(power),
+                    toughness: PtValue::Fixed(power),
+                })
+                .abilities(vec![Ability::Keyword(keyword)])
+        };
+        let card = Arc::new(
+            face("Village Watch", KeywordAbility::Daybound, 4)
+                .back_face(Box::new(
+                    face("Village Reavers", KeywordAbility::Nightbound, 5)
+                        .build()
+                        .expect("Failed to build the card."),
+                ))
+                .build()
+                .expect("Failed to build the card."),
+        );
+        let name = |game: &Game, entity| ObjectSnapshot::of(&game.world, entity).name;
+
+        // The first daybound permanent makes it day.
+        let werewolf = game
+            .spawn_object(&card, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        assert_eq!(game.day_night(), Some(DayNight::Day));
+        assert_eq!(name(&game, werewolf), "Village Watch");
+
+        // The player casts no spells, so it becomes night at the beginning of the next turn.
+        game.next_turn().expect("Could not begin the next turn.");
+        game.begin_step(Step::Upkeep)
+            .expect("Could not begin the step.");
+        assert_eq!(game.day_night(), Some(DayNight::Night));
+        assert_eq!(name(&game, werewolf), "Village Reavers");
+        assert_eq!(game.power_toughness(werewolf), Some((5, 5)));
+
+        // Daybound permanents enter the battlefield transformed while it is night.
+        let other = game
+            .spawn_object(&card, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        assert_eq!(name(&game, other), "Village Reavers");
+
+        let spell = find_card_by_name("Llanowar Elves").expect("Could not find the card.");
+        let active_player = game.turn().active_player;
+        game.record_spell_cast(active_player, spell);
+        game.record_spell_cast(active_player, spell);
+        game.next_turn().expect("Could not begin the next turn.");
+        assert_eq!(game.day_night(), Some(DayNight::Day));
+        assert_eq!(name(&game, werewolf), "Village Watch");
+        assert_eq!(name(&game, other), "Village Watch");
+
+        // A transformed permanent leaves the battlefield with its front face up.
+        game.next_turn().expect("Could not begin the next turn.");
+        assert_eq!(name(&game, werewolf), "Village Reavers");
+        game.move_object(werewolf, Zone::Hand(player))
+            .expect("Could not move the object.");
+        assert_eq!(name(&game, werewolf), "Village Watch");
+    }
+
+    #[test]
+    fn turn_tracker_is_reset_on_the_next_turn() {
+        let mut game = Game::new(2);
+        let first_player = game.players()[0].id;
+        let second_player = game.players()[1].id;
+        let elves = find_card_by_name("Llanowar Elves").expect("Could not find the card.");
+        let lantern = find_card_by_name("Soul-Guide Lantern").expect("Could not find the card.");
+
+        game.record_spell_cast(first_player, elves);
+        game.record_spell_cast(first_player, lantern);
+        game.turn.tracker.record_creature_died(first_player);
+        game.deal_damage_to_player(second_player, 3)
+            .expect("Could not deal the damage.");
+        game.gain_life(second_player, 1)
+            .expect("Could not gain life.");
+
+        let tracker = game.turn_tracker();
+        assert_eq!(tracker.spells_cast_by(first_player), 2);
+        assert_eq!(tracker.spells_cast_by(second_player), 0);
+        assert!(tracker.spells_cast()[0]
+            .card_type
+            .contains(&CardType::Creature));
+        assert_eq!(tracker.spells_cast()[1].color, ColorIdentity::Colorless);
+        assert_eq!(tracker.creatures_died_under_control_of(first_player), 1);
+        assert_eq!(tracker.damage_dealt_to(second_player), 3);
+        assert_eq!(tracker.life_lost_by(second_player), 3);
+        assert_eq!(tracker.life_gained_by(second_player), 1);
+        assert_eq!(
+            game.player(second_player)
+                .expect("Could not access the player.")
+                .life,
+            18
+        );
+
+        game.next_turn().expect("Could not begin the next turn.");
+
+        let tracker = game.turn_tracker();
+        assert!(tracker.spells_cast().is_empty());
+        assert_eq!(tracker.creatures_died(), 0);
+        assert_eq!(tracker.damage_dealt_to(second_player), 0);
+
+        let previous_tracker = game
+            .previous_turn_tracker()
+            .expect("Could not access the previous turn.");
+        assert_eq!(previous_tracker.spells_cast_by(first_player), 2);
+        assert_eq!(previous_tracker.creatures_died(), 1);
+    }
+
+    fn spawn_creature(game: &mut Game, power: i64, toughness: i64) -> Entity {
+        let card = Card::builder()
+            .pt(PtCharacteristic {
+                power: PtValue::Fixed(power),
+                toughness: PtValue::Fixed(toughness),
+            })
+            .build()
+            .expect("Failed to build the card.");
+        let owner = game.players()[0].id;
+        game.spawn_object(&Arc::new(card), owner, Zone::Battlefield)
+            .expect("Could not spawn the object.")
+    }
+
+    #[test]
+    fn until_end_of_turn_effects_end_during_cleanup() {
+        let mut game = Game::new(2);
+        let controller = game.turn().active_player;
+        let creature = spawn_creature(&mut game, 2, 2);
+
+        game.add_effect(ContinuousEffect {
+            source: Some(creature),
+            controller,
+            duration: Duration::UntilEndOfTurn,
+            kind: EffectKind::ModifyPowerToughness {
+                target: creature,
+                power: 3,
+                toughness: 3,
+            },
+        });
+        assert_eq!(game.power_toughness(creature), Some((5, 5)));
+
+        game.next_turn().expect("Could not begin the next turn.");
+        assert_eq!(game.power_toughness(creature), Some((2, 2)));
+        assert!(game.events().contains(&GameEvent::EffectEnded {
+            source: Some(ObjectSnapshot::of(&game.world, creature)),
+            controller,
+        }));
+    }
+
+    #[test]
+    fn until_your_next_turn_effects_last_through_the_opponents_turn() {
+        let mut game = Game::new(2);
+        let controller = game.turn().active_player;
+        let creature = spawn_creature(&mut game, 2, 2);
+
+        game.add_effect(ContinuousEffect {
+            source: None,
+            controller,
+            duration: Duration::UntilYourNextTurn,
+            kind: EffectKind::ModifyPowerToughness {
+                target: creature,
+                power: -1,
+                toughness: -1,
+            },
+        });
+
+        let ended = |game: &Game| {
+            game.events()
+                .iter()
+                .filter(|it| matches!(it, GameEvent::EffectEnded { .. }))
+                .count()
+        };
+
+        game.next_turn().expect("Could not begin the next turn.");
+        assert_ne!(game.turn().active_player, controller);
+        assert_eq!(game.power_toughness(creature), Some((1, 1)));
+        assert_eq!(ended(&game), 0);
+
+        game.next_turn().expect("Could not begin the next turn.");
+        assert_eq!(game.turn().active_player, controller);
+        assert_eq!(game.power_toughness(creature), Some((2, 2)));
+        assert_eq!(ended(&game), 1);
+    }
+
+    #[test]
+    fn prevention_shields_are_consumed_by_damage() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let source = game
+            .world_mut()
+            .spawn((Object, ColorIdentity::Monocolored(Color::Red)));
+        let damage = |amount| Damage {
+            source,
+            recipient: PermanentOrPlayer::Player(player),
+            amount,
+            combat: false,
+        };
+
+        game.add_prevention_effect(PreventionEffect::Shield {
+            recipient: PermanentOrPlayer::Player(player),
+            remaining: 3,
+        });
+        assert_eq!(
+            game.deal_damage(&damage(5))
+                .expect("Could not deal the damage."),
+            3
+        );
+        assert_eq!(
+            game.player(player)
+                .expect("Could not access the player.")
+                .life,
+            18
+        );
+        assert_eq!(
+            game.deal_damage(&damage(1))
+                .expect("Could not deal the damage."),
+            0
+        );
+        assert_eq!(
+            game.player(player)
+                .expect("Could not access the player.")
+                .life,
+            17
+        );
+    }
+
+    #[test]
+    fn prevention_effects_end_at_cleanup() {
+        let mut game = Game::new(2);
+        let creature = spawn_creature(&mut game, 2, 2);
+        let source = game
+            .world_mut()
+            .spawn((Object, ColorIdentity::Monocolored(Color::Red)));
+        let damage = |combat| Damage {
+            source,
+            recipient: PermanentOrPlayer::Permanent(creature),
+            amount: 2,
+            combat,
+        };
+
+        game.add_prevention_effect(PreventionEffect::AllCombatDamage);
+        assert_eq!(
+            game.deal_damage(&damage(true))
+                .expect("Could not deal the damage."),
+            2
+        );
+        assert_eq!(
+            game.deal_damage(&damage(false))
+                .expect("Could not deal the damage."),
+            0
+        );
+        assert_eq!(
+            game.world_mut()
+                .get::<MarkedDamage>(creature)
+                .expect("Could not find the damage.")
+                .0,
+            2
+        );
+
+        game.next_turn().expect("Could not begin the next turn.");
+        assert_eq!(
+            game.world_mut()
+                .get::<MarkedDamage>(creature)
+                .expect("Could not find the damage.")
+                .0,
+            0
+        );
+        assert_eq!(
+            game.deal_damage(&damage(true))
+                .expect("Could not deal the damage."),
+            0
+        );
+        assert_eq!(
+            game.world_mut()
+                .get::<MarkedDamage>(creature)
+                .expect("Could not find the damage.")
+                .0,
+            2
+        );
+    }
+
+    #[test]
+    fn protection_prevents_damage_from_sources_with_the_stated_quality() {
+        let mut game = Game::new(2);
+        let creature = spawn_creature(&mut game, 2, 2);
+        game.world_mut()
+            .insert_one(
+                creature,
+                Keywords(
+                    [KeywordAbility::Protection(ProtectionQuality::Color(
+                        Color::Red,
+                    ))]
+                    .into(),
+                ),
+            )
+            .expect("Could not add the keywords.");
+        let red_source = game
+            .world_mut()
+            .spawn((Object, ColorIdentity::Monocolored(Color::Red)));
+        let green_source = game
+            .world_mut()
+            .spawn((Object, ColorIdentity::Monocolored(Color::Green)));
+
+        let damage = |source| Damage {
+            source,
+            recipient: PermanentOrPlayer::Permanent(creature),
+            amount: 3,
+            combat: true,
+        };
+        assert_eq!(
+            game.deal_damage(&damage(red_source))
+                .expect("Could not deal the damage."),
+            3
+        );
+        assert_eq!(
+            game.deal_damage(&damage(green_source))
+                .expect("Could not deal the damage."),
+            0
+        );
+        assert_eq!(
+            game.world_mut()
+                .get::<MarkedDamage>(creature)
+                .expect("Could not find the damage.")
+                .0,
+            3
+        );
+    }
+
+    /// Spawns a creature with protection from red and a red and a green creature for the specified
+    /// player each, which all may attack, block and be targeted.
+    fn spawn_protection_from_red(game: &mut Game, player: PlayerId) -> [Entity; 3] {
+        let protection = KeywordAbility::Protection(ProtectionQuality::Color(Color::Red));
+        let protected = game
+            .spawn_object(
+                &creature_card(vec![Ability::Keyword(protection)]),
+                player,
+                Zone::Battlefield,
+            )
+            .expect("Could not spawn the object.");
+        let opponent = game
+            .players()
+            .iter()
+            .map(|it| it.id)
+            .find(|&it| it != player)
+            .expect("Could not find the opponent.");
+        let [red, green] = [Color::Red, Color::Green].map(|color| {
+            let creature = game
+                .spawn_object(&vanilla_creature(2, 2), opponent, Zone::Battlefield)
+                .expect("Could not spawn the object.");
+            game.world_mut()
+                .insert_one(creature, ColorIdentity::Monocolored(color))
+                .expect("Could not color the creature.");
+            creature
+        });
+        for creature in [protected, red, green] {
+            let _ = game.world_mut().remove_one::<SummoningSick>(creature);
+        }
+        [protected, red, green]
+    }
+
+    #[test]
+    fn creatures_with_protection_cant_be_blocked_by_creatures_with_the_stated_quality() {
+        let mut game = Game::new(2);
+        let [player, opponent] = [0, 1].map(|it| game.players()[it].id);
+        let [protected, red, green] = spawn_protection_from_red(&mut game, player);
+        game.begin_step(Step::DeclareAttackers)
+            .expect("Could not begin the step.");
+        game.declare_attackers(player, &[protected])
+            .expect("Could not declare the attackers.");
+        game.begin_step(Step::DeclareBlockers)
+            .expect("Could not begin the step.");
+
+        assert!(matches!(
+            game.declare_blockers(opponent, &[(red, protected)]),
+            Err(EngineError::IllegalAction(_))
+        ));
+        game.declare_blockers(opponent, &[(green, protected)])
+            .expect("Could not declare the blockers.");
+        assert_eq!(game.combat().blocked_by(green), [protected]);
+    }
+
+    #[test]
+    fn creatures_with_protection_cant_be_targeted_by_abilities_of_sources_with_the_quality() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let [protected, red, green] = spawn_protection_from_red(&mut game, player);
+        let controller = game.players()[1].id;
+        let destroy = [Effect::DestroyTarget(ObjectFilter::default())];
+        let target = [PermanentOrPlayer::Permanent(protected)];
+
+        game.resolve_effects(red, controller, &destroy, &target)
+            .expect("Could not resolve the effects.");
+        assert_eq!(game.zones.zone_of(protected), Some(Zone::Battlefield));
+        game.resolve_effects(green, controller, &destroy, &target)
+            .expect("Could not resolve the effects.");
+        assert_eq!(game.zones.zone_of(protected), Some(Zone::Graveyard(player)));
+    }
+
+    #[test]
+    fn permanents_with_protection_cant_be_enchanted_or_equipped_by_objects_with_the_quality() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let [protected, ..] = spawn_protection_from_red(&mut game, player);
+        let [red_aura, green_equipment] = [
+            (CardType::Enchantment, Color::Red),
+            (CardType::Artifact, Color::Green),
+        ]
+        .map(|(card_type, color)| {
+            let card = Card::builder()
+                .type_line(TypeLine {
+                    card_type: [card_type].into(),
+                    subtype: [].into(),
+                    supertype: [].into(),
+                })
+                .build()
+                .expect("Failed to build the card.");
+            let attachment = game
+                .spawn_object(&Arc::new(card), player, Zone::Battlefield)
+                .expect("Could not spawn the object.");
+            game.world_mut()
+                .insert_one(attachment, ColorIdentity::Monocolored(color))
+                .expect("Could not color the attachment.");
+            attachment
+        });
+
+        assert!(matches!(
+            game.attach(red_aura, protected),
+            Err(EngineError::InvalidTarget(_))
+        ));
+        game.attach(green_equipment, protected)
+            .expect("Could not attach the equipment.");
+    }
+
+    #[test]
+    fn attachments_fall_off_permanents_that_gain_protection_from_them() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let creature = game
+            .spawn_object(&creature_card(vec![]), player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let [aura, equipment] = [
+            (
+                CardType::Enchantment,
+                Subtype::Enchantment(EnchantmentType::Aura),
+            ),
+            (
+                CardType::Artifact,
+                Subtype::Artifact(ArtifactType::Equipment),
+            ),
+        ]
+        .map(|(card_type, subtype)| {
+            let card = Card::builder()
+                .type_line(TypeLine {
+                    card_type: [card_type].into(),
+                    subtype: [subtype].into(),
+                    supertype: [].into(),
+                })
+                .build()
+                .expect("Failed to build the card.");
+            let attachment = game
+                .spawn_object(&Arc::new(card), player, Zone::Battlefield)
+                .expect("Could not spawn the object.");
+            game.world_mut()
+                .insert_one(attachment, ColorIdentity::Monocolored(Color::Red))
+                .expect("Could not color the attachment.");
+            game.attach(attachment, creature)
+                .expect("Could not attach the attachment.");
+            attachment
+        });
+
+        // Target creature gains protection from red until end of turn.
+        game.add_effect(ContinuousEffect {
+            source: None,
+            controller: player,
+            duration: Duration::UntilEndOfTurn,
+            kind: EffectKind::AddKeyword {
+                target: creature,
+                keyword: KeywordAbility::Protection(ProtectionQuality::Color(Color::Red)),
+            },
+        });
+        game.check_state_based_actions()
+            .expect("Could not check the state-based actions.");
+
+        assert_eq!(game.zones.zone_of(aura), Some(Zone::Graveyard(player)));
+        assert!(game.is_on_battlefield(equipment));
+        assert!(game.world_mut().get::<AttachedTo>(equipment).is_err());
+    }
+
+    #[test]
+    fn damage_to_any_target_depends_on_the_recipient() {
+        let abilities = rules_text::parse(
+            "Lightning Bolt",
+            "Lightning Bolt deals 3 damage to any target.",
+        )
+        .expect("Could not parse the rules text.");
+        let lightning_bolt = instant_card(abilities);
+        let jace = Card::builder()
+            .type_line(TypeLine {
+                card_type: [CardType::Planeswalker].into(),
+                subtype: [Subtype::Planeswalker(PlaneswalkerType::Jace)].into(),
+                supertype: [].into(),
+            })
+            .loyalty(Loyalty(5))
+            .build()
+            .expect("Failed to build the card.");
+        let jace = Arc::new(jace);
+        let cast = |game: &mut Game, target| {
+            let player = game.players()[0].id;
+            let spell = game
+                .spawn_object(&lightning_bolt, player, Zone::Hand(player))
+                .expect("Could not spawn the object.");
+            game.cast_spell(player, spell, None)
+                .expect("Could not cast the spell.");
+            game.resolve_spell(spell, &[target])
+                .expect("Could not resolve the spell.");
+        };
+        let life = |game: &Game, player| {
+            game.player(player)
+                .expect("Could not access the player.")
+                .life
+        };
+
+        let mut game = Game::new(2);
+        let opponent = game.players()[1].id;
+        let creature = game
+            .spawn_object(&vanilla_creature(1, 4), opponent, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        cast(&mut game, PermanentOrPlayer::Permanent(creature));
+        assert_eq!(
+            game.world_mut()
+                .get::<MarkedDamage>(creature)
+                .expect("Could not find the damage.")
+                .0,
+            3
+        );
+        assert_eq!(life(&game, opponent), 20);
+
+        let mut game = Game::new(2);
+        let opponent = game.players()[1].id;
+        cast(&mut game, PermanentOrPlayer::Player(opponent));
+        assert_eq!(life(&game, opponent), 17);
+
+        let mut game = Game::new(2);
+        let opponent = game.players()[1].id;
+        let planeswalker = game
+            .spawn_object(&jace, opponent, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let loyalty = |game: &Game| {
+            game.counters(
+                PermanentOrPlayer::Permanent(planeswalker),
+                &CounterKind::Loyalty,
+            )
+            .expect("Could not count the counters.")
+        };
+        assert_eq!(loyalty(&game), 5);
+        cast(&mut game, PermanentOrPlayer::Permanent(planeswalker));
+        assert_eq!(loyalty(&game), 2);
+        assert!(game.events().contains(&GameEvent::CountersRemoved {
+            holder: CounterHolder::Permanent(ObjectSnapshot::of(&game.world, planeswalker)),
+            kind: CounterKind::Loyalty,
+            amount: 3,
+        }));
+        assert!(game.world_mut().get::<MarkedDamage>(planeswalker).is_err());
+        assert_eq!(life(&game, opponent), 20);
+
+        // Lands aren't any target, so the spell doesn't do anything as it resolves.
+        let forest = game
+            .spawn_object(
+                find_card_by_name("Forest").expect("Could not find the card."),
+                opponent,
+                Zone::Battlefield,
+            )
+            .expect("Could not spawn the object.");
+        cast(&mut game, PermanentOrPlayer::Permanent(forest));
+        assert!(game.world_mut().get::<MarkedDamage>(forest).is_err());
+    }
+
+    #[test]
+    fn quantities_are_determined_as_the_effect_resolves() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let elf = ObjectFilter {
+            subtype: Some(Subtype::Creature(CreatureType::Elf)),
+            you_control: true,
+            ..ObjectFilter::default()
+        };
+        // Draw a card for each Elf you control.
+        let spell = instant_card(vec![Ability::Spell(vec![Effect::DrawCards(
+            Quantity::Permanents(elf),
+        )])]);
+        let llanowar_elves = find_card_by_name("Llanowar Elves").expect("Could not find the card.");
+        let elves = (0..3)
+            .map(|_| {
+                game.spawn_object(llanowar_elves, player, Zone::Battlefield)
+                    .expect("Could not spawn the object.")
+            })
+            .collect::<Vec<_>>();
+        for _ in 0..5 {
+            game.spawn_object(&creature_card(vec![]), player, Zone::Library(player))
+                .expect("Could not spawn the object.");
+        }
+        let spell = game
+            .spawn_object(&spell, player, Zone::Hand(player))
+            .expect("Could not spawn the object.");
+        game.cast_spell(player, spell, None)
+            .expect("Could not cast the spell.");
+
+        // One of the Elves dies in response.
+        game.destroy(elves[0])
+            .expect("Could not destroy the permanent.");
+        game.resolve_spell(spell, &[])
+            .expect("Could not resolve the spell.");
+        assert_eq!(game.hand_size(player), 2);
+        assert_eq!(
+            game.library(player)
+                .expect("Could not access the library.")
+                .len(),
+            3
+        );
+
+        let creature_cards = ObjectFilter {
+            card_type: Some(CardType::Creature),
+            ..ObjectFilter::default()
+        };
+        let in_graveyard = Quantity::Cards(CountedZone::YourGraveyard, creature_cards);
+        assert_eq!(game.quantity(&in_graveyard, spell, player), Ok(1));
+        assert_eq!(game.quantity(&Quantity::YourLife, spell, player), Ok(20));
+    }
+
+    #[test]
+    fn counters_on_a_source_that_left_the_battlefield_are_its_last_known_information() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let creature = game
+            .spawn_object(&creature_card(vec![]), player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        game.add_counters(
+            PermanentOrPlayer::Permanent(creature),
+            CounterKind::PlusOnePlusOne,
+            2,
+        )
+        .expect("Could not add the counters.");
+        let counters = Quantity::CountersOnThis(CounterKind::PlusOnePlusOne);
+        assert_eq!(game.quantity(&counters, creature, player), Ok(2));
+
+        game.destroy(creature)
+            .expect("Could not destroy the permanent.");
+        assert_eq!(
+            game.counters(
+                PermanentOrPlayer::Permanent(creature),
+                &CounterKind::PlusOnePlusOne
+            ),
+            Ok(0)
+        );
+        assert_eq!(game.quantity(&counters, creature, player), Ok(2));
+    }
+
+    #[test]
+    fn creatures_enter_the_battlefield_with_the_counters_chosen_as_they_were_cast() {
+        // This creature enters the battlefield with X +1/+1 counters on it.
+        let hydra = Card::builder()
+            .name(Name("Hydra".into()))
+            .type_line(TypeLine {
+                card_type: [CardType::Creature].into(),
+                subtype: [].into(),
+                supertype: [].into(),
+            })
+            .mana_cost(ManaCost(vec![
+                ManaSymbol::Variable,
+                ManaSymbol::Colored(Color::Green),
+            ]))
+            .pt(PtCharacteristic {
+                power: PtValue::Fixed(0),
+                toughness: PtValue::Fixed(0),
+            })
+            .abilities(vec![Ability::Static(StaticAbility::EntersWithCounters(
+                CounterKind::PlusOnePlusOne,
+                CounterAmount::X,
+            ))])
+            .build()
+            .expect("Failed to build the card.");
+        let hydra = Arc::new(hydra);
+        let cast = |x| {
+            let mut game = Game::new(2);
+            let player = game.players()[0].id;
+            let mana_pool = &mut game
+                .player_mut(player)
+                .expect("Could not access the player.")
+                .mana_pool;
+            for _ in 0..=x {
+                mana_pool.add(Mana::Monocolored(Color::Green));
+            }
+            let spell = game
+                .spawn_object(&hydra, player, Zone::Hand(player))
+                .expect("Could not spawn the object.");
+            let cost = hydra.mana_cost.clone().expect("The card has a mana cost.");
+            assert_eq!(game.max_x(player, &cost, Some(spell)), Ok(Some(x)));
+            game.cast_spell_with_x(player, spell, x, None)
+                .expect("Could not cast the spell.");
+            game.resolve_spell(spell, &[])
+                .expect("Could not resolve the spell.");
+            (game, player, spell)
+        };
+
+        let (game, _, creature) = cast(3);
+        assert_eq!(game.zones.zone_of(creature), Some(Zone::Battlefield));
+        assert_eq!(game.power_toughness(creature), Some((3, 3)));
+        assert!(!game.events().iter().any(|it| matches!(
+            it,
+            GameEvent::ZoneChanged {
+                to: Zone::Graveyard(_),
+                ..
+            }
+        )));
+
+        let (game, player, creature) = cast(0);
+        assert_eq!(game.zones.zone_of(creature), Some(Zone::Graveyard(player)));
+        assert_eq!(
+            game.turn_tracker().creatures_died_under_control_of(player),
+            1
+        );
+
+        // X can only be chosen for spells with {X} in their cost.
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let spell = game
+            .spawn_object(&instant_card(vec![]), player, Zone::Hand(player))
+            .expect("Could not spawn the object.");
+        assert!(matches!(
+            game.cast_spell_with_x(player, spell, 1, None),
+            Err(EngineError::IllegalAction(_))
+        ));
+    }
+
+    fn creature_card(abilities: Vec<Ability>) -> Arc<Card> {
+        let card = Card::builder()
+            .type_line(TypeLine {
+                card_type: [CardType::Creature].into(),
+                subtype: [].into(),
+                supertype: [].into(),
+            })
+            .pt(PtCharacteristic {
+                power: PtValue::Fixed(1),
+                toughness: PtValue::Fixed(1),
+            })
+            .abilities(abilities)
+            .build()
+            .expect("Failed to build the card.");
+        Arc::new(card)
+    }
+
+    /// Returns a creature without abilities with the specified power and toughness.
+    fn vanilla_creature(power: i64, toughness: i64) -> Arc<Card> {
+        let card = Card::builder()
+            .type_line(TypeLine {
+                card_type: [CardType::Creature].into(),
+                subtype: [].into(),
+                supertype: [].into(),
+            })
+            .pt(PtCharacteristic {
+                power: PtValue::Fixed(power),
+                toughness: PtValue::Fixed(toughness),
+            })
+            .build()
+            .expect("Failed to build the card.");
+        Arc::new(card)
+    }
+
+    /// Returns an instant without mana symbols in its cost, so it can be cast for free.
+    fn instant_card(abilities: Vec<Ability>) -> Arc<Card> {
+        let card = Card::builder()
+            .type_line(TypeLine {
+                card_type: [CardType::Instant].into(),
+                subtype: [].into(),
+                supertype: [].into(),
+            })
+            .mana_cost(ManaCost(vec![]))
+            .abilities(abilities)
+            .build()
+            .expect("Failed to build the card.");
+        Arc::new(card)
+    }
+
+    #[test]
+    fn enters_the_battlefield_triggers_resolve_for_their_controller() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        // When Soulmender enters the battlefield, you gain 1 life.
+        let card = creature_card(vec![Ability::Triggered(TriggeredAbility {
+            condition: TriggerCondition::EntersBattlefield(TriggerSubject::This),
+            intervening_if: None,
+            effects: vec![Effect::GainLife(1)],
+        })]);
+
+        let soulmender = game
+            .spawn_object(&card, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        assert_eq!(game.pending_triggers().len(), 1);
+        assert_eq!(game.pending_triggers()[0].source, soulmender);
+
+        assert!(game
+            .resolve_next_trigger(&[])
+            .expect("Could not resolve the trigger."));
+        assert!(!game
+            .resolve_next_trigger(&[])
+            .expect("Could not resolve the trigger."));
+        assert_eq!(
+            game.player(player)
+                .expect("Could not access the player.")
+                .life,
+            21
+        );
+    }
+
+    #[test]
+    fn triggers_matching_a_filter_only_trigger_for_matching_objects() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let opponent = game.players()[1].id;
+        // Whenever a creature enters the battlefield under your control, you gain 1 life.
+        let card = Arc::new(
+            Card::builder()
+                .abilities(vec![Ability::Triggered(TriggeredAbility {
+                    condition: TriggerCondition::EntersBattlefield(TriggerSubject::Matching(
+                        ObjectFilter {
+                            card_type: Some(CardType::Creature),
+                            you_control: true,
+                            ..ObjectFilter::default()
+                        },
+                    )),
+                    intervening_if: None,
+                    effects: vec![Effect::GainLife(1)],
+                })])
+                .build()
+                .expect("Failed to build the card."),
+        );
+        game.spawn_object(&card, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        assert!(game.pending_triggers().is_empty());
+
+        game.spawn_object(&creature_card(Vec::new()), opponent, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        assert!(game.pending_triggers().is_empty());
+
+        game.spawn_object(&creature_card(Vec::new()), player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        assert_eq!(game.pending_triggers().len(), 1);
+    }
+
+    #[test]
+    fn dies_triggers_look_back_in_time() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let opponent = game.players()[1].id;
+        // When this creature dies, it deals 2 damage to any target.
+        let card = creature_card(vec![Ability::Triggered(TriggeredAbility {
+            condition: TriggerCondition::Dies(TriggerSubject::This),
+            intervening_if: None,
+            effects: vec![Effect::DealDamage(Quantity::Fixed(2))],
+        })]);
+
+        let creature = game
+            .spawn_object(&card, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        game.move_object(creature, Zone::Graveyard(player))
+            .expect("Could not move the object.");
+        assert_eq!(game.pending_triggers().len(), 1);
+        assert_eq!(game.pending_triggers()[0].controller, player);
+        assert_eq!(
+            game.turn_tracker().creatures_died_under_control_of(player),
+            1
+        );
+
+        assert!(game
+            .resolve_next_trigger(&[PermanentOrPlayer::Player(opponent)])
+            .expect("Could not resolve the trigger."));
+        assert_eq!(
+            game.player(opponent)
+                .expect("Could not access the player.")
+                .life,
+            18
+        );
+    }
+
+    #[test]
+    fn upkeep_triggers_scoped_to_you_only_fire_on_your_turns() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        // At the beginning of your upkeep, you lose 1 life.
+        let card = Arc::new(
+            Card::builder()
+                .abilities(vec![Ability::Triggered(TriggeredAbility {
+                    condition: TriggerCondition::BeginningOfUpkeep(PlayerScope::You),
+                    intervening_if: None,
+                    effects: vec![Effect::LoseLife(1)],
+                })])
+                .build()
+                .expect("Failed to build the card."),
+        );
+        game.spawn_object(&card, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+
+        for _ in 0..4 {
+            game.begin_step(Step::Upkeep)
+                .expect("Could not begin the step.");
+            while game
+                .resolve_next_trigger(&[])
+                .expect("Could not resolve the trigger.")
+            {}
+            game.begin_step(Step::End)
+                .expect("Could not begin the step.");
+            assert!(game.pending_triggers().is_empty());
+            game.next_turn().expect("Could not begin the next turn.");
+        }
+        assert_eq!(
+            game.player(player)
+                .expect("Could not access the player.")
+                .life,
+            18
+        );
+    }
+
+    #[test]
+    fn players_sacrifice_permanents_with_upkeep_costs_unless_they_pay() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        // At the beginning of your upkeep, sacrifice this creature unless you pay {G}.
+        let card = creature_card(vec![Ability::Triggered(TriggeredAbility {
+            condition: TriggerCondition::BeginningOfUpkeep(PlayerScope::You),
+            intervening_if: None,
+            effects: vec![Effect::SacrificeUnlessPay(ManaCost(vec![
+                ManaSymbol::Colored(Color::Green),
+            ]))],
+        })]);
+        let creature = game
+            .spawn_object(&card, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let forest = game
+            .spawn_object(
+                find_card_by_name("Forest").expect("Could not find the card."),
+                player,
+                Zone::Battlefield,
+            )
+            .expect("Could not spawn the object.");
+        let upkeep = |game: &mut Game, option| {
+            game.begin_step(Step::Upkeep)
+                .expect("Could not begin the step.");
+            assert_eq!(game.player_to_act(), Ok(Some(player)));
+            let choice = game
+                .view_for(player)
+                .expect("Could not view the game.")
+                .choice
+                .expect("Could not find the choice.");
+            assert_eq!(choice.options[0], "Pay {G}");
+            game.apply(player, GameAction::Choose { option })
+                .expect("Could not make the choice.");
+            game.resolve_mandatory_triggers()
+                .expect("Could not resolve the triggers.");
+        };
+
+        upkeep(&mut game, 0);
+        assert!(game.world_mut().get::<Tapped>(forest).is_ok());
+        assert_eq!(game.zones.zone_of(creature), Some(Zone::Battlefield));
+
+        // The forest is still tapped, so the cost can't be paid anymore.
+        upkeep(&mut game, 0);
+        assert_eq!(game.zones.zone_of(creature), Some(Zone::Graveyard(player)));
+    }
+
+    #[test]
+    fn intervening_if_clauses_are_checked_as_abilities_trigger_and_resolve() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        // At the beginning of each upkeep, if you control three or more artifacts, you gain 1
+        // life.
+        let card = Arc::new(
+            Card::builder()
+                .abilities(vec![Ability::Triggered(TriggeredAbility {
+                    condition: TriggerCondition::BeginningOfUpkeep(PlayerScope::EachPlayer),
+                    intervening_if: Some(InterveningIf::Permanents(
+                        ObjectFilter {
+                            card_type: Some(CardType::Artifact),
+                            you_control: true,
+                            ..ObjectFilter::default()
+                        },
+                        Comparison::AtLeast(3),
+                    )),
+                    effects: vec![Effect::GainLife(1)],
+                })])
+                .build()
+                .expect("Failed to build the card."),
+        );
+        let artifact = Arc::new(
+            Card::builder()
+                .type_line(TypeLine {
+                    card_type: [CardType::Artifact].into(),
+                    subtype: [].into(),
+                    supertype: [].into(),
+                })
+                .build()
+                .expect("Failed to build the card."),
+        );
+        game.spawn_object(&card, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let mut artifacts = (0..2)
+            .map(|_| {
+                game.spawn_object(&artifact, player, Zone::Battlefield)
+                    .expect("Could not spawn the object.")
+            })
+            .collect::<Vec<_>>();
+        let life = |game: &Game| {
+            game.player(player)
+                .expect("Could not access the player.")
+                .life
+        };
+
+        game.begin_step(Step::Upkeep)
+            .expect("Could not begin the step.");
+        assert!(game.pending_triggers().is_empty());
+
+        artifacts.push(
+            game.spawn_object(&artifact, player, Zone::Battlefield)
+                .expect("Could not spawn the object."),
+        );
+        game.begin_step(Step::Upkeep)
+            .expect("Could not begin the step.");
+        assert_eq!(game.pending_triggers().len(), 1);
+        game.move_object(artifacts[0], Zone::Graveyard(player))
+            .expect("Could not move the object.");
+        assert!(game
+            .resolve_next_trigger(&[])
+            .expect("Could not resolve the trigger."));
+        assert_eq!(life(&game), 20);
+
+        game.spawn_object(&artifact, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        game.begin_step(Step::Upkeep)
+            .expect("Could not begin the step.");
+        assert!(game
+            .resolve_next_trigger(&[])
+            .expect("Could not resolve the trigger."));
+        assert_eq!(life(&game), 21);
+    }
+
+    #[test]
+    fn declined_optional_actions_do_nothing() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        // When this creature enters the battlefield, you may draw a card.
+        let card = creature_card(vec![Ability::Triggered(TriggeredAbility {
+            condition: TriggerCondition::EntersBattlefield(TriggerSubject::This),
+            intervening_if: None,
+            effects: vec![Effect::May {
+                effects: vec![Effect::DrawCards(Quantity::Fixed(1))],
+                when_you_do: vec![],
+            }],
+        })]);
+        game.spawn_object(&creature_card(vec![]), player, Zone::Library(player))
+            .expect("Could not spawn the object.");
+
+        let creature = game
+            .spawn_object(&card, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        assert!(game.pending_triggers()[0].is_optional());
+        assert_eq!(
+            game.events().last(),
+            Some(&GameEvent::AbilityTriggered {
+                controller: player,
+                source: ObjectSnapshot::of(&game.world, creature),
+                optional: true,
+            })
+        );
+        // Optional actions are never taken automatically.
+        game.resolve_mandatory_triggers()
+            .expect("Could not resolve the triggers.");
+        assert_eq!(game.pending_triggers().len(), 1);
+
+        // The controller decides before the ability resolves.
+        assert_eq!(game.player_to_act(), Ok(Some(player)));
+        assert_eq!(
+            game.legal_actions(player),
+            Ok(vec![
+                GameAction::Choose { option: 0 },
+                GameAction::Choose { option: 1 },
+            ])
+        );
+        game.apply(player, GameAction::Choose { option: 1 })
+            .expect("Could not decline the option.");
+        game.resolve_mandatory_triggers()
+            .expect("Could not resolve the triggers.");
+        assert!(game.pending_triggers().is_empty());
+        assert_eq!(
+            game.world
+                .query::<&Zone>()
+                .iter()
+                .filter(|(_, &zone)| zone == Zone::Hand(player))
+                .count(),
+            0
+        );
+        assert_eq!(
+            game.events().last(),
+            Some(&GameEvent::OptionChosen {
+                player,
+                source: ObjectSnapshot::of(&game.world, creature),
+                accepted: false,
+            })
+        );
+    }
+
+    #[test]
+    fn taking_an_optional_action_triggers_reflexive_abilities() {
+        let mut game = Game::new(2);
+        let [player, opponent] = [0, 1].map(|it| game.players()[it].id);
+        // When this creature enters the battlefield, you may sacrifice it. When you do, it deals 2
+        // damage to any target.
+        let card = creature_card(vec![Ability::Triggered(TriggeredAbility {
+            condition: TriggerCondition::EntersBattlefield(TriggerSubject::This),
+            intervening_if: None,
+            effects: vec![Effect::May {
+                effects: vec![Effect::SacrificeThis],
+                when_you_do: vec![Effect::DealDamage(Quantity::Fixed(2))],
+            }],
+        })]);
+        let creature = game
+            .spawn_object(&card, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        assert_eq!(game.player_to_act(), Ok(Some(player)));
+        let choice = game
+            .view_for(player)
+            .expect("Could not view the game.")
+            .choice
+            .expect("Could not find the choice.");
+        assert!(choice.prompt.contains("Player 1 may: Sacrifice"));
+        assert_eq!(choice.options, vec!["Yes", "No"]);
+        game.apply(player, GameAction::Choose { option: 0 })
+            .expect("Could not take the option.");
+        game.resolve_mandatory_triggers()
+            .expect("Could not resolve the triggers.");
+        assert_eq!(game.zones.zone_of(creature), Some(Zone::Graveyard(player)));
+        assert_eq!(game.pending_triggers().len(), 1);
+        assert!(!game.pending_triggers()[0].is_optional());
+
+        assert!(game
+            .resolve_next_trigger(&[PermanentOrPlayer::Player(opponent)])
+            .expect("Could not resolve the trigger."));
+        assert_eq!(
+            game.player(opponent)
+                .expect("Could not access the player.")
+                .life,
+            18
+        );
+    }
+
+    #[test]
+    fn simultaneous_triggers_are_queued_in_apnap_order() {
+        let mut game = Game::new(2);
+        let active_player = game.players()[0].id;
+        let opponent = game.players()[1].id;
+        // At the beginning of each player's end step, you gain 1 life.
+        let card = Arc::new(
+            Card::builder()
+                .abilities(vec![Ability::Triggered(TriggeredAbility {
+                    condition: TriggerCondition::BeginningOfEndStep(PlayerScope::EachPlayer),
+                    intervening_if: None,
+                    effects: vec![Effect::GainLife(1)],
+                })])
+                .build()
+                .expect("Failed to build the card."),
+        );
+        game.spawn_object(&card, opponent, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        game.spawn_object(&card, active_player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+
+        game.begin_step(Step::End)
+            .expect("Could not begin the step.");
+        let controllers = game
+            .pending_triggers()
+            .iter()
+            .map(|it| it.controller)
+            .collect::<Vec<_>>();
+        assert_eq!(controllers, vec![active_player, opponent]);
+    }
+
+    #[test]
+    fn llanowar_elves_tap_for_green_mana() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let card = find_card_by_name("Llanowar Elves").expect("Could not find the card.");
+        let elves = game
+            .spawn_object(card, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let green = Mana::Monocolored(Color::Green);
+
+        // Creatures can't pay {T} costs the turn they come under their controller's control.
+        assert!(matches!(
+            game.activate_ability(player, elves, 0, &[]),
+            Err(EngineError::PaymentFailed(_))
+        ));
+        let cost = ManaCost(vec![ManaSymbol::Colored(Color::Green)]);
+        assert!(game
+            .plan_payment(player, &cost, None)
+            .expect("Could not plan the payment.")
+            .is_none());
+        game.next_turn().expect("Could not begin the next turn.");
+        game.next_turn().expect("Could not begin the next turn.");
+        assert!(game
+            .plan_payment(player, &cost, None)
+            .expect("Could not plan the payment.")
+            .is_some());
+        assert!(game.activate_ability(player, elves, 0, &[]).is_ok());
+        assert_eq!(
+            game.player(player)
+                .expect("Could not access the player.")
+                .mana_pool
+                .amount(green),
+            1
+        );
+        assert!(game.world_mut().get::<Tapped>(elves).is_ok());
+
+        // A tapped permanent can't pay a {T} cost again until it untaps.
+        assert!(game.activate_ability(player, elves, 0, &[]).is_err());
+        game.begin_step(Step::Upkeep)
+            .expect("Could not begin the step.");
+        assert_eq!(
+            game.player(player)
+                .expect("Could not access the player.")
+                .mana_pool
+                .amount(green),
+            0
+        );
+
+        game.next_turn().expect("Could not begin the next turn.");
+        game.next_turn().expect("Could not begin the next turn.");
+        assert!(game.activate_ability(player, elves, 0, &[]).is_ok());
+    }
+
+    #[test]
+    fn only_the_controller_can_activate_abilities() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let opponent = game.players()[1].id;
+        let soulmender = game
+            .spawn_object(
+                find_card_by_name("Soulmender").expect("Could not find the card."),
+                player,
+                Zone::Battlefield,
+            )
+            .expect("Could not spawn the object.");
+        let _ = game.world_mut().remove_one::<SummoningSick>(soulmender);
+
+        assert!(game.activate_ability(opponent, soulmender, 0, &[]).is_err());
+        assert!(game.activate_ability(player, soulmender, 0, &[]).is_ok());
+        game.resolve_top_of_stack()
+            .expect("Could not resolve the ability.");
+        assert_eq!(
+            game.player(player)
+                .expect("Could not access the player.")
+                .life,
+            21
+        );
+    }
+
+    #[test]
+    fn static_abilities_modify_power_and_toughness_of_matching_objects() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let opponent = game.players()[1].id;
+        // Creatures you control get +1/+1.
+        let anthem = Arc::new(
+            Card::builder()
+                .abilities(vec![Ability::Static(StaticAbility::ModifyPowerToughness {
+                    affected: ObjectFilter {
+                        card_type: Some(CardType::Creature),
+                        you_control: true,
+                        ..ObjectFilter::default()
+                    },
+                    power: 1,
+                    toughness: 1,
+                })])
+                .build()
+                .expect("Failed to build the card."),
+        );
+        game.spawn_object(&anthem, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+
+        let elves = game
+            .spawn_object(
+                find_card_by_name("Llanowar Elves").expect("Could not find the card."),
+                player,
+                Zone::Battlefield,
+            )
+            .expect("Could not spawn the object.");
+        let other_elves = game
+            .spawn_object(
+                find_card_by_name("Llanowar Elves").expect("Could not find the card."),
+                opponent,
+                Zone::Battlefield,
+            )
+            .expect("Could not spawn the object.");
+        assert_eq!(game.power_toughness(elves), Some((2, 2)));
+        assert_eq!(game.power_toughness(other_elves), Some((1, 1)));
+    }
+
+    #[test]
+    fn activated_abilities_can_pump_and_destroy() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let opponent = game.players()[1].id;
+        let card = creature_card(vec![
+            // {T}: ~ gets +2/+2 until end of turn.
+            Ability::Activated(ActivatedAbility {
+                cost: vec![ActivationCost::Tap],
+                effects: vec![Effect::PumpUntilEndOfTurn {
+                    power: 2,
+                    toughness: 2,
+                }],
+            }),
+            // {E}: Destroy target creature.
+            Ability::Activated(ActivatedAbility {
+                cost: vec![ActivationCost::Pay(Cost::PayEnergy(1))],
+                effects: vec![Effect::DestroyTarget(ObjectFilter {
+                    card_type: Some(CardType::Creature),
+                    you_control: false,
+                    ..ObjectFilter::default()
+                })],
+            }),
+        ]);
+        let creature = game
+            .spawn_object(&card, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let _ = game.world_mut().remove_one::<SummoningSick>(creature);
+        let target = game
+            .spawn_object(&creature_card(Vec::new()), opponent, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+
+        assert!(game.activate_ability(player, creature, 0, &[]).is_ok());
+        // The ability waits on the stack until it resolves, so players can respond to it.
+        assert_eq!(game.power_toughness(creature), Some((1, 1)));
+        game.resolve_top_of_stack()
+            .expect("Could not resolve the ability.");
+        assert_eq!(game.power_toughness(creature), Some((3, 3)));
+
+        assert!(game
+            .activate_ability(player, creature, 1, &[PermanentOrPlayer::Permanent(target)])
+            .is_err());
+        game.gain_energy(player, 1).expect("Could not gain energy.");
+        assert!(game
+            .activate_ability(player, creature, 1, &[PermanentOrPlayer::Permanent(target)])
+            .is_ok());
+        game.resolve_top_of_stack()
+            .expect("Could not resolve the ability.");
+        assert_eq!(
+            *game
+                .world_mut()
+                .get::<Zone>(target)
+                .expect("Could not find the zone."),
+            Zone::Graveyard(opponent)
+        );
+    }
+
+    #[test]
+    fn the_embedded_card_database_matches_the_json_one() {
+        let binary =
+            CardDatabase::from_msgpack(EMBEDDED_CARDS).expect("Could not load the database.");
+        let json = CardDatabase::from_json(include_str!("./cards.json"))
+            .expect("Could not load the database.");
+
+        let cards = |database: &CardDatabase| {
+            serde_json::to_value(&database.cards).expect("Could not serialize the cards.")
+        };
+        assert!(!binary.cards.is_empty());
+        assert_eq!(cards(&binary), cards(&json));
+        assert_eq!(binary.normalized_names, json.normalized_names);
+    }
+
+    /// Checks that loading the embedded card database is faster than parsing the JSON one. It
+    /// depends on timing, so it only runs with `cargo test --release -- --ignored`.
+    #[test]
+    #[ignore = "benchmark"]
+    fn loading_the_embedded_card_database_is_faster_than_parsing_json() {
+        const ITERATIONS: u32 = 1_000;
+        let json = include_str!("./cards.json");
+        let measure = |load: &dyn Fn() -> Result<CardDatabase, EngineError>| {
+            let start = Instant::now();
+            for _ in 0..ITERATIONS {
+                load().expect("Could not load the database.");
+            }
+            start.elapsed() / ITERATIONS
+        };
+
+        let from_json = measure(&|| CardDatabase::from_json(json));
+        let from_msgpack = measure(&|| CardDatabase::from_msgpack(EMBEDDED_CARDS));
+        assert!(
+            from_msgpack < from_json,
+            "Loading MessagePack took {from_msgpack:?} but JSON only {from_json:?}."
+        );
+    }
+
+    #[test]
+    fn card_database_filters_cards_by_format() {
+        let legal = Card::builder()
+            .name(Name("Llanowar Elves".into()))
+            .legalities([(Format::Pauper, Legality::Legal)].into())
+            .build()
+            .expect("Failed to build the card.");
+        let not_legal = Card::builder()
+            .name(Name("Polukranos, Unchained".into()))
+            .legalities([(Format::Pauper, Legality::NotLegal)].into())
+            .build()
+            .expect("Failed to build the card.");
+        let database = CardDatabase::from(vec![Arc::new(legal), Arc::new(not_legal)]);
+
+        let names = database
+            .legal_in(Format::Pauper)
+            .map(|it| it.name.0.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["Llanowar Elves"]);
+        assert_eq!(database.legal_in(Format::Modern).count(), 2);
+    }
+
+    #[test]
+    fn unknown_players_and_objects_are_rejected() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let unknown_player = PlayerId(7);
+        let card = creature_card(Vec::new());
+
+        assert_eq!(
+            game.gain_life(unknown_player, 1),
+            Err(EngineError::UnknownPlayer(unknown_player))
+        );
+        assert_eq!(
+            game.spawn_object(&card, unknown_player, Zone::Battlefield),
+            Err(EngineError::UnknownPlayer(unknown_player))
+        );
+        assert!(matches!(
+            game.spawn_object(&card, player, Zone::Hand(game.players()[1].id)),
+            Err(EngineError::IllegalZoneOperation(_))
+        ));
+
+        // Entities of another game are unknown to this one.
+        let mut other_game = Game::new(2);
+        let foreign = other_game
+            .spawn_object(&card, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        assert_eq!(
+            game.move_object(foreign, Zone::Graveyard(player)),
+            Err(EngineError::UnknownObject(foreign))
+        );
+        assert_eq!(
+            game.activate_ability(player, foreign, 0, &[]),
+            Err(EngineError::UnknownObject(foreign))
+        );
+    }
+
+    #[test]
+    fn games_can_only_start_with_a_nonempty_deck_for_each_player() {
+        let mut game = Game::new(2);
+        let first_player = game.players()[0].id;
+        let second_player = game.players()[1].id;
+        let deck = || Deck::from(&[("Forest", 20)]).expect("Could not build the deck.");
+
+        assert!(matches!(
+            game.start(&[(first_player, deck())].into()),
+            Err(EngineError::InvalidDeck(_))
+        ));
+        assert_eq!(
+            game.start(&[(first_player, deck()), (PlayerId(7), deck())].into()),
+            Err(EngineError::UnknownPlayer(PlayerId(7)))
+        );
+        assert!(matches!(
+            game.start(
+                &[
+                    (first_player, deck()),
+                    (
+                        second_player,
+                        Deck::from(&[]).expect("Could not build the deck.")
+                    )
+                ]
+                .into()
+            ),
+            Err(EngineError::InvalidDeck(_))
+        ));
+        assert!(matches!(
+            Deck::from(&[("Black Lotus", 1)]),
+            Err(EngineError::Database(_))
+        ));
+        // Nothing is put into a library unless all decks are valid.
+        assert_eq!(game.world_mut().query::<&Zone>().iter().count(), 0);
+    }
+
+    #[test]
+    fn steps_cannot_begin_while_triggers_are_pending() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let card = creature_card(vec![Ability::Triggered(TriggeredAbility {
+            condition: TriggerCondition::EntersBattlefield(TriggerSubject::This),
+            intervening_if: None,
+            effects: vec![Effect::DealDamage(Quantity::Fixed(1))],
+        })]);
+        game.spawn_object(&card, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+
+        assert!(matches!(
+            game.begin_step(Step::Upkeep),
+            Err(EngineError::IllegalTiming(_))
+        ));
+        assert!(matches!(
+            game.next_turn(),
+            Err(EngineError::IllegalTiming(_))
+        ));
+        // The trigger needs a target to resolve.
+        assert!(matches!(
+            game.resolve_next_trigger(&[]),
+            Err(EngineError::InvalidTarget(_))
+        ));
+        assert!(game.begin_step(Step::Upkeep).is_ok());
+    }
+
+    #[test]
+    fn spawned_objects_share_their_card() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let card = creature_card(Vec::new());
+
+        let objects = (0..60)
+            .map(|_| {
+                game.spawn_object(&card, player, Zone::Library(player))
+                    .expect("Could not spawn the object.")
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(Arc::strong_count(&card), 61);
+        for object in objects {
+            let card_ref = game
+                .world_mut()
+                .get::<CardRef>(object)
+                .map(|it| Arc::clone(&it.0))
+                .expect("Could not access the card of the object.");
+            assert!(Arc::ptr_eq(&card_ref, &card));
+        }
+    }
+
+    /// Returns a game whose first player has a library of the specified number of cards, ordered
+    /// from top to bottom.
+    fn game_with_library(amount: usize) -> (Game, PlayerId, Vec<Entity>) {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let card = creature_card(Vec::new());
+        for _ in 0..amount {
+            game.spawn_object(&card, player, Zone::Library(player))
+                .expect("Could not spawn the object.");
+        }
+        let cards = game
+            .library(player)
+            .expect("Could not access the library.")
+            .cards()
+            .collect();
+        (game, player, cards)
+    }
+
+    #[test]
+    fn cards_put_on_top_of_the_library_are_drawn_next() {
+        let (mut game, player, cards) = game_with_library(3);
+
+        assert_eq!(game.draw_card(player), Ok(Some(cards[0])));
+        assert_eq!(
+            *game
+                .world_mut()
+                .get::<Zone>(cards[0])
+                .expect("Could not find the zone."),
+            Zone::Hand(player)
+        );
+
+        game.move_object(cards[0], Zone::Library(player))
+            .expect("Could not move the object.");
+        assert_eq!(game.draw_card(player), Ok(Some(cards[0])));
+        assert_eq!(game.draw_card(player), Ok(Some(cards[1])));
+        assert_eq!(game.draw_card(player), Ok(Some(cards[2])));
+        assert_eq!(game.draw_card(player), Ok(None));
+    }
+
+    #[test]
+    fn draw_effects_draw_from_the_top_of_the_library() {
+        let (mut game, player, cards) = game_with_library(3);
+        // When this creature enters the battlefield, draw two cards.
+        let card = creature_card(vec![Ability::Triggered(TriggeredAbility {
+            condition: TriggerCondition::EntersBattlefield(TriggerSubject::This),
+            intervening_if: None,
+            effects: vec![Effect::DrawCards(Quantity::Fixed(2))],
+        })]);
+        game.spawn_object(&card, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        assert!(game
+            .resolve_next_trigger(&[])
+            .expect("Could not resolve the trigger."));
+
+        let library = game.library(player).expect("Could not access the library.");
+        assert_eq!(library.cards().collect::<Vec<_>>(), vec![cards[2]]);
+        assert_eq!(
+            *game
+                .world_mut()
+                .get::<Zone>(cards[1])
+                .expect("Could not find the zone."),
+            Zone::Hand(player)
+        );
+    }
+
+    #[test]
+    fn milled_cards_are_put_into_the_graveyard_from_the_top() {
+        let (mut game, player, cards) = game_with_library(3);
+
+        assert_eq!(game.mill(player, 2), Ok(vec![cards[0], cards[1]]));
+        assert_eq!(
+            *game
+                .world_mut()
+                .get::<Zone>(cards[1])
+                .expect("Could not find the zone."),
+            Zone::Graveyard(player)
+        );
+        assert_eq!(game.mill(player, 5), Ok(vec![cards[2]]));
+        assert!(game
+            .library(player)
+            .expect("Could not access the library.")
+            .is_empty());
+    }
+
+    #[test]
+    fn scrying_reorders_the_top_of_the_library() {
+        let (mut game, player, cards) = game_with_library(4);
+
+        game.scry(player, 3, &mut |looked_at| {
+            assert_eq!(looked_at, &cards[..3]);
+            ScryChoice {
+                top: vec![cards[2], cards[0]],
+                bottom: vec![cards[1]],
+            }
+        })
+        .expect("Could not scry.");
+        assert_eq!(
+            game.library(player)
+                .expect("Could not access the library.")
+                .cards()
+                .collect::<Vec<_>>(),
+            vec![cards[2], cards[0], cards[3], cards[1]]
+        );
+
+        // Every looked at card must be put somewhere exactly once.
+        assert!(matches!(
+            game.scry(player, 2, &mut |_| ScryChoice {
+                top: vec![cards[2]],
+                bottom: Vec::new(),
+            }),
+            Err(EngineError::IllegalAction(_))
+        ));
+    }
+
+    #[test]
+    fn searching_finds_a_matching_card_and_shuffles() {
+        let (mut game, player, cards) = game_with_library(2);
+        let land = Arc::new(
+            Card::builder()
+                .type_line(TypeLine {
+                    card_type: [CardType::Land].into(),
+                    subtype: [].into(),
+                    supertype: [].into(),
+                })
+                .build()
+                .expect("Failed to build the card."),
+        );
+        let land = game
+            .spawn_object(&land, player, Zone::Library(player))
+            .expect("Could not spawn the object.");
+        let filter = ObjectFilter {
+            card_type: Some(CardType::Land),
+            you_control: false,
+            ..ObjectFilter::default()
+        };
+
+        assert!(matches!(
+            game.search_library(player, &filter, Zone::Hand(player), &mut |_| Some(cards[0])),
+            Err(EngineError::InvalidTarget(_))
+        ));
+        let found = game.search_library(player, &filter, Zone::Hand(player), &mut |candidates| {
+            assert_eq!(candidates, &[land]);
+            candidates.first().copied()
+        });
+        assert_eq!(found, Ok(Some(land)));
+        assert_eq!(
+            *game
+                .world_mut()
+                .get::<Zone>(land)
+                .expect("Could not find the zone."),
+            Zone::Hand(player)
+        );
+        assert_eq!(
+            game.library(player)
+                .expect("Could not access the library.")
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn later_effects_setting_power_and_toughness_win() {
+        let set = |game: &mut Game, target, value| {
+            let controller = game.turn().active_player;
+            game.add_effect(ContinuousEffect {
+                source: None,
+                controller,
+                duration: Duration::UntilEndOfTurn,
+                kind: EffectKind::SetPowerToughness {
+                    target,
+                    power: value,
+                    toughness: value,
+                },
+            });
+        };
+
+        let mut game = Game::new(2);
+        let creature = spawn_creature(&mut game, 2, 2);
+        set(&mut game, creature, 3);
+        set(&mut game, creature, 1);
+        assert_eq!(game.power_toughness(creature), Some((1, 1)));
+
+        let mut game = Game::new(2);
+        let creature = spawn_creature(&mut game, 2, 2);
+        set(&mut game, creature, 1);
+        set(&mut game, creature, 3);
+        assert_eq!(game.power_toughness(creature), Some((3, 3)));
+
+        // Modifications apply in a later layer regardless of their timestamp.
+        let controller = game.turn().active_player;
+        game.add_effect(ContinuousEffect {
+            source: None,
+            controller,
+            duration: Duration::UntilEndOfTurn,
+            kind: EffectKind::ModifyPowerToughness {
+                target: creature,
+                power: 1,
+                toughness: 0,
+            },
+        });
+        set(&mut game, creature, 5);
+        assert_eq!(game.power_toughness(creature), Some((6, 5)));
+    }
+
+    #[test]
+    fn objects_receive_a_new_timestamp_when_entering_a_zone() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let first = spawn_creature(&mut game, 1, 1);
+        let second = spawn_creature(&mut game, 1, 1);
+        let timestamp = |game: &Game, entity| {
+            game.timestamp(entity)
+                .expect("Could not access the timestamp.")
+        };
+
+        assert!(timestamp(&game, first) < timestamp(&game, second));
+        game.move_object(first, Zone::Graveyard(player))
+            .expect("Could not move the object.");
+        assert!(timestamp(&game, first) > timestamp(&game, second));
+    }
+
+    #[test]
+    fn object_ids_change_with_zones_and_keep_last_known_information() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let card = find_card_by_name("Llanowar Elves").expect("Could not find the card.");
+        let elves = game
+            .spawn_object(card, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let ObjectSnapshot { id, .. } = ObjectSnapshot::of(&game.world, elves);
+        let id = id.expect("Could not access the id of the object.");
+        assert_eq!(game.object(id), Some(elves));
+
+        game.move_object(elves, Zone::Graveyard(player))
+            .expect("Could not move the object.");
+        // The creature that died is gone, the card in the graveyard is a new object.
+        assert_eq!(game.object(id), None);
+        assert_eq!(
+            game.last_known_information(id).map(|it| it.name.as_str()),
+            Some("Llanowar Elves")
+        );
+        let new_id = game
+            .object_id(elves)
+            .expect("Could not access the id of the object.");
+        assert_ne!(new_id, id);
+        assert_eq!(game.object(new_id), Some(elves));
+        assert_eq!(game.previous_id(elves), Some(id));
+
+        game.despawn_object(elves)
+            .expect("Could not despawn the object.");
+        assert_eq!(game.object(new_id), None);
+        assert!(game.last_known_information(new_id).is_some());
+    }
+
+    #[test]
+    fn query_helpers_find_matching_permanents() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let opponent = game.players()[1].id;
+        let mut spawn = |name, owner, zone| {
+            let card = find_card_by_name(name).expect("Could not find the card.");
+            let entity = game
+                .spawn_object(card, owner, zone)
+                .expect("Could not spawn the object.");
+            (
+                entity,
+                game.object_id(entity).expect("Could not access the id."),
+            )
+        };
+
+        let (_, elves) = spawn("Llanowar Elves", player, Zone::Battlefield);
+        let (_, soulmender) = spawn("Soulmender", opponent, Zone::Battlefield);
+        let (tapped_forest, forest) = spawn("Forest", player, Zone::Battlefield);
+        let (_, untapped_forest) = spawn("Forest", player, Zone::Battlefield);
+        let (_, plains) = spawn("Plains", opponent, Zone::Battlefield);
+        let (_, lantern) = spawn("Soul-Guide Lantern", player, Zone::Battlefield);
+        spawn("Llanowar Elves", player, Zone::Hand(player));
+        let (_, spell) = spawn("Polukranos, Unchained", opponent, Zone::Stack);
+        game.world
+            .insert_one(tapped_forest, Tapped)
+            .expect("Could not tap the land.");
+
+        assert_eq!(game.creatures_controlled_by(player), vec![elves]);
+        assert_eq!(game.creatures_controlled_by(opponent), vec![soulmender]);
+        assert_eq!(
+            game.permanents_of_type(CardType::Land),
+            vec![forest, untapped_forest, plains]
+        );
+        assert_eq!(game.permanents_of_type(CardType::Artifact), vec![lantern]);
+        assert_eq!(
+            game.permanents_with_subtype(Subtype::Land(LandType::Basic(BasicLandType::Forest))),
+            vec![forest, untapped_forest]
+        );
+        assert_eq!(
+            game.permanents_with_subtype(Subtype::Creature(CreatureType::Elf)),
+            vec![elves]
+        );
+        assert_eq!(game.untapped_lands(player), vec![untapped_forest]);
+        assert_eq!(game.objects_on_stack(), vec![spell]);
+    }
+
+    #[test]
+    fn stolen_creatures_return_to_their_owner_after_the_turn() {
+        let mut game = Game::new(3);
+        let thief = game.turn().active_player;
+        let owner = game.players()[2].id;
+        let creature = game
+            .spawn_object(&creature_card(vec![]), owner, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        assert_eq!(game.controller(creature), Some(owner));
+        assert!(!game.can_attack(creature));
+
+        // Gain control of target creature until end of turn. Untap that creature.
+        game.gain_control(thief, creature, Duration::UntilEndOfTurn)
+            .expect("Could not gain control of the creature.");
+        assert_eq!(game.controller(creature), Some(thief));
+        game.begin_step(Step::DeclareAttackers)
+            .expect("Could not begin the step.");
+        assert!(matches!(
+            game.declare_attackers(thief, &[creature]),
+            Err(EngineError::IllegalAction(_))
+        ));
+
+        // It gains haste until end of turn.
+        game.add_effect(ContinuousEffect {
+            source: None,
+            controller: thief,
+            duration: Duration::UntilEndOfTurn,
+            kind: EffectKind::AddKeyword {
+                target: creature,
+                keyword: KeywordAbility::Haste,
+            },
+        });
+        game.declare_attackers(thief, &[creature])
+            .expect("Could not declare the attackers.");
+        assert!(game.world_mut().get::<Tapped>(creature).is_ok());
+
+        game.next_turn().expect("Could not begin the next turn.");
+        assert_eq!(game.controller(creature), Some(owner));
+        assert!(!game.has_keyword(creature, &KeywordAbility::Haste));
+        // The owner's untap step hasn't come around yet.
+        assert!(game.world_mut().get::<Tapped>(creature).is_ok());
+    }
+
+    #[test]
+    fn copies_acquire_copiable_values_only() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let original = spawn_creature(&mut game, 4, 4);
+        game.add_counters(
+            PermanentOrPlayer::Permanent(original),
+            CounterKind::PlusOnePlusOne,
+            1,
+        )
+        .expect("Could not add the counters.");
+        let clone = Card::builder()
+            .name(Name("Clone".into()))
+            .pt(PtCharacteristic {
+                power: PtValue::Fixed(0),
+                toughness: PtValue::Fixed(0),
+            })
+            .build()
+            .expect("Failed to build the card.");
+        let clone = game
+            .spawn_object(&Arc::new(clone), player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let name = |game: &mut Game, entity| {
+            game.world_mut()
+                .get::<CardRef>(entity)
+                .map(|it| it.0.name.0.clone())
+                .expect("Could not access the card.")
+        };
+
+        // You may have Clone enter the battlefield as a copy of any creature on the battlefield.
+        game.copy_permanent(clone, original)
+            .expect("Could not copy the permanent.");
+        assert_eq!(game.power_toughness(original), Some((5, 5)));
+        assert_eq!(game.power_toughness(clone), Some((4, 4)));
+        assert_eq!(name(&mut game, clone), name(&mut game, original));
+
+        game.move_object(original, Zone::Graveyard(player))
+            .expect("Could not move the object.");
+        assert_eq!(game.power_toughness(clone), Some((4, 4)));
+
+        game.move_object(clone, Zone::Graveyard(player))
+            .expect("Could not move the object.");
+        assert_eq!(name(&mut game, clone), "Clone");
+        assert_eq!(game.power_toughness(clone), Some((0, 0)));
+    }
+
+    #[test]
+    fn extra_turns_are_taken_directly_after_the_current_turn() {
+        let mut game = Game::new(3);
+        let [first, second, third] = [0, 1, 2].map(|it| game.players()[it].id);
+
+        // Take an extra turn after this one.
+        game.take_extra_turn(first)
+            .expect("Could not add the extra turn.");
+        game.take_extra_turn(third)
+            .expect("Could not add the extra turn.");
+        // The most recently created turn will be taken first.
+        assert_eq!(
+            game.upcoming_turns(5)
+                .expect("Could not list the upcoming turns."),
+            vec![third, first, second, third, first]
+        );
+
+        game.next_turn().expect("Could not begin the next turn.");
+        game.next_turn().expect("Could not begin the next turn.");
+        assert_eq!(game.turn().active_player, first);
+        assert_eq!(game.turn().number, 3);
+        game.next_turn().expect("Could not begin the next turn.");
+        assert_eq!(game.turn().active_player, second);
+    }
+
+    #[test]
+    fn skipped_turns_consume_extra_turns() {
+        let mut game = Game::new(2);
+        let [player, opponent] = [0, 1].map(|it| game.players()[it].id);
+
+        game.skip_next_turn(opponent)
+            .expect("Could not skip the turn.");
+        assert_eq!(game.upcoming_turns(3), Ok(vec![player, opponent, player]));
+
+        game.take_extra_turn(player)
+            .expect("Could not add the extra turn.");
+        game.skip_next_turn(player)
+            .expect("Could not skip the turn.");
+        // The extra turn is skipped, then the opponent skips their turn as well.
+        assert_eq!(game.upcoming_turns(2), Ok(vec![player, opponent]));
+        game.next_turn().expect("Could not begin the next turn.");
+        assert_eq!(game.turn().active_player, player);
+        assert_eq!(game.turn().number, 2);
+    }
+
+    #[test]
+    fn additional_combat_phases_follow_the_current_main_phase() {
+        let mut game = Game::new(2);
+        let player = game.turn().active_player;
+        let creature = game
+            .spawn_object(&creature_card(vec![]), player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        game.world_mut()
+            .remove_one::<SummoningSick>(creature)
+            .expect("Could not remove the summoning sickness.");
+        let advance_to = |game: &mut Game, step| {
+            while game.turn().step != step {
+                game.advance_step()
+                    .expect("Could not advance the step.")
+                    .expect("The turn ended unexpectedly.");
+            }
+        };
+
+        advance_to(&mut game, Step::DeclareAttackers);
+        game.declare_attackers(player, &[creature])
+            .expect("Could not declare the attackers.");
+        assert!(matches!(
+            game.declare_attackers(player, &[creature]),
+            Err(EngineError::IllegalAction(_))
+        ));
+
+        // Untap all creatures that attacked this turn. After this main phase, there is an
+        // additional combat phase followed by an additional main phase.
+        advance_to(&mut game, Step::PostcombatMain);
+        assert!(game.world_mut().get::<Attacking>(creature).is_err());
+        game.untap_attackers()
+            .expect("Could not untap the attackers.");
+        game.add_combat_phase();
+        assert_eq!(
+            game.turn().schedule().collect::<Vec<_>>(),
+            vec![
+                Step::BeginningOfCombat,
+                Step::DeclareAttackers,
+                Step::DeclareBlockers,
+                Step::CombatDamage,
+                Step::EndOfCombat,
+                Step::PostcombatMain,
+                Step::End,
+                Step::Cleanup,
+            ]
+        );
+
+        advance_to(&mut game, Step::DeclareAttackers);
+        game.declare_attackers(player, &[creature])
+            .expect("Could not declare the attackers.");
+        advance_to(&mut game, Step::Cleanup);
+        assert_eq!(game.advance_step(), Ok(None));
+        assert_eq!(game.turn().tracker.attackers().count(), 1);
+        game.next_turn().expect("Could not begin the next turn.");
+    }
+
+    #[test]
+    fn phased_out_permanents_are_treated_as_though_they_do_not_exist() {
+        let mut game = Game::new(2);
+        let player = game.turn().active_player;
+        let creature = game
+            .spawn_object(&creature_card(vec![]), player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let other_creature = game
+            .spawn_object(&creature_card(vec![]), player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let aura = Card::builder()
+            .type_line(TypeLine {
+                card_type: [CardType::Enchantment].into(),
+                subtype: [].into(),
+                supertype: [].into(),
+            })
+            .build()
+            .expect("Failed to build the card.");
+        let aura = game
+            .spawn_object(&Arc::new(aura), player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        game.attach(aura, creature)
+            .expect("Could not attach the aura.");
+        game.add_counters(
+            PermanentOrPlayer::Permanent(creature),
+            CounterKind::PlusOnePlusOne,
+            1,
+        )
+        .expect("Could not add the counters.");
+        game.world_mut()
+            .insert_one(creature, Tapped)
+            .expect("Could not tap the creature.");
+
+        // Target creature phases out.
+        game.phase_out(creature)
+            .expect("Could not phase out the creature.");
+        assert!(game.world_mut().get::<PhasedOut>(aura).is_ok());
+        assert_eq!(game.permanents_of_type(CardType::Creature).len(), 1);
+        assert_eq!(game.permanents_of_type(CardType::Enchantment).len(), 0);
+
+        // Destroy all creatures.
+        game.destroy_all(
+            &ObjectFilter {
+                card_type: Some(CardType::Creature),
+                you_control: false,
+                ..ObjectFilter::default()
+            },
+            player,
+        )
+        .expect("Could not destroy the creatures.");
+        assert_eq!(
+            game.zones.zone_of(other_creature),
+            Some(Zone::Graveyard(player))
+        );
+        assert_eq!(game.zones.zone_of(creature), Some(Zone::Battlefield));
+
+        game.next_turn().expect("Could not begin the next turn.");
+        assert!(game.world_mut().get::<PhasedOut>(creature).is_ok());
+        game.next_turn().expect("Could not begin the next turn.");
+        assert!(game.world_mut().get::<PhasedOut>(creature).is_err());
+        assert!(game.world_mut().get::<PhasedOut>(aura).is_err());
+        assert!(game.world_mut().get::<Tapped>(creature).is_err());
+        assert_eq!(
+            game.world_mut()
+                .get::<AttachedTo>(aura)
+                .expect("Could not find the attachment.")
+                .0,
+            creature
+        );
+        assert_eq!(game.power_toughness(creature), Some((2, 2)));
+    }
+
+    #[test]
+    fn conceding_during_combat_ends_the_game_immediately() {
+        let mut game = Game::new(2);
+        let [player, opponent] = [0, 1].map(|it| game.players()[it].id);
+        let creature = game
+            .spawn_object(&creature_card(vec![]), player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let attacker = game
+            .spawn_object(&creature_card(vec![]), opponent, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        game.next_turn().expect("Could not begin the next turn.");
+        game.begin_step(Step::DeclareAttackers)
+            .expect("Could not begin the step.");
+        game.declare_attackers(opponent, &[attacker])
+            .expect("Could not declare the attackers.");
+
+        game.concede(player).expect("Could not concede the game.");
+        assert_eq!(game.outcome(), Some(GameOutcome::Win(opponent)));
+        assert_eq!(
+            game.events()[game.events().len() - 2..],
+            [
+                GameEvent::PlayerConceded { player },
+                GameEvent::GameEnded {
+                    outcome: GameOutcome::Win(opponent)
+                },
+            ]
+        );
+        // All objects owned by the player leave the game.
+        assert!(!game.world_mut().contains(creature));
+        assert!(matches!(
+            game.begin_step(Step::DeclareBlockers),
+            Err(EngineError::IllegalAction(_))
+        ));
+        assert!(matches!(
+            game.concede(opponent),
+            Err(EngineError::IllegalAction(_))
+        ));
+    }
+
+    #[test]
+    fn unbreakable_loops_of_mandatory_triggers_end_in_a_draw() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        // When this creature enters the battlefield, exile it, then return it to the battlefield
+        // under its owner’s control.
+        let card = creature_card(vec![Ability::Triggered(TriggeredAbility {
+            condition: TriggerCondition::EntersBattlefield(TriggerSubject::This),
+            intervening_if: None,
+            effects: vec![Effect::Blink],
+        })]);
+        game.spawn_object(&card, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        game.spawn_object(&card, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        game.set_loop_threshold(5);
+
+        game.resolve_mandatory_triggers()
+            .expect("Could not resolve the triggers.");
+        assert_eq!(game.outcome(), Some(GameOutcome::Draw));
+        let zone_changes = game
+            .events()
+            .iter()
+            .filter(|it| matches!(it, GameEvent::ZoneChanged { .. }))
+            .count();
+        assert!(zone_changes < 30);
+    }
+
+    #[test]
+    fn loops_that_keep_adding_counters_dont_end_in_a_draw() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        // When this creature enters the battlefield, if you have nine or fewer experience counters,
+        // you get an experience counter, then exile it and return it to the battlefield under its
+        // owner’s control.
+        let card = creature_card(vec![Ability::Triggered(TriggeredAbility {
+            condition: TriggerCondition::EntersBattlefield(TriggerSubject::This),
+            intervening_if: Some(InterveningIf::Quantity(
+                Quantity::CountersOnYou(CounterKind::Experience),
+                Comparison::AtMost(9),
+            )),
+            effects: vec![
+                Effect::GetCounters(CounterKind::Experience, 1),
+                Effect::Blink,
+            ],
+        })]);
+        game.spawn_object(&card, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        game.set_loop_threshold(3);
+
+        game.resolve_mandatory_triggers()
+            .expect("Could not resolve the triggers.");
+        assert_eq!(game.outcome(), None);
+        assert_eq!(
+            game.counters(PermanentOrPlayer::Player(player), &CounterKind::Experience),
+            Ok(10)
+        );
+    }
+
+    #[test]
+    fn apnap_order_rotates_with_the_active_player() {
+        let mut game = Game::new(4);
+        let [first, second, third, fourth] = [0, 1, 2, 3].map(|it| game.players()[it].id);
+
+        assert_eq!(
+            game.apnap_order().expect("Could not order the players."),
+            vec![first, second, third, fourth]
+        );
+        game.next_turn().expect("Could not begin the next turn.");
+        assert_eq!(
+            game.apnap_order().expect("Could not order the players."),
+            vec![second, third, fourth, first]
+        );
+        game.next_turn().expect("Could not begin the next turn.");
+        game.next_turn().expect("Could not begin the next turn.");
+        assert_eq!(
+            game.apnap_order().expect("Could not order the players."),
+            vec![fourth, first, second, third]
+        );
+
+        // Players that have left the game make no more choices.
+        game.concede(second).expect("Could not concede the game.");
+        assert_eq!(
+            game.apnap_order().expect("Could not order the players."),
+            vec![fourth, first, third]
+        );
+        game.next_turn().expect("Could not begin the next turn.");
+        assert_eq!(
+            game.apnap_order().expect("Could not order the players."),
+            vec![first, third, fourth]
+        );
+    }
+
+    #[test]
+    fn statistics_count_what_happened_during_the_game() {
+        let deck = || {
+            Deck::from(&[("Forest", 20), ("Llanowar Elves", 20)])
+                .expect("Could not build the deck.")
+        };
+        let (mut game, players) = GameBuilder::new()
+            .seed(3)
+            .player("Player 1", deck())
+            .player("Player 2", deck())
+            .starting_player(0)
+            .build()
+            .expect("Could not build the game.");
+        let [player, opponent] = [players[0], players[1]];
+
+        game.begin_step(Step::PrecombatMain)
+            .expect("Could not begin the step.");
+        game.spawn_object(
+            find_card_by_name("Forest").expect("Could not find the card."),
+            player,
+            Zone::Battlefield,
+        )
+        .expect("Could not spawn the object.");
+        let elves = game
+            .spawn_object(
+                find_card_by_name("Llanowar Elves").expect("Could not find the card."),
+                player,
+                Zone::Hand(player),
+            )
+            .expect("Could not spawn the object.");
+        game.cast_spell(player, elves, None)
+            .expect("Could not cast the spell.");
+        game.resolve_spell(elves, &[])
+            .expect("Could not resolve the spell.");
+        game.deal_damage(&Damage {
+            source: elves,
+            recipient: PermanentOrPlayer::Player(opponent),
+            amount: 1,
+            combat: false,
+        })
+        .expect("Could not deal the damage.");
+        game.next_turn().expect("Could not begin the next turn.");
+        // Both players pass priority in the upkeep and draw steps before the opponent stops at
+        // their main phase, having drawn a card for the turn.
+        assert_eq!(game.run_until_stop(), Ok(Stop::Priority(opponent)));
+
+        let stats = game.stats();
+        assert_eq!(stats.turns(), 2);
+        assert_eq!(stats.turns_taken_by(player), 1);
+        assert_eq!(stats.turns_taken_by(opponent), 1);
+        assert_eq!(stats.cards_drawn_by(player), 7);
+        assert_eq!(stats.cards_drawn_by(opponent), 8);
+        assert_eq!(stats.damage_dealt_by(player), 1);
+        assert_eq!(stats.damage_dealt_by(opponent), 0);
+        assert_eq!(stats.spells_cast(CardType::Creature), 1);
+        assert_eq!(stats.spells_cast(CardType::Instant), 0);
+        assert_eq!(stats.mana_spent_by(player), 1);
+        assert_eq!(stats.mana_spent_by(opponent), 0);
+        // The hand sizes are sampled as turns begin after the first one.
+        assert_eq!(stats.average_hand_size(opponent), Some(7.0));
+        assert_eq!(stats.average_hand_size(player), None);
+        assert_eq!(stats.priority_passes(), 4);
+    }
+
+    #[test]
+    fn default_priority_stops_ask_for_input_at_the_main_phases() {
+        let mut game = Game::new(2);
+        let [player, opponent] = [0, 1].map(|it| game.players()[it].id);
+        for owner in [player, opponent] {
+            for _ in 0..2 {
+                game.spawn_object(&vanilla_creature(1, 1), owner, Zone::Library(owner))
+                    .expect("Could not spawn the object.");
+            }
+        }
+
+        let mut inputs = Vec::new();
+        while game.turn().number == 1 {
+            let stop = game.run_until_stop().expect("Could not run the game.");
+            inputs.push((stop, game.turn().number, game.turn().step));
+        }
+        assert_eq!(
+            inputs,
+            vec![
+                (Stop::Priority(player), 1, Step::PrecombatMain),
+                (Stop::Priority(player), 1, Step::PostcombatMain),
+                (Stop::Priority(opponent), 2, Step::PrecombatMain),
+            ]
+        );
+
+        game.set_priority_stops(player, IndexSet::new())
+            .expect("Could not set the priority stops.");
+        game.set_priority_stops(
+            opponent,
+            [PriorityStop {
+                step: Step::End,
+                own_turn: false,
+            }]
+            .into(),
+        )
+        .expect("Could not set the priority stops.");
+        assert_eq!(game.run_until_stop(), Ok(Stop::Priority(opponent)));
+        assert_eq!(game.turn().number, 3);
+        assert_eq!(game.turn().step, Step::End);
+    }
+
+    #[test]
+    fn mana_costs_are_paid_by_tapping_the_right_lands() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let tap_for = |colors: &[Color]| {
+            let abilities = colors
+                .iter()
+                .map(|&color| {
+                    Ability::Activated(ActivatedAbility {
+                        cost: vec![ActivationCost::Tap],
+                        effects: vec![Effect::AddMana(Mana::Monocolored(color))],
+                    })
+                })
+                .collect();
+            let card = Card::builder()
+                .abilities(abilities)
+                .build()
+                .expect("Failed to build the card.");
+            Arc::new(card)
+        };
+        // {T}: Add {W} or {U}.
+        let dual = game
+            .spawn_object(
+                &tap_for(&[Color::White, Color::Blue]),
+                player,
+                Zone::Battlefield,
+            )
+            .expect("Could not spawn the object.");
+        let plains = game
+            .spawn_object(
+                find_card_by_name("Plains").expect("Could not find the card."),
+                player,
+                Zone::Battlefield,
+            )
+            .expect("Could not spawn the object.");
+        let cost = ManaCost(vec![
+            ManaSymbol::Colored(Color::White),
+            ManaSymbol::Colored(Color::Blue),
+        ]);
+
+        let greedy = PaymentPlan {
+            tapped: vec![
+                (dual, Mana::Monocolored(Color::White)),
+                (plains, Mana::Monocolored(Color::White)),
+            ],
+            spent: vec![Mana::Monocolored(Color::White); 2],
+            ..PaymentPlan::default()
+        };
+        assert!(matches!(
+            game.pay_mana_cost(player, &cost, None, Some(greedy)),
+            Err(EngineError::PaymentFailed(_))
+        ));
+        assert!(game.world_mut().get::<Tapped>(dual).is_err());
+
+        game.pay_mana_cost(player, &cost, None, None)
+            .expect("Could not pay the mana cost.");
+        assert!(game.world_mut().get::<Tapped>(dual).is_ok());
+        assert!(game.world_mut().get::<Tapped>(plains).is_ok());
+        assert!(game
+            .player(player)
+            .expect("Could not access the player.")
+            .mana_pool
+            .mana()
+            .is_empty());
+        assert_eq!(game.plan_payment(player, &cost, None), Ok(None));
+    }
+
+    /// Spawns a spell with the mana cost and keyword in the hand of the player.
+    fn spell_in_hand(
+        game: &mut Game,
+        player: PlayerId,
+        cost: Vec<ManaSymbol>,
+        keyword: KeywordAbility,
+    ) -> Entity {
+        let card = Card::builder()
+            .mana_cost(ManaCost(cost))
+            .abilities(vec![Ability::Keyword(keyword)])
+            .build()
+            .expect("Failed to build the card.");
+        game.spawn_object(&Arc::new(card), player, Zone::Hand(player))
+            .expect("Could not spawn the object.")
+    }
+
+    #[test]
+    fn creatures_are_tapped_for_convoke() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let forest = find_card_by_name("Forest").expect("Could not find the card.");
+        let creatures = (0..4)
+            .map(|_| {
+                game.spawn_object(&creature_card(vec![]), player, Zone::Battlefield)
+                    .expect("Could not spawn the object.")
+            })
+            .collect::<Vec<_>>();
+        let forests = (0..2)
+            .map(|_| {
+                game.spawn_object(forest, player, Zone::Battlefield)
+                    .expect("Could not spawn the object.")
+            })
+            .collect::<Vec<_>>();
+        // {4}{G}{G}
+        let cost = vec![
+            ManaSymbol::Generic(4),
+            ManaSymbol::Colored(Color::Green),
+            ManaSymbol::Colored(Color::Green),
+        ];
+        let spell = spell_in_hand(&mut game, player, cost.clone(), KeywordAbility::Convoke);
+        let cost = ManaCost(cost);
+
+        // Without convoke, two Forests don't suffice.
+        assert_eq!(game.plan_payment(player, &cost, None), Ok(None));
+        let plan = game
+            .plan_payment(player, &cost, Some(spell))
+            .expect("Could not plan the payment.")
+            .expect("Could not find a payment.");
+        assert_eq!(plan.convoked, creatures);
+        assert_eq!(plan.tapped.len(), 2);
+
+        game.pay_mana_cost(player, &cost, Some(spell), Some(plan))
+            .expect("Could not pay the mana cost.");
+        for entity in creatures.into_iter().chain(forests) {
+            assert!(game.world_mut().get::<Tapped>(entity).is_ok());
+        }
+        assert!(game
+            .player(player)
+            .expect("Could not access the player.")
+            .mana_pool
+            .mana()
+            .is_empty());
+    }
+
+    #[test]
+    fn cards_in_the_graveyard_are_exiled_for_delve() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let forest = find_card_by_name("Forest").expect("Could not find the card.");
+        let land = game
+            .spawn_object(forest, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let graveyard = (0..2)
+            .map(|_| {
+                game.spawn_object(forest, player, Zone::Graveyard(player))
+                    .expect("Could not spawn the object.")
+            })
+            .collect::<Vec<_>>();
+        // {3}{G}
+        let cost = vec![ManaSymbol::Generic(3), ManaSymbol::Colored(Color::Green)];
+        let spell = spell_in_hand(&mut game, player, cost.clone(), KeywordAbility::Delve);
+        let cost = ManaCost(cost);
+
+        // Two cards in the graveyard aren't enough and nothing is paid.
+        assert!(matches!(
+            game.pay_mana_cost(player, &cost, Some(spell), None),
+            Err(EngineError::PaymentFailed(_))
+        ));
+        assert!(game.world_mut().get::<Tapped>(land).is_err());
+        assert_eq!(
+            game.zones.zone_of(graveyard[0]),
+            Some(Zone::Graveyard(player))
+        );
+
+        let card = game
+            .spawn_object(forest, player, Zone::Graveyard(player))
+            .expect("Could not spawn the object.");
+        game.pay_mana_cost(player, &cost, Some(spell), None)
+            .expect("Could not pay the mana cost.");
+        assert!(game.world_mut().get::<Tapped>(land).is_ok());
+        for entity in graveyard.into_iter().chain([card]) {
+            assert_eq!(game.zones.zone_of(entity), Some(Zone::Exile));
+        }
+        assert_eq!(game.zones.zone_of(spell), Some(Zone::Hand(player)));
+    }
+
+    #[test]
+    fn mana_burn_costs_life_for_unused_mana() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let float_mana = |game: &mut Game| {
+            let mana_pool = &mut game
+                .player_mut(player)
+                .expect("Could not access the player.")
+                .mana_pool;
+            mana_pool.add(Mana::Monocolored(Color::Green));
+            mana_pool.add(Mana::Colorless);
+        };
+        let life = |game: &Game| {
+            game.player(player)
+                .expect("Could not access the player.")
+                .life
+        };
+
+        float_mana(&mut game);
+        game.begin_step(Step::Upkeep)
+            .expect("Could not begin the step.");
+        assert_eq!(life(&game), 20);
+
+        game.set_mana_burn(true);
+        float_mana(&mut game);
+        game.begin_step(Step::Draw)
+            .expect("Could not begin the step.");
+        assert_eq!(life(&game), 18);
+        assert!(game.events().contains(&GameEvent::LifeLost {
+            player,
+            amount: 2,
+            life: 18,
+        }));
+        // Empty mana pools don't cost any life.
+        game.begin_step(Step::PrecombatMain)
+            .expect("Could not begin the step.");
+        assert_eq!(life(&game), 18);
+    }
+
+    thread_local! {
+        /// The messages logged on the current thread, which keeps tests running in parallel apart.
+        static LOGGED: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+
+    /// Records every message logged on a thread for inspection by the test running on it.
+    struct CapturingLogger;
+
+    impl log::Log for CapturingLogger {
+        fn enabled(&self, _: &log::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record<'_>) {
+            LOGGED.with(|it| it.borrow_mut().push(record.args().to_string()));
+        }
+
+        fn flush(&self) {}
+    }
+
+    /// Returns the messages logged while running the function.
+    fn capture_logs(run: impl FnOnce()) -> Vec<String> {
+        static LOGGER: CapturingLogger = CapturingLogger;
+        static INSTALL: Once = Once::new();
+        INSTALL.call_once(|| {
+            log::set_logger(&LOGGER).expect("Could not install the logger.");
+            log::set_max_level(log::LevelFilter::Trace);
+        });
+        LOGGED.with(|it| it.borrow_mut().clear());
+        run();
+        LOGGED.with(RefCell::take)
+    }
+
+    #[test]
+    fn engine_internals_are_traced() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let forest = game
+            .spawn_object(
+                find_card_by_name("Forest").expect("Could not find the card."),
+                player,
+                Zone::Hand(player),
+            )
+            .expect("Could not spawn the object.");
+
+        let logged = capture_logs(|| {
+            game.begin_step(Step::PrecombatMain)
+                .expect("Could not begin the step.");
+            game.move_object(forest, Zone::Battlefield)
+                .expect("Could not move the object.");
+            game.pay_mana_cost(
+                player,
+                &ManaCost(vec![ManaSymbol::Colored(Color::Green)]),
+                None,
+                None,
+            )
+            .expect("Could not pay the mana cost.");
+            game.next_turn().expect("Could not begin the next turn.");
+        });
+        assert_eq!(
+            logged,
+            vec![
+                "Step PrecombatMain of turn 1 begins.".to_string(),
+                format!("{forest:?} moves from Hand(PlayerId(0)) to Battlefield."),
+                format!(
+                    "Player with id 0 pays {{G}} by tapping [({forest:?}, Monocolored(Green))], \
+                     convoking [] and delving []."
+                ),
+                "Layer 2 is applied.".into(),
+                "Layer 2 is applied.".into(),
+                "Turn 2 of player with id 1 begins.".into(),
+            ]
+        );
+    }
+
+    #[test]
+    fn events_sent_to_players_hide_cards_they_cannot_see() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let opponent = game.players()[1].id;
+        let forest = find_card_by_name("Forest").expect("Could not find the card.");
+        game.spawn_object(forest, player, Zone::Hand(player))
+            .expect("Could not spawn the object.");
+        game.spawn_object(forest, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+
+        let json = |player| {
+            serde_json::to_string(&game.events_for(player))
+                .expect("Could not serialize the events.")
+        };
+        assert_eq!(json(Some(player)).matches("Forest").count(), 2);
+        assert_eq!(json(Some(opponent)).matches("Forest").count(), 1);
+        assert_eq!(json(None).matches("Forest").count(), 1);
+        assert_eq!(
+            serde_json::to_value(&game.events_for(Some(opponent))[0])
+                .expect("Could not serialize the event."),
+            serde_json::json!({
+                "ZoneChanged": {
+                    "object": { "id": null, "name": "a card" },
+                    "owner": 0,
+                    "from": null,
+                    "to": { "Hand": 0 },
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn seeded_games_shuffle_libraries_reproducibly() {
+        let library_order = |seed| {
+            let mut game = Game::new(2);
+            game.set_seed(seed);
+            let players = game.players().iter().map(|it| it.id).collect::<Vec<_>>();
+            let deck = || {
+                Deck::from(&[("Forest", 20), ("Llanowar Elves", 20)])
+                    .expect("Could not build the deck.")
+            };
+            game.start(&[(players[0], deck()), (players[1], deck())].into())
+                .expect("Could not start the game.");
+            players
+                .iter()
+                .flat_map(|&player| {
+                    game.library(player)
+                        .expect("Could not access the library.")
+                        .cards()
+                        .collect::<Vec<_>>()
+                })
+                .map(|it| {
+                    let card = game
+                        .world
+                        .get::<CardRef>(it)
+                        .expect("Could not find the card.");
+                    card.0.name.0.clone()
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(library_order(42), library_order(42));
+        assert_ne!(library_order(42), library_order(43));
+    }
+
+    #[test]
+    fn the_starting_player_can_be_chosen_before_the_game_starts() {
+        let mut game = Game::new(2);
+        let opponent = game.players()[1].id;
+        game.set_starting_player(opponent)
+            .expect("Could not choose the starting player.");
+        assert_eq!(game.turn().active_player, opponent);
+
+        game.begin_step(Step::Upkeep)
+            .expect("Could not begin the step.");
+        assert!(matches!(
+            game.set_starting_player(opponent),
+            Err(EngineError::IllegalTiming(_))
+        ));
+    }
+
+    #[test]
+    fn games_start_with_the_printings_chosen_for_the_decks() {
+        let mut game = Game::new(2);
+        let players = game.players().iter().map(|it| it.id).collect::<Vec<_>>();
+        let mut plains =
+            Card::clone(find_card_by_name("Plains").expect("Could not find the card."));
+        plains.expansion_symbol.set = "M21".into();
+        plains.collector_number = CollectorNumber(260);
+        let mut chosen = Deck::from(&[("Plains", 20)]).expect("Could not build the deck.");
+        chosen.replace_printing(&Arc::new(plains));
+        let default = Deck::from(&[("Plains", 20)]).expect("Could not build the deck.");
+        game.start(&[(players[0], chosen), (players[1], default)].into())
+            .expect("Could not start the game.");
+
+        let sets = |player| {
+            game.zones
+                .library(player)
+                .expect("Could not access the library.")
+                .cards()
+                .map(|it| {
+                    let card = game
+                        .world
+                        .get::<CardRef>(it)
+                        .expect("Could not find the card.");
+                    card.0.expansion_symbol.set.clone()
+                })
+                .collect::<HashSet<_>>()
+        };
+        assert_eq!(sets(players[0]), HashSet::from(["M21".to_owned()]));
+        assert_eq!(sets(players[1]), HashSet::from(["THB".to_owned()]));
+    }
+
+    #[test]
+    fn games_built_from_the_same_seed_spawn_and_shuffle_identically() {
+        let build = || {
+            let mut game = Game::new(2);
+            game.set_seed(2022);
+            let players = game.players().iter().map(|it| it.id).collect::<Vec<_>>();
+            let forests = Deck::from(&[("Forest", 20)]).expect("Could not build the deck.");
+            let elves = Deck::from(&[("Llanowar Elves", 20)]).expect("Could not build the deck.");
+            game.start(&[(players[0], forests), (players[1], elves)].into())
+                .expect("Could not start the game.");
+            let describe = |entity| {
+                let id = *game
+                    .world
+                    .get::<ObjectId>(entity)
+                    .expect("Could not find the object id.");
+                let card = game
+                    .world
+                    .get::<CardRef>(entity)
+                    .expect("Could not find the card.");
+                (id, card.0.name.0.clone())
+            };
+            let mut spawned = game
+                .world
+                .query::<()>()
+                .iter()
+                .map(|(entity, ())| describe(entity))
+                .collect::<Vec<_>>();
+            spawned.sort();
+            let libraries = players
+                .iter()
+                .map(|&player| {
+                    game.library(player)
+                        .expect("Could not access the library.")
+                        .cards()
+                        .map(describe)
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            (spawned, libraries)
+        };
+
+        let (spawned, libraries) = build();
+        // The first player's deck is always spawned first.
+        assert!(spawned[..20].iter().all(|(_, name)| name == "Forest"));
+        assert_eq!((spawned, libraries), build());
+    }
+
+    /// Returns a 4/4 Beast with reach, trample and “Mutate {1}{G}{G}” in the hand of the player
+    /// and adds enough mana to cast it for its mutate cost.
+    fn mutating_spell_in_hand(game: &mut Game, player: PlayerId) -> Entity {
+        let card = Card::builder()
+            .name(Name("Gemrazer".into()))
+            .type_line(TypeLine {
+                card_type: [CardType::Creature].into(),
+                subtype: [Subtype::Creature(CreatureType::Beast)].into(),
+                supertype: [].into(),
+            })
+            .pt(PtCharacteristic {
+                power: PtValue::Fixed(4),
+                toughness: PtValue::Fixed(4),
+            })
+            .abilities(vec![
+                Ability::Keyword(KeywordAbility::Reach),
+                Ability::Keyword(KeywordAbility::Trample),
+                Ability::Keyword(KeywordAbility::Mutate(ManaCost(vec![
+                    ManaSymbol::Generic(1),
+                    ManaSymbol::Colored(Color::Green),
+                    ManaSymbol::Colored(Color::Green),
+                ]))),
+            ])
+            .build()
+            .expect("Failed to build the card.");
+        let mana_pool = &mut game
+            .player_mut(player)
+            .expect("Could not access the player.")
+            .mana_pool;
+        for _ in 0..3 {
+            mana_pool.add(Mana::Monocolored(Color::Green));
+        }
+        game.spawn_object(&Arc::new(card), player, Zone::Hand(player))
+            .expect("Could not spawn the object.")
+    }
+
+    #[test]
+    fn mutating_on_top_merges_the_characteristics_until_the_permanent_dies() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let elves = find_card_by_name("Llanowar Elves").expect("Could not find the card.");
+        let elves = game
+            .spawn_object(elves, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let gemrazer = mutating_spell_in_hand(&mut game, player);
+
+        game.cast_mutating_spell(player, gemrazer, elves, None)
+            .expect("Could not cast the spell.");
+        assert_eq!(game.zones.zone_of(gemrazer), Some(Zone::Stack));
+        game.resolve_mutating_spell(gemrazer, true)
+            .expect("Could not resolve the spell.");
+        game.debug_assert_zones_consistent();
+
+        // The merged permanent is still the same object as the creature it merged with.
+        assert_eq!(game.zones.zone_of(gemrazer), None);
+        assert_eq!(game.object_id(gemrazer), None);
+        assert_eq!(game.creatures_controlled_by(player).len(), 1);
+        assert_eq!(ObjectSnapshot::of(&game.world, elves).name, "Gemrazer");
+        assert_eq!(game.power_toughness(elves), Some((4, 4)));
+        assert!(game.has_keyword(elves, &KeywordAbility::Trample));
+        assert!(game.has_subtype(elves, Subtype::Creature(CreatureType::Beast)));
+        assert_eq!(
+            game.world
+                .get::<ActivatedAbilities>(elves)
+                .expect("Could not find the abilities.")
+                .0
+                .len(),
+            1
+        );
+
+        game.destroy(elves)
+            .expect("Could not destroy the permanent.");
+        game.debug_assert_zones_consistent();
+        assert_eq!(game.zones.zone_of(elves), Some(Zone::Graveyard(player)));
+        assert_eq!(game.zones.zone_of(gemrazer), Some(Zone::Graveyard(player)));
+        assert_eq!(
+            ObjectSnapshot::of(&game.world, elves).name,
+            "Llanowar Elves"
+        );
+        assert_eq!(ObjectSnapshot::of(&game.world, gemrazer).name, "Gemrazer");
+        assert!(!game.has_keyword(elves, &KeywordAbility::Trample));
+        assert_eq!(game.objects_in(Zone::Graveyard(player), |_| true).len(), 2);
+    }
+
+    #[test]
+    fn mutating_spells_need_a_legal_target() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let opponent = game.players()[1].id;
+        let elves = find_card_by_name("Llanowar Elves").expect("Could not find the card.");
+        let human = Card::builder()
+            .type_line(TypeLine {
+                card_type: [CardType::Creature].into(),
+                subtype: [Subtype::Creature(CreatureType::Human)].into(),
+                supertype: [].into(),
+            })
+            .build()
+            .expect("Failed to build the card.");
+        let human = game
+            .spawn_object(&Arc::new(human), player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let foreign = game
+            .spawn_object(elves, opponent, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let elves = game
+            .spawn_object(elves, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let gemrazer = mutating_spell_in_hand(&mut game, player);
+
+        for target in [human, foreign] {
+            assert!(matches!(
+                game.cast_mutating_spell(player, gemrazer, target, None),
+                Err(EngineError::InvalidTarget(_))
+            ));
+        }
+
+        // Mutating under the creature only adds the abilities.
+        game.cast_mutating_spell(player, gemrazer, elves, None)
+            .expect("Could not cast the spell.");
+        game.resolve_mutating_spell(gemrazer, false)
+            .expect("Could not resolve the spell.");
+        assert_eq!(
+            ObjectSnapshot::of(&game.world, elves).name,
+            "Llanowar Elves"
+        );
+        assert_eq!(game.power_toughness(elves), Some((1, 1)));
+        assert!(game.has_keyword(elves, &KeywordAbility::Reach));
+
+        // A spell whose target has left the battlefield enters the battlefield on its own.
+        let other = mutating_spell_in_hand(&mut game, player);
+        game.cast_mutating_spell(player, other, elves, None)
+            .expect("Could not cast the spell.");
+        game.move_object(elves, Zone::Hand(player))
+            .expect("Could not move the object.");
+        game.resolve_mutating_spell(other, true)
+            .expect("Could not resolve the spell.");
+        game.debug_assert_zones_consistent();
+        assert_eq!(game.zones.zone_of(other), Some(Zone::Battlefield));
+        assert_eq!(game.zones.zone_of(gemrazer), Some(Zone::Hand(player)));
+    }
+
+    #[test]
+    fn objects_exiled_until_the_source_leaves_return_as_new_objects() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let opponent = game.players()[1].id;
+        let ring = Card::builder()
+            .name(Name("Oblivion Ring".into()))
+            .type_line(TypeLine {
+                card_type: [CardType::Enchantment].into(),
+                subtype: [].into(),
+                supertype: [].into(),
+            })
+            .build()
+            .expect("Failed to build the card.");
+        let ring = game
+            .spawn_object(&Arc::new(ring), player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let elves = find_card_by_name("Llanowar Elves").expect("Could not find the card.");
+        let elves = game
+            .spawn_object(elves, opponent, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let id = game.object_id(elves);
+
+        game.exile_linked(ring, elves, ReturnCondition::SourceLeavesBattlefield)
+            .expect("Could not exile the object.");
+        assert_eq!(game.zones.zone_of(elves), Some(Zone::Exile));
+        let exiled_id = game.object_id(elves);
+        game.next_turn().expect("Could not begin the next turn.");
+        game.begin_step(Step::End)
+            .expect("Could not begin the step.");
+        assert_eq!(game.zones.zone_of(elves), Some(Zone::Exile));
+
+        game.destroy(ring)
+            .expect("Could not destroy the permanent.");
+        game.debug_assert_zones_consistent();
+        assert_eq!(game.zones.zone_of(elves), Some(Zone::Battlefield));
+        assert_eq!(game.controller(elves), Some(opponent));
+        assert_ne!(game.object_id(elves), id);
+        assert_ne!(game.object_id(elves), exiled_id);
+    }
+
+    #[test]
+    fn flickered_tokens_cease_to_exist_instead_of_returning() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let source = game
+            .spawn_object(&creature_card(vec![]), player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let token = game
+            .create_token(&creature_card(vec![]), player)
+            .expect("Could not create the token.");
+        let card = game
+            .spawn_object(&creature_card(vec![]), player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+
+        game.exile_linked(source, token, ReturnCondition::EndOfTurn)
+            .expect("Could not exile the object.");
+        game.exile_linked(source, card, ReturnCondition::NextEndStep)
+            .expect("Could not exile the object.");
+        // Tokens that have left the battlefield can't change zones anymore.
+        game.move_object(token, Zone::Hand(player))
+            .expect("Could not move the object.");
+        assert_eq!(game.zones.zone_of(token), Some(Zone::Exile));
+
+        game.begin_step(Step::End)
+            .expect("Could not begin the step.");
+        assert_eq!(game.zones.zone_of(card), Some(Zone::Battlefield));
+        assert_eq!(game.zones.zone_of(token), Some(Zone::Exile));
+        game.next_turn().expect("Could not begin the next turn.");
+        game.debug_assert_zones_consistent();
+        assert!(!game.world.contains(token));
+        assert_eq!(game.creatures_controlled_by(player).len(), 2);
+    }
+
+    #[test]
+    fn indestructible_permanents_can_be_sacrificed_by_their_controller() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let opponent = game.players()[1].id;
+        let card = creature_card(vec![
+            Ability::Keyword(KeywordAbility::Indestructible),
+            Ability::Triggered(TriggeredAbility {
+                condition: TriggerCondition::Dies(TriggerSubject::This),
+                intervening_if: None,
+                effects: vec![Effect::GainLife(1)],
+            }),
+        ]);
+        let creature = game
+            .spawn_object(&card, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let in_hand = game
+            .spawn_object(&card, player, Zone::Hand(player))
+            .expect("Could not spawn the object.");
+
+        for (sacrificing_player, permanent) in [(opponent, creature), (player, in_hand)] {
+            assert!(matches!(
+                game.sacrifice(sacrificing_player, permanent),
+                Err(EngineError::IllegalAction(_))
+            ));
+        }
+        game.destroy(creature)
+            .expect("Could not destroy the permanent.");
+        assert_eq!(game.zones.zone_of(creature), Some(Zone::Battlefield));
+
+        game.sacrifice(player, creature)
+            .expect("Could not sacrifice the permanent.");
+        assert_eq!(game.zones.zone_of(creature), Some(Zone::Graveyard(player)));
+        // Sacrificed creatures still die.
+        assert_eq!(game.pending_triggers().len(), 1);
+        assert!(matches!(
+            &game.events()[game.events().len() - 3..],
+            [
+                GameEvent::Sacrificed { .. },
+                GameEvent::ZoneChanged { .. },
+                GameEvent::AbilityTriggered { .. }
+            ]
+        ));
+    }
+
+    #[test]
+    fn sacrifice_costs_are_paid_together_with_the_rest_of_the_cost() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        // {T}, Sacrifice a creature: You gain 2 life.
+        let altar = creature_card(vec![Ability::Activated(ActivatedAbility {
+            cost: vec![
+                ActivationCost::Tap,
+                ActivationCost::Sacrifice(ObjectFilter {
+                    card_type: Some(CardType::Creature),
+                    you_control: true,
+                    ..ObjectFilter::default()
+                }),
+            ],
+            effects: vec![Effect::GainLife(2)],
+        })]);
+        let altar = game
+            .spawn_object(&altar, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let _ = game.world_mut().remove_one::<SummoningSick>(altar);
+        let creature = game
+            .spawn_object(&creature_card(vec![]), player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let forest = find_card_by_name("Forest").expect("Could not find the card.");
+        let forest = game
+            .spawn_object(forest, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+
+        assert!(matches!(
+            game.activate_ability(player, altar, 0, &[]),
+            Err(EngineError::IllegalAction(_))
+        ));
+        assert!(matches!(
+            game.activate_ability_sacrificing(player, altar, 0, &[], &[forest]),
+            Err(EngineError::PaymentFailed(_))
+        ));
+        // The creature chosen to be sacrificed stays when the rest of the cost can't be paid.
+        game.world
+            .insert_one(altar, Tapped)
+            .expect("Could not tap the permanent.");
+        assert!(matches!(
+            game.activate_ability_sacrificing(player, altar, 0, &[], &[creature]),
+            Err(EngineError::PaymentFailed(_))
+        ));
+        assert_eq!(game.zones.zone_of(creature), Some(Zone::Battlefield));
+
+        game.world
+            .remove_one::<Tapped>(altar)
+            .expect("Could not untap the permanent.");
+        game.activate_ability_sacrificing(player, altar, 0, &[], &[creature])
+            .expect("Could not activate the ability.");
+        game.resolve_top_of_stack()
+            .expect("Could not resolve the ability.");
+        assert_eq!(game.zones.zone_of(creature), Some(Zone::Graveyard(player)));
+        assert_eq!(
+            game.player(player)
+                .expect("Could not access the player.")
+                .life,
+            22
+        );
+    }
+
+    #[test]
+    fn costs_with_several_components_are_paid_all_at_once_or_not_at_all() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        // {1}, Pay 2 life, Discard a card: Draw a card.
+        let source = creature_card(vec![Ability::Activated(ActivatedAbility {
+            cost: vec![
+                ActivationCost::Mana(ManaCost(vec![ManaSymbol::Generic(1)])),
+                ActivationCost::PayLife(2),
+                ActivationCost::Discard,
+            ],
+            effects: vec![Effect::DrawCards(Quantity::Fixed(1))],
+        })]);
+        let source = game
+            .spawn_object(&source, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let forest = find_card_by_name("Forest").expect("Could not find the card.");
+        let forest = game
+            .spawn_object(forest, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let life = |game: &Game| {
+            game.player(player)
+                .expect("Could not access the player.")
+                .life
+        };
+
+        assert!(matches!(
+            game.activate_ability(player, source, 0, &[]),
+            Err(EngineError::IllegalAction(_))
+        ));
+        assert_eq!(life(&game), 20);
+        assert!(game.world.get::<Tapped>(forest).is_err());
+
+        let card = game
+            .spawn_object(&creature_card(vec![]), player, Zone::Hand(player))
+            .expect("Could not spawn the object.");
+        game.spawn_object(&creature_card(vec![]), player, Zone::Library(player))
+            .expect("Could not spawn the object.");
+        game.activate_ability_discarding(player, source, 0, &[], &[card])
+            .expect("Could not activate the ability.");
+        assert_eq!(life(&game), 18);
+        assert!(game.world.get::<Tapped>(forest).is_ok());
+        assert_eq!(game.zones.zone_of(card), Some(Zone::Graveyard(player)));
+    }
+
+    #[test]
+    fn mana_costs_of_abilities_cant_be_paid_by_tapping_their_source() {
+        let generic = ActivationCost::Mana(ManaCost(vec![ManaSymbol::Generic(1)]));
+        // The source has to be tapped for the cost, whether the mana is paid first or last.
+        for cost in [
+            vec![ActivationCost::Tap, generic.clone()],
+            vec![generic.clone(), ActivationCost::Tap],
+        ] {
+            let mut game = Game::new(2);
+            let player = game.players()[0].id;
+            // {T}: Add {G}.
+            // {1}, {T}: You gain 2 life.
+            let source = creature_card(vec![
+                Ability::Activated(ActivatedAbility {
+                    cost: vec![ActivationCost::Tap],
+                    effects: vec![Effect::AddMana(Mana::Monocolored(Color::Green))],
+                }),
+                Ability::Activated(ActivatedAbility {
+                    cost,
+                    effects: vec![Effect::GainLife(2)],
+                }),
+            ]);
+            let source = game
+                .spawn_object(&source, player, Zone::Battlefield)
+                .expect("Could not spawn the object.");
+            let _ = game.world_mut().remove_one::<SummoningSick>(source);
+
+            assert!(matches!(
+                game.activate_ability(player, source, 1, &[]),
+                Err(EngineError::PaymentFailed(_))
+            ));
+            assert!(game.world.get::<Tapped>(source).is_err());
+
+            let forest = find_card_by_name("Forest").expect("Could not find the card.");
+            let forest = game
+                .spawn_object(forest, player, Zone::Battlefield)
+                .expect("Could not spawn the object.");
+            game.activate_ability(player, source, 1, &[])
+                .expect("Could not activate the ability.");
+            assert!(game.world.get::<Tapped>(source).is_ok());
+            assert!(game.world.get::<Tapped>(forest).is_ok());
+        }
+    }
+
+    #[test]
+    fn permanents_sacrificed_for_a_cost_cant_pay_its_mana() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        // {1}, Sacrifice a land: You gain 2 life.
+        let source = creature_card(vec![Ability::Activated(ActivatedAbility {
+            cost: vec![
+                ActivationCost::Mana(ManaCost(vec![ManaSymbol::Generic(1)])),
+                ActivationCost::Sacrifice(ObjectFilter {
+                    card_type: Some(CardType::Land),
+                    you_control: true,
+                    ..ObjectFilter::default()
+                }),
+            ],
+            effects: vec![Effect::GainLife(2)],
+        })]);
+        let source = game
+            .spawn_object(&source, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let forest = find_card_by_name("Forest").expect("Could not find the card.");
+        let sacrificed = game
+            .spawn_object(forest, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+
+        assert!(matches!(
+            game.activate_ability_sacrificing(player, source, 0, &[], &[sacrificed]),
+            Err(EngineError::PaymentFailed(_))
+        ));
+        assert_eq!(game.zones.zone_of(sacrificed), Some(Zone::Battlefield));
+        assert!(game.world.get::<Tapped>(sacrificed).is_err());
+
+        let tapped = game
+            .spawn_object(forest, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        game.activate_ability_sacrificing(player, source, 0, &[], &[sacrificed])
+            .expect("Could not activate the ability.");
+        assert_eq!(
+            game.zones.zone_of(sacrificed),
+            Some(Zone::Graveyard(player))
+        );
+        assert!(game.world.get::<Tapped>(tapped).is_ok());
+    }
+
+    #[test]
+    fn life_paid_for_several_components_of_a_cost_is_added_up() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        // Pay 2 life, Pay 2 life: Draw a card.
+        let source = creature_card(vec![Ability::Activated(ActivatedAbility {
+            cost: vec![ActivationCost::PayLife(2), ActivationCost::PayLife(2)],
+            effects: vec![Effect::DrawCards(Quantity::Fixed(1))],
+        })]);
+        let source = game
+            .spawn_object(&source, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        game.spawn_object(&creature_card(vec![]), player, Zone::Library(player))
+            .expect("Could not spawn the object.");
+        let life = |game: &Game| {
+            game.player(player)
+                .expect("Could not access the player.")
+                .life
+        };
+
+        game.player_mut(player)
+            .expect("Could not access the player.")
+            .life = 3;
+        assert!(matches!(
+            game.activate_ability(player, source, 0, &[]),
+            Err(EngineError::PaymentFailed(_))
+        ));
+        assert_eq!(life(&game), 3);
+
+        game.player_mut(player)
+            .expect("Could not access the player.")
+            .life = 4;
+        game.activate_ability(player, source, 0, &[])
+            .expect("Could not activate the ability.");
+        assert_eq!(life(&game), 0);
+    }
+
+    #[test]
+    fn counters_removed_for_several_components_of_a_cost_are_added_up() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let remove = ActivationCost::RemoveCounters(CounterKind::PlusOnePlusOne, 1);
+        // Remove a +1/+1 counter from this creature, Remove a +1/+1 counter from this creature:
+        // You gain 2 life.
+        let source = creature_card(vec![Ability::Activated(ActivatedAbility {
+            cost: vec![remove.clone(), remove],
+            effects: vec![Effect::GainLife(2)],
+        })]);
+        let source = game
+            .spawn_object(&source, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let counters = |game: &Game| {
+            game.counters(
+                PermanentOrPlayer::Permanent(source),
+                &CounterKind::PlusOnePlusOne,
+            )
+            .expect("Could not count the counters.")
+        };
+
+        game.add_counters(
+            PermanentOrPlayer::Permanent(source),
+            CounterKind::PlusOnePlusOne,
+            1,
+        )
+        .expect("Could not add the counters.");
+        assert!(matches!(
+            game.activate_ability(player, source, 0, &[]),
+            Err(EngineError::PaymentFailed(_))
+        ));
+        assert_eq!(counters(&game), 1);
+
+        game.add_counters(
+            PermanentOrPlayer::Permanent(source),
+            CounterKind::PlusOnePlusOne,
+            1,
+        )
+        .expect("Could not add the counters.");
+        game.activate_ability(player, source, 0, &[])
+            .expect("Could not activate the ability.");
+        assert_eq!(counters(&game), 0);
+        let removed = game
+            .events()
+            .iter()
+            .filter(|it| {
+                matches!(
+                    it,
+                    GameEvent::CountersRemoved {
+                        kind: CounterKind::PlusOnePlusOne,
+                        amount: 1,
+                        ..
+                    }
+                )
+            })
+            .count();
+        assert_eq!(removed, 2);
+    }
+
+    #[test]
+    fn mass_removal_moves_all_permanents_at_the_same_time() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let opponent = game.players()[1].id;
+        let creatures = ObjectFilter {
+            card_type: Some(CardType::Creature),
+            ..ObjectFilter::default()
+        };
+        // When this creature dies, you gain 1 life.
+        let dies = creature_card(vec![Ability::Triggered(TriggeredAbility {
+            condition: TriggerCondition::Dies(TriggerSubject::This),
+            intervening_if: None,
+            effects: vec![Effect::GainLife(1)],
+        })]);
+        // Whenever a creature dies, you gain 1 life.
+        let observer = creature_card(vec![Ability::Triggered(TriggeredAbility {
+            condition: TriggerCondition::Dies(TriggerSubject::Matching(creatures.clone())),
+            intervening_if: None,
+            effects: vec![Effect::GainLife(1)],
+        })]);
+        let indestructible = creature_card(vec![Ability::Keyword(KeywordAbility::Indestructible)]);
+        let mut spawn = |card, owner| {
+            game.spawn_object(card, owner, Zone::Battlefield)
+                .expect("Could not spawn the object.")
+        };
+        let killed = [
+            spawn(&dies, player),
+            spawn(&observer, player),
+            spawn(&dies, opponent),
+        ];
+        let survivor = spawn(&indestructible, opponent);
+        let regenerating = spawn(&creature_card(vec![]), opponent);
+        game.create_regeneration_shield(regenerating)
+            .expect("Could not create the regeneration shield.");
+
+        game.destroy_all(&creatures, player)
+            .expect("Could not destroy the creatures.");
+        game.debug_assert_zones_consistent();
+        for creature in killed {
+            assert!(matches!(
+                game.zones.zone_of(creature),
+                Some(Zone::Graveyard(_))
+            ));
+        }
+        assert_eq!(game.zones.zone_of(survivor), Some(Zone::Battlefield));
+        assert_eq!(game.zones.zone_of(regenerating), Some(Zone::Battlefield));
+        assert!(game.world_mut().get::<Tapped>(regenerating).is_ok());
+        // Both creatures with a dies trigger trigger once, while the observer sees all three
+        // creatures die, including itself.
+        assert_eq!(game.pending_triggers().len(), 5);
+
+        let opponents_creatures = ObjectFilter {
+            opponent_controls: true,
+            ..creatures
+        };
+        game.exile_all(&opponents_creatures, player)
+            .expect("Could not exile the creatures.");
+        assert_eq!(game.zones.zone_of(survivor), Some(Zone::Exile));
+    }
+
+    #[test]
+    fn damage_is_dealt_to_each_matching_permanent() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let small = spawn_creature(&mut game, 1, 3);
+        let large = spawn_creature(&mut game, 4, 4);
+        for creature in [small, large] {
+            game.world
+                .insert_one(creature, (Controller(player), Object))
+                .expect("Could not insert the components.");
+        }
+        // Deal 2 damage to each creature with power 2 or less.
+        let effect = serde_json::from_str::<Effect>(
+            r#"{ "DamageEach": [{ "power": { "AtMost": 2 } }, 2] }"#,
+        )
+        .expect("Could not parse the effect.");
+
+        game.resolve_effects(large, player, &[effect], &[])
+            .expect("Could not resolve the effect.");
+        let marked_damage = |entity| game.world.get::<MarkedDamage>(entity).map_or(0, |it| it.0);
+        assert_eq!(marked_damage(small), 2);
+        assert_eq!(marked_damage(large), 0);
+    }
+
+    #[test]
+    fn creatures_dealt_lethal_damage_by_a_sweeper_die_at_the_same_time() {
+        let mut game = Game::new(2);
+        let [player, opponent] = [0, 1].map(|it| game.players()[it].id);
+        let creatures = ObjectFilter {
+            card_type: Some(CardType::Creature),
+            ..ObjectFilter::default()
+        };
+        // Whenever a creature dies, you gain 1 life.
+        let observer = creature_card(vec![Ability::Triggered(TriggeredAbility {
+            condition: TriggerCondition::Dies(TriggerSubject::Matching(creatures.clone())),
+            intervening_if: None,
+            effects: vec![Effect::GainLife(1)],
+        })]);
+        let (small, large) = (creature_card(vec![]), vanilla_creature(3, 3));
+        let mut spawn = |card, owner| {
+            game.spawn_object(card, owner, Zone::Battlefield)
+                .expect("Could not spawn the object.")
+        };
+        let killed = [
+            spawn(&observer, player),
+            spawn(&observer, opponent),
+            spawn(&small, opponent),
+        ];
+        let survivor = spawn(&large, opponent);
+        // Deal 2 damage to each creature.
+        let spell = game
+            .spawn_object(
+                &instant_card(vec![Ability::Spell(vec![Effect::DamageEach(creatures, 2)])]),
+                player,
+                Zone::Hand(player),
+            )
+            .expect("Could not spawn the object.");
+        game.cast_spell(player, spell, None)
+            .expect("Could not cast the spell.");
+        game.resolve_spell(spell, &[])
+            .expect("Could not resolve the spell.");
+
+        for creature in killed {
+            assert!(matches!(
+                game.zones.zone_of(creature),
+                Some(Zone::Graveyard(_))
+            ));
+        }
+        assert_eq!(game.zones.zone_of(survivor), Some(Zone::Battlefield));
+        // Each observer sees all three creatures die, including itself.
+        assert_eq!(game.pending_triggers().len(), 6);
+    }
+
+    #[test]
+    fn shuffling_forgets_the_known_top_card_and_is_logged() {
+        let (mut game, player, cards) = game_with_library(10);
+        let opponent = game.players()[1].id;
+        game.set_seed(7);
+
+        assert_eq!(game.look_at_top_card(player, player), Ok(Some(cards[0])));
+        let knows_top_card = |game: &Game, it| {
+            game.library(player)
+                .expect("Could not access the library.")
+                .is_top_card_known_to(it)
+        };
+        assert!(knows_top_card(&game, player));
+        assert!(!knows_top_card(&game, opponent));
+
+        game.resolve_effects(cards[1], player, &[Effect::ShuffleLibrary], &[])
+            .expect("Could not resolve the effect.");
+        assert!(!knows_top_card(&game, player));
+        assert_eq!(
+            GameLog::new(LogVisibility::Player(opponent)).lines(&game),
+            vec!["Player 1 shuffles their library."]
+        );
+
+        // Shuffling a card into a library moves it there first.
+        let card = game
+            .spawn_object(&creature_card(vec![]), player, Zone::Graveyard(player))
+            .expect("Could not spawn the object.");
+        game.shuffle_into_library(card)
+            .expect("Could not shuffle the card into the library.");
+        assert_eq!(game.zones.zone_of(card), Some(Zone::Library(player)));
+        assert_eq!(
+            game.events().last(),
+            Some(&GameEvent::LibraryShuffled { player })
+        );
+    }
+
+    #[test]
+    fn card_metadata_has_no_effect_on_game_play() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let elves = find_card_by_name("Llanowar Elves").expect("Could not find the card.");
+        let mut printing = Card::clone(elves);
+        printing.metadata = Some(CardMetadata {
+            image_uri: Some("https://example.com/llanowar-elves.jpg".into()),
+            artist: Some("Chris Rahn".into()),
+            ..CardMetadata::default()
+        });
+        let original = game
+            .spawn_object(elves, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let reprint = game
+            .spawn_object(&Arc::new(printing), player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+
+        assert_eq!(
+            game.power_toughness(original),
+            game.power_toughness(reprint)
+        );
+        assert_eq!(game.keywords(original), game.keywords(reprint));
+        assert_eq!(ObjectSnapshot::of(&game.world, original).metadata, None);
+        assert_eq!(
+            ObjectSnapshot::of(&game.world, reprint)
+                .metadata
+                .and_then(|it| it.image_uri),
+            Some("https://example.com/llanowar-elves.jpg".into())
+        );
+    }
+
+    #[test]
+    fn objects_spawned_from_localized_names_have_the_english_name() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let elves = find_card_by_localized_name(Locale::Japanese, "ラノワールのエルフ")
+            .expect("Could not find the card.");
+        assert!(find_card_by_localized_name(Locale::German, "Forest").is_err());
+        assert!(find_card_by_localized_name(Locale::English, "Forest").is_ok());
+
+        let elves = game
+            .spawn_object(elves, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        assert_eq!(
+            ObjectSnapshot::of(&game.world, elves).name,
+            "Llanowar Elves"
+        );
+    }
+
+    #[test]
+    fn card_search_ranks_exact_matches_first() {
+        let card = |name: &str| {
+            Arc::new(
+                Card::builder()
+                    .name(Name(name.into()))
+                    .build()
+                    .expect("Failed to build the card."),
+            )
+        };
+        let database = CardDatabase::from(vec![
+            card("Elvish Mystic"),
+            card("Llanowar Elves"),
+            card("Elves of Deep Shadow"),
+            card("Elves"),
+        ]);
+        let names = |query, limit| {
+            database
+                .search(query, limit)
+                .into_iter()
+                .map(|it| it.name.0.as_str())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            names("elves", 10),
+            vec!["Elves", "Elves of Deep Shadow", "Llanowar Elves"]
+        );
+        assert_eq!(names("ELVI", 10), vec!["Elvish Mystic"]);
+        assert_eq!(names("Lanowar Elves", 10), vec!["Llanowar Elves"]);
+        assert_eq!(names("elves", 1), vec!["Elves"]);
+        assert!(names("Polukranos", 10).is_empty());
+    }
+
+    #[test]
+    fn card_search_finds_typos_in_large_databases() {
+        let mut cards = (0..25_000)
+            .map(|it| {
+                Arc::new(
+                    Card::builder()
+                        .name(Name(format!("Generated Card {it}")))
+                        .build()
+                        .expect("Failed to build the card."),
+                )
+            })
+            .collect::<Vec<_>>();
+        cards.push(Arc::clone(
+            find_card_by_name("Llanowar Elves").expect("Could not find the card."),
+        ));
+        let database = CardDatabase::from(cards);
+
+        let results = database.search("Llanowar Elfes", 5);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name.0, "Llanowar Elves");
+        assert_eq!(database.search("generated card 1", 3).len(), 3);
+    }
+
+    #[test]
+    fn invariant_violations_are_reported() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let opponent = game.players()[1].id;
+        let forests = || Deck::from(&[("Forest", 20)]).expect("Could not build the deck.");
+        game.start(&HashMap::from([(player, forests()), (opponent, forests())]))
+            .expect("Could not start the game.");
+        let elves = find_card_by_name("Llanowar Elves").expect("Could not find the card.");
+        let creature = game
+            .spawn_object(elves, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let token = game
+            .create_token(elves, player)
+            .expect("Could not create the token.");
+        game.move_object(creature, Zone::Hand(player))
+            .expect("Could not move the object.");
+        game.draw_card(opponent).expect("Could not draw a card.");
+        assert_eq!(game.check_invariants(), Ok(()));
+
+        // Lose a card of the opponent, put a controller onto a card in a hand and move a token
+        // behind the back of the game.
+        let lost = game
+            .library(opponent)
+            .expect("Could not access the library.")
+            .cards()
+            .next()
+            .expect("The library is empty.");
+        game.zones.remove(&mut game.world, lost);
+        game.world
+            .insert_one(creature, Controller(player))
+            .expect("Could not insert the controller.");
+        game.zones
+            .place(&mut game.world, token, Zone::Graveyard(player))
+            .expect("Could not place the object.");
+        game.world
+            .insert_one(token, Zone::Exile)
+            .expect("Could not insert the zone.");
+
+        assert_eq!(
+            game.check_invariants(),
+            Err(vec![
+                InvariantViolation::CardsNotConserved {
+                    owner: opponent,
+                    expected: 20,
+                    actual: 19,
+                },
+                InvariantViolation::ZoneOutOfSync {
+                    entity: token,
+                    tracked: Some(Zone::Graveyard(player)),
+                    cached: Some(Zone::Exile),
+                },
+                InvariantViolation::ControllerOutsideStackOrBattlefield {
+                    entity: creature,
+                    zone: Some(Zone::Hand(player)),
+                },
+                InvariantViolation::ControllerOutsideStackOrBattlefield {
+                    entity: token,
+                    zone: Some(Zone::Graveyard(player)),
+                },
+                InvariantViolation::TokenOutsideBattlefield {
+                    entity: token,
+                    zone: Some(Zone::Graveyard(player)),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn spectator_views_are_rebuilt_from_deltas() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let opponent = game.players()[1].id;
+        game.set_seed(7);
+        let deck = || {
+            Deck::from(&[("Forest", 10), ("Llanowar Elves", 10)])
+                .expect("Could not build the deck.")
+        };
+        game.start(&HashMap::from([(player, deck()), (opponent, deck())]))
+            .expect("Could not start the game.");
+
+        let initial = game.spectator_view(true);
+        let mut view = initial.clone();
+        let mut deltas = Vec::new();
+        let card = game.draw_card(player).expect("Could not draw a card.");
+        deltas.push(game.spectator_delta(&view));
+        view = game.spectator_view(true);
+        game.move_object(card.expect("The library is empty."), Zone::Battlefield)
+            .expect("Could not move the object.");
+        deltas.push(game.spectator_delta(&view));
+        view = game.spectator_view(true);
+        game.begin_step(Step::Upkeep)
+            .expect("Could not begin the step.");
+        deltas.push(game.spectator_delta(&view));
+
+        let mut rebuilt = initial.clone();
+        for delta in deltas {
+            rebuilt.apply(delta);
+        }
+        assert_eq!(rebuilt, game.spectator_view(true));
+        // A delta only contains what changed, e.g. not the untouched cards of the opponent.
+        let delta = game.spectator_delta(&initial);
+        assert_eq!(delta.objects.len() + delta.removed.len(), 2);
+        assert_eq!(delta.players.len(), 1);
+        assert_eq!(delta.events.len(), 3);
+        // Hiding the libraries leaves only the card on the battlefield.
+        assert_eq!(game.spectator_view(false).objects.len(), 1);
+        let hidden = serde_json::to_value(game.spectator_view(false))
+            .expect("Could not serialize the view.");
+        assert_eq!(hidden["players"][0]["library_size"], 19);
+        assert_eq!(hidden["players"][0]["library"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn the_winner_of_a_game_played_for_ante_owns_the_ante() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let opponent = game.players()[1].id;
+        let deck = || {
+            Deck::from(&[("Forest", 30), ("Llanowar Elves", 30)])
+                .expect("Could not build the deck.")
+        };
+        game.set_playing_for_ante(true);
+        game.start(&HashMap::from([(player, deck()), (opponent, deck())]))
+            .expect("Could not start the game.");
+
+        let ante = |game: &Game| {
+            let mut ante = game
+                .world
+                .query::<&Zone>()
+                .iter()
+                .filter(|(_, &zone)| zone == Zone::Ante)
+                .map(|(entity, _)| entity)
+                .collect::<Vec<_>>();
+            ante.sort();
+            ante
+        };
+        let cards = ante(&game);
+        assert_eq!(cards.len(), 2);
+        for id in [player, opponent] {
+            assert_eq!(
+                game.library(id)
+                    .expect("Could not access the library.")
+                    .len(),
+                59
+            );
+        }
+        assert!(matches!(
+            game.move_object(cards[0], Zone::Hand(player)),
+            Err(EngineError::IllegalZoneOperation(_))
+        ));
+
+        game.concede(opponent).expect("Could not concede.");
+        assert_eq!(ante(&game), cards);
+        let owners = cards
+            .iter()
+            .map(|&it| game.world.get::<Owner>(it).map(|it| it.0).ok())
+            .collect::<Vec<_>>();
+        assert_eq!(owners, vec![Some(player); 2]);
+        let GameEvent::AnteWon { winner, cards: won } = &game.events()[game.events().len() - 2]
+        else {
+            panic!("The ante wasn't transferred.");
+        };
+        assert_eq!(*winner, player);
+        assert_eq!(won.len(), 2);
+        assert_eq!(game.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn games_are_not_played_for_ante_by_default() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let card = game
+            .spawn_object(
+                find_card_by_name("Forest").expect("Could not find the card."),
+                player,
+                Zone::Hand(player),
+            )
+            .expect("Could not spawn the object.");
+        assert!(matches!(
+            game.move_object(card, Zone::Ante),
+            Err(EngineError::IllegalZoneOperation(_))
+        ));
+    }
+
+    #[test]
+    fn trivial_discards_are_chosen_automatically_if_the_player_lets_the_engine() {
+        let discard = |auto_choose_trivial, choice: Option<Vec<Entity>>| {
+            let mut game = Game::new(2);
+            let player = game.turn().active_player;
+            game.player_mut(player)
+                .expect("Could not access the player.")
+                .maximum_hand_size = 0;
+            let card = game
+                .spawn_object(
+                    find_card_by_name("Forest").expect("Could not find the card."),
+                    player,
+                    Zone::Hand(player),
+                )
+                .expect("Could not spawn the object.");
+            game.set_auto_choose_trivial(player, auto_choose_trivial)
+                .expect("Could not change the setting.");
+            let calls = Arc::new(Mutex::new(0));
+            let counter = Arc::clone(&calls);
+            game.set_discard_chooser(Box::new(move |_, hand, amount| {
+                *counter.lock().expect("Could not lock the counter.") += 1;
+                choice.clone().unwrap_or_else(|| hand[..amount].to_vec())
+            }));
+            let result = game.begin_step(Step::Cleanup);
+            let calls = *calls.lock().expect("Could not lock the counter.");
+            (game, player, card, result, calls)
+        };
+
+        let (game, player, card, result, calls) = discard(true, None);
+        assert_eq!(result, Ok(()));
+        assert_eq!(calls, 0);
+        assert_eq!(game.zones.zone_of(card), Some(Zone::Graveyard(player)));
+        assert!(game.events().contains(&GameEvent::DiscardsChosen {
+            player,
+            amount: 1,
+            automatic: true,
+        }));
+
+        let (game, player, card, result, calls) = discard(false, None);
+        assert_eq!(result, Ok(()));
+        assert_eq!(calls, 1);
+        assert_eq!(game.zones.zone_of(card), Some(Zone::Graveyard(player)));
+        assert!(game.events().contains(&GameEvent::DiscardsChosen {
+            player,
+            amount: 1,
+            automatic: false,
+        }));
+
+        // The chooser has to choose as many cards from the hand as the player discards.
+        let (_, _, _, result, calls) = discard(false, Some(vec![]));
+        assert!(matches!(result, Err(EngineError::IllegalAction(_))));
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn single_excess_cards_are_discarded_automatically_if_the_player_lets_the_engine() {
+        let discard = |auto_choose_trivial| {
+            let mut game = Game::new(2);
+            let player = game.turn().active_player;
+            let forest = find_card_by_name("Forest").expect("Could not find the card.");
+            for _ in 0..8 {
+                game.spawn_object(forest, player, Zone::Hand(player))
+                    .expect("Could not spawn the object.");
+            }
+            game.set_auto_choose_trivial(player, auto_choose_trivial)
+                .expect("Could not change the setting.");
+            let calls = Arc::new(Mutex::new(0));
+            let counter = Arc::clone(&calls);
+            game.set_discard_chooser(Box::new(move |_, hand, amount| {
+                *counter.lock().expect("Could not lock the counter.") += 1;
+                hand[..amount].to_vec()
+            }));
+            game.begin_step(Step::Cleanup)
+                .expect("Could not begin the step.");
+            let calls = *calls.lock().expect("Could not lock the counter.");
+            (game, player, calls)
+        };
+
+        for (automatic, expected_calls) in [(true, 0), (false, 1)] {
+            let (game, player, calls) = discard(automatic);
+            assert_eq!(calls, expected_calls);
+            assert_eq!(game.hand_size(player), 7);
+            assert!(game.events().contains(&GameEvent::DiscardsChosen {
+                player,
+                amount: 1,
+                automatic,
+            }));
+        }
+    }
+
+    #[test]
+    fn only_legal_targets_are_chosen_automatically_if_the_player_lets_the_engine() {
+        let trigger = |auto_choose_trivial| {
+            let mut game = Game::new(2);
+            let player = game.turn().active_player;
+            // When this creature enters the battlefield, tap target creature. It doesn't untap
+            // during its controller's next untap step.
+            let card = creature_card(vec![Ability::Triggered(TriggeredAbility {
+                condition: TriggerCondition::EntersBattlefield(TriggerSubject::This),
+                intervening_if: None,
+                effects: vec![Effect::TapAndStun(1)],
+            })]);
+            game.set_auto_choose_trivial(player, auto_choose_trivial)
+                .expect("Could not change the setting.");
+            game.spawn_object(&card, player, Zone::Battlefield)
+                .expect("Could not spawn the object.");
+            let to_act = game.player_to_act().expect("Could not advance the game.");
+            (game, player, to_act)
+        };
+        let automatic = |game: &Game| {
+            game.events().iter().find_map(|it| match it {
+                GameEvent::TargetsChosen { automatic, .. } => Some(*automatic),
+                _ => None,
+            })
+        };
+
+        // The creature is the only legal target of its own ability.
+        let (game, _, _) = trigger(true);
+        assert!(game.pending_triggers().is_empty());
+        assert_eq!(automatic(&game), Some(true));
+
+        let (mut game, player, to_act) = trigger(false);
+        assert_eq!(to_act, Some(player));
+        assert_eq!(game.pending_triggers().len(), 1);
+        let actions = game
+            .legal_actions(player)
+            .expect("Could not list the legal actions.");
+        assert_eq!(actions.len(), 1);
+        game.apply(player, actions[0].clone())
+            .expect("Could not apply the action.");
+        assert!(game.pending_triggers().is_empty());
+        assert_eq!(automatic(&game), Some(false));
+    }
+
+    #[test]
+    fn players_without_maximum_hand_size_keep_their_cards_through_cleanup() {
+        let mut game = Game::new(2);
+        let player = game.turn().active_player;
+        let forest = find_card_by_name("Forest").expect("Could not find the card.");
+        for _ in 0..12 {
+            game.spawn_object(forest, player, Zone::Hand(player))
+                .expect("Could not spawn the object.");
+        }
+        // Reliquary Tower has “You have no maximum hand size.”
+        let tower = Arc::new(
+            Card::builder()
+                .name(Name("Reliquary Tower".into()))
+                .abilities(vec![Ability::Static(StaticAbility::NoMaximumHandSize)])
+                .build()
+                .expect("Failed to build the card."),
+        );
+        let tower = game
+            .spawn_object(&tower, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let hand_size = |game: &Game| {
+            game.world
+                .query::<&Zone>()
+                .iter()
+                .filter(|(_, &zone)| zone == Zone::Hand(player))
+                .count()
+        };
+
+        assert_eq!(game.maximum_hand_size(player), Ok(None));
+        game.begin_step(Step::Cleanup)
+            .expect("Could not begin the step.");
+        assert_eq!(hand_size(&game), 12);
+
+        game.move_object(tower, Zone::Graveyard(player))
+            .expect("Could not move the object.");
+        game.next_turn().expect("Could not begin the next turn.");
+        game.next_turn().expect("Could not begin the next turn.");
+        assert_eq!(game.maximum_hand_size(player), Ok(Some(7)));
+        game.begin_step(Step::Cleanup)
+            .expect("Could not begin the step.");
+        assert_eq!(hand_size(&game), 7);
+        assert!(matches!(
+            game.events()[game.events().len() - 2],
+            GameEvent::Discarded { .. }
+        ));
+
+        // Hand modifiers are applied to the base value.
+        game.add_effect(ContinuousEffect {
+            source: None,
+            controller: player,
+            duration: Duration::Permanent,
+            kind: EffectKind::ModifyMaximumHandSize { player, amount: -2 },
+        });
+        assert_eq!(game.maximum_hand_size(player), Ok(Some(5)));
+    }
+
+    #[test]
+    fn coin_flips_and_die_rolls_are_reproducible_with_a_seed() {
+        let play = || {
+            let mut game = Game::new(2);
+            game.set_seed(186);
+            let player = game.players()[0].id;
+            let opponent = game.players()[1].id;
+            let call = CoinCall {
+                caller: opponent,
+                call: CoinResult::Heads,
+            };
+            let results = (0..8)
+                .map(|_| {
+                    let flip = game.flip_coin(player).expect("Could not flip the coin.");
+                    let won = game
+                        .call_coin_flip(player, call)
+                        .expect("Could not flip the coin.");
+                    let roll = game.roll_die(player, 6).expect("Could not roll the die.");
+                    (flip, won, roll)
+                })
+                .collect::<Vec<_>>();
+            (results, game.events().to_vec())
+        };
+
+        let (results, events) = play();
+        assert_eq!(play(), (results.clone(), events.clone()));
+        assert!(results.iter().all(|&(_, _, roll)| (1..=6).contains(&roll)));
+        assert_eq!(events.len(), 24);
+        assert!(matches!(
+            events[1],
+            GameEvent::CoinFlipped {
+                call: Some(CoinCall {
+                    call: CoinResult::Heads,
+                    ..
+                }),
+                result,
+                ..
+            } if (result == CoinResult::Heads) == results[0].1
+        ));
+        assert!(matches!(
+            &events[2],
+            GameEvent::DieRolled { sides: 6, rolls, result, .. }
+                if *rolls == vec![*result] && *result == results[0].2
+        ));
+    }
+
+    #[test]
+    fn rolling_two_dice_keeps_the_highest_and_triggers_for_both() {
+        let mut game = Game::new(2);
+        game.set_seed(20);
+        let player = game.players()[0].id;
+        let opponent = game.players()[1].id;
+        // Whenever you roll a die, you gain 1 life.
+        let card = Arc::new(
+            Card::builder()
+                .abilities(vec![Ability::Triggered(TriggeredAbility {
+                    condition: TriggerCondition::RollsDie(PlayerScope::You),
+                    intervening_if: None,
+                    effects: vec![Effect::GainLife(1)],
+                })])
+                .build()
+                .expect("Failed to build the card."),
+        );
+        game.spawn_object(&card, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+
+        let result = game
+            .roll_dice(player, 20, DiceRoll::KeepHighest(2))
+            .expect("Could not roll the dice.");
+        let Some(rolls) = game.events().iter().rev().find_map(|it| match it {
+            GameEvent::DieRolled { rolls, .. } => Some(rolls),
+            _ => None,
+        }) else {
+            panic!("The dice were not rolled.");
+        };
+        assert_eq!(rolls.len(), 2);
+        assert_eq!(rolls.iter().max(), Some(&result));
+        assert_eq!(game.pending_triggers().len(), 2);
+        game.roll_die(opponent, 20)
+            .expect("Could not roll the die.");
+        assert_eq!(game.pending_triggers().len(), 2);
+
+        assert_eq!(
+            game.roll_dice(player, 0, DiceRoll::Single),
+            Err(EngineError::IllegalAction(
+                "Cannot roll 1 dice with 0 sides.".into()
+            ))
+        );
+        assert_eq!(
+            game.roll_dice(player, 6, DiceRoll::KeepHighest(0)),
+            Err(EngineError::IllegalAction(
+                "Cannot roll 0 dice with 6 sides.".into()
+            ))
+        );
+    }
+
+    #[test]
+    fn effects_can_scale_with_the_experience_counters_of_their_controller() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let opponent = game.players()[1].id;
+        game.add_counters(
+            PermanentOrPlayer::Player(player),
+            CounterKind::Experience,
+            2,
+        )
+        .expect("Could not add the counters.");
+        // When this creature enters the battlefield, you get an experience counter. Then it deals
+        // damage equal to the number of experience counters you have to any target.
+        let card = creature_card(vec![Ability::Triggered(TriggeredAbility {
+            condition: TriggerCondition::EntersBattlefield(TriggerSubject::This),
+            intervening_if: None,
+            effects: vec![
+                Effect::GetCounters(CounterKind::Experience, 1),
+                Effect::DealDamage(Quantity::CountersOnYou(CounterKind::Experience)),
+            ],
+        })]);
+
+        game.spawn_object(&card, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        assert!(game
+            .resolve_next_trigger(&[PermanentOrPlayer::Player(opponent)])
+            .expect("Could not resolve the trigger."));
+        assert_eq!(
+            game.player(opponent)
+                .expect("Could not access the player.")
+                .life,
+            17
+        );
+        assert_eq!(
+            game.player_counters(player),
+            Ok(vec![(CounterKind::Experience, 3)])
+        );
+        assert_eq!(game.player_counters(opponent), Ok(vec![]));
+        let view = serde_json::to_value(game.spectator_view(false))
+            .expect("Could not serialize the view.");
+        assert_eq!(
+            view["players"][0]["counters"],
+            serde_json::json!([["Experience", 3]])
+        );
+    }
+
+    #[test]
+    fn ascend_gives_the_citys_blessing_for_the_rest_of_the_game() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let forest = find_card_by_name("Forest").expect("Could not find the card.");
+        game.spawn_object(
+            &creature_card(vec![Ability::Keyword(KeywordAbility::Ascend)]),
+            player,
+            Zone::Battlefield,
+        )
+        .expect("Could not spawn the object.");
+        let mut forests = (0..8)
+            .map(|_| {
+                game.spawn_object(forest, player, Zone::Battlefield)
+                    .expect("Could not spawn the object.")
+            })
+            .collect::<Vec<_>>();
+        assert!(!game.has_designation(player, Designation::CitysBlessing));
+
+        forests.push(
+            game.spawn_object(forest, player, Zone::Battlefield)
+                .expect("Could not spawn the object."),
+        );
+        assert!(game.has_designation(player, Designation::CitysBlessing));
+        for forest in forests {
+            game.move_object(forest, Zone::Graveyard(player))
+                .expect("Could not move the object.");
+        }
+        assert!(game.has_designation(player, Designation::CitysBlessing));
+        assert!(!game.has_designation(game.players()[1].id, Designation::CitysBlessing));
+        assert_eq!(
+            game.events()
+                .iter()
+                .filter(|it| matches!(it, GameEvent::DesignationGained { .. }))
+                .count(),
+            1
+        );
+        let view = serde_json::to_value(game.spectator_view(false))
+            .expect("Could not serialize the view.");
+        assert_eq!(
+            view["players"][0]["designations"],
+            serde_json::json!(["CitysBlessing"])
+        );
+    }
+
+    #[test]
+    fn storm_copies_the_spell_for_each_spell_cast_before_it() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let opponent = game.players()[1].id;
+        // You gain 1 life.
+        let lifegain = instant_card(vec![Ability::Spell(vec![Effect::GainLife(1)])]);
+        // Deal 1 damage to any target. Storm
+        let storm = instant_card(vec![
+            Ability::Spell(vec![Effect::DealDamage(Quantity::Fixed(1))]),
+            Ability::Keyword(KeywordAbility::Storm),
+        ]);
+        let mut cast = |card| {
+            let spell = game
+                .spawn_object(card, player, Zone::Hand(player))
+                .expect("Could not spawn the object.");
+            game.cast_spell(player, spell, None)
+                .expect("Could not cast the spell.");
+            spell
+        };
+        cast(&lifegain);
+        cast(&lifegain);
+        let spell = cast(&storm);
+
+        let id = game.object_id(spell).expect("Could not find the spell.");
+        assert_eq!(game.storm_count(id), Some(2));
+        assert_eq!(game.turn_tracker().spells_cast().len(), 3);
+        let stack = game.objects_on_stack();
+        assert_eq!(stack.len(), 5);
+        for id in stack.into_iter().rev() {
+            let object = game.object(id).expect("Could not find the object.");
+            game.resolve_spell(object, &[PermanentOrPlayer::Player(opponent)])
+                .expect("Could not resolve the spell.");
+        }
+        assert_eq!(
+            game.player(opponent)
+                .expect("Could not access the player.")
+                .life,
+            17
+        );
+        assert_eq!(
+            game.player(player)
+                .expect("Could not access the player.")
+                .life,
+            22
+        );
+        // The copies cease to exist rather than being put into the graveyard.
+        assert!(game.objects_on_stack().is_empty());
+        assert_eq!(game.objects_in(Zone::Graveyard(player), |_| true).len(), 3);
+        assert_eq!(game.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn resolving_spells_are_described_before_their_effects_are_applied() {
+        let mut game = Game::new(2);
+        let [player, opponent] = [0, 1].map(|it| game.players()[it].id);
+        let card = Card::builder()
+            .name(Name("Shock".into()))
+            .type_line(TypeLine {
+                card_type: [CardType::Instant].into(),
+                subtype: [].into(),
+                supertype: [].into(),
+            })
+            .mana_cost(ManaCost(vec![]))
+            .abilities(vec![Ability::Spell(vec![Effect::DealDamage(
+                Quantity::Fixed(2),
+            )])])
+            .build()
+            .expect("Failed to build the card.");
+        let spell = game
+            .spawn_object(&Arc::new(card), player, Zone::Stack)
+            .expect("Could not spawn the object.");
+
+        game.resolve_spell(spell, &[PermanentOrPlayer::Player(opponent)])
+            .expect("Could not resolve the spell.");
+        let description = game.events().iter().find_map(|it| match it {
+            GameEvent::SpellResolved {
+                controller,
+                description,
+                ..
+            } if *controller == player => description.clone(),
+            _ => None,
+        });
+        assert_eq!(
+            description.as_deref(),
+            Some("Shock: Shock deals 2 damage to Player 2.")
+        );
+    }
+
+    #[test]
+    fn lands_and_cards_outside_the_hand_can_not_be_cast() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let forest = game
+            .spawn_object(
+                find_card_by_name("Forest").expect("Could not find the card."),
+                player,
+                Zone::Hand(player),
+            )
+            .expect("Could not spawn the object.");
+        let elves = game
+            .spawn_object(
+                find_card_by_name("Llanowar Elves").expect("Could not find the card."),
+                player,
+                Zone::Battlefield,
+            )
+            .expect("Could not spawn the object.");
+
+        assert_eq!(
+            game.cast_spell(player, forest, None),
+            Err(EngineError::IllegalAction(
+                "Forest is a land and can't be cast.".into()
+            ))
+        );
+        assert!(matches!(
+            game.cast_spell(player, elves, None),
+            Err(EngineError::IllegalAction(_))
+        ));
+        assert_eq!(game.storm_count(ObjectId(0)), None);
+    }
+
+    #[test]
+    fn prowess_triggers_on_casting_noncreature_spells_even_if_they_are_countered() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let creature = game
+            .spawn_object(
+                &creature_card(vec![Ability::Keyword(KeywordAbility::Prowess)]),
+                player,
+                Zone::Battlefield,
+            )
+            .expect("Could not spawn the object.");
+        let mut cast = |card| {
+            let spell = game
+                .spawn_object(card, player, Zone::Hand(player))
+                .expect("Could not spawn the object.");
+            game.cast_spell(player, spell, None)
+                .expect("Could not cast the spell.");
+            spell
+        };
+        let instant = instant_card(vec![Ability::Spell(vec![Effect::GainLife(1)])]);
+        let ornithopter = Arc::new(
+            Card::builder()
+                .type_line(TypeLine {
+                    card_type: [CardType::Artifact, CardType::Creature].into(),
+                    subtype: [].into(),
+                    supertype: [].into(),
+                })
+                .mana_cost(ManaCost(vec![]))
+                .pt(PtCharacteristic {
+                    power: PtValue::Fixed(0),
+                    toughness: PtValue::Fixed(2),
+                })
+                .build()
+                .expect("Failed to build the card."),
+        );
+        let first = cast(&instant);
+        let second = cast(&instant);
+        let creature_spell = cast(&ornithopter);
+        assert_eq!(game.pending_triggers().len(), 2);
+
+        game.resolve_spell(creature_spell, &[])
+            .expect("Could not resolve the spell.");
+        game.resolve_spell(second, &[])
+            .expect("Could not resolve the spell.");
+        game.counter_spell(first)
+            .expect("Could not counter the spell.");
+        while game
+            .resolve_next_trigger(&[])
+            .expect("Could not resolve the trigger.")
+        {}
+        game.begin_step(Step::DeclareAttackers)
+            .expect("Could not begin the step.");
+        assert_eq!(game.power_toughness(creature), Some((3, 3)));
+
+        game.next_turn().expect("Could not begin the next turn.");
+        assert_eq!(game.power_toughness(creature), Some((1, 1)));
+        assert_eq!(
+            game.player(player)
+                .expect("Could not access the player.")
+                .life,
+            21
+        );
+    }
+
+    #[test]
+    fn landfall_triggers_for_played_and_fetched_lands_under_your_control() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let opponent = game.players()[1].id;
+        let card = |name| find_card_by_name(name).expect("Could not find the card.");
+        let lynx = game
+            .spawn_object(card("Steppe Lynx"), player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let plains = game
+            .spawn_object(card("Plains"), player, Zone::Hand(player))
+            .expect("Could not spawn the object.");
+        let fetched = game
+            .spawn_object(card("Plains"), player, Zone::Library(player))
+            .expect("Could not spawn the object.");
+
+        game.begin_step(Step::PrecombatMain)
+            .expect("Could not begin the step.");
+        game.play_land(player, plains)
+            .expect("Could not play the land.");
+        let filter = ObjectFilter {
+            card_type: Some(CardType::Land),
+            ..ObjectFilter::default()
+        };
+        game.search_library(player, &filter, Zone::Battlefield, &mut |it| {
+            it.first().copied()
+        })
+        .expect("Could not search the library.");
+        assert_eq!(game.zones.zone_of(fetched), Some(Zone::Battlefield));
+        game.spawn_object(card("Forest"), opponent, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        assert_eq!(game.pending_triggers().len(), 2);
+
+        while game
+            .resolve_next_trigger(&[])
+            .expect("Could not resolve the trigger.")
+        {}
+        assert_eq!(game.power_toughness(lynx), Some((4, 5)));
+    }
+
+    #[test]
+    fn only_one_land_can_be_played_during_a_main_phase_of_your_turn() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let opponent = game.players()[1].id;
+        let forest = find_card_by_name("Forest").expect("Could not find the card.");
+        let mut spawn = |owner| {
+            game.spawn_object(forest, owner, Zone::Hand(owner))
+                .expect("Could not spawn the object.")
+        };
+        let (first, second, other) = (spawn(player), spawn(player), spawn(opponent));
+
+        assert!(matches!(
+            game.play_land(player, first),
+            Err(EngineError::IllegalTiming(_))
+        ));
+        game.begin_step(Step::PrecombatMain)
+            .expect("Could not begin the step.");
+        assert!(matches!(
+            game.play_land(opponent, other),
+            Err(EngineError::IllegalTiming(_))
+        ));
+        game.play_land(player, first)
+            .expect("Could not play the land.");
+        assert_eq!(
+            game.play_land(player, second),
+            Err(EngineError::IllegalAction(
+                "Player with id 0 has already played a land this turn.".into()
+            ))
+        );
+        assert_eq!(game.turn_tracker().lands_played_by(player), 1);
+    }
+
+    #[test]
+    fn cards_castable_from_the_graveyard_and_exile_are_offered_until_they_are_cast() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let generic = |amount| ManaCost(vec![ManaSymbol::Generic(amount)]);
+        for _ in 0..3 {
+            game.spawn_object(
+                find_card_by_name("Forest").expect("Could not find the card."),
+                player,
+                Zone::Battlefield,
+            )
+            .expect("Could not spawn the object.");
+        }
+        // You gain 1 life. Flashback {1}
+        let flashback = instant_card(vec![
+            Ability::Spell(vec![Effect::GainLife(1)]),
+            Ability::Keyword(KeywordAbility::Flashback(generic(1))),
+        ]);
+        // You gain 1 life. Foretell {1}
+        let foretell = Arc::new(
+            Card::builder()
+                .type_line(TypeLine {
+                    card_type: [CardType::Instant].into(),
+                    subtype: [].into(),
+                    supertype: [].into(),
+                })
+                .mana_cost(generic(3))
+                .abilities(vec![
+                    Ability::Spell(vec![Effect::GainLife(1)]),
+                    Ability::Keyword(KeywordAbility::Foretell(generic(1))),
+                ])
+                .build()
+                .expect("Failed to build the card."),
+        );
+        let graveyard = game
+            .spawn_object(&flashback, player, Zone::Graveyard(player))
+            .expect("Could not spawn the object.");
+        let hand = game
+            .spawn_object(&foretell, player, Zone::Hand(player))
+            .expect("Could not spawn the object.");
+        let options = |game: &Game| {
+            game.casting_options(player)
+                .expect("Could not enumerate the casting options.")
+                .into_iter()
+                .map(|it| (it.zone, it.method, it.cost))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            options(&game),
+            vec![
+                (
+                    Zone::Graveyard(player),
+                    CastingMethod::Flashback,
+                    generic(1)
+                ),
+                (Zone::Hand(player), CastingMethod::Normal, generic(3)),
+            ]
+        );
+        game.foretell(player, hand, None)
+            .expect("Could not foretell the card.");
+        // A foretold card can't be cast during the turn it was foretold.
+        assert_eq!(
+            options(&game),
+            vec![(
+                Zone::Graveyard(player),
+                CastingMethod::Flashback,
+                generic(1)
+            )]
+        );
+
+        game.next_turn().expect("Could not begin the next turn.");
+        game.next_turn().expect("Could not begin the next turn.");
+        assert_eq!(
+            options(&game),
+            vec![
+                (
+                    Zone::Graveyard(player),
+                    CastingMethod::Flashback,
+                    generic(1)
+                ),
+                (Zone::Exile, CastingMethod::Foretold, generic(1)),
+            ]
+        );
+        for spell in [graveyard, hand] {
+            game.cast_spell(player, spell, None)
+                .expect("Could not cast the spell.");
+            game.resolve_spell(spell, &[])
+                .expect("Could not resolve the spell.");
+        }
+        assert_eq!(options(&game), vec![]);
+        // 702.34a [...] exile this card instead of putting it anywhere else [...]
+        assert_eq!(game.zones.zone_of(graveyard), Some(Zone::Exile));
+        assert_eq!(game.zones.zone_of(hand), Some(Zone::Graveyard(player)));
+        assert_eq!(
+            game.player(player)
+                .expect("Could not access the player.")
+                .life,
+            22
+        );
+    }
+
+    #[test]
+    fn exiled_cards_are_listed_per_owner_and_face_down_cards_are_hidden_from_opponents() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let opponent = game.players()[1].id;
+        let forest = find_card_by_name("Forest").expect("Could not find the card.");
+        let graveyard = (0..2)
+            .map(|_| {
+                game.spawn_object(forest, player, Zone::Graveyard(player))
+                    .expect("Could not spawn the object.")
+            })
+            .collect::<Vec<_>>();
+        let spell = spell_in_hand(
+            &mut game,
+            player,
+            vec![ManaSymbol::Generic(2)],
+            KeywordAbility::Delve,
+        );
+        game.pay_mana_cost(
+            player,
+            &ManaCost(vec![ManaSymbol::Generic(2)]),
+            Some(spell),
+            None,
+        )
+        .expect("Could not pay the mana cost.");
+        for _ in 0..2 {
+            game.spawn_object(forest, player, Zone::Battlefield)
+                .expect("Could not spawn the object.");
+        }
+        let foretold = game
+            .spawn_object(
+                &instant_card(vec![Ability::Keyword(KeywordAbility::Foretell(ManaCost(
+                    vec![],
+                )))]),
+                player,
+                Zone::Hand(player),
+            )
+            .expect("Could not spawn the object.");
+        game.foretell(player, foretold, None)
+            .expect("Could not foretell the card.");
+
+        let names = |cards: Vec<ExiledCard>| {
+            cards
+                .into_iter()
+                .map(|it| (it.card.name, it.face_down))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(game.exile(player).expect("Could not list the exile.")),
+            vec![
+                ("Forest".into(), false),
+                ("Forest".into(), false),
+                ("Test Card".into(), true),
+            ]
+        );
+        assert_eq!(
+            names(
+                game.exile_for(player, Some(opponent))
+                    .expect("Could not list the exile.")
+            ),
+            vec![
+                ("Forest".into(), false),
+                ("Forest".into(), false),
+                ("a card".into(), true),
+            ]
+        );
+        assert_eq!(game.exile(opponent), Ok(vec![]));
+        assert!(matches!(
+            game.events_for(Some(opponent)).last(),
+            Some(GameEvent::ZoneChanged { object, to: Zone::Exile, face_down: true, .. })
+                if object.id.is_none()
+        ));
+
+        // Processors put cards their opponents own from exile into their graveyards.
+        assert!(matches!(
+            game.put_exiled_card_into_graveyard(player, graveyard[0]),
+            Err(EngineError::IllegalAction(_))
+        ));
+        game.put_exiled_card_into_graveyard(opponent, graveyard[0])
+            .expect("Could not put the card into the graveyard.");
+        assert_eq!(
+            game.zones.zone_of(graveyard[0]),
+            Some(Zone::Graveyard(player))
+        );
+        assert_eq!(game.exile(player).map(|it| it.len()), Ok(2));
+    }
+
+    #[test]
+    fn only_the_newest_world_permanent_and_planeswalker_of_each_type_remain() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let opponent = game.players()[1].id;
+        let card = |name: &str, card_type, subtype, supertype| {
+            let card = Card::builder()
+                .name(Name(name.into()))
+                .type_line(TypeLine {
+                    card_type: [card_type].into(),
+                    subtype,
+                    supertype,
+                })
+                .build()
+                .expect("Failed to build the card.");
+            Arc::new(card)
+        };
+        let world = card(
+            "Concordant Crossroads",
+            CardType::Enchantment,
+            [].into(),
+            [Supertype::World].into(),
+        );
+        let older = game
+            .spawn_object(&world, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let newer = game
+            .spawn_object(&world, opponent, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        assert_eq!(game.zones.zone_of(older), Some(Zone::Graveyard(player)));
+        assert_eq!(game.zones.zone_of(newer), Some(Zone::Battlefield));
+
+        let jace = |name| {
+            card(
+                name,
+                CardType::Planeswalker,
+                [Subtype::Planeswalker(PlaneswalkerType::Jace)].into(),
+                [].into(),
+            )
+        };
+        let beleren = game
+            .spawn_object(&jace("Jace Beleren"), player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let sculptor = game
+            .spawn_object(&jace("Jace, the Mind Sculptor"), player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        assert_eq!(game.zones.zone_of(beleren), Some(Zone::Battlefield));
+
+        game.set_planeswalker_uniqueness_rule(true);
+        let memory_adept = game
+            .spawn_object(&jace("Jace, Memory Adept"), player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let opponents_jace = game
+            .spawn_object(&jace("Jace Beleren"), opponent, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        for jace in [beleren, sculptor] {
+            assert_eq!(game.zones.zone_of(jace), Some(Zone::Graveyard(player)));
+        }
+        assert_eq!(game.zones.zone_of(memory_adept), Some(Zone::Battlefield));
+        assert_eq!(game.zones.zone_of(opponents_jace), Some(Zone::Battlefield));
+        assert_eq!(game.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn the_legend_rule_keeps_the_newest_legendary_permanent_of_each_player() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let opponent = game.players()[1].id;
+        let card = find_card_by_name("Polukranos, Unchained").expect("Could not find the card.");
+        let older = game
+            .spawn_object(card, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let opponents = game
+            .spawn_object(card, opponent, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let newer = game
+            .spawn_object(card, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+
+        assert_eq!(game.zones.zone_of(older), Some(Zone::Graveyard(player)));
+        assert_eq!(game.zones.zone_of(opponents), Some(Zone::Battlefield));
+        assert_eq!(game.zones.zone_of(newer), Some(Zone::Battlefield));
+    }
+
+    #[test]
+    fn blocked_attackers_assign_lethal_damage_along_the_damage_assignment_order() {
+        let mut game = Game::new(2);
+        let [player, opponent] = [0, 1].map(|it| game.players()[it].id);
+        let attacker = game
+            .spawn_object(&vanilla_creature(5, 5), opponent, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let [first, second] = [0, 1].map(|_| {
+            game.spawn_object(&vanilla_creature(2, 2), player, Zone::Battlefield)
+                .expect("Could not spawn the object.")
+        });
+        game.next_turn().expect("Could not begin the next turn.");
+        game.begin_step(Step::DeclareAttackers)
+            .expect("Could not begin the step.");
+        game.declare_attackers(opponent, &[attacker])
+            .expect("Could not declare the attackers.");
+        game.begin_step(Step::DeclareBlockers)
+            .expect("Could not begin the step.");
+        game.declare_blockers(player, &[(second, attacker), (first, attacker)])
+            .expect("Could not declare the blockers.");
+        assert!(matches!(
+            game.order_blockers(player, attacker, &[first, second]),
+            Err(EngineError::IllegalTiming(_))
+        ));
+        assert!(matches!(
+            game.order_blockers(opponent, attacker, &[first]),
+            Err(EngineError::IllegalAction(_))
+        ));
+        game.order_blockers(opponent, attacker, &[first, second])
+            .expect("Could not order the blockers.");
+        assert_eq!(game.combat().blockers(attacker), [first, second]);
+
+        let permanent = PermanentOrPlayer::Permanent;
+        assert!(matches!(
+            game.assign_combat_damage(
+                attacker,
+                vec![(permanent(first), 1), (permanent(second), 4)]
+            ),
+            Err(EngineError::IllegalAction(_))
+        ));
+        assert!(matches!(
+            game.assign_combat_damage(
+                attacker,
+                vec![(permanent(first), 2), (permanent(second), 2)]
+            ),
+            Err(EngineError::IllegalAction(_))
+        ));
+        game.assign_combat_damage(
+            attacker,
+            vec![(permanent(first), 3), (permanent(second), 2)],
+        )
+        .expect("Could not assign the combat damage.");
+        let [first, second] = [first, second].map(|it| game.object_id(it));
+        game.begin_step(Step::CombatDamage)
+            .expect("Could not begin the step.");
+
+        // Both blockers are dealt lethal damage and die, so the damage is read from the events.
+        let damage_dealt = |game: &Game, id| {
+            game.events()
+                .iter()
+                .filter_map(|it| match it {
+                    GameEvent::DamageDealt {
+                        recipient: DamageRecipient::Permanent(recipient),
+                        amount,
+                        ..
+                    } if recipient.id == id => Some(amount),
+                    _ => None,
+                })
+                .sum::<u64>()
+        };
+        assert_eq!(damage_dealt(&game, first), 3);
+        assert_eq!(damage_dealt(&game, second), 2);
+        assert_eq!(
+            game.world_mut()
+                .get::<MarkedDamage>(attacker)
+                .map_or(0, |it| it.0),
+            4
+        );
+        assert_eq!(
+            game.player(player)
+                .expect("Could not access the player.")
+                .life,
+            20
+        );
+    }
+
+    #[test]
+    fn creatures_block_additional_attackers_only_if_an_effect_allows_it() {
+        let mut game = Game::new(2);
+        let [player, opponent] = [0, 1].map(|it| game.players()[it].id);
+        let [first, second, unblocked] = [0, 1, 2].map(|_| {
+            game.spawn_object(&vanilla_creature(2, 2), opponent, Zone::Battlefield)
+                .expect("Could not spawn the object.")
+        });
+        let trampler = game
+            .spawn_object(
+                &creature_card(vec![Ability::Keyword(KeywordAbility::Trample)]),
+                opponent,
+                Zone::Battlefield,
+            )
+            .expect("Could not spawn the object.");
+        let blocker = game
+            .spawn_object(&vanilla_creature(1, 6), player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        game.next_turn().expect("Could not begin the next turn.");
+        game.begin_step(Step::DeclareAttackers)
+            .expect("Could not begin the step.");
+        game.declare_attackers(opponent, &[first, second, unblocked, trampler])
+            .expect("Could not declare the attackers.");
+        game.begin_step(Step::DeclareBlockers)
+            .expect("Could not begin the step.");
+        let blocks = [(blocker, first), (blocker, second), (blocker, trampler)];
+
+        assert!(matches!(
+            game.declare_blockers(player, &blocks[..2]),
+            Err(EngineError::IllegalAction(_))
+        ));
+        // Target creature can block an additional creature each combat this turn.
+        game.add_effect(ContinuousEffect {
+            source: None,
+            controller: player,
+            duration: Duration::UntilEndOfTurn,
+            kind: EffectKind::BlockAdditionalCreatures {
+                target: blocker,
+                amount: 2,
+            },
+        });
+        game.declare_blockers(player, &blocks)
+            .expect("Could not declare the blockers.");
+        assert_eq!(game.combat().blocked_by(blocker), [first, second, trampler]);
+        assert!(game.combat().is_blocked(second));
+        assert!(!game.combat().is_blocked(unblocked));
+        game.begin_step(Step::CombatDamage)
+            .expect("Could not begin the step.");
+
+        // The blocker deals its damage to the first creature in its order, the unblocked creature
+        // deals damage to the player, and nothing of the 1/1 trampler is left over.
+        let marked_damage = |game: &mut Game, entity| {
+            game.world_mut()
+                .get::<MarkedDamage>(entity)
+                .map_or(0, |it| it.0)
+        };
+        assert_eq!(marked_damage(&mut game, first), 1);
+        assert_eq!(marked_damage(&mut game, second), 0);
+        assert_eq!(marked_damage(&mut game, blocker), 5);
+        assert_eq!(
+            game.player(player)
+                .expect("Could not access the player.")
+                .life,
+            18
+        );
+    }
+
+    /// Spawns the creatures of the defending player and the active player, declares the latter as
+    /// attackers and begins the declare blockers step.
+    fn combat_with(
+        game: &mut Game,
+        blockers: &[Arc<Card>],
+        attackers: &[Arc<Card>],
+    ) -> (Vec<Entity>, Vec<Entity>) {
+        let [player, opponent] = [0, 1].map(|it| game.players()[it].id);
+        let mut spawn = |cards: &[Arc<Card>], owner| {
+            cards
+                .iter()
+                .map(|it| {
+                    game.spawn_object(it, owner, Zone::Battlefield)
+                        .expect("Could not spawn the object.")
+                })
+                .collect::<Vec<_>>()
+        };
+        let blockers = spawn(blockers, player);
+        let attackers = spawn(attackers, opponent);
+        game.next_turn().expect("Could not begin the next turn.");
+        game.begin_step(Step::DeclareAttackers)
+            .expect("Could not begin the step.");
+        game.declare_attackers(opponent, &attackers)
+            .expect("Could not declare the attackers.");
+        while game
+            .resolve_next_trigger(&[])
+            .expect("Could not resolve the trigger.")
+        {}
+        game.begin_step(Step::DeclareBlockers)
+            .expect("Could not begin the step.");
+        (blockers, attackers)
+    }
+
+    #[test]
+    fn attackers_whose_blockers_are_gone_stay_blocked_unless_they_have_trample() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let trampler = Arc::new(
+            Card::builder()
+                .type_line(TypeLine {
+                    card_type: [CardType::Creature].into(),
+                    subtype: [].into(),
+                    supertype: [].into(),
+                })
+                .pt(PtCharacteristic {
+                    power: PtValue::Fixed(3),
+                    toughness: PtValue::Fixed(3),
+                })
+                .abilities(vec![Ability::Keyword(KeywordAbility::Trample)])
+                .build()
+                .expect("Failed to build the card."),
+        );
+        let (blockers, attackers) = combat_with(
+            &mut game,
+            &[vanilla_creature(2, 2), vanilla_creature(2, 2)],
+            &[vanilla_creature(3, 3), trampler],
+        );
+        game.declare_blockers(
+            player,
+            &[(blockers[0], attackers[0]), (blockers[1], attackers[1])],
+        )
+        .expect("Could not declare the blockers.");
+
+        for &blocker in &blockers {
+            game.destroy(blocker)
+                .expect("Could not destroy the blocker.");
+        }
+        assert!(game.combat().is_blocked(attackers[0]));
+        assert_eq!(game.combat().blockers(attackers[0]), []);
+        game.begin_step(Step::CombatDamage)
+            .expect("Could not begin the step.");
+
+        // 702.19e If all the creatures blocking it are removed from combat, a creature with
+        //         trample assigns all its damage to the player [...]
+        assert_eq!(
+            game.player(player)
+                .expect("Could not access the player.")
+                .life,
+            17
+        );
+        assert!(!game
+            .events()
+            .iter()
+            .any(|it| matches!(it, GameEvent::DamageDealt { source, .. }
+                if source.id == game.object_id(attackers[0]))));
+    }
+
+    #[test]
+    fn attackers_that_leave_the_battlefield_or_regenerate_deal_no_combat_damage() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let (blockers, attackers) = combat_with(
+            &mut game,
+            &[vanilla_creature(2, 2), vanilla_creature(2, 2)],
+            &[vanilla_creature(4, 4), vanilla_creature(4, 4)],
+        );
+        game.declare_blockers(
+            player,
+            &[(blockers[0], attackers[0]), (blockers[1], attackers[1])],
+        )
+        .expect("Could not declare the blockers.");
+        game.assign_combat_damage(
+            attackers[0],
+            vec![(PermanentOrPlayer::Permanent(blockers[0]), 4)],
+        )
+        .expect("Could not assign the combat damage.");
+
+        game.destroy(attackers[0])
+            .expect("Could not destroy the attacker.");
+        game.create_regeneration_shield(attackers[1])
+            .expect("Could not create the regeneration shield.");
+        game.destroy(attackers[1])
+            .expect("Could not destroy the attacker.");
+        assert!(game.is_on_battlefield(attackers[1]));
+        assert!(game.world_mut().get::<Attacking>(attackers[1]).is_err());
+        assert_eq!(game.combat().attackers().count(), 0);
+        assert_eq!(game.combat().blocked_by(blockers[1]), []);
+        game.begin_step(Step::CombatDamage)
+            .expect("Could not begin the step.");
+
+        assert!(!game
+            .events()
+            .iter()
+            .any(|it| matches!(it, GameEvent::DamageDealt { .. })));
+        assert_eq!(
+            game.player(player)
+                .expect("Could not access the player.")
+                .life,
+            20
+        );
+    }
+
+    #[test]
+    fn indestructible_creatures_survive_lethal_damage_but_not_toughness_0_or_less() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let (blockers, attackers) = combat_with(
+            &mut game,
+            &[creature_card(vec![Ability::Keyword(
+                KeywordAbility::Indestructible,
+            )])],
+            &[vanilla_creature(3, 3)],
+        );
+        let blocker = blockers[0];
+        game.destroy(blocker)
+            .expect("Could not destroy the blocker.");
+        assert!(game.is_on_battlefield(blocker));
+
+        game.declare_blockers(player, &[(blocker, attackers[0])])
+            .expect("Could not declare the blockers.");
+        game.begin_step(Step::CombatDamage)
+            .expect("Could not begin the step.");
+        assert!(game.is_on_battlefield(blocker));
+        assert_eq!(
+            game.world_mut()
+                .get::<MarkedDamage>(blocker)
+                .map_or(0, |it| it.0),
+            3
+        );
+
+        game.add_effect(ContinuousEffect {
+            source: None,
+            controller: player,
+            duration: Duration::UntilEndOfTurn,
+            kind: EffectKind::ModifyPowerToughness {
+                target: blocker,
+                power: 0,
+                toughness: -1,
+            },
+        });
+        game.check_state_based_actions()
+            .expect("Could not check the state-based actions.");
+        assert_eq!(game.zones.zone_of(blocker), Some(Zone::Graveyard(player)));
+    }
+
+    #[test]
+    fn regenerated_creatures_survive_only_their_next_destruction() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let (blockers, attackers) = combat_with(
+            &mut game,
+            &[vanilla_creature(2, 2)],
+            &[vanilla_creature(3, 3)],
+        );
+        let blocker = blockers[0];
+        game.declare_blockers(player, &[(blocker, attackers[0])])
+            .expect("Could not declare the blockers.");
+        game.create_regeneration_shield(blocker)
+            .expect("Could not create the regeneration shield.");
+        game.begin_step(Step::CombatDamage)
+            .expect("Could not begin the step.");
+
+        // The lethal damage destroys the blocker, which regenerates instead.
+        assert!(game.is_on_battlefield(blocker));
+        assert!(game.world_mut().get::<Tapped>(blocker).is_ok());
+        assert!(game.world_mut().get::<MarkedDamage>(blocker).is_err());
+
+        game.destroy(blocker)
+            .expect("Could not destroy the blocker.");
+        assert_eq!(game.zones.zone_of(blocker), Some(Zone::Graveyard(player)));
+    }
+
+    #[test]
+    fn combat_damage_stops_once_the_defending_player_lost_the_game() {
+        let mut game = Game::new(2);
+        let [player, opponent] = [0, 1].map(|it| game.players()[it].id);
+        let (blockers, attackers) = combat_with(
+            &mut game,
+            &[vanilla_creature(2, 2)],
+            &[vanilla_creature(1, 1), vanilla_creature(2, 2)],
+        );
+        game.declare_blockers(player, &[(blockers[0], attackers[1])])
+            .expect("Could not declare the blockers.");
+        game.player_mut(player)
+            .expect("Could not access the player.")
+            .life = 1;
+
+        // The unblocked attacker deals its damage first, so the blocker leaves the game with its
+        // owner before it is dealt damage or deals damage itself.
+        game.begin_step(Step::CombatDamage)
+            .expect("Could not begin the step.");
+        assert_eq!(game.outcome(), Some(GameOutcome::Win(opponent)));
+        assert_eq!(
+            game.events()
+                .iter()
+                .filter(|it| matches!(it, GameEvent::DamageDealt { .. }))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn creatures_with_defender_cant_attack() {
+        let mut game = Game::new(2);
+        let player = game.turn().active_player;
+        let [defender, attacker] =
+            [vec![Ability::Keyword(KeywordAbility::Defender)], vec![]].map(|abilities| {
+                let creature = game
+                    .spawn_object(&creature_card(abilities), player, Zone::Battlefield)
+                    .expect("Could not spawn the object.");
+                let _ = game.world_mut().remove_one::<SummoningSick>(creature);
+                creature
+            });
+        game.begin_step(Step::DeclareAttackers)
+            .expect("Could not begin the step.");
+
+        assert!(!game.can_attack(defender));
+        assert!(matches!(
+            game.declare_attackers(player, &[defender, attacker]),
+            Err(EngineError::IllegalAction(_))
+        ));
+        game.declare_attackers(player, &[attacker])
+            .expect("Could not declare the attackers.");
+        assert!(game.world_mut().get::<Attacking>(defender).is_err());
+    }
+
+    #[test]
+    fn creatures_with_vigilance_dont_tap_to_attack() {
+        let mut game = Game::new(2);
+        let player = game.turn().active_player;
+        let [vigilant, attacker] =
+            [vec![Ability::Keyword(KeywordAbility::Vigilance)], vec![]].map(|abilities| {
+                let creature = game
+                    .spawn_object(&creature_card(abilities), player, Zone::Battlefield)
+                    .expect("Could not spawn the object.");
+                let _ = game.world_mut().remove_one::<SummoningSick>(creature);
+                creature
+            });
+        game.begin_step(Step::DeclareAttackers)
+            .expect("Could not begin the step.");
+
+        game.declare_attackers(player, &[vigilant, attacker])
+            .expect("Could not declare the attackers.");
+        assert!(game.world_mut().get::<Attacking>(vigilant).is_ok());
+        assert!(game.world_mut().get::<Tapped>(vigilant).is_err());
+        assert!(game.world_mut().get::<Tapped>(attacker).is_ok());
+    }
+
+    #[test]
+    fn creatures_with_haste_attack_the_turn_they_enter_the_battlefield() {
+        let mut game = Game::new(2);
+        let player = game.turn().active_player;
+        let [hasty, summoning_sick] =
+            [vec![Ability::Keyword(KeywordAbility::Haste)], vec![]].map(|abilities| {
+                game.spawn_object(&creature_card(abilities), player, Zone::Battlefield)
+                    .expect("Could not spawn the object.")
+            });
+        game.begin_step(Step::DeclareAttackers)
+            .expect("Could not begin the step.");
+
+        assert!(game.can_attack(hasty));
+        assert!(!game.can_attack(summoning_sick));
+        assert!(matches!(
+            game.declare_attackers(player, &[hasty, summoning_sick]),
+            Err(EngineError::IllegalAction(_))
+        ));
+        game.declare_attackers(player, &[hasty])
+            .expect("Could not declare the attackers.");
+        assert!(game.world_mut().get::<Attacking>(hasty).is_ok());
+    }
+
+    #[test]
+    fn creatures_with_flash_are_cast_during_the_opponents_turn() {
+        let mut game = Game::new(2);
+        let player = game.players()[1].id;
+        assert_ne!(game.turn().active_player, player);
+        let [flash, creature] =
+            [vec![Ability::Keyword(KeywordAbility::Flash)], vec![]].map(|abilities| {
+                let card = Card::builder()
+                    .type_line(TypeLine {
+                        card_type: [CardType::Creature].into(),
+                        subtype: [].into(),
+                        supertype: [].into(),
+                    })
+                    .mana_cost(ManaCost(vec![]))
+                    .pt(PtCharacteristic {
+                        power: PtValue::Fixed(1),
+                        toughness: PtValue::Fixed(1),
+                    })
+                    .abilities(abilities)
+                    .build()
+                    .expect("Failed to build the card.");
+                game.spawn_object(&Arc::new(card), player, Zone::Hand(player))
+                    .expect("Could not spawn the object.")
+            });
+        game.begin_step(Step::PrecombatMain)
+            .expect("Could not begin the step.");
+
+        let castable = game
+            .casting_options(player)
+            .expect("Could not list the casting options.")
+            .into_iter()
+            .map(|it| it.spell)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            castable,
+            [game.object_id(flash).expect("Could not find the id.")]
+        );
+        assert!(!castable.contains(&game.object_id(creature).expect("Could not find the id.")));
+        game.cast_spell(player, flash, None)
+            .expect("Could not cast the spell.");
+        game.resolve_spell(flash, &[])
+            .expect("Could not resolve the spell.");
+        assert!(game.is_on_battlefield(flash));
+    }
+
+    #[test]
+    fn goaded_creatures_attack_each_combat_if_able() {
+        let mut game = Game::new(2);
+        let [player, opponent] = [0, 1].map(|it| game.players()[it].id);
+        let [goaded, tapped, other] = [0, 1, 2].map(|_| {
+            game.spawn_object(&creature_card(vec![]), opponent, Zone::Battlefield)
+                .expect("Could not spawn the object.")
+        });
+        for creature in [goaded, tapped] {
+            game.goad(player, creature)
+                .expect("Could not goad the creature.");
+        }
+        game.next_turn().expect("Could not begin the next turn.");
+        game.world_mut()
+            .insert_one(tapped, Tapped)
+            .expect("Could not tap the creature.");
+        game.begin_step(Step::DeclareAttackers)
+            .expect("Could not begin the step.");
+
+        for attackers in [&[][..], &[other]] {
+            assert!(matches!(
+                game.declare_attackers(opponent, attackers),
+                Err(EngineError::IllegalAction(message)) if message.contains("Goaded")
+            ));
+        }
+        assert!(game.combat().attackers().next().is_none());
+        game.declare_attackers(opponent, &[goaded])
+            .expect("Could not declare the attackers.");
+        assert_eq!(game.combat().defender(goaded), Some(player));
+
+        for step in [Step::EndOfCombat, Step::End] {
+            game.begin_step(step).expect("Could not begin the step.");
+        }
+        game.next_turn().expect("Could not begin the next turn.");
+        game.next_turn().expect("Could not begin the next turn.");
+        game.begin_step(Step::DeclareAttackers)
+            .expect("Could not begin the step.");
+        game.declare_attackers(opponent, &[])
+            .expect("Could not declare the attackers.");
+    }
+
+    #[test]
+    fn attack_requirements_are_obeyed_as_far_as_restrictions_allow() {
+        let mut game = Game::new(2);
+        let [player, opponent] = [0, 1].map(|it| game.players()[it].id);
+        let [creature, pacifist] = [0, 1].map(|_| {
+            game.spawn_object(&creature_card(vec![]), opponent, Zone::Battlefield)
+                .expect("Could not spawn the object.")
+        });
+        let constrain = |game: &mut Game, target, constraint| {
+            game.add_effect(ContinuousEffect {
+                source: None,
+                controller: opponent,
+                duration: Duration::Permanent,
+                kind: EffectKind::AttackConstraint { target, constraint },
+            });
+        };
+        let island = Subtype::Land(LandType::Basic(BasicLandType::Island));
+        constrain(&mut game, creature, AttackConstraint::MustAttack);
+        constrain(
+            &mut game,
+            creature,
+            AttackConstraint::CantAttackUnlessDefenderControls(island),
+        );
+        constrain(&mut game, pacifist, AttackConstraint::MustAttack);
+        constrain(
+            &mut game,
+            pacifist,
+            AttackConstraint::CantAttackPlayer(player),
+        );
+        game.next_turn().expect("Could not begin the next turn.");
+        game.begin_step(Step::DeclareAttackers)
+            .expect("Could not begin the step.");
+
+        for attackers in [&[creature][..], &[pacifist]] {
+            assert!(matches!(
+                game.declare_attackers(opponent, attackers),
+                Err(EngineError::IllegalAction(message)) if message.contains("CantAttack")
+            ));
+        }
+        let land = Card::builder()
+            .type_line(TypeLine {
+                card_type: [CardType::Land].into(),
+                subtype: [island].into(),
+                supertype: [].into(),
+            })
+            .build()
+            .expect("Failed to build the card.");
+        game.spawn_object(&Arc::new(land), player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        assert!(matches!(
+            game.declare_attackers(opponent, &[]),
+            Err(EngineError::IllegalAction(message)) if message.contains("MustAttack")
+        ));
+        game.declare_attackers(opponent, &[creature])
+            .expect("Could not declare the attackers.");
+        assert!(matches!(
+            game.declare_attackers(opponent, &[creature]),
+            Err(EngineError::IllegalAction(_))
+        ));
+    }
+
+    #[test]
+    fn ninjutsu_swaps_an_unblocked_attacker_for_the_ninja() {
+        let mut game = Game::new(2);
+        let [player, opponent] = [0, 1].map(|it| game.players()[it].id);
+        // Whenever this creature attacks, you gain 1 life.
+        let attack_trigger = Ability::Triggered(TriggeredAbility {
+            condition: TriggerCondition::Attacks(TriggerSubject::This),
+            intervening_if: None,
+            effects: vec![Effect::GainLife(1)],
+        });
+        let ninja = Card::builder()
+            .type_line(TypeLine {
+                card_type: [CardType::Creature].into(),
+                subtype: [].into(),
+                supertype: [].into(),
+            })
+            .pt(PtCharacteristic {
+                power: PtValue::Fixed(3),
+                toughness: PtValue::Fixed(2),
+            })
+            .abilities(vec![
+                Ability::Keyword(KeywordAbility::Ninjutsu(ManaCost(vec![
+                    ManaSymbol::Generic(1),
+                ]))),
+                attack_trigger.clone(),
+            ])
+            .build()
+            .expect("Failed to build the card.");
+        let ninja = game
+            .spawn_object(&Arc::new(ninja), opponent, Zone::Hand(opponent))
+            .expect("Could not spawn the object.");
+        game.spawn_object(
+            find_card_by_name("Forest").expect("Could not find the card."),
+            opponent,
+            Zone::Battlefield,
+        )
+        .expect("Could not spawn the object.");
+        let (blockers, attackers) = combat_with(
+            &mut game,
+            &[vanilla_creature(2, 2)],
+            &[creature_card(vec![attack_trigger]), vanilla_creature(2, 2)],
+        );
+        let [unblocked, blocked] = [attackers[0], attackers[1]];
+        assert_eq!(game.pending_triggers().len(), 0);
+        assert_eq!(
+            game.player(opponent)
+                .expect("Could not access the player.")
+                .life,
+            21
+        );
+
+        assert!(matches!(
+            game.ninjutsu(opponent, ninja, unblocked, None),
+            Err(EngineError::IllegalTiming(_))
+        ));
+        game.declare_blockers(player, &[(blockers[0], blocked)])
+            .expect("Could not declare the blockers.");
+        assert!(matches!(
+            game.ninjutsu(opponent, ninja, blocked, None),
+            Err(EngineError::IllegalAction(_))
+        ));
+        game.ninjutsu(opponent, ninja, unblocked, None)
+            .expect("Could not activate ninjutsu.");
+        assert_eq!(game.zones.zone_of(unblocked), Some(Zone::Hand(opponent)));
+        assert_eq!(game.zones.zone_of(ninja), Some(Zone::Battlefield));
+        assert!(game.world_mut().get::<Tapped>(ninja).is_ok());
+        assert!(game.world_mut().get::<Attacking>(ninja).is_ok());
+        assert_eq!(game.combat().defender(ninja), Some(player));
+        assert!(!game.combat().is_blocked(ninja));
+        assert_eq!(game.pending_triggers().len(), 0);
+
+        game.begin_step(Step::CombatDamage)
+            .expect("Could not begin the step.");
+        assert_eq!(
+            game.player(player)
+                .expect("Could not access the player.")
+                .life,
+            17
+        );
+    }
+
+    #[test]
+    fn modal_spells_are_cast_with_modes_that_have_legal_targets() {
+        let mut game = Game::new(2);
+        let [player, opponent] = [0, 1].map(|it| game.players()[it].id);
+        // Choose one — • Draw a card. • Destroy target artifact.
+        let spell = game
+            .spawn_object(
+                &instant_card(vec![Ability::ModalSpell(Modes {
+                    count: ModeCount::Exactly(1),
+                    modes: vec![
+                        vec![Effect::DrawCards(Quantity::Fixed(1))],
+                        vec![Effect::DestroyTarget(ObjectFilter {
+                            card_type: Some(CardType::Artifact),
+                            ..ObjectFilter::default()
+                        })],
+                    ],
+                })]),
+                player,
+                Zone::Hand(player),
+            )
+            .expect("Could not spawn the object.");
+        let card = game
+            .spawn_object(&creature_card(vec![]), player, Zone::Library(player))
+            .expect("Could not spawn the object.");
+        let modes = |game: &Game| {
+            game.casting_options(player)
+                .expect("Could not determine the casting options.")
+                .into_iter()
+                .map(|it| it.modes)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(modes(&game), vec![vec![0]]);
+        assert!(matches!(
+            game.cast_spell(player, spell, None),
+            Err(EngineError::IllegalAction(_))
+        ));
+        assert!(matches!(
+            game.cast_spell_with_modes(player, spell, &[1], None),
+            Err(EngineError::IllegalAction(_))
+        ));
+        let lantern = game
+            .spawn_object(
+                find_card_by_name("Soul-Guide Lantern").expect("Could not find the card."),
+                opponent,
+                Zone::Battlefield,
+            )
+            .expect("Could not spawn the object.");
+        assert_eq!(modes(&game), vec![vec![0], vec![1]]);
+
+        game.cast_spell_with_modes(player, spell, &[1], None)
+            .expect("Could not cast the spell.");
+        let copy = game
+            .copy_spell(spell, player)
+            .expect("Could not copy the spell.");
+        assert_eq!(
+            game.world_mut()
+                .get::<ChosenModes>(copy)
+                .expect("Could not find the modes.")
+                .0,
+            [1]
+        );
+        game.resolve_spell(copy, &[PermanentOrPlayer::Permanent(lantern)])
+            .expect("Could not resolve the spell.");
+        assert_eq!(game.zones.zone_of(lantern), Some(Zone::Graveyard(opponent)));
+        game.resolve_spell(spell, &[PermanentOrPlayer::Permanent(lantern)])
+            .expect("Could not resolve the spell.");
+        assert_eq!(game.zones.zone_of(card), Some(Zone::Library(player)));
+        assert!(game.world_mut().get::<ChosenModes>(spell).is_err());
+    }
+
+    #[test]
+    fn choices_are_made_as_permanents_enter_the_battlefield() {
+        let (mut game, [player, opponent]) = built_game("20 Plains");
+        let with_entry_choice = |card_type, choice| {
+            let card = Card::builder()
+                .type_line(TypeLine {
+                    card_type: [card_type].into(),
+                    subtype: [].into(),
+                    supertype: [].into(),
+                })
+                .mana_cost(ManaCost(vec![]))
+                .abilities(vec![
+                    Ability::Static(StaticAbility::AsEntersBattlefield(choice)),
+                    // Creatures you control of the chosen color get +1/+1.
+                    Ability::Static(StaticAbility::ModifyPowerToughness {
+                        affected: ObjectFilter {
+                            card_type: Some(CardType::Creature),
+                            you_control: true,
+                            of_chosen_color: true,
+                            ..ObjectFilter::default()
+                        },
+                        power: 1,
+                        toughness: 1,
+                    }),
+                ])
+                .build()
+                .expect("Failed to build the card.");
+            Arc::new(card)
+        };
+        // As this land enters the battlefield, you may pay 2 life. If you don't, it enters the
+        // battlefield tapped.
+        let shockland = with_entry_choice(CardType::Land, EntryChoice::PayLifeOrEnterTapped(2));
+        let elves = game
+            .spawn_object(
+                find_card_by_name("Llanowar Elves").expect("Could not find the card."),
+                player,
+                Zone::Battlefield,
+            )
+            .expect("Could not spawn the object.");
+        let tapped = game
+            .spawn_object(&shockland, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        assert!(game.world_mut().get::<Tapped>(tapped).is_ok());
+        assert_eq!(game.power_toughness(elves), Some((1, 1)));
+
+        // The land waits in the hand until its controller decides.
+        let untapped = game
+            .spawn_object(&shockland, player, Zone::Hand(player))
+            .expect("Could not spawn the object.");
+        assert_eq!(game.player_to_act(), Ok(Some(player)));
+        let card = game
+            .object_id(untapped)
+            .expect("Could not find the object id.");
+        game.apply(player, GameAction::PlayLand { card })
+            .expect("Could not play the land.");
+        assert_eq!(game.zones.zone_of(untapped), Some(Zone::Hand(player)));
+        game.apply(player, GameAction::Choose { option: 0 })
+            .expect("Could not pay the life.");
+        assert_eq!(game.zones.zone_of(untapped), Some(Zone::Battlefield));
+        assert!(game.world_mut().get::<Tapped>(untapped).is_err());
+        assert_eq!(
+            game.player(player)
+                .expect("Could not access the player.")
+                .life,
+            18
+        );
+
+        // As this artifact enters the battlefield, choose a color.
+        let sun = game
+            .spawn_object(
+                &with_entry_choice(CardType::Artifact, EntryChoice::Color),
+                player,
+                Zone::Hand(player),
+            )
+            .expect("Could not spawn the object.");
+        let spell = game.object_id(sun).expect("Could not find the object id.");
+        game.apply(
+            player,
+            GameAction::CastSpell {
+                spell,
+                targets: Vec::new(),
+                x: 0,
+                modes: Vec::new(),
+            },
+        )
+        .expect("Could not cast the spell.");
+        game.apply(player, GameAction::PassPriority)
+            .expect("Could not pass priority.");
+        game.apply(opponent, GameAction::PassPriority)
+            .expect("Could not pass priority.");
+        assert_eq!(game.zones.zone_of(sun), Some(Zone::Stack));
+        let green = Color::ALL
+            .iter()
+            .position(|&it| it == Color::Green)
+            .expect("Could not find the color.");
+        game.apply(player, GameAction::Choose { option: green })
+            .expect("Could not choose the color.");
+        assert_eq!(
+            game.world_mut()
+                .get::<ChosenColor>(sun)
+                .expect("Could not find the color.")
+                .0,
+            Color::Green
+        );
+        assert_eq!(game.power_toughness(elves), Some((2, 2)));
+        game.move_object(sun, Zone::Hand(player))
+            .expect("Could not move the object.");
+        assert!(game.world_mut().get::<ChosenColor>(sun).is_err());
+        assert_eq!(game.power_toughness(elves), Some((1, 1)));
+    }
+
+    #[test]
+    fn permanents_enter_tapped_if_their_controller_cant_pay_the_life() {
+        let (mut game, [player, _]) = built_game("20 Plains");
+        // As this land enters the battlefield, you may pay 2 life. If you don't, it enters the
+        // battlefield tapped.
+        let card = Card::builder()
+            .type_line(TypeLine {
+                card_type: [CardType::Land].into(),
+                subtype: [].into(),
+                supertype: [].into(),
+            })
+            .abilities(vec![Ability::Static(StaticAbility::AsEntersBattlefield(
+                EntryChoice::PayLifeOrEnterTapped(2),
+            ))])
+            .build()
+            .expect("Failed to build the card.");
+        game.player_mut(player)
+            .expect("Could not access the player.")
+            .life = 1;
+
+        let land = game
+            .spawn_object(&Arc::new(card), player, Zone::Hand(player))
+            .expect("Could not spawn the object.");
+        assert_eq!(game.player_to_act(), Ok(Some(player)));
+        let card = game.object_id(land).expect("Could not find the object id.");
+        game.apply(player, GameAction::PlayLand { card })
+            .expect("Could not play the land.");
+        assert_eq!(
+            game.legal_actions(player),
+            Ok(vec![
+                GameAction::Choose { option: 0 },
+                GameAction::Choose { option: 1 }
+            ])
+        );
+        game.apply(player, GameAction::Choose { option: 0 })
+            .expect("Could not pay the life.");
+        assert_eq!(game.zones.zone_of(land), Some(Zone::Battlefield));
+        assert!(game.world_mut().get::<Tapped>(land).is_ok());
+        assert_eq!(
+            game.player(player)
+                .expect("Could not access the player.")
+                .life,
+            1
+        );
+    }
+
+    #[test]
+    fn creatures_with_stun_counters_stay_tapped_during_untap_steps() {
+        let mut game = Game::new(2);
+        let [player, opponent] = [0, 1].map(|it| game.players()[it].id);
+        let creature = game
+            .spawn_object(&creature_card(vec![]), opponent, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        // Tap target creature. Put two stun counters on it.
+        let spell = game
+            .spawn_object(
+                &instant_card(vec![Ability::Spell(vec![Effect::TapAndStun(2)])]),
+                player,
+                Zone::Hand(player),
+            )
+            .expect("Could not spawn the object.");
+        game.cast_spell(player, spell, None)
+            .expect("Could not cast the spell.");
+        game.resolve_spell(spell, &[PermanentOrPlayer::Permanent(creature)])
+            .expect("Could not resolve the spell.");
+        assert!(game.world_mut().get::<Tapped>(creature).is_ok());
+
+        let stun_counters = |game: &Game| {
+            game.counters(PermanentOrPlayer::Permanent(creature), &CounterKind::Stun)
+                .expect("Could not count the counters.")
+        };
+        for remaining in [1, 0] {
+            game.next_turn().expect("Could not begin the next turn.");
+            assert_eq!(game.turn().active_player, opponent);
+            assert!(game.world_mut().get::<Tapped>(creature).is_ok());
+            assert_eq!(stun_counters(&game), remaining);
+            game.next_turn().expect("Could not begin the next turn.");
+        }
+        game.next_turn().expect("Could not begin the next turn.");
+        assert!(game.world_mut().get::<Tapped>(creature).is_err());
+    }
+
+    #[test]
+    fn shield_counters_protect_from_one_destruction_or_damage_event_each() {
+        let mut game = Game::new(2);
+        let [player, opponent] = [0, 1].map(|it| game.players()[it].id);
+        let creature = game
+            .spawn_object(&creature_card(vec![]), opponent, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        game.add_counters(
+            PermanentOrPlayer::Permanent(creature),
+            CounterKind::Shield,
+            1,
+        )
+        .expect("Could not add the counters.");
+        let shield_counters = |game: &Game, entity| {
+            game.counters(PermanentOrPlayer::Permanent(entity), &CounterKind::Shield)
+                .expect("Could not count the counters.")
+        };
+
+        game.destroy(creature)
+            .expect("Could not destroy the creature.");
+        assert!(game.is_on_battlefield(creature));
+        assert_eq!(shield_counters(&game, creature), 0);
+        assert_eq!(
+            game.events().last(),
+            Some(&GameEvent::CountersRemoved {
+                holder: CounterHolder::Permanent(ObjectSnapshot::of(&game.world, creature)),
+                kind: CounterKind::Shield,
+                amount: 1,
+            })
+        );
+        game.destroy(creature)
+            .expect("Could not destroy the creature.");
+        assert_eq!(
+            *game
+                .world_mut()
+                .get::<Zone>(creature)
+                .expect("Could not find the zone."),
+            Zone::Graveyard(opponent)
+        );
+
+        let (blockers, attackers) = combat_with(
+            &mut game,
+            &[vanilla_creature(2, 2), vanilla_creature(2, 2)],
+            &[vanilla_creature(5, 5), vanilla_creature(5, 5)],
+        );
+        game.add_counters(
+            PermanentOrPlayer::Permanent(blockers[0]),
+            CounterKind::Shield,
+            1,
+        )
+        .expect("Could not add the counters.");
+        game.declare_blockers(
+            player,
+            &[(blockers[0], attackers[0]), (blockers[1], attackers[1])],
+        )
+        .expect("Could not declare the blockers.");
+        game.begin_step(Step::CombatDamage)
+            .expect("Could not begin the step.");
+
+        // The lethal damage is prevented entirely, so the blocker with the shield counter survives
+        // the state-based actions that put the other blocker into the graveyard.
+        assert!(game.is_on_battlefield(blockers[0]));
+        assert!(game.world_mut().get::<MarkedDamage>(blockers[0]).is_err());
+        assert_eq!(shield_counters(&game, blockers[0]), 0);
+        assert_eq!(
+            game.zones.zone_of(blockers[1]),
+            Some(Zone::Graveyard(player))
+        );
+        assert_eq!(
+            game.world_mut()
+                .get::<MarkedDamage>(attackers[0])
+                .map(|it| it.0)
+                .ok(),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn shield_counters_protect_from_mass_destruction() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let [shielded, unshielded] = [0, 1].map(|_| {
+            game.spawn_object(&creature_card(vec![]), player, Zone::Battlefield)
+                .expect("Could not spawn the object.")
+        });
+        game.add_counters(
+            PermanentOrPlayer::Permanent(shielded),
+            CounterKind::Shield,
+            1,
+        )
+        .expect("Could not add the counters.");
+        let creatures = ObjectFilter {
+            card_type: Some(CardType::Creature),
+            ..ObjectFilter::default()
+        };
+
+        game.destroy_all(&creatures, player)
+            .expect("Could not destroy the creatures.");
+        assert!(game.is_on_battlefield(shielded));
+        assert_eq!(
+            game.counters(PermanentOrPlayer::Permanent(shielded), &CounterKind::Shield),
+            Ok(0)
+        );
+        assert_eq!(
+            game.zones.zone_of(unshielded),
+            Some(Zone::Graveyard(player))
+        );
+
+        game.destroy_all(&creatures, player)
+            .expect("Could not destroy the creatures.");
+        assert_eq!(game.zones.zone_of(shielded), Some(Zone::Graveyard(player)));
+    }
+
+    #[test]
+    fn investigating_creates_a_clue_that_draws_a_card_when_sacrificed() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let forest = find_card_by_name("Forest").expect("Could not find the card.");
+        for _ in 0..2 {
+            game.spawn_object(forest, player, Zone::Battlefield)
+                .expect("Could not spawn the object.");
+        }
+        let card = game
+            .spawn_object(&creature_card(vec![]), player, Zone::Library(player))
+            .expect("Could not spawn the object.");
+        let spell = game
+            .spawn_object(
+                &instant_card(vec![Ability::Spell(vec![Effect::Investigate])]),
+                player,
+                Zone::Hand(player),
+            )
+            .expect("Could not spawn the object.");
+        game.cast_spell(player, spell, None)
+            .expect("Could not cast the spell.");
+        game.resolve_spell(spell, &[])
+            .expect("Could not resolve the spell.");
+
+        let clue = game
+            .world_mut()
+            .query::<&Token>()
+            .iter()
+            .map(|(entity, _)| entity)
+            .next()
+            .expect("Could not find the token.");
+        assert!(game.has_subtype(clue, Subtype::Artifact(ArtifactType::Clue)));
+        assert!(game.activate_ability(player, clue, 0, &[]).is_ok());
+        game.resolve_top_of_stack()
+            .expect("Could not resolve the ability.");
+        assert_eq!(game.zones.zone_of(card), Some(Zone::Hand(player)));
+        assert_eq!(game.zones.zone_of(clue), Some(Zone::Graveyard(player)));
+        assert!(game.activate_ability(player, clue, 0, &[]).is_err());
+    }
+
+    #[test]
+    fn exploring_puts_lands_into_the_hand_and_otherwise_grows_the_creature() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let creature = game
+            .spawn_object(&creature_card(vec![]), player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let plus_one_counters = |game: &Game| {
+            game.counters(
+                PermanentOrPlayer::Permanent(creature),
+                &CounterKind::PlusOnePlusOne,
+            )
+            .expect("Could not count the counters.")
+        };
+
+        let forest = game
+            .spawn_object(
+                find_card_by_name("Forest").expect("Could not find the card."),
+                player,
+                Zone::Library(player),
+            )
+            .expect("Could not spawn the object.");
+        let revealed = game
+            .explore(creature, &mut |_| unreachable!())
+            .expect("Could not explore.");
+        assert_eq!(revealed, Some(forest));
+        assert_eq!(game.zones.zone_of(forest), Some(Zone::Hand(player)));
+        assert_eq!(plus_one_counters(&game), 0);
+
+        let spell = game
+            .spawn_object(&instant_card(vec![]), player, Zone::Library(player))
+            .expect("Could not spawn the object.");
+        let revealed = game
+            .explore(creature, &mut |it| {
+                assert_eq!(it, spell);
+                true
+            })
+            .expect("Could not explore.");
+        assert_eq!(revealed, Some(spell));
+        assert_eq!(game.zones.zone_of(spell), Some(Zone::Graveyard(player)));
+        assert_eq!(plus_one_counters(&game), 1);
+        assert_eq!(game.power_toughness(creature), Some((2, 2)));
+    }
+
+    #[test]
+    fn effects_lasting_as_long_as_a_condition_holds_end_once_it_fails() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let (blockers, attackers) = combat_with(
+            &mut game,
+            &[vanilla_creature(1, 1), creature_card(vec![])],
+            &[vanilla_creature(0, 3)],
+        );
+        let [blocker, source] = [blockers[0], blockers[1]];
+        // Target creature gets +2/+2 for as long as you control [this creature].
+        game.add_effect(ContinuousEffect {
+            source: Some(source),
+            controller: player,
+            duration: Duration::AsLongAs(DurationCondition::YouControlSource),
+            kind: EffectKind::ModifyPowerToughness {
+                target: blocker,
+                power: 2,
+                toughness: 2,
+            },
+        });
+        game.declare_blockers(player, &[(blocker, attackers[0])])
+            .expect("Could not declare the blockers.");
+        assert_eq!(game.power_toughness(blocker), Some((3, 3)));
+
+        game.destroy(source).expect("Could not destroy the source.");
+        assert_eq!(game.power_toughness(blocker), Some((1, 1)));
+        game.begin_step(Step::CombatDamage)
+            .expect("Could not begin the step.");
+        assert_eq!(
+            game.world_mut()
+                .get::<MarkedDamage>(attackers[0])
+                .map(|it| it.0)
+                .ok(),
+            Some(1)
+        );
+
+        // Once the condition fails, the effect doesn't restart when it holds again.
+        game.world_mut()
+            .insert_one(blocker, Tapped)
+            .expect("Could not tap the creature.");
+        game.add_effect(ContinuousEffect {
+            source: None,
+            controller: player,
+            duration: Duration::AsLongAs(DurationCondition::RemainsTapped(blocker)),
+            kind: EffectKind::ModifyPowerToughness {
+                target: blocker,
+                power: 2,
+                toughness: 2,
+            },
+        });
+        assert_eq!(game.power_toughness(blocker), Some((3, 3)));
+        game.untap(blocker).expect("Could not untap the creature.");
+        game.world_mut()
+            .insert_one(blocker, Tapped)
+            .expect("Could not tap the creature.");
+        assert_eq!(game.power_toughness(blocker), Some((1, 1)));
+    }
+
+    /// Returns a card with the specified types and static abilities.
+    fn card_with_types(
+        card_type: CardType,
+        subtype: &[Subtype],
+        supertype: &[Supertype],
+        abilities: Vec<StaticAbility>,
+    ) -> Arc<Card> {
+        let card = Card::builder()
+            .type_line(TypeLine {
+                card_type: [card_type].into(),
+                subtype: subtype.iter().copied().collect(),
+                supertype: supertype.iter().copied().collect(),
+            })
+            .abilities(abilities.into_iter().map(Ability::Static).collect())
+            .build()
+            .expect("Failed to build the card.");
+        Arc::new(card)
+    }
+
+    #[test]
+    fn blood_moon_applies_before_the_land_type_effects_of_lands_it_affects() {
+        let land = |it| Subtype::Land(LandType::Basic(it));
+        // Nonbasic lands are Mountains.
+        let blood_moon = card_with_types(
+            CardType::Enchantment,
+            &[],
+            &[],
+            vec![StaticAbility::ChangeTypes {
+                affected: ObjectFilter {
+                    card_type: Some(CardType::Land),
+                    excluded_supertype: Some(Supertype::Basic),
+                    ..ObjectFilter::default()
+                },
+                change: TypeChange::SetBasicLandType(BasicLandType::Mountain),
+            }],
+        );
+        // Each land is a Swamp in addition to its other land types.
+        let urborg = card_with_types(
+            CardType::Land,
+            &[],
+            &[Supertype::Legendary],
+            vec![StaticAbility::ChangeTypes {
+                affected: ObjectFilter {
+                    card_type: Some(CardType::Land),
+                    ..ObjectFilter::default()
+                },
+                change: TypeChange::AddTypes {
+                    card_types: vec![],
+                    subtypes: vec![land(BasicLandType::Swamp)],
+                },
+            }],
+        );
+        let urzas_tower = card_with_types(
+            CardType::Land,
+            &[
+                Subtype::Land(LandType::Urzas),
+                Subtype::Land(LandType::Tower),
+            ],
+            &[],
+            vec![],
+        );
+
+        // Blood Moon removes the ability of Urborg, so Urborg's effect depends on it.
+        for blood_moon_first in [true, false] {
+            let mut game = Game::new(2);
+            let player = game.players()[0].id;
+            let mut spawn = |card: &Arc<Card>| {
+                game.spawn_object(card, player, Zone::Battlefield)
+                    .expect("Could not spawn the object.")
+            };
+            let forest = spawn(find_card_by_name("Forest").expect("Could not find the card."));
+            let tower = spawn(&urzas_tower);
+            let urborg = if blood_moon_first {
+                spawn(&blood_moon);
+                spawn(&urborg)
+            } else {
+                let urborg = spawn(&urborg);
+                spawn(&blood_moon);
+                urborg
+            };
+
+            for nonbasic in [urborg, tower] {
+                assert!(game.has_subtype(nonbasic, land(BasicLandType::Mountain)));
+                assert!(!game.has_subtype(nonbasic, land(BasicLandType::Swamp)));
+            }
+            assert!(!game.has_subtype(tower, Subtype::Land(LandType::Urzas)));
+            assert!(!game.has_subtype(tower, Subtype::Land(LandType::Tower)));
+            assert!(game.has_subtype(forest, land(BasicLandType::Forest)));
+            assert!(!game.has_subtype(forest, land(BasicLandType::Swamp)));
+            assert!(!game.has_subtype(forest, land(BasicLandType::Mountain)));
+        }
+    }
+
+    #[test]
+    fn type_changing_effects_apply_after_the_effects_changing_what_they_apply_to() {
+        let swamp = Subtype::Land(LandType::Basic(BasicLandType::Swamp));
+        // Each land is a Swamp in addition to its other land types.
+        let swamps_everywhere = card_with_types(
+            CardType::Enchantment,
+            &[],
+            &[],
+            vec![StaticAbility::ChangeTypes {
+                affected: ObjectFilter {
+                    card_type: Some(CardType::Land),
+                    ..ObjectFilter::default()
+                },
+                change: TypeChange::AddTypes {
+                    card_types: vec![],
+                    subtypes: vec![swamp],
+                },
+            }],
+        );
+        // Swamps are artifacts in addition to their other types.
+        let artifact_swamps = card_with_types(
+            CardType::Enchantment,
+            &[],
+            &[],
+            vec![StaticAbility::ChangeTypes {
+                affected: ObjectFilter {
+                    subtype: Some(swamp),
+                    ..ObjectFilter::default()
+                },
+                change: TypeChange::AddTypes {
+                    card_types: vec![CardType::Artifact],
+                    subtypes: vec![],
+                },
+            }],
+        );
+
+        for order in [
+            [&swamps_everywhere, &artifact_swamps],
+            [&artifact_swamps, &swamps_everywhere],
+        ] {
+            let mut game = Game::new(2);
+            let player = game.players()[0].id;
+            let forest = game
+                .spawn_object(
+                    find_card_by_name("Forest").expect("Could not find the card."),
+                    player,
+                    Zone::Battlefield,
+                )
+                .expect("Could not spawn the object.");
+            for card in order {
+                game.spawn_object(card, player, Zone::Battlefield)
+                    .expect("Could not spawn the object.");
+            }
+
+            assert!(game.has_subtype(forest, swamp));
+            assert!(game.has_card_type(forest, CardType::Artifact));
+            assert!(game.has_card_type(forest, CardType::Land));
+        }
+    }
+
+    #[test]
+    fn creatures_that_lose_all_abilities_only_have_abilities_granted_afterwards() {
+        let mut game = Game::new(2);
+        let [player, opponent] = [0, 1].map(|it| game.players()[it].id);
+        let blocker = game
+            .spawn_object(&creature_card(vec![]), player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        // Flying. {T}: Add {G}.
+        let flyer = game
+            .spawn_object(
+                &creature_card(vec![
+                    Ability::Keyword(KeywordAbility::Flying),
+                    Ability::Activated(ActivatedAbility {
+                        cost: vec![ActivationCost::Tap],
+                        effects: vec![Effect::AddMana(Mana::Monocolored(Color::Green))],
+                    }),
+                ]),
+                opponent,
+                Zone::Battlefield,
+            )
+            .expect("Could not spawn the object.");
+        game.next_turn().expect("Could not begin the next turn.");
+        assert_eq!(game.turn().active_player, opponent);
+        let is_mana_source = |game: &Game| {
+            game.mana_sources(opponent, None)
+                .expect("Could not determine the mana sources.")
+                .iter()
+                .any(|it| matches!(*it, ManaSource::Permanent { entity, .. } if entity == flyer))
+        };
+        assert!(is_mana_source(&game));
+
+        // Target creature loses all abilities until end of turn.
+        let spell = game
+            .spawn_object(
+                &instant_card(vec![Ability::Spell(vec![
+                    Effect::LoseAllAbilitiesUntilEndOfTurn,
+                ])]),
+                player,
+                Zone::Hand(player),
+            )
+            .expect("Could not spawn the object.");
+        game.cast_spell(player, spell, None)
+            .expect("Could not cast the spell.");
+        game.resolve_spell(spell, &[PermanentOrPlayer::Permanent(flyer)])
+            .expect("Could not resolve the spell.");
+        assert!(!game.has_keyword(flyer, &KeywordAbility::Flying));
+        assert!(!is_mana_source(&game));
+        assert!(game.activate_ability(opponent, flyer, 0, &[]).is_err());
+        assert!(game.world_mut().get::<Tapped>(flyer).is_err());
+
+        game.begin_step(Step::DeclareAttackers)
+            .expect("Could not begin the step.");
+        game.declare_attackers(opponent, &[flyer])
+            .expect("Could not declare the attackers.");
+        game.begin_step(Step::DeclareBlockers)
+            .expect("Could not begin the step.");
+        game.declare_blockers(player, &[(blocker, flyer)])
+            .expect("Could not declare the blockers.");
+
+        // Creatures you control gain flying until end of turn.
+        let spell = game
+            .spawn_object(
+                &instant_card(vec![Ability::Spell(vec![
+                    Effect::GainKeywordUntilEndOfTurn(
+                        ObjectFilter {
+                            card_type: Some(CardType::Creature),
+                            you_control: true,
+                            ..ObjectFilter::default()
+                        },
+                        KeywordAbility::Flying,
+                    ),
+                ])]),
+                opponent,
+                Zone::Hand(opponent),
+            )
+            .expect("Could not spawn the object.");
+        game.cast_spell(opponent, spell, None)
+            .expect("Could not cast the spell.");
+        game.resolve_spell(spell, &[])
+            .expect("Could not resolve the spell.");
+        assert!(game.has_keyword(flyer, &KeywordAbility::Flying));
+        assert!(!game.has_keyword(blocker, &KeywordAbility::Flying));
+
+        for _ in 0..2 {
+            game.next_turn().expect("Could not begin the next turn.");
+        }
+        assert!(game.has_keyword(flyer, &KeywordAbility::Flying));
+        assert!(is_mana_source(&game));
+    }
+
+    #[test]
+    fn players_who_cant_lose_lose_once_the_effect_ends() {
+        let mut game = Game::new(2);
+        let [player, opponent] = [0, 1].map(|it| game.players()[it].id);
+        // You can't lose the game.
+        let angel = game
+            .spawn_object(
+                &creature_card(vec![Ability::Static(StaticAbility::CantLoseGame)]),
+                player,
+                Zone::Battlefield,
+            )
+            .expect("Could not spawn the object.");
+        game.lose_life(player, 25)
+            .expect("Could not lose the life.");
+        assert_eq!(
+            game.player(player)
+                .expect("Could not access the player.")
+                .life,
+            -5
+        );
+        assert_eq!(game.outcome(), None);
+
+        game.destroy(angel)
+            .expect("Could not destroy the permanent.");
+        assert_eq!(game.outcome(), Some(GameOutcome::Win(opponent)));
+        assert!(game.events().contains(&GameEvent::PlayerLost { player }));
+    }
+
+    #[test]
+    fn players_win_the_game_unless_they_cant() {
+        let mut game = Game::new(2);
+        let [player, opponent] = [0, 1].map(|it| game.players()[it].id);
+        // Your opponents can't win the game.
+        let angel = game
+            .spawn_object(
+                &creature_card(vec![Ability::Static(StaticAbility::OpponentsCantWinGame)]),
+                opponent,
+                Zone::Battlefield,
+            )
+            .expect("Could not spawn the object.");
+        // You win the game.
+        let win = |game: &mut Game| {
+            let spell = game
+                .spawn_object(
+                    &instant_card(vec![Ability::Spell(vec![Effect::WinGame])]),
+                    player,
+                    Zone::Hand(player),
+                )
+                .expect("Could not spawn the object.");
+            game.cast_spell(player, spell, None)
+                .expect("Could not cast the spell.");
+            game.resolve_spell(spell, &[])
+                .expect("Could not resolve the spell.");
+        };
+        win(&mut game);
+        assert_eq!(game.outcome(), None);
+
+        game.destroy(angel)
+            .expect("Could not destroy the permanent.");
+        win(&mut game);
+        assert_eq!(game.outcome(), Some(GameOutcome::Win(player)));
+    }
+
+    #[test]
+    fn cards_put_into_the_graveyard_from_anywhere_can_return_to_the_library() {
+        let (mut game, player, _) = game_with_library(3);
+        let graveyard_size = |game: &Game| game.objects_in(Zone::Graveyard(player), |_| true).len();
+        let library_size = |game: &Game| {
+            game.library(player)
+                .expect("Could not access the library.")
+                .len()
+        };
+        // When this card is put into a graveyard from anywhere, its owner shuffles their graveyard
+        // into their library.
+        let titan = game
+            .spawn_object(
+                &creature_card(vec![Ability::Triggered(TriggeredAbility {
+                    condition: TriggerCondition::PutIntoGraveyardFromAnywhere,
+                    intervening_if: None,
+                    effects: vec![Effect::ShuffleGraveyardIntoLibrary],
+                })]),
+                player,
+                Zone::Library(player),
+            )
+            .expect("Could not spawn the object.");
+        assert_eq!(game.mill(player, 2).map(|it| it[0]), Ok(titan));
+        assert_eq!(graveyard_size(&game), 2);
+        assert_eq!(game.pending_triggers().len(), 1);
+        game.resolve_next_trigger(&[])
+            .expect("Could not resolve the trigger.");
+        assert_eq!(graveyard_size(&game), 0);
+        assert_eq!(library_size(&game), 4);
+
+        // If this card would be put into a graveyard from anywhere, reveal it and shuffle it into
+        // its owner's library instead.
+        let colossus = game
+            .spawn_object(
+                &creature_card(vec![Ability::Static(
+                    StaticAbility::ShuffleIntoLibraryInstead,
+                )]),
+                player,
+                Zone::Library(player),
+            )
+            .expect("Could not spawn the object.");
+        game.mill(player, 1).expect("Could not mill the card.");
+        assert_eq!(game.zones.zone_of(colossus), Some(Zone::Library(player)));
+        assert_eq!(graveyard_size(&game), 0);
+        assert_eq!(library_size(&game), 5);
+    }
+
+    #[test]
+    fn leaves_the_battlefield_abilities_trigger_for_any_zone_and_dies_abilities_for_graveyards() {
+        let (mut game, player, _) = game_with_library(1);
+        let triggered = |condition, amount| {
+            Ability::Triggered(TriggeredAbility {
+                condition,
+                intervening_if: None,
+                effects: vec![Effect::DrawCards(Quantity::Fixed(amount))],
+            })
+        };
+        let card = creature_card(vec![
+            triggered(TriggerCondition::LeavesBattlefield(TriggerSubject::This), 1),
+            triggered(TriggerCondition::Dies(TriggerSubject::This), 2),
+            triggered(TriggerCondition::PutIntoGraveyardFromAnywhere, 3),
+        ]);
+        let triggered_amounts = |game: &mut Game| {
+            game.pending_triggers
+                .drain(..)
+                .map(|it| match it.effects[..] {
+                    [Effect::DrawCards(Quantity::Fixed(amount))] => amount,
+                    _ => panic!("An unexpected ability triggered."),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let creature = game
+            .spawn_object(&card, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        game.move_object(creature, Zone::Hand(player))
+            .expect("Could not move the object.");
+        assert_eq!(triggered_amounts(&mut game), vec![1]);
+
+        game.move_object(creature, Zone::Battlefield)
+            .expect("Could not move the object.");
+        game.move_object(creature, Zone::Exile)
+            .expect("Could not move the object.");
+        assert_eq!(triggered_amounts(&mut game), vec![1]);
+
+        game.move_object(creature, Zone::Battlefield)
+            .expect("Could not move the object.");
+        game.destroy(creature)
+            .expect("Could not destroy the permanent.");
+        let mut amounts = triggered_amounts(&mut game);
+        amounts.sort_unstable();
+        assert_eq!(amounts, vec![1, 2, 3]);
+
+        // Cards milled from the library are put into the graveyard without dying.
+        game.spawn_object(&card, player, Zone::Library(player))
+            .expect("Could not spawn the object.");
+        game.mill(player, 1).expect("Could not mill the card.");
+        assert_eq!(triggered_amounts(&mut game), vec![3]);
+    }
+
+    #[test]
+    fn cards_are_returned_or_exiled_from_graveyards() {
+        let (mut game, player, cards) = game_with_library(3);
+        game.mill(player, 3).expect("Could not mill the cards.");
+        let cast = |game: &mut Game, effect, targets: &[PermanentOrPlayer]| {
+            let spell = game
+                .spawn_object(
+                    &instant_card(vec![Ability::Spell(vec![effect])]),
+                    player,
+                    Zone::Hand(player),
+                )
+                .expect("Could not spawn the object.");
+            game.cast_spell(player, spell, None)
+                .expect("Could not cast the spell.");
+            game.resolve_spell(spell, targets)
+                .expect("Could not resolve the spell.");
+        };
+
+        // Return target creature card from your graveyard to your hand.
+        let creature_card = ObjectFilter {
+            card_type: Some(CardType::Creature),
+            ..ObjectFilter::default()
+        };
+        assert_eq!(
+            game.graveyard_cards(player, &creature_card),
+            vec![cards[0], cards[1], cards[2]]
+        );
+        cast(
+            &mut game,
+            Effect::ReturnFromGraveyardToHand(creature_card.clone()),
+            &[PermanentOrPlayer::Permanent(cards[1])],
+        );
+        assert_eq!(game.zones.zone_of(cards[1]), Some(Zone::Hand(player)));
+
+        // Exile all cards from target player's graveyard.
+        cast(
+            &mut game,
+            Effect::ExileGraveyard,
+            &[PermanentOrPlayer::Player(player)],
+        );
+        for card in [cards[0], cards[2]] {
+            assert_eq!(game.zones.zone_of(card), Some(Zone::Exile));
+        }
+        // Only the resolved spell is left in the graveyard.
+        assert_eq!(
+            game.graveyard_cards(player, &ObjectFilter::default()).len(),
+            1
+        );
+    }
+
+    /// Builds a seeded game between two players with the same deck, in which the first player
+    /// takes the first turn.
+    fn built_game(deck: &str) -> (Game, [PlayerId; 2]) {
+        let deck = || Deck::parse(deck).expect("Could not parse the deck.");
+        let (game, players) = GameBuilder::new()
+            .seed(1)
+            .player("Alice", deck())
+            .player("Bob", deck())
+            .starting_player(0)
+            .build()
+            .expect("Could not build the game.");
+        (game, [players[0], players[1]])
+    }
+
+    #[test]
+    fn the_starting_player_skips_the_draw_of_their_first_turn() {
+        let (mut game, [player, opponent]) = built_game("20 Plains");
+        let advance_to_main_phase = |game: &mut Game| {
+            while game.turn().step != Step::PrecombatMain {
+                game.advance_step().expect("Could not advance the step.");
+            }
+        };
+
+        advance_to_main_phase(&mut game);
+        assert_eq!(game.hand_size(player), 7);
+        game.next_turn().expect("Could not begin the next turn.");
+        advance_to_main_phase(&mut game);
+        assert_eq!(game.hand_size(opponent), 8);
+        assert_eq!(game.library(opponent).map(Library::len), Ok(12));
+    }
+
+    #[test]
+    fn drawing_from_an_empty_library_loses_the_game() {
+        let mut game = Game::new(2);
+        let [player, opponent] = [0, 1].map(|it| game.players()[it].id);
+
+        assert_eq!(game.draw_card(player), Ok(None));
+        assert_eq!(game.outcome(), Some(GameOutcome::Win(opponent)));
+    }
+
+    #[test]
+    fn players_take_actions_when_it_is_their_turn_to_act() {
+        let (mut game, [player, opponent]) = built_game("20 Plains");
+        let spawn = |game: &mut Game, name: &str| {
+            let card = find_card_by_name(name).expect("Could not find the card.");
+            let entity = game
+                .spawn_object(card, player, Zone::Hand(player))
+                .expect("Could not spawn the object.");
+            game.object_id(entity)
+                .expect("Could not find the object id.")
+        };
+        let plains = spawn(&mut game, "Plains");
+        let soulmender = spawn(&mut game, "Soulmender");
+
+        // Both players pass automatically until the player stops at their main phase.
+        assert_eq!(game.player_to_act(), Ok(Some(player)));
+        assert_eq!(game.turn().step, Step::PrecombatMain);
+        assert!(matches!(
+            game.apply(opponent, GameAction::PassPriority),
+            Err(EngineError::IllegalTiming(_))
+        ));
+        assert!(game
+            .legal_actions(player)
+            .expect("Could not list the actions.")
+            .contains(&GameAction::PlayLand { card: plains }));
+        assert_eq!(game.legal_actions(opponent), Ok(Vec::new()));
+
+        game.apply(player, GameAction::PlayLand { card: plains })
+            .expect("Could not play the land.");
+        let cast = GameAction::CastSpell {
+            spell: soulmender,
+            targets: Vec::new(),
+            x: 0,
+            modes: Vec::new(),
+        };
+        assert!(game
+            .legal_actions(player)
+            .expect("Could not list the actions.")
+            .contains(&cast));
+        game.apply(player, cast).expect("Could not cast the spell.");
+        assert_eq!(game.player_to_act(), Ok(Some(player)));
+        assert_eq!(game.objects_on_stack().len(), 1);
+
+        // The spell resolves once both players have passed in succession.
+        game.apply(player, GameAction::PassPriority)
+            .expect("Could not pass priority.");
+        assert_eq!(game.player_to_act(), Ok(Some(opponent)));
+        game.apply(opponent, GameAction::PassPriority)
+            .expect("Could not pass priority.");
+        assert!(game.objects_on_stack().is_empty());
+        assert_eq!(game.creatures_controlled_by(player).len(), 1);
+        assert_eq!(game.player_to_act(), Ok(Some(player)));
+    }
+
+    #[test]
+    fn players_can_respond_to_activated_abilities() {
+        let (mut game, [player, opponent]) = built_game("20 Plains");
+        let soulmender = game
+            .spawn_object(
+                find_card_by_name("Soulmender").expect("Could not find the card."),
+                player,
+                Zone::Battlefield,
+            )
+            .expect("Could not spawn the object.");
+        let _ = game.world_mut().remove_one::<SummoningSick>(soulmender);
+        let source = game
+            .object_id(soulmender)
+            .expect("Could not find the object id.");
+        // You gain 3 life.
+        let instant = game
+            .spawn_object(
+                &instant_card(vec![Ability::Spell(vec![Effect::GainLife(3)])]),
+                opponent,
+                Zone::Hand(opponent),
+            )
+            .expect("Could not spawn the object.");
+        let spell = game
+            .object_id(instant)
+            .expect("Could not find the object id.");
+        let life = |game: &Game, player| {
+            game.player(player)
+                .expect("Could not access the player.")
+                .life
+        };
+
+        assert_eq!(game.player_to_act(), Ok(Some(player)));
+        game.apply(
+            player,
+            GameAction::ActivateAbility {
+                source,
+                index: 0,
+                targets: Vec::new(),
+            },
+        )
+        .expect("Could not activate the ability.");
+        assert_eq!(game.objects_on_stack().len(), 1);
+        assert_eq!(life(&game, player), 20);
+
+        // The opponent casts a spell in response, which resolves first.
+        game.apply(player, GameAction::PassPriority)
+            .expect("Could not pass priority.");
+        game.apply(
+            opponent,
+            GameAction::CastSpell {
+                spell,
+                targets: Vec::new(),
+                x: 0,
+                modes: Vec::new(),
+            },
+        )
+        .expect("Could not cast the spell.");
+        assert_eq!(game.objects_on_stack().len(), 2);
+        game.apply(opponent, GameAction::PassPriority)
+            .expect("Could not pass priority.");
+        game.apply(player, GameAction::PassPriority)
+            .expect("Could not pass priority.");
+        assert_eq!(game.objects_on_stack().len(), 1);
+        assert_eq!((life(&game, player), life(&game, opponent)), (20, 23));
+
+        game.apply(player, GameAction::PassPriority)
+            .expect("Could not pass priority.");
+        game.apply(opponent, GameAction::PassPriority)
+            .expect("Could not pass priority.");
+        assert!(game.objects_on_stack().is_empty());
+        assert_eq!((life(&game, player), life(&game, opponent)), (21, 23));
+        assert_eq!(game.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn players_choose_the_order_of_competing_replacement_effects() {
+        let (mut game, [player, opponent]) = built_game("20 Plains");
+        let draw = ReplaceableEvent::DrawCard { player };
+        // If you would draw a card, draw two cards instead.
+        game.add_replacement_effect(InsteadOfDrawing {
+            player,
+            events: vec![draw.clone(), draw],
+        });
+        // If you would draw a card, gain 2 life instead.
+        game.add_replacement_effect(InsteadOfDrawing {
+            player,
+            events: vec![ReplaceableEvent::GainLife { player, amount: 2 }],
+        });
+
+        // The affected player is asked as soon as the effects compete.
+        assert_eq!(game.player_to_act(), Ok(Some(player)));
+        let choice = game
+            .view_for(player)
+            .expect("Could not view the game.")
+            .choice
+            .expect("Could not find the choice.");
+        assert_eq!(
+            choice.options,
+            vec![
+                "Instead of drawing a card, draw a card and draw a card",
+                "Instead of drawing a card, gain 2 life",
+            ]
+        );
+        assert_eq!(
+            game.legal_actions(player),
+            Ok(vec![
+                GameAction::Choose { option: 0 },
+                GameAction::Choose { option: 1 },
+            ])
+        );
+        assert_eq!(game.legal_actions(opponent), Ok(Vec::new()));
+        assert!(matches!(
+            game.apply(player, GameAction::Choose { option: 2 }),
+            Err(EngineError::IllegalAction(_))
+        ));
+        game.apply(player, GameAction::Choose { option: 0 })
+            .expect("Could not choose the order.");
+        assert_eq!(game.player_to_act(), Ok(Some(player)));
+        assert_eq!(
+            game.view_for(player)
+                .expect("Could not view the game.")
+                .choice,
+            None
+        );
+
+        // Doubling the draw first replaces each of the two draws with gaining life.
+        let hand_size = game.hand_size(player);
+        game.draw_card(player).expect("Could not draw the card.");
+        assert_eq!(game.hand_size(player), hand_size);
+        assert_eq!(game.player(player).map(|it| it.life), Ok(24));
+    }
+
+    #[test]
+    fn replacement_effects_apply_to_permanents_entering_the_battlefield_and_dying() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let creature = game
+            .spawn_object(&vanilla_creature(2, 2), player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        let card = game
+            .spawn_object(&vanilla_creature(1, 1), player, Zone::Hand(player))
+            .expect("Could not spawn the object.");
+        game.add_replacement_effect(EntersTapped { object: card });
+        game.add_replacement_effect(ExiledInsteadOfDying { object: creature });
+
+        game.move_object(card, Zone::Battlefield)
+            .expect("Could not move the object.");
+        assert!(game.world.get::<Tapped>(card).is_ok());
+
+        game.destroy(creature)
+            .expect("Could not destroy the creature.");
+        assert_eq!(game.zones.zone_of(creature), Some(Zone::Exile));
+        assert_eq!(game.turn.tracker.creatures_died(), 0);
+    }
+
+    #[test]
+    fn players_decide_on_the_optional_actions_of_their_spells_before_they_resolve() {
+        let (mut game, [player, opponent]) = built_game("20 Plains");
+        // You may gain 3 life.
+        let card = instant_card(vec![Ability::Spell(vec![Effect::May {
+            effects: vec![Effect::GainLife(3)],
+            when_you_do: vec![],
+        }])]);
+        let spell = game
+            .spawn_object(&card, player, Zone::Hand(player))
+            .expect("Could not spawn the object.");
+        let spell = game
+            .object_id(spell)
+            .expect("Could not find the object id.");
+        assert_eq!(game.player_to_act(), Ok(Some(player)));
+        game.apply(
+            player,
+            GameAction::CastSpell {
+                spell,
+                targets: Vec::new(),
+                x: 0,
+                modes: Vec::new(),
+            },
+        )
+        .expect("Could not cast the spell.");
+        game.apply(player, GameAction::PassPriority)
+            .expect("Could not pass priority.");
+        game.apply(opponent, GameAction::PassPriority)
+            .expect("Could not pass priority.");
+
+        // The spell waits for its controller to decide.
+        assert_eq!(game.objects_on_stack().len(), 1);
+        assert_eq!(game.player_to_act(), Ok(Some(player)));
+        game.apply(player, GameAction::Choose { option: 0 })
+            .expect("Could not take the option.");
+        assert!(game.objects_on_stack().is_empty());
+        assert_eq!(game.player(player).map(|it| it.life), Ok(23));
+    }
+
+    #[test]
+    fn spells_resolve_with_the_targets_chosen_as_they_were_cast() {
+        let (mut game, [player, opponent]) = built_game("20 Plains");
+        // Deal 3 damage to any target.
+        let card = instant_card(vec![Ability::Spell(vec![Effect::DealDamage(
+            Quantity::Fixed(3),
+        )])]);
+        let spell = game
+            .spawn_object(&card, player, Zone::Hand(player))
+            .expect("Could not spawn the object.");
+        let spell = game
+            .object_id(spell)
+            .expect("Could not find the object id.");
+        assert_eq!(game.player_to_act(), Ok(Some(player)));
+
+        let targets = game
+            .legal_actions(player)
+            .expect("Could not list the actions.")
+            .into_iter()
+            .filter_map(|it| match it {
+                GameAction::CastSpell { targets, .. } => Some(targets),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            targets,
+            vec![vec![Target::Player(player)], vec![Target::Player(opponent)]]
+        );
+        let cast = |targets| GameAction::CastSpell {
+            spell,
+            targets,
+            x: 0,
+            modes: Vec::new(),
+        };
+        assert!(matches!(
+            game.apply(player, cast(Vec::new())),
+            Err(EngineError::InvalidTarget(_))
+        ));
+        game.apply(player, cast(vec![Target::Player(opponent)]))
+            .expect("Could not cast the spell.");
+        for it in [player, opponent] {
+            game.apply(it, GameAction::PassPriority)
+                .expect("Could not pass priority.");
+        }
+        assert_eq!(
+            game.player(opponent)
+                .expect("Could not access the player.")
+                .life,
+            17
+        );
+    }
+
+    #[test]
+    fn players_only_see_their_own_hand_and_no_libraries() {
+        let (mut game, [player, opponent]) = built_game("20 Plains");
+        let foretold = game
+            .spawn_object(
+                find_card_by_name("Soulmender").expect("Could not find the card."),
+                opponent,
+                Zone::Exile,
+            )
+            .expect("Could not spawn the object.");
+        game.world
+            .insert_one(foretold, FaceDown)
+            .expect("Could not turn the card face down.");
+
+        let view = game.view_for(player).expect("Could not view the game.");
+        assert_eq!(view.objects_in(Zone::Hand(player)).count(), 7);
+        assert_eq!(view.objects_in(Zone::Hand(opponent)).count(), 0);
+        assert_eq!(view.players[1].hand_size, 7);
+        assert!(view.players.iter().all(|it| it.library.is_none()));
+        assert_eq!(
+            view.objects_in(Zone::Exile)
+                .map(|it| it.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["a card"]
+        );
+        let view = game.view_for(opponent).expect("Could not view the game.");
+        assert_eq!(
+            view.objects_in(Zone::Exile)
+                .map(|it| it.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Soulmender"]
+        );
+        assert_eq!(
+            game.view_for(PlayerId(2)),
+            Err(EngineError::UnknownPlayer(PlayerId(2)))
+        );
     }
 }
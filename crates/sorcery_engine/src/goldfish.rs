@@ -0,0 +1,183 @@
+use std::{collections::BTreeMap, ops::RangeInclusive, sync::Arc};
+
+use rand::{prelude::SliceRandom, rngs::StdRng, SeedableRng};
+use serde::Serialize;
+
+use crate::{
+    abilities::{Ability, Effect},
+    core::{Card, CardType, Color, Deck, Mana, ManaSymbol},
+};
+
+/// 103.5. Each player draws a number of cards equal to their starting hand size, which is normally
+///        seven. [...]
+const OPENING_HAND_SIZE: usize = 7;
+
+/// The number of lands an opening hand must contain for it to be kept.
+const KEPT_LANDS: RangeInclusive<usize> = 2..=5;
+
+/// The number of turns for which castability on curve is analyzed.
+const ANALYZED_TURNS: usize = 3;
+
+/// The results of simulating opening hands of a deck without an opponent, commonly known as
+/// goldfishing.
+#[derive(Debug, PartialEq, Serialize)]
+pub(crate) struct OpeningHandReport {
+    pub(crate) iterations: usize,
+    /// The number of opening hands for each number of lands they contained.
+    pub(crate) lands_in_hand: BTreeMap<usize, usize>,
+    /// The probability of being able to cast a spell with mana value N on turn N while on the play
+    /// for the first three turns, assuming every land is played and only produces its colors.
+    pub(crate) on_curve: [f64; ANALYZED_TURNS],
+    /// The frequency of having to mulligan to six because the opening hand contained too few or
+    /// too many lands.
+    pub(crate) mulligan_rate: f64,
+}
+
+impl Deck {
+    /// Shuffles the deck and deals an opening hand the specified number of times using a random
+    /// number generator seeded with the specified seed, and reports statistics about the hands.
+    pub(crate) fn simulate_opening_hands(&self, seed: u64, iterations: usize) -> OpeningHandReport {
+        let mut random = StdRng::seed_from_u64(seed);
+        let mut cards = self.cards().iter().collect::<Vec<_>>();
+        let mut lands_in_hand = BTreeMap::new();
+        let mut on_curve = [0; ANALYZED_TURNS];
+        let mut mulligans = 0;
+
+        for _ in 0..iterations {
+            cards.shuffle(&mut random);
+            let lands = cards
+                .iter()
+                .take(OPENING_HAND_SIZE)
+                .filter(|it| is_land(it))
+                .count();
+            *lands_in_hand.entry(lands).or_default() += 1;
+            if !KEPT_LANDS.contains(&lands) {
+                mulligans += 1;
+            }
+            for (turn, castable) in (1..).zip(&mut on_curve) {
+                // 103.8a In a two-player game, the player who plays first skips the draw step (see
+                //        rule 504, “Draw Step”) of their first turn.
+                let cards_seen = &cards[..(OPENING_HAND_SIZE + turn - 1).min(cards.len())];
+                if can_cast_on_curve(cards_seen, turn as u64) {
+                    *castable += 1;
+                }
+            }
+        }
+
+        let frequency = |amount: usize| {
+            if iterations == 0 {
+                0.0
+            } else {
+                amount as f64 / iterations as f64
+            }
+        };
+        OpeningHandReport {
+            iterations,
+            lands_in_hand,
+            on_curve: on_curve.map(frequency),
+            mulligan_rate: frequency(mulligans),
+        }
+    }
+}
+
+/// Returns whether the card is a land.
+fn is_land(card: &Card) -> bool {
+    card.type_line.card_type.contains(&CardType::Land)
+}
+
+/// Returns the color of the mana the specified land produces, or `None` for colorless mana or
+/// lands without mana abilities.
+fn produced_color(land: &Card) -> Option<Color> {
+    land.abilities.iter().find_map(|ability| match ability {
+        Ability::Activated(ability) => ability.effects.iter().find_map(|effect| match effect {
+            Effect::AddMana(Mana::Monocolored(color)) => Some(*color),
+            _ => None,
+        }),
+        _ => None,
+    })
+}
+
+/// Returns whether any spell among the cards seen so far has a mana value equal to the turn and
+/// can be paid for using that many of the lands seen so far.
+fn can_cast_on_curve(seen: &[&Arc<Card>], turn: u64) -> bool {
+    let lands = seen.iter().filter(|it| is_land(it)).collect::<Vec<_>>();
+    if (lands.len() as u64) < turn {
+        return false;
+    }
+    seen.iter()
+        .filter(|it| !is_land(it) && it.mana_value() == turn)
+        .any(|spell| {
+            let mut required = BTreeMap::<Color, usize>::new();
+            for symbol in spell.mana_cost.iter().flat_map(|it| &it.0) {
+                if let ManaSymbol::Colored(color) = symbol {
+                    *required.entry(*color).or_default() += 1;
+                }
+            }
+            // Every land produces a single mana, so each colored symbol needs a land of its own
+            // while the remaining lands pay for the generic part of the cost.
+            required.iter().all(|(color, amount)| {
+                lands
+                    .iter()
+                    .filter(|it| produced_color(it) == Some(*color))
+                    .count()
+                    >= *amount
+            })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn deck() -> Deck {
+        Deck::from(&[
+            ("Forest", 8),
+            ("Plains", 2),
+            ("Llanowar Elves", 4),
+            ("Soulmender", 2),
+            ("Polukranos, Unchained", 4),
+        ])
+        .expect("Could not create the deck.")
+    }
+
+    #[test]
+    fn simulations_with_the_same_seed_are_reproducible() {
+        let report = deck().simulate_opening_hands(42, 1000);
+        assert_eq!(report, deck().simulate_opening_hands(42, 1000));
+        assert_eq!(
+            report,
+            OpeningHandReport {
+                iterations: 1000,
+                lands_in_hand: [
+                    (0, 1),
+                    (1, 36),
+                    (2, 148),
+                    (3, 317),
+                    (4, 334),
+                    (5, 147),
+                    (6, 17)
+                ]
+                .into(),
+                // The deck contains no spells with a mana value of 2 or 3.
+                on_curve: [0.928, 0.0, 0.0],
+                mulligan_rate: 0.054,
+            }
+        );
+    }
+
+    #[test]
+    fn spells_without_lands_of_their_colors_are_never_cast_on_curve() {
+        // Soulmender needs {W}, which a Forest can't produce.
+        let deck =
+            Deck::from(&[("Forest", 10), ("Soulmender", 10)]).expect("Could not create the deck.");
+        let report = deck.simulate_opening_hands(7, 100);
+        assert!(report.on_curve.iter().all(|it| it.abs() < f64::EPSILON));
+
+        let deck =
+            Deck::from(&[("Plains", 10), ("Soulmender", 10)]).expect("Could not create the deck.");
+        let report = deck.simulate_opening_hands(7, 100);
+        assert!(report.on_curve[0] > 0.9);
+    }
+}
@@ -0,0 +1,29 @@
+use hecs::Entity;
+
+use crate::core::{PlayerId, Zone};
+
+/// A violation of the bookkeeping the engine relies on, which indicates a bug in the engine rather
+/// than an illegal action of a player.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum InvariantViolation {
+    /// 400.7. An object that moves from one zone to another becomes a new object with no memory
+    ///        of, or relation to, its previous existence. [...]
+    ///
+    /// The cards of the owner in all zones don't add up to the number of cards they brought into
+    /// the game, so a card was lost or duplicated along the way.
+    CardsNotConserved {
+        owner: PlayerId,
+        expected: usize,
+        actual: usize,
+    },
+    /// The zone tracked for an object disagrees with its cached [`Zone`] component.
+    ZoneOutOfSync {
+        entity: Entity,
+        tracked: Option<Zone>,
+        cached: Option<Zone>,
+    },
+    /// 109.4. Only objects on the stack or on the battlefield have a controller. [...]
+    ControllerOutsideStackOrBattlefield { entity: Entity, zone: Option<Zone> },
+    /// 704.5d If a token is in a zone other than the battlefield, it ceases to exist.
+    TokenOutsideBattlefield { entity: Entity, zone: Option<Zone> },
+}
@@ -0,0 +1,159 @@
+use std::collections::{HashMap, HashSet};
+
+use hecs::Entity;
+
+use crate::{
+    abilities::{ObjectFilter, TypeChange},
+    core::{CardType, LandType, PlayerId, Subtype, TypeLine},
+    effects::Timestamp,
+};
+
+/// 613.1d Layer 4: Type-changing effects are applied. [...]
+///
+/// A type-changing effect generated by a static ability of a permanent.
+#[derive(Clone, Debug)]
+pub(crate) struct TypeChangingEffect {
+    pub(crate) source: Entity,
+    pub(crate) controller: PlayerId,
+    /// 613.7a A continuous effect generated by a static ability has the same timestamp as the
+    ///        object the static ability is on, or the timestamp of the effect that created the
+    ///        ability, whichever is later.
+    pub(crate) timestamp: Timestamp,
+    pub(crate) affected: ObjectFilter,
+    pub(crate) change: TypeChange,
+}
+
+/// The type lines of all permanents after the type-changing effects have been applied.
+#[derive(Clone)]
+pub(crate) struct TypeLayer {
+    pub(crate) type_lines: HashMap<Entity, TypeLine>,
+    /// The permanents that lost the abilities generated from their rules text because an effect
+    /// set their land type.
+    pub(crate) lost_abilities: HashSet<Entity>,
+}
+
+impl TypeLayer {
+    /// 613.8. Within a layer or sublayer, determining which order effects are applied in is
+    ///        sometimes done using a dependency system. If a dependency exists, it will override
+    ///        the timestamp system.
+    ///
+    /// 613.8b If several dependent effects form a dependency loop, then this rule is ignored and
+    ///        the effects in the dependency loop are applied in timestamp order. [...]
+    ///
+    /// 613.8c After each effect is applied, the order of remaining effects is reevaluated and may
+    ///        change if an effect that has not yet been applied becomes dependent on or independent
+    ///        of one or more other effects that have not yet been applied.
+    ///
+    /// Applies the effects to the printed type lines of the permanents. Next is always the earliest
+    /// effect that doesn't depend on another effect yet to be applied, or the earliest effect of
+    /// all if every effect does. `matches` returns whether an effect affects a permanent with the
+    /// specified type line.
+    ///
+    /// # Remarks
+    /// Only effects that depend on each other are recognized as a dependency loop. An effect that
+    /// depends on an effect in a longer loop is applied in timestamp order with the loop.
+    pub(crate) fn apply(
+        type_lines: HashMap<Entity, TypeLine>,
+        mut effects: Vec<TypeChangingEffect>,
+        matches: &impl Fn(&TypeChangingEffect, Entity, &TypeLine) -> bool,
+    ) -> Self {
+        let mut layer = Self {
+            type_lines,
+            lost_abilities: HashSet::new(),
+        };
+        effects.sort_by_key(|it| it.timestamp);
+        loop {
+            // Effects of static abilities that were lost don't exist anymore.
+            effects.retain(|it| layer.exists(it));
+            if effects.is_empty() {
+                return layer;
+            }
+            let next = (0..effects.len())
+                .find(|&index| {
+                    let effect = &effects[index];
+                    !effects.iter().enumerate().any(|(other_index, other)| {
+                        other_index != index
+                            && layer.depends_on(effect, other, matches)
+                            && !layer.depends_on(other, effect, matches)
+                    })
+                })
+                .unwrap_or(0);
+            let effect = effects.remove(next);
+            layer.apply_effect(&effect, matches);
+        }
+    }
+
+    /// 613.8a An effect is said to “depend on” another if (a) it’s applied in the same layer (and,
+    ///        if applicable, sublayer) as the other effect [...]; (b) applying the other would
+    ///        change the text or the existence of the first effect, what it applies to, or what it
+    ///        does to any of the things it applies to; and (c) neither effect is from a
+    ///        characteristic-defining ability or both are. [...]
+    ///
+    /// What an effect does never depends on other effects, since it always changes the types in
+    /// the same way.
+    fn depends_on(
+        &self,
+        effect: &TypeChangingEffect,
+        other: &TypeChangingEffect,
+        matches: &impl Fn(&TypeChangingEffect, Entity, &TypeLine) -> bool,
+    ) -> bool {
+        let mut after = self.clone();
+        after.apply_effect(other, matches);
+        after.exists(effect) != self.exists(effect)
+            || after.affected(effect, matches) != self.affected(effect, matches)
+    }
+
+    /// Returns whether the static ability generating the effect still exists.
+    fn exists(&self, effect: &TypeChangingEffect) -> bool {
+        !self.lost_abilities.contains(&effect.source)
+    }
+
+    /// Returns the permanents the effect currently applies to, ordered to be comparable.
+    fn affected(
+        &self,
+        effect: &TypeChangingEffect,
+        matches: &impl Fn(&TypeChangingEffect, Entity, &TypeLine) -> bool,
+    ) -> Vec<Entity> {
+        let mut affected = self
+            .type_lines
+            .iter()
+            .filter(|&(&entity, type_line)| matches(effect, entity, type_line))
+            .map(|(&entity, _)| entity)
+            .collect::<Vec<_>>();
+        affected.sort();
+        affected
+    }
+
+    fn apply_effect(
+        &mut self,
+        effect: &TypeChangingEffect,
+        matches: &impl Fn(&TypeChangingEffect, Entity, &TypeLine) -> bool,
+    ) {
+        for entity in self.affected(effect, matches) {
+            let Some(type_line) = self.type_lines.get_mut(&entity) else {
+                continue;
+            };
+            match &effect.change {
+                TypeChange::SetBasicLandType(land_type) => {
+                    if !type_line.card_type.contains(&CardType::Land) {
+                        continue;
+                    }
+                    type_line
+                        .subtype
+                        .retain(|it| !matches!(it, Subtype::Land(_)));
+                    type_line
+                        .subtype
+                        .insert(Subtype::Land(LandType::Basic(*land_type)));
+                    self.lost_abilities.insert(entity);
+                }
+                TypeChange::AddTypes {
+                    card_types,
+                    subtypes,
+                } => {
+                    type_line.card_type.extend(card_types.iter().copied());
+                    type_line.subtype.extend(subtypes.iter().copied());
+                }
+            }
+        }
+    }
+}
@@ -1,6 +1,50 @@
 #![warn(clippy::pedantic)]
-#![allow(dead_code, clippy::cast_possible_truncation)]
+#![allow(
+    dead_code,
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_precision_loss
+)]
 
+mod abilities;
+mod actions;
+mod agents;
+mod casting;
+mod combat;
 mod components;
+mod consistency;
 mod core;
+mod database_diff;
+mod description;
+mod effects;
+mod error;
+mod events;
+mod exile;
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod fuzzing;
 mod game;
+mod goldfish;
+mod invariants;
+mod layers;
+mod library;
+mod log;
+mod matches;
+mod payment;
+pub mod prelude;
+mod prevention;
+mod replacement;
+mod rules_text;
+#[cfg(test)]
+mod scenarios;
+mod setup;
+mod simulation;
+mod snapshot;
+#[cfg(feature = "tracing")]
+mod spans;
+mod spectator;
+mod stats;
+#[cfg(any(test, feature = "proptest"))]
+pub mod strategies;
+mod tokens;
+mod turn;
+mod zones;
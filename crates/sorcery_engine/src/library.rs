@@ -0,0 +1,201 @@
+use std::collections::VecDeque;
+
+use hecs::Entity;
+use indexmap::IndexSet;
+use rand::{prelude::SliceRandom, Rng};
+
+use crate::core::PlayerId;
+
+/// 401.1. When a game begins, each player’s deck becomes their library.
+///
+/// 401.2. Each library must be kept in a single face-down pile. [...]
+///
+/// The front of the pile is the top of the library, i.e. the card that is drawn next.
+#[derive(Default)]
+pub(crate) struct Library {
+    cards: VecDeque<Entity>,
+    /// The players who have looked at the current top card, e.g. because an effect lets them look
+    /// at the top card of the library any time. The knowledge is lost once another card becomes
+    /// the top card or the library is shuffled.
+    top_card_known_to: IndexSet<PlayerId>,
+}
+
+impl Library {
+    /// Returns the number of cards in the library.
+    pub(crate) fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    /// Returns whether the library contains no cards.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+
+    /// Returns whether the specified card is in the library.
+    pub(crate) fn contains(&self, entity: Entity) -> bool {
+        self.cards.contains(&entity)
+    }
+
+    /// Returns all cards in the library from top to bottom.
+    pub(crate) fn cards(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.cards.iter().copied()
+    }
+
+    /// Returns up to the specified number of cards from the top of the library, starting with the
+    /// topmost card, without removing them.
+    pub(crate) fn peek_top(&self, amount: usize) -> Vec<Entity> {
+        self.cards.iter().take(amount).copied().collect()
+    }
+
+    /// Returns whether the specified player knows which card is on top of the library.
+    pub(crate) fn is_top_card_known_to(&self, player: PlayerId) -> bool {
+        self.top_card_known_to.contains(&player)
+    }
+
+    /// Lets the specified player look at the top card of the library and returns it, if there is
+    /// any.
+    pub(crate) fn look_at_top(&mut self, player: PlayerId) -> Option<Entity> {
+        let top = self.cards.front().copied()?;
+        self.top_card_known_to.insert(player);
+        Some(top)
+    }
+
+    /// Removes the top card of the library and returns it, if there is any.
+    pub(crate) fn draw_top(&mut self) -> Option<Entity> {
+        self.top_card_known_to.clear();
+        self.cards.pop_front()
+    }
+
+    /// Puts the specified card on top of the library.
+    pub(crate) fn put_on_top(&mut self, entity: Entity) {
+        self.top_card_known_to.clear();
+        self.cards.push_front(entity);
+    }
+
+    /// Puts the specified card on the bottom of the library.
+    pub(crate) fn put_on_bottom(&mut self, entity: Entity) {
+        self.cards.push_back(entity);
+    }
+
+    /// Puts the specified card into the library so that it becomes the nth card from the top,
+    /// starting at one for the top card. Positions beyond the bottom put it on the bottom instead.
+    pub(crate) fn put_nth_from_top(&mut self, entity: Entity, n: usize) {
+        let index = n.saturating_sub(1).min(self.cards.len());
+        if index == 0 {
+            self.top_card_known_to.clear();
+        }
+        self.cards.insert(index, entity);
+    }
+
+    /// Removes the specified card from wherever it is in the library and returns whether it was
+    /// found.
+    pub(crate) fn remove(&mut self, entity: Entity) -> bool {
+        let Some(index) = self.cards.iter().position(|&it| it == entity) else {
+            return false;
+        };
+        if index == 0 {
+            self.top_card_known_to.clear();
+        }
+        self.cards.remove(index);
+        true
+    }
+
+    /// 701.20a To shuffle a library or a face-down pile of cards, randomize the cards within it so
+    ///         that no player knows their order.
+    ///
+    /// Shuffles the library using the specified random number generator.
+    pub(crate) fn shuffle(&mut self, random: &mut impl Rng) {
+        self.top_card_known_to.clear();
+        self.cards.make_contiguous().shuffle(random);
+    }
+}
+
+/// 701.18a [...] put any number of them on the bottom of your library in any order and the rest
+///         on top of your library in any order.
+///
+/// The arrangement of the cards looked at while scrying, each ordered from top to bottom.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ScryChoice {
+    pub(crate) top: Vec<Entity>,
+    pub(crate) bottom: Vec<Entity>,
+}
+
+#[cfg(test)]
+mod tests {
+    use hecs::World;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    /// Returns a library with the specified number of cards in the order they were spawned from
+    /// top to bottom.
+    fn library(world: &mut World, amount: usize) -> (Library, Vec<Entity>) {
+        let cards = (0..amount).map(|_| world.spawn(())).collect::<Vec<_>>();
+        let mut library = Library::default();
+        for &card in &cards {
+            library.put_on_bottom(card);
+        }
+        (library, cards)
+    }
+
+    #[test]
+    fn cards_put_on_top_are_drawn_first() {
+        let mut world = World::new();
+        let (mut library, cards) = library(&mut world, 3);
+        let card = world.spawn(());
+
+        library.put_on_top(card);
+        assert_eq!(library.len(), 4);
+        assert_eq!(library.peek_top(2), vec![card, cards[0]]);
+        assert_eq!(library.draw_top(), Some(card));
+        assert_eq!(library.draw_top(), Some(cards[0]));
+    }
+
+    #[test]
+    fn cards_put_on_the_bottom_are_drawn_last() {
+        let mut world = World::new();
+        let (mut library, cards) = library(&mut world, 2);
+        let card = world.spawn(());
+
+        library.put_on_bottom(card);
+        assert_eq!(library.draw_top(), Some(cards[0]));
+        assert_eq!(library.draw_top(), Some(cards[1]));
+        assert_eq!(library.draw_top(), Some(card));
+        assert_eq!(library.draw_top(), None);
+        assert!(library.is_empty());
+    }
+
+    #[test]
+    fn cards_can_be_put_at_a_position_from_the_top() {
+        let mut world = World::new();
+        let (mut library, cards) = library(&mut world, 3);
+        let second = world.spawn(());
+        let bottom = world.spawn(());
+
+        // Put it into its owner’s library second from the top.
+        library.put_nth_from_top(second, 2);
+        library.put_nth_from_top(bottom, 10);
+        assert_eq!(
+            library.cards().collect::<Vec<_>>(),
+            vec![cards[0], second, cards[1], cards[2], bottom]
+        );
+        assert_eq!(library.peek_top(10).len(), 5);
+
+        assert!(library.remove(second));
+        assert!(!library.remove(second));
+        assert!(!library.contains(second));
+    }
+
+    #[test]
+    fn the_known_top_card_is_forgotten_once_it_changes() {
+        let mut world = World::new();
+        let (mut library, cards) = library(&mut world, 3);
+        let player = PlayerId(0);
+
+        assert_eq!(library.look_at_top(player), Some(cards[0]));
+        library.put_nth_from_top(world.spawn(()), 2);
+        assert!(library.is_top_card_known_to(player));
+        assert_eq!(library.draw_top(), Some(cards[0]));
+        assert!(!library.is_top_card_known_to(player));
+    }
+}
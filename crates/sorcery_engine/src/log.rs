@@ -0,0 +1,639 @@
+use std::io;
+
+use crate::{
+    core::{CoinCall, CoinResult, CounterKind, Designation, GameOutcome, PlayerId, Zone},
+    description,
+    events::{CounterHolder, DamageRecipient, GameEvent, ObjectSnapshot},
+    game::Game,
+    turn::Step,
+};
+
+/// Determines which information a [`GameLog`] reveals.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum LogVisibility {
+    /// Reveals all information, including the contents of hidden zones.
+    Full,
+    /// Reveals only the information available to the specified player.
+    Player(PlayerId),
+}
+
+/// Renders the events of a game into human-readable lines.
+pub(crate) struct GameLog {
+    visibility: LogVisibility,
+    /// The number of events that have already been written.
+    written: usize,
+}
+
+impl GameLog {
+    pub(crate) fn new(visibility: LogVisibility) -> Self {
+        Self {
+            visibility,
+            written: 0,
+        }
+    }
+
+    /// Returns all events of the game rendered as lines.
+    pub(crate) fn lines(&self, game: &Game) -> Vec<String> {
+        game.events()
+            .iter()
+            .map(|it| self.render(game, it))
+            .collect()
+    }
+
+    /// Writes all events that have happened since the previous call as lines to the writer.
+    pub(crate) fn write_new(&mut self, game: &Game, writer: &mut impl io::Write) -> io::Result<()> {
+        for event in &game.events()[self.written..] {
+            writeln!(writer, "{}", self.render(game, event))?;
+            self.written += 1;
+        }
+        Ok(())
+    }
+
+    // The match has an arm for each kind of event, so it can't be any shorter.
+    #[allow(clippy::too_many_lines)]
+    fn render(&self, game: &Game, event: &GameEvent) -> String {
+        let event = &self.reveal(event);
+        let name = |player| player_name(game, player);
+        match event {
+            GameEvent::TurnBegan {
+                number,
+                active_player,
+            } => format!("Turn {number} — {}'s turn begins.", name(*active_player)),
+            GameEvent::StepBegan {
+                number,
+                active_player,
+                step,
+            } => format!(
+                "Turn {number} — {}'s {} begins.",
+                name(*active_player),
+                step_name(*step)
+            ),
+            GameEvent::SpellCast {
+                caster,
+                name: spell,
+            } => format!("{} casts {spell}.", name(*caster)),
+            GameEvent::AbilityActivated {
+                player,
+                source,
+                description,
+            } => activation(name(*player), source, description.as_deref()),
+            GameEvent::AbilityTriggered {
+                source, optional, ..
+            } => trigger(&source.name, *optional),
+            GameEvent::TriggeredAbilityResolved {
+                source,
+                description,
+                ..
+            } => resolution(description.as_deref(), || {
+                format!("An ability of {} resolves.", source.name)
+            }),
+            GameEvent::SpellResolved {
+                spell, description, ..
+            } => resolution(description.as_deref(), || {
+                format!("{} resolves.", spell.name)
+            }),
+            GameEvent::Sacrificed { player, object } => act(name(*player), "sacrifices", object),
+            GameEvent::Discarded { player, object } => act(name(*player), "discards", object),
+            GameEvent::CoinFlipped {
+                player,
+                call,
+                result,
+            } => coin_flip(*player, *call, *result, name),
+            GameEvent::DieRolled {
+                player,
+                sides,
+                rolls,
+                result,
+            } => format!("{} {}", name(*player), die_roll(*sides, rolls, *result)),
+            GameEvent::DesignationGained {
+                player,
+                designation: Designation::CitysBlessing,
+            } => format!("{} gets the city's blessing.", name(*player)),
+            GameEvent::ZoneChanged { object, to, .. } => zone_change(&object.name, *to, name),
+            GameEvent::LibraryShuffled { player } => {
+                format!("{} shuffles their library.", name(*player))
+            }
+            GameEvent::DamageDealt {
+                source,
+                recipient,
+                amount,
+            } => damage(&source.name, recipient, *amount, name),
+            GameEvent::CountersAdded {
+                holder,
+                kind,
+                amount,
+            } => counter_change(holder, kind, *amount, true, name),
+            GameEvent::CountersRemoved {
+                holder,
+                kind,
+                amount,
+            } => counter_change(holder, kind, *amount, false, name),
+            GameEvent::EnergyGained {
+                player,
+                amount,
+                total,
+            } => format!("{} gets {amount} {{E}} ({total} {{E}}).", name(*player)),
+            GameEvent::EnergySpent {
+                player,
+                amount,
+                total,
+            } => format!("{} pays {amount} {{E}} ({total} {{E}}).", name(*player)),
+            GameEvent::LifeGained {
+                player,
+                amount,
+                life,
+            } => format!("{} gains {amount} life ({life} life).", name(*player)),
+            GameEvent::LifeLost {
+                player,
+                amount,
+                life,
+            } => format!("{} loses {amount} life ({life} life).", name(*player)),
+            GameEvent::EffectEnded { source, .. } => source.as_ref().map_or_else(
+                || "An effect ends.".into(),
+                |it| format!("An effect of {} ends.", it.name),
+            ),
+            GameEvent::PlayerConceded { player } => format!("{} concedes.", name(*player)),
+            GameEvent::PlayerLost { player } => format!("{} loses the game.", name(*player)),
+            GameEvent::AnteWon { winner, cards } => {
+                format!("{} wins {} from the ante.", name(*winner), names(cards))
+            }
+            GameEvent::GameEnded { outcome } => game_end(*outcome, name),
+            GameEvent::OptionChosen {
+                player,
+                source,
+                accepted,
+            } => option_choice(name(*player), &source.name, *accepted),
+            GameEvent::DiscardsChosen {
+                player,
+                amount,
+                automatic,
+            } => discard_choice(name(*player), *amount, *automatic),
+            GameEvent::TargetsChosen {
+                player,
+                source,
+                automatic,
+            } => target_choice(name(*player), &source.name, *automatic),
+        }
+    }
+
+    /// Returns the event with the information hidden from the player the log is written for.
+    fn reveal(&self, event: &GameEvent) -> GameEvent {
+        match self.visibility {
+            LogVisibility::Full => event.clone(),
+            LogVisibility::Player(player) => event.visible_to(Some(player)),
+        }
+    }
+}
+
+/// Returns the name the player joined with, which is made up if the player is unknown.
+fn player_name(game: &Game, player: PlayerId) -> &str {
+    game.player(player)
+        .map_or("An unknown player", |it| it.info.name.as_str())
+}
+
+/// Lists the names of the objects separated by commas.
+fn names(objects: &[ObjectSnapshot]) -> String {
+    objects
+        .iter()
+        .map(|it| it.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Describes a player activating an ability, which is described by its source if the ability
+/// itself can't be described.
+fn activation(player: &str, source: &ObjectSnapshot, description: Option<&str>) -> String {
+    match description {
+        Some(description) => format!("{player} activates {description}"),
+        None => format!("{player} activates an ability of {}.", source.name),
+    }
+}
+
+/// Describes an ability or a spell resolving by the description of its effects, or falls back to
+/// naming it if it has none.
+fn resolution(description: Option<&str>, fallback: impl FnOnce() -> String) -> String {
+    description.map_or_else(fallback, ToString::to_string)
+}
+
+/// Describes a player flipping a coin, preceded by the call of the player who called it, if any.
+fn coin_flip<'a>(
+    player: PlayerId,
+    call: Option<CoinCall>,
+    result: CoinResult,
+    name: impl Fn(PlayerId) -> &'a str,
+) -> String {
+    let call = call.map_or_else(String::new, |it| {
+        format!("{} calls {}. ", name(it.caller), coin_side(it.call))
+    });
+    format!(
+        "{call}{} flips a coin: {}.",
+        name(player),
+        coin_side(result)
+    )
+}
+
+/// Describes a source dealing damage, including the resulting life total if a player is dealt the
+/// damage.
+fn damage<'a>(
+    source: &str,
+    recipient: &DamageRecipient,
+    amount: u64,
+    name: impl Fn(PlayerId) -> &'a str,
+) -> String {
+    match recipient {
+        DamageRecipient::Permanent(permanent) => {
+            format!("{source} deals {amount} damage to {}.", permanent.name)
+        }
+        DamageRecipient::Player { player, life } => format!(
+            "{source} deals {amount} damage to {} ({life} life).",
+            name(*player)
+        ),
+    }
+}
+
+/// Describes counters being added to or removed from a permanent or player.
+fn counter_change<'a>(
+    holder: &CounterHolder,
+    kind: &CounterKind,
+    amount: u64,
+    added: bool,
+    name: impl Fn(PlayerId) -> &'a str,
+) -> String {
+    let holder = match holder {
+        CounterHolder::Permanent(permanent) => permanent.name.as_str(),
+        CounterHolder::Player(player) => name(*player),
+    };
+    let counters = description::count(
+        amount,
+        &format!("{} counter", description::counter_name(kind)),
+    );
+    match (added, amount) {
+        (true, _) => format!("{holder} gets {counters}."),
+        (false, 1) => format!("{counters} is removed from {holder}."),
+        (false, _) => format!("{counters} are removed from {holder}."),
+    }
+}
+
+/// Describes the outcome of a game that ended.
+fn game_end<'a>(outcome: GameOutcome, name: impl Fn(PlayerId) -> &'a str) -> String {
+    match outcome {
+        GameOutcome::Win(player) => format!("{} wins the game.", name(player)),
+        GameOutcome::Draw => "The game is a draw.".into(),
+    }
+}
+
+/// Describes a player doing something to an object, e.g. sacrificing it.
+fn act(player: &str, verb: &str, object: &ObjectSnapshot) -> String {
+    format!("{player} {verb} {}.", object.name)
+}
+
+/// Describes an ability of a source triggering, noting if its controller may take an optional
+/// action as it resolves.
+fn trigger(source: &str, optional: bool) -> String {
+    let kind = if optional {
+        "An optional ability"
+    } else {
+        "An ability"
+    };
+    format!("{kind} of {source} triggers.")
+}
+
+/// Describes a player taking or declining an optional effect of a source.
+fn option_choice(player: &str, source: &str, accepted: bool) -> String {
+    let choice = if accepted { "takes" } else { "declines" };
+    format!("{player} {choice} the option of {source}.")
+}
+
+/// Describes a player choosing cards to discard, noting if the engine made the only legal choice
+/// for them.
+fn discard_choice(player: &str, amount: u64, automatic: bool) -> String {
+    let cards = if amount == 1 { "card" } else { "cards" };
+    let how = if automatic { " automatically" } else { "" };
+    format!("{player} chooses {amount} {cards} to discard{how}.")
+}
+
+/// Describes a player choosing the targets of a triggered ability of a source, noting if the
+/// engine made the only legal choice for them.
+fn target_choice(player: &str, source: &str, automatic: bool) -> String {
+    let how = if automatic { " automatically" } else { "" };
+    format!("{player} chooses the targets of an ability of {source}{how}.")
+}
+
+/// Describes an object moving to the specified zone.
+fn zone_change<'a>(object: &str, to: Zone, name: impl Fn(PlayerId) -> &'a str) -> String {
+    match to {
+        Zone::Library(player) => format!("{object} is put into {}'s library.", name(player)),
+        Zone::Hand(player) => format!("{} puts {object} into their hand.", name(player)),
+        Zone::Battlefield => format!("{object} enters the battlefield."),
+        Zone::Graveyard(player) => format!("{object} is put into {}'s graveyard.", name(player)),
+        Zone::Stack => format!("{object} is put onto the stack."),
+        Zone::Exile => format!("{object} is exiled."),
+        Zone::Command => format!("{object} is put into the command zone."),
+        Zone::Ante => format!("{object} is put into the ante."),
+    }
+}
+
+/// Returns the name of the side of a coin as used in rules text.
+fn coin_side(side: CoinResult) -> &'static str {
+    match side {
+        CoinResult::Heads => "heads",
+        CoinResult::Tails => "tails",
+    }
+}
+
+/// Describes the dice a player rolled, omitting the ignored rolls if there are none.
+fn die_roll(sides: u32, rolls: &[u32], result: u32) -> String {
+    match rolls {
+        [_] => format!("rolls a d{sides}: {result}."),
+        _ => format!(
+            "rolls {} d{sides} ({}) and keeps {result}.",
+            rolls.len(),
+            rolls
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+/// Returns the name of a step as used in rules text.
+fn step_name(step: Step) -> &'static str {
+    match step {
+        Step::Untap => "untap step",
+        Step::Upkeep => "upkeep",
+        Step::Draw => "draw step",
+        Step::PrecombatMain => "precombat main phase",
+        Step::BeginningOfCombat => "combat phase",
+        Step::DeclareAttackers => "declare attackers step",
+        Step::DeclareBlockers => "declare blockers step",
+        Step::CombatDamage => "combat damage step",
+        Step::EndOfCombat => "end of combat step",
+        Step::PostcombatMain => "postcombat main phase",
+        Step::End => "end step",
+        Step::Cleanup => "cleanup step",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{
+        abilities::{
+            Ability, Effect, Quantity, TriggerCondition, TriggerSubject, TriggeredAbility,
+        },
+        components::SummoningSick,
+        core::{Card, CoinCall, DiceRoll, Name, PlayerInfo, PlayerSpec},
+        game::{find_card_by_name, PermanentOrPlayer},
+    };
+
+    /// Plays a few scripted actions and returns the game. The invariants of the game are checked
+    /// after every action.
+    fn scripted_game() -> Game {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let opponent = game.players()[1].id;
+        let card = |name| find_card_by_name(name).expect("Could not find the card.");
+
+        let soulmender = game
+            .spawn_object(card("Soulmender"), player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        // Soulmender has been under the control of the player since the turn began.
+        let _ = game.world_mut().remove_one::<SummoningSick>(soulmender);
+        game.debug_assert_invariants();
+        game.spawn_object(card("Forest"), player, Zone::Hand(player))
+            .expect("Could not spawn the object.");
+        game.debug_assert_invariants();
+        game.begin_step(Step::Upkeep)
+            .expect("Could not begin the step.");
+        game.debug_assert_invariants();
+        game.activate_ability(player, soulmender, 0, &[])
+            .expect("Could not activate the ability.");
+        game.debug_assert_invariants();
+        game.resolve_top_of_stack()
+            .expect("Could not resolve the ability.");
+        game.debug_assert_invariants();
+
+        // When Perilous Myr dies, it deals 2 damage to any target.
+        let perilous_myr = Arc::new(
+            Card::builder()
+                .name(Name("Perilous Myr".into()))
+                .abilities(vec![Ability::Triggered(TriggeredAbility {
+                    condition: TriggerCondition::Dies(TriggerSubject::This),
+                    intervening_if: None,
+                    effects: vec![Effect::DealDamage(Quantity::Fixed(2))],
+                })])
+                .build()
+                .expect("Failed to build the card."),
+        );
+        let perilous_myr = game
+            .spawn_object(&perilous_myr, player, Zone::Battlefield)
+            .expect("Could not spawn the object.");
+        game.debug_assert_invariants();
+        game.move_object(perilous_myr, Zone::Graveyard(player))
+            .expect("Could not move the object.");
+        game.debug_assert_invariants();
+        game.resolve_next_trigger(&[PermanentOrPlayer::Player(opponent)])
+            .expect("Could not resolve the trigger.");
+        game.debug_assert_invariants();
+        // The log must not depend on objects that are gone by now.
+        game.despawn_object(perilous_myr)
+            .expect("Could not despawn the object.");
+        game.debug_assert_invariants();
+
+        game.next_turn().expect("Could not begin the next turn.");
+        game.debug_assert_invariants();
+        game.begin_step(Step::BeginningOfCombat)
+            .expect("Could not begin the step.");
+        game.debug_assert_invariants();
+        game
+    }
+
+    #[test]
+    fn full_log_reveals_hidden_information() {
+        let game = scripted_game();
+        assert_eq!(
+            GameLog::new(LogVisibility::Full).lines(&game),
+            vec![
+                "Soulmender enters the battlefield.",
+                "Player 1 puts Forest into their hand.",
+                "Turn 1 — Player 1's upkeep begins.",
+                "Player 1 activates Soulmender: Player 1 gains 1 life.",
+                "Player 1 gains 1 life (21 life).",
+                "Perilous Myr enters the battlefield.",
+                "Perilous Myr is put into Player 1's graveyard.",
+                "An ability of Perilous Myr triggers.",
+                "Perilous Myr: Perilous Myr deals 2 damage to Player 2.",
+                "Perilous Myr deals 2 damage to Player 2 (18 life).",
+                "Turn 2 — Player 2's turn begins.",
+                "Turn 2 — Player 2's combat phase begins.",
+            ]
+        );
+    }
+
+    #[test]
+    fn abilities_are_described_as_they_were_when_they_resolved() {
+        let game = scripted_game();
+        let descriptions = game
+            .events()
+            .iter()
+            .filter_map(|it| match it {
+                GameEvent::AbilityActivated { description, .. }
+                | GameEvent::TriggeredAbilityResolved { description, .. } => description.clone(),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        // Perilous Myr has been despawned by now, but its trigger is still described by name.
+        assert_eq!(
+            descriptions,
+            vec![
+                "Soulmender: Player 1 gains 1 life.",
+                "Perilous Myr: Perilous Myr deals 2 damage to Player 2.",
+            ]
+        );
+
+        let trigger = game
+            .events()
+            .iter()
+            .find(|it| matches!(it, GameEvent::TriggeredAbilityResolved { .. }))
+            .expect("Could not find the trigger.");
+        let json = serde_json::to_value(trigger).expect("Could not serialize the event.");
+        assert_eq!(
+            json["TriggeredAbilityResolved"]["description"],
+            "Perilous Myr: Perilous Myr deals 2 damage to Player 2."
+        );
+    }
+
+    #[test]
+    fn player_log_hides_cards_in_the_hands_of_other_players() {
+        let game = scripted_game();
+        let player = game.players()[0].id;
+        let opponent = game.players()[1].id;
+
+        assert_eq!(
+            GameLog::new(LogVisibility::Player(player)).lines(&game)[1],
+            "Player 1 puts Forest into their hand."
+        );
+        assert_eq!(
+            GameLog::new(LogVisibility::Player(opponent)).lines(&game)[1],
+            "Player 1 puts a card into their hand."
+        );
+    }
+
+    #[test]
+    fn logs_are_written_incrementally() {
+        let mut game = Game::new(2);
+        let mut log = GameLog::new(LogVisibility::Full);
+        let mut output = Vec::new();
+
+        game.begin_step(Step::Upkeep)
+            .expect("Could not begin the step.");
+        log.write_new(&game, &mut output)
+            .expect("Could not write the log.");
+        game.begin_step(Step::Draw)
+            .expect("Could not begin the step.");
+        log.write_new(&game, &mut output)
+            .expect("Could not write the log.");
+
+        assert_eq!(
+            String::from_utf8(output).expect("The log is not valid UTF-8."),
+            "Turn 1 — Player 1's upkeep begins.\nTurn 1 — Player 1's draw step begins.\n"
+        );
+    }
+
+    #[test]
+    fn players_are_logged_with_the_names_they_joined_with() {
+        let spec = |name: &str, external_id: &str| PlayerSpec {
+            name: name.into(),
+            external_id: external_id.into(),
+            auto_choose_trivial: false,
+        };
+        let mut game =
+            Game::new_with_players(vec![spec("Alice", "7f1c5a52"), spec("Bob", "0b9e41d7")]);
+        let bob = game.players()[1].id;
+        game.concede(bob).expect("Could not concede.");
+
+        assert_eq!(
+            GameLog::new(LogVisibility::Full).lines(&game),
+            vec!["Bob concedes.", "Alice wins the game."]
+        );
+        let view = serde_json::to_value(game.spectator_view(false))
+            .expect("Could not serialize the view.");
+        assert_eq!(view["players"][1]["name"], "Bob");
+        assert_eq!(view["players"][1]["external_id"], "0b9e41d7");
+
+        let info = game.player_info(bob).expect("Could not find the player.");
+        let json = serde_json::to_string(info).expect("Could not serialize the player.");
+        assert_eq!(
+            serde_json::from_str::<PlayerInfo>(&json).expect("Could not deserialize the player."),
+            *info
+        );
+    }
+
+    #[test]
+    fn coin_flips_and_die_rolls_are_logged_with_their_results() {
+        let mut game = Game::new(2);
+        game.set_seed(186);
+        let player = game.players()[0].id;
+        let opponent = game.players()[1].id;
+
+        let flip = game.flip_coin(player).expect("Could not flip the coin.");
+        game.call_coin_flip(
+            player,
+            CoinCall {
+                caller: opponent,
+                call: CoinResult::Tails,
+            },
+        )
+        .expect("Could not flip the coin.");
+        let roll = game
+            .roll_die(opponent, 20)
+            .expect("Could not roll the die.");
+        game.roll_dice(player, 6, DiceRoll::KeepHighest(2))
+            .expect("Could not roll the dice.");
+
+        let lines = GameLog::new(LogVisibility::Full).lines(&game);
+        assert_eq!(
+            lines[0],
+            format!("Player 1 flips a coin: {}.", coin_side(flip))
+        );
+        assert!(lines[1].starts_with("Player 2 calls tails. Player 1 flips a coin: "));
+        assert_eq!(lines[2], format!("Player 2 rolls a d20: {roll}."));
+        assert!(lines[3].starts_with("Player 1 rolls 2 d6 ("));
+    }
+
+    #[test]
+    fn counters_are_logged_as_they_are_added_and_removed() {
+        let mut game = Game::new(2);
+        let player = game.players()[0].id;
+        let opponent = game.players()[1].id;
+        let soulmender = game
+            .spawn_object(
+                find_card_by_name("Soulmender").expect("Could not find the card."),
+                player,
+                Zone::Battlefield,
+            )
+            .expect("Could not spawn the object.");
+
+        let permanent = PermanentOrPlayer::Permanent(soulmender);
+        game.add_counters(permanent, CounterKind::PlusOnePlusOne, 2)
+            .expect("Could not add the counters.");
+        game.remove_counters(permanent, &CounterKind::PlusOnePlusOne, 1)
+            .expect("Could not remove the counters.");
+        game.add_counters(PermanentOrPlayer::Player(opponent), CounterKind::Poison, 1)
+            .expect("Could not add the counters.");
+
+        assert_eq!(
+            GameLog::new(LogVisibility::Full).lines(&game)[1..],
+            [
+                "Soulmender gets 2 +1/+1 counters.",
+                "1 +1/+1 counter is removed from Soulmender.",
+                "Player 2 gets 1 poison counter.",
+            ]
+        );
+    }
+}
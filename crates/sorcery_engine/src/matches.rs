@@ -0,0 +1,191 @@
+use std::{num::NonZeroUsize, thread};
+
+use serde::Serialize;
+
+use crate::{
+    agents::PlayerAgent,
+    core::{Deck, GameOutcome},
+    error::EngineError,
+    setup::GameBuilder,
+    simulation::simulate_games,
+};
+
+/// The number of turns after which a game that hasn't ended yet is stopped and counted as a draw,
+/// e.g. because neither agent is able to win.
+pub const TURN_LIMIT: u64 = 100;
+
+/// The result of a single game of a match from the perspective of the two agents.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum GameResult {
+    AgentA,
+    AgentB,
+    /// The game ended in a draw or was stopped at the [`TURN_LIMIT`].
+    Draw,
+}
+
+/// A game of a match, which can be replayed from its seed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct MatchGame {
+    pub seed: u64,
+    /// Whether agent A took the first turn.
+    pub agent_a_started: bool,
+    pub result: GameResult,
+    /// The number of the turn the game ended in.
+    pub turns: u64,
+}
+
+/// The results of a match between two agents, see [`run_matches`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct MatchReport {
+    pub agent_a_wins: usize,
+    pub agent_b_wins: usize,
+    pub draws: usize,
+    /// The average number of turns a game lasted.
+    pub average_turns: f64,
+    /// The games in the order they were played.
+    pub games: Vec<MatchGame>,
+}
+
+/// Plays the specified number of games between two agents, e.g. to notice a change of the rules
+/// that affects how often a deck wins. Game `i` is seeded with `base_seed + i` and agent A takes the
+/// first turn of every other game, starting with the first one. Each game is played by fresh clones
+/// of the agents, so the report only depends on the seeds even though the games are played in
+/// parallel.
+///
+/// # Errors
+/// Fails if a game can't be set up, e.g. because a deck is illegal, or if an agent chooses an
+/// illegal action.
+pub fn run_matches<A, B>(
+    agent_a: &A,
+    agent_b: &B,
+    deck_a: &Deck,
+    deck_b: &Deck,
+    games: u64,
+    base_seed: u64,
+) -> Result<MatchReport, EngineError>
+where
+    A: PlayerAgent + Clone + Sync,
+    B: PlayerAgent + Clone + Sync,
+{
+    let seeds = (0..games)
+        .map(|it| base_seed.wrapping_add(it))
+        .collect::<Vec<_>>();
+    let threads = thread::available_parallelism().unwrap_or(NonZeroUsize::MIN);
+    let games = simulate_games(&seeds, threads, |seed| {
+        let agent_a_started = seed.wrapping_sub(base_seed) % 2 == 0;
+        play_game(
+            agent_a.clone(),
+            agent_b.clone(),
+            deck_a,
+            deck_b,
+            seed,
+            agent_a_started,
+        )
+    })
+    .into_iter()
+    .collect::<Result<Vec<_>, _>>()?;
+
+    let count = |result| games.iter().filter(|it| it.result == result).count();
+    let average_turns = if games.is_empty() {
+        0.0
+    } else {
+        games.iter().map(|it| it.turns).sum::<u64>() as f64 / games.len() as f64
+    };
+    Ok(MatchReport {
+        agent_a_wins: count(GameResult::AgentA),
+        agent_b_wins: count(GameResult::AgentB),
+        draws: count(GameResult::Draw),
+        average_turns,
+        games,
+    })
+}
+
+/// Plays a single game of a match until it ends or reaches the [`TURN_LIMIT`].
+fn play_game(
+    mut agent_a: impl PlayerAgent,
+    mut agent_b: impl PlayerAgent,
+    deck_a: &Deck,
+    deck_b: &Deck,
+    seed: u64,
+    agent_a_started: bool,
+) -> Result<MatchGame, EngineError> {
+    let (mut game, players) = GameBuilder::new()
+        .seed(seed)
+        .player("Agent A", deck_a.clone())
+        .player("Agent B", deck_b.clone())
+        .starting_player(usize::from(!agent_a_started))
+        .build()?;
+    while let Some(player) = game.player_to_act()? {
+        if game.turn().number > TURN_LIMIT {
+            break;
+        }
+        let view = game.view_for(player)?;
+        let actions = game.legal_actions(player)?;
+        let action = if player == players[0] {
+            agent_a.choose(&view, &actions)
+        } else {
+            agent_b.choose(&view, &actions)
+        };
+        game.apply(player, action)?;
+    }
+    let result = match game.outcome() {
+        Some(GameOutcome::Win(winner)) if winner == players[0] => GameResult::AgentA,
+        Some(GameOutcome::Win(_)) => GameResult::AgentB,
+        Some(GameOutcome::Draw) | None => GameResult::Draw,
+    };
+    Ok(MatchGame {
+        seed,
+        agent_a_started,
+        result,
+        turns: game.turn().number.min(TURN_LIMIT),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::agents::GreedyAgent;
+
+    fn deck(decklist: &str) -> Deck {
+        Deck::parse(decklist).expect("Could not parse the deck.")
+    }
+
+    #[test]
+    fn mirror_matches_are_reproducible() {
+        let deck = deck("24 Plains\n18 Soulmender\n18 Steppe Lynx");
+        let run = || {
+            run_matches(&GreedyAgent, &GreedyAgent, &deck, &deck, 20, 42)
+                .expect("Could not run the matches.")
+        };
+
+        let report = run();
+        assert_eq!(report, run());
+        assert_eq!(report.agent_a_wins + report.agent_b_wins + report.draws, 20);
+        assert_eq!(
+            report.games.iter().map(|it| it.seed).collect::<Vec<_>>(),
+            (42..62).collect::<Vec<_>>()
+        );
+        assert!(report
+            .games
+            .iter()
+            .zip([true, false].into_iter().cycle())
+            .all(|(game, started)| game.agent_a_started == started));
+        // Both agents play the same deck the same way, so each of them wins some of the games.
+        assert!(report.agent_a_wins > 0 && report.agent_b_wins > 0);
+        assert!(report.average_turns > 1.0);
+    }
+
+    #[test]
+    fn games_that_reach_the_turn_limit_are_draws() {
+        // Neither player can deal damage and the libraries last longer than the turn limit.
+        let deck = deck("60 Plains");
+        let report = run_matches(&GreedyAgent, &GreedyAgent, &deck, &deck, 2, 0)
+            .expect("Could not run the matches.");
+
+        assert_eq!((report.agent_a_wins, report.agent_b_wins), (0, 0));
+        assert_eq!(report.draws, 2);
+        assert!(report.games.iter().all(|it| it.turns == TURN_LIMIT));
+    }
+}
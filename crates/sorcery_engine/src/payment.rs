@@ -0,0 +1,418 @@
+use hecs::Entity;
+
+use crate::core::{ColorIdentity, Mana, ManaCost, ManaSymbol};
+
+/// Something that can pay for a single mana of a cost.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ManaSource {
+    /// Mana that is already in the mana pool.
+    Pool(Mana),
+    /// An untapped permanent that can be tapped for one mana of any of the specified kinds. A
+    /// creature that can be tapped for convoke has the colors it can pay for.
+    Permanent {
+        entity: Entity,
+        produces: Vec<Mana>,
+        convoke: Option<ColorIdentity>,
+    },
+    /// A card in the graveyard that can be exiled for delve.
+    Graveyard(Entity),
+}
+
+impl ManaSource {
+    /// Returns whether the source can pay for one mana of the symbol.
+    fn can_pay(&self, symbol: ManaSymbol) -> bool {
+        match self {
+            Self::Pool(mana) => pays(*mana, symbol),
+            Self::Permanent {
+                produces, convoke, ..
+            } => {
+                produces.iter().any(|&it| pays(it, symbol))
+                    || convoke.as_ref().is_some_and(|it| convoke_pays(it, symbol))
+            }
+            // 702.66a [...] “Delve” means “For each generic mana in this spell’s total cost, you
+            //         may exile a card from your graveyard rather than pay that mana.”
+            Self::Graveyard(_) => matches!(symbol, ManaSymbol::Generic(_)),
+        }
+    }
+}
+
+/// 601.2h The player pays the total cost. First, they can activate mana abilities (see rule 605).
+///        [...]
+///
+/// Describes how to pay a mana cost: which permanents to tap for which mana, which mana to spend
+/// from the mana pool and which creatures to tap and cards to exile instead of paying mana.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct PaymentPlan {
+    /// The permanents to tap along with the mana each of them adds.
+    pub(crate) tapped: Vec<(Entity, Mana)>,
+    /// The mana to spend from the mana pool, including the mana added by tapping permanents.
+    pub(crate) spent: Vec<Mana>,
+    /// The creatures to tap for convoke.
+    pub(crate) convoked: Vec<Entity>,
+    /// The cards to exile from the graveyard for delve.
+    pub(crate) delved: Vec<Entity>,
+}
+
+/// Returns whether a single mana can pay for one mana of the symbol.
+fn pays(mana: Mana, symbol: ManaSymbol) -> bool {
+    match symbol {
+        ManaSymbol::Colored(color) => mana == Mana::Monocolored(color),
+        ManaSymbol::Colorless => mana == Mana::Colorless,
+        ManaSymbol::Hybrid(first, second) => {
+            mana == Mana::Monocolored(first) || mana == Mana::Monocolored(second)
+        }
+        ManaSymbol::Generic(_) | ManaSymbol::Variable | ManaSymbol::MonocoloredHybrid(_) => true,
+    }
+}
+
+/// 702.51a [...] “Convoke” means “For each colored mana in this spell’s total cost, you may tap
+///         an untapped creature of that color you control rather than pay that mana. For each
+///         generic mana in this spell’s total cost, you may tap an untapped creature you control
+///         rather than pay that mana.”
+fn convoke_pays(colors: &ColorIdentity, symbol: ManaSymbol) -> bool {
+    match symbol {
+        ManaSymbol::Colored(color) => colors.contains(color),
+        ManaSymbol::Hybrid(first, second) => colors.contains(first) || colors.contains(second),
+        ManaSymbol::Generic(_) | ManaSymbol::MonocoloredHybrid(_) => true,
+        ManaSymbol::Colorless | ManaSymbol::Variable => false,
+    }
+}
+
+/// Splits the cost into the symbols that are each paid for by a single mana, once for each way of
+/// paying its monocolored hybrid symbols. Paying them with mana of their color comes first, since
+/// that requires less mana. {X} is treated as 0.
+///
+/// 107.4e [...] a monocolored hybrid symbol such as {2/B} can be paid with either one black mana
+///        or two mana of any type. [...]
+fn requirements(cost: &ManaCost) -> Vec<Vec<ManaSymbol>> {
+    cost.0.iter().fold(vec![vec![]], |alternatives, &symbol| {
+        let ways = match symbol {
+            ManaSymbol::Generic(amount) => vec![vec![ManaSymbol::Generic(1); amount as usize]],
+            ManaSymbol::Variable => vec![vec![]],
+            ManaSymbol::MonocoloredHybrid(color) => vec![
+                vec![ManaSymbol::Colored(color)],
+                vec![ManaSymbol::Generic(1); 2],
+            ],
+            symbol => vec![vec![symbol]],
+        };
+        alternatives
+            .iter()
+            .flat_map(|alternative| ways.iter().map(move |way| [&alternative[..], way].concat()))
+            .collect()
+    })
+}
+
+/// Finds a way to pay the cost using each of the sources at most once, or returns `None` if there
+/// is none. Sources listed first are preferred, so mana in the pool should come first.
+///
+/// Every mana of the cost is matched with a source using augmenting paths, so a payment is found
+/// whenever one exists even if tapping sources greedily would fail. {X} is treated as 0.
+pub(crate) fn plan_payment(cost: &ManaCost, sources: &[ManaSource]) -> Option<PaymentPlan> {
+    let (requirements, assignments) = requirements(cost)
+        .into_iter()
+        .find_map(|it| match_requirements(&it, sources).map(|assignments| (it, assignments)))?;
+
+    let mut plan = PaymentPlan::default();
+    for (source, requirement) in sources.iter().zip(assignments) {
+        let Some(requirement) = requirement else {
+            continue;
+        };
+        let symbol = requirements[requirement];
+        match source {
+            ManaSource::Pool(mana) => plan.spent.push(*mana),
+            ManaSource::Permanent {
+                entity, produces, ..
+            } => match produces.iter().copied().find(|&it| pays(it, symbol)) {
+                Some(mana) => {
+                    plan.tapped.push((*entity, mana));
+                    plan.spent.push(mana);
+                }
+                None => plan.convoked.push(*entity),
+            },
+            ManaSource::Graveyard(entity) => plan.delved.push(*entity),
+        }
+    }
+    Some(plan)
+}
+
+/// 107.3a [...] the controller of that spell or ability chooses and announces the value of X as
+///        part of casting the spell or activating the ability. [...]
+///
+/// Returns the largest value of X the sources can pay the cost with, or `None` if they can't pay
+/// it at all. Costs without {X} can only be paid with X being 0.
+pub(crate) fn max_x(cost: &ManaCost, sources: &[ManaSource]) -> Option<u64> {
+    if !cost.0.contains(&ManaSymbol::Variable) {
+        return plan_payment(cost, sources).map(|_| 0);
+    }
+    // Each source pays for at most one mana, so X can't exceed the number of sources.
+    (0..=sources.len() as u64)
+        .rev()
+        .find(|&x| plan_payment(&cost.with_x(x), sources).is_some())
+}
+
+/// Returns whether the plan pays for the cost exactly using each of the available sources at most
+/// once, so nothing is left unpaid or paid in excess.
+pub(crate) fn is_valid(cost: &ManaCost, sources: &[ManaSource], plan: &PaymentPlan) -> bool {
+    let mut used = plan
+        .tapped
+        .iter()
+        .map(|it| it.0)
+        .chain(plan.convoked.iter().copied())
+        .chain(plan.delved.iter().copied())
+        .collect::<Vec<_>>();
+    let used_count = used.len();
+    used.sort();
+    used.dedup();
+    if used.len() != used_count {
+        return false;
+    }
+
+    let mut available = sources
+        .iter()
+        .filter_map(|it| match it {
+            ManaSource::Pool(mana) => Some(*mana),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    for &(entity, mana) in &plan.tapped {
+        let can_tap = sources.iter().any(|it| {
+            matches!(it, ManaSource::Permanent { entity: source, produces, .. }
+                if *source == entity && produces.contains(&mana))
+        });
+        if !can_tap {
+            return false;
+        }
+        available.push(mana);
+    }
+    let mut paid_with = Vec::new();
+    for mana in &plan.spent {
+        let Some(index) = available.iter().position(|it| it == mana) else {
+            return false;
+        };
+        available.remove(index);
+        paid_with.push(ManaSource::Pool(*mana));
+    }
+    for &entity in &plan.convoked {
+        let convoke = sources.iter().find_map(|it| match it {
+            ManaSource::Permanent {
+                entity: source,
+                convoke: Some(convoke),
+                ..
+            } if *source == entity => Some(convoke.clone()),
+            _ => None,
+        });
+        let Some(convoke) = convoke else {
+            return false;
+        };
+        paid_with.push(ManaSource::Permanent {
+            entity,
+            produces: vec![],
+            convoke: Some(convoke),
+        });
+    }
+    for &entity in &plan.delved {
+        if !sources.contains(&ManaSource::Graveyard(entity)) {
+            return false;
+        }
+        paid_with.push(ManaSource::Graveyard(entity));
+    }
+    pays_exactly(cost, &paid_with)
+}
+
+/// Returns whether the sources pay for the cost with each of them paying for exactly one mana of
+/// it, so nothing is left unpaid or paid in excess.
+fn pays_exactly(cost: &ManaCost, sources: &[ManaSource]) -> bool {
+    requirements(cost)
+        .iter()
+        .any(|it| it.len() == sources.len() && match_requirements(it, sources).is_some())
+}
+
+/// Matches every requirement with a different source that can pay for it and returns the
+/// requirement each source has been assigned to pay, if any, or `None` if there is no such
+/// matching.
+fn match_requirements(
+    requirements: &[ManaSymbol],
+    sources: &[ManaSource],
+) -> Option<Vec<Option<usize>>> {
+    let mut assignments = vec![None; sources.len()];
+    for requirement in 0..requirements.len() {
+        let mut visited = vec![false; sources.len()];
+        if !assign(
+            requirement,
+            requirements,
+            sources,
+            &mut assignments,
+            &mut visited,
+        ) {
+            return None;
+        }
+    }
+    Some(assignments)
+}
+
+/// Tries to assign a source to the requirement, reassigning other requirements along the way if
+/// necessary, and returns whether it succeeded.
+fn assign(
+    requirement: usize,
+    requirements: &[ManaSymbol],
+    sources: &[ManaSource],
+    assignments: &mut [Option<usize>],
+    visited: &mut [bool],
+) -> bool {
+    for (source, candidate) in sources.iter().enumerate() {
+        if visited[source] || !candidate.can_pay(requirements[requirement]) {
+            continue;
+        }
+        visited[source] = true;
+        let reassigned = match assignments[source] {
+            None => true,
+            Some(other) => assign(other, requirements, sources, assignments, visited),
+        };
+        if reassigned {
+            assignments[source] = Some(requirement);
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use hecs::World;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::core::Color;
+
+    fn land(world: &mut World, produces: &[Color]) -> ManaSource {
+        ManaSource::Permanent {
+            entity: world.spawn(()),
+            produces: produces.iter().map(|&it| Mana::Monocolored(it)).collect(),
+            convoke: None,
+        }
+    }
+
+    #[test]
+    fn dual_lands_are_used_for_the_color_nothing_else_provides() {
+        let mut world = World::new();
+        let dual = land(&mut world, &[Color::White, Color::Blue]);
+        let plains = land(&mut world, &[Color::White]);
+        let cost = ManaCost(vec![
+            ManaSymbol::Colored(Color::White),
+            ManaSymbol::Colored(Color::Blue),
+        ]);
+
+        // Tapping the dual land for {W} first would leave nothing to pay {U} with.
+        let plan = plan_payment(&cost, &[dual.clone(), plains.clone()])
+            .expect("Could not plan the payment.");
+        let entity = |source: &ManaSource| match source {
+            ManaSource::Permanent { entity, .. } => *entity,
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            plan.tapped,
+            vec![
+                (entity(&dual), Mana::Monocolored(Color::Blue)),
+                (entity(&plains), Mana::Monocolored(Color::White)),
+            ]
+        );
+        assert_eq!(plan_payment(&cost, &[plains]), None);
+    }
+
+    #[test]
+    fn mana_in_the_pool_is_spent_before_tapping_permanents() {
+        let mut world = World::new();
+        let forest = land(&mut world, &[Color::Green]);
+        // {1}{G/W}
+        let cost = ManaCost(vec![
+            ManaSymbol::Generic(1),
+            ManaSymbol::Hybrid(Color::Green, Color::White),
+        ]);
+
+        let plan = plan_payment(
+            &cost,
+            &[ManaSource::Pool(Mana::Monocolored(Color::White)), forest],
+        )
+        .expect("Could not plan the payment.");
+        assert_eq!(plan.tapped.len(), 1);
+        assert_eq!(plan.spent.len(), 2);
+        assert_eq!(
+            plan_payment(&cost, &[ManaSource::Pool(Mana::Colorless)]),
+            None
+        );
+    }
+
+    #[test]
+    fn creatures_that_tap_for_mana_pay_for_a_single_mana() {
+        let mut world = World::new();
+        let elves = ManaSource::Permanent {
+            entity: world.spawn(()),
+            produces: vec![Mana::Monocolored(Color::Green)],
+            convoke: Some(ColorIdentity::Monocolored(Color::Green)),
+        };
+        let graveyard = ManaSource::Graveyard(world.spawn(()));
+        // {1}{G}
+        let cost = ManaCost(vec![
+            ManaSymbol::Generic(1),
+            ManaSymbol::Colored(Color::Green),
+        ]);
+
+        assert_eq!(plan_payment(&cost, std::slice::from_ref(&elves)), None);
+        let plan = plan_payment(&cost, &[elves, graveyard]).expect("Could not plan the payment.");
+        assert_eq!(plan.tapped.len(), 1);
+        assert!(plan.convoked.is_empty());
+        assert_eq!(plan.delved.len(), 1);
+        // Cards in the graveyard can only pay for generic mana.
+        let cost = ManaCost(vec![ManaSymbol::Colored(Color::Green)]);
+        assert!(!pays_exactly(
+            &cost,
+            &[ManaSource::Graveyard(world.spawn(()))]
+        ));
+    }
+
+    #[test]
+    fn monocolored_hybrid_symbols_are_paid_with_their_color_or_two_mana() {
+        let mut world = World::new();
+        let plains = land(&mut world, &[Color::White]);
+        let swamps = [
+            land(&mut world, &[Color::Black]),
+            land(&mut world, &[Color::Black]),
+        ];
+        // {2/W}
+        let cost = ManaCost(vec![ManaSymbol::MonocoloredHybrid(Color::White)]);
+
+        let plan = plan_payment(
+            &cost,
+            &[swamps[0].clone(), plains.clone(), swamps[1].clone()],
+        )
+        .expect("Could not plan the payment.");
+        assert_eq!(plan.tapped.len(), 1);
+        assert_eq!(plan.spent, vec![Mana::Monocolored(Color::White)]);
+        let plan = plan_payment(&cost, &swamps).expect("Could not plan the payment.");
+        assert_eq!(plan.tapped.len(), 2);
+        assert!(is_valid(&cost, &swamps, &plan));
+        assert_eq!(plan_payment(&cost, &swamps[..1]), None);
+    }
+
+    #[test]
+    fn the_largest_value_of_x_uses_up_the_remaining_sources() {
+        let mut world = World::new();
+        let forests = [
+            land(&mut world, &[Color::Green]),
+            land(&mut world, &[Color::Green]),
+            land(&mut world, &[Color::Green]),
+        ];
+        // {X}{G}
+        let cost = ManaCost(vec![
+            ManaSymbol::Variable,
+            ManaSymbol::Colored(Color::Green),
+        ]);
+
+        assert_eq!(max_x(&cost, &forests), Some(2));
+        assert_eq!(max_x(&cost, &[]), None);
+        assert_eq!(
+            max_x(&ManaCost(vec![ManaSymbol::Colored(Color::Green)]), &forests),
+            Some(0)
+        );
+    }
+}
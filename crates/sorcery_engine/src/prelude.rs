@@ -0,0 +1,19 @@
+//! The types needed to set up and play a game, which can be imported all at once using
+//! `use sorcery_engine::prelude::*;`.
+
+pub use crate::{
+    actions::{GameAction, Target},
+    agents::{GreedyAgent, PlayerAgent},
+    core::{
+        Color, CounterKind, Deck, Designation, Format, GameOutcome, Mana, ObjectId, PlayerId,
+        PlayerInfo, PlayerSpec, Zone,
+    },
+    error::EngineError,
+    events::{CounterHolder, DamageRecipient, GameEvent, ObjectSnapshot},
+    game::Game,
+    matches::{run_matches, GameResult, MatchGame, MatchReport, TURN_LIMIT},
+    setup::{GameBuilder, GameConfig},
+    snapshot::GameSnapshot,
+    spectator::{ChoiceStatus, ObjectState, ObjectStatus, PlayerStatus, PlayerView},
+    turn::Step,
+};
@@ -0,0 +1,83 @@
+use hecs::Entity;
+
+use crate::game::PermanentOrPlayer;
+
+/// 120.1. Objects can deal damage to creatures, planeswalkers, and players. This is generally
+///        detrimental to the object or player that receives that damage. An object that deals
+///        damage is the source of that damage.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Damage {
+    pub(crate) source: Entity,
+    pub(crate) recipient: PermanentOrPlayer,
+    pub(crate) amount: u64,
+    /// 510.2. [...] Combat damage is dealt simultaneously by attacking and blocking creatures.
+    pub(crate) combat: bool,
+}
+
+/// 615.1. Some continuous effects are prevention effects. Like replacement effects (see rule 614),
+///        prevention effects apply continuously as events happen—they aren’t locked in ahead of
+///        time. Such effects watch for a damage event that would happen and completely or
+///        partially prevent the damage that would be dealt. They act like “shields” around
+///        whatever they’re affecting.
+///
+/// # Remarks
+/// All prevention effects created by spells and abilities so far last until the end of the turn.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum PreventionEffect {
+    /// 615.7. Some prevention effects generated by the resolution of a spell or ability refer to a
+    ///        specific amount of damage—for example, “Prevent the next 3 damage that would be
+    ///        dealt to target creature or player this turn.” These work like shields. Each 1
+    ///        damage that would be dealt to the “shielded” creature or player is prevented.
+    ///        Preventing 1 damage reduces the remaining shield by 1. [...]
+    Shield {
+        recipient: PermanentOrPlayer,
+        remaining: u64,
+    },
+    /// Prevent all combat damage that would be dealt this turn.
+    AllCombatDamage,
+}
+
+/// Stores all prevention effects currently watching for damage.
+#[derive(Default)]
+pub(crate) struct PreventionEffects(Vec<PreventionEffect>);
+
+impl PreventionEffects {
+    pub(crate) fn add(&mut self, effect: PreventionEffect) {
+        self.0.push(effect);
+    }
+
+    /// Prevents as much of the specified damage as possible, consuming shields along the way, and
+    /// returns the amount of damage that was prevented.
+    ///
+    /// # Remarks
+    /// Effects are applied in the order they were created rather than letting the affected player
+    /// choose as described in rule 616.1.
+    pub(crate) fn prevent(&mut self, damage: &Damage) -> u64 {
+        let mut remaining = damage.amount;
+        for effect in &mut self.0 {
+            if remaining == 0 {
+                break;
+            }
+            match effect {
+                PreventionEffect::Shield {
+                    recipient,
+                    remaining: shield,
+                } if *recipient == damage.recipient => {
+                    let prevented = remaining.min(*shield);
+                    *shield -= prevented;
+                    remaining -= prevented;
+                }
+                PreventionEffect::AllCombatDamage if damage.combat => remaining = 0,
+                _ => {}
+            }
+        }
+        self.0
+            .retain(|it| !matches!(it, PreventionEffect::Shield { remaining: 0, .. }));
+        damage.amount - remaining
+    }
+
+    /// 514.2. [...] all “until end of turn” and “this turn” effects end. [...]
+    pub(crate) fn end_of_turn(&mut self) {
+        self.0.clear();
+    }
+}
@@ -0,0 +1,526 @@
+use std::sync::Arc;
+
+use hecs::Entity;
+
+use crate::{
+    core::{CounterKind, PlayerId},
+    error::EngineError,
+    game::PermanentOrPlayer,
+};
+
+/// An event that is about to happen and that replacement effects can watch for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ReplaceableEvent {
+    DealDamage {
+        recipient: PermanentOrPlayer,
+        amount: u64,
+    },
+    DrawCard {
+        player: PlayerId,
+    },
+    EnterBattlefield {
+        object: Entity,
+        tapped: bool,
+    },
+    Die {
+        object: Entity,
+    },
+    Destroy {
+        object: Entity,
+    },
+    PutIntoGraveyard {
+        object: Entity,
+    },
+    ShuffleIntoLibrary {
+        object: Entity,
+    },
+    GainLife {
+        player: PlayerId,
+        amount: u64,
+    },
+    Untap {
+        object: Entity,
+    },
+    RemoveCounters {
+        object: Entity,
+        kind: CounterKind,
+        amount: u64,
+    },
+    Exile {
+        object: Entity,
+    },
+}
+
+impl ReplaceableEvent {
+    /// Returns the event regardless of its amounts, i.e. the kind of event replacement effects
+    /// compete to modify.
+    pub(crate) fn generalized(&self) -> Self {
+        match self.clone() {
+            Self::DealDamage { recipient, .. } => Self::DealDamage {
+                recipient,
+                amount: 1,
+            },
+            Self::EnterBattlefield { object, .. } => Self::EnterBattlefield {
+                object,
+                tapped: false,
+            },
+            Self::GainLife { player, .. } => Self::GainLife { player, amount: 1 },
+            Self::RemoveCounters { object, kind, .. } => Self::RemoveCounters {
+                object,
+                kind,
+                amount: 1,
+            },
+            event => event,
+        }
+    }
+
+    /// Describes what happens to the affected object or player, e.g. “gain 2 life”.
+    pub(crate) fn describe(&self) -> String {
+        match self {
+            Self::DealDamage { amount, .. } => format!("be dealt {amount} damage"),
+            Self::DrawCard { .. } => "draw a card".into(),
+            Self::EnterBattlefield { tapped: false, .. } => "enter the battlefield".into(),
+            Self::EnterBattlefield { tapped: true, .. } => "enter the battlefield tapped".into(),
+            Self::Die { .. } => "die".into(),
+            Self::Destroy { .. } => "be destroyed".into(),
+            Self::PutIntoGraveyard { .. } => "be put into a graveyard".into(),
+            Self::ShuffleIntoLibrary { .. } => "be shuffled into its owner’s library".into(),
+            Self::GainLife { amount, .. } => format!("gain {amount} life"),
+            Self::Untap { .. } => "untap".into(),
+            Self::RemoveCounters { kind, amount, .. } => {
+                format!("lose {amount} {kind:?} counters")
+            }
+            Self::Exile { .. } => "be exiled".into(),
+        }
+    }
+}
+
+/// 614.1. Some continuous effects are replacement effects. Like prevention effects (see rule 615),
+///        replacement effects apply continuously as events happen—they aren’t locked in ahead of
+///        time. Such effects watch for a particular event that would happen and completely or
+///        partially replace that event with a different event. They act like “shields” around
+///        whatever they’re affecting.
+///
+/// Effects must be shareable across threads so that games holding them can be simulated in
+/// parallel.
+pub(crate) trait ReplacementEffect: Send + Sync {
+    /// Returns whether this effect watches for the specified event.
+    fn applies_to(&self, event: &ReplaceableEvent) -> bool;
+
+    /// Returns the events that happen instead of the specified event.
+    fn replace(&self, event: ReplaceableEvent) -> Vec<ReplaceableEvent>;
+
+    /// Describes the effect to the player who chooses the order competing effects apply in.
+    /// Effects with the same description are interchangeable.
+    fn describe(&self) -> String;
+}
+
+/// 614.1d Continuous effects that read “[This permanent] enters the battlefield . . . ,” or
+///        “[Objects] enter the battlefield . . . ,” are replacement effects.
+///
+/// Represents “[This permanent] enters the battlefield tapped.”
+pub(crate) struct EntersTapped {
+    pub(crate) object: Entity,
+}
+
+impl ReplacementEffect for EntersTapped {
+    fn applies_to(&self, event: &ReplaceableEvent) -> bool {
+        matches!(event, ReplaceableEvent::EnterBattlefield { object, .. } if *object == self.object)
+    }
+
+    fn replace(&self, event: ReplaceableEvent) -> Vec<ReplaceableEvent> {
+        match event {
+            ReplaceableEvent::EnterBattlefield { object, .. } => {
+                vec![ReplaceableEvent::EnterBattlefield {
+                    object,
+                    tapped: true,
+                }]
+            }
+            _ => vec![event],
+        }
+    }
+
+    fn describe(&self) -> String {
+        "It enters the battlefield tapped".into()
+    }
+}
+
+/// 614.1a Effects that use the word “instead” are replacement effects. Most replacement effects use
+///        the word “instead” to indicate what events will be replaced with other events.
+///
+/// Represents “If [player] would draw a card, [events] instead.”
+pub(crate) struct InsteadOfDrawing {
+    pub(crate) player: PlayerId,
+    pub(crate) events: Vec<ReplaceableEvent>,
+}
+
+impl ReplacementEffect for InsteadOfDrawing {
+    fn applies_to(&self, event: &ReplaceableEvent) -> bool {
+        matches!(event, ReplaceableEvent::DrawCard { player } if *player == self.player)
+    }
+
+    fn replace(&self, _: ReplaceableEvent) -> Vec<ReplaceableEvent> {
+        self.events.clone()
+    }
+
+    fn describe(&self) -> String {
+        let events = self
+            .events
+            .iter()
+            .map(ReplaceableEvent::describe)
+            .collect::<Vec<_>>();
+        format!("Instead of drawing a card, {}", events.join(" and "))
+    }
+}
+
+/// 122.1d Stun counters. If a permanent with a stun counter would become untapped, instead remove
+///        a stun counter from it.
+///
+/// Applies to a permanent as long as it has a stun counter on it.
+pub(crate) struct StunCounter {
+    pub(crate) object: Entity,
+}
+
+impl ReplacementEffect for StunCounter {
+    fn applies_to(&self, event: &ReplaceableEvent) -> bool {
+        matches!(event, ReplaceableEvent::Untap { object } if *object == self.object)
+    }
+
+    fn replace(&self, _: ReplaceableEvent) -> Vec<ReplaceableEvent> {
+        vec![ReplaceableEvent::RemoveCounters {
+            object: self.object,
+            kind: CounterKind::Stun,
+            amount: 1,
+        }]
+    }
+
+    fn describe(&self) -> String {
+        "Remove a stun counter instead of untapping".into()
+    }
+}
+
+/// 122.1c Shield counters. If a permanent with a shield counter on it would be dealt damage or
+///        destroyed, instead remove a shield counter from it.
+///
+/// Applies to a permanent as long as it has a shield counter on it.
+pub(crate) struct ShieldCounter {
+    pub(crate) object: Entity,
+}
+
+impl ReplacementEffect for ShieldCounter {
+    fn applies_to(&self, event: &ReplaceableEvent) -> bool {
+        match event {
+            ReplaceableEvent::DealDamage {
+                recipient: PermanentOrPlayer::Permanent(object),
+                ..
+            }
+            | ReplaceableEvent::Destroy { object } => *object == self.object,
+            _ => false,
+        }
+    }
+
+    fn replace(&self, _: ReplaceableEvent) -> Vec<ReplaceableEvent> {
+        vec![ReplaceableEvent::RemoveCounters {
+            object: self.object,
+            kind: CounterKind::Shield,
+            amount: 1,
+        }]
+    }
+
+    fn describe(&self) -> String {
+        "Remove a shield counter instead".into()
+    }
+}
+
+/// Represents “If [this card] would be put into a graveyard from anywhere, reveal it and shuffle it
+/// into its owner’s library instead.”
+pub(crate) struct ShuffleIntoLibraryInstead {
+    pub(crate) object: Entity,
+}
+
+impl ReplacementEffect for ShuffleIntoLibraryInstead {
+    fn applies_to(&self, event: &ReplaceableEvent) -> bool {
+        matches!(event, ReplaceableEvent::PutIntoGraveyard { object } if *object == self.object)
+    }
+
+    fn replace(&self, _: ReplaceableEvent) -> Vec<ReplaceableEvent> {
+        vec![ReplaceableEvent::ShuffleIntoLibrary {
+            object: self.object,
+        }]
+    }
+
+    fn describe(&self) -> String {
+        "Shuffle it into its owner’s library instead".into()
+    }
+}
+
+/// Represents “If [object] would die, exile it instead.”
+pub(crate) struct ExiledInsteadOfDying {
+    pub(crate) object: Entity,
+}
+
+impl ReplacementEffect for ExiledInsteadOfDying {
+    fn applies_to(&self, event: &ReplaceableEvent) -> bool {
+        matches!(event, ReplaceableEvent::Die { object } if *object == self.object)
+    }
+
+    fn replace(&self, _: ReplaceableEvent) -> Vec<ReplaceableEvent> {
+        vec![ReplaceableEvent::Exile {
+            object: self.object,
+        }]
+    }
+
+    fn describe(&self) -> String {
+        "Exile it instead".into()
+    }
+}
+
+/// Stores all replacement effects currently watching for events.
+///
+/// # Remarks
+/// All replacement effects created by spells and abilities so far last until the end of the turn.
+#[derive(Clone, Default)]
+pub(crate) struct ReplacementEffects(Vec<Arc<dyn ReplacementEffect>>);
+
+impl ReplacementEffects {
+    pub(crate) fn add(&mut self, effect: impl ReplacementEffect + 'static) {
+        self.0.push(Arc::new(effect));
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the descriptions of the effects that watch for the specified event without
+    /// duplicates, in the order the effects were created.
+    pub(crate) fn describe_candidates(&self, event: &ReplaceableEvent) -> Vec<String> {
+        let mut descriptions = Vec::<String>::new();
+        for effect in self.0.iter().filter(|it| it.applies_to(event)) {
+            let description = effect.describe();
+            if !descriptions.contains(&description) {
+                descriptions.push(description);
+            }
+        }
+        descriptions
+    }
+
+    /// Returns the description of the effect at the specified index.
+    pub(crate) fn describe(&self, index: usize) -> String {
+        self.0
+            .get(index)
+            .map(|it| it.describe())
+            .unwrap_or_default()
+    }
+
+    /// 514.2. [...] all “until end of turn” and “this turn” effects end. [...]
+    pub(crate) fn end_of_turn(&mut self) {
+        self.0.clear();
+    }
+
+    /// Applies all replacement effects to the specified event and returns the events that happen
+    /// instead. Whenever more than one effect applies, `choose` is called with the event and the
+    /// candidates and has to return the candidate to apply next.
+    ///
+    /// 614.5. A replacement effect doesn’t invoke itself repeatedly; it gets only one opportunity
+    ///        to affect an event or any modified events that may replace that event.
+    ///
+    /// 616.1. If two or more replacement and/or prevention effects are attempting to modify the
+    ///        way an event affects an object or player, the affected object’s controller (or its
+    ///        owner if it has no controller) or the affected player chooses one to apply [...]
+    ///
+    /// 616.1f Once the chosen effect has been applied, this process is repeated (taking into
+    ///        account only replacement or prevention effects that would now be applicable) until
+    ///        there are no more left to apply.
+    ///
+    /// # Errors
+    /// Fails if `choose` returns an effect that isn't one of the candidates.
+    pub(crate) fn apply(
+        &self,
+        event: ReplaceableEvent,
+        choose: &mut dyn FnMut(&ReplaceableEvent, &[usize]) -> usize,
+    ) -> Result<Vec<ReplaceableEvent>, EngineError> {
+        self.apply_excluding(event, &mut Vec::new(), choose)
+    }
+
+    fn apply_excluding(
+        &self,
+        event: ReplaceableEvent,
+        applied: &mut Vec<usize>,
+        choose: &mut dyn FnMut(&ReplaceableEvent, &[usize]) -> usize,
+    ) -> Result<Vec<ReplaceableEvent>, EngineError> {
+        let candidates = self
+            .0
+            .iter()
+            .enumerate()
+            .filter(|(index, effect)| !applied.contains(index) && effect.applies_to(&event))
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+
+        let chosen = match candidates.as_slice() {
+            [] => return Ok(vec![event]),
+            [candidate] => *candidate,
+            _ => {
+                let chosen = choose(&event, &candidates);
+                if !candidates.contains(&chosen) {
+                    return Err(EngineError::IllegalAction(format!(
+                        "The replacement effect {chosen} does not apply to {event:?}."
+                    )));
+                }
+                chosen
+            }
+        };
+
+        applied.push(chosen);
+        let mut events = Vec::new();
+        for event in self.0[chosen].replace(event) {
+            events.extend(self.apply_excluding(event, applied, choose)?);
+        }
+        applied.pop();
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hecs::World;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn lands_with_an_enters_tapped_replacement_enter_tapped() {
+        let mut world = World::new();
+        let land = world.spawn(());
+        let other_land = world.spawn(());
+
+        let mut effects = ReplacementEffects::default();
+        effects.add(EntersTapped { object: land });
+
+        let mut choose = |_: &ReplaceableEvent, _: &[usize]| unreachable!();
+        assert_eq!(
+            effects.apply(
+                ReplaceableEvent::EnterBattlefield {
+                    object: land,
+                    tapped: false
+                },
+                &mut choose
+            ),
+            Ok(vec![ReplaceableEvent::EnterBattlefield {
+                object: land,
+                tapped: true
+            }])
+        );
+        assert_eq!(
+            effects.apply(
+                ReplaceableEvent::EnterBattlefield {
+                    object: other_land,
+                    tapped: false
+                },
+                &mut choose
+            ),
+            Ok(vec![ReplaceableEvent::EnterBattlefield {
+                object: other_land,
+                tapped: false
+            }])
+        );
+    }
+
+    #[test]
+    fn competing_draw_replacements_apply_in_the_chosen_order() {
+        let player = PlayerId(0);
+        let draw = ReplaceableEvent::DrawCard { player };
+        let gain_life = ReplaceableEvent::GainLife { player, amount: 2 };
+
+        let mut effects = ReplacementEffects::default();
+        // If you would draw a card, draw two cards instead.
+        effects.add(InsteadOfDrawing {
+            player,
+            events: vec![draw.clone(), draw.clone()],
+        });
+        // If you would draw a card, gain 2 life instead.
+        effects.add(InsteadOfDrawing {
+            player,
+            events: vec![gain_life.clone()],
+        });
+
+        // Doubling the draw first replaces each of the two draws with gaining life.
+        assert_eq!(
+            effects.apply(draw.clone(), &mut |_, _| 0),
+            Ok(vec![gain_life.clone(), gain_life.clone()])
+        );
+        // Replacing the draw with gaining life first leaves nothing to double.
+        assert_eq!(
+            effects.apply(draw.clone(), &mut |_, _| 1),
+            Ok(vec![gain_life])
+        );
+        assert!(matches!(
+            effects.apply(draw, &mut |_, _| 2),
+            Err(EngineError::IllegalAction(_))
+        ));
+    }
+
+    #[test]
+    fn stunned_permanents_lose_a_stun_counter_instead_of_untapping() {
+        let mut world = World::new();
+        let creature = world.spawn(());
+        let other_creature = world.spawn(());
+
+        let mut effects = ReplacementEffects::default();
+        effects.add(StunCounter { object: creature });
+
+        let mut choose = |_: &ReplaceableEvent, _: &[usize]| unreachable!();
+        assert_eq!(
+            effects.apply(ReplaceableEvent::Untap { object: creature }, &mut choose),
+            Ok(vec![ReplaceableEvent::RemoveCounters {
+                object: creature,
+                kind: CounterKind::Stun,
+                amount: 1
+            }])
+        );
+        assert_eq!(
+            effects.apply(
+                ReplaceableEvent::Untap {
+                    object: other_creature
+                },
+                &mut choose
+            ),
+            Ok(vec![ReplaceableEvent::Untap {
+                object: other_creature
+            }])
+        );
+    }
+
+    #[test]
+    fn shield_counters_are_removed_instead_of_dealing_damage_or_destroying() {
+        let mut world = World::new();
+        let creature = world.spawn(());
+
+        let mut effects = ReplacementEffects::default();
+        effects.add(ShieldCounter { object: creature });
+
+        let mut choose = |_: &ReplaceableEvent, _: &[usize]| unreachable!();
+        let removal = vec![ReplaceableEvent::RemoveCounters {
+            object: creature,
+            kind: CounterKind::Shield,
+            amount: 1,
+        }];
+        assert_eq!(
+            effects.apply(
+                ReplaceableEvent::DealDamage {
+                    recipient: PermanentOrPlayer::Permanent(creature),
+                    amount: 5
+                },
+                &mut choose
+            ),
+            Ok(removal.clone())
+        );
+        assert_eq!(
+            effects.apply(ReplaceableEvent::Destroy { object: creature }, &mut choose),
+            Ok(removal)
+        );
+        assert_eq!(
+            effects.apply(ReplaceableEvent::Die { object: creature }, &mut choose),
+            Ok(vec![ReplaceableEvent::Die { object: creature }])
+        );
+    }
+}
@@ -0,0 +1,393 @@
+use crate::{
+    abilities::{
+        Ability, ActivatedAbility, ActivationCost, Effect, Quantity, TriggerCondition,
+        TriggerSubject, TriggeredAbility,
+    },
+    core::{Color, KeywordAbility, Mana, ProtectionQuality},
+};
+
+/// Marks rules text that can't be expressed as abilities yet along with the offending line.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct NotSupported(pub(crate) String);
+
+/// 207.1. The text box is printed on the lower half of the card. It usually contains rules text
+///        defining the card’s abilities.
+///
+/// Parses the Oracle text of the card with the specified name into its abilities. Only vanilla
+/// cards, keyword lines, basic mana abilities, simple enters-the-battlefield triggers and spells
+/// dealing damage to any target are recognized; any other line results in [`NotSupported`].
+pub(crate) fn parse(name: &str, text: &str) -> Result<Vec<Ability>, NotSupported> {
+    let mut abilities = Vec::new();
+    for line in text.lines().map(str::trim).filter(|it| !it.is_empty()) {
+        // An object refers to itself by name, which is normalized to “~” to simplify matching.
+        let line = strip_reminder_text(line).replace(name, "~");
+        if let Some(ability) = parse_mana_ability(&line) {
+            abilities.push(ability);
+        } else if let Some(ability) = parse_enters_the_battlefield_trigger(&line) {
+            abilities.push(ability);
+        } else if let Some(effect) = parse_damage_to_any_target(&line) {
+            abilities.push(Ability::Spell(vec![effect]));
+        } else if let Some(keywords) = parse_keywords(&line) {
+            abilities.extend(keywords.into_iter().map(Ability::Keyword));
+        } else {
+            return Err(NotSupported(line));
+        }
+    }
+    Ok(abilities)
+}
+
+/// 207.2. [...] Reminder text is italicized text within parentheses that summarizes a rule that
+///        applies to that card. [...]
+///
+/// Lines that consist solely of reminder text, such as the mana abilities of basic lands, keep
+/// their contents since they are the only description of the ability.
+fn strip_reminder_text(line: &str) -> String {
+    if let Some(inner) = line
+        .strip_prefix('(')
+        .and_then(|it| it.strip_suffix(')'))
+        .filter(|it| !it.contains('('))
+    {
+        return inner.into();
+    }
+
+    let mut result = String::new();
+    let mut depth = 0_u32;
+    for character in line.chars() {
+        match character {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            _ if depth == 0 => result.push(character),
+            _ => {}
+        }
+    }
+    result.trim().into()
+}
+
+/// 605.1a An activated ability is a mana ability if it meets all of the following criteria: it
+///        doesn’t require a target (see rule 115.6), it could add mana to a player’s mana pool
+///        when it resolves, and it’s not a loyalty ability. (See rule 606, “Loyalty Abilities.”)
+fn parse_mana_ability(line: &str) -> Option<Ability> {
+    let mana = match line.strip_prefix("{T}: Add ")?.strip_suffix('.')? {
+        "{W}" => Mana::Monocolored(Color::White),
+        "{U}" => Mana::Monocolored(Color::Blue),
+        "{B}" => Mana::Monocolored(Color::Black),
+        "{R}" => Mana::Monocolored(Color::Red),
+        "{G}" => Mana::Monocolored(Color::Green),
+        "{C}" => Mana::Colorless,
+        _ => return None,
+    };
+    Some(Ability::Activated(ActivatedAbility {
+        cost: vec![ActivationCost::Tap],
+        effects: vec![Effect::AddMana(mana)],
+    }))
+}
+
+/// 603.6a Enters-the-battlefield abilities trigger when a permanent enters the battlefield. These
+///        are written, “When [this object] enters the battlefield, . . . ” [...]
+fn parse_enters_the_battlefield_trigger(line: &str) -> Option<Ability> {
+    let effect = line
+        .strip_prefix("When ~ enters the battlefield, ")
+        .or_else(|| line.strip_prefix("When ~ enters, "))?
+        .strip_suffix('.')?;
+
+    let effect = if let Some(amount) = effect
+        .strip_prefix("you gain ")
+        .and_then(|it| it.strip_suffix(" life"))
+    {
+        Effect::GainLife(amount.parse().ok()?)
+    } else if effect == "draw a card" {
+        Effect::DrawCards(Quantity::Fixed(1))
+    } else {
+        let amount = effect
+            .strip_prefix("it deals ")
+            .or_else(|| effect.strip_prefix("~ deals "))?
+            .strip_suffix(" damage to any target")?;
+        Effect::DealDamage(Quantity::Fixed(amount.parse().ok()?))
+    };
+
+    Some(Ability::Triggered(TriggeredAbility {
+        condition: TriggerCondition::EntersBattlefield(TriggerSubject::This),
+        intervening_if: None,
+        effects: vec![effect],
+    }))
+}
+
+/// 113.3a Spell abilities are abilities that are followed as instructions while an instant or
+///        sorcery spell is resolving. [...]
+///
+/// Parses the spell ability of burn spells such as “~ deals 3 damage to any target.”
+fn parse_damage_to_any_target(line: &str) -> Option<Effect> {
+    let amount = line
+        .strip_prefix("~ deals ")?
+        .strip_suffix(" damage to any target.")?;
+    Some(Effect::DealDamage(Quantity::Fixed(amount.parse().ok()?)))
+}
+
+/// 702.1. [...] In these cases, the object lists only the name of the ability as a “keyword”;
+///        [...]
+///
+/// Parses a comma-separated list of keywords such as “Flying, vigilance”.
+fn parse_keywords(line: &str) -> Option<Vec<KeywordAbility>> {
+    line.split(", ").map(parse_keyword).collect()
+}
+
+fn parse_keyword(keyword: &str) -> Option<KeywordAbility> {
+    let keyword = keyword.to_lowercase();
+    if let Some(quality) = keyword.strip_prefix("protection from ") {
+        let quality = match quality {
+            "white" => ProtectionQuality::Color(Color::White),
+            "blue" => ProtectionQuality::Color(Color::Blue),
+            "black" => ProtectionQuality::Color(Color::Black),
+            "red" => ProtectionQuality::Color(Color::Red),
+            "green" => ProtectionQuality::Color(Color::Green),
+            "all colors" => ProtectionQuality::AllColors,
+            _ => return None,
+        };
+        return Some(KeywordAbility::Protection(quality));
+    }
+
+    Some(match keyword.as_str() {
+        "deathtouch" => KeywordAbility::Deathtouch,
+        "defender" => KeywordAbility::Defender,
+        "first strike" => KeywordAbility::FirstStrike,
+        "flash" => KeywordAbility::Flash,
+        "flying" => KeywordAbility::Flying,
+        "haste" => KeywordAbility::Haste,
+        "indestructible" => KeywordAbility::Indestructible,
+        "lifelink" => KeywordAbility::Lifelink,
+        "reach" => KeywordAbility::Reach,
+        "trample" => KeywordAbility::Trample,
+        "vigilance" => KeywordAbility::Vigilance,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::consistency::read_corpus;
+
+    fn keywords(keywords: &[KeywordAbility]) -> Vec<Ability> {
+        keywords.iter().cloned().map(Ability::Keyword).collect()
+    }
+
+    fn mana_ability(mana: Mana) -> Ability {
+        Ability::Activated(ActivatedAbility {
+            cost: vec![ActivationCost::Tap],
+            effects: vec![Effect::AddMana(mana)],
+        })
+    }
+
+    fn enters_the_battlefield(effect: Effect) -> Ability {
+        Ability::Triggered(TriggeredAbility {
+            condition: TriggerCondition::EntersBattlefield(TriggerSubject::This),
+            intervening_if: None,
+            effects: vec![effect],
+        })
+    }
+
+    #[test]
+    fn vanilla_creatures_have_no_abilities() {
+        assert_eq!(parse("Grizzly Bears", ""), Ok(Vec::new()));
+        assert_eq!(parse("Hill Giant", "\n"), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn keyword_lines_are_parsed() {
+        assert_eq!(
+            parse("Serra Angel", "Flying, vigilance"),
+            Ok(keywords(&[
+                KeywordAbility::Flying,
+                KeywordAbility::Vigilance
+            ]))
+        );
+        assert_eq!(
+            parse(
+                "Raging Goblin",
+                "Haste (This creature can attack and {T} as soon as it comes under your control.)"
+            ),
+            Ok(keywords(&[KeywordAbility::Haste]))
+        );
+        assert_eq!(
+            parse("Wall of Stone", "Defender"),
+            Ok(keywords(&[KeywordAbility::Defender]))
+        );
+        assert_eq!(
+            parse("Giant Spider", "Reach"),
+            Ok(keywords(&[KeywordAbility::Reach]))
+        );
+        assert_eq!(
+            parse("Healer's Hawk", "Flying, lifelink"),
+            Ok(keywords(&[
+                KeywordAbility::Flying,
+                KeywordAbility::Lifelink
+            ]))
+        );
+        assert_eq!(
+            parse("Ambush Viper", "Flash\nDeathtouch"),
+            Ok(keywords(&[
+                KeywordAbility::Flash,
+                KeywordAbility::Deathtouch
+            ]))
+        );
+        assert_eq!(
+            parse("Black Knight", "First strike\nProtection from white"),
+            Ok(keywords(&[
+                KeywordAbility::FirstStrike,
+                KeywordAbility::Protection(ProtectionQuality::Color(Color::White))
+            ]))
+        );
+        assert_eq!(
+            parse("Colossal Dreadmaw", "Trample"),
+            Ok(keywords(&[KeywordAbility::Trample]))
+        );
+    }
+
+    #[test]
+    fn basic_mana_abilities_are_parsed() {
+        assert_eq!(
+            parse("Plains", "({T}: Add {W}.)"),
+            Ok(vec![mana_ability(Mana::Monocolored(Color::White))])
+        );
+        assert_eq!(
+            parse("Forest", "({T}: Add {G}.)"),
+            Ok(vec![mana_ability(Mana::Monocolored(Color::Green))])
+        );
+        assert_eq!(
+            parse("Llanowar Elves", "{T}: Add {G}."),
+            Ok(vec![mana_ability(Mana::Monocolored(Color::Green))])
+        );
+        assert_eq!(
+            parse("Wastes", "({T}: Add {C}.)"),
+            Ok(vec![mana_ability(Mana::Colorless)])
+        );
+    }
+
+    #[test]
+    fn simple_enters_the_battlefield_triggers_are_parsed() {
+        assert_eq!(
+            parse(
+                "Inspiring Cleric",
+                "When Inspiring Cleric enters the battlefield, you gain 4 life."
+            ),
+            Ok(vec![enters_the_battlefield(Effect::GainLife(4))])
+        );
+        assert_eq!(
+            parse(
+                "Elvish Visionary",
+                "When Elvish Visionary enters the battlefield, draw a card."
+            ),
+            Ok(vec![enters_the_battlefield(Effect::DrawCards(
+                Quantity::Fixed(1)
+            ))])
+        );
+        assert_eq!(
+            parse(
+                "Blisterstick Shaman",
+                "When Blisterstick Shaman enters, it deals 1 damage to any target."
+            ),
+            Ok(vec![enters_the_battlefield(Effect::DealDamage(
+                Quantity::Fixed(1)
+            ))])
+        );
+        assert_eq!(
+            parse(
+                "Cloudkin Seer",
+                "Flying\nWhen Cloudkin Seer enters the battlefield, draw a card."
+            ),
+            Ok(vec![
+                Ability::Keyword(KeywordAbility::Flying),
+                enters_the_battlefield(Effect::DrawCards(Quantity::Fixed(1)))
+            ])
+        );
+    }
+
+    #[test]
+    fn damage_spells_are_parsed() {
+        assert_eq!(
+            parse(
+                "Lightning Bolt",
+                "Lightning Bolt deals 3 damage to any target."
+            ),
+            Ok(vec![Ability::Spell(vec![Effect::DealDamage(
+                Quantity::Fixed(3)
+            )])])
+        );
+        assert_eq!(
+            parse(
+                "Lava Spike",
+                "Lava Spike deals 3 damage to target player or planeswalker."
+            ),
+            Err(NotSupported(
+                "~ deals 3 damage to target player or planeswalker.".into()
+            ))
+        );
+    }
+
+    #[test]
+    fn complicated_rules_text_is_not_supported() {
+        assert_eq!(
+            parse(
+                "Flametongue Kavu",
+                "When Flametongue Kavu enters the battlefield, it deals 4 damage to target creature."
+            ),
+            Err(NotSupported(
+                "When ~ enters the battlefield, it deals 4 damage to target creature.".into()
+            ))
+        );
+        assert_eq!(
+            parse(
+                "Mogg Fanatic",
+                "Sacrifice Mogg Fanatic: It deals 1 damage to any target."
+            ),
+            Err(NotSupported(
+                "Sacrifice ~: It deals 1 damage to any target.".into()
+            ))
+        );
+        assert_eq!(
+            parse(
+                "Shivan Dragon",
+                "Flying\n{R}: Shivan Dragon gets +1/+0 until end of turn."
+            ),
+            Err(NotSupported("{R}: ~ gets +1/+0 until end of turn.".into()))
+        );
+        assert_eq!(
+            parse(
+                "Baneslayer Angel",
+                "Flying, first strike, lifelink, protection from Demons and from Dragons"
+            ),
+            Err(NotSupported(
+                "Flying, first strike, lifelink, protection from Demons and from Dragons".into()
+            ))
+        );
+        assert_eq!(
+            parse(
+                "Goblin Guide",
+                "Haste\nWhenever Goblin Guide attacks, defending player reveals the top card of \
+                 their library. If it's a land card, that player puts it into their hand."
+            ),
+            Err(NotSupported(
+                "Whenever ~ attacks, defending player reveals the top card of their library. If \
+                 it's a land card, that player puts it into their hand."
+                    .into()
+            ))
+        );
+        assert!(parse(
+            "Birds of Paradise",
+            "Flying\n{T}: Add one mana of any color."
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn rules_text_in_the_regression_corpus_is_parsed_without_panicking() {
+        let inputs = read_corpus("rules_text");
+        assert!(!inputs.is_empty());
+        for input in inputs {
+            // Any result is fine as long as parsing returns.
+            let _ = parse("Test Card", &input);
+        }
+    }
+}
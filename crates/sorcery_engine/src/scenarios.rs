@@ -0,0 +1,714 @@
+//! Scenario tests, which set up a game state, apply a sequence of actions and compare the outcome
+//! serialized as RON with a golden file in `tests/scenarios`. Setting the environment variable
+//! `SORCERY_BLESS` writes the outcomes to the golden files instead, which is how new scenarios are
+//! added and intended changes of existing ones are accepted.
+
+use std::{collections::HashMap, env, fs, path::Path, sync::Arc};
+
+use hecs::Entity;
+use pretty_assertions::assert_eq;
+use ron::ser::PrettyConfig;
+use serde::Serialize;
+
+use crate::{
+    components::SummoningSick,
+    core::{Card, CounterKind, GameOutcome, Mana, PlayerId, Zone},
+    error::EngineError,
+    game::{find_card_by_name, Game, PermanentOrPlayer},
+    spectator::{ObjectState, ObjectStatus},
+    turn::Step,
+};
+
+/// The environment variable that makes scenarios overwrite their golden files.
+const BLESS_VARIABLE: &str = "SORCERY_BLESS";
+
+/// An action a scenario applies to its game. Players are referred to by their index and objects by
+/// the labels they were given as the scenario was set up.
+#[derive(Clone, Debug)]
+pub(crate) enum ScenarioAction {
+    NextTurn,
+    BeginStep(Step),
+    PlayLand {
+        player: usize,
+        land: &'static str,
+    },
+    CastSpell {
+        player: usize,
+        spell: &'static str,
+    },
+    CastSpellWithX {
+        player: usize,
+        spell: &'static str,
+        x: u64,
+    },
+    ResolveSpell {
+        spell: &'static str,
+        targets: Vec<ScenarioTarget>,
+    },
+    ActivateAbility {
+        player: usize,
+        source: &'static str,
+        index: usize,
+        targets: Vec<ScenarioTarget>,
+    },
+    /// Resolves the activated ability on top of the stack.
+    ResolveAbility,
+    DeclareAttackers {
+        player: usize,
+        attackers: Vec<&'static str>,
+    },
+    /// Declares blocks as pairs of a blocker and the attacker it blocks.
+    DeclareBlockers {
+        player: usize,
+        blocks: Vec<(&'static str, &'static str)>,
+    },
+    /// Resolves all pending triggered abilities without any targets.
+    ResolveTriggers,
+}
+
+/// A target of a [`ScenarioAction`].
+#[derive(Copy, Clone, Debug)]
+pub(crate) enum ScenarioTarget {
+    Player(usize),
+    Object(&'static str),
+}
+
+/// A game state together with the actions to apply to it and the name of its golden file.
+pub(crate) struct Scenario {
+    name: &'static str,
+    game: Game,
+    objects: HashMap<&'static str, Entity>,
+    actions: Vec<ScenarioAction>,
+}
+
+impl Scenario {
+    /// Creates a scenario of a new game with the specified number of players, whose libraries are
+    /// empty.
+    pub(crate) fn new(name: &'static str, players: u32) -> Self {
+        Self::from_game(name, Game::new(players))
+    }
+
+    /// Creates a scenario of an existing game, e.g. one set up by a
+    /// [`crate::setup::GameBuilder`].
+    pub(crate) fn from_game(name: &'static str, game: Game) -> Self {
+        Self {
+            name,
+            game,
+            objects: HashMap::new(),
+            actions: Vec::new(),
+        }
+    }
+
+    /// Puts a card onto the battlefield under the control of the specified player. Unlike
+    /// permanents entering during the game it can attack and tap right away.
+    #[must_use]
+    pub(crate) fn battlefield(self, player: usize, label: &'static str, card: &Arc<Card>) -> Self {
+        let mut scenario = self.spawn(player, label, card, |_| Zone::Battlefield);
+        let entity = scenario.entity(label);
+        let _ = scenario
+            .game
+            .world_mut()
+            .remove_one::<SummoningSick>(entity);
+        scenario
+    }
+
+    /// Puts a card into the hand of the specified player.
+    #[must_use]
+    pub(crate) fn hand(self, player: usize, label: &'static str, card: &Arc<Card>) -> Self {
+        self.spawn(player, label, card, Zone::Hand)
+    }
+
+    /// Puts a card into the graveyard of the specified player.
+    #[must_use]
+    pub(crate) fn graveyard(self, player: usize, label: &'static str, card: &Arc<Card>) -> Self {
+        self.spawn(player, label, card, Zone::Graveyard)
+    }
+
+    /// Puts a card owned by the specified player onto the stack as if it had been cast.
+    #[must_use]
+    pub(crate) fn stack(self, player: usize, label: &'static str, card: &Arc<Card>) -> Self {
+        self.spawn(player, label, card, |_| Zone::Stack)
+    }
+
+    /// Sets the life total of the specified player.
+    #[must_use]
+    pub(crate) fn life(mut self, player: usize, life: i64) -> Self {
+        let player = self.player(player);
+        let current = self
+            .game
+            .player(player)
+            .expect("Could not access the player.")
+            .life;
+        let difference = (life - current).unsigned_abs();
+        if life < current {
+            self.game.lose_life(player, difference)
+        } else {
+            self.game.gain_life(player, difference)
+        }
+        .expect("Could not set the life total.");
+        self
+    }
+
+    /// Adds an action that is applied once the scenario runs.
+    #[must_use]
+    pub(crate) fn then(mut self, action: ScenarioAction) -> Self {
+        self.actions.push(action);
+        self
+    }
+
+    /// Applies the actions in order and returns the rendered outcome. Failing actions don't stop
+    /// the scenario, instead their errors are part of the outcome.
+    pub(crate) fn run(mut self) -> String {
+        let mut errors = Vec::new();
+        for (index, action) in std::mem::take(&mut self.actions).into_iter().enumerate() {
+            if let Err(error) = self.apply(action) {
+                errors.push((index + 1, error));
+            }
+        }
+        self.render(&errors)
+    }
+
+    /// Runs the scenario and compares its outcome with its golden file, or overwrites the golden
+    /// file if the scenario is blessed.
+    pub(crate) fn check(self) {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("scenarios")
+            .join(format!("{}.ron", self.name));
+        let actual = self.run();
+        if env::var_os(BLESS_VARIABLE).is_some() {
+            if let Some(directory) = path.parent() {
+                fs::create_dir_all(directory).expect("Could not create the directory.");
+            }
+            fs::write(&path, actual).expect("Could not write the golden file.");
+            return;
+        }
+        let expected = fs::read_to_string(&path).unwrap_or_else(|_| {
+            panic!(
+                "Could not read {}, run the scenario with {BLESS_VARIABLE}=1 to create it.",
+                path.display()
+            )
+        });
+        assert_eq!(
+            actual,
+            expected,
+            "The outcome differs from {}, run the scenario with {BLESS_VARIABLE}=1 if the change \
+             is intended.",
+            path.display()
+        );
+    }
+
+    fn spawn(
+        mut self,
+        player: usize,
+        label: &'static str,
+        card: &Arc<Card>,
+        zone: impl FnOnce(PlayerId) -> Zone,
+    ) -> Self {
+        let owner = self.player(player);
+        let entity = self
+            .game
+            .spawn_object(card, owner, zone(owner))
+            .expect("Could not spawn the object.");
+        assert!(
+            self.objects.insert(label, entity).is_none(),
+            "The label {label} is used more than once."
+        );
+        self
+    }
+
+    fn player(&self, index: usize) -> PlayerId {
+        self.game
+            .players()
+            .get(index)
+            .unwrap_or_else(|| panic!("Could not find the player at index {index}."))
+            .id
+    }
+
+    fn entity(&self, label: &str) -> Entity {
+        *self
+            .objects
+            .get(label)
+            .unwrap_or_else(|| panic!("Could not find the object labeled {label}."))
+    }
+
+    fn targets(&self, targets: &[ScenarioTarget]) -> Vec<PermanentOrPlayer> {
+        targets
+            .iter()
+            .map(|it| match *it {
+                ScenarioTarget::Player(index) => PermanentOrPlayer::Player(self.player(index)),
+                ScenarioTarget::Object(label) => PermanentOrPlayer::Permanent(self.entity(label)),
+            })
+            .collect()
+    }
+
+    fn apply(&mut self, action: ScenarioAction) -> Result<(), EngineError> {
+        match action {
+            ScenarioAction::NextTurn => self.game.next_turn(),
+            ScenarioAction::BeginStep(step) => self.game.begin_step(step),
+            ScenarioAction::PlayLand { player, land } => {
+                let (player, land) = (self.player(player), self.entity(land));
+                self.game.play_land(player, land)
+            }
+            ScenarioAction::CastSpell { player, spell } => {
+                let (player, spell) = (self.player(player), self.entity(spell));
+                self.game.cast_spell(player, spell, None)
+            }
+            ScenarioAction::CastSpellWithX { player, spell, x } => {
+                let (player, spell) = (self.player(player), self.entity(spell));
+                self.game.cast_spell_with_x(player, spell, x, None)
+            }
+            ScenarioAction::ResolveSpell { spell, targets } => {
+                let (spell, targets) = (self.entity(spell), self.targets(&targets));
+                self.game.resolve_spell(spell, &targets)
+            }
+            ScenarioAction::ActivateAbility {
+                player,
+                source,
+                index,
+                targets,
+            } => {
+                let (player, source) = (self.player(player), self.entity(source));
+                let targets = self.targets(&targets);
+                self.game.activate_ability(player, source, index, &targets)
+            }
+            ScenarioAction::ResolveAbility => self.game.resolve_top_of_stack(),
+            ScenarioAction::DeclareAttackers { player, attackers } => {
+                let player = self.player(player);
+                let attackers = attackers
+                    .iter()
+                    .map(|it| self.entity(it))
+                    .collect::<Vec<_>>();
+                self.game.declare_attackers(player, &attackers)
+            }
+            ScenarioAction::DeclareBlockers { player, blocks } => {
+                let player = self.player(player);
+                let blocks = blocks
+                    .iter()
+                    .map(|(blocker, attacker)| (self.entity(blocker), self.entity(attacker)))
+                    .collect::<Vec<_>>();
+                self.game.declare_blockers(player, &blocks)
+            }
+            ScenarioAction::ResolveTriggers => {
+                while self.game.resolve_next_trigger(&[])? {}
+                Ok(())
+            }
+        }
+    }
+
+    /// Renders the public state of the game: the turn, each player with the objects they control
+    /// on the battlefield and own in their graveyard and exile, the stack, the outcome of the game
+    /// and the errors of the actions that failed. Hidden zones are only rendered as their sizes.
+    fn render(&self, errors: &[(usize, EngineError)]) -> String {
+        let view = self.game.spectator_view(false);
+        let name = |player: PlayerId| {
+            view.players
+                .iter()
+                .find(|it| it.id == player)
+                .map_or("an unknown player", |it| it.info.name.as_str())
+                .to_owned()
+        };
+        let objects = |predicate: &dyn Fn(&ObjectStatus) -> bool| {
+            view.objects
+                .iter()
+                .filter(|it| predicate(it))
+                .map(|it| self.describe(it))
+                .collect::<Vec<_>>()
+        };
+
+        let outcome = Outcome {
+            turn: view.turn,
+            active_player: name(view.active_player),
+            step: view.step,
+            players: view
+                .players
+                .iter()
+                .map(|player| PlayerOutcome {
+                    name: player.info.name.clone(),
+                    life: player.life,
+                    hand: objects(&|it| it.zone == Zone::Hand(player.id)).len(),
+                    library: player.library_size,
+                    counters: player.counters.clone(),
+                    left_the_game: player.has_left,
+                    mana_pool: player.mana_pool.clone(),
+                    battlefield: objects(&|it| {
+                        it.zone == Zone::Battlefield && it.controller == Some(player.id)
+                    }),
+                    graveyard: objects(&|it| it.zone == Zone::Graveyard(player.id)),
+                    exile: objects(&|it| it.zone == Zone::Exile && it.owner == player.id),
+                })
+                .collect(),
+            stack: objects(&|it| it.zone == Zone::Stack),
+            result: self.game.outcome().map(|it| match it {
+                GameOutcome::Win(winner) => GameResult::Win(name(winner)),
+                GameOutcome::Draw => GameResult::Draw,
+            }),
+            errors: errors
+                .iter()
+                .map(|(index, error)| (*index, error.to_string()))
+                .collect(),
+        };
+        let config = PrettyConfig::new().struct_names(true);
+        ron::ser::to_string_pretty(&outcome, config).expect("Could not serialize the outcome.")
+            + "\n"
+    }
+
+    /// Describes an object by its name, its power and toughness on the battlefield and its states,
+    /// damage and counters.
+    fn describe(&self, object: &ObjectStatus) -> ObjectOutcome {
+        ObjectOutcome {
+            name: object.name.clone(),
+            power_toughness: self
+                .game
+                .object(object.id)
+                .filter(|_| object.zone == Zone::Battlefield)
+                .and_then(|it| self.game.power_toughness(it)),
+            token: object.token,
+            states: object.statuses.clone(),
+            damage: (object.marked_damage > 0).then_some(object.marked_damage),
+            counters: object.counters.clone(),
+        }
+    }
+}
+
+/// The public state of a game after a scenario ran, which is stored in the golden file of the
+/// scenario. Empty collections and absent values are left out to keep the files short.
+#[derive(Debug, Serialize)]
+#[serde(rename = "Scenario")]
+struct Outcome {
+    turn: u64,
+    active_player: String,
+    step: Step,
+    players: Vec<PlayerOutcome>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    stack: Vec<ObjectOutcome>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<GameResult>,
+    /// The errors of the actions that failed, along with the number of the action.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    errors: Vec<(usize, String)>,
+}
+
+/// A player within an [`Outcome`], with the number of cards in their hidden zones.
+#[derive(Debug, Serialize)]
+#[serde(rename = "Player")]
+struct PlayerOutcome {
+    name: String,
+    life: i64,
+    hand: usize,
+    library: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    counters: Vec<(CounterKind, u64)>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    left_the_game: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    mana_pool: Vec<Mana>,
+    /// The permanents the player controls.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    battlefield: Vec<ObjectOutcome>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    graveyard: Vec<ObjectOutcome>,
+    /// The cards the player owns in exile.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    exile: Vec<ObjectOutcome>,
+}
+
+/// An object within an [`Outcome`]. Only permanents have a power and toughness.
+#[derive(Debug, Serialize)]
+#[serde(rename = "Object")]
+struct ObjectOutcome {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    power_toughness: Option<(i64, i64)>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    token: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    states: Vec<ObjectState>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    damage: Option<u64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    counters: Vec<(CounterKind, u64)>,
+}
+
+/// How the game of an [`Outcome`] ended.
+#[derive(Debug, Serialize)]
+enum GameResult {
+    Win(String),
+    Draw,
+}
+
+/// Returns the card with the specified name from the card database.
+pub(crate) fn card(name: &str) -> Arc<Card> {
+    Arc::clone(find_card_by_name(name).expect("Could not find the card."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        abilities::{Ability, CounterAmount, StaticAbility},
+        core::{
+            CardType, CounterKind, Deck, KeywordAbility, ManaCost, ManaSymbol, Name,
+            PtCharacteristic, PtValue, TypeLine,
+        },
+        rules_text,
+        setup::GameBuilder,
+    };
+
+    /// Returns a creature card with the specified name, mana cost, power, toughness and abilities.
+    fn creature(
+        name: &str,
+        mana_cost: ManaCost,
+        (power, toughness): (i64, i64),
+        abilities: Vec<Ability>,
+    ) -> Arc<Card> {
+        let card = Card::builder()
+            .name(Name(name.into()))
+            .type_line(TypeLine {
+                card_type: [CardType::Creature].into(),
+                subtype: [].into(),
+                supertype: [].into(),
+            })
+            .mana_cost(mana_cost)
+            .pt(PtCharacteristic {
+                power: PtValue::Fixed(power),
+                toughness: PtValue::Fixed(toughness),
+            })
+            .abilities(abilities)
+            .build()
+            .expect("Failed to build the card.");
+        Arc::new(card)
+    }
+
+    fn vanilla(name: &str, pt: (i64, i64)) -> Arc<Card> {
+        creature(name, ManaCost(vec![]), pt, vec![])
+    }
+
+    /// Returns a Lightning Bolt that costs nothing, so scenarios don't need lands to cast it.
+    fn lightning_bolt() -> Arc<Card> {
+        let abilities = rules_text::parse(
+            "Lightning Bolt",
+            "Lightning Bolt deals 3 damage to any target.",
+        )
+        .expect("Could not parse the rules text.");
+        let card = Card::builder()
+            .name(Name("Lightning Bolt".into()))
+            .type_line(TypeLine {
+                card_type: [CardType::Instant].into(),
+                subtype: [].into(),
+                supertype: [].into(),
+            })
+            .mana_cost(ManaCost(vec![]))
+            .abilities(abilities)
+            .build()
+            .expect("Failed to build the card.");
+        Arc::new(card)
+    }
+
+    /// Declares the attackers and the blockers and deals combat damage during the first turn.
+    fn combat(
+        scenario: Scenario,
+        attackers: Vec<&'static str>,
+        blocks: Vec<(&'static str, &'static str)>,
+    ) -> Scenario {
+        scenario
+            .then(ScenarioAction::BeginStep(Step::DeclareAttackers))
+            .then(ScenarioAction::DeclareAttackers {
+                player: 0,
+                attackers,
+            })
+            .then(ScenarioAction::BeginStep(Step::DeclareBlockers))
+            .then(ScenarioAction::DeclareBlockers { player: 1, blocks })
+            .then(ScenarioAction::BeginStep(Step::CombatDamage))
+    }
+
+    #[test]
+    fn opening_hands() {
+        let white_deck =
+            Deck::from(&[("Plains", 30), ("Soulmender", 30)]).expect("Could not build the deck.");
+        let green_deck = Deck::from(&[("Forest", 30), ("Llanowar Elves", 30)])
+            .expect("Could not build the deck.");
+        let (game, _) = GameBuilder::new()
+            .player("Player 1", white_deck)
+            .player("Player 2", green_deck)
+            .starting_player(0)
+            .build()
+            .expect("Could not build the game.");
+
+        Scenario::from_game("opening_hands", game).check();
+    }
+
+    #[test]
+    fn lands_are_played_once_per_turn() {
+        Scenario::new("lands_are_played_once_per_turn", 2)
+            .hand(0, "first", &card("Forest"))
+            .hand(0, "second", &card("Forest"))
+            .then(ScenarioAction::BeginStep(Step::PrecombatMain))
+            .then(ScenarioAction::PlayLand {
+                player: 0,
+                land: "first",
+            })
+            .then(ScenarioAction::PlayLand {
+                player: 0,
+                land: "second",
+            })
+            .check();
+    }
+
+    #[test]
+    fn creature_spells_resolve_onto_the_battlefield() {
+        Scenario::new("creature_spells_resolve_onto_the_battlefield", 2)
+            .battlefield(0, "forest", &card("Forest"))
+            .hand(0, "elves", &card("Llanowar Elves"))
+            .then(ScenarioAction::BeginStep(Step::PrecombatMain))
+            .then(ScenarioAction::CastSpell {
+                player: 0,
+                spell: "elves",
+            })
+            .then(ScenarioAction::ResolveSpell {
+                spell: "elves",
+                targets: vec![],
+            })
+            .check();
+    }
+
+    #[test]
+    fn damage_is_marked_on_creatures() {
+        Scenario::new("damage_is_marked_on_creatures", 2)
+            .hand(0, "bolt", &lightning_bolt())
+            .battlefield(1, "soulmender", &card("Soulmender"))
+            .then(ScenarioAction::CastSpell {
+                player: 0,
+                spell: "bolt",
+            })
+            .then(ScenarioAction::ResolveSpell {
+                spell: "bolt",
+                targets: vec![ScenarioTarget::Object("soulmender")],
+            })
+            .check();
+    }
+
+    #[test]
+    fn players_without_life_lose_the_game() {
+        Scenario::new("players_without_life_lose_the_game", 2)
+            .life(1, 3)
+            .hand(0, "bolt", &lightning_bolt())
+            .then(ScenarioAction::CastSpell {
+                player: 0,
+                spell: "bolt",
+            })
+            .then(ScenarioAction::ResolveSpell {
+                spell: "bolt",
+                targets: vec![ScenarioTarget::Player(1)],
+            })
+            .check();
+    }
+
+    #[test]
+    fn unblocked_attackers_damage_the_defending_player() {
+        let scenario = Scenario::new("unblocked_attackers_damage_the_defending_player", 2)
+            .battlefield(0, "bears", &vanilla("Grizzly Bears", (2, 2)));
+        combat(scenario, vec!["bears"], vec![]).check();
+    }
+
+    #[test]
+    fn blocked_attackers_and_blockers_damage_each_other() {
+        let scenario = Scenario::new("blocked_attackers_and_blockers_damage_each_other", 2)
+            .battlefield(0, "giant", &vanilla("Hill Giant", (3, 3)))
+            .battlefield(1, "bears", &vanilla("Grizzly Bears", (2, 2)));
+        combat(scenario, vec!["giant"], vec![("bears", "giant")]).check();
+    }
+
+    #[test]
+    fn trample_assigns_excess_damage_to_the_defending_player() {
+        let dreadmaw = creature(
+            "Colossal Dreadmaw",
+            ManaCost(vec![]),
+            (6, 6),
+            vec![Ability::Keyword(KeywordAbility::Trample)],
+        );
+        let scenario = Scenario::new("trample_assigns_excess_damage_to_the_defending_player", 2)
+            .battlefield(0, "dreadmaw", &dreadmaw)
+            .battlefield(1, "bears", &vanilla("Grizzly Bears", (2, 2)));
+        combat(scenario, vec!["dreadmaw"], vec![("bears", "dreadmaw")]).check();
+    }
+
+    #[test]
+    fn creatures_enter_with_x_counters() {
+        // Endless One enters the battlefield with X +1/+1 counters on it.
+        let endless_one = creature(
+            "Endless One",
+            ManaCost(vec![ManaSymbol::Variable]),
+            (0, 0),
+            vec![Ability::Static(StaticAbility::EntersWithCounters(
+                CounterKind::PlusOnePlusOne,
+                CounterAmount::X,
+            ))],
+        );
+        Scenario::new("creatures_enter_with_x_counters", 2)
+            .battlefield(0, "first forest", &card("Forest"))
+            .battlefield(0, "second forest", &card("Forest"))
+            .hand(0, "large", &endless_one)
+            .hand(0, "small", &endless_one)
+            .then(ScenarioAction::BeginStep(Step::PrecombatMain))
+            .then(ScenarioAction::CastSpellWithX {
+                player: 0,
+                spell: "large",
+                x: 2,
+            })
+            .then(ScenarioAction::ResolveSpell {
+                spell: "large",
+                targets: vec![],
+            })
+            .then(ScenarioAction::CastSpellWithX {
+                player: 0,
+                spell: "small",
+                x: 0,
+            })
+            .then(ScenarioAction::ResolveSpell {
+                spell: "small",
+                targets: vec![],
+            })
+            .check();
+    }
+
+    #[test]
+    fn the_legend_rule_keeps_the_newest_legendary_permanent() {
+        Scenario::new("the_legend_rule_keeps_the_newest_legendary_permanent", 2)
+            .battlefield(0, "older", &card("Polukranos, Unchained"))
+            .stack(0, "newer", &card("Polukranos, Unchained"))
+            .then(ScenarioAction::ResolveSpell {
+                spell: "newer",
+                targets: vec![],
+            })
+            .check();
+    }
+
+    #[test]
+    fn activated_abilities_pay_their_costs_and_resolve() {
+        Scenario::new("activated_abilities_pay_their_costs_and_resolve", 2)
+            .battlefield(0, "soulmender", &card("Soulmender"))
+            .then(ScenarioAction::ActivateAbility {
+                player: 0,
+                source: "soulmender",
+                index: 0,
+                targets: vec![],
+            })
+            .then(ScenarioAction::ResolveAbility)
+            .check();
+    }
+
+    #[test]
+    fn landfall_triggers_when_a_land_is_played() {
+        Scenario::new("landfall_triggers_when_a_land_is_played", 2)
+            .battlefield(0, "lynx", &card("Steppe Lynx"))
+            .hand(0, "forest", &card("Forest"))
+            .then(ScenarioAction::BeginStep(Step::PrecombatMain))
+            .then(ScenarioAction::PlayLand {
+                player: 0,
+                land: "forest",
+            })
+            .then(ScenarioAction::ResolveTriggers)
+            .check();
+    }
+}
@@ -0,0 +1,338 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::{Deck, Format, PlayerId, PlayerInfo},
+    error::EngineError,
+    game::Game,
+};
+
+/// The optional rules a game is played with. Every rule is disabled by default, so the default
+/// configuration plays by the current rules without checking decks against a format.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameConfig {
+    /// The format the decks are validated against before the game starts, if any.
+    format: Option<Format>,
+    mana_burn: bool,
+    ante: bool,
+    planeswalker_uniqueness: bool,
+    /// The number of times the same game state may repeat while mandatory triggers resolve, unless
+    /// the game keeps its default.
+    loop_threshold: Option<usize>,
+}
+
+impl GameConfig {
+    /// Validates the decks against the specified format before the game starts.
+    #[must_use]
+    pub fn format(mut self, format: Format) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// Makes players lose life for the mana that empties from their mana pools, as under the rules
+    /// before Magic 2010.
+    #[must_use]
+    pub fn mana_burn(mut self, mana_burn: bool) -> Self {
+        self.mana_burn = mana_burn;
+        self
+    }
+
+    /// Makes the game be played for ante.
+    #[must_use]
+    pub fn ante(mut self, ante: bool) -> Self {
+        self.ante = ante;
+        self
+    }
+
+    /// Applies the planeswalker uniqueness rule of the rules before Ixalan.
+    #[must_use]
+    pub fn planeswalker_uniqueness(mut self, planeswalker_uniqueness: bool) -> Self {
+        self.planeswalker_uniqueness = planeswalker_uniqueness;
+        self
+    }
+
+    /// Sets the number of times the same game state may repeat while mandatory triggers resolve
+    /// before the game ends in a draw.
+    #[must_use]
+    pub fn loop_threshold(mut self, loop_threshold: usize) -> Self {
+        self.loop_threshold = Some(loop_threshold);
+        self
+    }
+}
+
+/// Sets up a game in a single call: validates the decks, creates the players, chooses the starting
+/// player at random and makes each player draw their opening hand.
+///
+/// ```
+/// use sorcery_engine::prelude::*;
+///
+/// let deck = || Deck::parse("20 Forest\n20 Llanowar Elves");
+/// let (game, players) = GameBuilder::new()
+///     .seed(42)
+///     .player("Alice", deck()?)
+///     .player("Bob", deck()?)
+///     .build()?;
+/// # Ok::<(), EngineError>(())
+/// ```
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct GameBuilder {
+    seed: Option<u64>,
+    players: Vec<(String, Deck)>,
+    starting_player: Option<usize>,
+    /// The indices of the players whose trivial decisions the engine makes on their behalf.
+    auto_choose_trivial: Vec<usize>,
+    config: GameConfig,
+}
+
+impl GameBuilder {
+    /// Creates a builder for a game without any players.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Seeds the random number generator of the game, which makes choosing the starting player and
+    /// shuffling the libraries reproducible. Without a seed, the game is seeded at random.
+    #[must_use]
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Adds a player with the specified name and deck. The players receive their ids in the order
+    /// they are added.
+    #[must_use]
+    pub fn player(mut self, name: impl Into<String>, deck: Deck) -> Self {
+        self.players.push((name.into(), deck));
+        self
+    }
+
+    /// Makes the player that was added at the specified index take the first turn instead of a
+    /// random one.
+    #[must_use]
+    pub fn starting_player(mut self, index: usize) -> Self {
+        self.starting_player = Some(index);
+        self
+    }
+
+    /// Makes the engine make the decisions with a single legal option of the player that was
+    /// added at the specified index without asking them, see [`Game::set_auto_choose_trivial`].
+    #[must_use]
+    pub fn auto_choose_trivial(mut self, index: usize) -> Self {
+        self.auto_choose_trivial.push(index);
+        self
+    }
+
+    /// Sets the optional rules the game is played with.
+    #[must_use]
+    pub fn config(mut self, config: GameConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Creates and starts the game and returns it together with the ids of the players in the order
+    /// they were added.
+    ///
+    /// # Errors
+    /// Fails if there are no players, a deck is empty or not valid in the configured format, or
+    /// the starting player or a player whose trivial decisions are made automatically doesn't
+    /// exist.
+    pub fn build(self) -> Result<(Game, Vec<PlayerId>), EngineError> {
+        if self.players.is_empty() {
+            return Err(EngineError::IllegalAction(
+                "A game needs at least one player.".into(),
+            ));
+        }
+        if let Some(format) = self.config.format {
+            for (name, deck) in &self.players {
+                deck.validate(format).map_err(|it| {
+                    EngineError::InvalidDeck(format!("The deck of {name} is invalid: {it}"))
+                })?;
+            }
+        }
+
+        // The seed is kept with the game, so that it can be saved and restored.
+        let seed = self.seed.unwrap_or_else(rand::random);
+        let setup = GameBuilder {
+            seed: Some(seed),
+            ..self.clone()
+        };
+        let (infos, decks): (Vec<_>, Vec<_>) = self
+            .players
+            .into_iter()
+            .map(|(name, deck)| {
+                let info = PlayerInfo {
+                    name,
+                    external_id: None,
+                };
+                (info, deck)
+            })
+            .unzip();
+        let mut game = Game::with_players(infos);
+        let players = game.players().iter().map(|it| it.id).collect::<Vec<_>>();
+
+        game.set_seed(seed);
+        let config = self.config;
+        game.set_mana_burn(config.mana_burn);
+        game.set_playing_for_ante(config.ante);
+        game.set_planeswalker_uniqueness_rule(config.planeswalker_uniqueness);
+        if let Some(loop_threshold) = config.loop_threshold {
+            game.set_loop_threshold(loop_threshold);
+        }
+        for index in self.auto_choose_trivial {
+            let player = *players.get(index).ok_or_else(|| {
+                EngineError::IllegalAction(format!("There is no player at index {index}."))
+            })?;
+            game.set_auto_choose_trivial(player, true)?;
+        }
+
+        match self.starting_player {
+            Some(index) => {
+                let player = *players.get(index).ok_or_else(|| {
+                    EngineError::IllegalAction(format!("There is no player at index {index}."))
+                })?;
+                game.set_starting_player(player)?;
+            }
+            None => {
+                game.choose_random_starting_player()?;
+            }
+        }
+        game.start(
+            &players
+                .iter()
+                .copied()
+                .zip(decks)
+                .collect::<HashMap<_, _>>(),
+        )?;
+        game.draw_opening_hands()?;
+        game.set_setup(setup);
+        Ok((game, players))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::core::{Card, Legality, Name, Zone};
+
+    fn deck() -> Deck {
+        Deck::parse("20 Forest\n20 Llanowar Elves").expect("Could not create the deck.")
+    }
+
+    #[test]
+    fn built_games_have_drawn_their_opening_hands() {
+        let (game, players) = GameBuilder::new()
+            .seed(42)
+            .player("Alice", deck())
+            .player("Bob", deck())
+            .build()
+            .expect("Could not build the game.");
+        assert_eq!(players.len(), 2);
+
+        let view = game.spectator_view(false);
+        for player in players {
+            let status = view
+                .players
+                .iter()
+                .find(|it| it.id == player)
+                .expect("Could not find the player.");
+            assert_eq!(status.library_size, 33);
+            let hand = view
+                .objects
+                .iter()
+                .filter(|it| it.zone == Zone::Hand(player))
+                .count();
+            assert_eq!(hand, 7);
+        }
+    }
+
+    #[test]
+    fn games_built_from_the_same_seed_have_the_same_starting_player_and_libraries() {
+        let build = |seed| {
+            let (game, _) = GameBuilder::new()
+                .seed(seed)
+                .player("Alice", deck())
+                .player("Bob", deck())
+                .build()
+                .expect("Could not build the game.");
+            game.spectator_view(true)
+        };
+        assert_eq!(build(7), build(7));
+    }
+
+    #[test]
+    fn the_engine_makes_the_trivial_decisions_of_the_chosen_players() {
+        let (game, _) = GameBuilder::new()
+            .player("Alice", deck())
+            .player("Bob", deck())
+            .auto_choose_trivial(1)
+            .build()
+            .expect("Could not build the game.");
+        let automatic = game
+            .players()
+            .iter()
+            .map(|it| it.auto_choose_trivial)
+            .collect::<Vec<_>>();
+        assert_eq!(automatic, vec![false, true]);
+
+        let result = GameBuilder::new()
+            .player("Alice", deck())
+            .auto_choose_trivial(1)
+            .build();
+        assert!(matches!(result, Err(EngineError::IllegalAction(_))));
+    }
+
+    #[test]
+    fn the_starting_player_can_be_chosen() {
+        let (game, players) = GameBuilder::new()
+            .player("Alice", deck())
+            .player("Bob", deck())
+            .starting_player(1)
+            .build()
+            .expect("Could not build the game.");
+        assert_eq!(game.turn().active_player, players[1]);
+
+        let result = GameBuilder::new()
+            .player("Alice", deck())
+            .starting_player(1)
+            .build();
+        assert!(matches!(result, Err(EngineError::IllegalAction(_))));
+    }
+
+    #[test]
+    fn decks_are_validated_against_the_configured_format() {
+        let banned = Card::builder()
+            .name(Name("Hogaak, Arisen Necropolis".into()))
+            .legalities([(Format::Modern, Legality::Banned)].into())
+            .build()
+            .expect("Failed to build the card.");
+        let build = |format| {
+            GameBuilder::new()
+                .player("Alice", Deck::from_cards(vec![Arc::new(banned.clone())]))
+                .config(GameConfig::default().format(format))
+                .build()
+                .map(|_| ())
+        };
+        assert_eq!(
+            build(Format::Modern),
+            Err(EngineError::InvalidDeck(
+                "The deck of Alice is invalid: Hogaak, Arisen Necropolis is banned in Modern."
+                    .into()
+            ))
+        );
+        assert_eq!(build(Format::Legacy), Ok(()));
+    }
+
+    #[test]
+    fn games_need_players() {
+        assert!(matches!(
+            GameBuilder::new().build(),
+            Err(EngineError::IllegalAction(_))
+        ));
+    }
+}
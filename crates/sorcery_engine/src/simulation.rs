@@ -0,0 +1,96 @@
+use std::{num::NonZeroUsize, thread};
+
+/// Plays an independent game for each of the seeds on the specified number of threads and returns
+/// the results in the order of the seeds. Each game is created and played by `play` on a single
+/// thread, so the results only depend on the seeds and not on how the games are scheduled.
+pub(crate) fn simulate_games<T: Send>(
+    seeds: &[u64],
+    threads: NonZeroUsize,
+    play: impl Fn(u64) -> T + Sync,
+) -> Vec<T> {
+    let chunk_size = seeds.len().div_ceil(threads.get()).max(1);
+    let play = &play;
+    thread::scope(|scope| {
+        let handles = seeds
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(|&it| play(it)).collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .flat_map(|it| it.join().expect("A simulated game panicked."))
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Arc};
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{
+        core::{Deck, Zone},
+        game::{CardDatabase, Game},
+        turn::Step,
+    };
+
+    const fn assert_send<T: Send>() {}
+    const fn assert_send_and_sync<T: Send + Sync>() {}
+
+    // Games can be moved to other threads and the card database can be shared between them.
+    const _: () = assert_send::<Game>();
+    const _: () = assert_send_and_sync::<Arc<CardDatabase>>();
+
+    /// Plays a few turns of a seeded game in which every player plays the top card of their library
+    /// each turn and returns the hash of the final state.
+    fn play_seeded_game(seed: u64) -> u64 {
+        let mut game = Game::new(2);
+        game.set_seed(seed);
+        let players = game.players().iter().map(|it| it.id).collect::<Vec<_>>();
+        let decks = [
+            Deck::from(&[("Plains", 30), ("Soulmender", 30)]),
+            Deck::from(&[("Forest", 30), ("Llanowar Elves", 30)]),
+        ]
+        .into_iter()
+        .map(|it| it.expect("Could not build the deck."));
+        game.start(
+            &players
+                .iter()
+                .copied()
+                .zip(decks)
+                .collect::<HashMap<_, _>>(),
+        )
+        .expect("Could not start the game.");
+        for _ in 0..4 {
+            let player = game.turn().active_player;
+            game.begin_step(Step::PrecombatMain)
+                .expect("Could not begin the step.");
+            let card = game
+                .library(player)
+                .expect("Could not access the library.")
+                .cards()
+                .next()
+                .expect("The library is empty.");
+            game.move_object(card, Zone::Battlefield)
+                .expect("Could not move the object.");
+            game.next_turn().expect("Could not begin the next turn.");
+        }
+        game.state_hash()
+    }
+
+    #[test]
+    fn parallel_games_match_the_same_games_played_serially() {
+        let seeds = (0..8).collect::<Vec<_>>();
+        let threads = NonZeroUsize::new(4).expect("The number of threads is zero.");
+
+        let parallel = simulate_games(&seeds, threads, play_seeded_game);
+        let serial = seeds
+            .iter()
+            .map(|&it| play_seeded_game(it))
+            .collect::<Vec<_>>();
+        assert_eq!(parallel, serial);
+        // The games actually depend on their seeds.
+        assert_ne!(parallel[0], parallel[1]);
+    }
+}
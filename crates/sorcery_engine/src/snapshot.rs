@@ -0,0 +1,264 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    actions::GameAction, core::PlayerId, error::EngineError, game::Game, setup::GameBuilder,
+};
+
+/// A saved game that can be serialized and restored later, e.g. to resume a game after the server
+/// restarted or to reproduce a bug report. Rather than the state of every object, the snapshot
+/// keeps how the game was set up, including its seed, and the inputs the game received since.
+/// Restoring the snapshot replays them, which results in the very same game since everything
+/// random in a game is seeded, down to the ids and timestamps of its objects.
+///
+/// ```
+/// use sorcery_engine::prelude::*;
+///
+/// let deck = || Deck::parse("20 Forest\n20 Llanowar Elves");
+/// let (game, _) = GameBuilder::new()
+///     .player("Alice", deck()?)
+///     .player("Bob", deck()?)
+///     .build()?;
+/// let json = serde_json::to_string(&game.snapshot()?).expect("Could not save the game.");
+/// let snapshot: GameSnapshot = serde_json::from_str(&json).expect("Could not load the game.");
+/// let restored = snapshot.restore()?;
+/// # Ok::<(), EngineError>(())
+/// ```
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GameSnapshot {
+    /// The builder the game was set up with, which always has a seed.
+    pub(crate) setup: GameBuilder,
+    pub(crate) inputs: Vec<GameInput>,
+}
+
+impl GameSnapshot {
+    /// Sets up the game again and replays all inputs it received.
+    ///
+    /// # Errors
+    /// Fails if the game can't be set up or an input is rejected, which only happens if the
+    /// snapshot was modified or saved by a version of the engine that plays by different rules.
+    pub fn restore(&self) -> Result<Game, EngineError> {
+        let (mut game, _) = self.setup.clone().build()?;
+        for input in &self.inputs {
+            match input {
+                GameInput::Action { player, action } => game.apply(*player, action.clone())?,
+                GameInput::AutoChooseTrivial { player, enabled } => {
+                    game.set_auto_choose_trivial(*player, *enabled)?;
+                }
+            }
+        }
+        Ok(game)
+    }
+}
+
+/// Something a game received through its public interface after it was set up, in the order the
+/// game received it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum GameInput {
+    /// An action a player took successfully, see [`Game::apply`].
+    Action {
+        player: PlayerId,
+        action: GameAction,
+    },
+    /// See [`Game::set_auto_choose_trivial`].
+    AutoChooseTrivial { player: PlayerId, enabled: bool },
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{
+        agents::{GreedyAgent, PlayerAgent},
+        consistency::StateTrace,
+        core::{Deck, Zone},
+        events::GameEvent,
+    };
+
+    /// Builds a seeded game between two players with the same deck.
+    fn new_game() -> Game {
+        let deck = || {
+            Deck::parse("24 Plains\n18 Soulmender\n18 Steppe Lynx")
+                .expect("Could not parse the deck.")
+        };
+        let (game, _) = GameBuilder::new()
+            .seed(7)
+            .player("Alice", deck())
+            .player("Bob", deck())
+            .build()
+            .expect("Could not build the game.");
+        game
+    }
+
+    /// Lets a greedy agent take the next action of the game and returns whether there was one.
+    fn take_action(game: &mut Game) -> bool {
+        let Some(player) = game.player_to_act().expect("Could not advance the game.") else {
+            return false;
+        };
+        let view = game.view_for(player).expect("Could not view the game.");
+        let actions = game
+            .legal_actions(player)
+            .expect("Could not list the actions.");
+        let action = GreedyAgent.choose(&view, &actions);
+        game.apply(player, action)
+            .expect("Could not apply the action.");
+        true
+    }
+
+    /// Saves the game as JSON and restores it from there.
+    fn save_and_restore(game: &Game) -> Game {
+        let json = serde_json::to_string(&game.snapshot().expect("Could not save the game."))
+            .expect("Could not serialize the snapshot.");
+        serde_json::from_str::<GameSnapshot>(&json)
+            .expect("Could not deserialize the snapshot.")
+            .restore()
+            .expect("Could not restore the game.")
+    }
+
+    #[test]
+    fn saved_games_are_restored_in_the_same_state() {
+        let mut game = new_game();
+        for _ in 0..60 {
+            assert!(take_action(&mut game));
+        }
+
+        let restored = save_and_restore(&game);
+        assert_eq!(restored.state_hash(), game.state_hash());
+        assert_eq!(restored.spectator_view(true), game.spectator_view(true));
+        assert_eq!(restored.events(), game.events());
+        assert_eq!(
+            restored.snapshot().map(|it| it.inputs),
+            game.snapshot().map(|it| it.inputs)
+        );
+        for object in game.spectator_view(false).objects {
+            let timestamp = |game: &Game| game.object(object.id).and_then(|it| game.timestamp(it));
+            assert_eq!(timestamp(&restored), timestamp(&game));
+        }
+    }
+
+    #[test]
+    fn restored_games_resolve_object_ids_and_resume_where_they_were_saved() {
+        let mut game = new_game();
+        let died = |game: &Game| {
+            game.events().iter().find_map(|it| match it {
+                GameEvent::ZoneChanged {
+                    object,
+                    from: Some(Zone::Battlefield),
+                    to: Zone::Graveyard(_),
+                    ..
+                } => object.id,
+                _ => None,
+            })
+        };
+        while died(&game).is_none() {
+            assert!(take_action(&mut game), "No creature died during the game.");
+        }
+        let card = died(&game).expect("Could not find the creature that died.");
+        let dead = game
+            .object(card)
+            .and_then(|it| game.previous_id(it))
+            .expect("Could not find the id of the creature.");
+
+        let mut restored = save_and_restore(&game);
+        // The id the creature had on the battlefield resolves to its last known information only,
+        // while the card in the graveyard is a new object.
+        assert!(restored.object(card).is_some());
+        assert_eq!(restored.object(dead), None);
+        assert!(restored.last_known_information(dead).is_some());
+        assert_eq!(
+            restored.last_known_information(dead),
+            game.last_known_information(dead)
+        );
+        for player in game.players().iter().map(|it| it.id).collect::<Vec<_>>() {
+            let view = game.view_for(player).expect("Could not view the game.");
+            assert_eq!(restored.view_for(player), Ok(view.clone()));
+            assert!(view
+                .objects
+                .iter()
+                .all(|it| restored.object(it.id).is_some()));
+        }
+
+        // Both games take the same actions until the end.
+        while let Some(player) = game.player_to_act().expect("Could not advance the game.") {
+            assert_eq!(restored.player_to_act(), Ok(Some(player)));
+            let view = game.view_for(player).expect("Could not view the game.");
+            let actions = game
+                .legal_actions(player)
+                .expect("Could not list the actions.");
+            let action = GreedyAgent.choose(&view, &actions);
+            game.apply(player, action.clone())
+                .expect("Could not apply the action.");
+            restored
+                .apply(player, action)
+                .expect("Could not apply the action.");
+        }
+        assert_eq!(restored.player_to_act(), Ok(None));
+        assert!(game.outcome().is_some());
+        assert_eq!(restored.outcome(), game.outcome());
+        assert_eq!(restored.state_hash(), game.state_hash());
+    }
+
+    #[test]
+    fn restored_games_play_out_the_same_way_as_the_saved_one() {
+        let mut game = new_game();
+        for _ in 0..40 {
+            assert!(take_action(&mut game));
+        }
+        let json = serde_json::to_string(&game.snapshot().expect("Could not save the game."))
+            .expect("Could not serialize the snapshot.");
+        let play_out = |mut game: Game| {
+            let mut trace = StateTrace::default();
+            while take_action(&mut game) {
+                trace.record(&game);
+            }
+            trace
+        };
+
+        let expected = play_out(game);
+        assert!(!expected.hashes().is_empty());
+        // Every restored copy of the game takes the same course, so restoring a game doesn't
+        // depend on anything but the snapshot.
+        for _ in 0..2 {
+            let restored = serde_json::from_str::<GameSnapshot>(&json)
+                .expect("Could not deserialize the snapshot.")
+                .restore()
+                .expect("Could not restore the game.");
+            assert_eq!(play_out(restored).first_divergence(expected.hashes()), None);
+        }
+    }
+
+    #[test]
+    fn restored_games_keep_their_statistics_and_timestamps() {
+        let mut game = new_game();
+        for _ in 0..60 {
+            assert!(take_action(&mut game));
+        }
+        let timestamps = |game: &Game| {
+            game.spectator_view(true)
+                .objects
+                .iter()
+                .map(|it| game.object(it.id).and_then(|it| game.timestamp(it)))
+                .collect::<Vec<_>>()
+        };
+
+        let mut restored = save_and_restore(&game);
+        assert_eq!(restored.stats(), game.stats());
+        assert!(game.stats().turns() > 1);
+        // The objects that enter a zone after the game was restored are handed out the same
+        // timestamps as well.
+        for _ in 0..20 {
+            assert!(take_action(&mut game));
+            assert!(take_action(&mut restored));
+            assert_eq!(timestamps(&restored), timestamps(&game));
+        }
+        assert_eq!(restored.stats(), game.stats());
+    }
+
+    #[test]
+    fn only_games_set_up_by_a_builder_can_be_saved() {
+        assert!(matches!(
+            Game::new(2).snapshot(),
+            Err(EngineError::IllegalAction(_))
+        ));
+    }
+}
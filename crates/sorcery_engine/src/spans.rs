@@ -0,0 +1,208 @@
+//! Spans of the `tracing` crate that structure the diagnostics of a game by turn, phase, step and
+//! action. Spans are created at the debug and trace levels, so nothing is emitted at the info level
+//! unless a subscriber asks for more.
+
+use tracing::{span::EnteredSpan, Span};
+
+use crate::{
+    core::PlayerId,
+    turn::{Phase, Step},
+};
+
+/// The spans of the current turn, phase and step, which are the parents of the spans of the
+/// actions taken during the step.
+pub(crate) struct TurnSpans {
+    turn: Span,
+    phase: Option<(Phase, Span)>,
+    step: Span,
+}
+
+impl Default for TurnSpans {
+    fn default() -> Self {
+        Self {
+            turn: Span::none(),
+            phase: None,
+            step: Span::none(),
+        }
+    }
+}
+
+impl TurnSpans {
+    /// Starts the span of the turn with the specified number, which ends the spans of the previous
+    /// turn.
+    pub(crate) fn begin_turn(&mut self, number: u64, active_player: PlayerId) {
+        self.turn = tracing::debug_span!(
+            parent: None,
+            "turn",
+            number,
+            active_player = active_player.0
+        );
+        self.phase = None;
+        self.step = Span::none();
+    }
+
+    /// Starts the span of the step, and the span of its phase unless it is part of the phase of the
+    /// previous step, and enters it until the returned guard is dropped.
+    pub(crate) fn begin_step(&mut self, step: Step) -> EnteredSpan {
+        let phase = step.phase();
+        if self.phase.as_ref().map(|(it, _)| *it) != Some(phase) {
+            let span = tracing::debug_span!(parent: &self.turn, "phase", phase = ?phase);
+            self.phase = Some((phase, span));
+        }
+        let parent = self.phase.as_ref().map_or(&self.turn, |(_, span)| span);
+        self.step = tracing::debug_span!(parent: parent, "step", step = ?step);
+        self.step.clone().entered()
+    }
+
+    /// Enters a span for an action of the specified player during the current step until the
+    /// returned guard is dropped.
+    pub(crate) fn action(&self, action: &'static str, player: PlayerId) -> EnteredSpan {
+        tracing::trace_span!(parent: &self.step, "action", action, player = player.0).entered()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fmt::{self, Write},
+        sync::{Arc, Mutex},
+    };
+
+    use pretty_assertions::assert_eq;
+    use tracing::{
+        field::{Field, Visit},
+        span::{Attributes, Id, Record},
+        Event, Metadata, Subscriber,
+    };
+
+    use crate::{core::Zone, game::find_card_by_name, prelude::*, turn::Step};
+
+    /// A span described by its name and fields along with the index of its parent, if any.
+    type RecordedSpan = (String, Option<usize>);
+
+    /// Records the spans created while it is the default subscriber of the current thread.
+    #[derive(Clone, Default)]
+    struct SpanRecorder {
+        spans: Arc<Mutex<Vec<RecordedSpan>>>,
+        /// The spans that are currently entered, innermost last.
+        entered: Arc<Mutex<Vec<usize>>>,
+    }
+
+    /// Writes the fields of a span after its name.
+    struct FieldWriter<'a>(&'a mut String);
+
+    impl Visit for FieldWriter<'_> {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            let _ = write!(self.0, " {}={value}", field.name());
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            let _ = write!(self.0, " {}={value:?}", field.name());
+        }
+    }
+
+    impl Subscriber for SpanRecorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, attributes: &Attributes<'_>) -> Id {
+            let parent = match attributes.parent() {
+                Some(parent) => Some(parent.into_u64() as usize - 1),
+                None if attributes.is_contextual() => self
+                    .entered
+                    .lock()
+                    .expect("Could not lock the entered spans.")
+                    .last()
+                    .copied(),
+                None => None,
+            };
+            let mut name = attributes.metadata().name().to_string();
+            attributes.record(&mut FieldWriter(&mut name));
+            let mut spans = self.spans.lock().expect("Could not lock the spans.");
+            spans.push((name, parent));
+            Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, _: &Event<'_>) {}
+
+        fn enter(&self, span: &Id) {
+            let index = span.into_u64() as usize - 1;
+            self.entered
+                .lock()
+                .expect("Could not lock the entered spans.")
+                .push(index);
+        }
+
+        fn exit(&self, _: &Id) {
+            self.entered
+                .lock()
+                .expect("Could not lock the entered spans.")
+                .pop();
+        }
+    }
+
+    #[test]
+    fn turns_phases_steps_and_actions_are_nested_spans() {
+        let deck = || Deck::parse("20 Forest").expect("Could not parse the deck.");
+        let (mut game, players) = GameBuilder::new()
+            .seed(0)
+            .player("Alice", deck())
+            .player("Bob", deck())
+            .starting_player(0)
+            .build()
+            .expect("Could not build the game.");
+        let forest = game
+            .spawn_object(
+                find_card_by_name("Forest").expect("Could not find the card."),
+                players[1],
+                Zone::Hand(players[1]),
+            )
+            .expect("Could not spawn the object.");
+
+        let recorder = SpanRecorder::default();
+        tracing::subscriber::with_default(recorder.clone(), || {
+            game.next_turn().expect("Could not begin the next turn.");
+            while game.turn().step != Step::PrecombatMain {
+                game.advance_step().expect("Could not advance the step.");
+            }
+            game.play_land(players[1], forest)
+                .expect("Could not play the land.");
+        });
+
+        let spans = recorder
+            .spans
+            .lock()
+            .expect("Could not lock the spans.")
+            .clone();
+        let parent = |index: Option<usize>| index.map(|it| spans[it].0.as_str());
+        assert_eq!(
+            spans
+                .iter()
+                .map(|(name, index)| (name.as_str(), parent(*index)))
+                .collect::<Vec<_>>(),
+            vec![
+                ("turn number=2 active_player=1", None),
+                (
+                    "phase phase=Beginning",
+                    Some("turn number=2 active_player=1")
+                ),
+                ("step step=Upkeep", Some("phase phase=Beginning")),
+                ("step step=Draw", Some("phase phase=Beginning")),
+                (
+                    "phase phase=PrecombatMain",
+                    Some("turn number=2 active_player=1")
+                ),
+                ("step step=PrecombatMain", Some("phase phase=PrecombatMain")),
+                (
+                    "action action=play_land player=1",
+                    Some("step step=PrecombatMain")
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use serde::Serialize;
+
+use crate::{
+    core::{CounterKind, Designation, GameOutcome, Mana, ObjectId, PlayerId, PlayerInfo, Zone},
+    events::GameEvent,
+    turn::Step,
+};
+
+/// The whole state of a game as seen by an omniscient spectator, e.g. to render a broadcast overlay
+/// or a debugger. Unlike the events revealed to players, nothing is hidden except for the order of
+/// the libraries, which is only included on request.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub(crate) struct SpectatorView {
+    /// The number of events that had happened when the view was taken, which is the index of the
+    /// first event a delta to a later view contains.
+    pub(crate) event_index: usize,
+    /// Whether the cards in the libraries are included.
+    pub(crate) libraries_revealed: bool,
+    pub(crate) turn: u64,
+    pub(crate) active_player: PlayerId,
+    pub(crate) step: Step,
+    pub(crate) players: Vec<PlayerStatus>,
+    /// All objects in the game ordered by their ids.
+    pub(crate) objects: Vec<ObjectStatus>,
+}
+
+/// The state of a game as seen by one of its players, e.g. to render their client or to let an
+/// agent decide on their behalf. Unlike a [`SpectatorView`], it leaves out the libraries, the cards
+/// in the hands of the other players and the identity of cards the other players exiled face down.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct PlayerView {
+    /// The player the game is seen by.
+    pub player: PlayerId,
+    /// The number of events that had happened when the view was taken.
+    pub event_index: usize,
+    pub turn: u64,
+    pub active_player: PlayerId,
+    pub step: Step,
+    /// How the game ended, if it has.
+    pub outcome: Option<GameOutcome>,
+    pub players: Vec<PlayerStatus>,
+    /// The objects the player can see ordered by their ids.
+    pub objects: Vec<ObjectStatus>,
+    /// The choice the player faces, if they have to make one.
+    pub choice: Option<ChoiceStatus>,
+}
+
+impl PlayerView {
+    /// Returns the objects in the specified zone ordered by their ids.
+    #[must_use]
+    pub fn objects_in(&self, zone: Zone) -> impl DoubleEndedIterator<Item = &ObjectStatus> {
+        self.objects.iter().filter(move |it| it.zone == zone)
+    }
+}
+
+/// A choice the player of a [`PlayerView`] faces, which they make with
+/// [`GameAction::Choose`](crate::actions::GameAction::Choose) by the index of an option.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ChoiceStatus {
+    pub prompt: String,
+    pub options: Vec<String>,
+}
+
+/// The state of a player within a [`SpectatorView`] or a [`PlayerView`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct PlayerStatus {
+    pub id: PlayerId,
+    #[serde(flatten)]
+    pub info: PlayerInfo,
+    pub life: i64,
+    pub counters: Vec<(CounterKind, u64)>,
+    pub mana_pool: Vec<Mana>,
+    pub hand_size: usize,
+    pub library_size: usize,
+    /// The cards in the library from top to bottom, if the libraries are revealed.
+    pub library: Option<Vec<ObjectId>>,
+    pub designations: Vec<Designation>,
+    pub has_left: bool,
+}
+
+/// The state of an object within a [`SpectatorView`] or a [`PlayerView`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ObjectStatus {
+    pub id: ObjectId,
+    pub name: String,
+    /// The mana cost as printed on the card, e.g. `{1}{W}`, unless it has none.
+    pub mana_cost: Option<String>,
+    pub mana_value: u64,
+    pub power_toughness: Option<(i64, i64)>,
+    pub owner: PlayerId,
+    pub controller: Option<PlayerId>,
+    pub zone: Zone,
+    pub token: bool,
+    pub statuses: Vec<ObjectState>,
+    pub marked_damage: u64,
+    pub counters: Vec<(CounterKind, u64)>,
+}
+
+/// A state of a permanent that is shown to spectators. Permanents that are untapped, phased in
+/// and so on have none of these.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum ObjectState {
+    Tapped,
+    PhasedOut,
+    SummoningSick,
+    Attacking,
+    Blocking,
+    /// A card exiled face down, whose identity spectators can see nonetheless.
+    FaceDown,
+}
+
+/// The changes between two spectator views of the same game, which keeps payloads small once a
+/// spectator has received a full view.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub(crate) struct SpectatorDelta {
+    /// The number of events that had happened when the newer view was taken.
+    pub(crate) event_index: usize,
+    /// The events that happened in between.
+    pub(crate) events: Vec<GameEvent>,
+    pub(crate) turn: u64,
+    pub(crate) active_player: PlayerId,
+    pub(crate) step: Step,
+    /// The players whose state changed.
+    pub(crate) players: Vec<PlayerStatus>,
+    /// The objects that are new or whose state changed.
+    pub(crate) objects: Vec<ObjectStatus>,
+    /// The ids of the objects that no longer exist, most notably because they changed zones and
+    /// became new objects.
+    pub(crate) removed: Vec<ObjectId>,
+}
+
+impl SpectatorView {
+    /// Returns the changes from this view to the newer one along with the events that happened in
+    /// between.
+    pub(crate) fn delta_to(&self, newer: &SpectatorView, events: Vec<GameEvent>) -> SpectatorDelta {
+        let players = newer
+            .players
+            .iter()
+            .filter(|it| !self.players.contains(it))
+            .cloned()
+            .collect();
+        let objects = newer
+            .objects
+            .iter()
+            .filter(|it| self.object(it.id) != Some(*it))
+            .cloned()
+            .collect();
+        let removed = self
+            .objects
+            .iter()
+            .filter(|it| newer.object(it.id).is_none())
+            .map(|it| it.id)
+            .collect();
+        SpectatorDelta {
+            event_index: newer.event_index,
+            events,
+            turn: newer.turn,
+            active_player: newer.active_player,
+            step: newer.step,
+            players,
+            objects,
+            removed,
+        }
+    }
+
+    /// Updates the view with the changes of the delta.
+    pub(crate) fn apply(&mut self, delta: SpectatorDelta) {
+        self.event_index = delta.event_index;
+        self.turn = delta.turn;
+        self.active_player = delta.active_player;
+        self.step = delta.step;
+        for player in delta.players {
+            match self.players.iter_mut().find(|it| it.id == player.id) {
+                Some(existing) => *existing = player,
+                None => self.players.push(player),
+            }
+        }
+        self.objects.retain(|it| !delta.removed.contains(&it.id));
+        for object in delta.objects {
+            match self.objects.binary_search_by_key(&object.id, |it| it.id) {
+                Ok(index) => self.objects[index] = object,
+                Err(index) => self.objects.insert(index, object),
+            }
+        }
+    }
+
+    /// Returns the state of the object with the specified id, if it exists.
+    fn object(&self, id: ObjectId) -> Option<&ObjectStatus> {
+        self.objects
+            .binary_search_by_key(&id, |it| it.id)
+            .ok()
+            .map(|it| &self.objects[it])
+    }
+}
@@ -0,0 +1,104 @@
+use indexmap::{IndexMap, IndexSet};
+use serde::Serialize;
+
+use crate::core::{CardType, PlayerId};
+
+/// Counters about the whole game that are kept up to date as it is played, e.g. to balance cards or
+/// to evaluate bots without going through the events.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub(crate) struct GameStats {
+    turns_taken: IndexMap<PlayerId, u64>,
+    cards_drawn: IndexMap<PlayerId, u64>,
+    /// The damage dealt by the sources each player controlled, or owned if they had no controller.
+    damage_dealt: IndexMap<PlayerId, u64>,
+    /// The number of spells cast for each of their card types, so an artifact creature spell counts
+    /// for both.
+    spells_cast: IndexMap<CardType, u64>,
+    mana_spent: IndexMap<PlayerId, u64>,
+    /// The sum of the sizes of the hand of each player as each of their turns began and the number
+    /// of sizes that were summed up.
+    hand_sizes: IndexMap<PlayerId, (u64, u64)>,
+    /// 117.3d If a player has priority and chooses not to take any actions, that player passes.
+    ///        [...]
+    priority_passes: u64,
+}
+
+impl GameStats {
+    /// Returns the number of turns the specified player has taken, including the current one.
+    pub(crate) fn turns_taken_by(&self, player: PlayerId) -> u64 {
+        self.turns_taken.get(&player).copied().unwrap_or_default()
+    }
+
+    /// Returns the total number of turns taken by all players.
+    pub(crate) fn turns(&self) -> u64 {
+        self.turns_taken.values().sum()
+    }
+
+    /// Returns the number of cards the specified player has drawn, including their opening hand.
+    pub(crate) fn cards_drawn_by(&self, player: PlayerId) -> u64 {
+        self.cards_drawn.get(&player).copied().unwrap_or_default()
+    }
+
+    /// Returns the total amount of damage dealt by sources of the specified player.
+    pub(crate) fn damage_dealt_by(&self, player: PlayerId) -> u64 {
+        self.damage_dealt.get(&player).copied().unwrap_or_default()
+    }
+
+    /// Returns the number of spells of the specified card type that have been cast.
+    pub(crate) fn spells_cast(&self, card_type: CardType) -> u64 {
+        self.spells_cast
+            .get(&card_type)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns the amount of mana the specified player has spent to pay costs.
+    pub(crate) fn mana_spent_by(&self, player: PlayerId) -> u64 {
+        self.mana_spent.get(&player).copied().unwrap_or_default()
+    }
+
+    /// Returns the average number of cards in the hand of the specified player as their turns
+    /// began, or `None` if none of their turns has begun after the first one.
+    pub(crate) fn average_hand_size(&self, player: PlayerId) -> Option<f64> {
+        let &(total, samples) = self.hand_sizes.get(&player)?;
+        Some(total as f64 / samples as f64)
+    }
+
+    /// Returns the number of times a player passed priority, which measures how long the game
+    /// lasted independently of how long the players took to decide.
+    pub(crate) fn priority_passes(&self) -> u64 {
+        self.priority_passes
+    }
+
+    pub(crate) fn record_turn(&mut self, player: PlayerId) {
+        *self.turns_taken.entry(player).or_default() += 1;
+    }
+
+    pub(crate) fn record_card_drawn(&mut self, player: PlayerId) {
+        *self.cards_drawn.entry(player).or_default() += 1;
+    }
+
+    pub(crate) fn record_damage_dealt(&mut self, player: PlayerId, amount: u64) {
+        *self.damage_dealt.entry(player).or_default() += amount;
+    }
+
+    pub(crate) fn record_spell_cast(&mut self, card_types: &IndexSet<CardType>) {
+        for &card_type in card_types {
+            *self.spells_cast.entry(card_type).or_default() += 1;
+        }
+    }
+
+    pub(crate) fn record_mana_spent(&mut self, player: PlayerId, amount: u64) {
+        *self.mana_spent.entry(player).or_default() += amount;
+    }
+
+    pub(crate) fn record_hand_size(&mut self, player: PlayerId, size: u64) {
+        let (total, samples) = self.hand_sizes.entry(player).or_default();
+        *total += size;
+        *samples += 1;
+    }
+
+    pub(crate) fn record_priority_passes(&mut self, amount: u64) {
+        self.priority_passes += amount;
+    }
+}
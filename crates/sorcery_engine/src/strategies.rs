@@ -0,0 +1,300 @@
+use std::sync::Arc;
+
+use proptest::{
+    collection::{btree_set, hash_map, vec},
+    option,
+    prelude::*,
+    sample::select,
+};
+
+use crate::core::{
+    ArtifactType, BasicLandType, Card, CardType, CollectorNumber, Color, ColorIdentity, ColorPair,
+    CreatureType, Deck, EnchantmentType, ExpansionSymbol, Format, LandType, Legality, Loyalty,
+    ManaCost, ManaSymbol, Name, PlaneswalkerType, PtCharacteristic, PtValue, Rarity, RulesText,
+    SpellType, Subtype, Supertype, TypeLine,
+};
+
+/// The names of generated cards. Only a few names are used so that generated decks contain
+/// several cards sharing a name, as real decks do.
+const NAMES: [&str; 6] = [
+    "Llanowar Elves",
+    "Lightning Bolt",
+    "Black Lotus",
+    "Forest",
+    "Jace, the Mind Sculptor",
+    "Thran Dynamo",
+];
+
+/// Returns a strategy generating any of the five colors.
+pub(crate) fn color() -> impl Strategy<Value = Color> {
+    select(Color::ALL.to_vec())
+}
+
+/// Returns a strategy generating the colors of an object, e.g. of a color indicator.
+pub(crate) fn color_identity() -> impl Strategy<Value = ColorIdentity> {
+    btree_set(color(), 1..=5).prop_map(|colors| match colors.len() {
+        1 => ColorIdentity::Monocolored(colors.into_iter().next().expect("The set is not empty.")),
+        _ => ColorIdentity::Multicolored(colors),
+    })
+}
+
+/// Returns a strategy generating any of the supported mana symbols. Hybrid symbols always consist
+/// of two different colors in the order they are printed in.
+pub(crate) fn mana_symbol() -> impl Strategy<Value = ManaSymbol> {
+    prop_oneof![
+        color().prop_map(ManaSymbol::Colored),
+        (0..=16_u64).prop_map(ManaSymbol::Generic),
+        Just(ManaSymbol::Variable),
+        Just(ManaSymbol::Colorless),
+        select(ColorPair::ALL.to_vec()).prop_map(|pair| {
+            let [first, second] = pair.colors();
+            ManaSymbol::Hybrid(first, second)
+        }),
+        color().prop_map(ManaSymbol::MonocoloredHybrid),
+    ]
+}
+
+/// Returns a strategy generating mana costs of up to seven symbols in any order, including empty
+/// mana costs.
+pub fn mana_cost() -> impl Strategy<Value = ManaCost> {
+    vec(mana_symbol(), 0..8).prop_map(ManaCost)
+}
+
+/// Returns a strategy generating type lines of the common card types whose subtypes are each
+/// correlated to one of their card types, see rule 205.3c.
+pub fn type_line() -> impl Strategy<Value = TypeLine> {
+    let creature = (
+        any::<bool>(),
+        vec(
+            select(vec![
+                CreatureType::Beast,
+                CreatureType::Druid,
+                CreatureType::Elf,
+                CreatureType::Goblin,
+                CreatureType::Human,
+                CreatureType::Soldier,
+                CreatureType::Wizard,
+            ]),
+            0..3,
+        ),
+    )
+        .prop_map(|(is_artifact, creature_types)| {
+            let mut card_type = vec![CardType::Creature];
+            if is_artifact {
+                card_type.insert(0, CardType::Artifact);
+            }
+            let subtype = creature_types.into_iter().map(Subtype::Creature);
+            type_line_of(&card_type, subtype, [])
+        });
+    let land = prop_oneof![
+        select(vec![
+            BasicLandType::Forest,
+            BasicLandType::Island,
+            BasicLandType::Mountain,
+            BasicLandType::Plains,
+            BasicLandType::Swamp,
+        ])
+        .prop_map(|it| {
+            let subtype = [Subtype::Land(LandType::Basic(it))];
+            type_line_of(&[CardType::Land], subtype, [Supertype::Basic])
+        }),
+        option::of(select(vec![LandType::Desert, LandType::Gate]))
+            .prop_map(|it| { type_line_of(&[CardType::Land], it.map(Subtype::Land), []) }),
+    ];
+    let spell = (
+        select(vec![CardType::Instant, CardType::Sorcery]),
+        option::of(select(vec![SpellType::Adventure, SpellType::Arcane])),
+    )
+        .prop_map(|(card_type, spell_type)| {
+            type_line_of(&[card_type], spell_type.map(Subtype::Spell), [])
+        });
+    let artifact = option::of(select(vec![
+        ArtifactType::Equipment,
+        ArtifactType::Treasure,
+    ]))
+    .prop_map(|it| type_line_of(&[CardType::Artifact], it.map(Subtype::Artifact), []));
+    let enchantment = option::of(select(vec![EnchantmentType::Aura, EnchantmentType::Saga]))
+        .prop_map(|it| type_line_of(&[CardType::Enchantment], it.map(Subtype::Enchantment), []));
+    // Planeswalkers are printed as legendary, though nothing in the rules requires it.
+    let planeswalker = select(vec![
+        PlaneswalkerType::Ajani,
+        PlaneswalkerType::Chandra,
+        PlaneswalkerType::Jace,
+        PlaneswalkerType::Liliana,
+    ])
+    .prop_map(|it| {
+        type_line_of(
+            &[CardType::Planeswalker],
+            [Subtype::Planeswalker(it)],
+            [Supertype::Legendary],
+        )
+    });
+
+    (
+        prop_oneof![creature, land, spell, artifact, enchantment, planeswalker],
+        option::weighted(0.2, select(vec![Supertype::Legendary, Supertype::Snow])),
+    )
+        .prop_map(|(mut type_line, supertype)| {
+            type_line.supertype.extend(supertype);
+            type_line
+        })
+}
+
+/// Returns a type line with the specified types.
+fn type_line_of(
+    card_type: &[CardType],
+    subtype: impl IntoIterator<Item = Subtype>,
+    supertype: impl IntoIterator<Item = Supertype>,
+) -> TypeLine {
+    TypeLine {
+        card_type: card_type.iter().copied().collect(),
+        subtype: subtype.into_iter().collect(),
+        supertype: supertype.into_iter().collect(),
+    }
+}
+
+/// Returns a strategy generating one of the formats legalities are tracked for.
+pub(crate) fn format() -> impl Strategy<Value = Format> {
+    select(vec![
+        Format::Standard,
+        Format::Pioneer,
+        Format::Modern,
+        Format::Legacy,
+        Format::Vintage,
+        Format::Commander,
+        Format::Pauper,
+    ])
+}
+
+/// Returns a strategy generating the legality of a card in a format.
+pub(crate) fn legality() -> impl Strategy<Value = Legality> {
+    select(vec![
+        Legality::Legal,
+        Legality::Banned,
+        Legality::Restricted,
+        Legality::NotLegal,
+    ])
+}
+
+/// Returns a strategy generating cards without abilities that have the characteristics their type
+/// line calls for: lands have no mana cost, creatures have power and toughness and planeswalkers
+/// have loyalty. Cards without a mana cost may have a color indicator instead.
+pub fn card() -> impl Strategy<Value = Card> {
+    (
+        select(NAMES.to_vec()),
+        type_line(),
+        option::weighted(0.9, mana_cost()),
+        option::of(color_identity()),
+        select(vec![
+            Rarity::Common,
+            Rarity::Uncommon,
+            Rarity::Rare,
+            Rarity::MythicRare,
+        ]),
+        (0..=8_i64, 1..=8_i64, 1..=7_u64),
+        1..=300_u64,
+        hash_map(format(), legality(), 0..3),
+    )
+        .prop_map(
+            |(name, type_line, mana_cost, color_indicator, rarity, stats, number, legalities)| {
+                let (power, toughness, loyalty) = stats;
+                let is = |card_type| type_line.card_type.contains(&card_type);
+                let mana_cost = mana_cost.filter(|_| !is(CardType::Land));
+                let color_indicator = color_indicator.filter(|_| mana_cost.is_none());
+                let pt = is(CardType::Creature).then_some(PtCharacteristic {
+                    power: PtValue::Fixed(power),
+                    toughness: PtValue::Fixed(toughness),
+                });
+                let loyalty = is(CardType::Planeswalker).then_some(Loyalty(loyalty));
+                Card {
+                    name: Name(name.into()),
+                    mana_cost,
+                    color_indicator,
+                    type_line,
+                    expansion_symbol: ExpansionSymbol {
+                        set: "TEST".into(),
+                        rarity,
+                    },
+                    rules_text: RulesText(String::new()),
+                    pt,
+                    loyalty,
+                    collector_number: CollectorNumber(number),
+                    abilities: Vec::new(),
+                    legalities,
+                    localized_names: [].into(),
+                    metadata: None,
+                    back_face: None,
+                }
+            },
+        )
+}
+
+/// Returns a strategy generating decks of up to 80 cards that contain up to seven different cards
+/// in any number of copies.
+pub fn deck() -> impl Strategy<Value = Deck> {
+    vec(card().prop_map(Arc::new), 1..8)
+        .prop_flat_map(|cards| vec(select(cards), 0..=80))
+        .prop_map(Deck::from_cards)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn mana_costs_round_trip_through_their_display(cost in mana_cost()) {
+            prop_assert_eq!(ManaCost::parse(&cost.to_string()), Some(cost));
+        }
+
+        #[test]
+        fn mana_value_is_the_sum_of_its_symbols(first in mana_cost(), second in mana_cost()) {
+            let symbols = first
+                .0
+                .iter()
+                .map(|it| ManaCost(vec![*it]).mana_value())
+                .sum::<u64>();
+            prop_assert_eq!(first.mana_value(), symbols);
+
+            let combined = ManaCost([first.0.clone(), second.0.clone()].concat());
+            prop_assert_eq!(
+                combined.mana_value(),
+                first.mana_value() + second.mana_value()
+            );
+        }
+
+        #[test]
+        fn cards_are_the_colors_of_their_mana_symbols(card in card()) {
+            let identity = card.color();
+            for color in Color::ALL {
+                let expected = match card.color_indicator {
+                    Some(ref color_indicator) => color_indicator.contains(color),
+                    None => card.mana_cost.iter().flat_map(|it| &it.0).any(|it| match *it {
+                        ManaSymbol::Colored(it) | ManaSymbol::MonocoloredHybrid(it) => it == color,
+                        ManaSymbol::Hybrid(first, second) => first == color || second == color,
+                        ManaSymbol::Generic(_) | ManaSymbol::Variable | ManaSymbol::Colorless => {
+                            false
+                        }
+                    }),
+                };
+                prop_assert_eq!(identity.contains(color), expected);
+            }
+            let colors = identity.colors().len();
+            prop_assert_eq!(identity == ColorIdentity::Colorless, colors == 0);
+            prop_assert_eq!(matches!(identity, ColorIdentity::Monocolored(_)), colors == 1);
+        }
+
+        #[test]
+        fn removing_a_card_keeps_a_valid_deck_valid(deck in deck(), format in format()) {
+            // Validation doesn't enforce a minimum deck size, which is the only rule removing a
+            // card could break.
+            if deck.validate(format).is_ok() {
+                for index in 0..deck.cards().len() {
+                    let mut cards = deck.cards().to_vec();
+                    cards.remove(index);
+                    prop_assert!(Deck::from_cards(cards).validate(format).is_ok());
+                }
+            }
+        }
+    }
+}
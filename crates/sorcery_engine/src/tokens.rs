@@ -0,0 +1,110 @@
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{
+    abilities::{Ability, ActivatedAbility, ActivationCost, Effect, Quantity},
+    core::{
+        ArtifactType, Card, CardType, CollectorNumber, Color, ExpansionSymbol, Mana, ManaCost,
+        ManaSymbol, Name, Rarity, RulesText, Subtype, TypeLine,
+    },
+};
+
+/// 111.10. Some effects instruct a player to create a predefined token. These effects use the
+///         definition below to determine the characteristics the token is created with. [...]
+///
+/// A token many cards create by name, so that card data only has to refer to it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum PredefinedToken {
+    /// A colorless Clue artifact token with “{2}, Sacrifice this artifact: Draw a card.”
+    Clue,
+    /// A colorless Treasure artifact token with “{T}, Sacrifice this artifact: Add one mana of any
+    /// color.”
+    Treasure,
+    /// A colorless Food artifact token with “{2}, {T}, Sacrifice this artifact: You gain 3 life.”
+    Food,
+    /// A colorless Blood artifact token with “{1}, {T}, Discard a card, Sacrifice this artifact:
+    /// Draw a card.”
+    Blood,
+}
+
+impl PredefinedToken {
+    /// Returns the card whose characteristics the token is created with.
+    ///
+    /// # Remarks
+    /// Treasures have an ability for each color instead of a single ability adding mana of any
+    /// color, because mana abilities resolve immediately without a way to choose the color.
+    pub(crate) fn card(self) -> Arc<Card> {
+        let (artifact_type, rules_text, abilities) = match self {
+            Self::Clue => (
+                ArtifactType::Clue,
+                "{2}, Sacrifice this artifact: Draw a card.",
+                vec![ActivatedAbility {
+                    cost: vec![
+                        ActivationCost::Mana(ManaCost(vec![ManaSymbol::Generic(2)])),
+                        ActivationCost::SacrificeThis,
+                    ],
+                    effects: vec![Effect::DrawCards(Quantity::Fixed(1))],
+                }],
+            ),
+            Self::Treasure => (
+                ArtifactType::Treasure,
+                "{T}, Sacrifice this artifact: Add one mana of any color.",
+                Color::ALL
+                    .into_iter()
+                    .map(|color| ActivatedAbility {
+                        cost: vec![ActivationCost::Tap, ActivationCost::SacrificeThis],
+                        effects: vec![Effect::AddMana(Mana::Monocolored(color))],
+                    })
+                    .collect(),
+            ),
+            Self::Food => (
+                ArtifactType::Food,
+                "{2}, {T}, Sacrifice this artifact: You gain 3 life.",
+                vec![ActivatedAbility {
+                    cost: vec![
+                        ActivationCost::Mana(ManaCost(vec![ManaSymbol::Generic(2)])),
+                        ActivationCost::Tap,
+                        ActivationCost::SacrificeThis,
+                    ],
+                    effects: vec![Effect::GainLife(3)],
+                }],
+            ),
+            Self::Blood => (
+                ArtifactType::Blood,
+                "{1}, {T}, Discard a card, Sacrifice this artifact: Draw a card.",
+                vec![ActivatedAbility {
+                    cost: vec![
+                        ActivationCost::Mana(ManaCost(vec![ManaSymbol::Generic(1)])),
+                        ActivationCost::Tap,
+                        ActivationCost::Discard,
+                        ActivationCost::SacrificeThis,
+                    ],
+                    effects: vec![Effect::DrawCards(Quantity::Fixed(1))],
+                }],
+            ),
+        };
+        Arc::new(Card {
+            name: Name(format!("{artifact_type:?}")),
+            mana_cost: None,
+            color_indicator: None,
+            type_line: TypeLine {
+                card_type: [CardType::Artifact].into(),
+                subtype: [Subtype::Artifact(artifact_type)].into(),
+                supertype: [].into(),
+            },
+            // Tokens aren't printed as part of a set.
+            expansion_symbol: ExpansionSymbol {
+                set: String::new(),
+                rarity: Rarity::Common,
+            },
+            rules_text: RulesText(rules_text.into()),
+            pt: None,
+            loyalty: None,
+            collector_number: CollectorNumber(0),
+            abilities: abilities.into_iter().map(Ability::Activated).collect(),
+            legalities: HashMap::new(),
+            localized_names: HashMap::new(),
+            metadata: None,
+            back_face: None,
+        })
+    }
+}
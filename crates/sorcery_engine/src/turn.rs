@@ -0,0 +1,397 @@
+use std::collections::{HashMap, VecDeque};
+
+use hecs::Entity;
+use indexmap::{IndexMap, IndexSet};
+use serde::Serialize;
+
+use crate::{
+    core::{CardType, ColorIdentity, ObjectId, PlayerId},
+    error::EngineError,
+};
+
+/// 500.1. A turn consists of five phases, in this order: beginning, precombat main, combat,
+///        postcombat main, and ending. Each of these phases takes place every turn, even if nothing
+///        happens during the phase.
+pub(crate) struct TurnState {
+    /// 102.1. [...] The active player is the player whose turn it is.
+    pub(crate) active_player: PlayerId,
+    pub(crate) number: u64,
+    pub(crate) step: Step,
+    pub(crate) tracker: TurnTracker,
+    /// The steps that are still to come this turn, starting with the next one.
+    schedule: VecDeque<Step>,
+}
+
+impl TurnState {
+    /// Creates the state for the turn with the specified number.
+    pub(crate) fn new(active_player: PlayerId, number: u64) -> Self {
+        Self {
+            active_player,
+            number,
+            step: Step::Untap,
+            tracker: TurnTracker::default(),
+            schedule: Step::ALL[1..].iter().copied().collect(),
+        }
+    }
+
+    /// Returns the steps that are still to come this turn, starting with the next one.
+    pub(crate) fn schedule(&self) -> impl Iterator<Item = Step> + '_ {
+        self.schedule.iter().copied()
+    }
+
+    /// Removes the next step from the schedule and returns it, unless the turn is over.
+    pub(crate) fn next_step(&mut self) -> Option<Step> {
+        self.schedule.pop_front()
+    }
+
+    /// 500.8. Some effects can add phases to a turn. They do this by adding the phases directly
+    ///        after the specified phase. If multiple extra phases are created after the same phase,
+    ///        the most recently created phase will occur first.
+    ///
+    /// Adds an additional combat phase followed by an additional main phase directly after the
+    /// current phase.
+    pub(crate) fn add_combat_phase(&mut self) {
+        let current = self.step.phase();
+        let index = self
+            .schedule
+            .iter()
+            .position(|it| it.phase() != current)
+            .unwrap_or(self.schedule.len());
+        let steps = [
+            Step::BeginningOfCombat,
+            Step::DeclareAttackers,
+            Step::DeclareBlockers,
+            Step::CombatDamage,
+            Step::EndOfCombat,
+            Step::PostcombatMain,
+        ];
+        for (offset, step) in steps.into_iter().enumerate() {
+            self.schedule.insert(index + offset, step);
+        }
+    }
+}
+
+/// 500.1. A turn consists of five phases, in this order: beginning, precombat main, combat,
+///        postcombat main, and ending. [...]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Phase {
+    /// 501. Beginning Phase
+    Beginning,
+    /// 505. Main Phase
+    PrecombatMain,
+    /// 506. Combat Phase
+    Combat,
+    /// 505. Main Phase
+    PostcombatMain,
+    /// 512. Ending Phase
+    Ending,
+}
+
+/// 500.7. Some effects can give a player extra turns. They do this by adding the turns directly
+///        after the specified turn. If a player is given multiple extra turns, the extra turns are
+///        added one at a time. If multiple players are given extra turns, the extra turns are added
+///        one at a time, in APNAP order (see rule 101.4). The most recently created turn will be
+///        taken first.
+///
+/// Determines which player takes the next turn, taking extra turns and skipped turns into account
+/// before falling back to the regular turn order.
+#[derive(Clone, Default)]
+pub(crate) struct TurnOrder {
+    /// The extra turns added directly after the current turn, starting with the one taken first.
+    extra_turns: VecDeque<PlayerId>,
+    /// The number of turns each player skips the next time they would take a turn.
+    skipped_turns: HashMap<PlayerId, usize>,
+}
+
+impl TurnOrder {
+    /// Adds an extra turn for the specified player directly after the current turn.
+    pub(crate) fn add_extra_turn(&mut self, player: PlayerId) {
+        self.extra_turns.push_front(player);
+    }
+
+    /// 614.10. An effect that causes a player to skip an event, step, phase, or turn is a
+    ///         replacement effect. [...]
+    ///
+    /// Makes the specified player skip their next turn.
+    pub(crate) fn skip_next_turn(&mut self, player: PlayerId) {
+        *self.skipped_turns.entry(player).or_default() += 1;
+    }
+
+    /// Returns the player taking the turn after the one of the active player and removes the turns
+    /// that have been used up or skipped on the way. Players take their regular turns in the
+    /// order of the specified players.
+    pub(crate) fn advance(
+        &mut self,
+        active_player: PlayerId,
+        players: &[PlayerId],
+    ) -> Result<PlayerId, EngineError> {
+        let mut previous = active_player;
+        loop {
+            let player = if let Some(player) = self.extra_turns.pop_front() {
+                player
+            } else {
+                let index = players
+                    .iter()
+                    .position(|&it| it == previous)
+                    .ok_or(EngineError::UnknownPlayer(previous))?;
+                players[(index + 1) % players.len()]
+            };
+            // 614.10a Anything that would happen during a skipped step, phase, or turn doesn’t
+            //         happen. [...]
+            match self.skipped_turns.get_mut(&player) {
+                Some(skipped) if *skipped > 0 => {
+                    *skipped -= 1;
+                    previous = player;
+                }
+                _ => return Ok(player),
+            }
+        }
+    }
+
+    /// Returns the players taking the specified number of upcoming turns without changing the
+    /// turn order.
+    pub(crate) fn upcoming(
+        &self,
+        active_player: PlayerId,
+        players: &[PlayerId],
+        amount: usize,
+    ) -> Result<Vec<PlayerId>, EngineError> {
+        let mut order = self.clone();
+        let mut previous = active_player;
+        (0..amount)
+            .map(|_| {
+                previous = order.advance(previous, players)?;
+                Ok(previous)
+            })
+            .collect()
+    }
+}
+
+/// 117.3a The active player receives priority at the beginning of most steps and phases, after
+///        any turn-based actions (such as drawing a card during the draw step; see rule 703) have
+///        been dealt with and abilities that trigger at the beginning of that phase or step have
+///        been put on the stack. No player receives priority during the untap step. Players
+///        usually don’t get priority during the cleanup step (see rule 514.3).
+///
+/// A step at which a player wants to be asked before passing priority, either during their own
+/// turns or during the turns of their opponents. Players pass priority automatically everywhere
+/// else.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct PriorityStop {
+    pub(crate) step: Step,
+    pub(crate) own_turn: bool,
+}
+
+impl PriorityStop {
+    /// Returns the stops players start with, which are both main phases of their own turns and the
+    /// declare blockers step of their opponents' turns.
+    pub(crate) fn defaults() -> IndexSet<Self> {
+        [
+            Self {
+                step: Step::PrecombatMain,
+                own_turn: true,
+            },
+            Self {
+                step: Step::PostcombatMain,
+                own_turn: true,
+            },
+            Self {
+                step: Step::DeclareBlockers,
+                own_turn: false,
+            },
+        ]
+        .into()
+    }
+}
+
+/// The reason [`Game::run_until_stop`](crate::game::Game::run_until_stop) stopped advancing the
+/// game.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Stop {
+    /// The player has a priority stop at the current step.
+    Priority(PlayerId),
+    /// A pending triggered ability requires choices from its controller.
+    PendingChoice(PlayerId),
+    /// The game has ended.
+    GameOver,
+}
+
+/// 500.1. [...] Some phases are subdivided into steps.
+///
+/// # Remarks
+/// The precombat and postcombat main phases have no steps and are represented as steps here for
+/// simplicity.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Hash)]
+pub enum Step {
+    /// 502. Untap Step
+    Untap,
+    /// 503. Upkeep Step
+    Upkeep,
+    /// 504. Draw Step
+    Draw,
+    /// 505. Main Phase
+    PrecombatMain,
+    /// 507. Beginning of Combat Step
+    BeginningOfCombat,
+    /// 508. Declare Attackers Step
+    DeclareAttackers,
+    /// 509. Declare Blockers Step
+    DeclareBlockers,
+    /// 510. Combat Damage Step
+    CombatDamage,
+    /// 511. End of Combat Step
+    EndOfCombat,
+    /// 505. Main Phase
+    PostcombatMain,
+    /// 513. End Step
+    End,
+    /// 514. Cleanup Step
+    Cleanup,
+}
+
+impl Step {
+    /// All steps of a regular turn in the order they happen.
+    pub(crate) const ALL: [Self; 12] = [
+        Self::Untap,
+        Self::Upkeep,
+        Self::Draw,
+        Self::PrecombatMain,
+        Self::BeginningOfCombat,
+        Self::DeclareAttackers,
+        Self::DeclareBlockers,
+        Self::CombatDamage,
+        Self::EndOfCombat,
+        Self::PostcombatMain,
+        Self::End,
+        Self::Cleanup,
+    ];
+
+    /// Returns the phase the step is part of.
+    pub(crate) fn phase(self) -> Phase {
+        match self {
+            Self::Untap | Self::Upkeep | Self::Draw => Phase::Beginning,
+            Self::PrecombatMain => Phase::PrecombatMain,
+            Self::BeginningOfCombat
+            | Self::DeclareAttackers
+            | Self::DeclareBlockers
+            | Self::CombatDamage
+            | Self::EndOfCombat => Phase::Combat,
+            Self::PostcombatMain => Phase::PostcombatMain,
+            Self::End | Self::Cleanup => Phase::Ending,
+        }
+    }
+}
+
+/// A spell that has been cast during a turn along with the characteristics it had when it was
+/// cast.
+pub(crate) struct SpellCast {
+    /// The id of the spell on the stack, unless the spell was recorded without being cast.
+    pub(crate) spell: Option<ObjectId>,
+    pub(crate) caster: PlayerId,
+    pub(crate) card_type: IndexSet<CardType>,
+    pub(crate) color: ColorIdentity,
+}
+
+/// Records the facts about a single turn that abilities refer to as happening “this turn”.
+#[derive(Default)]
+pub(crate) struct TurnTracker {
+    spells_cast: Vec<SpellCast>,
+    lands_played: Vec<PlayerId>,
+    /// The controllers of all creatures that died this turn.
+    creatures_died: Vec<PlayerId>,
+    /// All creatures that have been declared as attackers this turn.
+    attackers: IndexSet<Entity>,
+    damage_dealt: IndexMap<PlayerId, u64>,
+    life_gained: IndexMap<PlayerId, u64>,
+    life_lost: IndexMap<PlayerId, u64>,
+}
+
+impl TurnTracker {
+    /// Returns all spells cast this turn in the order they have been cast.
+    pub(crate) fn spells_cast(&self) -> &[SpellCast] {
+        &self.spells_cast
+    }
+
+    /// Returns the number of spells the specified player cast this turn.
+    pub(crate) fn spells_cast_by(&self, player: PlayerId) -> usize {
+        self.spells_cast
+            .iter()
+            .filter(|it| it.caster == player)
+            .count()
+    }
+
+    /// Returns the number of spells cast this turn before the spell with the specified id, or `None`
+    /// if it hasn't been cast this turn.
+    pub(crate) fn spells_cast_before(&self, spell: ObjectId) -> Option<usize> {
+        self.spells_cast
+            .iter()
+            .position(|it| it.spell == Some(spell))
+    }
+
+    /// Returns the number of lands the specified player played this turn.
+    pub(crate) fn lands_played_by(&self, player: PlayerId) -> usize {
+        self.lands_played.iter().filter(|&&it| it == player).count()
+    }
+
+    /// Returns the number of creatures that died this turn.
+    pub(crate) fn creatures_died(&self) -> usize {
+        self.creatures_died.len()
+    }
+
+    /// Returns the number of creatures that died this turn while controlled by the specified
+    /// player.
+    pub(crate) fn creatures_died_under_control_of(&self, player: PlayerId) -> usize {
+        self.creatures_died
+            .iter()
+            .filter(|&&it| it == player)
+            .count()
+    }
+
+    /// Returns all creatures that have been declared as attackers this turn.
+    pub(crate) fn attackers(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.attackers.iter().copied()
+    }
+
+    /// Returns the total amount of damage dealt to the specified player this turn.
+    pub(crate) fn damage_dealt_to(&self, player: PlayerId) -> u64 {
+        self.damage_dealt.get(&player).copied().unwrap_or_default()
+    }
+
+    /// Returns the total amount of life the specified player gained this turn.
+    pub(crate) fn life_gained_by(&self, player: PlayerId) -> u64 {
+        self.life_gained.get(&player).copied().unwrap_or_default()
+    }
+
+    /// Returns the total amount of life the specified player lost this turn.
+    pub(crate) fn life_lost_by(&self, player: PlayerId) -> u64 {
+        self.life_lost.get(&player).copied().unwrap_or_default()
+    }
+
+    pub(crate) fn record_spell_cast(&mut self, spell: SpellCast) {
+        self.spells_cast.push(spell);
+    }
+
+    pub(crate) fn record_land_played(&mut self, player: PlayerId) {
+        self.lands_played.push(player);
+    }
+
+    pub(crate) fn record_creature_died(&mut self, controller: PlayerId) {
+        self.creatures_died.push(controller);
+    }
+
+    pub(crate) fn record_attacker(&mut self, attacker: Entity) {
+        self.attackers.insert(attacker);
+    }
+
+    pub(crate) fn record_damage_dealt(&mut self, player: PlayerId, amount: u64) {
+        *self.damage_dealt.entry(player).or_default() += amount;
+    }
+
+    pub(crate) fn record_life_gained(&mut self, player: PlayerId, amount: u64) {
+        *self.life_gained.entry(player).or_default() += amount;
+    }
+
+    pub(crate) fn record_life_lost(&mut self, player: PlayerId, amount: u64) {
+        *self.life_lost.entry(player).or_default() += amount;
+    }
+}
@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+
+use hecs::{Entity, World};
+use indexmap::IndexMap;
+use rand::Rng;
+
+use crate::{
+    core::{PlayerId, Zone},
+    error::EngineError,
+    library::Library,
+};
+
+/// 400.1. A zone is a place where objects can be during a game. [...]
+///
+/// Keeps track of the zone every object is in and of the order of the cards in each library. All
+/// zone changes go through the manager, which keeps the [`Zone`] component of each object in sync
+/// as a cache for queries.
+pub(crate) struct ZoneManager {
+    zones: HashMap<Entity, Zone>,
+    /// The libraries in turn order, so that iterating them doesn't depend on hashing.
+    libraries: IndexMap<PlayerId, Library>,
+}
+
+impl ZoneManager {
+    /// Creates a manager without any objects and an empty library for each of the players.
+    pub(crate) fn new(players: impl IntoIterator<Item = PlayerId>) -> Self {
+        Self {
+            zones: HashMap::new(),
+            libraries: players
+                .into_iter()
+                .map(|it| (it, Library::default()))
+                .collect(),
+        }
+    }
+
+    /// Returns the zone the specified object is in, if it is known.
+    pub(crate) fn zone_of(&self, entity: Entity) -> Option<Zone> {
+        self.zones.get(&entity).copied()
+    }
+
+    /// Returns a reference to the library of the specified player.
+    pub(crate) fn library(&self, player: PlayerId) -> Result<&Library, EngineError> {
+        self.libraries
+            .get(&player)
+            .ok_or(EngineError::UnknownPlayer(player))
+    }
+
+    /// Puts an object into the specified zone and returns the zone it was in before, if any.
+    /// Objects put into a library are put on top of it.
+    pub(crate) fn place(
+        &mut self,
+        world: &mut World,
+        entity: Entity,
+        zone: Zone,
+    ) -> Result<Option<Zone>, EngineError> {
+        if !world.contains(entity) {
+            return Err(EngineError::UnknownObject(entity));
+        }
+        if let Zone::Library(player) = zone {
+            self.library(player)?;
+        }
+
+        let previous = self.zones.insert(entity, zone);
+        if let Some(Zone::Library(player)) = previous {
+            self.library_mut(player)?.remove(entity);
+        }
+        if let Zone::Library(player) = zone {
+            self.library_mut(player)?.put_on_top(entity);
+        }
+        world
+            .insert_one(entity, zone)
+            .map_err(|_| EngineError::UnknownObject(entity))?;
+        Ok(previous)
+    }
+
+    /// Stops tracking an object that ceases to exist and returns the zone it was in, if any.
+    pub(crate) fn remove(&mut self, world: &mut World, entity: Entity) -> Option<Zone> {
+        let previous = self.zones.remove(&entity);
+        if let Some(Zone::Library(player)) = previous {
+            if let Some(library) = self.libraries.get_mut(&player) {
+                library.remove(entity);
+            }
+        }
+        let _ = world.remove_one::<Zone>(entity);
+        previous
+    }
+
+    /// Lets a player look at the top card of the library of the specified player and returns it, if
+    /// there is any.
+    pub(crate) fn look_at_top_card(
+        &mut self,
+        library: PlayerId,
+        player: PlayerId,
+    ) -> Result<Option<Entity>, EngineError> {
+        Ok(self.library_mut(library)?.look_at_top(player))
+    }
+
+    /// Shuffles the library of the specified player using the random number generator.
+    pub(crate) fn shuffle_library(
+        &mut self,
+        player: PlayerId,
+        random: &mut impl Rng,
+    ) -> Result<(), EngineError> {
+        self.library_mut(player)?.shuffle(random);
+        Ok(())
+    }
+
+    /// Puts the specified cards from the library of a player back on top and on the bottom of it,
+    /// each ordered from top to bottom. All cards must be in that library already.
+    pub(crate) fn rearrange_library(
+        &mut self,
+        player: PlayerId,
+        top: &[Entity],
+        bottom: &[Entity],
+    ) -> Result<(), EngineError> {
+        let library = self.library_mut(player)?;
+        if let Some(&card) = top.iter().chain(bottom).find(|&&it| !library.contains(it)) {
+            return Err(EngineError::IllegalZoneOperation(format!(
+                "{card:?} is not in the library of player with id {}.",
+                player.0
+            )));
+        }
+        for &card in top.iter().chain(bottom) {
+            library.remove(card);
+        }
+        for &card in top.iter().rev() {
+            library.put_on_top(card);
+        }
+        for &card in bottom {
+            library.put_on_bottom(card);
+        }
+        Ok(())
+    }
+
+    /// Returns the objects whose cached [`Zone`] component disagrees with the zone tracked by the
+    /// manager, along with the tracked zone and the cached one.
+    pub(crate) fn out_of_sync(&self, world: &World) -> Vec<(Entity, Option<Zone>, Option<Zone>)> {
+        let mut entities = world
+            .query::<&Zone>()
+            .iter()
+            .map(|(entity, _)| entity)
+            .chain(self.zones.keys().copied())
+            .collect::<Vec<_>>();
+        entities.sort();
+        entities.dedup();
+        entities
+            .into_iter()
+            .map(|it| {
+                (
+                    it,
+                    self.zone_of(it),
+                    world.get::<Zone>(it).ok().map(|it| *it),
+                )
+            })
+            .filter(|(_, tracked, cached)| tracked != cached)
+            .collect()
+    }
+
+    /// Asserts that the zones tracked by the manager, the order of the libraries and the cached
+    /// [`Zone`] components all agree with each other.
+    pub(crate) fn debug_assert_consistent(&self, world: &World) {
+        for (entity, zone) in &mut world.query::<&Zone>() {
+            debug_assert_eq!(
+                self.zone_of(entity),
+                Some(*zone),
+                "The zone component of {entity:?} is out of sync."
+            );
+        }
+        for (&entity, &zone) in &self.zones {
+            debug_assert_eq!(
+                world.get::<Zone>(entity).ok().as_deref(),
+                Some(&zone),
+                "{entity:?} is tracked in {zone:?} but its component disagrees."
+            );
+        }
+        for (&player, library) in &self.libraries {
+            for card in library.cards() {
+                debug_assert_eq!(
+                    self.zone_of(card),
+                    Some(Zone::Library(player)),
+                    "{card:?} is in a library it isn't tracked in."
+                );
+            }
+        }
+        let tracked_in_libraries = self
+            .zones
+            .values()
+            .filter(|it| matches!(it, Zone::Library(_)))
+            .count();
+        debug_assert_eq!(
+            tracked_in_libraries,
+            self.libraries.values().map(Library::len).sum::<usize>(),
+            "Some cards tracked in a library are missing from it."
+        );
+    }
+
+    /// Returns a mutable reference to the library of the specified player.
+    fn library_mut(&mut self, player: PlayerId) -> Result<&mut Library, EngineError> {
+        self.libraries
+            .get_mut(&player)
+            .ok_or(EngineError::UnknownPlayer(player))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn zone_components_follow_the_tracked_zone() {
+        let mut world = World::new();
+        let player = PlayerId(0);
+        let mut zones = ZoneManager::new([player]);
+        let card = world.spawn(());
+
+        assert_eq!(
+            zones.place(&mut world, card, Zone::Library(player)),
+            Ok(None)
+        );
+        assert_eq!(
+            *world.get::<Zone>(card).expect("Could not find the zone."),
+            Zone::Library(player)
+        );
+        assert_eq!(
+            zones.place(&mut world, card, Zone::Hand(player)),
+            Ok(Some(Zone::Library(player)))
+        );
+        assert!(zones
+            .library(player)
+            .expect("Could not access the library.")
+            .is_empty());
+        zones.debug_assert_consistent(&world);
+
+        assert_eq!(zones.remove(&mut world, card), Some(Zone::Hand(player)));
+        assert!(world.get::<Zone>(card).is_err());
+        zones.debug_assert_consistent(&world);
+    }
+
+    #[test]
+    fn unknown_objects_and_libraries_are_rejected() {
+        let mut world = World::new();
+        let mut zones = ZoneManager::new([PlayerId(0)]);
+        let card = world.spawn(());
+        let despawned = world.spawn(());
+        world
+            .despawn(despawned)
+            .expect("Could not despawn the object.");
+
+        assert_eq!(
+            zones.place(&mut world, despawned, Zone::Exile),
+            Err(EngineError::UnknownObject(despawned))
+        );
+        assert_eq!(
+            zones.place(&mut world, card, Zone::Library(PlayerId(1))),
+            Err(EngineError::UnknownPlayer(PlayerId(1)))
+        );
+        assert_eq!(zones.zone_of(card), None);
+        assert!(matches!(
+            zones.rearrange_library(PlayerId(0), &[card], &[]),
+            Err(EngineError::IllegalZoneOperation(_))
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "out of sync")]
+    fn zone_components_changed_behind_the_back_of_the_manager_are_detected() {
+        let mut world = World::new();
+        let mut zones = ZoneManager::new([PlayerId(0)]);
+        let card = world.spawn(());
+        zones
+            .place(&mut world, card, Zone::Battlefield)
+            .expect("Could not place the object.");
+
+        world
+            .insert_one(card, Zone::Exile)
+            .expect("Could not insert the zone.");
+        zones.debug_assert_consistent(&world);
+    }
+}
@@ -0,0 +1,14 @@
+//! Serves games to clients connecting over a WebSocket. Clients create and join games and take
+//! actions as described by the [`ClientMessage`]s, and are sent the game as their players see it
+//! after every change as described by the [`ServerMessage`]s.
+
+#[cfg(feature = "websocket")]
+mod protocol;
+#[cfg(feature = "websocket")]
+mod server;
+
+#[cfg(feature = "websocket")]
+pub use crate::{
+    protocol::{ClientMessage, ServerMessage},
+    server::serve,
+};
@@ -1,3 +1,27 @@
-fn main() {
-    println!("Hello, world!");
+#[cfg(feature = "websocket")]
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    env_logger::init();
+
+    let address = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "127.0.0.1:8080".into());
+    let listener = tokio::net::TcpListener::bind(&address).await?;
+    log::info!("Listening on {address}.");
+    sorcery_server::serve(listener).await
+}
+
+#[cfg(not(feature = "websocket"))]
+fn main() -> Result<(), sorcery_engine::prelude::EngineError> {
+    use sorcery_engine::prelude::*;
+
+    env_logger::init();
+
+    let deck = || Deck::parse("20 Forest\n20 Llanowar Elves");
+    let (_game, players) = GameBuilder::new()
+        .player("Player 1", deck()?)
+        .player("Player 2", deck()?)
+        .build()?;
+    log::info!("Started a game for {} players.", players.len());
+    Ok(())
 }
@@ -0,0 +1,51 @@
+//! The messages exchanged with clients, each sent as a JSON text frame tagged by its `type`.
+
+use serde::{Deserialize, Serialize};
+use sorcery_engine::prelude::*;
+
+/// A message sent by a client to the server.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// Opens a game that other clients can join. It starts with the specified seed, if any, as soon
+    /// as two players have joined.
+    CreateGame {
+        #[serde(default)]
+        seed: Option<u64>,
+    },
+    /// Joins a game with a decklist of `<count> <card name>` lines. A client can only join a single
+    /// game.
+    JoinGame {
+        game: u64,
+        name: String,
+        decklist: String,
+    },
+    /// Takes an action in the game the client joined, which the game validates.
+    Act { action: GameAction },
+}
+
+/// A message sent by the server to a client.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    GameCreated {
+        game: u64,
+    },
+    /// The client joined the game at the specified seat, counting from 0.
+    Joined {
+        game: u64,
+        seat: usize,
+    },
+    /// The game changed, which is sent to every player once it has started. The player only sees
+    /// the game through their own view and the events as they are revealed to them. The actions
+    /// are the ones they can take, which are none unless it is their turn to act.
+    Update {
+        view: PlayerView,
+        events: Vec<GameEvent>,
+        actions: Vec<GameAction>,
+    },
+    /// The last message of the client was rejected.
+    Error {
+        message: String,
+    },
+}
@@ -0,0 +1,276 @@
+use std::{
+    collections::HashMap,
+    io,
+    sync::{Arc, Mutex},
+};
+
+use futures_util::{SinkExt, StreamExt};
+use sorcery_engine::prelude::*;
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::mpsc::{self, UnboundedSender},
+};
+use tokio_tungstenite::tungstenite::{self, Message};
+
+use crate::protocol::{ClientMessage, ServerMessage};
+
+/// The number of players a game starts with.
+const PLAYERS_PER_GAME: usize = 2;
+
+/// Accepts WebSocket connections on the listener and serves games to them until accepting a
+/// connection fails.
+///
+/// # Errors
+/// Fails if a connection can't be accepted.
+pub async fn serve(listener: TcpListener) -> io::Result<()> {
+    let lobby = Arc::new(Mutex::new(Lobby::default()));
+    loop {
+        let (stream, address) = listener.accept().await?;
+        let lobby = Arc::clone(&lobby);
+        tokio::spawn(async move {
+            log::debug!("Accepted a connection from {address}.");
+            if let Err(error) = handle_connection(stream, &lobby).await {
+                log::warn!("The connection to {address} failed: {error}");
+            }
+        });
+    }
+}
+
+/// Handles the messages of a client until it disconnects, which concedes the game it joined.
+async fn handle_connection(
+    stream: TcpStream,
+    lobby: &Mutex<Lobby>,
+) -> Result<(), tungstenite::Error> {
+    let (mut sink, mut messages) = tokio_tungstenite::accept_async(stream).await?.split();
+    let (sender, mut receiver) = mpsc::unbounded_channel::<ServerMessage>();
+    let writer = tokio::spawn(async move {
+        while let Some(message) = receiver.recv().await {
+            match serde_json::to_string(&message) {
+                Ok(text) => sink.send(Message::text(text)).await?,
+                Err(error) => log::error!("Could not serialize {message:?}: {error}"),
+            }
+        }
+        Ok::<_, tungstenite::Error>(())
+    });
+
+    let mut seat = None;
+    let mut result = Ok(());
+    while let Some(message) = messages.next().await {
+        let text = match message {
+            Ok(Message::Text(text)) => text,
+            Ok(Message::Close(_)) => break,
+            Ok(_) => continue,
+            Err(error) => {
+                result = Err(error);
+                break;
+            }
+        };
+        let handled = match serde_json::from_str::<ClientMessage>(&text) {
+            Ok(message) => lock(lobby).handle(message, &sender, &mut seat),
+            Err(error) => Err(format!("Malformed message: {error}")),
+        };
+        if let Err(message) = handled {
+            let _ = sender.send(ServerMessage::Error { message });
+        }
+    }
+
+    if let Some(seat) = seat {
+        lock(lobby).leave(seat);
+    }
+    writer.abort();
+    result
+}
+
+/// Locks the lobby, which stays usable even if handling another message panicked.
+fn lock(lobby: &Mutex<Lobby>) -> std::sync::MutexGuard<'_, Lobby> {
+    lobby
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// The seat a client took, identified by the game and its index within the game.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct SeatRef {
+    game: u64,
+    index: usize,
+}
+
+/// All games that were created, including the ones that haven't started yet.
+#[derive(Default)]
+struct Lobby {
+    tables: HashMap<u64, Table>,
+    next_game: u64,
+}
+
+/// A game together with the clients playing it.
+struct Table {
+    seed: Option<u64>,
+    seats: Vec<Seat>,
+    /// The game, which starts once all seats are taken.
+    game: Option<Game>,
+}
+
+/// A player and the client they are connected with.
+struct Seat {
+    name: String,
+    decklist: String,
+    /// The player in the game, once it has started.
+    player: Option<PlayerId>,
+    sender: UnboundedSender<ServerMessage>,
+    /// The number of events the client has been sent.
+    event_index: usize,
+}
+
+impl Lobby {
+    /// Handles a message of the client in the specified seat, if it took one, and replies to it
+    /// through the sender. Returns the reason if the message is rejected.
+    fn handle(
+        &mut self,
+        message: ClientMessage,
+        sender: &UnboundedSender<ServerMessage>,
+        seat: &mut Option<SeatRef>,
+    ) -> Result<(), String> {
+        match message {
+            ClientMessage::CreateGame { seed } => {
+                let game = self.next_game;
+                self.next_game += 1;
+                self.tables.insert(
+                    game,
+                    Table {
+                        seed,
+                        seats: Vec::new(),
+                        game: None,
+                    },
+                );
+                log::info!("Created game {game}.");
+                let _ = sender.send(ServerMessage::GameCreated { game });
+            }
+            ClientMessage::JoinGame {
+                game,
+                name,
+                decklist,
+            } => {
+                if seat.is_some() {
+                    return Err("You already joined a game.".into());
+                }
+                let table = self.table(game)?;
+                if table.seats.len() == PLAYERS_PER_GAME {
+                    return Err(format!("The game {game} is already full."));
+                }
+                Deck::parse(&decklist).map_err(|it| it.to_string())?;
+                table.seats.push(Seat {
+                    name,
+                    decklist,
+                    player: None,
+                    sender: sender.clone(),
+                    event_index: 0,
+                });
+                let index = table.seats.len() - 1;
+                if table.seats.len() == PLAYERS_PER_GAME {
+                    if let Err(error) = table.start() {
+                        table.seats.pop();
+                        return Err(error.to_string());
+                    }
+                    log::info!("Started game {game}.");
+                }
+                *seat = Some(SeatRef { game, index });
+                let _ = sender.send(ServerMessage::Joined { game, seat: index });
+                table.update().map_err(|it| it.to_string())?;
+            }
+            ClientMessage::Act { action } => {
+                let seat = seat.ok_or("Join a game first.")?;
+                let table = self.table(seat.game)?;
+                let player = table.seats[seat.index]
+                    .player
+                    .ok_or("The game hasn't started yet.")?;
+                let game = table.game.as_mut().ok_or("The game hasn't started yet.")?;
+                game.apply(player, action).map_err(|it| it.to_string())?;
+                table.update().map_err(|it| it.to_string())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 104.3a A player can concede the game at any time. [...]
+    ///
+    /// Lets the client in the specified seat leave, conceding the game if it has already started.
+    fn leave(&mut self, seat: SeatRef) {
+        let Ok(table) = self.table(seat.game) else {
+            return;
+        };
+        let Some(player) = table.seats[seat.index].player else {
+            table.seats.remove(seat.index);
+            return;
+        };
+        let Some(game) = table.game.as_mut() else {
+            return;
+        };
+        if game.view_for(player).is_ok_and(|it| it.outcome.is_none()) {
+            log::info!("Player {player:?} left game {} and conceded.", seat.game);
+            if let Err(error) = game
+                .apply(player, GameAction::Concede)
+                .and_then(|()| table.update())
+            {
+                log::warn!("Could not concede the game {}: {error}", seat.game);
+            }
+        }
+    }
+
+    /// Returns the table of the specified game.
+    fn table(&mut self, game: u64) -> Result<&mut Table, String> {
+        self.tables
+            .get_mut(&game)
+            .ok_or_else(|| format!("There is no game {game}."))
+    }
+}
+
+impl Table {
+    /// Starts the game with the players in the order they took their seats.
+    fn start(&mut self) -> Result<(), EngineError> {
+        let mut builder = GameBuilder::new();
+        if let Some(seed) = self.seed {
+            builder = builder.seed(seed);
+        }
+        for seat in &self.seats {
+            builder = builder.player(seat.name.clone(), Deck::parse(&seat.decklist)?);
+        }
+        let (game, players) = builder.build()?;
+        for (seat, player) in self.seats.iter_mut().zip(players) {
+            seat.player = Some(player);
+        }
+        self.game = Some(game);
+        Ok(())
+    }
+
+    /// Advances the game to the next decision and sends every player their view of the game, the
+    /// events they haven't been sent yet and the actions they can take. Nothing is sent before the
+    /// game has started.
+    fn update(&mut self) -> Result<(), EngineError> {
+        let Some(game) = self.game.as_mut() else {
+            return Ok(());
+        };
+        let to_act = game.player_to_act()?;
+        for seat in &mut self.seats {
+            let Some(player) = seat.player else {
+                continue;
+            };
+            // Hidden information is only ever sent as the player sees it.
+            let view = game.view_for(player)?;
+            let events = game
+                .events_for(Some(player))
+                .split_off(seat.event_index.min(view.event_index));
+            let actions = if to_act == Some(player) {
+                game.legal_actions(player)?
+            } else {
+                Vec::new()
+            };
+            seat.event_index = view.event_index;
+            let _ = seat.sender.send(ServerMessage::Update {
+                view,
+                events,
+                actions,
+            });
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,195 @@
+#![cfg(feature = "websocket")]
+
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use pretty_assertions::assert_eq;
+use serde_json::{json, Value};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::{tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+const DECKLIST: &str = "24 Forest\n36 Llanowar Elves";
+
+/// A client talking to the server like any other client would, through JSON text frames.
+struct Client(WebSocketStream<MaybeTlsStream<TcpStream>>);
+
+impl Client {
+    async fn connect(address: &str) -> Self {
+        let (socket, _) = tokio_tungstenite::connect_async(format!("ws://{address}"))
+            .await
+            .expect("Could not connect to the server.");
+        Self(socket)
+    }
+
+    async fn send(&mut self, message: Value) {
+        self.0
+            .send(Message::text(message.to_string()))
+            .await
+            .expect("Could not send the message.");
+    }
+
+    /// Receives the next message, failing the test if the server doesn't send one in time.
+    async fn receive(&mut self) -> Value {
+        let message = tokio::time::timeout(Duration::from_secs(10), self.0.next())
+            .await
+            .expect("The server did not send a message in time.")
+            .expect("The connection was closed.")
+            .expect("Could not receive the message.");
+        serde_json::from_str(message.to_text().expect("Could not read the message."))
+            .expect("Could not parse the message.")
+    }
+
+    /// Receives the next message, which has to be of the specified type.
+    async fn expect(&mut self, kind: &str) -> Value {
+        let message = self.receive().await;
+        assert_eq!(message["type"], kind, "Unexpected message {message}");
+        message
+    }
+}
+
+/// Returns the action a scripted player takes: play a land, cast a spell, attack with everything
+/// or pass priority, in that order of preference.
+fn scripted_action(actions: &[Value]) -> Value {
+    let preference = |action: &Value| match action {
+        Value::Object(map) if map.contains_key("PlayLand") => 0,
+        Value::Object(map) if map.contains_key("CastSpell") => 1,
+        _ => 2,
+    };
+    actions
+        .iter()
+        .min_by_key(|it| preference(it))
+        .cloned()
+        .expect("There are no actions to take.")
+}
+
+/// Returns whether the player sees any object they aren't allowed to see.
+fn sees_hidden_objects(view: &Value) -> bool {
+    let player = &view["player"];
+    view["objects"]
+        .as_array()
+        .expect("The view has no objects.")
+        .iter()
+        .any(|it| {
+            let zone = &it["zone"];
+            zone.get("Library").is_some() || zone.get("Hand").is_some_and(|owner| owner != player)
+        })
+}
+
+/// Returns whether the player was told which cards another player drew.
+fn sees_cards_drawn_by_others(update: &Value) -> bool {
+    let player = &update["view"]["player"];
+    update["events"]
+        .as_array()
+        .expect("The events are missing.")
+        .iter()
+        .filter_map(|it| it.get("ZoneChanged"))
+        .any(|it| {
+            it["from"].get("Library").is_some()
+                && it["to"].get("Hand").is_some_and(|owner| owner != player)
+                && it["object"]["name"] != "a card"
+        })
+}
+
+#[tokio::test]
+async fn two_clients_play_a_few_turns_over_the_socket() {
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Could not bind the listener.");
+    let address = listener
+        .local_addr()
+        .expect("Could not get the address.")
+        .to_string();
+    tokio::spawn(sorcery_server::serve(listener));
+
+    let mut clients = [
+        Client::connect(&address).await,
+        Client::connect(&address).await,
+    ];
+    clients[0]
+        .send(json!({ "type": "create_game", "seed": 3 }))
+        .await;
+    let game = clients[0].expect("game_created").await["game"].clone();
+    for (seat, (client, name)) in clients.iter_mut().zip(["Alice", "Bob"]).enumerate() {
+        client
+            .send(json!({ "type": "join_game", "game": game, "name": name, "decklist": DECKLIST }))
+            .await;
+        assert_eq!(client.expect("joined").await["seat"], seat);
+    }
+
+    let mut updates = Vec::new();
+    for client in &mut clients {
+        updates.push(client.expect("update").await);
+    }
+    // Only the player to act may take actions, everyone else is rejected by the game.
+    let waiting = updates
+        .iter()
+        .position(|it| it["actions"].as_array().is_some_and(Vec::is_empty))
+        .expect("Both players are asked to act.");
+    clients[waiting]
+        .send(json!({ "type": "act", "action": "PassPriority" }))
+        .await;
+    let error = clients[waiting].expect("error").await;
+    assert!(error["message"]
+        .as_str()
+        .is_some_and(|it| it.contains("can't take the action")));
+
+    let mut turn = 1;
+    while turn <= 4 {
+        for update in &updates {
+            assert!(!sees_hidden_objects(&update["view"]));
+            assert!(!sees_cards_drawn_by_others(update));
+        }
+        let acting = updates
+            .iter()
+            .position(|it| !it["actions"].as_array().is_some_and(Vec::is_empty))
+            .expect("Nobody is asked to act.");
+        let actions = updates[acting]["actions"]
+            .as_array()
+            .expect("The actions are missing.");
+        let action = scripted_action(actions);
+        clients[acting]
+            .send(json!({ "type": "act", "action": action }))
+            .await;
+        updates.clear();
+        for client in &mut clients {
+            updates.push(client.expect("update").await);
+        }
+        turn = updates[0]["view"]["turn"]
+            .as_u64()
+            .expect("The turn is missing.");
+    }
+
+    // Both players see the same permanents, including lands and creatures of each of them.
+    let battlefields = updates
+        .iter()
+        .map(|update| {
+            update["view"]["objects"]
+                .as_array()
+                .expect("The view has no objects.")
+                .iter()
+                .filter(|it| it["zone"] == "Battlefield")
+                .map(|it| (it["name"].clone(), it["controller"].clone()))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(battlefields[0], battlefields[1]);
+    for update in &updates {
+        let player = &update["view"]["player"];
+        for name in ["Forest", "Llanowar Elves"] {
+            assert!(battlefields[0].contains(&(json!(name), player.clone())));
+        }
+    }
+
+    clients[0]
+        .send(json!({ "type": "act", "action": "Concede" }))
+        .await;
+    let bob = updates[1]["view"]["player"].clone();
+    for client in &mut clients {
+        let update = client.expect("update").await;
+        assert_eq!(update["view"]["outcome"], json!({ "Win": bob }));
+        let events = update["events"]
+            .as_array()
+            .expect("The events are missing.");
+        assert!(events.iter().any(|it| it.get("PlayerConceded").is_some()));
+    }
+}
@@ -0,0 +1,7 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    sorcery_engine::fuzzing::decklist(&String::from_utf8_lossy(data));
+});